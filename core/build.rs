@@ -0,0 +1,29 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+fn main() {
+    #[cfg(feature = "remote")]
+    {
+        println!("cargo:rerun-if-changed=proto/fsct_driver.proto");
+        // vendored, so building needs no protoc installation
+        let mut config = prost_build::Config::new();
+        config.protoc_executable(protoc_bin_vendored::protoc_bin_path().expect("vendored protoc"));
+        tonic_prost_build::configure()
+            .compile_with_config(config, &["proto/fsct_driver.proto"], &["proto"])
+            .expect("Failed to compile proto/fsct_driver.proto");
+    }
+}
@@ -48,6 +48,24 @@ async fn main() -> Result<()> {
                 DeviceEvent::Removed(device_id) => {
                     info!("Device removed with managed ID: {}", device_id);
                 }
+                DeviceEvent::DeviceError { device_id, error } => {
+                    info!("Device {} reported error: {:?}", device_id, error);
+                }
+                DeviceEvent::WarmupStep { device_id, step } => {
+                    info!("Device {} warm-up step {:?} done", device_id, step);
+                }
+                DeviceEvent::WatchInterrupted => {
+                    info!("USB device watch interrupted, re-establishing it");
+                }
+                DeviceEvent::DeviceCommand { device_id, command } => {
+                    info!("Device {} sent command {:?}", device_id, command);
+                }
+                DeviceEvent::VolumeChanged { device_id, volume } => {
+                    info!("Volume of device {} changed to {:?}", device_id, volume);
+                }
+                DeviceEvent::FirmwareUpdate { device_id, updating } => {
+                    info!("Firmware update of device {} {}", device_id, if updating { "started" } else { "ended" });
+                }
             }
         }
     });
@@ -84,7 +102,7 @@ async fn main() -> Result<()> {
         // Create a progress object
         let progress = TimelineInfo {
             position: Duration::from_secs(30),
-            duration: Duration::from_secs(180),
+            duration: Some(Duration::from_secs(180)),
             rate: 1.0,
             update_time: std::time::SystemTime::now(),
         };
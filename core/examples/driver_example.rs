@@ -27,7 +27,7 @@ async fn main() -> Result<()> {
         status: FsctStatus::Playing,
         timeline: Some(TimelineInfo {
             position: Duration::from_secs(5),
-            duration: Duration::from_secs(200),
+            duration: Some(Duration::from_secs(200)),
             rate: 1.0,
             update_time: std::time::SystemTime::now(),
         }),
@@ -35,7 +35,9 @@ async fn main() -> Result<()> {
             title: Option::from("Пісня Сміливих Дівчат".to_string()),
             artist: Option::from("KAZKA".to_string()),
             ..Default::default()
-        }
+        },
+        volume: None,
+        modes: Default::default(),
     };
 
     driver.update_player_state(player_id, state).await?;
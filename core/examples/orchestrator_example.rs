@@ -13,7 +13,7 @@ async fn main() -> Result<()> {
     env_logger::init();
 
     let player_manager = PlayerManager::new();
-    let player_events = player_manager.subscribe();
+    let player_events = player_manager.subscribe_queued();
 
     let device_manager = Arc::new(DeviceManager::new());
     let mut driver_service_handle = MultiServiceHandle::new();
@@ -33,7 +33,7 @@ async fn main() -> Result<()> {
          status: FsctStatus::Playing,
          timeline: Some(TimelineInfo{
              position: Duration::from_secs(13),
-             duration: Duration::from_secs(184),
+             duration: Some(Duration::from_secs(184)),
              rate: 1.0,
              update_time: std::time::SystemTime::now()
          }),
@@ -42,6 +42,8 @@ async fn main() -> Result<()> {
             title: Some("Demo title".to_string()),
             ..Default::default()
         },
+        volume: None,
+        modes: Default::default(),
     };
     // do some small changes if needed; for now defaults
     player_manager.update_player_state(player_id, state.clone()).await?;
@@ -53,7 +53,7 @@ async fn main() -> anyhow::Result<()> {
         fsct_device.set_progress(Some(TimelineInfo {
             update_time: std::time::SystemTime::now() - Duration::from_secs(60),
             position: Duration::from_secs(60),
-            duration: Duration::from_secs(186),
+            duration: Some(Duration::from_secs(186)),
             rate: 1.0,
         })).await?;
         println!(
@@ -72,7 +72,7 @@ async fn main() -> anyhow::Result<()> {
         fsct_device.set_progress(Some(TimelineInfo {
             update_time: std::time::SystemTime::now(),
             position: Duration::from_secs(120) + sleep,
-            duration: Duration::from_secs(186),
+            duration: Some(Duration::from_secs(186)),
             rate: 0.0,
         })).await?;
         fsct_device.set_status(FsctStatus::Paused).await?;
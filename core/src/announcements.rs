@@ -0,0 +1,312 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Spoken announcements of the routed playback, for visually impaired users of headless streamers.
+//!
+//! The [`Announcer`] follows what devices show (as a [`DisplayObserver`]) and speaks track changes, status changes
+//! and periodically the elapsed time, as enabled in [`AnnouncementConfig`]. Speech goes through a
+//! [`SpeechOutput`]; [`CommandSpeech`] uses the text-to-speech of the OS.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Error;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::definitions::FsctStatus;
+use crate::device_manager::ManagedDeviceId;
+use crate::orchestrator::DisplayObserver;
+use crate::player_state::PlayerState;
+use crate::serde_format::optional_duration_secs;
+use crate::service::{spawn_service, ServiceHandle};
+
+/// Which events are announced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnnouncementConfig {
+    /// Announce title and artist of a new track.
+    pub track_change: bool,
+    /// Announce playback starting, pausing and stopping.
+    pub status_change: bool,
+    /// Announce the elapsed time of the playing track at this interval.
+    #[serde(with = "optional_duration_secs")]
+    pub elapsed_interval: Option<Duration>,
+}
+
+impl AnnouncementConfig {
+    /// Announcing nothing.
+    pub fn silent() -> Self {
+        Self { track_change: false, status_change: false, elapsed_interval: None }
+    }
+}
+
+impl Default for AnnouncementConfig {
+    fn default() -> Self {
+        Self { track_change: true, status_change: false, elapsed_interval: None }
+    }
+}
+
+/// Speaks announcements.
+pub trait SpeechOutput: Send + Sync {
+    /// Starts speaking `text`, interrupting the announcement being spoken.
+    fn speak(&self, text: &str) -> Result<(), Error>;
+}
+
+/// Speech through a text-to-speech command of the OS: SAPI (through PowerShell) on Windows, `say` (the system
+/// speech synthesizer) on macOS and `espeak-ng` elsewhere.
+pub struct CommandSpeech {
+    program: String,
+    args: Vec<String>,
+    /// Whether the text is written to standard input instead of passed as the last argument.
+    text_on_stdin: bool,
+    speaking: Mutex<Option<Child>>,
+}
+
+impl CommandSpeech {
+    /// Speech through the given command, with the text passed as its last argument.
+    pub fn new(program: impl Into<String>, args: Vec<String>) -> Self {
+        Self { program: program.into(), args, text_on_stdin: false, speaking: Mutex::new(None) }
+    }
+
+    /// Text-to-speech of the OS the host runs on.
+    pub fn system() -> Self {
+        if cfg!(target_os = "windows") {
+            let script = "Add-Type -AssemblyName System.Speech; \
+                          (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak([Console]::In.ReadToEnd())";
+            Self {
+                text_on_stdin: true,
+                ..Self::new("powershell", ["-NoProfile", "-NonInteractive", "-Command", script].map(String::from).to_vec())
+            }
+        } else if cfg!(target_os = "macos") {
+            Self::new("say", Vec::new())
+        } else {
+            Self::new("espeak-ng", Vec::new())
+        }
+    }
+}
+
+impl SpeechOutput for CommandSpeech {
+    fn speak(&self, text: &str) -> Result<(), Error> {
+        let mut speaking = self.speaking.lock().unwrap();
+        if let Some(mut previous) = speaking.take() {
+            let _ = previous.kill();
+            let _ = previous.wait();
+        }
+        let mut command = Command::new(&self.program);
+        command.args(&self.args).stdout(Stdio::null()).stderr(Stdio::null());
+        if self.text_on_stdin {
+            command.stdin(Stdio::piped());
+        } else {
+            command.arg(text).stdin(Stdio::null());
+        }
+        let mut child = command.spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        *speaking = Some(child);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct SourceAnnouncements {
+    title: Option<String>,
+    status: Option<FsctStatus>,
+    /// Position of the playing track and when it was taken.
+    position: Option<(Duration, Instant, f64)>,
+    duration: Option<Duration>,
+    last_elapsed: Option<Instant>,
+}
+
+/// Announces what devices show; the same player shown on several devices is announced once.
+pub struct Announcer {
+    config: Mutex<AnnouncementConfig>,
+    speech: Arc<dyn SpeechOutput>,
+    sources: Mutex<HashMap<String, SourceAnnouncements>>,
+}
+
+impl Announcer {
+    pub fn new(config: AnnouncementConfig, speech: Arc<dyn SpeechOutput>) -> Self {
+        Self { config: Mutex::new(config), speech, sources: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn config(&self) -> AnnouncementConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    pub fn set_config(&self, config: AnnouncementConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// Announces the elapsed time of playing tracks whose interval has passed.
+    pub fn announce_elapsed(&self) {
+        let Some(interval) = self.config().elapsed_interval else { return };
+        let now = Instant::now();
+        let mut announcements = Vec::new();
+        for source in self.sources.lock().unwrap().values_mut() {
+            let Some((position, taken_at, rate)) = source.position else { continue };
+            if source.last_elapsed.is_some_and(|last| now - last < interval) {
+                continue;
+            }
+            source.last_elapsed = Some(now);
+            let elapsed = position + (now - taken_at).mul_f64(rate);
+            announcements.push(match source.duration {
+                Some(duration) => format!("{} of {}", spoken_duration(elapsed), spoken_duration(duration)),
+                None => format!("{} elapsed", spoken_duration(elapsed)),
+            });
+        }
+        for announcement in announcements {
+            self.speak(&announcement);
+        }
+    }
+
+    fn speak(&self, text: &str) {
+        if let Err(e) = self.speech.speak(text) {
+            warn!("Failed to announce \"{}\": {}", text, e);
+        }
+    }
+}
+
+fn spoken_duration(duration: Duration) -> String {
+    let secs = duration.as_secs_f64().round() as u64;
+    let unit = |count: u64, name: &str| format!("{} {}{}", count, name, if count == 1 { "" } else { "s" });
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => unit(s, "second"),
+        (0, m, 0) => unit(m, "minute"),
+        (0, m, s) => format!("{} {}", unit(m, "minute"), unit(s, "second")),
+        (h, m, _) => format!("{} {}", unit(h, "hour"), unit(m, "minute")),
+    }
+}
+
+fn spoken_status(status: FsctStatus) -> Option<&'static str> {
+    match status {
+        FsctStatus::Playing => Some("Playing"),
+        FsctStatus::Paused => Some("Paused"),
+        FsctStatus::Stopped => Some("Stopped"),
+        _ => None,
+    }
+}
+
+impl DisplayObserver for Announcer {
+    fn observe(&self, _device_id: ManagedDeviceId, source: Option<&str>, state: &PlayerState) {
+        let config = self.config();
+        let mut announcements = Vec::new();
+        {
+            let mut sources = self.sources.lock().unwrap();
+            let announced = sources.entry(source.unwrap_or_default().to_string()).or_default();
+            let title = state.texts.title.as_ref();
+            if let Some(title) = title && announced.title.as_ref() != Some(title) {
+                if config.track_change {
+                    announcements.push(match &state.texts.artist {
+                        Some(artist) => format!("Now playing: {} by {}", title, artist),
+                        None => format!("Now playing: {}", title),
+                    });
+                }
+                announced.last_elapsed = None;
+            }
+            if announced.status != Some(state.status) && config.status_change
+                && let Some(status) = spoken_status(state.status) {
+                announcements.push(status.to_string());
+            }
+            announced.title = title.cloned();
+            announced.status = Some(state.status);
+            announced.duration = state.timeline.as_ref().and_then(|timeline| timeline.duration);
+            announced.position = state.timeline.as_ref().filter(|_| state.status == FsctStatus::Playing).map(|timeline| {
+                let since_update = SystemTime::now().duration_since(timeline.update_time).unwrap_or_default();
+                (timeline.position + since_update.mul_f64(timeline.rate), Instant::now(), timeline.rate)
+            });
+            if announced.position.is_some() && announced.last_elapsed.is_none() {
+                // first announcement one interval after the track started playing
+                announced.last_elapsed = Some(Instant::now());
+            }
+        }
+        for announcement in announcements {
+            self.speak(&announcement);
+        }
+    }
+
+    fn device_idle(&self, _device_id: ManagedDeviceId) {}
+
+    fn device_detached(&self, _device_id: ManagedDeviceId) {}
+}
+
+/// How often [`run_announcer`] checks for due elapsed time announcements.
+pub const ELAPSED_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Announces the elapsed time of playing tracks, see [`AnnouncementConfig::elapsed_interval`].
+pub fn run_announcer(announcer: Arc<Announcer>) -> ServiceHandle {
+    spawn_service(move |mut stop_handle| async move {
+        loop {
+            tokio::select! {
+                _ = stop_handle.signaled() => break,
+                _ = tokio::time::sleep(ELAPSED_CHECK_INTERVAL) => announcer.announce_elapsed(),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::TimelineInfo;
+    use uuid::Uuid;
+
+    #[derive(Default)]
+    struct RecordingSpeech {
+        spoken: Mutex<Vec<String>>,
+    }
+
+    impl SpeechOutput for RecordingSpeech {
+        fn speak(&self, text: &str) -> Result<(), Error> {
+            self.spoken.lock().unwrap().push(text.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn track_changes_and_elapsed_time_are_announced_once_per_source() {
+        let speech = Arc::new(RecordingSpeech::default());
+        let config = AnnouncementConfig { elapsed_interval: Some(Duration::from_secs(60)), ..Default::default() };
+        let announcer = Announcer::new(config, speech.clone());
+        let mut state = PlayerState { status: FsctStatus::Playing, ..Default::default() };
+        state.texts.title = Some("Song".to_string());
+        state.texts.artist = Some("Band".to_string());
+        state.timeline = Some(TimelineInfo {
+            position: Duration::from_secs(30),
+            update_time: SystemTime::now(),
+            duration: Some(Duration::from_secs(245)),
+            rate: 1.0,
+        });
+
+        announcer.observe(Uuid::new_v4(), Some("spotify"), &state);
+        announcer.observe(Uuid::new_v4(), Some("spotify"), &state);
+        tokio::time::advance(Duration::from_secs(30)).await;
+        announcer.announce_elapsed();
+        tokio::time::advance(Duration::from_secs(30)).await;
+        announcer.announce_elapsed();
+
+        assert_eq!(*speech.spoken.lock().unwrap(), vec![
+            "Now playing: Song by Band".to_string(),
+            "1 minute 30 seconds of 4 minutes 5 seconds".to_string(),
+        ]);
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Portable file of player to device assignments, for replicating a configured setup onto another machine or
+//! restoring it after a reinstall (`fsctctl assignments export` / `import`).
+//!
+//! Devices are named by their managed id, which is derived from the USB vendor id, product id and serial number and
+//! so stays the same on every host. Players are named by self id patterns, where `*` stands for any run of
+//! characters, so a pattern can match the players of another machine or service instance, e.g.
+//! `native@*-linux-mpris-spotify`:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "assignments": [
+//!     { "device": "4f3c1d2e-8a1b-5c3d-9e4f-0a1b2c3d4e5f", "player": "native@*-linux-mpris-spotify" }
+//!   ]
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::device_manager::ManagedDeviceId;
+use crate::{ManagedPlayerId, PlayerInfo};
+
+/// Version of the file format written by [`AssignmentFile::export`].
+pub const ASSIGNMENT_FILE_VERSION: u32 = 1;
+
+/// Assignments of players to devices, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AssignmentFile {
+    pub version: u32,
+    pub assignments: Vec<AssignmentEntry>,
+}
+
+/// Players whose self id matches `player` are assigned to `device`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AssignmentEntry {
+    pub device: ManagedDeviceId,
+    pub player: String,
+}
+
+impl AssignmentFile {
+    /// Assignments of the players, with their self ids as patterns.
+    pub fn export(players: &[PlayerInfo]) -> Self {
+        let mut assignments: Vec<_> = players
+            .iter()
+            .filter_map(|player| {
+                Some(AssignmentEntry { device: player.assigned_device?, player: player.self_id.clone() })
+            })
+            .collect();
+        assignments.sort_by(|a, b| (a.device, &a.player).cmp(&(b.device, &b.player)));
+        assignments.dedup();
+        Self { version: ASSIGNMENT_FILE_VERSION, assignments }
+    }
+
+    /// Refuses files written in a format version newer than the supported one.
+    pub fn check_version(&self) -> Result<(), anyhow::Error> {
+        if self.version > ASSIGNMENT_FILE_VERSION {
+            anyhow::bail!("Assignment file version {} is newer than the supported {}", self.version,
+                          ASSIGNMENT_FILE_VERSION);
+        }
+        Ok(())
+    }
+
+    /// Assignments of the registered players matching the entries, and the entries no player matches.
+    pub fn resolve<'a>(&'a self, players: &[PlayerInfo])
+                       -> (Vec<(ManagedPlayerId, ManagedDeviceId)>, Vec<&'a AssignmentEntry>) {
+        let mut assignments = Vec::new();
+        let mut unmatched = Vec::new();
+        for entry in &self.assignments {
+            let before = assignments.len();
+            assignments.extend(players.iter()
+                .filter(|player| matches_self_id(&entry.player, &player.self_id))
+                .map(|player| (player.player_id, entry.device)));
+            if assignments.len() == before {
+                unmatched.push(entry);
+            }
+        }
+        (assignments, unmatched)
+    }
+}
+
+/// Whether the self id matches the pattern, `*` matching any run of characters.
+pub fn matches_self_id(pattern: &str, self_id: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = self_id.strip_prefix(first) else { return false };
+    let parts: Vec<_> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else { return rest.is_empty() };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlayerState;
+    use uuid::Uuid;
+
+    fn player(id: u32, self_id: &str, assigned_device: Option<ManagedDeviceId>) -> PlayerInfo {
+        PlayerInfo {
+            player_id: ManagedPlayerId::new(id).unwrap(),
+            self_id: self_id.to_string(),
+            assigned_device,
+            state: PlayerState::default(),
+            origin: Default::default(),
+        }
+    }
+
+    #[test]
+    fn self_id_patterns() {
+        assert!(matches_self_id("spotify", "spotify"));
+        assert!(!matches_self_id("spotify", "spotify2"));
+        assert!(matches_self_id("native@*-linux-mpris-spotify", "native@studio-pc#2-linux-mpris-spotify"));
+        assert!(!matches_self_id("native@*-linux-mpris-spotify", "native-linux-mpris-vlc"));
+        assert!(matches_self_id("*spotify*", "native-windows-gsmtc-Spotify.exe-spotify"));
+        assert!(!matches_self_id("a*a", "a"));
+        assert!(matches_self_id("*", ""));
+    }
+
+    #[test]
+    fn exported_assignments_resolve_on_another_host() {
+        let device = Uuid::new_v4();
+        let players = [player(1, "native@studio-pc-linux-mpris-spotify", Some(device)), player(2, "vlc", None)];
+        let mut file = AssignmentFile::export(&players);
+        assert_eq!(file.assignments, [AssignmentEntry { device, player: players[0].self_id.clone() }]);
+
+        file.assignments[0].player = "native@*-linux-mpris-spotify".to_string();
+        file.assignments.push(AssignmentEntry { device, player: "foobar2000".to_string() });
+        let file: AssignmentFile = serde_json::from_str(&serde_json::to_string(&file).unwrap()).unwrap();
+        file.check_version().unwrap();
+        let other_host = [player(7, "native@living-room-linux-mpris-spotify", None), player(8, "vlc", None)];
+        let (assignments, unmatched) = file.resolve(&other_host);
+        assert_eq!(assignments, [(other_host[0].player_id, device)]);
+        assert_eq!(unmatched, [&file.assignments[1]]);
+
+        let newer: AssignmentFile = serde_json::from_str(r#"{ "version": 2, "assignments": [] }"#).unwrap();
+        assert!(newer.check_version().is_err());
+    }
+}
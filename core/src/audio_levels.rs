@@ -0,0 +1,225 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Coarse audio levels of what the host plays, for devices with VU meter displays.
+//!
+//! An [`AudioCapture`] backend of the platform (e.g. the PipeWire monitor capture of the Linux port, or
+//! [`NullCapture`] where there is none yet) publishes the latest [`AudioBlock`] into a `watch` channel;
+//! [`run_audio_level_stream`] computes [`AudioLevels`] of it and sends them to the devices taking them, at a limited
+//! rate and only when they changed; devices suspended by do-not-disturb are skipped. [`run_audio_analysis`] runs a
+//! backend together with the analyses.
+//!
+//! Devices take levels on an interrupt or bulk OUT endpoint of their FSCT interface, as a 2-byte report: left and
+//! right channel, each from 0 ([`LEVEL_FLOOR_DB`] or below) to 255 (0 dBFS).
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{debug, info};
+use tokio::sync::watch;
+use tokio::time::MissedTickBehavior;
+
+use crate::device_manager::{DeviceManagement, DeviceManager};
+use crate::service::{spawn_service, MultiServiceHandle, ServiceHandle};
+#[cfg(feature = "spectrum")]
+use crate::spectrum::{run_spectrum_stream, SpectrumConfig};
+
+/// Level sent as 0; quieter signals are shown as silence.
+pub const LEVEL_FLOOR_DB: f32 = -60.0;
+
+/// Default number of level reports per second.
+pub const DEFAULT_AUDIO_LEVEL_RATE: u32 = 15;
+
+/// Most level reports per second, so the reports don't take the USB bandwidth of audio.
+pub const MAX_AUDIO_LEVEL_RATE: u32 = 30;
+
+/// Interleaved samples in -1.0..=1.0 captured during the latest capture period.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioBlock {
+    pub samples: Arc<[f32]>,
+    pub channels: usize,
+    pub sample_rate: u32,
+}
+
+/// RMS levels of the left and right channel, scaled from [`LEVEL_FLOOR_DB`] (0) to 0 dBFS (255).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AudioLevels {
+    pub left: u8,
+    pub right: u8,
+}
+
+fn level(sum_of_squares: f32, count: usize) -> u8 {
+    if count == 0 {
+        return 0;
+    }
+    power_level(sum_of_squares / count as f32)
+}
+
+/// Scales a signal power (1.0 being full scale) from [`LEVEL_FLOOR_DB`] (0) to 0 dBFS (255).
+pub(crate) fn power_level(power: f32) -> u8 {
+    let db = 10.0 * power.log10();
+    ((db - LEVEL_FLOOR_DB) / -LEVEL_FLOOR_DB * 255.0).clamp(0.0, 255.0).round() as u8
+}
+
+impl AudioLevels {
+    /// Levels of interleaved samples in -1.0..=1.0; the first channel is left, the second right (mono shows on
+    /// both), further channels are ignored.
+    pub fn from_samples(samples: &[f32], channels: usize) -> Self {
+        let channels = channels.max(1);
+        let (mut left, mut right) = (0.0, 0.0);
+        for frame in samples.chunks_exact(channels) {
+            left += frame[0] * frame[0];
+            right += frame[channels.min(2) - 1] * frame[channels.min(2) - 1];
+        }
+        let frames = samples.len() / channels;
+        Self { left: level(left, frames), right: level(right, frames) }
+    }
+
+    /// Report sent to devices.
+    pub fn to_report(self) -> [u8; 2] {
+        [self.left, self.right]
+    }
+}
+
+/// Platform backend capturing what the host plays.
+pub trait AudioCapture: Send + Sync {
+    /// Name of the backend, for logs.
+    fn name(&self) -> &'static str;
+
+    /// Publishes blocks of what plays into `blocks` until the service is stopped or the capture fails.
+    fn run(&self, blocks: watch::Sender<AudioBlock>) -> ServiceHandle;
+}
+
+/// Backend capturing nothing, for platforms without a capture yet or devices analyzing audio themselves.
+pub struct NullCapture;
+
+impl AudioCapture for NullCapture {
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    fn run(&self, blocks: watch::Sender<AudioBlock>) -> ServiceHandle {
+        // keeps the channel open, so the analyses wait instead of seeing the capture end
+        spawn_service(move |mut stop| async move {
+            stop.signaled().await;
+            drop(blocks);
+        })
+    }
+}
+
+/// Rates of the analyses run by [`run_audio_analysis`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioAnalysisConfig {
+    /// Level reports per second, capped at [`MAX_AUDIO_LEVEL_RATE`].
+    pub level_rate: u32,
+    /// Spectrum analysis, `None` to skip it.
+    #[cfg(feature = "spectrum")]
+    pub spectrum: Option<SpectrumConfig>,
+}
+
+impl Default for AudioAnalysisConfig {
+    fn default() -> Self {
+        Self {
+            level_rate: DEFAULT_AUDIO_LEVEL_RATE,
+            #[cfg(feature = "spectrum")]
+            spectrum: Some(SpectrumConfig::default()),
+        }
+    }
+}
+
+/// Runs `capture` and the analyses of what it captures for the devices of `device_manager`, until the service is
+/// stopped.
+pub fn run_audio_analysis(device_manager: Arc<DeviceManager>, capture: &dyn AudioCapture,
+                          config: AudioAnalysisConfig) -> ServiceHandle {
+    info!("Capturing audio for level displays with the {} backend", capture.name());
+    let (blocks_tx, blocks_rx) = watch::channel(AudioBlock::default());
+    let mut handles = MultiServiceHandle::new();
+    handles.add(capture.run(blocks_tx));
+    #[cfg(feature = "spectrum")]
+    if let Some(spectrum) = config.spectrum {
+        handles.add(run_spectrum_stream(device_manager.clone(), blocks_rx.clone(), spectrum));
+    }
+    handles.add(run_audio_level_stream(device_manager, blocks_rx, config.level_rate));
+    spawn_service(move |mut stop| async move {
+        stop.signaled().await;
+        if let Err(e) = handles.shutdown().await {
+            debug!("Failed to stop audio analysis: {}", e);
+        }
+    })
+}
+
+/// Sends the levels of the blocks published into `blocks` to the devices taking them, at most `rate` (capped at
+/// [`MAX_AUDIO_LEVEL_RATE`]) times per second, until the service is stopped or the capture ends.
+pub fn run_audio_level_stream(device_manager: Arc<DeviceManager>, mut blocks: watch::Receiver<AudioBlock>,
+                              rate: u32) -> ServiceHandle {
+    spawn_service(move |mut stop| async move {
+        let mut last_sent = None;
+        let mut interval = tokio::time::interval(Duration::from_secs(1) / rate.clamp(1, MAX_AUDIO_LEVEL_RATE));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = stop.signaled() => break,
+                _ = interval.tick() => {}
+            }
+            match blocks.has_changed() {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(_) => {
+                    debug!("Audio level capture ended");
+                    break;
+                }
+            }
+            let current = {
+                let block = blocks.borrow_and_update();
+                AudioLevels::from_samples(&block.samples, block.channels)
+            };
+            if last_sent == Some(current) {
+                continue;
+            }
+            last_sent = Some(current);
+            for device_id in device_manager.get_all_managed_ids() {
+                if device_manager.is_suspended(device_id) {
+                    continue;
+                }
+                if let Err(e) = device_manager.send_audio_levels(device_id, current).await {
+                    debug!("Failed to send audio levels to device {}: {}", device_id, e);
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_are_scaled_between_floor_and_full_scale() {
+        assert_eq!(AudioLevels::from_samples(&[], 2), AudioLevels::default());
+        assert_eq!(AudioLevels::from_samples(&[0.0; 8], 2), AudioLevels::default());
+
+        // full scale on the left, -30 dBFS on the right
+        let quiet = 10f32.powf(-30.0 / 20.0);
+        let levels = AudioLevels::from_samples(&[1.0, quiet, -1.0, -quiet], 2);
+        assert_eq!(levels.left, 255);
+        assert!((127..=128).contains(&levels.right));
+
+        let mono = AudioLevels::from_samples(&[0.5, -0.5], 1);
+        assert_eq!(mono.left, mono.right);
+        assert_eq!(levels.to_report(), [255, levels.right]);
+    }
+}
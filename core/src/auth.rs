@@ -0,0 +1,258 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Authentication and authorization for network-exposed APIs (REST, WebSocket, gRPC).
+//!
+//! Transports extract [`Credentials`] from a request (bearer token, peer credentials of a local socket) and ask the
+//! [`AuthPolicy`] for a [`Principal`] with the scope the operation needs. Control actions performed by a principal
+//! are written to the `fsct::audit` log target with [`audit_control`].
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Log target of the audit trail.
+pub const AUDIT_LOG_TARGET: &str = "fsct::audit";
+
+/// What a caller is allowed to do. `Control` includes everything `Read` allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// Query players, devices and state, subscribe to events.
+    Read,
+    /// Additionally register players, push state and change routing.
+    Control,
+}
+
+/// A bearer token accepted by the API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiToken {
+    /// Name used in logs instead of the secret.
+    pub name: String,
+    pub token: String,
+    pub scope: Scope,
+}
+
+/// Credentials presented by a caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Credentials {
+    Anonymous,
+    Bearer(String),
+    /// Peer credentials of a local (unix socket / named pipe) connection.
+    LocalPeer { uid: u32 },
+}
+
+impl Credentials {
+    /// Parses an HTTP `Authorization` header value; anything but a bearer token is anonymous.
+    pub fn from_authorization_header(value: Option<&str>) -> Self {
+        value
+            .and_then(|v| v.trim().strip_prefix("Bearer "))
+            .map(|token| Credentials::Bearer(token.trim().to_string()))
+            .unwrap_or(Credentials::Anonymous)
+    }
+}
+
+/// Authenticated caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    pub name: String,
+    pub scope: Scope,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    #[error("Authentication required")]
+    Unauthenticated,
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("{principal} is not allowed to perform {required:?} operations")]
+    Forbidden { principal: String, required: Scope },
+}
+
+/// Which credentials are accepted and what they allow.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthPolicy {
+    /// Accepted bearer tokens.
+    #[serde(default)]
+    pub tokens: Vec<ApiToken>,
+    /// Local peers with these uids get `Control` scope.
+    #[serde(default)]
+    pub local_peer_uids: Vec<u32>,
+    /// Scope of callers without credentials; `None` rejects them.
+    #[serde(default)]
+    pub anonymous_scope: Option<Scope>,
+}
+
+impl AuthPolicy {
+    /// Policy letting everyone control the host, for APIs reachable only from trusted places.
+    pub fn open() -> Self {
+        Self { anonymous_scope: Some(Scope::Control), ..Default::default() }
+    }
+
+    /// Policy giving `Control` to callers presenting `token` and rejecting everyone else.
+    pub fn token(token: impl Into<String>) -> Self {
+        let token = ApiToken { name: "local".to_string(), token: token.into(), scope: Scope::Control };
+        Self { tokens: vec![token], ..Default::default() }
+    }
+
+    pub fn authenticate(&self, credentials: &Credentials) -> Result<Principal, AuthError> {
+        match credentials {
+            Credentials::Anonymous => self
+                .anonymous_scope
+                .map(|scope| Principal { name: "anonymous".to_string(), scope })
+                .ok_or(AuthError::Unauthenticated),
+            Credentials::Bearer(presented) => self
+                .tokens
+                .iter()
+                .find(|token| constant_time_eq(token.token.as_bytes(), presented.as_bytes()))
+                .map(|token| Principal { name: format!("token:{}", token.name), scope: token.scope })
+                .ok_or(AuthError::InvalidCredentials),
+            Credentials::LocalPeer { uid } => {
+                if self.local_peer_uids.contains(uid) {
+                    Ok(Principal { name: format!("uid:{}", uid), scope: Scope::Control })
+                } else {
+                    self.authenticate(&Credentials::Anonymous)
+                }
+            }
+        }
+    }
+
+    /// Authenticates the caller and checks it has at least the `required` scope.
+    pub fn authorize(&self, credentials: &Credentials, required: Scope) -> Result<Principal, AuthError> {
+        let principal = self.authenticate(credentials)?;
+        if principal.scope < required {
+            return Err(AuthError::Forbidden { principal: principal.name, required });
+        }
+        Ok(principal)
+    }
+}
+
+/// Records a control action in the audit log.
+pub fn audit_control(principal: &Principal, action: &str) {
+    info!(target: AUDIT_LOG_TARGET, "{} performed {}", principal.name, action);
+}
+
+/// Where a service keeps the token of its driver server when no policy is configured: `FSCT_TOKEN_FILE` if set,
+/// otherwise `fsct-host.token` next to the IPC socket on Unix and in `%ProgramData%\FSCT` on Windows.
+pub fn default_token_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("FSCT_TOKEN_FILE") {
+        return PathBuf::from(path);
+    }
+    let env_dir = |name: &str| std::env::var_os(name).filter(|dir| !dir.is_empty()).map(PathBuf::from);
+    if cfg!(windows) {
+        env_dir("PROGRAMDATA").unwrap_or_else(|| PathBuf::from("C:\\ProgramData")).join("FSCT").join("fsct-host.token")
+    } else {
+        env_dir("XDG_RUNTIME_DIR").unwrap_or_else(|| PathBuf::from("/tmp")).join("fsct-host.token")
+    }
+}
+
+/// A new random token.
+pub fn generate_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// Writes a new random token to `path`, readable only by its owner on Unix, and returns it. A file left over by a
+/// previous run is replaced; one owned by another user cannot be and fails the call.
+pub fn create_token_file(path: &Path) -> io::Result<String> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let token = generate_token();
+    options.open(path)?.write_all(token.as_bytes())?;
+    Ok(token)
+}
+
+/// Token written by [`create_token_file`].
+pub fn read_token_file(path: &Path) -> io::Result<String> {
+    Ok(std::fs::read_to_string(path)?.trim().to_string())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> AuthPolicy {
+        AuthPolicy {
+            tokens: vec![
+                ApiToken { name: "dashboard".into(), token: "read-secret".into(), scope: Scope::Read },
+                ApiToken { name: "remote".into(), token: "control-secret".into(), scope: Scope::Control },
+            ],
+            local_peer_uids: vec![501],
+            anonymous_scope: None,
+        }
+    }
+
+    #[test]
+    fn token_scopes_are_enforced() {
+        let policy = policy();
+        let read = Credentials::from_authorization_header(Some("Bearer read-secret"));
+        assert_eq!(policy.authorize(&read, Scope::Read).unwrap().name, "token:dashboard");
+        assert_eq!(
+            policy.authorize(&read, Scope::Control),
+            Err(AuthError::Forbidden { principal: "token:dashboard".into(), required: Scope::Control })
+        );
+        let control = Credentials::Bearer("control-secret".into());
+        assert_eq!(policy.authorize(&control, Scope::Control).unwrap().scope, Scope::Control);
+        assert_eq!(policy.authorize(&Credentials::Bearer("guess".into()), Scope::Read), Err(AuthError::InvalidCredentials));
+    }
+
+    #[test]
+    fn local_peers_and_anonymous_callers() {
+        let policy = policy();
+        assert_eq!(policy.authorize(&Credentials::LocalPeer { uid: 501 }, Scope::Control).unwrap().name, "uid:501");
+        assert_eq!(policy.authorize(&Credentials::LocalPeer { uid: 0 }, Scope::Read), Err(AuthError::Unauthenticated));
+        assert_eq!(policy.authorize(&Credentials::from_authorization_header(None), Scope::Read), Err(AuthError::Unauthenticated));
+        assert!(AuthPolicy::open().authorize(&Credentials::Anonymous, Scope::Control).is_ok());
+        assert_eq!(AuthPolicy::default().authorize(&Credentials::Anonymous, Scope::Read), Err(AuthError::Unauthenticated));
+    }
+
+    #[test]
+    fn token_files_are_replaced_on_creation() {
+        let path = std::env::temp_dir().join(format!("fsct-token-{}", generate_token()));
+        let first = create_token_file(&path).unwrap();
+        let second = create_token_file(&path).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(read_token_file(&path).unwrap(), second);
+        let policy = AuthPolicy::token(second.clone());
+        assert!(policy.authorize(&Credentials::Bearer(second), Scope::Control).is_ok());
+        assert!(policy.authorize(&Credentials::Bearer(first), Scope::Read).is_err());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+}
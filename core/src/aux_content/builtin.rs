@@ -0,0 +1,88 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use chrono::{Local, Timelike};
+
+use super::{AuxContent, AuxContentProvider};
+use crate::time_format::ClockTime;
+
+/// Current local time, e.g. `"14:05"`; by default in the time format of each device, see
+/// [`AuxContent::clock`].
+#[derive(Default)]
+pub struct ClockProvider {
+    format: Option<String>,
+}
+
+impl ClockProvider {
+    /// Clock written in the same `format` on all devices; `format` uses chrono's strftime syntax.
+    pub fn new(format: impl Into<String>) -> Self {
+        Self { format: Some(format.into()) }
+    }
+}
+
+#[async_trait]
+impl AuxContentProvider for ClockProvider {
+    fn name(&self) -> &str { "clock" }
+
+    fn refresh_interval(&self) -> Duration { Duration::from_secs(1) }
+
+    async fn fetch(&self) -> Result<AuxContent, Error> {
+        let now = Local::now();
+        Ok(match &self.format {
+            Some(format) => AuxContent::new(now.format(format).to_string()),
+            None => AuxContent::clock(ClockTime { hour: now.hour() as u8, minute: now.minute() as u8 }),
+        })
+    }
+}
+
+/// Current local date with weekday, e.g. `"Monday"` / `"16 June 2025"`.
+pub struct DateProvider;
+
+#[async_trait]
+impl AuxContentProvider for DateProvider {
+    fn name(&self) -> &str { "date" }
+
+    fn refresh_interval(&self) -> Duration { Duration::from_secs(60) }
+
+    async fn fetch(&self) -> Result<AuxContent, Error> {
+        let now = Local::now();
+        Ok(AuxContent::new(now.format("%A").to_string()).with_secondary(now.format("%-d %B %Y").to_string()))
+    }
+}
+
+/// CPU temperature read from the first thermal zone; only available on Linux.
+pub struct CpuTemperatureProvider;
+
+#[async_trait]
+impl AuxContentProvider for CpuTemperatureProvider {
+    fn name(&self) -> &str { "cpu_temperature" }
+
+    fn refresh_interval(&self) -> Duration { Duration::from_secs(10) }
+
+    async fn fetch(&self) -> Result<AuxContent, Error> {
+        if !cfg!(target_os = "linux") {
+            return Err(anyhow!("CPU temperature is not available on this platform"));
+        }
+        let raw = tokio::fs::read_to_string("/sys/class/thermal/thermal_zone0/temp").await?;
+        let millidegrees: i64 = raw.trim().parse()?;
+        Ok(AuxContent::new(format!("{:.1} °C", millidegrees as f64 / 1000.0)).with_secondary("CPU"))
+    }
+}
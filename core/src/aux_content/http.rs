@@ -0,0 +1,110 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+use std::time::Duration;
+
+use anyhow::Error;
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::{AuxContent, AuxContentProvider};
+
+/// Generic provider fetching a JSON document over HTTP, e.g. a weather API.
+///
+/// Lines are rendered from templates with `{/json/pointer}` placeholders (RFC 6901), for example
+/// `"{/current/temperature_2m} °C"` with an Open-Meteo forecast URL.
+pub struct HttpJsonProvider {
+    name: String,
+    url: String,
+    primary_template: String,
+    secondary_template: Option<String>,
+    refresh_interval: Duration,
+    client: reqwest::Client,
+}
+
+impl HttpJsonProvider {
+    pub fn new(name: impl Into<String>, url: impl Into<String>, primary_template: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            primary_template: primary_template.into(),
+            secondary_template: None,
+            refresh_interval: Duration::from_secs(15 * 60),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn with_secondary_template(mut self, template: impl Into<String>) -> Self {
+        self.secondary_template = Some(template.into());
+        self
+    }
+
+    pub fn with_refresh_interval(mut self, refresh_interval: Duration) -> Self {
+        self.refresh_interval = refresh_interval;
+        self
+    }
+}
+
+#[async_trait]
+impl AuxContentProvider for HttpJsonProvider {
+    fn name(&self) -> &str { &self.name }
+
+    fn refresh_interval(&self) -> Duration { self.refresh_interval }
+
+    async fn fetch(&self) -> Result<AuxContent, Error> {
+        let document: Value = self.client.get(&self.url).send().await?.error_for_status()?.json().await?;
+        Ok(AuxContent {
+            primary: render_json_template(&self.primary_template, &document),
+            secondary: self.secondary_template.as_ref().map(|t| render_json_template(t, &document)),
+            clock: None,
+        })
+    }
+}
+
+/// Replaces `{/json/pointer}` placeholders with the pointed values; missing values render as `?`.
+pub fn render_json_template(template: &str, document: &Value) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{/") {
+        let Some(len) = rest[start..].find('}') else { break };
+        out.push_str(&rest[..start]);
+        let pointer = &rest[start + 1..start + len];
+        match document.pointer(pointer) {
+            Some(Value::String(s)) => out.push_str(s),
+            Some(Value::Null) | None => out.push('?'),
+            Some(value) => out.push_str(&value.to_string()),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn template_placeholders_are_resolved_safely() {
+        let document = json!({ "current": { "temperature_2m": 21.5, "summary": "Sunny" } });
+        assert_eq!(
+            render_json_template("{/current/summary}, {/current/temperature_2m} °C, {/missing} {unclosed", &document),
+            "Sunny, 21.5 °C, ? {unclosed"
+        );
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Auxiliary (non-music) content shown on idle devices, e.g. clock, date, CPU temperature or weather.
+//!
+//! Providers are refreshed in the background by [`AuxContentRegistry::run`]; the orchestrator only reads the latest
+//! content, so a slow provider never delays routing. Which providers a device rotates through while idle is set per
+//! device with [`crate::OrchestratorControl::set_aux_rotation`].
+
+#[cfg(feature = "aux-content")]
+mod builtin;
+#[cfg(feature = "aux-http")]
+mod http;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Error;
+use async_trait::async_trait;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use tokio::select;
+use tokio::time::{sleep_until, timeout, Instant};
+
+use crate::definitions::FsctTextMetadata;
+use crate::player_state::PlayerState;
+use crate::service::{spawn_service, ServiceHandle};
+use crate::time_format::{ClockTime, TimeFormat};
+
+#[cfg(feature = "aux-content")]
+pub use builtin::{ClockProvider, CpuTemperatureProvider, DateProvider};
+#[cfg(feature = "aux-http")]
+pub use http::{render_json_template, HttpJsonProvider};
+
+/// Longest time a single provider refresh may take.
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Content to show, as the two lines most displays have.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuxContent {
+    pub primary: String,
+    pub secondary: Option<String>,
+    /// Time of day shown as the primary line, written in the time format of each device instead of `primary`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock: Option<ClockTime>,
+}
+
+impl AuxContent {
+    pub fn new(primary: impl Into<String>) -> Self {
+        Self { primary: primary.into(), secondary: None, clock: None }
+    }
+
+    /// Content showing the time of day; `primary` holds it in the default format.
+    pub fn clock(time: ClockTime) -> Self {
+        Self { primary: TimeFormat::default().format_time(time), secondary: None, clock: Some(time) }
+    }
+
+    pub fn with_secondary(mut self, secondary: impl Into<String>) -> Self {
+        self.secondary = Some(secondary.into());
+        self
+    }
+
+    /// State applied to the device: primary line as title, secondary line as artist; a clock is written in
+    /// `time_format`.
+    pub fn to_player_state(&self, time_format: &TimeFormat) -> PlayerState {
+        let mut state = PlayerState::default();
+        let primary = match self.clock {
+            Some(time) => time_format.format_time(time),
+            None => self.primary.clone(),
+        };
+        *state.texts.get_mut_text(FsctTextMetadata::CurrentTitle) = Some(primary);
+        *state.texts.get_mut_text(FsctTextMetadata::CurrentAuthor) = self.secondary.clone();
+        state
+    }
+}
+
+/// Source of auxiliary content.
+#[async_trait]
+pub trait AuxContentProvider: Send + Sync {
+    /// Unique name the provider is referred to by in rotations, e.g. `"clock"`.
+    fn name(&self) -> &str;
+
+    /// How often the content is refreshed.
+    fn refresh_interval(&self) -> Duration;
+
+    async fn fetch(&self) -> Result<AuxContent, Error>;
+}
+
+/// Which providers a device rotates through while it shows no playback.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuxRotation {
+    /// Provider names, shown in this order.
+    pub providers: Vec<String>,
+    /// How long each provider's content stays on the display.
+    #[serde(with = "crate::serde_format::duration_secs")]
+    pub interval: Duration,
+    /// How long the device has to be idle before the rotation starts.
+    #[serde(with = "crate::serde_format::duration_secs")]
+    pub start_after: Duration,
+    /// Format of clocks on the device, overriding the one of the orchestrator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_format: Option<TimeFormat>,
+}
+
+/// Set of providers with their latest content.
+#[derive(Default)]
+pub struct AuxContentRegistry {
+    providers: Vec<Arc<dyn AuxContentProvider>>,
+    latest: Mutex<HashMap<String, AuxContent>>,
+}
+
+impl AuxContentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_provider(mut self, provider: Arc<dyn AuxContentProvider>) -> Self {
+        self.providers.push(provider);
+        self
+    }
+
+    /// Registry of the built-in providers: `clock`, `date` and, on Linux, `cpu_temperature`.
+    #[cfg(feature = "aux-content")]
+    pub fn builtin() -> Self {
+        let registry = Self::new()
+            .with_provider(Arc::new(ClockProvider::default()))
+            .with_provider(Arc::new(DateProvider));
+        if cfg!(target_os = "linux") {
+            registry.with_provider(Arc::new(CpuTemperatureProvider))
+        } else {
+            registry
+        }
+    }
+
+    pub fn provider_names(&self) -> Vec<String> {
+        self.providers.iter().map(|p| p.name().to_string()).collect()
+    }
+
+    /// The most recently fetched content of the provider, if any fetch succeeded yet.
+    pub fn latest(&self, name: &str) -> Option<AuxContent> {
+        self.latest.lock().unwrap().get(name).cloned()
+    }
+
+    async fn refresh(&self, provider: &dyn AuxContentProvider) {
+        match timeout(FETCH_TIMEOUT, provider.fetch()).await {
+            Ok(Ok(content)) => {
+                self.latest.lock().unwrap().insert(provider.name().to_string(), content);
+            }
+            Ok(Err(e)) => warn!("Auxiliary content provider {} failed: {}", provider.name(), e),
+            Err(_) => warn!("Auxiliary content provider {} timed out", provider.name()),
+        }
+    }
+
+    /// Refreshes every provider on its own interval until shut down.
+    pub fn run(self: Arc<Self>) -> ServiceHandle {
+        spawn_service(move |mut stop_handle| async move {
+            let mut next_refresh = vec![Instant::now(); self.providers.len()];
+            loop {
+                let Some((index, deadline)) = next_refresh.iter().copied().enumerate().min_by_key(|(_, t)| *t) else {
+                    stop_handle.signaled().await;
+                    break;
+                };
+                select! {
+                    _ = stop_handle.signaled() => break,
+                    _ = sleep_until(deadline) => {
+                        let provider = self.providers[index].clone();
+                        self.refresh(provider.as_ref()).await;
+                        next_refresh[index] = Instant::now() + provider.refresh_interval();
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct Counter(AtomicU32);
+
+    #[async_trait]
+    impl AuxContentProvider for Counter {
+        fn name(&self) -> &str { "counter" }
+
+        fn refresh_interval(&self) -> Duration { Duration::from_secs(60) }
+
+        async fn fetch(&self) -> Result<AuxContent, Error> {
+            Ok(AuxContent::new(self.0.fetch_add(1, Ordering::SeqCst).to_string()))
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn registry_refreshes_providers_on_their_interval() {
+        let registry = Arc::new(AuxContentRegistry::new().with_provider(Arc::new(Counter(AtomicU32::new(0)))));
+        let handle = registry.clone().run();
+        crate::test_util::drain().await;
+        assert_eq!(registry.latest("counter"), Some(AuxContent::new("0")));
+
+        crate::test_util::advance(Duration::from_secs(60)).await;
+        assert_eq!(registry.latest("counter"), Some(AuxContent::new("1")));
+        assert_eq!(registry.latest("weather"), None);
+        let _ = handle.shutdown().await;
+    }
+}
@@ -0,0 +1,975 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Configuration file of host services.
+//!
+//! A TOML file; everything is optional:
+//!
+//! ```toml
+//! log_level = "debug"
+//! # self id of the player to prefer once it registers
+//! preferred_player = "spotify"
+//! # pause players assigned to a device when it disconnects, e.g. a headphone amplifier being unplugged
+//! pause_on_disconnect = true
+//! # record which players are shown on which devices for how long, see `fsctctl report`
+//! usage_stats = true
+//! # where services serve their driver over gRPC, e.g. for fsctctl
+//! driver_server = "127.0.0.1:50151"
+//! # advertise the driver server on the LAN over mDNS, by default when it is not on loopback
+//! zeroconf = true
+//! # where services serve JSON-RPC for GUIs and CLIs on the host, a named pipe on Windows
+//! ipc_socket = "/run/fsct-host.sock"
+//! # stream events over WebSocket to web dashboards, for pages served from the host and of these origins
+//! ws_server = "127.0.0.1:50152"
+//! ws_allowed_origins = ["https://dashboard.example.com"]
+//! # serve the readiness of the service on GET /healthz, for orchestration systems
+//! health_endpoint = "127.0.0.1:50153"
+//!
+//! # on battery below 30 %, poll players 4 times less often and correct the shown progress at most every 30 seconds
+//! [energy]
+//! battery_threshold = 30
+//! polling_slowdown = 4.0
+//! timeline_interval = 30.0
+//!
+//! # speak the title and artist of new tracks through the text-to-speech of the OS
+//! [announcements]
+//! track_change = true
+//!
+//! # callers of a driver server reachable from the LAN, see fsct_core::auth::AuthPolicy
+//! [driver_auth]
+//! tokens = [{ name = "office", token = "…", scope = "control" }]
+//!
+//! # forward the player of this host to the devices of another service
+//! [[bridges]]
+//! target = "http://hallway.local:50151"
+//! token = "…"
+//! players = ["spotify"]
+//! host = "office-pc"
+//!
+//! # self ids of the players of this service, `native@studio-pc#2-windows-gsmtc-Spotify.exe` here
+//! [self_ids]
+//! machine = "auto"
+//! instance = "2"
+//!
+//! # format of clocks shown on idle devices; `hours` ("12" or "24") and `separator` override the locale
+//! [time_format]
+//! locale = "en-US"
+//!
+//! [devices]
+//! allow = ["31c0:*"]
+//! deny = ["31c0:0002"]
+//!
+//! # seconds USB control transfers to devices may take, per kind of request, for slow firmwares
+//! [usb.timeouts]
+//! text = 2.0
+//!
+//! [polling]
+//! # seconds between reads of firmware error reports
+//! device_errors = 5.0
+//! ports.jxa = { interval = 1.0, jitter = 0.2 }
+//!
+//! [[text_limits]]
+//! device = "31c0:0001"
+//! max_length = 32
+//!
+//! # send progress ahead by the display lag and the measured write latency, so it is shown in step with the audio
+//! [[latency]]
+//! device = "31c0:0001"
+//! display_lag = 0.12
+//! measure = true
+//!
+//! # work around firmwares with broken clocks, text encodings or slow request handling
+//! [[quirks]]
+//! device = "31c0:0002"
+//! skip_time_sync = true
+//! text_encoding = "utf16"
+//! write_delay = 0.01
+//! warmup = ["enable", "encoding", "status", "texts"]
+//!
+//! # write the shown texts and status again every 10 minutes, for devices losing them on internal resets
+//! [[text_refresh]]
+//! device = "31c0:0003"
+//! interval = 600.0
+//!
+//! # switch the display off after 5 minutes without playback
+//! [[idle_timeout]]
+//! device = "31c0:0001"
+//! timeout = 300.0
+//!
+//! # flash the display when the title changes
+//! [[notify]]
+//! device = "31c0:0001"
+//! on_track_change = true
+//! notification = "flash"
+//!
+//! # rotate through the clock and the date after a minute without playback
+//! [[aux_rotation]]
+//! device = "31c0:0001"
+//! providers = ["clock", "date"]
+//! interval = 10.0
+//! start_after = 60.0
+//!
+//! # compose the text lines of the device from the player texts, see fsct_core::text_template
+//! [[text_layout]]
+//! device = "31c0:0001"
+//! current_title = "{title}[ – {artist}]"
+//! ```
+//!
+//! Packages installing a port can add its settings without rewriting the file, as fragments in the `config.d`
+//! directory beside it (`config.toml` with `config.d/mpd.toml`, `config.d/mqtt.toml`, …). Fragments are read in the
+//! order of their names and merged into the file: tables key by key, arrays extended, other values replaced.
+//!
+//! A [`ConfigHandle`] applies the file to a [`LocalDriver`] and applies it again on [`ConfigHandle::reload`];
+//! [`run_config_service`] reloads it on `SIGHUP`.
+
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "usb")]
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::LevelFilter;
+#[cfg(feature = "usb")]
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::announcements::AnnouncementConfig;
+use crate::auth::AuthPolicy;
+use crate::aux_content::AuxRotation;
+use crate::definitions::{FsctNotification, FsctTextEncoding, UsbRequestTimeouts};
+use crate::device_filter::{DeviceFilter, UsbIdPattern};
+use crate::orchestrator::NotifyPolicy;
+use crate::power::EnergyConfig;
+use crate::polling::PollingConfig;
+#[cfg(feature = "usb")]
+use crate::polling::PollingRegistry;
+use crate::quirks::{DeviceQuirks, QuirkEntry, QuirkTable};
+use crate::self_id::SelfIdNamespace;
+use crate::serde_format::{duration_secs, optional_duration_secs};
+use crate::text_template::TextLayout;
+use crate::time_format::TimeFormat;
+use crate::warmup::WarmupStep;
+#[cfg(feature = "usb")]
+use crate::device_history::DeviceAttachRecord;
+#[cfg(feature = "usb")]
+use crate::device_manager::{DeviceEvent, DEFAULT_ERROR_POLL_INTERVAL};
+#[cfg(feature = "usb")]
+use crate::player_events::{PlayerEvent, PlayerEventFilter, PlayerEventKind};
+#[cfg(feature = "usb")]
+use crate::service::{spawn_service, ServiceHandle};
+#[cfg(feature = "usb")]
+use crate::{FsctDriver, LocalDriver};
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file {}: {source}", path.display())]
+    Read { path: PathBuf, source: std::io::Error },
+    #[error("Invalid config file {}: {source}", path.display())]
+    Parse { path: PathBuf, source: toml::de::Error },
+    #[error("Failed to read config directory {}: {source}", path.display())]
+    ReadDir { path: PathBuf, source: std::io::Error },
+}
+
+/// Settings of a host service, see the [module documentation](self) for the file format.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HostConfig {
+    /// Most verbose level logged. The logger of the service is set up with it at start; a reload can lower it,
+    /// raising it above the level the service started with takes a restart. Removing it returns to the level the
+    /// service started with.
+    pub log_level: Option<LevelFilter>,
+    /// Self id of the player preferred for devices, once it registers.
+    pub preferred_player: Option<String>,
+    /// Whether playing players assigned to a device are paused when it disconnects.
+    pub pause_on_disconnect: bool,
+    /// Whether the usage statistics of the driver are recorded, see [`UsageStats`](crate::usage_stats::UsageStats);
+    /// statistics recorded before are kept when turned off.
+    pub usage_stats: bool,
+    /// Spoken announcements of what devices show, see [`Announcer`](crate::announcements::Announcer); none if unset.
+    pub announcements: Option<AnnouncementConfig>,
+    /// Throttling of updates while the host runs on battery, see [`PowerMonitor`](crate::power::PowerMonitor).
+    pub energy: EnergyConfig,
+    /// Device models to drive.
+    pub devices: DeviceFilter,
+    pub usb: UsbConfig,
+    pub polling: PollingIntervals,
+    /// Text lengths of device models, lowering the lengths the devices announce.
+    pub text_limits: Vec<TextLimit>,
+    /// Compensation of the progress display lag of device models.
+    pub latency: Vec<LatencyCompensation>,
+    /// Periodic refresh of the texts of device models losing them.
+    pub text_refresh: Vec<TextRefresh>,
+    /// Workarounds for device models misbehaving otherwise.
+    pub quirks: Vec<QuirkOverride>,
+    /// Idle policies of device models; displays stay on without one.
+    pub idle_timeout: Vec<IdleTimeout>,
+    /// When device models are asked for attention signals; never without an entry.
+    pub notify: Vec<DeviceNotifyPolicy>,
+    /// Auxiliary content device models rotate through while idle; idle displays are left as they are without one.
+    pub aux_rotation: Vec<DeviceAuxRotation>,
+    /// Composition of the text lines of device models; player texts are shown as they are without one.
+    pub text_layout: Vec<DeviceTextLayout>,
+    /// Address services serve their driver on over gRPC, for `fsctctl` and player ports in other processes; the
+    /// service default (loopback) if unset. Changes take a restart.
+    pub driver_server: Option<SocketAddr>,
+    /// Whether services advertise their driver server over mDNS as `_fsct-host._tcp`; by default they do when it is
+    /// reachable from the LAN, i.e. not on loopback. A `ws_server` reachable from the LAN is advertised with it.
+    /// Changes take a restart.
+    pub zeroconf: Option<bool>,
+    /// Who may use the driver server; callers presenting the token of `fsct_core::auth::default_token_path` if
+    /// unset. Changes take a restart.
+    pub driver_auth: Option<AuthPolicy>,
+    /// Unix socket (named pipe on Windows) services serve JSON-RPC on, see `fsct_core::ipc`; the default path if
+    /// unset. Changes take a restart.
+    pub ipc_socket: Option<PathBuf>,
+    /// Address services stream their events on over WebSocket, see `fsct_core::ipc::ws`; none if unset. Clients are
+    /// authenticated like callers of the driver server, with `driver_auth`. Changes take a restart.
+    pub ws_server: Option<SocketAddr>,
+    /// Origins of the web pages allowed to use the WebSocket server besides pages served from the host.
+    pub ws_allowed_origins: Option<Vec<String>>,
+    /// Address services serve their readiness on over HTTP, see `fsct_core::readiness`; none if unset. Changes take a
+    /// restart.
+    pub health_endpoint: Option<SocketAddr>,
+    /// Driver servers of other services the players of this one are forwarded to. Changes take a restart.
+    pub bridges: Vec<BridgeConfig>,
+    /// Namespace of the self ids of the players registered by the native watchers. Changes take a restart.
+    pub self_ids: SelfIdNamespace,
+    /// Format of clocks shown on devices whose auxiliary content rotation sets none.
+    pub time_format: TimeFormat,
+}
+
+/// Driver server of another service players are forwarded to, see `fsct_core::remote::DriverBridge`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BridgeConfig {
+    /// Endpoint of the server, e.g. `http://hallway.local:50151`.
+    pub target: String,
+    /// Bearer token presented to the server.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Self ids of the players forwarded; all players if empty.
+    #[serde(default)]
+    pub players: Vec<String>,
+    /// Host name the players are tagged with on the other service; the address of this host if unset.
+    #[serde(default)]
+    pub host: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UsbConfig {
+    /// Timeouts of control transfers to devices; those not set keep their defaults.
+    pub timeouts: UsbRequestTimeouts,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PollingIntervals {
+    /// Time between reads of firmware error reports of devices.
+    #[serde(with = "optional_duration_secs")]
+    pub device_errors: Option<Duration>,
+    /// Polling of ports by port name, see [`PollingRegistry`](crate::polling::PollingRegistry).
+    pub ports: BTreeMap<String, PollingConfig>,
+}
+
+/// Maximum length of texts of a device model in bytes; longer texts are cut on the host.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TextLimit {
+    pub device: UsbIdPattern,
+    pub max_length: usize,
+}
+
+/// Progress display lag of a device model, see [`DeviceQuirks::display_lag`] and
+/// [`DeviceQuirks::measure_latency`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LatencyCompensation {
+    pub device: UsbIdPattern,
+    /// Time the device takes to show progress.
+    #[serde(default, with = "optional_duration_secs")]
+    pub display_lag: Option<Duration>,
+    /// Whether the write latency of the device is measured and compensated as well.
+    #[serde(default)]
+    pub measure: bool,
+}
+
+/// Interval at which the shown texts and status of a device model are written again, see
+/// [`DeviceQuirks::text_refresh`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TextRefresh {
+    pub device: UsbIdPattern,
+    #[serde(with = "duration_secs")]
+    pub interval: Duration,
+}
+
+/// Workarounds for a device model, see [`DeviceQuirks`]; those not set keep the built-in ones.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuirkOverride {
+    pub device: UsbIdPattern,
+    /// Don't read the device clock.
+    #[serde(default)]
+    pub skip_time_sync: Option<bool>,
+    /// Text encoding to use instead of the one announced in the descriptors.
+    #[serde(default)]
+    pub text_encoding: Option<FsctTextEncoding>,
+    /// Minimum time between consecutive control transfers to the device.
+    #[serde(default, with = "optional_duration_secs")]
+    pub write_delay: Option<Duration>,
+    /// Warm-up sequence of attached devices; steps not listed are skipped.
+    #[serde(default)]
+    pub warmup: Option<Vec<WarmupStep>>,
+}
+
+/// Time after which the display of a device model showing no playback is switched off, see
+/// [`OrchestratorControl::set_idle_timeout`](crate::orchestrator::OrchestratorControl::set_idle_timeout).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IdleTimeout {
+    pub device: UsbIdPattern,
+    #[serde(with = "duration_secs")]
+    pub timeout: Duration,
+}
+
+/// When a device model is asked for an attention signal, see [`NotifyPolicy`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceNotifyPolicy {
+    pub device: UsbIdPattern,
+    #[serde(default)]
+    pub on_track_change: bool,
+    #[serde(default)]
+    pub on_assignment: bool,
+    #[serde(default)]
+    pub notification: FsctNotification,
+}
+
+impl DeviceNotifyPolicy {
+    pub fn policy(&self) -> NotifyPolicy {
+        NotifyPolicy {
+            on_track_change: self.on_track_change,
+            on_assignment: self.on_assignment,
+            notification: self.notification,
+        }
+    }
+}
+
+/// Auxiliary content (clock, date, ...) a device model rotates through while it shows no playback, see
+/// [`AuxRotation`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceAuxRotation {
+    pub device: UsbIdPattern,
+    /// Provider names, shown in this order.
+    pub providers: Vec<String>,
+    #[serde(with = "duration_secs")]
+    pub interval: Duration,
+    #[serde(with = "duration_secs")]
+    pub start_after: Duration,
+    /// Format of clocks on the device, overriding `time_format`.
+    #[serde(default)]
+    pub time_format: Option<TimeFormat>,
+}
+
+impl DeviceAuxRotation {
+    pub fn rotation(&self) -> AuxRotation {
+        AuxRotation {
+            providers: self.providers.clone(),
+            interval: self.interval,
+            start_after: self.start_after,
+            time_format: self.time_format,
+        }
+    }
+}
+
+/// Templates of the text slots of a device model, keyed by slot, e.g. `current_title`; see [`TextLayout`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceTextLayout {
+    pub device: UsbIdPattern,
+    #[serde(flatten)]
+    pub layout: TextLayout,
+}
+
+/// The last of the entries for the device model, so fragments override the config file.
+fn device_entry<T>(entries: &[T], pattern: impl Fn(&T) -> &UsbIdPattern, vendor_id: u16, product_id: u16)
+                   -> Option<&T> {
+    entries.iter().rev().find(|entry| pattern(entry).matches(vendor_id, product_id))
+}
+
+impl HostConfig {
+    pub fn parse(toml: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(toml)
+    }
+
+    /// Reads the config file and merges the fragments of its [drop-in directory](fragment_dir) into it; without
+    /// either the defaults apply.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let mut table = read_table(path)?.unwrap_or_default();
+        for fragment in fragment_paths(&fragment_dir(path))? {
+            if let Some(fragment) = read_table(&fragment)? {
+                merge_table(&mut table, fragment);
+            }
+        }
+        toml::Value::Table(table).try_into().map_err(|source| ConfigError::Parse { path: path.to_path_buf(), source })
+    }
+
+    /// Quirks overriding the built-in ones with the configured quirks, text limits, latency compensation and text
+    /// refresh.
+    pub fn quirk_overrides(&self) -> QuirkTable {
+        let entry = |device: &UsbIdPattern, quirks| QuirkEntry {
+            vendor_id: device.vendor_id,
+            product_id: device.product_id,
+            min_firmware_version: None,
+            max_firmware_version: None,
+            quirks,
+        };
+        let text_limits = self.text_limits.iter().map(|limit| {
+            entry(&limit.device, DeviceQuirks { max_text_length: Some(limit.max_length), ..Default::default() })
+        });
+        let latency = self.latency.iter().map(|latency| {
+            let quirks = DeviceQuirks {
+                display_lag: latency.display_lag,
+                measure_latency: Some(latency.measure),
+                ..Default::default()
+            };
+            entry(&latency.device, quirks)
+        });
+        let text_refresh = self.text_refresh.iter().map(|refresh| {
+            entry(&refresh.device, DeviceQuirks { text_refresh: Some(refresh.interval), ..Default::default() })
+        });
+        let quirks = self.quirks.iter().map(|quirks| {
+            let overrides = DeviceQuirks {
+                skip_time_sync: quirks.skip_time_sync,
+                text_encoding: quirks.text_encoding,
+                write_delay: quirks.write_delay,
+                warmup: quirks.warmup.clone(),
+                ..Default::default()
+            };
+            entry(&quirks.device, overrides)
+        });
+        QuirkTable::new(quirks.chain(text_limits).chain(latency).chain(text_refresh).collect())
+    }
+
+    /// Idle timeout of the device model, if it has one.
+    pub fn idle_timeout(&self, vendor_id: u16, product_id: u16) -> Option<Duration> {
+        device_entry(&self.idle_timeout, |entry| &entry.device, vendor_id, product_id).map(|entry| entry.timeout)
+    }
+
+    /// When the device model is asked for attention signals, if ever.
+    pub fn notify_policy(&self, vendor_id: u16, product_id: u16) -> Option<NotifyPolicy> {
+        device_entry(&self.notify, |entry| &entry.device, vendor_id, product_id).map(DeviceNotifyPolicy::policy)
+    }
+
+    /// Auxiliary content rotation of the device model, if it has one.
+    pub fn aux_rotation(&self, vendor_id: u16, product_id: u16) -> Option<AuxRotation> {
+        device_entry(&self.aux_rotation, |entry| &entry.device, vendor_id, product_id).map(DeviceAuxRotation::rotation)
+    }
+
+    /// Text layout of the device model, if it has one.
+    pub fn text_layout(&self, vendor_id: u16, product_id: u16) -> Option<TextLayout> {
+        device_entry(&self.text_layout, |entry| &entry.device, vendor_id, product_id).map(|entry| entry.layout.clone())
+    }
+}
+
+/// Directory of the config fragments merged into the config file at `path`: `config.d` beside `config.toml`.
+pub fn fragment_dir(path: &Path) -> PathBuf {
+    path.with_extension("d")
+}
+
+/// The `.toml` files of the directory by name; none if it does not exist.
+fn fragment_paths(dir: &Path) -> Result<Vec<PathBuf>, ConfigError> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(source) => return Err(ConfigError::ReadDir { path: dir.to_path_buf(), source }),
+    };
+    let mut paths = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|source| ConfigError::ReadDir { path: dir.to_path_buf(), source })?;
+    paths.retain(|path| path.extension().is_some_and(|extension| extension == "toml") && path.is_file());
+    paths.sort();
+    Ok(paths)
+}
+
+/// Reads a config file as a table, checked to be a valid config on its own so errors point into it; `None` if the
+/// file does not exist.
+fn read_table(path: &Path) -> Result<Option<toml::Table>, ConfigError> {
+    let toml = match std::fs::read_to_string(path) {
+        Ok(toml) => toml,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(source) => return Err(ConfigError::Read { path: path.to_path_buf(), source }),
+    };
+    let parse_error = |source| ConfigError::Parse { path: path.to_path_buf(), source };
+    HostConfig::parse(&toml).map_err(parse_error)?;
+    toml.parse().map(Some).map_err(parse_error)
+}
+
+/// Merges `fragment` into `table`: tables key by key, arrays extended, other values replaced.
+fn merge_table(table: &mut toml::Table, fragment: toml::Table) {
+    for (key, value) in fragment {
+        match (table.get_mut(&key), value) {
+            (Some(toml::Value::Table(table)), toml::Value::Table(fragment)) => merge_table(table, fragment),
+            (Some(toml::Value::Array(array)), toml::Value::Array(fragment)) => array.extend(fragment),
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Location of the config file: `FSCT_CONFIG` if set, otherwise `%ProgramData%\FSCT\config.toml` on Windows,
+/// `~/Library/Application Support/FSCT/config.toml` on macOS and `$XDG_CONFIG_HOME/fsct/config.toml` elsewhere.
+pub fn default_config_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("FSCT_CONFIG") {
+        return PathBuf::from(path);
+    }
+    let env_dir = |name: &str| std::env::var_os(name).filter(|dir| !dir.is_empty()).map(PathBuf::from);
+    if cfg!(target_os = "windows") {
+        env_dir("PROGRAMDATA").unwrap_or_else(|| PathBuf::from("C:\\ProgramData")).join("FSCT").join("config.toml")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").unwrap_or_default().join("Library/Application Support/FSCT/config.toml")
+    } else {
+        env_dir("XDG_CONFIG_HOME")
+            .or_else(|| env_dir("HOME").map(|home| home.join(".config")))
+            .unwrap_or_else(|| PathBuf::from("/etc"))
+            .join("fsct/config.toml")
+    }
+}
+
+/// The config file applied to a [`LocalDriver`].
+#[cfg(feature = "usb")]
+pub struct ConfigHandle {
+    path: PathBuf,
+    driver: Arc<LocalDriver>,
+    polling: Option<Arc<PollingRegistry>>,
+    /// Level the logger was set up with before the config was applied.
+    startup_log_level: LevelFilter,
+    applied: Mutex<HostConfig>,
+}
+
+#[cfg(feature = "usb")]
+impl ConfigHandle {
+    /// Handle of the config file at `path`; nothing is applied before [`ConfigHandle::reload`]. Create it once the
+    /// logger is set up, a config without `log_level` returns to the level it was set up with.
+    pub fn new(path: impl Into<PathBuf>, driver: Arc<LocalDriver>) -> Self {
+        Self {
+            path: path.into(),
+            driver,
+            polling: None,
+            startup_log_level: log::max_level(),
+            applied: Mutex::new(HostConfig::default()),
+        }
+    }
+
+    /// Applies the configured polling of ports to the registry of the service.
+    pub fn with_polling(mut self, registry: Arc<PollingRegistry>) -> Self {
+        self.polling = Some(registry);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The config applied last.
+    pub fn config(&self) -> HostConfig {
+        self.applied.lock().unwrap().clone()
+    }
+
+    /// Reads the config file and applies it; an unreadable or invalid file leaves the applied config in place.
+    pub async fn reload(&self) -> Result<(), ConfigError> {
+        let config = HostConfig::load(&self.path)?;
+        self.apply(config).await;
+        info!("Applied config file {}", self.path.display());
+        Ok(())
+    }
+
+    /// Applies `config` to the driver. Settings missing from it return to their defaults, the log level to the one
+    /// the logger was set up with and the polling of ports to the one they registered with; text limits take effect
+    /// for devices attached from now on, settings of device models such as idle timeouts for attached devices once
+    /// the driver runs.
+    pub async fn apply(&self, config: HostConfig) {
+        log::set_max_level(config.log_level.unwrap_or(self.startup_log_level));
+        let device_manager = self.driver.device_manager();
+        device_manager.set_quirks(QuirkTable::builtin().with_overrides(config.quirk_overrides()));
+        device_manager.set_error_poll_interval(config.polling.device_errors.unwrap_or(DEFAULT_ERROR_POLL_INTERVAL));
+        device_manager.set_request_timeouts(config.usb.timeouts);
+        let previous_ports = self.applied.lock().unwrap().polling.ports.clone();
+        if let Some(registry) = &self.polling {
+            for port in previous_ports.keys().filter(|port| !config.polling.ports.contains_key(*port)) {
+                registry.reset(port);
+            }
+        }
+        for (port, polling) in &config.polling.ports {
+            let Some(registry) = &self.polling else {
+                warn!("Polling of port {} is configured, but the service has no polling ports", port);
+                continue;
+            };
+            if let Err(e) = registry.configure(port, *polling) {
+                warn!("Invalid polling of port {}: {}", port, e);
+            }
+        }
+        if device_manager.device_filter() != config.devices {
+            self.driver.set_device_filter(config.devices.clone()).await;
+        }
+        if let Err(e) = self.driver.set_pause_on_disconnect(config.pause_on_disconnect).await {
+            warn!("Failed to apply pause on disconnect: {}", e);
+        }
+        if let Err(e) = self.driver.set_time_format(config.time_format).await {
+            warn!("Failed to apply time format: {}", e);
+        }
+        if let Some(stats) = self.driver.usage_stats() {
+            stats.set_enabled(config.usage_stats);
+        }
+        if let Some(power) = self.driver.power_monitor() {
+            power.set_config(config.energy.clone());
+        }
+        if let Some(announcer) = self.driver.announcer() {
+            announcer.set_config(config.announcements.clone().unwrap_or_else(AnnouncementConfig::silent));
+        }
+
+        let previous = std::mem::replace(&mut *self.applied.lock().unwrap(), config.clone());
+        let player_manager = self.driver.player_manager();
+        match &config.preferred_player {
+            Some(self_id) => {
+                if let Some(player_id) = player_manager.find_player(self_id) {
+                    let _ = self.driver.set_preferred_player(Some(player_id));
+                }
+            }
+            None => {
+                // clear the preference only if it was set from the config
+                let previous = previous.preferred_player.and_then(|self_id| player_manager.find_player(&self_id));
+                if previous.is_some() && self.driver.get_preferred_player() == previous {
+                    let _ = self.driver.set_preferred_player(None);
+                }
+            }
+        }
+        self.apply_to_attached_devices().await;
+    }
+
+    /// Applies the settings of device models to the attached devices; nothing before the driver runs.
+    async fn apply_to_attached_devices(&self) {
+        if self.driver.control().is_err() {
+            return;
+        }
+        for record in self.driver.list_devices().await.unwrap_or_default() {
+            self.apply_to_device(&record).await;
+        }
+    }
+
+    /// Applies the settings of its device model to the device, e.g. its idle timeout and auxiliary content rotation.
+    async fn apply_to_device(&self, record: &DeviceAttachRecord) {
+        let Ok(control) = self.driver.control() else {
+            return;
+        };
+        let config = self.config();
+        let (device_id, vendor_id, product_id) = (record.device_id, record.vendor_id, record.product_id);
+        if let Err(e) = control.set_idle_timeout(device_id, config.idle_timeout(vendor_id, product_id)).await {
+            warn!("Failed to apply idle timeout of device {}: {}", device_id, e);
+        }
+        if let Err(e) = control.set_notify_policy(device_id, config.notify_policy(vendor_id, product_id)).await {
+            warn!("Failed to apply notify policy of device {}: {}", device_id, e);
+        }
+        if let Err(e) = control.set_aux_rotation(device_id, config.aux_rotation(vendor_id, product_id)).await {
+            warn!("Failed to apply auxiliary content rotation of device {}: {}", device_id, e);
+        }
+        if let Err(e) = self.driver.set_text_layout(device_id, config.text_layout(vendor_id, product_id)).await {
+            warn!("Failed to apply text layout of device {}: {}", device_id, e);
+        }
+    }
+
+    /// Prefers the player if it is the configured one.
+    fn player_registered(&self, player_id: crate::ManagedPlayerId, self_id: &str) {
+        if self.applied.lock().unwrap().preferred_player.as_deref() == Some(self_id) {
+            info!("Preferring configured player {} ({})", self_id, player_id);
+            let _ = self.driver.set_preferred_player(Some(player_id));
+        }
+    }
+}
+
+/// Resolves on every `SIGHUP`; never on platforms without it.
+#[cfg(feature = "usb")]
+struct ReloadSignal {
+    #[cfg(unix)]
+    hangup: Option<tokio::signal::unix::Signal>,
+}
+
+#[cfg(feature = "usb")]
+impl ReloadSignal {
+    fn new() -> Self {
+        Self {
+            #[cfg(unix)]
+            hangup: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+                .inspect_err(|e| warn!("Config file won't be reloaded on SIGHUP: {}", e))
+                .ok(),
+        }
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(hangup) = &mut self.hangup && hangup.recv().await.is_some() {
+            return;
+        }
+        std::future::pending().await
+    }
+}
+
+/// Prefers the configured player when it registers, applies the settings of device models to devices as they
+/// attach and reloads the config file on `SIGHUP`.
+#[cfg(feature = "usb")]
+pub fn run_config_service(handle: Arc<ConfigHandle>) -> ServiceHandle {
+    spawn_service(move |mut stop| async move {
+        let mut registrations = handle.driver
+            .subscribe_filtered(PlayerEventFilter::all().with_kinds([PlayerEventKind::Registered]));
+        let mut devices = handle.driver.subscribe_device_events();
+        let mut reload = ReloadSignal::new();
+        // devices attached while the driver started
+        handle.apply_to_attached_devices().await;
+        loop {
+            tokio::select! {
+                _ = stop.signaled() => break,
+                event = registrations.recv() => match event {
+                    Ok(PlayerEvent::Registered { player_id, self_id }) => handle.player_registered(player_id, &self_id),
+                    Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                },
+                event = devices.recv() => match event {
+                    Ok(DeviceEvent::Added(device_id)) => {
+                        if let Some(record) = handle.driver.device_manager().attach_record(device_id) {
+                            handle.apply_to_device(&record).await;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
+                        handle.apply_to_attached_devices().await
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                },
+                _ = reload.recv() => {
+                    if let Err(e) = handle.reload().await {
+                        warn!("{}", e);
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::FsctTextMetadata;
+    use crate::text_template::TextTemplate;
+    use crate::time_format::HourCycle;
+
+    const CONFIG: &str = r#"
+        log_level = "debug"
+        preferred_player = "spotify"
+        pause_on_disconnect = true
+        usage_stats = true
+
+        [devices]
+        allow = ["31c0:*"]
+        deny = ["31c0:0002"]
+
+        [energy]
+        battery_threshold = 30
+        timeline_interval = 30.0
+
+        [announcements]
+        status_change = true
+
+        [usb.timeouts]
+        text = 2.0
+
+        [polling]
+        device_errors = 5.0
+        ports.jxa = { interval = 1.0, jitter = 0.2 }
+
+        [[text_limits]]
+        device = "31c0:0001"
+        max_length = 32
+
+        [[bridges]]
+        target = "http://hallway.local:50151"
+        players = ["spotify"]
+
+        [self_ids]
+        instance = "2"
+
+        [time_format]
+        locale = "fi-FI"
+        hours = "12"
+
+        [[latency]]
+        device = "31c0:0001"
+        display_lag = 0.12
+
+        [[text_refresh]]
+        device = "31c0:0001"
+        interval = 600.0
+
+        [[quirks]]
+        device = "31c0:0001"
+        skip_time_sync = true
+        text_encoding = "utf16"
+        write_delay = 0.01
+        warmup = ["enable", "encoding", "texts"]
+
+        [[idle_timeout]]
+        device = "31c0:*"
+        timeout = 300.0
+
+        [[idle_timeout]]
+        device = "31c0:0002"
+        timeout = 60.0
+
+        [[notify]]
+        device = "31c0:0001"
+        on_track_change = true
+        notification = "beep"
+
+        [[aux_rotation]]
+        device = "31c0:0001"
+        providers = ["clock", "date"]
+        interval = 10.0
+        start_after = 60.0
+
+        [[text_layout]]
+        device = "31c0:0001"
+        current_title = "{title}[ – {artist}]"
+    "#;
+
+    #[test]
+    fn config_file_is_parsed() {
+        let config = HostConfig::parse(CONFIG).unwrap();
+        assert_eq!(config.log_level, Some(LevelFilter::Debug));
+        assert_eq!(config.driver_server, None);
+        assert!(config.pause_on_disconnect && config.usage_stats);
+        let announcements = config.announcements.clone().unwrap();
+        assert!(announcements.track_change && announcements.status_change);
+        assert_eq!(config.energy.battery_threshold, Some(30));
+        assert_eq!(config.energy.timeline_interval, Duration::from_secs(30));
+        assert!(!config.devices.accepts(0x31c0, 0x0002));
+        assert_eq!(config.polling.device_errors, Some(Duration::from_secs(5)));
+        let timeouts = UsbRequestTimeouts { text: Duration::from_secs(2), ..Default::default() };
+        assert_eq!(config.usb.timeouts, timeouts);
+        assert_eq!(config.polling.ports["jxa"], PollingConfig::new(Duration::from_secs(1), Duration::from_millis(200)));
+        let quirks = config.quirk_overrides().lookup(0x31c0, 0x0001, 0x0100);
+        assert_eq!((quirks.max_text_length, quirks.display_lag), (Some(32), Some(Duration::from_millis(120))));
+        assert!(!quirks.measures_latency());
+        assert_eq!(quirks.text_refresh_interval(), Some(Duration::from_secs(600)));
+        assert!(quirks.skips_time_sync());
+        assert_eq!(quirks.text_encoding, Some(FsctTextEncoding::Utf16));
+        assert_eq!(quirks.write_delay, Some(Duration::from_millis(10)));
+        assert_eq!(quirks.warmup_sequence(), [WarmupStep::Enable, WarmupStep::Encoding, WarmupStep::Texts]);
+        assert_eq!(config.idle_timeout(0x31c0, 0x0001), Some(Duration::from_secs(300)));
+        assert_eq!(config.idle_timeout(0x31c0, 0x0002), Some(Duration::from_secs(60)));
+        assert_eq!(config.idle_timeout(0x1234, 0x0001), None);
+        let notify = config.notify_policy(0x31c0, 0x0001).unwrap();
+        assert!(notify.on_track_change && !notify.on_assignment && notify.notification == FsctNotification::Beep);
+        assert_eq!(config.notify_policy(0x31c0, 0x0002), None);
+        let rotation = config.aux_rotation(0x31c0, 0x0001).unwrap();
+        assert_eq!(rotation.providers, ["clock", "date"]);
+        assert_eq!(rotation.start_after, Duration::from_secs(60));
+        let title = TextTemplate::parse("{title}[ – {artist}]").unwrap();
+        let layout = TextLayout::new().with_template(FsctTextMetadata::CurrentTitle, title);
+        assert_eq!(config.text_layout(0x31c0, 0x0001), Some(layout));
+        assert!(HostConfig::parse("[[text_layout]]\ndevice = \"31c0:0001\"\nunknown = \"{title}\"").is_err());
+        assert_eq!(config.bridges[0].players, ["spotify"]);
+        assert_eq!((config.bridges[0].token.as_deref(), config.driver_auth), (None, None));
+        assert_eq!(config.self_ids.self_id("linux-mpris-vlc"), "native#2-linux-mpris-vlc");
+        assert_eq!(config.time_format, TimeFormat::new(HourCycle::H12, '.'));
+        assert!(HostConfig::parse("unknown = 1").is_err());
+        assert_eq!(HostConfig::load(Path::new("/nonexistent/fsct.toml")).unwrap(), HostConfig::default());
+    }
+
+    #[test]
+    fn fragments_are_merged_into_the_config_file() {
+        let dir = std::env::temp_dir().join(format!("fsct-config-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("config.toml");
+        std::fs::create_dir_all(fragment_dir(&path)).unwrap();
+        std::fs::write(&path, CONFIG).unwrap();
+        let mqtt = "log_level = \"info\"\n[polling]\nports.mqtt = { interval = 2.0 }";
+        std::fs::write(fragment_dir(&path).join("20-mqtt.toml"), mqtt).unwrap();
+        let mpd = "preferred_player = \"mpd\"\n[devices]\ndeny = [\"31c0:0003\"]\n\
+                   [polling]\nports.mpd = { interval = 1.0 }";
+        std::fs::write(fragment_dir(&path).join("10-mpd.toml"), mpd).unwrap();
+        std::fs::write(fragment_dir(&path).join("README"), "not a fragment").unwrap();
+
+        let config = HostConfig::load(&path).unwrap();
+        assert_eq!((config.log_level, config.preferred_player.as_deref()), (Some(LevelFilter::Info), Some("mpd")));
+        assert!(config.devices.accepts(0x31c0, 0x0001));
+        assert!(!config.devices.accepts(0x31c0, 0x0002) && !config.devices.accepts(0x31c0, 0x0003));
+        assert_eq!(config.polling.ports.keys().collect::<Vec<_>>(), ["jxa", "mpd", "mqtt"]);
+        assert_eq!(config.polling.device_errors, Some(Duration::from_secs(5)));
+
+        std::fs::write(fragment_dir(&path).join("30-broken.toml"), "unknown = 1").unwrap();
+        let error = HostConfig::load(&path).unwrap_err();
+        assert!(matches!(error, ConfigError::Parse { path, .. } if path.ends_with("30-broken.toml")));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "usb")]
+    #[tokio::test]
+    async fn reload_applies_config_to_the_driver() {
+        use crate::power::{PowerMode, PowerMonitor, SysfsPowerSource};
+
+        let power = Arc::new(PowerMonitor::new(Box::new(SysfsPowerSource::with_root("/nonexistent")),
+                                               EnergyConfig::default()));
+        let driver = Arc::new(LocalDriver::with_new_managers().with_power_monitor(power.clone()));
+        let spotify = driver.register_player("spotify".to_string()).await.unwrap();
+        let path = std::env::temp_dir().join(format!("fsct-config-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, CONFIG).unwrap();
+        let polling = Arc::new(PollingRegistry::new());
+        let jxa_default = PollingConfig::new(Duration::from_millis(500), Duration::ZERO);
+        let jxa = polling.register("jxa", jxa_default);
+        let startup_log_level = log::max_level();
+        let handle = ConfigHandle::new(&path, driver.clone()).with_polling(polling);
+
+        handle.reload().await.unwrap();
+        assert_eq!(driver.get_preferred_player(), Some(spotify));
+        assert_eq!(driver.device_manager().error_poll_interval(), Duration::from_secs(5));
+        assert_eq!(driver.device_manager().request_timeouts().text, Duration::from_secs(2));
+        assert_eq!(driver.device_manager().device_filter(), handle.config().devices);
+        assert_eq!(power.config().battery_threshold, Some(30));
+        assert_eq!(jxa.configured().interval, Duration::from_secs(1));
+        assert_eq!(log::max_level(), LevelFilter::Debug);
+        assert_eq!(driver.readiness().power_mode, Some(PowerMode::Normal));
+
+        std::fs::write(&path, "preferred_player = \"vlc\"").unwrap();
+        handle.reload().await.unwrap();
+        let vlc = driver.register_player("vlc".to_string()).await.unwrap();
+        handle.player_registered(vlc, "vlc");
+        assert_eq!(driver.get_preferred_player(), Some(vlc));
+        assert_eq!(driver.device_manager().error_poll_interval(), DEFAULT_ERROR_POLL_INTERVAL);
+        assert_eq!(driver.device_manager().request_timeouts(), UsbRequestTimeouts::default());
+        assert_eq!(power.config(), EnergyConfig::default());
+        assert_eq!(jxa.configured(), jxa_default);
+        assert_eq!(log::max_level(), startup_log_level);
+
+        std::fs::write(&path, "log_level = 3").unwrap();
+        assert!(handle.reload().await.is_err());
+        assert_eq!(handle.config().preferred_player.as_deref(), Some("vlc"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
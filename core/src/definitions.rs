@@ -16,6 +16,11 @@
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
 use bitflags::bitflags;
+use serde::{Deserialize, Serialize};
+
+use crate::device_manager::ManagedDeviceId;
+
+use crate::serde_format::{duration_secs, optional_duration_secs, system_time_millis};
 
 bitflags! {
     #[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
@@ -24,21 +29,199 @@ bitflags! {
         const CurrentPlaybackProgress = 0x02;
         const CurrentPlaybackStatus = 0x04;
         const PlaybackQueueMetadata = 0x08;
+        const AttentionSignal = 0x10;
+        /// Device can show elapsed time of content without a known duration (live streams).
+        const LiveProgress = 0x20;
+        /// Device reports firmware side errors through the error report request.
+        const ErrorReporting = 0x40;
+        /// Device sends playback commands over the interrupt IN endpoint of the FSCT interface.
+        const PlaybackCommands = 0x80;
+    }
+}
+
+bitflags! {
+    /// Functionalities announced in the extended functionality descriptor, as the bits of
+    /// [`FsctFunctionality`] are all taken.
+    #[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+    pub struct FsctExtendedFunctionality: u8 {
+        /// Device shows the volume of the player, sent with the volume request.
+        const Volume = 0x01;
+        /// Device shows the shuffle and repeat modes of the player, sent with the playback modes request.
+        const PlaybackModes = 0x02;
+        /// Device takes firmware updates over its FSCT interface, see the `usb::dfu` module.
+        const FirmwareUpdate = 0x04;
     }
 }
 
+/// Volume of a player, `level` in percent (0–100).
+///
+/// Only sent to devices announcing [`FsctExtendedFunctionality::Volume`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VolumeInfo {
+    pub level: u8,
+    pub muted: bool,
+}
+
+/// Repeat mode of a player.
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepeatMode {
+    #[default]
+    Off = 0x00,
+    /// The current track is repeated.
+    Track = 0x01,
+    /// The queue or playlist is repeated.
+    List = 0x02,
+}
+
+/// Shuffle and repeat modes of a player; `None` for modes the player doesn't report.
+///
+/// Only sent to devices announcing [`FsctExtendedFunctionality::PlaybackModes`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaybackModes {
+    #[serde(default)]
+    pub shuffle: Option<bool>,
+    #[serde(default)]
+    pub repeat: Option<RepeatMode>,
+}
+
+/// Brief attention signal requested from a device, e.g. on track change.
+///
+/// Only sent to devices announcing [`FsctFunctionality::AttentionSignal`]; how it is rendered is up to the device.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FsctNotification {
+    #[default]
+    Flash = 0x01,
+    Beep = 0x02,
+}
+
+/// Playback command sent by a device, e.g. from its buttons or remote control.
+///
+/// Only devices announcing [`FsctFunctionality::PlaybackCommands`] send commands; they are routed to the player the
+/// device shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum PlaybackCommand {
+    Play,
+    Pause,
+    Next,
+    Previous,
+    /// Seek to the position from the start of the track.
+    Seek {
+        #[serde(with = "duration_secs")]
+        position: std::time::Duration,
+    },
+    /// Set the volume of the player, e.g. after a turn of the volume knob of the device.
+    SetVolume { volume: VolumeInfo },
+}
+
+/// First request code available for vendor extensions; lower codes are reserved for FSCT requests.
+#[cfg(feature = "vendor-requests")]
+pub const FIRST_VENDOR_REQUEST_CODE: u8 = 0x80;
+
+/// Raw vendor control request to the FSCT interface of a device, for developing proprietary extensions.
+///
+/// With `response_length` 0 the request is sent as control OUT carrying `payload`, otherwise as control IN
+/// reading up to `response_length` bytes (and `payload` must be empty).
+#[cfg(feature = "vendor-requests")]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VendorRequest {
+    pub request: u8,
+    #[serde(default)]
+    pub value: u16,
+    #[serde(default)]
+    pub payload: Vec<u8>,
+    #[serde(default)]
+    pub response_length: u16,
+}
+
+/// Timeouts of USB control transfers to devices, per kind of request.
+///
+/// Slow firmwares may need longer text writes, while a short status timeout detects unresponsive devices fast.
+/// Serialized with durations in fractional seconds; missing fields take their defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsbRequestTimeouts {
+    #[serde(with = "duration_secs")]
+    pub status: std::time::Duration,
+    #[serde(with = "duration_secs")]
+    pub text: std::time::Duration,
+    #[serde(with = "duration_secs")]
+    pub progress: std::time::Duration,
+    /// Any other request: enable, notifications, time synchronization, error reports and vendor requests.
+    #[serde(with = "duration_secs")]
+    pub control: std::time::Duration,
+}
+
+impl Default for UsbRequestTimeouts {
+    fn default() -> Self {
+        Self {
+            status: std::time::Duration::from_millis(250),
+            text: std::time::Duration::from_secs(1),
+            progress: std::time::Duration::from_millis(500),
+            control: std::time::Duration::from_secs(1),
+        }
+    }
+}
+
+/// Kind of an error reported by device firmware.
+///
+/// Serialized as snake_case name, codes without a name as `{"other": <code>}`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FsctDeviceErrorCode {
+    /// A text or the progress could not be rendered; `detail` is the text metadata id, 0 for progress.
+    RenderFailure,
+    /// Text in an encoding the device can't decode; `detail` is the text metadata id.
+    UnsupportedEncoding,
+    /// Data did not fit into a device buffer; `detail` is the request code.
+    BufferOverflow,
+    /// Vendor specific or newer error code.
+    Other(u16),
+}
+
+impl FsctDeviceErrorCode {
+    /// Decodes the raw code; 0 means there is no error.
+    pub fn from_raw(code: u16) -> Option<Self> {
+        match code {
+            0x0000 => None,
+            0x0001 => Some(Self::RenderFailure),
+            0x0002 => Some(Self::UnsupportedEncoding),
+            0x0003 => Some(Self::BufferOverflow),
+            other => Some(Self::Other(other)),
+        }
+    }
+}
+
+/// Error reported by device firmware, together with how often it occurred since the previous report.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DeviceErrorReport {
+    pub code: FsctDeviceErrorCode,
+    pub detail: u16,
+    pub count: u16,
+}
+
+/// Serialized as snake_case name, e.g. `"current_title"`.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FsctTextMetadata {
     #[default]
     CurrentTitle = 0x01,
     CurrentAuthor = 0x02,
     CurrentAlbum = 0x03,
     CurrentGenre = 0x04,
+    CurrentYear = 0x05,
+    CurrentComposer = 0x06,
     QueueTitle = 0x31,
     QueueAuthor = 0x32,
     QueueAlbum = 0x33,
     QueueGenre = 0x34,
+    QueueYear = 0x35,
+    QueueComposer = 0x36,
 }
 
 #[repr(u8)]
@@ -60,8 +243,10 @@ pub enum FsctTextDirection {
     RightToLeft = 1,
 }
 
+/// Serialized as snake_case name, e.g. `"utf8"`.
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FsctTextEncoding {
     Utf8 = 0,
     Utf16 = 1,
@@ -69,21 +254,102 @@ pub enum FsctTextEncoding {
     Utf32 = 3,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl FsctTextEncoding {
+    /// Encoded length of the character in bytes.
+    pub fn encoded_char_length(self, c: char) -> usize {
+        match self {
+            FsctTextEncoding::Utf8 => c.len_utf8(),
+            FsctTextEncoding::Utf16 => c.len_utf16() * 2,
+            FsctTextEncoding::Ucs2 => 2,
+            FsctTextEncoding::Utf32 => 4,
+        }
+    }
+
+    /// Encoded length of the text in bytes.
+    pub fn encoded_length(self, text: &str) -> usize {
+        text.chars().map(|c| self.encoded_char_length(c)).sum()
+    }
+}
+
+/// A text field supported by a device, with the maximum encoded length in bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SupportedText {
+    pub metadata: FsctTextMetadata,
+    pub max_length: usize,
+}
+
+/// Progress of streaming a firmware image to a device, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DfuProgress {
+    pub sent: usize,
+    pub total: usize,
+}
+
+/// Outcome of a firmware update: the firmware versions (BCD, like bcdDevice) the device reported before and after.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FirmwareUpdateReport {
+    pub device_id: ManagedDeviceId,
+    pub previous_version: u16,
+    pub version: u16,
+}
+
+/// What a device can show, for producers to shorten texts or warn users before they get cut on the device.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceLimits {
+    pub device_id: ManagedDeviceId,
+    /// FSCT protocol version the device speaks.
+    pub protocol_version: u8,
+    pub text_encoding: FsctTextEncoding,
+    /// Texts the device shows; texts not listed are dropped.
+    pub texts: Vec<SupportedText>,
+}
+
+impl DeviceLimits {
+    /// Maximum encoded length of the text in bytes, `None` if the device doesn't show it.
+    pub fn max_text_length(&self, metadata: FsctTextMetadata) -> Option<usize> {
+        self.texts.iter().find(|text| text.metadata == metadata).map(|text| text.max_length)
+    }
+
+    /// Whether the device shows the text in full.
+    pub fn fits(&self, metadata: FsctTextMetadata, text: &str) -> bool {
+        self.max_text_length(metadata).is_some_and(|max_length| self.text_encoding.encoded_length(text) <= max_length)
+    }
+}
+
+/// Playback progress of a track.
+///
+/// `duration` is `None` for content without a known end, like live streams or some podcasts.
+///
+/// In JSON `position` and `duration` are fractional seconds (`duration` is `null` if unknown) and `update_time` is
+/// milliseconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TimelineInfo {
+    #[serde(with = "duration_secs")]
     pub position: std::time::Duration,                      // current position in seconds
+    #[serde(with = "system_time_millis")]
     pub update_time: std::time::SystemTime, // when the position was last updated
-    pub duration: std::time::Duration,                      // total duration in seconds
+    #[serde(with = "optional_duration_secs", default)]
+    pub duration: Option<std::time::Duration>,              // total duration in seconds, None if unknown
     pub rate: f64,                          // playback rate
 }
 
+impl TimelineInfo {
+    /// Content without a known duration, e.g. a live stream.
+    pub fn is_live(&self) -> bool {
+        self.duration.is_none()
+    }
+}
+
 /// Represents the various playback states within the Ferrum Streaming Control Technology (FSCT) system.
 ///
 /// This enumeration defines distinct states that describe the current playback status of a media session
 /// in FSCT-enabled devices. It facilitates precise communication of playback conditions between a USB-connected
 /// device and a host system.
+///
+/// Serialized as snake_case name, e.g. `"playing"`.
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 #[allow(non_snake_case)]
 #[allow(unused)]
 pub enum FsctStatus {
@@ -108,3 +374,22 @@ impl Default for FsctStatus {
         Self::Unknown
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn device_limits_count_encoded_bytes() {
+        let limits = DeviceLimits {
+            device_id: Uuid::new_v4(),
+            protocol_version: 1,
+            text_encoding: FsctTextEncoding::Utf16,
+            texts: vec![SupportedText { metadata: FsctTextMetadata::CurrentTitle, max_length: 8 }],
+        };
+        assert!(limits.fits(FsctTextMetadata::CurrentTitle, "abcd"));
+        assert!(!limits.fits(FsctTextMetadata::CurrentTitle, "abc😀"));
+        assert!(!limits.fits(FsctTextMetadata::CurrentAuthor, ""));
+    }
+}
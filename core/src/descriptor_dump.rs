@@ -0,0 +1,62 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Descriptors as read from devices, for firmware developers verifying their descriptor tables against the host
+//! parser, e.g. with `fsctctl device descriptors`.
+
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Serialize};
+
+/// One descriptor with what the host made of it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DescriptorDump {
+    /// Kind of the descriptor, e.g. `"FSCT text metadata"`.
+    pub name: String,
+    /// Bytes as sent by the device, as hex, e.g. `"05 0f 21 00 01"`.
+    pub raw: String,
+    /// Fields as parsed by the host, or why parsing failed.
+    pub decoded: String,
+}
+
+impl DescriptorDump {
+    pub fn new(name: impl Into<String>, raw: &[u8], decoded: impl Into<String>) -> Self {
+        Self { name: name.into(), raw: to_hex(raw), decoded: decoded.into() }
+    }
+}
+
+/// Descriptors of a device the host reads its FSCT capabilities from.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceDescriptorDump {
+    /// BOS descriptor and its device capabilities, the FSCT platform capability among them; none for network
+    /// devices.
+    pub bos: Vec<DescriptorDump>,
+    /// FSCT functionality descriptor and its subordinate descriptors, e.g. text metadata.
+    pub fsct: Vec<DescriptorDump>,
+}
+
+/// Bytes as space separated hex.
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 3);
+    for (i, byte) in bytes.iter().enumerate() {
+        if i > 0 {
+            hex.push(' ');
+        }
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    hex
+}
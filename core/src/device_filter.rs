@@ -0,0 +1,114 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Allow and deny lists of the USB devices the host drives.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Invalid USB id pattern \"{0}\", expected VID:PID in hex, e.g. 31c0:0001, or VID:*")]
+pub struct UsbIdPatternError(String);
+
+/// USB vendor id and optionally product id of device models; without product id all products of the vendor match.
+///
+/// Written as `VID:PID` in hex, e.g. `31c0:0001`, or `VID:*`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UsbIdPattern {
+    pub vendor_id: u16,
+    pub product_id: Option<u16>,
+}
+
+impl UsbIdPattern {
+    pub fn matches(&self, vendor_id: u16, product_id: u16) -> bool {
+        self.vendor_id == vendor_id && self.product_id.is_none_or(|id| id == product_id)
+    }
+}
+
+impl FromStr for UsbIdPattern {
+    type Err = UsbIdPatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || UsbIdPatternError(s.to_string());
+        let (vendor_id, product_id) = s.split_once(':').ok_or_else(error)?;
+        let vendor_id = u16::from_str_radix(vendor_id, 16).map_err(|_| error())?;
+        let product_id = match product_id {
+            "*" => None,
+            product_id => Some(u16::from_str_radix(product_id, 16).map_err(|_| error())?),
+        };
+        Ok(Self { vendor_id, product_id })
+    }
+}
+
+impl fmt::Display for UsbIdPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.product_id {
+            Some(product_id) => write!(f, "{:04x}:{:04x}", self.vendor_id, product_id),
+            None => write!(f, "{:04x}:*", self.vendor_id),
+        }
+    }
+}
+
+impl Serialize for UsbIdPattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for UsbIdPattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Which device models the host drives: those on the allow list (all if it is empty) that are not on the deny list.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceFilter {
+    pub allow: Vec<UsbIdPattern>,
+    pub deny: Vec<UsbIdPattern>,
+}
+
+impl DeviceFilter {
+    pub fn accepts(&self, vendor_id: u16, product_id: u16) -> bool {
+        !self.deny.iter().any(|pattern| pattern.matches(vendor_id, product_id))
+            && (self.allow.is_empty() || self.allow.iter().any(|pattern| pattern.matches(vendor_id, product_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deny_list_wins_over_allow_list() {
+        let filter = DeviceFilter {
+            allow: vec!["31c0:*".parse().unwrap()],
+            deny: vec!["31C0:0002".parse().unwrap()],
+        };
+        assert!(filter.accepts(0x31c0, 0x0001));
+        assert!(!filter.accepts(0x31c0, 0x0002));
+        assert!(!filter.accepts(0x1234, 0x0001));
+        assert!(DeviceFilter::default().accepts(0x1234, 0x0001));
+        assert_eq!(filter.deny[0].to_string(), "31c0:0002");
+        assert!("31c0".parse::<UsbIdPattern>().is_err());
+        assert!("31c0:xyz".parse::<UsbIdPattern>().is_err());
+    }
+}
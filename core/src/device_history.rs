@@ -0,0 +1,134 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::definitions::FsctTextEncoding;
+pub use crate::definitions::SupportedText;
+use crate::device_manager::ManagedDeviceId;
+use crate::serde_format::system_time_millis;
+
+/// Number of attach records kept by default.
+pub const DEFAULT_HISTORY_CAPACITY: usize = 100;
+
+/// What was learned about a device when it was attached, kept so that issues can be correlated with
+/// specific firmware revisions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceAttachRecord {
+    pub device_id: ManagedDeviceId,
+    #[serde(with = "system_time_millis")]
+    pub attached_at: SystemTime,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<String>,
+    /// Device release number (bcdDevice), e.g. `"1.02"`.
+    pub firmware_version: String,
+    /// USB specification release (bcdUSB), e.g. `"2.10"`; empty for network devices.
+    pub usb_version: String,
+    /// FSCT BOS capability descriptor version, e.g. `"1.00"`.
+    pub fsct_capability_version: String,
+    pub fsct_protocol_version: u8,
+    /// Names of the supported FSCT functionalities.
+    pub functionality: Vec<String>,
+    pub text_encoding: FsctTextEncoding,
+    pub supported_texts: Vec<SupportedText>,
+}
+
+/// Formats a binary coded decimal version number (as used in USB descriptors) as `major.minor`.
+pub fn format_bcd_version(version: u16) -> String {
+    format!("{:x}.{:02x}", version >> 8, version & 0xff)
+}
+
+/// Bounded history of device attach records, oldest first.
+pub struct DeviceHistory {
+    capacity: usize,
+    records: Mutex<VecDeque<DeviceAttachRecord>>,
+}
+
+impl DeviceHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, records: Mutex::new(VecDeque::with_capacity(capacity)) }
+    }
+
+    pub fn record(&self, record: DeviceAttachRecord) {
+        let mut records = self.records.lock().unwrap();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// All kept records, oldest first.
+    pub fn records(&self) -> Vec<DeviceAttachRecord> {
+        self.records.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// The most recent record of the given device.
+    pub fn latest(&self, device_id: ManagedDeviceId) -> Option<DeviceAttachRecord> {
+        self.records.lock().unwrap().iter().rev().find(|r| r.device_id == device_id).cloned()
+    }
+}
+
+impl Default for DeviceHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn record(device_id: ManagedDeviceId, firmware_version: &str) -> DeviceAttachRecord {
+        DeviceAttachRecord {
+            device_id,
+            attached_at: SystemTime::now(),
+            vendor_id: 0x1234,
+            product_id: 0x5678,
+            manufacturer: None,
+            product: None,
+            serial_number: None,
+            firmware_version: firmware_version.to_string(),
+            usb_version: format_bcd_version(0x0210),
+            fsct_capability_version: format_bcd_version(0x0100),
+            fsct_protocol_version: 1,
+            functionality: vec![],
+            text_encoding: FsctTextEncoding::Utf8,
+            supported_texts: vec![],
+        }
+    }
+
+    #[test]
+    fn history_keeps_latest_records() {
+        let history = DeviceHistory::new(2);
+        let device = Uuid::new_v4();
+        history.record(record(device, "1.00"));
+        history.record(record(Uuid::new_v4(), "2.00"));
+        history.record(record(device, "1.01"));
+
+        assert_eq!(history.records().len(), 2);
+        assert_eq!(history.latest(device).unwrap().firmware_version, "1.01");
+        assert_eq!(format_bcd_version(0x0102), "1.02");
+    }
+}
@@ -15,29 +15,129 @@
 // This file is part of an implementation of Ferrum Streaming Control Technology™,
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
-use std::collections::HashMap;
+#[cfg(feature = "usb")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "usb")]
 use std::mem::swap;
+#[cfg(feature = "usb")]
 use std::ops::DerefMut;
-use std::sync::{Arc, Mutex};
+#[cfg(feature = "usb")]
+use std::sync::{Arc, Mutex, Weak};
+#[cfg(feature = "usb")]
+use std::time::Duration;
+#[cfg(feature = "usb")]
 use nusb::{DeviceId, DeviceInfo};
+use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast;
 use thiserror::Error;
 use uuid::Uuid;
-use crate::definitions::{FsctStatus, FsctTextMetadata, TimelineInfo};
+#[cfg(feature = "usb")]
+use log::{debug, info, warn};
+use crate::definitions::{DeviceErrorReport, FsctNotification, FsctStatus, FsctTextMetadata, PlaybackCommand, PlaybackModes, TimelineInfo, VolumeInfo};
+#[cfg(feature = "usb")]
+use crate::definitions::{DeviceLimits, DfuProgress, UsbRequestTimeouts};
+#[cfg(feature = "vendor-requests")]
+use crate::definitions::VendorRequest;
+#[cfg(feature = "usb")]
 use crate::usb::errors::FsctDeviceError;
+#[cfg(all(feature = "usb", any(target_os = "linux", target_os = "android")))]
+use crate::usb::{create_fsct_device_from_fd, errors::DeviceDiscoveryError};
+use crate::warmup::WarmupStep;
+use crate::quirks::StatusMap;
+#[cfg(feature = "usb")]
+use crate::device_filter::DeviceFilter;
+#[cfg(feature = "usb")]
+use crate::quirks::{DeviceQuirks, QuirkTable};
+#[cfg(feature = "usb")]
 use crate::usb::fsct_device::FsctDevice;
+#[cfg(feature = "usb")]
+use crate::descriptor_dump::DeviceDescriptorDump;
+#[cfg(feature = "usb")]
 use crate::device_uuid_calculator::calculate_uuid;
+#[cfg(feature = "usb")]
+use crate::event_stamp::{EventStamper, Stamped};
+#[cfg(feature = "usb")]
+use crate::service::{spawn_service, ServiceHandle};
+#[cfg(feature = "usb")]
+use crate::device_history::{format_bcd_version, DeviceAttachRecord, DeviceHistory, SupportedText};
+#[cfg(feature = "usb")]
+use crate::usb::fsct_bos_finder::FSCT_CAPABILITY_DESCRIPTOR_VERSION;
+#[cfg(feature = "usb")]
+use crate::usb::dfu::{DFU_DETACH_TIMEOUT, DFU_RESTART_TIMEOUT};
+#[cfg(feature = "network")]
+use crate::network::NetworkDeviceInfo;
+#[cfg(feature = "simulator")]
+use crate::simulator::{DescriptorProfile, SimulatedDevice};
 
 /// Unique identifier for managed devices
 pub type ManagedDeviceId = Uuid;
 
 /// Device event types that can be broadcast by the DeviceManager
-#[derive(Debug, Clone)]
+///
+/// Serialized as `{"type": "added" | "removed", "device_id": "<uuid>"}`; device errors additionally carry `error`,
+/// warm-up steps `step`, firmware updates `updating`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "DeviceEventRepr", from = "DeviceEventRepr")]
 pub enum DeviceEvent {
     /// A device was added with the given managed ID
     Added(ManagedDeviceId),
     /// A device was removed with the given managed ID
     Removed(ManagedDeviceId),
+    /// The firmware of the device reported an error
+    DeviceError { device_id: ManagedDeviceId, error: DeviceErrorReport },
+    /// A step of bringing up an attached device completed; emitted before the device is added
+    WarmupStep { device_id: ManagedDeviceId, step: WarmupStep },
+    /// The USB device watch stopped unexpectedly; it is re-established with a re-enumeration of devices
+    WatchInterrupted,
+    /// The device sent a playback command, to be carried out by the player it shows
+    DeviceCommand { device_id: ManagedDeviceId, command: PlaybackCommand },
+    /// The volume was changed on the device, e.g. with its knob, to be set on the player it shows
+    VolumeChanged { device_id: ManagedDeviceId, volume: VolumeInfo },
+    /// A firmware update of the device started or ended; the device detaching and attaching meanwhile is part of it
+    FirmwareUpdate { device_id: ManagedDeviceId, updating: bool },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum DeviceEventRepr {
+    Added { device_id: ManagedDeviceId },
+    Removed { device_id: ManagedDeviceId },
+    DeviceError { device_id: ManagedDeviceId, error: DeviceErrorReport },
+    WarmupStep { device_id: ManagedDeviceId, step: WarmupStep },
+    WatchInterrupted,
+    DeviceCommand { device_id: ManagedDeviceId, command: PlaybackCommand },
+    VolumeChanged { device_id: ManagedDeviceId, volume: VolumeInfo },
+    FirmwareUpdate { device_id: ManagedDeviceId, updating: bool },
+}
+
+impl From<DeviceEvent> for DeviceEventRepr {
+    fn from(event: DeviceEvent) -> Self {
+        match event {
+            DeviceEvent::Added(device_id) => Self::Added { device_id },
+            DeviceEvent::Removed(device_id) => Self::Removed { device_id },
+            DeviceEvent::DeviceError { device_id, error } => Self::DeviceError { device_id, error },
+            DeviceEvent::WarmupStep { device_id, step } => Self::WarmupStep { device_id, step },
+            DeviceEvent::WatchInterrupted => Self::WatchInterrupted,
+            DeviceEvent::DeviceCommand { device_id, command } => Self::DeviceCommand { device_id, command },
+            DeviceEvent::VolumeChanged { device_id, volume } => Self::VolumeChanged { device_id, volume },
+            DeviceEvent::FirmwareUpdate { device_id, updating } => Self::FirmwareUpdate { device_id, updating },
+        }
+    }
+}
+
+impl From<DeviceEventRepr> for DeviceEvent {
+    fn from(event: DeviceEventRepr) -> Self {
+        match event {
+            DeviceEventRepr::Added { device_id } => Self::Added(device_id),
+            DeviceEventRepr::Removed { device_id } => Self::Removed(device_id),
+            DeviceEventRepr::DeviceError { device_id, error } => Self::DeviceError { device_id, error },
+            DeviceEventRepr::WarmupStep { device_id, step } => Self::WarmupStep { device_id, step },
+            DeviceEventRepr::WatchInterrupted => Self::WatchInterrupted,
+            DeviceEventRepr::DeviceCommand { device_id, command } => Self::DeviceCommand { device_id, command },
+            DeviceEventRepr::VolumeChanged { device_id, volume } => Self::VolumeChanged { device_id, volume },
+            DeviceEventRepr::FirmwareUpdate { device_id, updating } => Self::FirmwareUpdate { device_id, updating },
+        }
+    }
 }
 
 /// Error type for device manager operations
@@ -48,15 +148,25 @@ pub enum DeviceManagerError {
     DeviceNotFound(ManagedDeviceId),
     
     /// An error occurred in the underlying FSCT device
+    #[cfg(feature = "usb")]
     #[error("FSCT device error: {0}")]
     FsctDeviceError(#[from] FsctDeviceError),
 }
 
 /// Trait for device management operations
+#[cfg(feature = "usb")]
 pub trait DeviceManagement {
+    /// Bring up a freshly attached device before it is added, emitting [`DeviceEvent::WarmupStep`] per completed step
+    fn warm_up_device(&self, device: &FsctDevice, device_info: &DeviceInfo) -> impl std::future::Future<Output = Result<(), FsctDeviceError>> + Send;
+
     /// Add a device to the manager and return its managed ID
     fn add_device(&self, device: Arc<FsctDevice>, device_info: &DeviceInfo) -> ManagedDeviceId;
     
+    /// Whether the device is to be driven at all; devices not accepted are left alone
+    fn accepts_device(&self, _device_info: &DeviceInfo) -> bool {
+        true
+    }
+
     /// Remove a device from the manager by its USB device ID
     fn remove_device_by_usb_id(&self, device_id: DeviceId) -> Option<Arc<FsctDevice>>;
 
@@ -69,6 +179,11 @@ pub trait DeviceManagement {
     /// Get all devices managed ID
     fn get_all_managed_ids(&self) -> Vec<ManagedDeviceId>;
 
+    /// Get the USB device IDs of all managed devices
+    fn get_all_usb_ids(&self) -> Vec<DeviceId>;
+
+    /// Report that the device watch was interrupted, emitting [`DeviceEvent::WatchInterrupted`]
+    fn report_watch_interrupted(&self);
 }
 
 /// Trait for device control operations
@@ -88,11 +203,33 @@ pub trait DeviceControl {
     /// Set status for a device
     fn set_status(&self, managed_id: ManagedDeviceId, status: FsctStatus) -> impl std::future::Future<Output =Result<(), DeviceManagerError>> + Send + Sync;
 
+    /// Set the volume of the shown player; devices not showing volume ignore the request
+    fn set_volume(&self, managed_id: ManagedDeviceId, volume: Option<VolumeInfo>) -> impl std::future::Future<Output = Result<(), DeviceManagerError>> + Send + Sync;
+
+    /// Set the shuffle and repeat modes of the shown player; devices not showing them ignore the request
+    fn set_playback_modes(&self, managed_id: ManagedDeviceId, modes: PlaybackModes) -> impl std::future::Future<Output = Result<(), DeviceManagerError>> + Send + Sync;
+
+    /// Request a brief attention signal; devices not supporting it ignore the request
+    fn notify(&self, managed_id: ManagedDeviceId, notification: FsctNotification) -> impl std::future::Future<Output = Result<(), DeviceManagerError>> + Send + Sync;
+
+    /// Send a raw vendor request, returning the response data (empty for requests without response)
+    #[cfg(feature = "vendor-requests")]
+    fn send_vendor_request(&self, managed_id: ManagedDeviceId, request: VendorRequest) -> impl std::future::Future<Output = Result<Vec<u8>, DeviceManagerError>> + Send + Sync;
+
+    /// Translation of statuses the device doesn't understand, as its quirks require
+    fn status_map(&self, _managed_id: ManagedDeviceId) -> Option<StatusMap> {
+        None
+    }
+
+    /// Reinitialize a device whose state is unknown, e.g. after it was re-attached
+    fn reinitialize(&self, managed_id: ManagedDeviceId) -> impl std::future::Future<Output = Result<(), DeviceManagerError>> + Send + Sync;
+
     /// Subscribe to device events
     fn subscribe(&self) -> broadcast::Receiver<DeviceEvent>;
 }
 
 /// Device manager that handles device ID management and provides a unified API for device operations
+#[cfg(feature = "usb")]
 pub struct DeviceManager {
     /// Map of managed device IDs to FSCT devices
     devices: Arc<Mutex<HashMap<ManagedDeviceId, Arc<FsctDevice>>>>,
@@ -102,19 +239,478 @@ pub struct DeviceManager {
     
     /// Broadcast sender for device events
     event_sender: broadcast::Sender<DeviceEvent>,
+
+    /// Broadcast sender for device events with origin stamps
+    stamped_sender: broadcast::Sender<Stamped<DeviceEvent>>,
+    stamper: EventStamper,
+
+    /// Records of device attaches, for support diagnostics
+    history: DeviceHistory,
+
+    /// USB control transfer timeouts applied to attached devices
+    request_timeouts: Mutex<UsbRequestTimeouts>,
+
+    /// Quirks of device models, applied when devices are attached
+    quirks: Mutex<QuirkTable>,
+
+    /// Device models to drive
+    device_filter: Mutex<DeviceFilter>,
+
+    /// How often firmware error reports are read, see [`run_device_error_watch`]
+    error_poll_interval: Mutex<Duration>,
+
+    /// Devices a firmware update is in progress for, left alone by error polling, command reading and refreshes
+    updating: Mutex<HashSet<ManagedDeviceId>>,
+
+    /// Tells whether writes to a device are suspended by do-not-disturb, for writers bypassing the orchestrator
+    suspension_check: Mutex<Option<SuspensionCheck>>,
+
+    /// Vendor and product IDs of the attached network devices, for the device filter
+    #[cfg(feature = "network")]
+    network_devices: Mutex<HashMap<ManagedDeviceId, (u16, u16)>>,
 }
 
+#[cfg(feature = "usb")]
 impl DeviceManager {
     /// Create a new device manager
     pub fn new() -> Self {
         // Create a broadcast channel with a capacity of 100 events
         let (event_sender, _) = broadcast::channel(100);
+        let (stamped_sender, _) = broadcast::channel(100);
         
         Self {
             devices: Arc::new(Mutex::new(HashMap::new())),
             usb_id_to_managed_id: Arc::new(Mutex::new(HashMap::new())),
             event_sender,
+            stamped_sender,
+            stamper: EventStamper::new(),
+            history: DeviceHistory::default(),
+            request_timeouts: Mutex::new(UsbRequestTimeouts::default()),
+            quirks: Mutex::new(QuirkTable::builtin()),
+            device_filter: Mutex::new(DeviceFilter::default()),
+            error_poll_interval: Mutex::new(DEFAULT_ERROR_POLL_INTERVAL),
+            updating: Mutex::new(HashSet::new()),
+            suspension_check: Mutex::new(None),
+            #[cfg(feature = "network")]
+            network_devices: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Attach records of recently attached devices, oldest first.
+    pub fn attach_history(&self) -> Vec<DeviceAttachRecord> {
+        self.history.records()
+    }
+
+    /// The most recent attach record of the given device.
+    pub fn attach_record(&self, managed_id: ManagedDeviceId) -> Option<DeviceAttachRecord> {
+        self.history.latest(managed_id)
+    }
+
+    /// Limits of the attached device, see [`DeviceLimits`].
+    pub fn device_limits(&self, managed_id: ManagedDeviceId) -> Result<DeviceLimits, DeviceManagerError> {
+        Ok(self.get_device(managed_id)?.limits(managed_id))
+    }
+
+    /// Descriptors the attached device announced its capabilities in.
+    pub fn descriptor_dump(&self, managed_id: ManagedDeviceId) -> Result<DeviceDescriptorDump, DeviceManagerError> {
+        Ok(self.get_device(managed_id)?.descriptor_dump().clone())
+    }
+
+    /// How often the status and texts of the device are written again, as its quirks require; `None` if they are
+    /// not or the device is not attached.
+    pub fn text_refresh_interval(&self, managed_id: ManagedDeviceId) -> Option<Duration> {
+        self.get_device(managed_id).ok()?.quirks().text_refresh_interval()
+    }
+
+    /// Limits of all attached devices.
+    pub fn all_device_limits(&self) -> Vec<DeviceLimits> {
+        self.devices.lock().unwrap().iter().map(|(id, device)| device.limits(*id)).collect()
+    }
+
+    pub fn request_timeouts(&self) -> UsbRequestTimeouts {
+        *self.request_timeouts.lock().unwrap()
+    }
+
+    /// Sets the USB control transfer timeouts of attached devices and of devices attached from now on.
+    pub fn set_request_timeouts(&self, timeouts: UsbRequestTimeouts) {
+        *self.request_timeouts.lock().unwrap() = timeouts;
+        for device in self.devices.lock().unwrap().values() {
+            device.set_request_timeouts(timeouts);
+        }
+    }
+
+    pub fn quirks(&self) -> QuirkTable {
+        self.quirks.lock().unwrap().clone()
+    }
+
+    /// Sets the quirks of device models, usually the built-in table with user overrides
+    /// (`QuirkTable::builtin().with_overrides(user_quirks)`); they take effect for devices attached from now on.
+    pub fn set_quirks(&self, quirks: QuirkTable) {
+        *self.quirks.lock().unwrap() = quirks;
+    }
+
+    pub fn device_filter(&self) -> DeviceFilter {
+        self.device_filter.lock().unwrap().clone()
+    }
+
+    /// Sets which device models are driven, removing attached devices the filter no longer accepts; the removed
+    /// devices are returned so they can be disabled. Present devices the filter newly accepts are picked up by
+    /// the next enumeration.
+    pub fn set_device_filter(&self, filter: DeviceFilter) -> Vec<(ManagedDeviceId, Arc<FsctDevice>)> {
+        let denied: Vec<DeviceId> = match nusb::list_devices() {
+            Ok(devices) => devices
+                .filter(|info| !filter.accepts(info.vendor_id(), info.product_id()))
+                .map(|info| info.id())
+                .collect(),
+            Err(e) => {
+                warn!("Failed to enumerate USB devices: {}", e);
+                Vec::new()
+            }
+        };
+        #[cfg(feature = "network")]
+        let denied_network: Vec<ManagedDeviceId> = self.network_devices.lock().unwrap().iter()
+            .filter(|(_, (vendor_id, product_id))| !filter.accepts(*vendor_id, *product_id))
+            .map(|(managed_id, _)| *managed_id)
+            .collect();
+        *self.device_filter.lock().unwrap() = filter;
+        let removed = denied.into_iter()
+            .filter_map(|usb_id| {
+                let managed_id = self.get_managed_id_for_usb_id(usb_id)?;
+                info!("Device {} is no longer accepted by the device filter", managed_id);
+                Some((managed_id, self.remove_device_by_usb_id(usb_id)?))
+            });
+        #[cfg(feature = "network")]
+        let removed = removed.chain(denied_network.into_iter().filter_map(|managed_id| {
+            info!("Device {} is no longer accepted by the device filter", managed_id);
+            Some((managed_id, self.detach_network_device(managed_id)?))
+        }));
+        removed.collect()
+    }
+
+    pub fn error_poll_interval(&self) -> Duration {
+        *self.error_poll_interval.lock().unwrap()
+    }
+
+    /// Sets how often firmware error reports are read; a running watch picks it up after its current wait.
+    pub fn set_error_poll_interval(&self, interval: Duration) {
+        *self.error_poll_interval.lock().unwrap() = interval;
+    }
+
+    /// Subscribe to device events stamped with origin time and sequence number.
+    pub fn subscribe_stamped(&self) -> broadcast::Receiver<Stamped<DeviceEvent>> {
+        self.stamped_sender.subscribe()
+    }
+
+    fn emit(&self, event: DeviceEvent) {
+        let _ = self.stamped_sender.send(self.stamper.stamp(event.clone()));
+        let _ = self.event_sender.send(event);
+    }
+
+    /// Reads pending firmware error reports of all devices, logging them and emitting [`DeviceEvent::DeviceError`].
+    pub async fn poll_device_errors(&self) {
+        let devices: Vec<_> = self.devices.lock().unwrap().iter().map(|(id, device)| (*id, device.clone())).collect();
+        for (device_id, device) in devices {
+            if self.is_updating(device_id) {
+                continue;
+            }
+            match device.take_error_report().await {
+                Ok(Some(error)) => {
+                    warn!("Device {} reported error {:?} (detail {}, {} times)", device_id, error.code, error.detail,
+                          error.count);
+                    self.emit(DeviceEvent::DeviceError { device_id, error });
+                }
+                Ok(None) => {}
+                Err(e) => debug!("Failed to read error report of device {}: {}", device_id, e),
+            }
+        }
+    }
+
+    /// Emits the playback commands of the device as [`DeviceEvent::DeviceCommand`], and volume changes as
+    /// [`DeviceEvent::VolumeChanged`]. Failed reads are retried with a growing delay; the reader is cancelled once
+    /// the device is removed.
+    async fn forward_device_commands(&self, device_id: ManagedDeviceId) {
+        let Ok(device) = self.get_device(device_id) else { return };
+        if !device.sends_commands() || self.is_updating(device_id) {
+            return;
+        }
+        let mut delay = INITIAL_COMMAND_RETRY_DELAY;
+        loop {
+            match device.read_command().await {
+                Ok(PlaybackCommand::SetVolume { volume }) => {
+                    debug!("Volume of device {} changed to {:?}", device_id, volume);
+                    self.emit(DeviceEvent::VolumeChanged { device_id, volume });
+                    delay = INITIAL_COMMAND_RETRY_DELAY;
+                }
+                Ok(command) => {
+                    debug!("Device {} sent command {:?}", device_id, command);
+                    self.emit(DeviceEvent::DeviceCommand { device_id, command });
+                    delay = INITIAL_COMMAND_RETRY_DELAY;
+                }
+                Err(e) => {
+                    debug!("Failed to read commands of device {}: {}, retrying in {:?}", device_id, e, delay);
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(MAX_COMMAND_RETRY_DELAY);
+                }
+            }
+        }
+    }
+
+    /// Measures the write latency of the device, see [`FsctDevice::measure_latency`].
+    pub async fn measure_latency(&self, managed_id: ManagedDeviceId) -> Result<Duration, DeviceManagerError> {
+        let device = self.get_device(managed_id)?;
+        device.measure_latency().await.map_err(DeviceManagerError::from)
+    }
+
+    /// Sends audio levels to the device if it takes them, see [`audio_levels`](crate::audio_levels).
+    #[cfg(feature = "audio-levels")]
+    pub async fn send_audio_levels(&self, managed_id: ManagedDeviceId, levels: crate::audio_levels::AudioLevels)
+                                   -> Result<(), DeviceManagerError> {
+        let device = self.get_device(managed_id)?;
+        if self.is_updating(managed_id) {
+            return Ok(());
+        }
+        device.send_audio_levels(levels).await.map_err(DeviceManagerError::from)
+    }
+
+    /// Sends band energies to the device if it shows a spectrum, see [`spectrum`](crate::spectrum).
+    #[cfg(feature = "spectrum")]
+    pub async fn send_spectrum(&self, managed_id: ManagedDeviceId, spectrum: &crate::spectrum::Spectrum)
+                               -> Result<(), DeviceManagerError> {
+        let device = self.get_device(managed_id)?;
+        if self.is_updating(managed_id) {
+            return Ok(());
+        }
+        device.send_spectrum(spectrum).await.map_err(DeviceManagerError::from)
+    }
+
+    /// Version of the firmware the device runs, read with the `firmwareVersion` request.
+    pub async fn firmware_version(&self, managed_id: ManagedDeviceId) -> Result<u16, DeviceManagerError> {
+        let device = self.get_device(managed_id)?;
+        device.firmware_version().await.map_err(DeviceManagerError::from)
+    }
+
+    /// Streams the firmware image to the device and waits for it to restart, returning the firmware version it runs
+    /// afterwards: the device has to detach within [`DFU_DETACH_TIMEOUT`], attach again within
+    /// [`DFU_RESTART_TIMEOUT`] and report another version than before; see [`dfu`](crate::usb::dfu).
+    ///
+    /// Meanwhile the device is [updating](DeviceManager::is_updating), announced with
+    /// [`DeviceEvent::FirmwareUpdate`].
+    pub async fn update_firmware(&self, managed_id: ManagedDeviceId, image: &[u8],
+                                 progress: &mut (dyn FnMut(DfuProgress) + Send)) -> Result<u16, DeviceManagerError> {
+        let device = self.get_device(managed_id)?;
+        let previous_version = device.firmware_version().await?;
+        let mut events = self.subscribe();
+        let _updating = UpdatingGuard::new(self, managed_id, device.clone())
+            .ok_or(FsctDeviceError::FirmwareUpdateInProgress)?;
+        device.update_firmware(image, progress).await?;
+        drop(device);
+        info!("Firmware image sent to device {}, waiting for it to restart", managed_id);
+
+        let detached = tokio::time::timeout(DFU_DETACH_TIMEOUT, async {
+            loop {
+                match events.recv().await {
+                    Ok(DeviceEvent::Removed(id)) if id == managed_id => return true,
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return false,
+                }
+            }
+        }).await.unwrap_or(false);
+        if !detached {
+            warn!("Device {} did not detach within {:?} of the firmware update", managed_id, DFU_DETACH_TIMEOUT);
+            return Err(FsctDeviceError::FirmwareNotRestarted.into());
+        }
+        let attached = tokio::time::timeout(DFU_RESTART_TIMEOUT, async {
+            loop {
+                match events.recv().await {
+                    Ok(DeviceEvent::Added(id)) if id == managed_id => return,
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        }).await;
+        if attached.is_err() || self.get_device(managed_id).is_err() {
+            return Err(DeviceManagerError::DeviceNotFound(managed_id));
+        }
+        let version = self.firmware_version(managed_id).await?;
+        if version == previous_version {
+            return Err(FsctDeviceError::FirmwareNotUpdated(version).into());
+        }
+        Ok(version)
+    }
+
+    /// Whether a firmware update of the device is in progress, see [`DeviceManager::update_firmware`]; periodic
+    /// requests and refreshes leave such devices alone.
+    pub fn is_updating(&self, managed_id: ManagedDeviceId) -> bool {
+        self.updating.lock().unwrap().contains(&managed_id)
+    }
+
+    /// Sets how writers bypassing the orchestrator, e.g. of audio levels, tell whether do-not-disturb suspends the
+    /// device, usually [`OrchestratorControl::is_suspended`](crate::OrchestratorControl::is_suspended).
+    pub fn set_suspension_check(&self, check: impl Fn(ManagedDeviceId) -> bool + Send + Sync + 'static) {
+        *self.suspension_check.lock().unwrap() = Some(Arc::new(check));
+    }
+
+    /// Whether do-not-disturb suspends writes to the device, see [`DeviceManager::set_suspension_check`].
+    pub fn is_suspended(&self, managed_id: ManagedDeviceId) -> bool {
+        let check = self.suspension_check.lock().unwrap().clone();
+        check.is_some_and(|check| check(managed_id))
+    }
+
+    /// Brings the device up as the quirks of its model require, emitting [`DeviceEvent::WarmupStep`]s.
+    async fn warm_up_model(&self, device: &FsctDevice, managed_id: ManagedDeviceId, vendor_id: u16, product_id: u16,
+                           device_version: u16) -> Result<(), FsctDeviceError> {
+        let quirks = self.quirks.lock().unwrap().lookup(vendor_id, product_id, device_version);
+        if quirks != DeviceQuirks::default() {
+            info!("Device {} has quirks {:?}", managed_id, quirks);
         }
+        device.set_request_timeouts(self.request_timeouts());
+        device.set_quirks(quirks.clone());
+        for step in quirks.warmup_sequence() {
+            device.warm_up(step).await?;
+            debug!("Device {} warm-up step {:?} done", managed_id, step);
+            self.emit(DeviceEvent::WarmupStep { device_id: managed_id, step });
+        }
+        Ok(())
+    }
+
+    /// Records the attach and adds the device, emitting [`DeviceEvent::Added`].
+    fn insert_device(&self, device: Arc<FsctDevice>, record: DeviceAttachRecord) {
+        let managed_id = record.device_id;
+        info!("Device {} attached: firmware {}, FSCT capability {}, protocol {}, functionality {:?}, encoding {:?}, \
+              texts {:?}, serial {:?}", managed_id, record.firmware_version, record.fsct_capability_version,
+              record.fsct_protocol_version, record.functionality, record.text_encoding, record.supported_texts,
+              record.serial_number);
+        self.history.record(record);
+        device.set_request_timeouts(self.request_timeouts());
+        self.devices.lock().unwrap().insert(managed_id, device);
+        self.emit(DeviceEvent::Added(managed_id));
+    }
+
+    /// Brings up a device on the LAN and adds it like a USB device, see [`network`](crate::network). Returns
+    /// `None` if the device filter leaves the device alone or the device is already attached, e.g. over USB.
+    #[cfg(feature = "network")]
+    pub async fn attach_network_device(&self, device: Arc<FsctDevice>, device_info: &NetworkDeviceInfo)
+                                       -> Result<Option<ManagedDeviceId>, FsctDeviceError> {
+        let (vendor_id, product_id) = (device_info.vendor_id, device_info.product_id);
+        if !self.device_filter.lock().unwrap().accepts(vendor_id, product_id) {
+            debug!("Ignoring network device {:04x}:{:04x} excluded by the device filter", vendor_id, product_id);
+            return Ok(None);
+        }
+        let managed_id = device_info.managed_id();
+        if self.devices.lock().unwrap().contains_key(&managed_id) {
+            debug!("Network device {} at {} is already attached", managed_id, device_info.address);
+            return Ok(None);
+        }
+        self.warm_up_model(&device, managed_id, vendor_id, product_id, device_info.firmware_version).await?;
+        let record = DeviceAttachRecord {
+            vendor_id,
+            product_id,
+            manufacturer: device_info.manufacturer.clone(),
+            product: device_info.product.clone(),
+            serial_number: device_info.serial_number.clone(),
+            firmware_version: format_bcd_version(device_info.firmware_version),
+            usb_version: String::new(),
+            ..capability_record(managed_id, &device)
+        };
+        self.network_devices.lock().unwrap().insert(managed_id, (vendor_id, product_id));
+        self.insert_device(device, record);
+        Ok(Some(managed_id))
+    }
+
+    /// Removes a device attached with [`attach_network_device`](Self::attach_network_device), emitting
+    /// [`DeviceEvent::Removed`]; USB devices of the same ID are left alone.
+    #[cfg(feature = "network")]
+    pub fn detach_network_device(&self, managed_id: ManagedDeviceId) -> Option<Arc<FsctDevice>> {
+        self.network_devices.lock().unwrap().remove(&managed_id)?;
+        let device = self.devices.lock().unwrap().remove(&managed_id);
+        if device.is_some() {
+            self.emit(DeviceEvent::Removed(managed_id));
+        }
+        device
+    }
+
+    /// Brings up a device the OS opened on behalf of the host and adds it like a watched USB device, e.g. one
+    /// Android's `UsbManager` handed over as a file descriptor. String descriptors are taken from `strings`, as the
+    /// OS read them. Returns `None` if the device filter leaves the device alone or the device is already attached.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub async fn attach_opened_device(&self, fd: std::os::fd::OwnedFd, strings: DeviceStrings)
+                                      -> Result<Option<ManagedDeviceId>, DeviceDiscoveryError> {
+        let (device, identity) = create_fsct_device_from_fd(fd).await?;
+        let (vendor_id, product_id) = (identity.vendor_id, identity.product_id);
+        if !self.device_filter.lock().unwrap().accepts(vendor_id, product_id) {
+            debug!("Ignoring device {:04x}:{:04x} excluded by the device filter", vendor_id, product_id);
+            return Ok(None);
+        }
+        let managed_id = calculate_uuid(vendor_id, product_id, strings.serial_number.as_deref().unwrap_or(""));
+        if self.devices.lock().unwrap().contains_key(&managed_id) {
+            debug!("Device {} is already attached", managed_id);
+            return Ok(None);
+        }
+        self.warm_up_model(&device, managed_id, vendor_id, product_id, identity.device_version).await?;
+        let record = DeviceAttachRecord {
+            vendor_id,
+            product_id,
+            manufacturer: strings.manufacturer,
+            product: strings.product,
+            serial_number: strings.serial_number,
+            firmware_version: format_bcd_version(identity.device_version),
+            usb_version: format_bcd_version(identity.usb_version),
+            ..capability_record(managed_id, &device)
+        };
+        self.insert_device(Arc::new(device), record);
+        Ok(Some(managed_id))
+    }
+
+    /// Removes a device attached with [`attach_opened_device`](Self::attach_opened_device), emitting
+    /// [`DeviceEvent::Removed`].
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn detach_opened_device(&self, managed_id: ManagedDeviceId) -> Option<Arc<FsctDevice>> {
+        let device = self.devices.lock().unwrap().remove(&managed_id);
+        if device.is_some() {
+            self.emit(DeviceEvent::Removed(managed_id));
+        }
+        device
+    }
+
+    /// Adds a simulated device of the descriptor profile, brought up like a USB device of the profile's identity,
+    /// see [`simulator`](crate::simulator).
+    #[cfg(feature = "simulator")]
+    pub async fn attach_simulated_device(&self, profile: &DescriptorProfile) -> Result<SimulatedDevice, anyhow::Error> {
+        let (device, display) = profile.create_device()?;
+        let device = Arc::new(device);
+        let managed_id = Uuid::new_v4();
+        self.warm_up_model(&device, managed_id, profile.vendor_id, profile.product_id, profile.firmware_version).await?;
+        display.set_encoding(device.capabilities().text_encoding);
+        let record = DeviceAttachRecord {
+            vendor_id: profile.vendor_id,
+            product_id: profile.product_id,
+            product: Some(profile.name.clone()),
+            firmware_version: format_bcd_version(profile.firmware_version),
+            ..capability_record(managed_id, &device)
+        };
+        self.insert_device(device, record);
+        Ok(SimulatedDevice { managed_id, display })
+    }
+
+    /// Restarts a simulated device like after a firmware update, emitting [`DeviceEvent::Removed`] and
+    /// [`DeviceEvent::Added`]; returns `false` if the device is not attached.
+    #[cfg(feature = "simulator")]
+    pub fn restart_simulated_device(&self, device: &SimulatedDevice) -> bool {
+        let Some(fsct_device) = self.detach_simulated_device(device.managed_id) else { return false };
+        device.display.restart();
+        self.devices.lock().unwrap().insert(device.managed_id, fsct_device);
+        self.emit(DeviceEvent::Added(device.managed_id));
+        true
+    }
+
+    /// Removes a simulated device, emitting [`DeviceEvent::Removed`].
+    #[cfg(feature = "simulator")]
+    pub fn detach_simulated_device(&self, managed_id: ManagedDeviceId) -> Option<Arc<FsctDevice>> {
+        let device = self.devices.lock().unwrap().remove(&managed_id);
+        if device.is_some() {
+            self.emit(DeviceEvent::Removed(managed_id));
+        }
+        device
     }
 
     fn get_device(&self, managed_id: ManagedDeviceId) -> Result<Arc<FsctDevice>, DeviceManagerError> {
@@ -123,29 +719,215 @@ impl DeviceManager {
     }
 }
 
+#[cfg(feature = "usb")]
+type SuspensionCheck = Arc<dyn Fn(ManagedDeviceId) -> bool + Send + Sync>;
+
+/// Marks a device as updating for as long as it lives, see [`DeviceManager::is_updating`].
+#[cfg(feature = "usb")]
+struct UpdatingGuard<'a> {
+    device_manager: &'a DeviceManager,
+    device_id: ManagedDeviceId,
+    // not keeping the device open while it restarts
+    device: Weak<FsctDevice>,
+}
+
+#[cfg(feature = "usb")]
+impl<'a> UpdatingGuard<'a> {
+    /// `None` if an update of the device is already in progress.
+    fn new(device_manager: &'a DeviceManager, device_id: ManagedDeviceId, device: Arc<FsctDevice>) -> Option<Self> {
+        if !device_manager.updating.lock().unwrap().insert(device_id) {
+            return None;
+        }
+        device.set_updating(true);
+        device_manager.emit(DeviceEvent::FirmwareUpdate { device_id, updating: true });
+        Some(Self { device_manager, device_id, device: Arc::downgrade(&device) })
+    }
+}
+
+#[cfg(feature = "usb")]
+impl Drop for UpdatingGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(device) = self.device.upgrade() {
+            device.set_updating(false);
+        }
+        self.device_manager.updating.lock().unwrap().remove(&self.device_id);
+        self.device_manager.emit(DeviceEvent::FirmwareUpdate { device_id: self.device_id, updating: false });
+    }
+}
+
+/// How often firmware error reports are read from devices.
+#[cfg(feature = "usb")]
+pub const DEFAULT_ERROR_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[cfg(feature = "usb")]
+const INITIAL_COMMAND_RETRY_DELAY: Duration = Duration::from_millis(100);
+#[cfg(feature = "usb")]
+const MAX_COMMAND_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Periodically reads firmware error reports of all devices, see [`DeviceManager::poll_device_errors`], every
+/// `interval` until changed with [`DeviceManager::set_error_poll_interval`].
+#[cfg(feature = "usb")]
+pub fn run_device_error_watch(device_manager: Arc<DeviceManager>, interval: Duration) -> ServiceHandle {
+    device_manager.set_error_poll_interval(interval);
+    spawn_service(move |mut stop| async move {
+        loop {
+            tokio::select! {
+                _ = stop.signaled() => break,
+                _ = tokio::time::sleep(device_manager.error_poll_interval()) => device_manager.poll_device_errors().await,
+            }
+        }
+    })
+}
+
+/// Reads playback commands of all devices sending them, see [`DeviceEvent::DeviceCommand`].
+///
+/// Every device gets its own reader, started when the device is added and cancelled when it is removed, so the
+/// reader doesn't keep a detached device open; until then failed reads are retried. Readers are cancelled as well
+/// while the firmware of the device is updated.
+#[cfg(feature = "usb")]
+pub fn run_device_command_watch(device_manager: Arc<DeviceManager>) -> ServiceHandle {
+    spawn_service(move |mut stop| async move {
+        let mut events = device_manager.subscribe();
+        let mut readers = HashMap::new();
+        let start_reader = |device_id| {
+            let device_manager = device_manager.clone();
+            tokio::spawn(async move { device_manager.forward_device_commands(device_id).await })
+        };
+        for device_id in device_manager.get_all_managed_ids() {
+            readers.insert(device_id, start_reader(device_id));
+        }
+        loop {
+            let event = tokio::select! {
+                _ = stop.signaled() => break,
+                event = events.recv() => event,
+            };
+            match event {
+                Ok(DeviceEvent::Added(device_id)) => {
+                    if let Some(previous) = readers.insert(device_id, start_reader(device_id)) {
+                        previous.abort();
+                    }
+                }
+                Ok(DeviceEvent::Removed(device_id))
+                | Ok(DeviceEvent::FirmwareUpdate { device_id, updating: true }) => {
+                    if let Some(reader) = readers.remove(&device_id) {
+                        reader.abort();
+                    }
+                }
+                Ok(DeviceEvent::FirmwareUpdate { device_id, updating: false }) => {
+                    if device_manager.get_device(device_id).is_ok()
+                        && let Some(previous) = readers.insert(device_id, start_reader(device_id)) {
+                        previous.abort();
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    // added or removed devices may have been missed
+                    let device_ids = device_manager.get_all_managed_ids();
+                    readers.retain(|device_id, reader| {
+                        let keep = device_ids.contains(device_id);
+                        if !keep {
+                            reader.abort();
+                        }
+                        keep
+                    });
+                    for device_id in device_ids {
+                        readers.entry(device_id).or_insert_with(|| start_reader(device_id));
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+        for reader in readers.into_values() {
+            reader.abort();
+        }
+    })
+}
+
+/// String descriptors of a device opened by the OS, see [`DeviceManager::attach_opened_device`].
+#[cfg(feature = "usb")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceStrings {
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<String>,
+}
+
+#[cfg(feature = "usb")]
+fn managed_id_of(device_info: &DeviceInfo) -> ManagedDeviceId {
+    // Compute UUID from VID, PID, and Serial Number
+    calculate_uuid(device_info.vendor_id(), device_info.product_id(), device_info.serial_number().unwrap_or(""))
+}
+
+#[cfg(feature = "usb")]
+fn attach_record(managed_id: ManagedDeviceId, device: &FsctDevice, device_info: &DeviceInfo) -> DeviceAttachRecord {
+    DeviceAttachRecord {
+        vendor_id: device_info.vendor_id(),
+        product_id: device_info.product_id(),
+        manufacturer: device_info.manufacturer_string().map(str::to_string),
+        product: device_info.product_string().map(str::to_string),
+        serial_number: device_info.serial_number().map(str::to_string),
+        firmware_version: format_bcd_version(device_info.device_version()),
+        usb_version: format_bcd_version(device_info.usb_version()),
+        ..capability_record(managed_id, device)
+    }
+}
+
+/// Attach record with the FSCT capabilities of the device; the identity of the device is left for the transport to
+/// fill in.
+#[cfg(feature = "usb")]
+fn capability_record(managed_id: ManagedDeviceId, device: &FsctDevice) -> DeviceAttachRecord {
+    let capabilities = device.capabilities();
+    DeviceAttachRecord {
+        device_id: managed_id,
+        attached_at: std::time::SystemTime::now(),
+        vendor_id: 0,
+        product_id: 0,
+        manufacturer: None,
+        product: None,
+        serial_number: None,
+        firmware_version: String::new(),
+        usb_version: String::new(),
+        fsct_capability_version: format_bcd_version(FSCT_CAPABILITY_DESCRIPTOR_VERSION),
+        fsct_protocol_version: device.protocol().version(),
+        functionality: capabilities
+            .functionality
+            .iter_names()
+            .map(|(name, _)| name.to_string())
+            .chain(capabilities.extended_functionality.iter_names().map(|(name, _)| name.to_string()))
+            .collect(),
+        text_encoding: capabilities.text_encoding,
+        supported_texts: capabilities
+            .supported_texts
+            .into_iter()
+            .map(|(metadata, max_length)| SupportedText { metadata, max_length })
+            .collect(),
+    }
+}
+
+#[cfg(feature = "usb")]
 impl DeviceManagement for DeviceManager {
+    fn accepts_device(&self, device_info: &DeviceInfo) -> bool {
+        self.device_filter.lock().unwrap().accepts(device_info.vendor_id(), device_info.product_id())
+    }
+
+    async fn warm_up_device(&self, device: &FsctDevice, device_info: &DeviceInfo) -> Result<(), FsctDeviceError> {
+        self.warm_up_model(device, managed_id_of(device_info), device_info.vendor_id(), device_info.product_id(),
+                           device_info.device_version()).await
+    }
+
     fn add_device(&self, device: Arc<FsctDevice>, device_info: &DeviceInfo) -> ManagedDeviceId {
-        // Compute UUID from VID, PID, and Serial Number
-        let vid = device_info.vendor_id();
-        let pid = device_info.product_id();
-        let sn = device_info.serial_number().unwrap_or("");
-        let managed_id = calculate_uuid(vid, pid, sn);
-        
-        // Add to devices map
-        {
-            let mut devices = self.devices.lock().unwrap();
-            devices.insert(managed_id, device);
-        }
-        
+        let managed_id = managed_id_of(device_info);
+        let record = attach_record(managed_id, &device, device_info);
+
         // Add to USB ID mapping
         {
             let mut usb_id_map = self.usb_id_to_managed_id.lock().unwrap();
             usb_id_map.insert(device_info.id(), managed_id);
         }
-        
-        // Broadcast device added event
-        let _ = self.event_sender.send(DeviceEvent::Added(managed_id));
-        
+
+        // Add to devices map and broadcast device added event
+        self.insert_device(device, record);
+
         managed_id
     }
     
@@ -170,7 +952,7 @@ impl DeviceManagement for DeviceManager {
         
         // Broadcast device removed event if a device was actually removed
         if device.is_some() {
-            let _ = self.event_sender.send(DeviceEvent::Removed(managed_id));
+            self.emit(DeviceEvent::Removed(managed_id));
         }
         
         device
@@ -194,9 +976,22 @@ impl DeviceManagement for DeviceManager {
         let devices = self.devices.lock().unwrap();
         devices.keys().copied().collect()
     }
+
+    fn get_all_usb_ids(&self) -> Vec<DeviceId> {
+        self.usb_id_to_managed_id.lock().unwrap().keys().copied().collect()
+    }
+
+    fn report_watch_interrupted(&self) {
+        self.emit(DeviceEvent::WatchInterrupted);
+    }
 }
 
+#[cfg(feature = "usb")]
 impl DeviceControl for DeviceManager {
+    fn status_map(&self, managed_id: ManagedDeviceId) -> Option<StatusMap> {
+        self.get_device(managed_id).ok()?.quirks().status_map
+    }
+
     async fn set_enable(&self, managed_id: ManagedDeviceId, enable: bool) -> Result<(), DeviceManagerError> {
         let device = self.get_device(managed_id)?;
         device.set_enable(enable).await.map_err(DeviceManagerError::from)
@@ -222,12 +1017,48 @@ impl DeviceControl for DeviceManager {
         device.set_status(status).await.map_err(DeviceManagerError::from)
     }
 
+    async fn set_volume(&self, managed_id: ManagedDeviceId, volume: Option<VolumeInfo>) -> Result<(), DeviceManagerError> {
+        let device = self.get_device(managed_id)?;
+        device.set_volume(volume).await.map_err(DeviceManagerError::from)
+    }
+
+    async fn set_playback_modes(&self, managed_id: ManagedDeviceId, modes: PlaybackModes) -> Result<(), DeviceManagerError> {
+        let device = self.get_device(managed_id)?;
+        device.set_playback_modes(modes).await.map_err(DeviceManagerError::from)
+    }
+
+    async fn notify(&self, managed_id: ManagedDeviceId, notification: FsctNotification) -> Result<(), DeviceManagerError> {
+        let device = self.get_device(managed_id)?;
+        device.notify(notification).await.map_err(DeviceManagerError::from)
+    }
+
+    async fn reinitialize(&self, managed_id: ManagedDeviceId) -> Result<(), DeviceManagerError> {
+        let device = self.get_device(managed_id)?;
+        info!("Reinitializing device {}", managed_id);
+        device.reinitialize().await.map_err(DeviceManagerError::from)
+    }
+
+    #[cfg(feature = "vendor-requests")]
+    async fn send_vendor_request(&self, managed_id: ManagedDeviceId, request: VendorRequest) -> Result<Vec<u8>, DeviceManagerError> {
+        let device = self.get_device(managed_id)?;
+        info!("Vendor request {:#04x} (value {:#06x}, {} bytes out, {} bytes in) to device {}",
+              request.request, request.value, request.payload.len(), request.response_length, managed_id);
+        let result = device.send_vendor_request(&request).await;
+        match &result {
+            Ok(response) => info!("Vendor request {:#04x} to device {} succeeded, {} bytes received",
+                                  request.request, managed_id, response.len()),
+            Err(e) => warn!("Vendor request {:#04x} to device {} failed: {}", request.request, managed_id, e),
+        }
+        result.map_err(DeviceManagerError::from)
+    }
+
 
     fn subscribe(&self) -> broadcast::Receiver<DeviceEvent> {
         self.event_sender.subscribe()
     }
 }
 
+#[cfg(feature = "usb")]
 impl Default for DeviceManager {
     fn default() -> Self {
         Self::new()
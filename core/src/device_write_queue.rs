@@ -0,0 +1,633 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Ordered per-device write queue in front of a [`DeviceControl`].
+//!
+//! Writes return as soon as they are queued and a background task per device performs them in order, so callers
+//! don't wait for every control transfer when several fields change at once. A write supersedes a pending write
+//! of the same field; a text write also cancels the in-flight transfer of the same text.
+//!
+//! Writes are coalesced: an idle device queue waits for the coalesce window before writing, so bursts of updates
+//! (e.g. timelines several times per second) end up as one transfer per field, and status, progress and text writes
+//! equal to the last successful write of the field are skipped.
+//!
+//! Writes of device state that fail, e.g. while the link of a network device is down, stay in an outbox holding
+//! the latest value of each field. The outbox is replayed when the device is reinitialized after reconnecting, or
+//! by the transport calling [`DeviceWriteQueue::replay`]; values older than the outbox expiry are dropped as stale.
+//! Since writes return before they are performed, failures are reported to the handler set with
+//! [`DeviceWriteQueue::on_write_failed`], e.g. so the applier no longer assumes the device shows the value.
+//! The queue of a device is dropped when the device is removed.
+//!
+//! Devices losing their texts on internal resets without telling the host get the shown status and texts written
+//! again periodically, see [`run_text_refresh`] and [`DeviceQuirks::text_refresh`](crate::quirks::DeviceQuirks).
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use log::{debug, warn};
+use tokio::sync::{broadcast, oneshot, Notify};
+use tokio::time::Instant;
+
+use crate::definitions::{FsctNotification, FsctStatus, FsctTextMetadata, PlaybackModes, TimelineInfo, VolumeInfo};
+#[cfg(feature = "vendor-requests")]
+use crate::definitions::VendorRequest;
+use crate::device_manager::{DeviceControl, DeviceEvent, DeviceManagerError, ManagedDeviceId};
+#[cfg(feature = "usb")]
+use crate::device_manager::{DeviceManagement, DeviceManager};
+use crate::quirks::StatusMap;
+#[cfg(feature = "usb")]
+use crate::service::{spawn_service, ServiceHandle};
+
+/// How long an idle device queue collects writes before performing them, by default.
+pub const DEFAULT_COALESCE_WINDOW: Duration = Duration::from_millis(50);
+
+/// How long failed writes are kept for replay, by default.
+pub const DEFAULT_OUTBOX_EXPIRY: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq)]
+enum DeviceWrite {
+    Enable(bool),
+    Status(FsctStatus),
+    Progress(Option<TimelineInfo>),
+    Text(FsctTextMetadata, Option<String>),
+    Volume(Option<VolumeInfo>),
+    Modes(PlaybackModes),
+    Notify(FsctNotification),
+}
+
+impl DeviceWrite {
+    /// Whether `self` makes the pending write `other` obsolete.
+    fn supersedes(&self, other: &DeviceWrite) -> bool {
+        match (self, other) {
+            (DeviceWrite::Enable(_), DeviceWrite::Enable(_)) => true,
+            (DeviceWrite::Status(_), DeviceWrite::Status(_)) => true,
+            (DeviceWrite::Progress(_), DeviceWrite::Progress(_)) => true,
+            (DeviceWrite::Volume(_), DeviceWrite::Volume(_)) => true,
+            (DeviceWrite::Modes(_), DeviceWrite::Modes(_)) => true,
+            (DeviceWrite::Text(id, _), DeviceWrite::Text(other_id, _)) => id == other_id,
+            _ => false,
+        }
+    }
+
+    /// Whether the write sets device state that stays until overwritten, so repeating it changes nothing.
+    fn is_idempotent(&self) -> bool {
+        matches!(self, DeviceWrite::Status(_) | DeviceWrite::Progress(_) | DeviceWrite::Text(..)
+            | DeviceWrite::Volume(_) | DeviceWrite::Modes(_))
+    }
+
+    /// Whether a failed write is kept in the outbox for replay; notifications are not worth showing late.
+    fn is_retained(&self) -> bool {
+        !matches!(self, DeviceWrite::Notify(_))
+    }
+}
+
+#[derive(Default)]
+struct DeviceQueue {
+    pending: VecDeque<DeviceWrite>,
+    in_flight: Option<(DeviceWrite, oneshot::Sender<()>)>,
+    running: bool,
+    // The device was removed while a write was in flight
+    removed: bool,
+    // Last successful idempotent write of each field
+    written: Vec<DeviceWrite>,
+    // Latest failed write of each field and when it failed
+    outbox: Vec<(DeviceWrite, Instant)>,
+}
+
+struct Shared<T> {
+    device_control: Arc<T>,
+    queues: Mutex<HashMap<ManagedDeviceId, DeviceQueue>>,
+    idle: Notify,
+    coalesce_window: Mutex<Duration>,
+    outbox_expiry: Mutex<Duration>,
+    failure_handler: Mutex<Option<Arc<FailureHandler>>>,
+}
+
+type FailureHandler = dyn Fn(ManagedDeviceId) + Send + Sync;
+
+/// DeviceControl wrapper queueing writes per device; reads, vendor requests and reinitialization pass through.
+pub struct DeviceWriteQueue<T: DeviceControl + Send + Sync + 'static> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T: DeviceControl + Send + Sync + 'static> DeviceWriteQueue<T> {
+    /// Creates the queue; must be called within a Tokio runtime, as it watches for removed devices.
+    pub fn new(device_control: Arc<T>) -> Self {
+        let device_rx = device_control.subscribe();
+        let shared = Arc::new(Shared {
+            device_control,
+            queues: Mutex::new(HashMap::new()),
+            idle: Notify::new(),
+            coalesce_window: Mutex::new(DEFAULT_COALESCE_WINDOW),
+            outbox_expiry: Mutex::new(DEFAULT_OUTBOX_EXPIRY),
+            failure_handler: Mutex::new(None),
+        });
+        tokio::spawn(drop_removed_devices(Arc::downgrade(&shared), device_rx));
+        Self { shared }
+    }
+
+    /// Sets the handler called with the device whenever a queued write to it fails.
+    pub fn on_write_failed(&self, handler: impl Fn(ManagedDeviceId) + Send + Sync + 'static) {
+        *self.shared.failure_handler.lock().unwrap() = Some(Arc::new(handler));
+    }
+
+    /// Sets how long an idle device queue collects writes before performing them; zero writes right away.
+    pub fn with_coalesce_window(self, window: Duration) -> Self {
+        self.set_coalesce_window(window);
+        self
+    }
+
+    /// Changes the coalesce window, see [`DeviceWriteQueue::with_coalesce_window`].
+    pub fn set_coalesce_window(&self, window: Duration) {
+        *self.shared.coalesce_window.lock().unwrap() = window;
+    }
+
+    /// Sets how long failed writes are kept for replay; zero keeps none.
+    pub fn with_outbox_expiry(self, expiry: Duration) -> Self {
+        self.set_outbox_expiry(expiry);
+        self
+    }
+
+    /// Changes the outbox expiry, see [`DeviceWriteQueue::with_outbox_expiry`].
+    pub fn set_outbox_expiry(&self, expiry: Duration) {
+        *self.shared.outbox_expiry.lock().unwrap() = expiry;
+    }
+
+    /// Queues the writes in the outbox of the device again, e.g. when its transport has reconnected. Values older
+    /// than the outbox expiry are dropped, and so are values of fields with a newer write queued.
+    pub fn replay(&self, device_id: ManagedDeviceId) {
+        let expiry = *self.shared.outbox_expiry.lock().unwrap();
+        let outbox = match self.shared.queues.lock().unwrap().get_mut(&device_id) {
+            Some(queue) => {
+                let outbox = std::mem::take(&mut queue.outbox);
+                outbox.into_iter()
+                    .filter(|(write, _)| !queue.pending.iter().any(|pending| pending.supersedes(write)))
+                    .collect::<Vec<_>>()
+            }
+            None => return,
+        };
+        for (write, failed_at) in outbox {
+            if failed_at.elapsed() <= expiry {
+                debug!("Replaying {:?} to device {}", write, device_id);
+                self.push(device_id, write);
+            }
+        }
+    }
+
+    /// Writes the status and texts the device shows again, although unchanged, for devices losing them without
+    /// telling the host. Cleared texts and fields with a newer write queued are left alone.
+    pub fn refresh(&self, device_id: ManagedDeviceId) {
+        let writes = match self.shared.queues.lock().unwrap().get_mut(&device_id) {
+            Some(queue) => {
+                let (refreshed, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut queue.written)
+                    .into_iter()
+                    .partition(|write| matches!(write, DeviceWrite::Status(_) | DeviceWrite::Text(_, Some(_))));
+                queue.written = kept;
+                refreshed.into_iter()
+                    .filter(|write| !queue.pending.iter().any(|pending| pending.supersedes(write)))
+                    .collect::<Vec<_>>()
+            }
+            None => return,
+        };
+        for write in writes {
+            self.push(device_id, write);
+        }
+    }
+
+    /// Waits until all queued writes have been performed.
+    pub async fn wait_idle(&self) {
+        loop {
+            let idle = self.shared.idle.notified();
+            tokio::pin!(idle);
+            idle.as_mut().enable();
+            if self.shared.queues.lock().unwrap().values().all(|queue| !queue.running) {
+                return;
+            }
+            idle.await;
+        }
+    }
+
+    fn push(&self, device_id: ManagedDeviceId, write: DeviceWrite) {
+        let mut queues = self.shared.queues.lock().unwrap();
+        let queue = queues.entry(device_id).or_default();
+        queue.pending.retain(|pending| !write.supersedes(pending));
+        if matches!(write, DeviceWrite::Text(..))
+            && queue.in_flight.as_ref().is_some_and(|(in_flight, _)| write.supersedes(in_flight))
+            && let Some((_, cancel)) = queue.in_flight.take() {
+            let _ = cancel.send(());
+        }
+        queue.pending.push_back(write);
+        queue.removed = false;
+        if !queue.running {
+            queue.running = true;
+            tokio::spawn(run_queue(self.shared.clone(), device_id));
+        }
+    }
+}
+
+async fn run_queue<T: DeviceControl + Send + Sync + 'static>(shared: Arc<Shared<T>>, device_id: ManagedDeviceId) {
+    // collect the writes of a burst, superseding each other while they wait
+    let window = *shared.coalesce_window.lock().unwrap();
+    if !window.is_zero() {
+        tokio::time::sleep(window).await;
+    }
+    loop {
+        let (write, cancelled) = {
+            let mut queues = shared.queues.lock().unwrap();
+            let queue = queues.entry(device_id).or_default();
+            let Some(write) = queue.pending.pop_front() else {
+                if queue.removed {
+                    queues.remove(&device_id);
+                } else {
+                    queue.running = false;
+                    queue.in_flight = None;
+                }
+                shared.idle.notify_waiters();
+                return;
+            };
+            if queue.written.contains(&write) {
+                continue;
+            }
+            let (cancel_tx, cancel_rx) = oneshot::channel();
+            queue.in_flight = Some((write.clone(), cancel_tx));
+            (write, cancel_rx)
+        };
+        let (written, failed) = tokio::select! {
+            result = perform(shared.device_control.as_ref(), device_id, write.clone()) => {
+                let written = result.inspect_err(|e| warn!("Failed to write to device {}: {}", device_id, e)).is_ok();
+                (written, !written)
+            }
+            Ok(()) = cancelled => {
+                debug!("Superseded text write to device {} cancelled", device_id);
+                (false, false)
+            }
+        };
+        if failed {
+            let handler = shared.failure_handler.lock().unwrap().clone();
+            if let Some(handler) = handler {
+                handler(device_id);
+            }
+        }
+        let mut queues = shared.queues.lock().unwrap();
+        let queue = queues.entry(device_id).or_default();
+        if write.is_idempotent() {
+            // a failed or cancelled write leaves the field unknown
+            queue.written.retain(|previous| !write.supersedes(previous));
+            if written {
+                queue.written.push(write.clone());
+            }
+        }
+        if write.is_retained() {
+            queue.outbox.retain(|(previous, _)| !write.supersedes(previous));
+            if failed && !shared.outbox_expiry.lock().unwrap().is_zero() {
+                queue.outbox.push((write, Instant::now()));
+            }
+        }
+    }
+}
+
+/// Drops the queue of each removed device while the [`DeviceWriteQueue`] exists; a write in flight ends first.
+async fn drop_removed_devices<T: DeviceControl + Send + Sync + 'static>(
+    shared: Weak<Shared<T>>, mut device_rx: broadcast::Receiver<DeviceEvent>) {
+    loop {
+        let device_id = match device_rx.recv().await {
+            Ok(DeviceEvent::Removed(device_id)) => device_id,
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+        let Some(shared) = shared.upgrade() else { return };
+        let mut queues = shared.queues.lock().unwrap();
+        if let Some(queue) = queues.get_mut(&device_id) {
+            if queue.running {
+                // the running task drops the entry once the write in flight has ended
+                queue.pending.clear();
+                queue.removed = true;
+            } else {
+                queues.remove(&device_id);
+            }
+        }
+    }
+}
+
+/// How often [`run_text_refresh`] checks whether devices are due for a refresh.
+#[cfg(feature = "usb")]
+const TEXT_REFRESH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Refreshes the status and texts of devices whose quirks set a
+/// [`text_refresh`](crate::quirks::DeviceQuirks::text_refresh) interval, see [`DeviceWriteQueue::refresh`]. Devices
+/// suspended by do-not-disturb are left alone; they are resynced in full when resumed.
+#[cfg(feature = "usb")]
+pub fn run_text_refresh(queue: Arc<DeviceWriteQueue<DeviceManager>>) -> ServiceHandle {
+    spawn_service(move |mut stop| async move {
+        let mut last_refresh: HashMap<ManagedDeviceId, Instant> = HashMap::new();
+        loop {
+            tokio::select! {
+                _ = stop.signaled() => break,
+                _ = tokio::time::sleep(TEXT_REFRESH_CHECK_INTERVAL) => {}
+            }
+            let device_manager = &queue.shared.device_control;
+            let devices = device_manager.get_all_managed_ids();
+            last_refresh.retain(|device_id, _| devices.contains(device_id));
+            for device_id in devices {
+                let Some(interval) = device_manager.text_refresh_interval(device_id) else { continue };
+                if device_manager.is_updating(device_id) || device_manager.is_suspended(device_id) {
+                    continue;
+                }
+                let last = *last_refresh.entry(device_id).or_insert_with(Instant::now);
+                if last.elapsed() >= interval {
+                    debug!("Refreshing the texts of device {}", device_id);
+                    queue.refresh(device_id);
+                    last_refresh.insert(device_id, Instant::now());
+                }
+            }
+        }
+    })
+}
+
+async fn perform<T: DeviceControl>(device_control: &T, device_id: ManagedDeviceId, write: DeviceWrite)
+                                   -> Result<(), DeviceManagerError> {
+    match write {
+        DeviceWrite::Enable(enable) => device_control.set_enable(device_id, enable).await,
+        DeviceWrite::Status(status) => device_control.set_status(device_id, status).await,
+        DeviceWrite::Progress(progress) => device_control.set_progress(device_id, progress).await,
+        DeviceWrite::Text(text_id, text) => device_control.set_current_text(device_id, text_id, text.as_deref()).await,
+        DeviceWrite::Volume(volume) => device_control.set_volume(device_id, volume).await,
+        DeviceWrite::Modes(modes) => device_control.set_playback_modes(device_id, modes).await,
+        DeviceWrite::Notify(notification) => device_control.notify(device_id, notification).await,
+    }
+}
+
+impl<T: DeviceControl + Send + Sync + 'static> DeviceControl for DeviceWriteQueue<T> {
+    fn status_map(&self, managed_id: ManagedDeviceId) -> Option<StatusMap> {
+        self.shared.device_control.status_map(managed_id)
+    }
+
+    async fn set_enable(&self, managed_id: ManagedDeviceId, enable: bool) -> Result<(), DeviceManagerError> {
+        self.push(managed_id, DeviceWrite::Enable(enable));
+        Ok(())
+    }
+
+    async fn get_enable(&self, managed_id: ManagedDeviceId) -> Result<bool, DeviceManagerError> {
+        self.shared.device_control.get_enable(managed_id).await
+    }
+
+    async fn set_progress(&self, managed_id: ManagedDeviceId, progress: Option<TimelineInfo>) -> Result<(), DeviceManagerError> {
+        self.push(managed_id, DeviceWrite::Progress(progress));
+        Ok(())
+    }
+
+    async fn set_current_text(&self, managed_id: ManagedDeviceId, text_id: FsctTextMetadata, text: Option<&str>) -> Result<(), DeviceManagerError> {
+        self.push(managed_id, DeviceWrite::Text(text_id, text.map(str::to_string)));
+        Ok(())
+    }
+
+    async fn set_status(&self, managed_id: ManagedDeviceId, status: FsctStatus) -> Result<(), DeviceManagerError> {
+        self.push(managed_id, DeviceWrite::Status(status));
+        Ok(())
+    }
+
+    async fn set_volume(&self, managed_id: ManagedDeviceId, volume: Option<VolumeInfo>) -> Result<(), DeviceManagerError> {
+        self.push(managed_id, DeviceWrite::Volume(volume));
+        Ok(())
+    }
+
+    async fn set_playback_modes(&self, managed_id: ManagedDeviceId, modes: PlaybackModes) -> Result<(), DeviceManagerError> {
+        self.push(managed_id, DeviceWrite::Modes(modes));
+        Ok(())
+    }
+
+    async fn notify(&self, managed_id: ManagedDeviceId, notification: FsctNotification) -> Result<(), DeviceManagerError> {
+        self.push(managed_id, DeviceWrite::Notify(notification));
+        Ok(())
+    }
+
+    #[cfg(feature = "vendor-requests")]
+    async fn send_vendor_request(&self, managed_id: ManagedDeviceId, request: VendorRequest) -> Result<Vec<u8>, DeviceManagerError> {
+        self.shared.device_control.send_vendor_request(managed_id, request).await
+    }
+
+    /// Drops writes still queued for the device, as they were meant for its state before reinitialization, and
+    /// replays the outbox once the device is reinitialized.
+    async fn reinitialize(&self, managed_id: ManagedDeviceId) -> Result<(), DeviceManagerError> {
+        if let Some(queue) = self.shared.queues.lock().unwrap().get_mut(&managed_id) {
+            queue.pending.clear();
+            queue.written.clear();
+        }
+        self.shared.device_control.reinitialize(managed_id).await?;
+        self.replay(managed_id);
+        Ok(())
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<DeviceEvent> {
+        self.shared.device_control.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    /// DeviceControl taking 10ms per write and recording completed text, progress and status writes; status writes
+    /// fail while `offline` is set.
+    struct SlowDevice {
+        texts: Mutex<Vec<Option<String>>>,
+        progress_writes: Mutex<usize>,
+        statuses: Mutex<Vec<FsctStatus>>,
+        offline: Mutex<bool>,
+        events: broadcast::Sender<DeviceEvent>,
+    }
+
+    impl Default for SlowDevice {
+        fn default() -> Self {
+            Self {
+                texts: Mutex::default(),
+                progress_writes: Mutex::default(),
+                statuses: Mutex::default(),
+                offline: Mutex::default(),
+                events: broadcast::channel(8).0,
+            }
+        }
+    }
+
+    impl DeviceControl for SlowDevice {
+        async fn set_enable(&self, _: ManagedDeviceId, _: bool) -> Result<(), DeviceManagerError> { Ok(()) }
+        async fn get_enable(&self, _: ManagedDeviceId) -> Result<bool, DeviceManagerError> { Ok(true) }
+        async fn set_progress(&self, _: ManagedDeviceId, _: Option<TimelineInfo>) -> Result<(), DeviceManagerError> {
+            *self.progress_writes.lock().unwrap() += 1;
+            Ok(())
+        }
+        async fn set_current_text(&self, _: ManagedDeviceId, _: FsctTextMetadata, text: Option<&str>)
+                                  -> Result<(), DeviceManagerError> {
+            let text = text.map(str::to_string);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            self.texts.lock().unwrap().push(text);
+            Ok(())
+        }
+        async fn set_status(&self, device_id: ManagedDeviceId, status: FsctStatus) -> Result<(), DeviceManagerError> {
+            if *self.offline.lock().unwrap() {
+                return Err(DeviceManagerError::DeviceNotFound(device_id));
+            }
+            self.statuses.lock().unwrap().push(status);
+            Ok(())
+        }
+        async fn set_volume(&self, _: ManagedDeviceId, _: Option<VolumeInfo>) -> Result<(), DeviceManagerError> { Ok(()) }
+        async fn set_playback_modes(&self, _: ManagedDeviceId, _: PlaybackModes) -> Result<(), DeviceManagerError> {
+            Ok(())
+        }
+        async fn notify(&self, _: ManagedDeviceId, _: FsctNotification) -> Result<(), DeviceManagerError> { Ok(()) }
+        #[cfg(feature = "vendor-requests")]
+        async fn send_vendor_request(&self, _: ManagedDeviceId, _: VendorRequest) -> Result<Vec<u8>, DeviceManagerError> {
+            Ok(Vec::new())
+        }
+        async fn reinitialize(&self, _: ManagedDeviceId) -> Result<(), DeviceManagerError> { Ok(()) }
+        fn subscribe(&self) -> broadcast::Receiver<DeviceEvent> { self.events.subscribe() }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn superseded_text_writes_are_dropped() {
+        let device = Arc::new(SlowDevice::default());
+        let queue = DeviceWriteQueue::new(device.clone()).with_coalesce_window(Duration::ZERO);
+        let device_id = Uuid::new_v4();
+
+        queue.set_current_text(device_id, FsctTextMetadata::CurrentTitle, Some("a")).await.unwrap();
+        // let the write of "a" start, so it gets cancelled in flight
+        tokio::task::yield_now().await;
+        queue.set_current_text(device_id, FsctTextMetadata::CurrentTitle, Some("b")).await.unwrap();
+        queue.set_current_text(device_id, FsctTextMetadata::CurrentTitle, Some("c")).await.unwrap();
+        queue.wait_idle().await;
+
+        assert_eq!(*device.texts.lock().unwrap(), vec![Some("c".to_string())]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn bursts_are_coalesced_and_repeated_writes_skipped() {
+        let device = Arc::new(SlowDevice::default());
+        let queue = DeviceWriteQueue::new(device.clone());
+        let device_id = Uuid::new_v4();
+
+        for _ in 0..5 {
+            queue.set_progress(device_id, None).await.unwrap();
+            queue.set_current_text(device_id, FsctTextMetadata::CurrentTitle, Some("a")).await.unwrap();
+        }
+        queue.wait_idle().await;
+        assert_eq!(*device.progress_writes.lock().unwrap(), 1);
+        assert_eq!(*device.texts.lock().unwrap(), vec![Some("a".to_string())]);
+
+        // equal to what the device already shows
+        queue.set_progress(device_id, None).await.unwrap();
+        queue.set_current_text(device_id, FsctTextMetadata::CurrentTitle, Some("a")).await.unwrap();
+        queue.wait_idle().await;
+        assert_eq!(*device.progress_writes.lock().unwrap(), 1);
+        assert_eq!(device.texts.lock().unwrap().len(), 1);
+
+        // unless the device was reinitialized in between
+        queue.reinitialize(device_id).await.unwrap();
+        queue.set_progress(device_id, None).await.unwrap();
+        queue.wait_idle().await;
+        assert_eq!(*device.progress_writes.lock().unwrap(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failed_writes_are_replayed_until_they_expire() {
+        let device = Arc::new(SlowDevice::default());
+        let queue = DeviceWriteQueue::new(device.clone()).with_outbox_expiry(Duration::from_secs(30));
+        let device_id = Uuid::new_v4();
+
+        *device.offline.lock().unwrap() = true;
+        queue.set_status(device_id, FsctStatus::Paused).await.unwrap();
+        queue.wait_idle().await;
+        queue.set_status(device_id, FsctStatus::Playing).await.unwrap();
+        queue.wait_idle().await;
+        assert!(device.statuses.lock().unwrap().is_empty());
+
+        // the link is back, only the latest value is replayed
+        *device.offline.lock().unwrap() = false;
+        queue.reinitialize(device_id).await.unwrap();
+        queue.wait_idle().await;
+        assert_eq!(*device.statuses.lock().unwrap(), vec![FsctStatus::Playing]);
+
+        *device.offline.lock().unwrap() = true;
+        queue.set_status(device_id, FsctStatus::Stopped).await.unwrap();
+        queue.wait_idle().await;
+        *device.offline.lock().unwrap() = false;
+        tokio::time::advance(Duration::from_secs(31)).await;
+        queue.replay(device_id);
+        queue.wait_idle().await;
+        assert_eq!(*device.statuses.lock().unwrap(), vec![FsctStatus::Playing]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refresh_writes_shown_status_and_texts_again() {
+        let device = Arc::new(SlowDevice::default());
+        let queue = DeviceWriteQueue::new(device.clone());
+        let device_id = Uuid::new_v4();
+
+        queue.set_status(device_id, FsctStatus::Playing).await.unwrap();
+        queue.set_current_text(device_id, FsctTextMetadata::CurrentTitle, Some("a")).await.unwrap();
+        queue.set_current_text(device_id, FsctTextMetadata::CurrentAuthor, None).await.unwrap();
+        queue.set_progress(device_id, None).await.unwrap();
+        queue.wait_idle().await;
+
+        queue.refresh(device_id);
+        queue.wait_idle().await;
+        assert_eq!(*device.statuses.lock().unwrap(), vec![FsctStatus::Playing, FsctStatus::Playing]);
+        assert_eq!(*device.texts.lock().unwrap(), vec![Some("a".to_string()), None, Some("a".to_string())]);
+        assert_eq!(*device.progress_writes.lock().unwrap(), 1);
+
+        // refreshed writes are remembered as shown again
+        queue.set_current_text(device_id, FsctTextMetadata::CurrentTitle, Some("a")).await.unwrap();
+        queue.wait_idle().await;
+        assert_eq!(device.texts.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failed_writes_are_reported() {
+        let device = Arc::new(SlowDevice::default());
+        let queue = DeviceWriteQueue::new(device.clone());
+        let failed = Arc::new(Mutex::new(Vec::new()));
+        let reported = failed.clone();
+        queue.on_write_failed(move |device_id| reported.lock().unwrap().push(device_id));
+        let device_id = Uuid::new_v4();
+
+        queue.set_status(device_id, FsctStatus::Playing).await.unwrap();
+        queue.wait_idle().await;
+        assert!(failed.lock().unwrap().is_empty());
+
+        *device.offline.lock().unwrap() = true;
+        queue.set_status(device_id, FsctStatus::Paused).await.unwrap();
+        queue.wait_idle().await;
+        assert_eq!(*failed.lock().unwrap(), vec![device_id]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn queues_of_removed_devices_are_dropped() {
+        let device = Arc::new(SlowDevice::default());
+        let queue = DeviceWriteQueue::new(device.clone());
+        let device_id = Uuid::new_v4();
+
+        queue.set_status(device_id, FsctStatus::Playing).await.unwrap();
+        queue.wait_idle().await;
+        assert!(queue.shared.queues.lock().unwrap().contains_key(&device_id));
+
+        device.events.send(DeviceEvent::Removed(device_id)).unwrap();
+        tokio::task::yield_now().await;
+        assert!(queue.shared.queues.lock().unwrap().is_empty());
+    }
+}
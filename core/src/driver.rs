@@ -16,21 +16,74 @@
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
 use std::sync::Arc;
-
+#[cfg(feature = "usb")]
+use std::sync::Mutex;
+#[cfg(feature = "usb")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "usb")]
+use std::time::Duration;
+
+#[cfg(feature = "usb")]
+use anyhow::anyhow;
 use anyhow::Error;
+#[cfg(feature = "usb")]
+use log::{info, warn};
 use async_trait::async_trait;
 use tokio::sync::broadcast;
-use crate::definitions::{FsctStatus, FsctTextMetadata, TimelineInfo};
-use crate::device_manager::{DeviceManager, ManagedDeviceId};
-use crate::player_events::PlayerEvent;
-use crate::player_manager::{ManagedPlayerId, PlayerManager};
+use crate::definitions::{DeviceLimits, FsctStatus, FsctTextMetadata, PlaybackModes, TimelineInfo, VolumeInfo};
+use crate::device_history::DeviceAttachRecord;
+#[cfg(feature = "usb")]
+use crate::definitions::{DfuProgress, FirmwareUpdateReport};
+#[cfg(feature = "usb")]
+use crate::device_history::format_bcd_version;
+use crate::device_manager::{DeviceEvent, ManagedDeviceId};
+#[cfg(feature = "usb")]
+use crate::device_manager::DeviceControl;
+#[cfg(feature = "usb")]
+use crate::device_manager::{run_device_command_watch, run_device_error_watch, DeviceManagement, DeviceManager};
+#[cfg(feature = "usb")]
+use crate::device_filter::DeviceFilter;
+#[cfg(feature = "usb")]
+use crate::usb_device_watch::enumerate_devices;
+use crate::player_events::{PlayerEvent, PlayerEventFilter};
+use crate::player_interface::PlayerInterface;
+use crate::player_manager::ManagedPlayerId;
+#[cfg(feature = "usb")]
+use crate::player_origin::PlayerOrigin;
+#[cfg(feature = "usb")]
+use crate::player_manager::PlayerManager;
 use crate::player_state::PlayerState;
-use crate::service::MultiServiceHandle;
-use crate::orchestrator::Orchestrator;
+#[cfg(feature = "usb")]
+use crate::service::{MultiServiceHandle, ServiceHandle};
+#[cfg(feature = "usb")]
+use crate::port_supervisor::{PortError, PortSupervisor};
+#[cfg(feature = "usb")]
+use crate::readiness::Readiness;
+#[cfg(feature = "usb")]
+use crate::orchestrator::{ApplyAckHandle, DndScope, Orchestrator, OrchestratorControl};
+#[cfg(feature = "usb")]
 use crate::usb_device_watch::run_usb_device_watch;
+#[cfg(feature = "usb")]
+use crate::player_state_applier::DirectDeviceControlApplier;
+#[cfg(feature = "usb")]
+use crate::device_write_queue::{run_text_refresh, DeviceWriteQueue};
+#[cfg(feature = "usb")]
+use crate::text_template::TextLayout;
+#[cfg(feature = "usb")]
+use crate::time_format::TimeFormat;
+#[cfg(feature = "usb")]
+use crate::quirks::StatusMap;
+#[cfg(feature = "usb")]
+use crate::aux_content::AuxContentRegistry;
+#[cfg(feature = "usb")]
+use crate::usage_stats::UsageStats;
+#[cfg(feature = "usb")]
+use crate::announcements::{run_announcer, Announcer};
+#[cfg(feature = "usb")]
+use crate::power::PowerMonitor;
 
 /// Abstraction over FSCT host driver functionality that can be backed by a local
-/// in-process implementation or a future IPC-based implementation.
+/// in-process implementation or, with the `remote` feature, by a `RemoteDriver` over gRPC.
 #[async_trait]
 pub trait FsctDriver: Send + Sync {
     // --- Player management ---
@@ -46,6 +99,12 @@ pub trait FsctDriver: Send + Sync {
 
     async fn update_player_timeline(&self, player_id: ManagedPlayerId, new_timeline: Option<TimelineInfo>) -> Result<(), Error>;
 
+    /// Volume of the player, shown by devices announcing the volume functionality; `None` if it isn't known.
+    async fn update_player_volume(&self, player_id: ManagedPlayerId, new_volume: Option<VolumeInfo>) -> Result<(), Error>;
+
+    /// Shuffle and repeat modes of the player, shown by devices announcing the playback modes functionality.
+    async fn update_player_modes(&self, player_id: ManagedPlayerId, new_modes: PlaybackModes) -> Result<(), Error>;
+
     async fn update_player_metadata(&self, player_id: ManagedPlayerId, metadata_id: FsctTextMetadata, new_text: Option<String>) -> Result<(), Error>;
 
     fn set_preferred_player(&self, preferred: Option<ManagedPlayerId>) -> Result<(), Error>;
@@ -53,21 +112,80 @@ pub trait FsctDriver: Send + Sync {
 
     fn get_player_assigned_device(&self, player_id: ManagedPlayerId) -> Result<Option<ManagedDeviceId>, Error>;
 
+    /// Attaches the interface through which playback commands of devices showing the player are carried out, or
+    /// detaches it with `None`.
+    fn set_player_interface(&self, player_id: ManagedPlayerId, interface: Option<Arc<dyn PlayerInterface>>)
+                            -> Result<(), Error>;
+
+    /// Limits of the connected devices, so producers can shorten texts before they get cut on the device.
+    fn get_device_limits(&self) -> Vec<DeviceLimits>;
+
+    // --- Device inventory ---
+    /// Connected devices in the order they were attached, with product, USB ids, protocol version, functionalities
+    /// and text limits as read from their descriptors.
+    async fn list_devices(&self) -> Result<Vec<DeviceAttachRecord>, Error>;
+
+    /// Subscribes to events of the devices: attached, removed, firmware errors and playback commands.
+    fn subscribe_device_events(&self) -> broadcast::Receiver<DeviceEvent>;
+
     // Events (player-facing only)
     fn subscribe_player_events(&self) -> broadcast::Receiver<PlayerEvent>;
+
+    /// Subscribes to the player events matching `filter` only, e.g. state updates of selected players.
+    fn subscribe_filtered(&self, filter: PlayerEventFilter) -> broadcast::Receiver<PlayerEvent>;
+
+    /// Waits until all changes made so far have been applied to the connected devices.
+    async fn wait_applied(&self) -> Result<(), Error>;
 }
 
 /// Local, in-process implementation of FsctDriver.
 /// Wraps the existing PlayerManager and DeviceManager and forwards all calls.
+#[cfg(feature = "usb")]
 pub struct LocalDriver {
     player_manager: Arc<PlayerManager>,
     device_manager: Arc<DeviceManager>,
+    ack_handle: Mutex<Option<ApplyAckHandle>>,
+    control: Mutex<Option<OrchestratorControl>>,
+    applier: Mutex<Option<Arc<DirectDeviceControlApplier<DeviceWriteQueue<DeviceManager>>>>>,
+    usage_stats: Option<Arc<UsageStats>>,
+    announcer: Option<Arc<Announcer>>,
+    power_monitor: Option<Arc<PowerMonitor>>,
+    aux_content: Option<Arc<AuxContentRegistry>>,
+    sticky_source: Option<Duration>,
+    write_coalescing: Option<Duration>,
+    outbox_expiry: Option<Duration>,
+    usb_device_watch: bool,
+    pause_on_disconnect: AtomicBool,
+    time_format: Mutex<TimeFormat>,
+    ports: PortSupervisor,
+    usb_watch_running: AtomicBool,
+    ipc_listening: Mutex<Option<bool>>,
 }
 
+#[cfg(feature = "usb")]
 impl LocalDriver {
     /// Create a LocalDriver from existing managers.
     pub fn new(player_manager: Arc<PlayerManager>, device_manager: Arc<DeviceManager>) -> Self {
-        Self { player_manager, device_manager }
+        Self {
+            player_manager,
+            device_manager,
+            ack_handle: Mutex::new(None),
+            control: Mutex::new(None),
+            applier: Mutex::new(None),
+            usage_stats: None,
+            announcer: None,
+            power_monitor: None,
+            aux_content: None,
+            sticky_source: None,
+            write_coalescing: None,
+            outbox_expiry: None,
+            usb_device_watch: true,
+            pause_on_disconnect: AtomicBool::new(false),
+            time_format: Mutex::new(TimeFormat::default()),
+            ports: PortSupervisor::new(),
+            usb_watch_running: AtomicBool::new(false),
+            ipc_listening: Mutex::new(None),
+        }
     }
 
     /// Create a LocalDriver with freshly created managers.
@@ -75,30 +193,280 @@ impl LocalDriver {
         Self::new(Arc::new(PlayerManager::new()), Arc::new(DeviceManager::new()))
     }
 
+    /// Feeds what is shown on devices to the usage statistics once the driver runs.
+    pub fn with_usage_stats(mut self, stats: Arc<UsageStats>) -> Self {
+        self.usage_stats = Some(stats);
+        self
+    }
+
+    /// Usage statistics fed by the driver, if it was created with them.
+    pub fn usage_stats(&self) -> Option<Arc<UsageStats>> {
+        self.usage_stats.clone()
+    }
+
+    /// Announcer fed by the driver, if it was created with one.
+    pub fn announcer(&self) -> Option<Arc<Announcer>> {
+        self.announcer.clone()
+    }
+
+    /// Speaks announcements of what is shown on devices once the driver runs.
+    pub fn with_announcer(mut self, announcer: Arc<Announcer>) -> Self {
+        self.announcer = Some(announcer);
+        self
+    }
+
+    /// Reports the power mode of the monitor in the [`Readiness`]; the monitor throttles updates of the players it
+    /// intercepts on its own.
+    pub fn with_power_monitor(mut self, monitor: Arc<PowerMonitor>) -> Self {
+        self.power_monitor = Some(monitor);
+        self
+    }
+
+    /// Power monitor of the service, if the driver was created with one.
+    pub fn power_monitor(&self) -> Option<Arc<PowerMonitor>> {
+        self.power_monitor.clone()
+    }
+
+    /// Fetches the auxiliary content idle devices rotate through once the driver runs, see
+    /// [`OrchestratorControl::set_aux_rotation`].
+    pub fn with_aux_content(mut self, registry: Arc<AuxContentRegistry>) -> Self {
+        self.aux_content = Some(registry);
+        self
+    }
+
+    /// Keeps paused players shown on devices for `window` before switching to another playing player, see
+    /// [`Orchestrator::with_sticky_source`]; changed while running with [`OrchestratorControl::set_sticky_source`].
+    pub fn with_sticky_source(mut self, window: Duration) -> Self {
+        self.sticky_source = Some(window);
+        self
+    }
+
+    /// Sets how long writes to an idle device are collected before they are sent, so bursts of updates are sent
+    /// once; zero sends them right away. Defaults to
+    /// [`DEFAULT_COALESCE_WINDOW`](crate::device_write_queue::DEFAULT_COALESCE_WINDOW).
+    pub fn with_write_coalescing(mut self, window: Duration) -> Self {
+        self.write_coalescing = Some(window);
+        self
+    }
+
+    /// Sets how long writes that failed to reach a device are kept for replay once it reconnects; zero drops them.
+    /// Defaults to [`DEFAULT_OUTBOX_EXPIRY`](crate::device_write_queue::DEFAULT_OUTBOX_EXPIRY).
+    pub fn with_outbox_expiry(mut self, expiry: Duration) -> Self {
+        self.outbox_expiry = Some(expiry);
+        self
+    }
+
+    /// Sets whether [`LocalDriver::run`] watches USB for FSCT devices, e.g. off for hosts embedded in apps whose
+    /// devices are driven by the system service. Defaults to on.
+    pub fn with_usb_device_watch(mut self, enabled: bool) -> Self {
+        self.usb_device_watch = enabled;
+        self
+    }
+
     /// Access the underlying managers if needed by advanced callers.
     pub fn player_manager(&self) -> Arc<PlayerManager> { self.player_manager.clone() }
     pub fn device_manager(&self) -> Arc<DeviceManager> { self.device_manager.clone() }
 
-    /// Run orchestrator and USB device watch services and return a combined handle.
+    /// Registers a player tagged with the host and user it was registered from, see
+    /// [`player_origin`](crate::player_origin).
+    pub async fn register_player_with_origin(&self, self_id: String, origin: PlayerOrigin)
+                                             -> Result<ManagedPlayerId, Error> {
+        self.player_manager.register_player_with_origin(self_id, origin).await
+    }
+
+    /// Control of route overrides and do-not-disturb; available once the driver runs.
+    pub fn control(&self) -> Result<OrchestratorControl, Error> {
+        self.control.lock().unwrap().clone().ok_or_else(|| anyhow!("Driver is not running"))
+    }
+
+    /// Sets how the text lines of the device are composed from player texts; `None` shows player texts as they are.
+    pub async fn set_text_layout(&self, device_id: ManagedDeviceId, layout: Option<TextLayout>) -> Result<(), Error> {
+        let applier = self.applier.lock().unwrap().clone().ok_or_else(|| anyhow!("Driver is not running"))?;
+        applier.set_text_layout(device_id, layout).await
+    }
+
+    /// Sets whether playing players assigned to a device are paused when it disconnects, see
+    /// [`Orchestrator::with_pause_on_disconnect`]; applies once the driver runs if it doesn't yet.
+    pub async fn set_pause_on_disconnect(&self, enabled: bool) -> Result<(), Error> {
+        self.pause_on_disconnect.store(enabled, Ordering::Relaxed);
+        let control = self.control.lock().unwrap().clone();
+        match control {
+            Some(control) => control.set_pause_on_disconnect(enabled).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Sets the format of clocks on devices, see [`Orchestrator::with_time_format`]; applies once the driver runs if
+    /// it doesn't yet.
+    pub async fn set_time_format(&self, format: TimeFormat) -> Result<(), Error> {
+        *self.time_format.lock().unwrap() = format;
+        let control = self.control.lock().unwrap().clone();
+        match control {
+            Some(control) => control.set_time_format(format).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Sets how statuses are translated for the device, overriding its quirks; `None` falls back to the quirks.
+    pub fn set_status_map(&self, device_id: ManagedDeviceId, status_map: Option<StatusMap>) -> Result<(), Error> {
+        let applier = self.applier.lock().unwrap().clone().ok_or_else(|| anyhow!("Driver is not running"))?;
+        applier.set_status_map(device_id, status_map);
+        Ok(())
+    }
+
+    /// Sets which device models are driven. Attached devices the filter no longer accepts are disabled and removed;
+    /// while the driver runs, present devices it newly accepts are attached.
+    pub async fn set_device_filter(&self, filter: DeviceFilter) {
+        for (device_id, device) in self.device_manager.set_device_filter(filter) {
+            if let Err(e) = device.set_enable(false).await {
+                warn!("Failed to disable device {}: {}", device_id, e);
+            }
+        }
+        if self.control.lock().unwrap().is_some() {
+            enumerate_devices(&*self.device_manager).await;
+        }
+    }
+
+    /// Runs a player port, e.g. the OS watcher of the native service, under `name` so that it can be restarted with
+    /// [`LocalDriver::restart_port`]; see [`PortSupervisor::run_port`].
+    pub async fn run_port<F, Fut>(&self, name: impl Into<String>, start: F) -> Result<ServiceHandle, PortError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<ServiceHandle, Error>> + Send + 'static,
+    {
+        self.ports.run_port(name, start).await
+    }
+
+    /// Stops the port run under `name` and starts it anew, e.g. a watcher that stopped getting updates after the OS
+    /// media service restarted, without stopping the rest of the driver.
+    pub async fn restart_port(&self, name: &str) -> Result<(), PortError> {
+        self.ports.restart_port(name).await
+    }
+
+    /// Names of the ports run with [`LocalDriver::run_port`].
+    pub fn list_ports(&self) -> Vec<String> {
+        self.ports.list()
+    }
+
+    /// Sends a raw vendor request to the device; see [`VendorRequest`](crate::definitions::VendorRequest).
+    #[cfg(feature = "vendor-requests")]
+    pub async fn send_vendor_request(&self, device_id: ManagedDeviceId, request: crate::definitions::VendorRequest)
+                                     -> Result<Vec<u8>, Error> {
+        Ok(self.device_manager.send_vendor_request(device_id, request).await?)
+    }
+
+    /// Updates the firmware of the device with `image`, see [`dfu`](crate::usb::dfu), reporting progress while the
+    /// image is streamed. Writes to the device are suspended meanwhile, as with do-not-disturb; afterwards the device
+    /// is asked which firmware it runs.
+    pub async fn update_firmware(&self, device_id: ManagedDeviceId, image: &[u8],
+                                 mut progress: impl FnMut(DfuProgress) + Send) -> Result<FirmwareUpdateReport, Error> {
+        let previous_version = self.device_manager.firmware_version(device_id).await?;
+        let control = self.control()?;
+        let was_suspended = control.dnd().await?.devices.contains(&device_id);
+        control.set_dnd(DndScope::Device(device_id), true).await?;
+        let version = self.device_manager.update_firmware(device_id, image, &mut progress).await;
+        if !was_suspended {
+            control.set_dnd(DndScope::Device(device_id), false).await?;
+        }
+        let version = version?;
+        info!("Firmware of device {} updated from {} to {}", device_id, format_bcd_version(previous_version),
+              format_bcd_version(version));
+        Ok(FirmwareUpdateReport { device_id, previous_version, version })
+    }
+
+    /// Records whether the IPC socket of the service listens, for its [`Readiness`].
+    pub fn set_ipc_listening(&self, listening: bool) {
+        *self.ipc_listening.lock().unwrap() = Some(listening);
+    }
+
+    /// Whether the service running the driver is functional, see [`readiness`](crate::readiness).
+    pub fn readiness(&self) -> Readiness {
+        Readiness::new(self.usb_watch_running.load(Ordering::Relaxed), self.list_ports(),
+                       *self.ipc_listening.lock().unwrap())
+            .with_power_mode(self.power_monitor.as_ref().map(|monitor| monitor.mode()))
+    }
+
+    /// Run orchestrator and USB device watch services (with the `network` feature also the network device watch) and
+    /// return a combined handle.
     pub async fn run(&self) -> Result<MultiServiceHandle, Error> {
         // Subscribe to player events from the PlayerManager
-        let player_rx = self.player_manager.subscribe();
+        let player_rx = self.player_manager.subscribe_queued();
 
         // Build and run the orchestrator using the DeviceManager
-        let orchestrator = Orchestrator::with_device_manager(player_rx, self.device_manager.clone());
+        let mut orchestrator = Orchestrator::with_device_manager(player_rx, self.device_manager.clone())
+            .with_player_interfaces(self.player_manager.player_interfaces())
+            .with_player_origins(self.player_manager.player_origins());
+        if let Some(stats) = &self.usage_stats {
+            orchestrator = orchestrator.with_usage_stats(stats.clone());
+        }
+        if let Some(announcer) = &self.announcer {
+            orchestrator = orchestrator.with_display_observer(announcer.clone());
+        }
+        if let Some(registry) = &self.aux_content {
+            orchestrator = orchestrator.with_aux_content(registry.clone());
+        }
+        if let Some(window) = self.sticky_source {
+            orchestrator = orchestrator.with_sticky_source(window);
+        }
+        orchestrator = orchestrator.with_pause_on_disconnect(self.pause_on_disconnect.load(Ordering::Relaxed))
+                                   .with_time_format(*self.time_format.lock().unwrap());
+        if let Some(window) = self.write_coalescing {
+            orchestrator.applier().device_control().set_coalesce_window(window);
+        }
+        if let Some(expiry) = self.outbox_expiry {
+            orchestrator.applier().device_control().set_outbox_expiry(expiry);
+        }
+        *self.ack_handle.lock().unwrap() = Some(orchestrator.ack_handle());
+        let control = orchestrator.control();
+        self.device_manager.set_suspension_check(move |device_id| control.is_suspended(device_id));
+        *self.control.lock().unwrap() = Some(orchestrator.control());
+        *self.applier.lock().unwrap() = Some(orchestrator.applier());
+        let text_refresh_handle = run_text_refresh(orchestrator.applier().device_control());
         let orch_handle = orchestrator.run();
 
         // Start USB device watch
-        let usb_handle = run_usb_device_watch(self.device_manager.clone()).await?;
-
-        // Combine both service handles into a MultiServiceHandle
-        let mut multi = MultiServiceHandle::with_capacity(2);
+        let usb_handle = if self.usb_device_watch {
+            let handle = run_usb_device_watch(self.device_manager.clone()).await?;
+            self.usb_watch_running.store(true, Ordering::Relaxed);
+            Some(handle)
+        } else {
+            None
+        };
+
+        // Read firmware error reports of attached devices
+        let error_watch_handle = run_device_error_watch(self.device_manager.clone(),
+                                                        self.device_manager.error_poll_interval());
+
+        // Read playback commands of attached devices
+        let command_watch_handle = run_device_command_watch(self.device_manager.clone());
+
+        // Combine all service handles into a MultiServiceHandle
+        let mut multi = MultiServiceHandle::with_capacity(6);
         multi.add(orch_handle);
-        multi.add(usb_handle);
+        multi.add(text_refresh_handle);
+        if let Some(usb_handle) = usb_handle {
+            multi.add(usb_handle);
+        }
+        multi.add(error_watch_handle);
+        multi.add(command_watch_handle);
+        if let Some(announcer) = &self.announcer {
+            multi.add(run_announcer(announcer.clone()));
+        }
+        if let Some(registry) = &self.aux_content {
+            multi.add(registry.clone().run());
+        }
+
+        // Attach FSCT devices announced on the LAN next to the USB ones
+        #[cfg(feature = "network")]
+        match crate::network::run_network_device_watch(self.device_manager.clone()) {
+            Ok(network_handle) => multi.add(network_handle),
+            Err(e) => warn!("Failed to browse for network devices: {}", e),
+        }
         Ok(multi)
     }
 }
 
+#[cfg(feature = "usb")]
 #[async_trait]
 impl FsctDriver for LocalDriver {
     async fn register_player(&self, self_id: String) -> Result<ManagedPlayerId, Error> {
@@ -130,6 +498,14 @@ impl FsctDriver for LocalDriver {
         self.player_manager.update_player_timeline(player_id, new_timeline).await
     }
 
+    async fn update_player_volume(&self, player_id: ManagedPlayerId, new_volume: Option<VolumeInfo>) -> Result<(), Error> {
+        self.player_manager.update_player_volume(player_id, new_volume).await
+    }
+
+    async fn update_player_modes(&self, player_id: ManagedPlayerId, new_modes: PlaybackModes) -> Result<(), Error> {
+        self.player_manager.update_player_modes(player_id, new_modes).await
+    }
+
     async fn update_player_metadata(&self, player_id: ManagedPlayerId, metadata_id: FsctTextMetadata, new_text: Option<String>) -> Result<(), Error> {
         self.player_manager.update_player_metadata(player_id, metadata_id, new_text).await
     }
@@ -146,10 +522,46 @@ impl FsctDriver for LocalDriver {
         self.player_manager.get_player_assigned_devices(player_id)
     }
 
+    fn set_player_interface(&self, player_id: ManagedPlayerId, interface: Option<Arc<dyn PlayerInterface>>)
+                            -> Result<(), Error> {
+        self.player_manager.set_player_interface(player_id, interface)
+    }
+
+    fn get_device_limits(&self) -> Vec<DeviceLimits> {
+        self.device_manager.all_device_limits()
+    }
+
+    async fn list_devices(&self) -> Result<Vec<DeviceAttachRecord>, Error> {
+        let mut devices: Vec<_> = self.device_manager.get_all_managed_ids().into_iter()
+            .filter_map(|device_id| self.device_manager.attach_record(device_id))
+            .collect();
+        devices.sort_by_key(|record| record.attached_at);
+        Ok(devices)
+    }
+
+    fn subscribe_device_events(&self) -> broadcast::Receiver<DeviceEvent> {
+        self.device_manager.subscribe()
+    }
+
     fn subscribe_player_events(&self) -> broadcast::Receiver<PlayerEvent> {
         self.player_manager.subscribe()
     }
 
+    fn subscribe_filtered(&self, filter: PlayerEventFilter) -> broadcast::Receiver<PlayerEvent> {
+        self.player_manager.subscribe_filtered(filter)
+    }
+
+    async fn wait_applied(&self) -> Result<(), Error> {
+        let ack_handle = self.ack_handle.lock().unwrap().clone();
+        ack_handle.ok_or_else(|| anyhow!("Driver is not running"))?.wait_applied().await?;
+        // routing decisions are queued by then, wait until the devices got them
+        let applier = self.applier.lock().unwrap().clone();
+        if let Some(applier) = applier {
+            applier.device_control().wait_idle().await;
+        }
+        Ok(())
+    }
+
 
 
 }
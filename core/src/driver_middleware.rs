@@ -0,0 +1,243 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+use std::sync::Arc;
+
+use anyhow::Error;
+use async_trait::async_trait;
+use tokio::sync::broadcast;
+
+use crate::definitions::{DeviceLimits, FsctStatus, FsctTextMetadata, PlaybackModes, TimelineInfo, VolumeInfo};
+use crate::device_history::DeviceAttachRecord;
+use crate::device_manager::{DeviceEvent, ManagedDeviceId};
+use crate::driver::FsctDriver;
+use crate::player_events::{PlayerEvent, PlayerEventFilter};
+use crate::player_interface::PlayerInterface;
+use crate::player_manager::ManagedPlayerId;
+use crate::player_state::PlayerState;
+
+/// A player state update on its way from a player to the PlayerManager.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateUpdate {
+    State(PlayerState),
+    Status(FsctStatus),
+    Timeline(Option<TimelineInfo>),
+    Volume(Option<VolumeInfo>),
+    Modes(PlaybackModes),
+    Text(FsctTextMetadata, Option<String>),
+}
+
+/// Pluggable hook observing or rewriting player state updates before they reach the PlayerManager.
+///
+/// Interceptors are used for cross-cutting features like text normalization, scripting, scrobbling or metrics.
+#[async_trait]
+pub trait DriverInterceptor: Send + Sync {
+    /// Returns the update to pass on (possibly rewritten, even into another kind of update), or `None` to drop it.
+    async fn intercept(&self, player_id: ManagedPlayerId, update: StateUpdate) -> Option<StateUpdate>;
+
+    /// Called after a player has been registered.
+    async fn on_player_registered(&self, _player_id: ManagedPlayerId, _self_id: &str) {}
+
+    /// Called after a player has been unregistered.
+    async fn on_player_unregistered(&self, _player_id: ManagedPlayerId) {}
+}
+
+/// FsctDriver wrapper that passes state updates through a chain of interceptors, in the order they were added.
+pub struct InterceptedDriver {
+    inner: Arc<dyn FsctDriver>,
+    interceptors: Vec<Arc<dyn DriverInterceptor>>,
+}
+
+impl InterceptedDriver {
+    pub fn new(inner: Arc<dyn FsctDriver>) -> Self {
+        Self { inner, interceptors: Vec::new() }
+    }
+
+    /// Appends an interceptor to the end of the chain.
+    pub fn with_interceptor(mut self, interceptor: Arc<dyn DriverInterceptor>) -> Self {
+        self.interceptors.push(interceptor);
+        self
+    }
+
+    async fn forward(&self, player_id: ManagedPlayerId, update: StateUpdate) -> Result<(), Error> {
+        let mut update = update;
+        for interceptor in &self.interceptors {
+            match interceptor.intercept(player_id, update).await {
+                Some(rewritten) => update = rewritten,
+                None => return Ok(()),
+            }
+        }
+        match update {
+            StateUpdate::State(state) => self.inner.update_player_state(player_id, state).await,
+            StateUpdate::Status(status) => self.inner.update_player_status(player_id, status).await,
+            StateUpdate::Timeline(timeline) => self.inner.update_player_timeline(player_id, timeline).await,
+            StateUpdate::Volume(volume) => self.inner.update_player_volume(player_id, volume).await,
+            StateUpdate::Modes(modes) => self.inner.update_player_modes(player_id, modes).await,
+            StateUpdate::Text(metadata_id, text) => self.inner.update_player_metadata(player_id, metadata_id, text).await,
+        }
+    }
+}
+
+#[async_trait]
+impl FsctDriver for InterceptedDriver {
+    async fn register_player(&self, self_id: String) -> Result<ManagedPlayerId, Error> {
+        let player_id = self.inner.register_player(self_id.clone()).await?;
+        for interceptor in &self.interceptors {
+            interceptor.on_player_registered(player_id, &self_id).await;
+        }
+        Ok(player_id)
+    }
+
+    async fn unregister_player(&self, player_id: ManagedPlayerId) -> Result<(), Error> {
+        self.inner.unregister_player(player_id).await?;
+        for interceptor in &self.interceptors {
+            interceptor.on_player_unregistered(player_id).await;
+        }
+        Ok(())
+    }
+
+    async fn assign_player_to_device(&self, player_id: ManagedPlayerId, device_id: ManagedDeviceId) -> Result<(), Error> {
+        self.inner.assign_player_to_device(player_id, device_id).await
+    }
+
+    async fn unassign_player_from_device(&self, player_id: ManagedPlayerId, device_id: ManagedDeviceId) -> Result<(), Error> {
+        self.inner.unassign_player_from_device(player_id, device_id).await
+    }
+
+    async fn update_player_state(&self, player_id: ManagedPlayerId, new_state: PlayerState) -> Result<(), Error> {
+        self.forward(player_id, StateUpdate::State(new_state)).await
+    }
+
+    async fn update_player_status(&self, player_id: ManagedPlayerId, new_status: FsctStatus) -> Result<(), Error> {
+        self.forward(player_id, StateUpdate::Status(new_status)).await
+    }
+
+    async fn update_player_timeline(&self, player_id: ManagedPlayerId, new_timeline: Option<TimelineInfo>) -> Result<(), Error> {
+        self.forward(player_id, StateUpdate::Timeline(new_timeline)).await
+    }
+
+    async fn update_player_volume(&self, player_id: ManagedPlayerId, new_volume: Option<VolumeInfo>) -> Result<(), Error> {
+        self.forward(player_id, StateUpdate::Volume(new_volume)).await
+    }
+
+    async fn update_player_modes(&self, player_id: ManagedPlayerId, new_modes: PlaybackModes) -> Result<(), Error> {
+        self.forward(player_id, StateUpdate::Modes(new_modes)).await
+    }
+
+    async fn update_player_metadata(&self, player_id: ManagedPlayerId, metadata_id: FsctTextMetadata, new_text: Option<String>) -> Result<(), Error> {
+        self.forward(player_id, StateUpdate::Text(metadata_id, new_text)).await
+    }
+
+    fn set_preferred_player(&self, preferred: Option<ManagedPlayerId>) -> Result<(), Error> {
+        self.inner.set_preferred_player(preferred)
+    }
+
+    fn get_preferred_player(&self) -> Option<ManagedPlayerId> {
+        self.inner.get_preferred_player()
+    }
+
+    fn get_player_assigned_device(&self, player_id: ManagedPlayerId) -> Result<Option<ManagedDeviceId>, Error> {
+        self.inner.get_player_assigned_device(player_id)
+    }
+
+    fn get_device_limits(&self) -> Vec<DeviceLimits> {
+        self.inner.get_device_limits()
+    }
+
+    async fn list_devices(&self) -> Result<Vec<DeviceAttachRecord>, Error> {
+        self.inner.list_devices().await
+    }
+
+    fn subscribe_device_events(&self) -> broadcast::Receiver<DeviceEvent> {
+        self.inner.subscribe_device_events()
+    }
+
+    fn subscribe_player_events(&self) -> broadcast::Receiver<PlayerEvent> {
+        self.inner.subscribe_player_events()
+    }
+
+    fn subscribe_filtered(&self, filter: PlayerEventFilter) -> broadcast::Receiver<PlayerEvent> {
+        self.inner.subscribe_filtered(filter)
+    }
+
+    fn set_player_interface(&self, player_id: ManagedPlayerId, interface: Option<Arc<dyn PlayerInterface>>)
+                            -> Result<(), Error> {
+        self.inner.set_player_interface(player_id, interface)
+    }
+
+    async fn wait_applied(&self) -> Result<(), Error> {
+        self.inner.wait_applied().await
+    }
+}
+
+#[cfg(all(test, feature = "usb"))]
+mod tests {
+    use super::*;
+    use crate::driver::LocalDriver;
+
+    struct UppercaseTitles;
+
+    #[async_trait]
+    impl DriverInterceptor for UppercaseTitles {
+        async fn intercept(&self, _player_id: ManagedPlayerId, update: StateUpdate) -> Option<StateUpdate> {
+            match update {
+                StateUpdate::Text(FsctTextMetadata::CurrentTitle, text) => {
+                    Some(StateUpdate::Text(FsctTextMetadata::CurrentTitle, text.map(|t| t.to_uppercase())))
+                }
+                other => Some(other),
+            }
+        }
+    }
+
+    struct DropTimeline;
+
+    #[async_trait]
+    impl DriverInterceptor for DropTimeline {
+        async fn intercept(&self, _player_id: ManagedPlayerId, update: StateUpdate) -> Option<StateUpdate> {
+            match update {
+                StateUpdate::Timeline(_) => None,
+                other => Some(other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn interceptors_rewrite_and_drop_updates() {
+        let driver = InterceptedDriver::new(Arc::new(LocalDriver::with_new_managers()))
+            .with_interceptor(Arc::new(UppercaseTitles))
+            .with_interceptor(Arc::new(DropTimeline));
+        let mut events = driver.subscribe_player_events();
+        let player_id = driver.register_player("p1".into()).await.unwrap();
+
+        driver.update_player_metadata(player_id, FsctTextMetadata::CurrentTitle, Some("song".into())).await.unwrap();
+        let timeline = TimelineInfo {
+            position: std::time::Duration::from_secs(1),
+            update_time: std::time::SystemTime::now(),
+            duration: Some(std::time::Duration::from_secs(100)),
+            rate: 1.0,
+        };
+        driver.update_player_timeline(player_id, Some(timeline)).await.unwrap();
+        driver.update_player_status(player_id, FsctStatus::Playing).await.unwrap();
+
+        assert!(matches!(events.recv().await, Ok(PlayerEvent::Registered { .. })));
+        match events.recv().await {
+            Ok(PlayerEvent::TextMetadataUpdated { text, .. }) => assert_eq!(text.as_deref(), Some("SONG")),
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert!(matches!(events.recv().await, Ok(PlayerEvent::StatusUpdated { status: FsctStatus::Playing, .. })));
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::serde_format::system_time_millis;
+
+/// Origin metadata attached to an event when it is emitted.
+///
+/// `seq` is increasing without gaps per `source`. Every emitter instance gets a new random `source`, so a consumer
+/// reconnecting to a restarted host sees a new source instead of a sequence number that went backwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventStamp {
+    pub source: Uuid,
+    pub seq: u64,
+    #[serde(with = "system_time_millis")]
+    pub timestamp: SystemTime,
+}
+
+/// An event together with its origin stamp; serialized as the event object extended with the stamp fields.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stamped<E> {
+    #[serde(flatten)]
+    pub stamp: EventStamp,
+    #[serde(flatten)]
+    pub event: E,
+}
+
+impl<E> Stamped<E> {
+    /// Returns true if `other` was stamped by the same source before this event.
+    pub fn follows(&self, other: &EventStamp) -> bool {
+        self.stamp.source == other.source && self.stamp.seq > other.seq
+    }
+}
+
+/// Stamps events of a single source.
+pub struct EventStamper {
+    source: Uuid,
+    next_seq: AtomicU64,
+}
+
+impl EventStamper {
+    pub fn new() -> Self {
+        Self { source: Uuid::new_v4(), next_seq: AtomicU64::new(1) }
+    }
+
+    pub fn source(&self) -> Uuid {
+        self.source
+    }
+
+    pub fn stamp<E>(&self, event: E) -> Stamped<E> {
+        let seq = self.next_seq.fetch_add(1, Ordering::SeqCst);
+        Stamped { stamp: EventStamp { source: self.source, seq, timestamp: SystemTime::now() }, event }
+    }
+}
+
+impl Default for EventStamper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device_manager::DeviceEvent;
+
+    #[test]
+    fn stamps_have_increasing_sequence_per_source() {
+        let stamper = EventStamper::new();
+        let first = stamper.stamp(DeviceEvent::Added(Uuid::new_v4()));
+        let second = stamper.stamp(DeviceEvent::Removed(Uuid::new_v4()));
+        assert_eq!((first.stamp.seq, second.stamp.seq), (1, 2));
+        assert!(second.follows(&first.stamp));
+        assert!(!first.follows(&second.stamp));
+        assert!(!EventStamper::new().stamp(()).follows(&first.stamp));
+    }
+
+    #[test]
+    fn stamped_event_serializes_flat() {
+        let device_id = Uuid::new_v4();
+        let stamped = EventStamper::new().stamp(DeviceEvent::Added(device_id));
+        let value = serde_json::to_value(&stamped).unwrap();
+        assert_eq!(value["type"], "added");
+        assert_eq!(value["device_id"], device_id.to_string());
+        assert_eq!(value["seq"], 1);
+        let parsed: Stamped<DeviceEvent> = serde_json::from_value(value).unwrap();
+        assert_eq!((parsed.stamp.source, parsed.stamp.seq), (stamped.stamp.source, 1));
+    }
+}
@@ -0,0 +1,476 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! JSON-RPC 2.0 access to a running [`LocalDriver`] for GUIs and CLIs on the same host.
+//!
+//! The server listens on a Unix domain socket (a named pipe on Windows), see [`run_ipc_server`]. Messages are JSON
+//! objects or batches, one per line. Methods follow the [`FsctDriver`] API and take named parameters:
+//!
+//! | Method                        | Parameters                                 | Result                        |
+//! |-------------------------------|--------------------------------------------|-------------------------------|
+//! | `list_players`                |                                            | players                       |
+//! | `get_player`                  | `player_id`                                | player                        |
+//! | `register_player`             | `self_id`                                  | `{ "player_id": 1 }`          |
+//! | `unregister_player`           | `player_id`                                | `null`                        |
+//! | `update_player_state`         | `player_id`, `state`                       | `null`                        |
+//! | `list_assignments`            |                                            | `player_id`/`device_id` pairs |
+//! | `assign_player_to_device`     | `player_id`, `device_id`                   | `null`                        |
+//! | `unassign_player_from_device` | `player_id`, `device_id`                   | `null`                        |
+//! | `get_preferred_player`        |                                            | player id or `null`           |
+//! | `set_preferred_player`        | `player_id` or `null`                      | `null`                        |
+//! | `list_devices`                |                                            | attached devices              |
+//! | `get_device_limits`           |                                            | device limits                 |
+//! | `get_dnd`                     |                                            | do-not-disturb switches       |
+//! | `set_dnd`                     | `device_id`?, `enabled`                    | `null`                        |
+//! | `list_routes`                 |                                            | route overrides               |
+//! | `force_route`                 | `player_id`, `device_id`, `duration_secs`? | `null`                        |
+//! | `clear_route`                 | `device_id`                                | whether there was one         |
+//!
+//! Players and states are in the format of `docs/json_representation.md`. Players registered over a connection are
+//! unregistered when it closes. Access is limited by the permissions of the socket, read and write for its owner and
+//! group only (mode 0660): whoever may connect is trusted like the user of the service, so callers are not
+//! authenticated. On Windows, the default security of the pipe lets only the service's user, administrators and
+//! `SYSTEM` send requests. The WebSocket server, reachable over TCP, authenticates clients with an
+//! [`AuthPolicy`](crate::auth::AuthPolicy) instead; callers with [`Scope::Read`] may only call the methods that
+//! change nothing.
+//!
+//! With the `ws` feature, [`ws`] streams the events of the driver to web dashboards.
+
+mod server;
+#[cfg(feature = "ws")]
+pub mod ws;
+
+pub use server::{default_ipc_path, run_ipc_server};
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Error;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::auth::{audit_control, AuthError, Principal, Scope};
+use crate::device_manager::ManagedDeviceId;
+use crate::driver_middleware::InterceptedDriver;
+use crate::orchestrator::DndScope;
+use crate::rate_limit::RateLimiter;
+use crate::validation::{validate_route_duration, validate_self_id, ValidatingInterceptor};
+use crate::player_manager::ManagedPlayerId;
+use crate::{FsctDriver, LocalDriver, PlayerState};
+
+/// Invalid JSON was received.
+pub const PARSE_ERROR: i64 = -32700;
+/// The JSON sent is not a valid request object.
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// The driver refused the call, e.g. for an unknown player.
+pub const DRIVER_ERROR: i64 = -32000;
+/// The caller may not call the method.
+pub const FORBIDDEN: i64 = -32001;
+/// The caller sent more requests than it may; the message says when to retry.
+pub const RATE_LIMITED: i64 = -32002;
+
+/// Methods changing the driver, which take [`Scope::Control`].
+const CONTROL_METHODS: [&str; 9] = ["register_player", "unregister_player", "update_player_state",
+                                    "assign_player_to_device", "unassign_player_from_device", "set_preferred_player",
+                                    "set_dnd", "force_route", "clear_route"];
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    /// Absent in notifications, which get no response.
+    id: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+impl From<Error> for RpcError {
+    fn from(e: Error) -> Self {
+        Self::new(DRIVER_ERROR, e.to_string())
+    }
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+        Err(error) => json!({ "jsonrpc": "2.0", "error": error, "id": id }),
+    }
+}
+
+fn params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    // methods without parameters may be called with `params` left out
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn to_value<T: Serialize>(value: T) -> Value {
+    serde_json::to_value(value).expect("core types serialize to JSON")
+}
+
+#[derive(Deserialize)]
+struct PlayerParams {
+    player_id: ManagedPlayerId,
+}
+
+#[derive(Deserialize)]
+struct RegisterParams {
+    self_id: String,
+}
+
+#[derive(Deserialize)]
+struct StateParams {
+    player_id: ManagedPlayerId,
+    state: PlayerState,
+}
+
+#[derive(Deserialize)]
+struct AssignmentParams {
+    player_id: ManagedPlayerId,
+    device_id: ManagedDeviceId,
+}
+
+#[derive(Deserialize)]
+struct PreferredParams {
+    player_id: Option<ManagedPlayerId>,
+}
+
+#[derive(Deserialize)]
+struct DndParams {
+    /// All devices if absent.
+    #[serde(default)]
+    device_id: Option<ManagedDeviceId>,
+    enabled: bool,
+}
+
+#[derive(Deserialize)]
+struct RouteParams {
+    player_id: ManagedPlayerId,
+    device_id: ManagedDeviceId,
+    /// Until the player stops if absent.
+    #[serde(default, with = "crate::serde_format::optional_duration_secs")]
+    duration_secs: Option<Duration>,
+}
+
+#[derive(Deserialize)]
+struct DeviceParams {
+    device_id: ManagedDeviceId,
+}
+
+#[derive(Deserialize)]
+struct NoParams {}
+
+/// Calls of one IPC connection, tracking the players it registered.
+///
+/// Self ids are validated, and state updates pass a [`ValidatingInterceptor`] before reaching the driver.
+pub struct IpcSession {
+    driver: Arc<LocalDriver>,
+    updates: InterceptedDriver,
+    principal: Principal,
+    registered: Mutex<Vec<ManagedPlayerId>>,
+    rate_limit: Option<(Arc<RateLimiter<String>>, String)>,
+}
+
+impl IpcSession {
+    /// Session of a caller trusted to control the driver, i.e. a client of the IPC socket.
+    pub fn new(driver: Arc<LocalDriver>) -> Self {
+        Self::with_principal(driver, Principal { name: "ipc".to_string(), scope: Scope::Control })
+    }
+
+    /// Session of an authenticated caller, limited to the methods its scope allows.
+    pub fn with_principal(driver: Arc<LocalDriver>, principal: Principal) -> Self {
+        let updates = InterceptedDriver::new(driver.clone()).with_interceptor(Arc::new(ValidatingInterceptor));
+        Self { driver, updates, principal, registered: Mutex::new(Vec::new()), rate_limit: None }
+    }
+
+    /// Limits the requests of the session with `limiter`, as those of `client`.
+    pub fn with_rate_limit(mut self, limiter: Arc<RateLimiter<String>>, client: String) -> Self {
+        self.rate_limit = Some((limiter, client));
+        self
+    }
+
+    /// Handles one message, a request or a batch of them; returns the response, if any is due.
+    pub async fn handle_message(&self, message: &str) -> Option<String> {
+        let message: Value = match serde_json::from_str(message) {
+            Ok(message) => message,
+            Err(e) => return Some(response(Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string()))).to_string()),
+        };
+        let response = match message {
+            Value::Array(batch) if batch.is_empty() => {
+                Some(response(Value::Null, Err(RpcError::new(INVALID_REQUEST, "Empty batch"))))
+            }
+            Value::Array(batch) => {
+                let mut responses = Vec::new();
+                for request in batch {
+                    responses.extend(self.handle_request(request).await);
+                }
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            request => self.handle_request(request).await,
+        };
+        response.map(|response| response.to_string())
+    }
+
+    async fn handle_request(&self, request: Value) -> Option<Value> {
+        let request = match serde_json::from_value::<RpcRequest>(request) {
+            Ok(request) if request.jsonrpc == "2.0" => request,
+            Ok(_) => {
+                return Some(response(Value::Null, Err(RpcError::new(INVALID_REQUEST, "Unsupported jsonrpc version"))));
+            }
+            Err(e) => return Some(response(Value::Null, Err(RpcError::new(INVALID_REQUEST, e.to_string())))),
+        };
+        let limited = self.rate_limit.as_ref().and_then(|(limiter, client)| limiter.check(client).err());
+        let result = match limited {
+            Some(retry_after) => {
+                let message = format!("Too many requests, retry in {} ms", retry_after.as_millis());
+                Err(RpcError::new(RATE_LIMITED, message))
+            }
+            None => self.call(&request.method, request.params).await,
+        };
+        request.id.map(|id| response(id, result))
+    }
+
+    async fn call(&self, method: &str, params_value: Value) -> Result<Value, RpcError> {
+        let driver = &self.driver;
+        if CONTROL_METHODS.contains(&method) && self.principal.scope < Scope::Control {
+            let e = AuthError::Forbidden { principal: self.principal.name.clone(), required: Scope::Control };
+            return Err(RpcError::new(FORBIDDEN, e.to_string()));
+        }
+        match method {
+            "list_players" => {
+                let NoParams {} = params(params_value)?;
+                Ok(to_value(driver.player_manager().list_players()))
+            }
+            "get_player" => {
+                let PlayerParams { player_id } = params(params_value)?;
+                let player = driver.player_manager().list_players().into_iter().find(|p| p.player_id == player_id);
+                player.map(to_value).ok_or_else(|| RpcError::new(DRIVER_ERROR, format!("No player {}", player_id)))
+            }
+            "register_player" => {
+                let RegisterParams { self_id } = params(params_value)?;
+                validate_self_id(&self_id).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))?;
+                audit_control(&self.principal, &format!("registration of player {}", self_id));
+                let player_id = driver.register_player(self_id).await?;
+                self.registered.lock().unwrap().push(player_id);
+                Ok(json!({ "player_id": player_id }))
+            }
+            "unregister_player" => {
+                let PlayerParams { player_id } = params(params_value)?;
+                driver.unregister_player(player_id).await?;
+                self.registered.lock().unwrap().retain(|id| *id != player_id);
+                Ok(Value::Null)
+            }
+            "update_player_state" => {
+                let StateParams { player_id, state } = params(params_value)?;
+                self.updates.update_player_state(player_id, state).await?;
+                Ok(Value::Null)
+            }
+            "list_assignments" => {
+                let NoParams {} = params(params_value)?;
+                let assignments: Vec<_> = driver.player_manager().list_players().into_iter()
+                    .filter_map(|p| {
+                        p.assigned_device.map(|device_id| json!({ "player_id": p.player_id, "device_id": device_id }))
+                    })
+                    .collect();
+                Ok(Value::Array(assignments))
+            }
+            "assign_player_to_device" => {
+                let AssignmentParams { player_id, device_id } = params(params_value)?;
+                audit_control(&self.principal, &format!("assignment of player {} to device {}", player_id, device_id));
+                driver.assign_player_to_device(player_id, device_id).await?;
+                Ok(Value::Null)
+            }
+            "unassign_player_from_device" => {
+                let AssignmentParams { player_id, device_id } = params(params_value)?;
+                let action = format!("unassignment of player {} from device {}", player_id, device_id);
+                audit_control(&self.principal, &action);
+                driver.unassign_player_from_device(player_id, device_id).await?;
+                Ok(Value::Null)
+            }
+            "get_preferred_player" => {
+                let NoParams {} = params(params_value)?;
+                Ok(to_value(driver.get_preferred_player()))
+            }
+            "set_preferred_player" => {
+                let PreferredParams { player_id } = params(params_value)?;
+                audit_control(&self.principal, &format!("preference of player {:?}", player_id.map(|id| id.get())));
+                driver.set_preferred_player(player_id)?;
+                Ok(Value::Null)
+            }
+            "list_devices" => {
+                let NoParams {} = params(params_value)?;
+                Ok(to_value(driver.list_devices().await?))
+            }
+            "get_device_limits" => {
+                let NoParams {} = params(params_value)?;
+                Ok(to_value(driver.get_device_limits()))
+            }
+            "get_dnd" => {
+                let NoParams {} = params(params_value)?;
+                Ok(to_value(driver.control()?.dnd().await?))
+            }
+            "set_dnd" => {
+                let DndParams { device_id, enabled } = params(params_value)?;
+                let scope = device_id.map_or(DndScope::Global, DndScope::Device);
+                let action = format!("do-not-disturb {:?} turned {}", scope, if enabled { "on" } else { "off" });
+                audit_control(&self.principal, &action);
+                driver.control()?.set_dnd(scope, enabled).await?;
+                Ok(Value::Null)
+            }
+            "list_routes" => {
+                let NoParams {} = params(params_value)?;
+                Ok(to_value(driver.control()?.routes().await?))
+            }
+            "force_route" => {
+                let RouteParams { player_id, device_id, duration_secs } = params(params_value)?;
+                if let Some(duration) = duration_secs {
+                    validate_route_duration(duration).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))?;
+                }
+                let action = format!("route override of device {} to player {}", device_id, player_id);
+                audit_control(&self.principal, &action);
+                driver.control()?.force_route(player_id, device_id, duration_secs).await?;
+                Ok(Value::Null)
+            }
+            "clear_route" => {
+                let DeviceParams { device_id } = params(params_value)?;
+                audit_control(&self.principal, &format!("removal of the route override of device {}", device_id));
+                Ok(Value::Bool(driver.control()?.clear_route(device_id).await?))
+            }
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+        }
+    }
+
+    /// Unregisters the players registered in the session, when its connection closes.
+    pub async fn close(&self) {
+        let registered = std::mem::take(&mut *self.registered.lock().unwrap());
+        for player_id in registered {
+            // the player may have been unregistered by someone else in the meantime
+            let _ = self.driver.unregister_player(player_id).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn call(session: &IpcSession, request: Value) -> Value {
+        let response = session.handle_message(&request.to_string()).await.unwrap();
+        serde_json::from_str(&response).unwrap()
+    }
+
+    #[tokio::test]
+    async fn driver_calls_are_answered_and_players_removed_on_close() {
+        let driver = Arc::new(LocalDriver::with_new_managers());
+        let session = IpcSession::new(driver.clone());
+
+        let registered = call(&session, json!({
+            "jsonrpc": "2.0", "method": "register_player", "params": { "self_id": "gui" }, "id": 1
+        })).await;
+        assert_eq!(registered["id"], 1);
+        let player_id = registered["result"]["player_id"].clone();
+        let state = json!({ "status": "playing", "timeline": null, "texts": { "title": "Title" } });
+        let update = json!({
+            "jsonrpc": "2.0", "method": "update_player_state", "params": { "player_id": player_id, "state": state }
+        });
+        assert_eq!(session.handle_message(&update.to_string()).await, None);
+
+        let players = call(&session, json!({ "jsonrpc": "2.0", "method": "list_players", "id": "a" })).await;
+        assert_eq!(players["result"][0]["self_id"], "gui");
+        assert_eq!(players["result"][0]["state"]["status"], "playing");
+
+        let batch = json!([
+            { "jsonrpc": "2.0", "method": "get_preferred_player", "id": 2 },
+            { "jsonrpc": "2.0", "method": "set_preferred_player", "params": { "player_id": 0 }, "id": 3 },
+            { "jsonrpc": "2.0", "method": "pause", "id": 4 },
+            { "jsonrpc": "1.0", "method": "list_devices", "id": 5 },
+        ]);
+        let responses = call(&session, batch).await;
+        assert_eq!(responses[0]["result"], Value::Null);
+        assert_eq!(responses[1]["error"]["code"], INVALID_PARAMS);
+        assert_eq!(responses[2]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(responses[3]["error"]["code"], INVALID_REQUEST);
+        let unknown = json!({
+            "jsonrpc": "2.0", "method": "get_player", "params": { "player_id": 99 }, "id": 6
+        });
+        assert_eq!(call(&session, unknown).await["error"]["code"], DRIVER_ERROR);
+        let garbage = session.handle_message("{").await.unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&garbage).unwrap()["error"]["code"], PARSE_ERROR);
+
+        session.close().await;
+        assert!(driver.player_manager().list_players().is_empty());
+    }
+
+    #[tokio::test]
+    async fn readers_may_not_change_the_driver() {
+        let driver = Arc::new(LocalDriver::with_new_managers());
+        let reader = Principal { name: "token:dashboard".to_string(), scope: Scope::Read };
+        let session = IpcSession::with_principal(driver.clone(), reader);
+
+        let players = call(&session, json!({ "jsonrpc": "2.0", "method": "list_players", "id": 1 })).await;
+        assert_eq!(players["result"], json!([]));
+        let register = json!({
+            "jsonrpc": "2.0", "method": "register_player", "params": { "self_id": "intruder" }, "id": 2
+        });
+        assert_eq!(call(&session, register).await["error"]["code"], FORBIDDEN);
+        assert!(driver.player_manager().list_players().is_empty());
+    }
+
+    #[tokio::test]
+    async fn invalid_and_excess_requests_are_rejected() {
+        let driver = Arc::new(LocalDriver::with_new_managers());
+        let limiter = Arc::new(RateLimiter::new(2, 0.001));
+        let session = IpcSession::new(driver.clone()).with_rate_limit(limiter, "test".to_string());
+
+        let register = json!({
+            "jsonrpc": "2.0", "method": "register_player", "params": { "self_id": "bad\u{1b}[2J" }, "id": 1
+        });
+        assert_eq!(call(&session, register).await["error"]["code"], INVALID_PARAMS);
+        let list = json!({ "jsonrpc": "2.0", "method": "list_players", "id": 2 });
+        assert_eq!(call(&session, list.clone()).await["result"], json!([]));
+        assert_eq!(call(&session, list).await["error"]["code"], RATE_LIMITED);
+    }
+
+    #[tokio::test]
+    async fn overlong_route_overrides_are_rejected() {
+        let driver = Arc::new(LocalDriver::with_new_managers());
+        let session = IpcSession::new(driver);
+
+        let route = json!({
+            "jsonrpc": "2.0", "method": "force_route",
+            "params": {
+                "player_id": 1, "device_id": "00000000-0000-0000-0000-000000000001", "duration_secs": 1e18
+            },
+            "id": 1
+        });
+        assert_eq!(call(&session, route).await["error"]["code"], INVALID_PARAMS);
+    }
+}
@@ -0,0 +1,189 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Accepting IPC connections on a Unix domain socket or a Windows named pipe.
+
+use std::io;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use log::{debug, error, info};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::task::JoinSet;
+
+use super::IpcSession;
+use crate::rate_limit::{RateLimiter, API_BURST, API_REQUESTS_PER_SECOND};
+use crate::service::{spawn_service, ServiceHandle};
+use crate::LocalDriver;
+
+/// Where the service listens by default: `$XDG_RUNTIME_DIR/fsct-host.sock`, falling back to `/tmp`, on Unix, and
+/// `\\.\pipe\fsct-host` on Windows.
+pub fn default_ipc_path() -> PathBuf {
+    if cfg!(windows) {
+        return PathBuf::from(r"\\.\pipe\fsct-host");
+    }
+    let directory = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("/tmp"));
+    directory.join("fsct-host.sock")
+}
+
+/// Serves JSON-RPC on `path` until the service is stopped, limiting the requests of every connection to
+/// [`API_REQUESTS_PER_SECOND`]. A socket left over by a previous run is replaced.
+pub fn run_ipc_server(driver: Arc<LocalDriver>, path: &Path) -> io::Result<ServiceHandle> {
+    let listener = Listener::bind(path)?;
+    info!("Serving IPC on {}", path.display());
+    let limiter = Arc::new(RateLimiter::new(API_BURST, API_REQUESTS_PER_SECOND));
+    Ok(spawn_service(move |mut stop| async move {
+        let mut connections = JoinSet::new();
+        let mut accepted_count = 0u64;
+        loop {
+            tokio::select! {
+                _ = stop.signaled() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok(connection) => {
+                        accepted_count += 1;
+                        let client = format!("connection #{}", accepted_count);
+                        let session = IpcSession::new(driver.clone()).with_rate_limit(limiter.clone(), client.clone());
+                        let limiter = limiter.clone();
+                        connections.spawn(async move {
+                            serve_connection(session, connection).await;
+                            limiter.remove(&client);
+                        });
+                    }
+                    Err(e) => {
+                        error!("IPC server failed: {}", e);
+                        break;
+                    }
+                },
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            }
+        }
+        listener.close();
+    }))
+}
+
+/// Answers the messages of one connection, one per line, until the peer closes it.
+async fn serve_connection<S: AsyncRead + AsyncWrite + Send + 'static>(session: IpcSession, stream: S) {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(e) => {
+                debug!("IPC connection failed: {}", e);
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = session.handle_message(&line).await {
+            let written = async {
+                writer.write_all(response.as_bytes()).await?;
+                writer.write_all(b"\n").await?;
+                writer.flush().await
+            };
+            if let Err(e) = written.await {
+                debug!("IPC connection failed: {}", e);
+                break;
+            }
+        }
+    }
+    session.close().await;
+}
+
+/// Permissions of the socket: read and write for the owner and its group.
+#[cfg(unix)]
+const SOCKET_MODE: u32 = 0o660;
+
+#[cfg(unix)]
+struct Listener {
+    listener: tokio::net::UnixListener,
+    path: PathBuf,
+}
+
+#[cfg(unix)]
+impl Listener {
+    fn bind(path: &Path) -> io::Result<Self> {
+        if let Err(e) = std::fs::remove_file(path) && e.kind() != io::ErrorKind::NotFound {
+            return Err(e);
+        }
+        let listener = tokio::net::UnixListener::bind(path)?;
+        // the umask may leave the socket open to other users; only the owner and its group may control the driver
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(SOCKET_MODE))?;
+        Ok(Self { listener, path: path.to_path_buf() })
+    }
+
+    async fn accept(&self) -> io::Result<tokio::net::UnixStream> {
+        self.listener.accept().await.map(|(stream, _)| stream)
+    }
+
+    fn close(self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(windows)]
+struct Listener {
+    /// The pipe instance waiting for the next client.
+    next: tokio::sync::Mutex<tokio::net::windows::named_pipe::NamedPipeServer>,
+    path: PathBuf,
+}
+
+#[cfg(windows)]
+impl Listener {
+    fn bind(path: &Path) -> io::Result<Self> {
+        use tokio::net::windows::named_pipe::ServerOptions;
+        let next = ServerOptions::new().first_pipe_instance(true).create(path)?;
+        Ok(Self { next: tokio::sync::Mutex::new(next), path: path.to_path_buf() })
+    }
+
+    async fn accept(&self) -> io::Result<tokio::net::windows::named_pipe::NamedPipeServer> {
+        use tokio::net::windows::named_pipe::ServerOptions;
+        let mut next = self.next.lock().await;
+        next.connect().await?;
+        // the connected instance goes to the client, a new one waits for the next client
+        let waiting = ServerOptions::new().create(&self.path)?;
+        Ok(std::mem::replace(&mut *next, waiting))
+    }
+
+    fn close(self) {}
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn requests_are_answered_over_the_socket() {
+        let path = std::env::temp_dir().join(format!("fsct-ipc-{}.sock", std::process::id()));
+        let driver = Arc::new(LocalDriver::with_new_managers());
+        let server = run_ipc_server(driver, &path).unwrap();
+
+        let stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        let (reader, mut writer) = tokio::io::split(stream);
+        writer.write_all(b"{\"jsonrpc\": \"2.0\", \"method\": \"list_players\", \"id\": 7}\n").await.unwrap();
+        let response = BufReader::new(reader).lines().next_line().await.unwrap().unwrap();
+        let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response, serde_json::json!({ "jsonrpc": "2.0", "result": [], "id": 7 }));
+
+        server.shutdown().await.unwrap();
+        assert!(!path.exists());
+    }
+}
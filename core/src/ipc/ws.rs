@@ -0,0 +1,297 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Player and device events over WebSocket, for web dashboards showing which player drives which device.
+//!
+//! After connecting, clients get a snapshot and then every event, one JSON text message each:
+//!
+//! ```json
+//! { "snapshot": { "players": [], "preferred_player": null, "device_limits": [] } }
+//! { "player_event": { "type": "assigned", "player_id": 1, "device_id": "…" } }
+//! { "device_event": { "type": "added", "device_id": "…" } }
+//! ```
+//!
+//! A new snapshot follows when a client falls behind. Text messages sent by clients are JSON-RPC requests, answered
+//! like on the [IPC socket](super), e.g. `set_preferred_player` or `assign_player_to_device`.
+//!
+//! Browsers are only accepted on pages served from the host itself (`localhost`, `127.0.0.1` or `[::1]`) and of the
+//! origins allowed with [`WsServer::with_allowed_origins`], so that arbitrary web pages can't control the driver.
+//!
+//! Clients are authenticated with the [`AuthPolicy`] of the server when connecting, by a bearer token in the
+//! `Authorization` header or, for browsers, which can't set it, in the `token` query parameter, e.g.
+//! `ws://127.0.0.1:50152/?token=…`. Clients with [`Scope::Read`](crate::auth::Scope::Read) get the events but may
+//! not change the driver. The requests of clients are limited to [`API_REQUESTS_PER_SECOND`] per address.
+
+use std::sync::{Arc, OnceLock};
+
+use futures::{SinkExt, StreamExt};
+use log::{debug, error, info};
+use serde_json::{json, Value};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio::task::JoinSet;
+use tokio_tungstenite::tungstenite::handshake::server::{Callback, ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+
+use super::IpcSession;
+use crate::auth::{AuthPolicy, Credentials, Principal};
+use crate::rate_limit::{RateLimiter, API_BURST, API_REQUESTS_PER_SECOND};
+use crate::service::{spawn_service, ServiceHandle};
+use crate::{FsctDriver, LocalDriver};
+
+/// WebSocket server of a [`LocalDriver`].
+pub struct WsServer {
+    driver: Arc<LocalDriver>,
+    allowed_origins: Vec<String>,
+    auth: AuthPolicy,
+    limiter: Arc<RateLimiter<String>>,
+}
+
+impl WsServer {
+    /// Server accepting browsers on pages served from the host only, see [`WsServer::with_allowed_origins`], and
+    /// rejecting every client until given a policy, see [`WsServer::with_auth_policy`].
+    pub fn new(driver: Arc<LocalDriver>) -> Self {
+        let limiter = Arc::new(RateLimiter::new(API_BURST, API_REQUESTS_PER_SECOND));
+        Self { driver, allowed_origins: Vec::new(), auth: AuthPolicy::default(), limiter }
+    }
+
+    /// Limits who may connect and what they may do.
+    pub fn with_auth_policy(mut self, auth: AuthPolicy) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Accepts browsers on pages of these origins as well, e.g. `https://dashboard.example.com`. Clients sending no
+    /// `Origin`, i.e. no browsers, are always accepted.
+    pub fn with_allowed_origins(mut self, origins: Vec<String>) -> Self {
+        self.allowed_origins = origins;
+        self
+    }
+
+    fn accepts_origin(&self, origin: Option<&str>) -> bool {
+        match origin {
+            Some(origin) => is_local_origin(origin) || self.allowed_origins.iter().any(|allowed| allowed == origin),
+            None => true,
+        }
+    }
+}
+
+/// Whether the origin is a page served from the host itself.
+fn is_local_origin(origin: &str) -> bool {
+    let Some((_, authority)) = origin.split_once("://") else { return false };
+    let host = match authority.strip_prefix('[') {
+        Some(ipv6) => ipv6.split_once(']').map_or(authority, |(host, _)| host),
+        None => authority.split(':').next().unwrap_or(authority),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+/// Credentials of the handshake: the `Authorization` header, or the `token` query parameter.
+fn credentials(request: &Request) -> Credentials {
+    let header = request.headers().get("authorization").and_then(|value| value.to_str().ok());
+    let token = request.uri().query().into_iter()
+        .flat_map(|query| query.split('&'))
+        .find_map(|parameter| parameter.strip_prefix("token="));
+    match (header, token) {
+        (None, Some(token)) => Credentials::Bearer(token.to_string()),
+        (header, _) => Credentials::from_authorization_header(header),
+    }
+}
+
+fn rejection(status: StatusCode, reason: &str) -> ErrorResponse {
+    let mut rejection = ErrorResponse::new(Some(reason.to_string()));
+    *rejection.status_mut() = status;
+    rejection
+}
+
+/// Handshake callback rejecting browsers of origins the server doesn't accept and clients the policy doesn't
+/// authenticate, leaving the principal of accepted ones.
+struct HandshakeCheck {
+    server: Arc<WsServer>,
+    principal: Arc<OnceLock<Principal>>,
+}
+
+impl Callback for HandshakeCheck {
+    fn on_request(self, request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+        let origin = request.headers().get("origin").and_then(|origin| origin.to_str().ok());
+        if !self.server.accepts_origin(origin) {
+            debug!("Rejected WebSocket client of origin {:?}", origin);
+            return Err(rejection(StatusCode::FORBIDDEN, "Origin not allowed"));
+        }
+        match self.server.auth.authenticate(&credentials(request)) {
+            Ok(principal) => {
+                let _ = self.principal.set(principal);
+                Ok(response)
+            }
+            Err(e) => {
+                debug!("Rejected WebSocket client: {}", e);
+                Err(rejection(StatusCode::UNAUTHORIZED, &e.to_string()))
+            }
+        }
+    }
+}
+
+/// Serves the events on connections accepted by `listener`, until the service is stopped.
+pub fn run_ws_server(server: WsServer, listener: TcpListener) -> ServiceHandle {
+    if let Ok(address) = listener.local_addr() {
+        info!("Serving driver events over WebSocket on {}", address);
+    }
+    let server = Arc::new(server);
+    spawn_service(move |mut stop| async move {
+        let mut connections = JoinSet::new();
+        loop {
+            tokio::select! {
+                _ = stop.signaled() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, peer)) => {
+                        let (server, client) = (server.clone(), peer.ip().to_string());
+                        connections.spawn(async move {
+                            serve_connection(server.clone(), stream, client.clone()).await;
+                            server.limiter.remove(&client);
+                        });
+                    }
+                    Err(e) => {
+                        error!("WebSocket server failed: {}", e);
+                        break;
+                    }
+                },
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            }
+        }
+    })
+}
+
+fn snapshot(driver: &LocalDriver) -> Value {
+    json!({ "snapshot": {
+        "players": driver.player_manager().list_players(),
+        "preferred_player": driver.get_preferred_player(),
+        "device_limits": driver.get_device_limits(),
+    }})
+}
+
+async fn serve_connection(server: Arc<WsServer>, stream: TcpStream, client: String) {
+    let principal = Arc::new(OnceLock::new());
+    let check = HandshakeCheck { server: server.clone(), principal: principal.clone() };
+    let websocket = match tokio_tungstenite::accept_hdr_async(stream, check).await {
+        Ok(websocket) => websocket,
+        Err(e) => {
+            debug!("WebSocket handshake failed: {}", e);
+            return;
+        }
+    };
+    let Some(principal) = principal.get().cloned() else { return };
+    let (mut outgoing, mut incoming) = websocket.split();
+    let driver = server.driver.clone();
+    let session = IpcSession::with_principal(driver.clone(), principal).with_rate_limit(server.limiter.clone(), client);
+    // subscribe before taking the snapshot so that no change in between is missed
+    let mut player_events = driver.subscribe_player_events();
+    let mut device_events = driver.subscribe_device_events();
+    let mut pending = vec![snapshot(&driver).to_string()];
+    loop {
+        for message in pending.drain(..) {
+            if let Err(e) = outgoing.send(Message::Text(message.into())).await {
+                debug!("WebSocket connection failed: {}", e);
+                session.close().await;
+                return;
+            }
+        }
+        tokio::select! {
+            received = incoming.next() => match received {
+                Some(Ok(Message::Text(text))) => pending.extend(session.handle_message(text.as_str()).await),
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    debug!("WebSocket connection failed: {}", e);
+                    break;
+                }
+            },
+            received = player_events.recv() => match received {
+                Ok(event) => pending.push(json!({ "player_event": event }).to_string()),
+                Err(broadcast::error::RecvError::Lagged(_)) => pending.push(snapshot(&driver).to_string()),
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            received = device_events.recv() => match received {
+                Ok(event) => pending.push(json!({ "device_event": event }).to_string()),
+                Err(broadcast::error::RecvError::Lagged(_)) => pending.push(snapshot(&driver).to_string()),
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        }
+    }
+    session.close().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn next_json<S>(incoming: &mut S) -> Value
+    where
+        S: futures::Stream<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+    {
+        match incoming.next().await.unwrap().unwrap() {
+            Message::Text(text) => serde_json::from_str(text.as_str()).unwrap(),
+            message => panic!("Unexpected message {:?}", message),
+        }
+    }
+
+    #[tokio::test]
+    async fn events_are_streamed_and_control_messages_answered() {
+        let driver = Arc::new(LocalDriver::with_new_managers());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = WsServer::new(driver.clone()).with_auth_policy(AuthPolicy::token("secret"));
+        let server = run_ws_server(server, listener);
+
+        assert!(tokio_tungstenite::connect_async(format!("ws://{}", address)).await.is_err());
+        let (websocket, _) = tokio_tungstenite::connect_async(format!("ws://{}/?token=secret", address)).await.unwrap();
+        let (mut outgoing, mut incoming) = websocket.split();
+        assert_eq!(next_json(&mut incoming).await["snapshot"]["players"], json!([]));
+
+        let player_id = driver.register_player("dashboard-test".to_string()).await.unwrap();
+        let registered = next_json(&mut incoming).await;
+        assert_eq!(registered["player_event"]["type"], "registered");
+        assert_eq!(registered["player_event"]["self_id"], "dashboard-test");
+
+        let request = json!({
+            "jsonrpc": "2.0", "method": "set_preferred_player", "params": { "player_id": player_id }, "id": 1
+        });
+        outgoing.send(Message::Text(request.to_string().into())).await.unwrap();
+        let mut messages = [next_json(&mut incoming).await, next_json(&mut incoming).await];
+        messages.sort_by_key(|message| message.get("jsonrpc").is_some());
+        assert_eq!(messages[0]["player_event"]["type"], "preferred_changed");
+        assert_eq!(messages[1], json!({ "jsonrpc": "2.0", "result": null, "id": 1 }));
+        assert_eq!(driver.get_preferred_player(), Some(player_id));
+
+        server.shutdown().await.unwrap();
+    }
+
+    #[test]
+    fn browsers_of_other_origins_are_rejected() {
+        let server = WsServer::new(Arc::new(LocalDriver::with_new_managers()));
+        assert!(server.accepts_origin(None));
+        assert!(server.accepts_origin(Some("http://localhost:8080")));
+        assert!(server.accepts_origin(Some("http://127.0.0.1")));
+        assert!(server.accepts_origin(Some("http://[::1]:3000")));
+        assert!(!server.accepts_origin(Some("https://example.com")));
+        assert!(!server.accepts_origin(Some("http://localhost.example.com")));
+
+        let server = server.with_allowed_origins(vec!["https://dashboard.example.com".to_string()]);
+        assert!(server.accepts_origin(Some("https://dashboard.example.com")));
+        assert!(!server.accepts_origin(Some("https://example.com")));
+    }
+}
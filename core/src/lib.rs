@@ -14,31 +14,92 @@
 //
 // This file is part of an implementation of Ferrum Streaming Control Technology™,
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
+#[cfg(feature = "usb")]
 pub mod usb;
 pub mod definitions;
 
 mod player_manager;
 pub mod player_state_applier;
 pub mod player_events;
+pub mod player_interface;
+pub mod player_origin;
+pub mod player_event_queue;
 pub mod orchestrator;
 pub mod service;
 pub mod driver;
+pub mod driver_middleware;
 pub mod device_manager;
+pub mod device_history;
+pub mod device_filter;
+pub mod assignment_file;
+pub mod descriptor_dump;
+pub mod readiness;
+pub mod device_write_queue;
+#[cfg(feature = "usb")]
 pub mod usb_device_watch;
 pub mod player_state;
+pub mod serde_format;
+pub mod event_stamp;
+pub mod auth;
+pub mod validation;
+pub mod rate_limit;
+pub mod timeline_smoothing;
+pub mod track_events;
+pub mod polling;
+pub mod port_supervisor;
+pub mod self_id;
+pub mod power;
+pub mod usage_stats;
+pub mod announcements;
+pub mod warmup;
+pub mod quirks;
+#[cfg(feature = "storage")]
+pub mod storage;
+pub mod aux_content;
+pub mod text_template;
+pub mod time_format;
+#[cfg(feature = "self-update")]
+pub mod update;
+#[cfg(feature = "remote")]
+pub mod remote;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(feature = "ipc")]
+pub mod ipc;
+#[cfg(feature = "audio-levels")]
+pub mod audio_levels;
+#[cfg(feature = "spectrum")]
+pub mod spectrum;
+#[cfg(feature = "network")]
+pub mod network;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+#[cfg(feature = "usb")]
 mod device_uuid_calculator;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 
-pub use player_manager::{ManagedPlayerId, PlayerManager};
+pub use player_manager::{ManagedPlayerId, PlayerInfo, PlayerManager};
 pub use player_state::PlayerState;
-pub use player_events::PlayerEvent;
-pub use orchestrator::Orchestrator;
+pub use player_events::{PlayerEvent, PlayerEventFilter, PlayerEventKind};
+pub use player_interface::{PlayerInterface, PlayerInterfaces};
+pub use player_origin::{OriginFilter, PlayerOrigin};
+pub use event_stamp::{EventStamp, EventStamper, Stamped};
+pub use orchestrator::{ApplyAckHandle, DisplayObserver, DndScope, DndState, NotifyPolicy, Orchestrator, OrchestratorControl, RouteOverride};
 
 // Export driver abstraction
-pub use driver::{FsctDriver, LocalDriver};
+pub use driver::FsctDriver;
+#[cfg(feature = "usb")]
+pub use driver::LocalDriver;
+pub use driver_middleware::{DriverInterceptor, InterceptedDriver, StateUpdate};
 
 // Export device management types
-pub use device_manager::{DeviceManager, DeviceManagement, DeviceControl, ManagedDeviceId, DeviceEvent, DeviceManagerError};
+pub use device_manager::{DeviceControl, ManagedDeviceId, DeviceEvent, DeviceManagerError};
+#[cfg(feature = "usb")]
+pub use device_manager::{DeviceManager, DeviceManagement};
+#[cfg(feature = "usb")]
 pub use usb_device_watch::run_usb_device_watch;
 pub use service::{ServiceHandle, StopHandle, spawn_service, MultiServiceHandle};
 
+#[cfg(feature = "usb")]
 pub use nusb::DeviceId;
@@ -0,0 +1,234 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Error;
+use log::{debug, info, warn};
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use tokio::task::JoinHandle;
+
+use super::{FsctNetworkInterface, NetworkDeviceInfo, FSCT_DEVICE_SERVICE_TYPE};
+use crate::device_manager::{DeviceManager, ManagedDeviceId};
+use crate::service::{spawn_service, ServiceHandle};
+use crate::descriptor_dump::DeviceDescriptorDump;
+use crate::usb::descriptor_utils::{dump_fsct_descriptor_set, parse_fsct_descriptor_set};
+use crate::usb::fsct_device::FsctDevice;
+use crate::usb::protocol::FsctProtocol;
+
+/// Time a device has to accept the session and send its hello.
+pub const NETWORK_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Time between attempts to open a session with an announced device, and between checks whether a device left alone
+/// (excluded by the device filter or attached over USB) can be attached.
+pub const NETWORK_RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Detaches the device from the manager when the session ends, also when its task is aborted.
+struct AttachedDevice<'a> {
+    device_manager: &'a DeviceManager,
+    managed_id: ManagedDeviceId,
+}
+
+impl Drop for AttachedDevice<'_> {
+    fn drop(&mut self) {
+        if self.device_manager.detach_network_device(self.managed_id).is_some() {
+            info!("Network device {} removed", self.managed_id);
+        }
+    }
+}
+
+/// Opens a session with the device at `address` and keeps it attached to the device manager until the session ends.
+async fn attach_over_session(device_manager: &DeviceManager, address: SocketAddr) -> Result<(), Error> {
+    let (interface, hello) = FsctNetworkInterface::connect(address, NETWORK_CONNECT_TIMEOUT).await?;
+    let protocol = FsctProtocol::negotiate(hello.protocol)?;
+    let descriptors = parse_fsct_descriptor_set(&hello.descriptors)?;
+    let device_info = NetworkDeviceInfo::new(address, &hello);
+    let mut closed = interface.subscribe_closed();
+    let mut device = FsctDevice::new(interface);
+    device.set_protocol(protocol);
+    let fsct = dump_fsct_descriptor_set(&hello.descriptors);
+    device.set_descriptor_dump(DeviceDescriptorDump { bos: Vec::new(), fsct });
+    device.init(&descriptors);
+    let device = Arc::new(device);
+
+    let managed_id = loop {
+        if let Some(managed_id) = device_manager.attach_network_device(device.clone(), &device_info).await? {
+            break managed_id;
+        }
+        tokio::select! {
+            _ = closed.wait_for(|closed| *closed) => return Ok(()),
+            _ = tokio::time::sleep(NETWORK_RECONNECT_INTERVAL) => {}
+        }
+    };
+    let _attached = AttachedDevice { device_manager, managed_id };
+    info!("Network device with Ferrum Streaming Control Technology capability found: \"{}\" ({:04X}:{:04X}) at {}",
+          device_info.product.as_deref().unwrap_or("Unknown"), device_info.vendor_id, device_info.product_id,
+          address);
+    let _ = closed.wait_for(|closed| *closed).await;
+    Ok(())
+}
+
+/// Keeps a session with the announced device, reopening it every [`NETWORK_RECONNECT_INTERVAL`] until aborted.
+async fn run_device_session(device_manager: Arc<DeviceManager>, address: SocketAddr) {
+    loop {
+        if let Err(e) = attach_over_session(&device_manager, address).await {
+            warn!("Failed to attach network device at {}: {}", address, e);
+        }
+        tokio::time::sleep(NETWORK_RECONNECT_INTERVAL).await;
+    }
+}
+
+/// Address to open the session with, IPv4 preferred.
+fn session_address(info: &ServiceInfo) -> Option<SocketAddr> {
+    let addresses = info.get_addresses();
+    let address = addresses.iter().find(|address| address.is_ipv4()).or_else(|| addresses.iter().next())?;
+    Some(SocketAddr::new(*address, info.get_port()))
+}
+
+/// Browses for [`FSCT_DEVICE_SERVICE_TYPE`] instances and attaches the announced devices to the device manager
+/// until the service is stopped. Devices are removed when their session ends or their announcement is withdrawn.
+pub fn run_network_device_watch(device_manager: Arc<DeviceManager>) -> Result<ServiceHandle, Error> {
+    let daemon = ServiceDaemon::new()?;
+    let browser = daemon.browse(FSCT_DEVICE_SERVICE_TYPE)?;
+
+    Ok(spawn_service(move |mut stop| async move {
+        let mut sessions: HashMap<String, JoinHandle<()>> = HashMap::new();
+        loop {
+            let event = tokio::select! {
+                _ = stop.signaled() => break,
+                event = browser.recv_async() => match event {
+                    Ok(event) => event,
+                    Err(_) => {
+                        warn!("mDNS browsing for network devices ended");
+                        break;
+                    }
+                },
+            };
+            match event {
+                ServiceEvent::ServiceResolved(info) => {
+                    let fullname = info.get_fullname().to_string();
+                    if sessions.get(&fullname).is_some_and(|session| !session.is_finished()) {
+                        continue;
+                    }
+                    let Some(address) = session_address(&info) else {
+                        debug!("Network device {} announced without an address", fullname);
+                        continue;
+                    };
+                    debug!("Network device {} announced at {}", fullname, address);
+                    sessions.insert(fullname, tokio::spawn(run_device_session(device_manager.clone(), address)));
+                }
+                ServiceEvent::ServiceRemoved(_, fullname) => {
+                    if let Some(session) = sessions.remove(&fullname) {
+                        debug!("Network device {} withdrew its announcement", fullname);
+                        session.abort();
+                    }
+                }
+                _ => {}
+            }
+        }
+        for session in sessions.into_values() {
+            session.abort();
+        }
+        if let Err(e) = daemon.stop_browse(FSCT_DEVICE_SERVICE_TYPE) {
+            debug!("Failed to stop browsing for network devices: {}", e);
+        }
+        if let Err(e) = daemon.shutdown() {
+            warn!("Failed to stop mDNS daemon: {}", e);
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::FsctStatus;
+    use crate::device_manager::{DeviceControl, DeviceEvent};
+    use crate::network::frame::{read_frame, write_frame, DeviceHello, Frame, FrameKind};
+    use crate::usb::protocol::FSCT_PROTOCOL_V1;
+    use crate::usb::requests::FsctRequestCode;
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+
+    /// Device announcing the status functionality, acknowledging every request and reporting the `Out` ones.
+    async fn run_fake_device(listener: TcpListener, requests: mpsc::UnboundedSender<Frame>) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let hello = DeviceHello {
+            vendor_id: 0x1234,
+            product_id: 0x5678,
+            firmware_version: 0x0100,
+            protocol: FSCT_PROTOCOL_V1,
+            serial_number: Some("NET-1".into()),
+            manufacturer: None,
+            product: Some("Hallway Display".into()),
+            descriptors: vec![0x05, 0x31, 0x05, 0x00, 0x04],
+        };
+        let payload = hello.encode();
+        let hello = Frame { kind: FrameKind::Hello, length: payload.len() as u16, payload,
+                            ..Frame::input(0, 0, 0, 0, 0) };
+        write_frame(&mut stream, &hello).await.unwrap();
+        while let Ok(request) = read_frame(&mut stream).await {
+            let response = match request.kind {
+                FrameKind::In => vec![0u8; request.length as usize],
+                _ => Vec::new(),
+            };
+            let ack = Frame { kind: FrameKind::Ack, length: response.len() as u16, payload: response,
+                              ..request.clone() };
+            write_frame(&mut stream, &ack).await.unwrap();
+            if request.kind == FrameKind::Out && requests.send(request).is_err() {
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn network_devices_are_attached_for_the_session() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (request_sender, mut requests) = mpsc::unbounded_channel();
+        let fake_device = tokio::spawn(run_fake_device(listener, request_sender));
+
+        let device_manager = Arc::new(DeviceManager::new());
+        let mut events = device_manager.subscribe();
+        let session = tokio::spawn({
+            let device_manager = device_manager.clone();
+            async move { attach_over_session(&device_manager, address).await }
+        });
+
+        let managed_id = loop {
+            if let DeviceEvent::Added(managed_id) = events.recv().await.unwrap() {
+                break managed_id;
+            }
+        };
+        assert_eq!(device_manager.attach_record(managed_id).unwrap().product.as_deref(), Some("Hallway Display"));
+        // warm-up enabled the device and reset its status
+        assert_eq!(requests.recv().await.unwrap().request, FsctRequestCode::Enable as u8);
+        let status = requests.recv().await.unwrap();
+        assert_eq!((status.request, status.value), (FsctRequestCode::Status as u8, FsctStatus::Stopped as u16));
+
+        device_manager.set_status(managed_id, FsctStatus::Playing).await.unwrap();
+        let status = requests.recv().await.unwrap();
+        assert_eq!((status.request, status.value), (FsctRequestCode::Status as u8, FsctStatus::Playing as u16));
+
+        // the device going away ends the session and removes it
+        fake_device.abort();
+        session.await.unwrap().unwrap();
+        assert!(matches!(events.recv().await.unwrap(), DeviceEvent::Removed(id) if id == managed_id));
+    }
+}
@@ -0,0 +1,239 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Framing of FSCT requests over a TCP session.
+//!
+//! Every frame starts with a 9-byte header: kind (`u8`), sequence number (`u8`), request code (`u8`), then `value`,
+//! `index` and `length` as little-endian `u16`. `request`, `value` and `index` carry what the USB control request
+//! would carry in `bRequest`, `wValue` and `wIndex` (without the interface number). The header is followed by
+//! `length` bytes of payload, except for [`FrameKind::In`], where `length` is the wanted response length.
+
+use std::io;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+pub const FRAME_HEADER_LENGTH: usize = 9;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameKind {
+    /// Host to device: request with data, like a control OUT transfer; answered by `Ack` or `Stall`.
+    Out = 0x01,
+    /// Host to device: request for data, like a control IN transfer; answered by `Ack` with the data or `Stall`.
+    In = 0x02,
+    /// Device to host, first frame of the session: the [`DeviceHello`].
+    Hello = 0x81,
+    /// Device to host: the request with the same sequence number completed, with the response data of `In`.
+    Ack = 0x82,
+    /// Device to host: the request with the same sequence number was rejected.
+    Stall = 0x83,
+    /// Device to host, unsolicited: a command report, see [`FsctCommandCode`](crate::usb::requests::FsctCommandCode).
+    Command = 0x84,
+}
+
+impl FrameKind {
+    pub fn from_raw(raw: u8) -> Option<Self> {
+        match raw {
+            0x01 => Some(Self::Out),
+            0x02 => Some(Self::In),
+            0x81 => Some(Self::Hello),
+            0x82 => Some(Self::Ack),
+            0x83 => Some(Self::Stall),
+            0x84 => Some(Self::Command),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub kind: FrameKind,
+    pub sequence: u8,
+    pub request: u8,
+    pub value: u16,
+    pub index: u16,
+    /// Response length wanted by an `In` frame; the payload length of the others.
+    pub length: u16,
+    pub payload: Vec<u8>,
+}
+
+impl Frame {
+    pub fn out(sequence: u8, request: u8, value: u16, index: u16, payload: &[u8]) -> io::Result<Self> {
+        let length = u16::try_from(payload.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Frame payload is too long"))?;
+        Ok(Self { kind: FrameKind::Out, sequence, request, value, index, length, payload: payload.to_vec() })
+    }
+
+    pub fn input(sequence: u8, request: u8, value: u16, index: u16, length: u16) -> Self {
+        Self { kind: FrameKind::In, sequence, request, value, index, length, payload: Vec::new() }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity(FRAME_HEADER_LENGTH + self.payload.len());
+        raw.push(self.kind as u8);
+        raw.push(self.sequence);
+        raw.push(self.request);
+        raw.extend_from_slice(&self.value.to_le_bytes());
+        raw.extend_from_slice(&self.index.to_le_bytes());
+        raw.extend_from_slice(&self.length.to_le_bytes());
+        raw.extend_from_slice(&self.payload);
+        raw
+    }
+}
+
+pub async fn read_frame(reader: &mut (impl AsyncRead + Unpin)) -> io::Result<Frame> {
+    let mut header = [0u8; FRAME_HEADER_LENGTH];
+    reader.read_exact(&mut header).await?;
+    let kind = FrameKind::from_raw(header[0]).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("Unknown frame kind {:#04x}", header[0]))
+    })?;
+    let length = u16::from_le_bytes([header[7], header[8]]);
+    let mut payload = vec![0u8; if kind == FrameKind::In { 0 } else { length as usize }];
+    reader.read_exact(&mut payload).await?;
+    Ok(Frame {
+        kind,
+        sequence: header[1],
+        request: header[2],
+        value: u16::from_le_bytes([header[3], header[4]]),
+        index: u16::from_le_bytes([header[5], header[6]]),
+        length,
+        payload,
+    })
+}
+
+pub async fn write_frame(writer: &mut (impl AsyncWrite + Unpin), frame: &Frame) -> io::Result<()> {
+    writer.write_all(&frame.encode()).await?;
+    writer.flush().await
+}
+
+/// What a device tells about itself in the payload of its `Hello` frame: `vendor_id`, `product_id` and
+/// `firmware_version` (BCD) as little-endian `u16`, the FSCT protocol version (`u8`), the serial number,
+/// manufacturer and product as strings prefixed with their `u8` length (empty if unknown), then the FSCT
+/// functionality descriptor with its subordinate descriptors as a USB device returns it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceHello {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub firmware_version: u16,
+    pub protocol: u8,
+    pub serial_number: Option<String>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub descriptors: Vec<u8>,
+}
+
+impl DeviceHello {
+    pub fn decode(payload: &[u8]) -> io::Result<Self> {
+        let mut reader = PayloadReader(payload);
+        Ok(Self {
+            vendor_id: reader.u16()?,
+            product_id: reader.u16()?,
+            firmware_version: reader.u16()?,
+            protocol: reader.u8()?,
+            serial_number: reader.string()?,
+            manufacturer: reader.string()?,
+            product: reader.string()?,
+            descriptors: reader.0.to_vec(),
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut raw = Vec::new();
+        raw.extend_from_slice(&self.vendor_id.to_le_bytes());
+        raw.extend_from_slice(&self.product_id.to_le_bytes());
+        raw.extend_from_slice(&self.firmware_version.to_le_bytes());
+        raw.push(self.protocol);
+        for string in [&self.serial_number, &self.manufacturer, &self.product] {
+            let string = string.as_deref().unwrap_or_default().as_bytes();
+            let string = &string[..string.len().min(u8::MAX as usize)];
+            raw.push(string.len() as u8);
+            raw.extend_from_slice(string);
+        }
+        raw.extend_from_slice(&self.descriptors);
+        raw
+    }
+}
+
+struct PayloadReader<'a>(&'a [u8]);
+
+impl PayloadReader<'_> {
+    fn take(&mut self, length: usize) -> io::Result<&[u8]> {
+        if self.0.len() < length {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Device hello is too short"));
+        }
+        let (taken, rest) = self.0.split_at(length);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        let raw = self.take(2)?;
+        Ok(u16::from_le_bytes([raw[0], raw[1]]))
+    }
+
+    fn string(&mut self) -> io::Result<Option<String>> {
+        let length = self.u8()? as usize;
+        let raw = self.take(length)?;
+        Ok((!raw.is_empty()).then(|| String::from_utf8_lossy(raw).into_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn frames_round_trip() {
+        let out = Frame::out(7, 0x10, 0, 0x0300, b"Artist").unwrap();
+        let input = Frame::input(8, 0x02, 0, 0, 8);
+        let mut raw = out.encode();
+        raw.extend(input.encode());
+        assert_eq!(&raw[..FRAME_HEADER_LENGTH], &[0x01, 7, 0x10, 0, 0, 0x00, 0x03, 6, 0]);
+
+        let mut reader = raw.as_slice();
+        assert_eq!(read_frame(&mut reader).await.unwrap(), out);
+        // the length of an `In` frame is the wanted response length, no payload follows
+        assert_eq!(read_frame(&mut reader).await.unwrap(), input);
+        assert!(reader.is_empty());
+
+        let mut unknown = [0u8; FRAME_HEADER_LENGTH];
+        unknown[0] = 0x42;
+        let error = read_frame(&mut unknown.as_slice()).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn device_hello_round_trips() {
+        let hello = DeviceHello {
+            vendor_id: 0x1234,
+            product_id: 0x5678,
+            firmware_version: 0x0102,
+            protocol: 0x01,
+            serial_number: Some("A1B2".into()),
+            manufacturer: None,
+            product: Some("Hallway Display".into()),
+            descriptors: vec![0x05, 0x40, 0x01, 0x00, 0x00],
+        };
+        let raw = hello.encode();
+        assert_eq!(DeviceHello::decode(&raw).unwrap(), hello);
+        assert!(DeviceHello::decode(&raw[..8]).is_err());
+    }
+}
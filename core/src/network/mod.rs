@@ -0,0 +1,72 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! FSCT devices on the LAN, e.g. streamers and displays without a USB connection to the host.
+//!
+//! Devices announce themselves as [`FSCT_DEVICE_SERVICE_TYPE`] instances over mDNS. The host opens a TCP session with
+//! each of them, in which the device first sends its hello (identity and FSCT descriptors, see [`DeviceHello`]) and
+//! then answers the FSCT requests the host sends in [`frame`]s, one at a time. Devices are added to the
+//! [`DeviceManager`](crate::DeviceManager) with the same IDs and events as USB devices, so the orchestrator drives
+//! them the same way. See docs/network_devices.md for the protocol.
+
+use std::net::SocketAddr;
+
+use crate::device_manager::ManagedDeviceId;
+use crate::device_uuid_calculator::calculate_uuid;
+
+mod discovery;
+pub mod frame;
+mod session;
+
+pub use discovery::{run_network_device_watch, NETWORK_CONNECT_TIMEOUT, NETWORK_RECONNECT_INTERVAL};
+pub use frame::DeviceHello;
+pub use session::FsctNetworkInterface;
+
+/// DNS-SD service type of FSCT devices on the LAN.
+pub const FSCT_DEVICE_SERVICE_TYPE: &str = "_fsct._tcp.local.";
+
+/// Identity of a network device, from its hello.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkDeviceInfo {
+    pub address: SocketAddr,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// Device release number, BCD like bcdDevice.
+    pub firmware_version: u16,
+    pub serial_number: Option<String>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+}
+
+impl NetworkDeviceInfo {
+    pub fn new(address: SocketAddr, hello: &DeviceHello) -> Self {
+        Self {
+            address,
+            vendor_id: hello.vendor_id,
+            product_id: hello.product_id,
+            firmware_version: hello.firmware_version,
+            serial_number: hello.serial_number.clone(),
+            manufacturer: hello.manufacturer.clone(),
+            product: hello.product.clone(),
+        }
+    }
+
+    /// ID of the device in the device manager, the same as the device would get over USB.
+    pub fn managed_id(&self) -> ManagedDeviceId {
+        calculate_uuid(self.vendor_id, self.product_id, self.serial_number.as_deref().unwrap_or(""))
+    }
+}
@@ -0,0 +1,323 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+use std::io;
+use std::mem::size_of;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::anyhow;
+use log::debug;
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use super::frame::{read_frame, write_frame, DeviceHello, Frame, FrameKind};
+use crate::definitions::{FsctNotification, FsctStatus, FsctTextMetadata, UsbRequestTimeouts};
+#[cfg(feature = "vendor-requests")]
+use crate::definitions::VendorRequest;
+use crate::usb::errors::FsctDeviceError;
+use crate::usb::requests;
+
+/// Command reports buffered until the command watch reads them.
+const COMMAND_QUEUE_LENGTH: usize = 16;
+
+type PendingResponse = Arc<Mutex<Option<(u8, oneshot::Sender<Frame>)>>>;
+
+/// Write half of the session with what is needed to start the next request.
+struct Exchange {
+    writer: OwnedWriteHalf,
+    sequence: u8,
+    last_transfer: Option<Instant>,
+}
+
+/// FSCT requests of a device over a TCP session, one at a time like the control endpoint of a USB device.
+///
+/// `index` carries the text metadata id in its upper byte like `wIndex` does, the lower byte (the interface number on
+/// USB) is 0. Structured request data is little-endian.
+pub struct FsctNetworkInterface {
+    address: SocketAddr,
+    exchange: tokio::sync::Mutex<Exchange>,
+    pending: PendingResponse,
+    commands: tokio::sync::Mutex<mpsc::Receiver<Vec<u8>>>,
+    closed: watch::Receiver<bool>,
+    reader: JoinHandle<()>,
+    timeouts: Mutex<UsbRequestTimeouts>,
+    write_delay: Mutex<Duration>,
+}
+
+fn request_error(error: impl Into<anyhow::Error>) -> FsctDeviceError {
+    FsctDeviceError::NetworkRequestError(error.into())
+}
+
+/// Routes responses to the pending request and command reports to the command queue, until the session ends.
+async fn read_session(mut reader: tokio::net::tcp::OwnedReadHalf, address: SocketAddr, pending: PendingResponse,
+                      commands: mpsc::Sender<Vec<u8>>, closed: watch::Sender<bool>) {
+    loop {
+        let frame = match read_frame(&mut reader).await {
+            Ok(frame) => frame,
+            Err(e) => {
+                debug!("Session with {} ended: {}", address, e);
+                break;
+            }
+        };
+        match frame.kind {
+            FrameKind::Ack | FrameKind::Stall => {
+                let mut pending = pending.lock().unwrap();
+                match pending.take() {
+                    Some((sequence, response)) if sequence == frame.sequence => {
+                        let _ = response.send(frame);
+                    }
+                    // response to a request that timed out
+                    other => *pending = other,
+                }
+            }
+            FrameKind::Command => {
+                if commands.try_send(frame.payload).is_err() {
+                    debug!("Dropped command report of {}, the queue is full", address);
+                }
+            }
+            kind => debug!("Ignoring unexpected {:?} frame of {}", kind, address),
+        }
+    }
+    pending.lock().unwrap().take();
+    let _ = closed.send(true);
+}
+
+impl FsctNetworkInterface {
+    /// Opens a session with the device at `address`, returning it with the hello of the device.
+    pub async fn connect(address: SocketAddr, timeout: Duration) -> Result<(Self, DeviceHello), FsctDeviceError> {
+        let (stream, hello) = tokio::time::timeout(timeout, async {
+            let mut stream = TcpStream::connect(address).await?;
+            stream.set_nodelay(true)?;
+            let hello = read_frame(&mut stream).await?;
+            if hello.kind != FrameKind::Hello {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                          format!("Expected a hello, got a {:?} frame", hello.kind)));
+            }
+            Ok((stream, DeviceHello::decode(&hello.payload)?))
+        }).await.map_err(|_| FsctDeviceError::NetworkRequestTimeout(timeout))?.map_err(request_error)?;
+
+        let (reader, writer) = stream.into_split();
+        let pending = PendingResponse::default();
+        let (command_sender, command_receiver) = mpsc::channel(COMMAND_QUEUE_LENGTH);
+        let (closed_sender, closed) = watch::channel(false);
+        let reader = tokio::spawn(read_session(reader, address, pending.clone(), command_sender, closed_sender));
+        let interface = Self {
+            address,
+            exchange: tokio::sync::Mutex::new(Exchange { writer, sequence: 0, last_transfer: None }),
+            pending,
+            commands: tokio::sync::Mutex::new(command_receiver),
+            closed,
+            reader,
+            timeouts: Mutex::new(UsbRequestTimeouts::default()),
+            write_delay: Mutex::new(Duration::ZERO),
+        };
+        Ok((interface, hello))
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Receiver turning `true` when the device closes the session.
+    pub fn subscribe_closed(&self) -> watch::Receiver<bool> {
+        self.closed.clone()
+    }
+
+    pub fn timeouts(&self) -> UsbRequestTimeouts {
+        *self.timeouts.lock().unwrap()
+    }
+
+    pub fn set_timeouts(&self, timeouts: UsbRequestTimeouts) {
+        *self.timeouts.lock().unwrap() = timeouts;
+    }
+
+    /// Sets the minimum time between consecutive requests, for devices that can't keep up with back-to-back ones.
+    pub fn set_write_delay(&self, write_delay: Duration) {
+        *self.write_delay.lock().unwrap() = write_delay;
+    }
+
+    /// Sends the request built for the next sequence number once the write delay has passed and waits for the
+    /// response, returning its data and how long the exchange took, not counting the write delay.
+    async fn exchange(&self, timeout: Duration, build: impl FnOnce(u8) -> io::Result<Frame>)
+                      -> Result<(Vec<u8>, Duration), FsctDeviceError> {
+        let mut exchange = self.exchange.lock().await;
+        let write_delay = *self.write_delay.lock().unwrap();
+        if let Some(last_transfer) = exchange.last_transfer {
+            tokio::time::sleep_until(last_transfer + write_delay).await;
+        }
+        exchange.sequence = exchange.sequence.wrapping_add(1);
+        let frame = build(exchange.sequence).map_err(request_error)?;
+        let (response_sender, response) = oneshot::channel();
+        *self.pending.lock().unwrap() = Some((frame.sequence, response_sender));
+
+        let started = Instant::now();
+        let writer = &mut exchange.writer;
+        let result = tokio::time::timeout(timeout, async {
+            write_frame(writer, &frame).await?;
+            response.await.map_err(|_| io::Error::new(io::ErrorKind::ConnectionAborted, "Session closed"))
+        }).await;
+        let elapsed = started.elapsed();
+        exchange.last_transfer = Some(Instant::now());
+        self.pending.lock().unwrap().take();
+
+        let response = result.map_err(|_| FsctDeviceError::NetworkRequestTimeout(timeout))?.map_err(request_error)?;
+        match response.kind {
+            FrameKind::Ack => Ok((response.payload, elapsed)),
+            _ => Err(request_error(anyhow!("Device stalled request {:#04x}", frame.request))),
+        }
+    }
+
+    async fn send_out(&self, timeout: Duration, request: requests::FsctRequestCode, value: u16, index: u16,
+                      data: &[u8]) -> Result<(), FsctDeviceError> {
+        self.exchange(timeout, |sequence| Frame::out(sequence, request as u8, value, index, data)).await?;
+        Ok(())
+    }
+
+    async fn receive_in(&self, request: requests::FsctRequestCode, length: usize) -> Result<Vec<u8>, FsctDeviceError> {
+        let (data, _) = self.exchange(self.timeouts().control,
+                                      |sequence| Ok(Frame::input(sequence, request as u8, 0, 0, length as u16))).await?;
+        if data.len() != length {
+            return Err(FsctDeviceError::DataSizeMismatch { expected: length, actual: data.len() });
+        }
+        Ok(data)
+    }
+
+    pub async fn get_device_timestamp(&self) -> Result<requests::Timestamp, FsctDeviceError> {
+        let raw = self.receive_in(requests::FsctRequestCode::Timestamp, size_of::<requests::Timestamp>()).await?;
+        Ok(requests::Timestamp::from_le_bytes(raw.try_into().unwrap()))
+    }
+
+    pub async fn get_error_report(&self) -> Result<requests::ErrorReportRequestData, FsctDeviceError> {
+        let raw = self.receive_in(requests::FsctRequestCode::ErrorReport,
+                                  size_of::<requests::ErrorReportRequestData>()).await?;
+        Ok(requests::ErrorReportRequestData {
+            code: u16::from_le_bytes([raw[0], raw[1]]),
+            detail: u16::from_le_bytes([raw[2], raw[3]]),
+            count: u16::from_le_bytes([raw[4], raw[5]]),
+        })
+    }
+
+    pub async fn get_firmware_version(&self) -> Result<u16, FsctDeviceError> {
+        let raw = self.receive_in(requests::FsctRequestCode::FirmwareVersion, 2).await?;
+        Ok(u16::from_le_bytes([raw[0], raw[1]]))
+    }
+
+    pub async fn get_enable(&self) -> Result<bool, FsctDeviceError> {
+        let raw = self.receive_in(requests::FsctRequestCode::Enable, 1).await?;
+        Ok(raw[0] != 0)
+    }
+
+    pub async fn set_enable(&self, enable: bool) -> Result<(), FsctDeviceError> {
+        self.send_out(self.timeouts().control, requests::FsctRequestCode::Enable, enable as u16, 0, &[]).await
+    }
+
+    /// Waits for the next command report the device sends; there is no timeout, as on USB.
+    pub async fn read_command_report(&self) -> Result<Vec<u8>, FsctDeviceError> {
+        self.commands.lock().await.recv().await
+            .ok_or_else(|| request_error(anyhow!("Session with {} closed", self.address)))
+    }
+
+    /// Network devices have no level endpoint yet.
+    #[cfg(feature = "audio-levels")]
+    pub fn takes_audio_levels(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "audio-levels")]
+    pub async fn send_level_report(&self, _report: &[u8]) -> Result<(), FsctDeviceError> {
+        Err(FsctDeviceError::AudioLevelsNotSupported)
+    }
+
+    /// Sends the empty `poll` request, returning how long the exchange took, not counting the write delay.
+    pub async fn send_poll(&self) -> Result<Duration, FsctDeviceError> {
+        let poll = requests::FsctRequestCode::Poll as u8;
+        let (_, elapsed) = self.exchange(self.timeouts().control,
+                                         |sequence| Frame::out(sequence, poll, 0, 0, &[])).await?;
+        Ok(elapsed)
+    }
+
+    pub async fn send_notify(&self, notification: FsctNotification) -> Result<(), FsctDeviceError> {
+        self.send_out(self.timeouts().control, requests::FsctRequestCode::Notify, notification as u16, 0, &[]).await
+    }
+
+    #[cfg(feature = "vendor-requests")]
+    pub async fn send_vendor_request(&self, vendor_request: &VendorRequest) -> Result<Vec<u8>, FsctDeviceError> {
+        let (data, _) = self.exchange(self.timeouts().control, |sequence| {
+            if vendor_request.response_length == 0 {
+                Frame::out(sequence, vendor_request.request, vendor_request.value, 0, &vendor_request.payload)
+            } else {
+                Ok(Frame::input(sequence, vendor_request.request, vendor_request.value, 0,
+                                vendor_request.response_length))
+            }
+        }).await?;
+        Ok(data)
+    }
+
+    pub async fn send_track_progress(&self, progress: &requests::TrackProgressRequestData)
+                                     -> Result<(), FsctDeviceError> {
+        let progress = *progress;
+        let mut data = Vec::with_capacity(size_of::<requests::TrackProgressRequestData>());
+        data.extend_from_slice(&{ progress.duration }.to_le_bytes());
+        data.extend_from_slice(&{ progress.position }.to_le_bytes());
+        data.extend_from_slice(&{ progress.timestamp }.to_le_bytes());
+        data.extend_from_slice(&{ progress.rate }.to_le_bytes());
+        self.send_out(self.timeouts().progress, requests::FsctRequestCode::Progress, 0, 0, &data).await
+    }
+
+    pub async fn disable_track_progress(&self) -> Result<(), FsctDeviceError> {
+        self.send_out(self.timeouts().progress, requests::FsctRequestCode::Progress, 0, 0, &[]).await
+    }
+
+    pub async fn send_current_text(&self, text_id: FsctTextMetadata, text_raw: &[u8]) -> Result<(), FsctDeviceError> {
+        self.send_out(self.timeouts().text, requests::FsctRequestCode::CurrentText, 0, (text_id as u16) << 8,
+                      text_raw).await
+    }
+
+    pub async fn disable_current_text(&self, text_id: FsctTextMetadata) -> Result<(), FsctDeviceError> {
+        self.send_out(self.timeouts().text, requests::FsctRequestCode::CurrentText, 0, (text_id as u16) << 8,
+                      &[]).await
+    }
+
+    pub async fn send_status(&self, status: FsctStatus) -> Result<(), FsctDeviceError> {
+        self.send_out(self.timeouts().status, requests::FsctRequestCode::Status, status as u16, 0, &[]).await
+    }
+
+    pub async fn send_volume(&self, value: u16) -> Result<(), FsctDeviceError> {
+        self.send_out(self.timeouts().status, requests::FsctRequestCode::Volume, value, 0, &[]).await
+    }
+
+    pub async fn send_playback_modes(&self, value: u16) -> Result<(), FsctDeviceError> {
+        self.send_out(self.timeouts().status, requests::FsctRequestCode::PlaybackModes, value, 0, &[]).await
+    }
+
+    /// Sends a firmware update request, see [`dfu`](crate::usb::dfu).
+    pub async fn send_dfu_request(&self, request: requests::FsctRequestCode, value: u16, data: &[u8],
+                                  timeout: Duration) -> Result<(), FsctDeviceError> {
+        self.send_out(timeout, request, value, 0, data).await
+    }
+}
+
+impl Drop for FsctNetworkInterface {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
@@ -16,88 +16,549 @@
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
 use std::cmp::{PartialOrd};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use anyhow::anyhow;
 use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
 use tokio::select;
-use tokio::sync::broadcast;
-use crate::definitions::{FsctStatus, FsctTextMetadata, TimelineInfo};
-use crate::device_manager::{DeviceEvent, DeviceManager, ManagedDeviceId};
-use crate::device_manager::DeviceControl;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::time::{sleep_until, Instant};
+use crate::aux_content::{AuxContentRegistry, AuxRotation};
+use crate::time_format::TimeFormat;
+use crate::definitions::{FsctNotification, FsctStatus, FsctTextMetadata, PlaybackCommand, PlaybackModes, TimelineInfo, VolumeInfo};
+use crate::device_manager::{DeviceEvent, ManagedDeviceId};
+#[cfg(feature = "usb")]
+use crate::device_manager::{DeviceControl, DeviceManager};
 use crate::player_events::PlayerEvent;
+use crate::player_event_queue::PlayerEventReceiver;
+use crate::player_interface::PlayerInterfaces;
+use crate::player_origin::{OriginFilter, PlayerOrigin, PlayerOrigins};
 use crate::player_manager::ManagedPlayerId;
 use crate::player_state::PlayerState;
-use crate::player_state_applier::{DirectDeviceControlApplier, PlayerStateApplier};
+use crate::player_state_applier::PlayerStateApplier;
+#[cfg(feature = "usb")]
+use crate::player_state_applier::DirectDeviceControlApplier;
+#[cfg(feature = "usb")]
+use crate::device_write_queue::DeviceWriteQueue;
 use crate::service::{ServiceHandle, spawn_service};
+use crate::usage_stats::UsageStats;
+
+/// Follows what devices show, e.g. for statistics or announcements; called after every event the orchestrator
+/// processed.
+pub trait DisplayObserver: Send + Sync {
+    /// The device shows `state` of the player with self id `source`.
+    fn observe(&self, device_id: ManagedDeviceId, source: Option<&str>, state: &PlayerState);
+
+    /// The device shows no playback (nothing routed, auxiliary content or switched off).
+    fn device_idle(&self, device_id: ManagedDeviceId);
+
+    fn device_detached(&self, device_id: ManagedDeviceId);
+}
+
+/// How long a buffering player keeps counting as playing for selection, by default.
+pub const DEFAULT_BUFFERING_GRACE: Duration = Duration::from_secs(10);
+
+/// How long a paused player stays shown before devices switch to another playing one, by default; zero switches
+/// right away.
+pub const DEFAULT_STICKY_SOURCE: Duration = Duration::ZERO;
 
 #[derive(Debug, Clone, Default)]
 struct RegisteredPlayer {
+    self_id: String,
     assigned_device: Option<ManagedDeviceId>,
     state: PlayerState,
     is_assigned_device_attached: bool,
+    // While buffering, the player counts as playing until then
+    buffering_grace_until: Option<Instant>,
+    // After it stopped playing, devices showing the player keep it until then
+    sticky_until: Option<Instant>,
+    origin: PlayerOrigin,
+}
+
+impl RegisteredPlayer {
+    fn set_status(&mut self, status: FsctStatus, buffering_grace: Duration, sticky_source: Duration) {
+        let was_playing = self.is_playing();
+        if status != FsctStatus::Buffering {
+            self.buffering_grace_until = None;
+        } else if self.state.status != FsctStatus::Buffering {
+            self.buffering_grace_until = Instant::now().checked_add(buffering_grace);
+        }
+        self.state.status = status;
+        if self.is_playing() {
+            self.sticky_until = None;
+        } else if was_playing && !sticky_source.is_zero() {
+            self.sticky_until = Instant::now().checked_add(sticky_source);
+        }
+    }
+
+    fn is_sticky(&self, now: Instant) -> bool {
+        self.sticky_until.is_some_and(|t| t > now)
+    }
+
+    fn is_playing(&self) -> bool {
+        self.state.status == FsctStatus::Playing || self.buffering_grace_until.is_some()
+    }
 }
 
 #[derive(Debug, Clone, Default)]
 struct ConnectedDevice {
     player_id: Option<ManagedPlayerId>,
     requires_update: bool,
+    // Since when the device shows no playback, tracked for devices with an idle timeout
+    idle_since: Option<Instant>,
+    disabled_by_idle: bool,
+    // Auxiliary content rotation while idle
+    showing_aux: bool,
+    aux_index: usize,
+    aux_next: Option<Instant>,
+}
+
+
+/// Lets callers wait until routing decisions have actually reached devices.
+///
+/// Obtained from [`Orchestrator::ack_handle`] before the orchestrator is started.
+#[derive(Debug, Clone)]
+pub struct ApplyAckHandle {
+    ack_tx: mpsc::UnboundedSender<oneshot::Sender<()>>,
+}
+
+impl ApplyAckHandle {
+    /// Resolves once every player and device event emitted before this call has been processed
+    /// and all resulting applies have completed.
+    pub async fn wait_applied(&self) -> Result<(), anyhow::Error> {
+        let (done_tx, done_rx) = oneshot::channel();
+        self.ack_tx.send(done_tx).map_err(|_| anyhow!("Orchestrator is not running"))?;
+        done_rx.await.map_err(|_| anyhow!("Orchestrator stopped before acknowledging applied events"))
+    }
 }
 
+/// Transient "force this player on this device" routing, taking precedence over assignments and selection rules.
+///
+/// Unlike an assignment it is never persisted; it ends when the player stops or unregisters, the device is
+/// removed, the optional timeout passes or it is cleared explicitly.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteOverride {
+    pub player_id: ManagedPlayerId,
+    pub device_id: ManagedDeviceId,
+    /// Time left until the override expires, `None` if it only ends with the player stopping.
+    #[serde(with = "crate::serde_format::optional_duration_secs")]
+    pub remaining: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ActiveOverride {
+    player_id: ManagedPlayerId,
+    expires_at: Option<Instant>,
+}
+
+/// Scope of a do-not-disturb switch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "scope", content = "device_id", rename_all = "snake_case")]
+pub enum DndScope {
+    Global,
+    Device(ManagedDeviceId),
+}
+
+/// Active do-not-disturb switches.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DndState {
+    pub global: bool,
+    pub devices: Vec<ManagedDeviceId>,
+}
+
+/// When a device is asked for an attention signal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotifyPolicy {
+    /// Signal when the title shown on the device changes.
+    pub on_track_change: bool,
+    /// Signal when a player gets assigned to the device.
+    pub on_assignment: bool,
+    pub notification: FsctNotification,
+}
+
+enum ControlCommand {
+    ForceRoute {
+        player_id: ManagedPlayerId,
+        device_id: ManagedDeviceId,
+        duration: Option<Duration>,
+        done: oneshot::Sender<Result<(), anyhow::Error>>,
+    },
+    ClearRoute { device_id: ManagedDeviceId, done: oneshot::Sender<bool> },
+    ListRoutes(oneshot::Sender<Vec<RouteOverride>>),
+    SetDnd { scope: DndScope, enabled: bool, done: oneshot::Sender<()> },
+    GetDnd(oneshot::Sender<DndState>),
+    SetIdleTimeout { device_id: ManagedDeviceId, timeout: Option<Duration>, done: oneshot::Sender<()> },
+    SetNotifyPolicy { device_id: ManagedDeviceId, policy: Option<NotifyPolicy>, done: oneshot::Sender<()> },
+    SetAuxRotation { device_id: ManagedDeviceId, rotation: Option<AuxRotation>, done: oneshot::Sender<()> },
+    SetStickySource { window: Duration, done: oneshot::Sender<()> },
+    SetPauseOnDisconnect { enabled: bool, done: oneshot::Sender<()> },
+    SetTimeFormat { format: TimeFormat, done: oneshot::Sender<()> },
+    SetOriginFilter { device_id: ManagedDeviceId, filter: Option<OriginFilter>, done: oneshot::Sender<()> },
+}
+
+/// Controls transient route overrides and do-not-disturb of a running orchestrator.
+///
+/// Obtained from [`Orchestrator::control`] before the orchestrator is started.
+#[derive(Debug, Clone)]
+pub struct OrchestratorControl {
+    control_tx: mpsc::UnboundedSender<ControlCommand>,
+    dnd: Arc<Mutex<DndState>>,
+}
+
+impl std::fmt::Debug for ControlCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ControlCommand::ForceRoute { player_id, device_id, duration, .. } => {
+                write!(f, "ForceRoute({} -> {}, {:?})", player_id, device_id, duration)
+            }
+            ControlCommand::ClearRoute { device_id, .. } => write!(f, "ClearRoute({})", device_id),
+            ControlCommand::ListRoutes(_) => write!(f, "ListRoutes"),
+            ControlCommand::SetDnd { scope, enabled, .. } => write!(f, "SetDnd({:?}, {})", scope, enabled),
+            ControlCommand::GetDnd(_) => write!(f, "GetDnd"),
+            ControlCommand::SetIdleTimeout { device_id, timeout, .. } => {
+                write!(f, "SetIdleTimeout({}, {:?})", device_id, timeout)
+            }
+            ControlCommand::SetNotifyPolicy { device_id, policy, .. } => {
+                write!(f, "SetNotifyPolicy({}, {:?})", device_id, policy)
+            }
+            ControlCommand::SetAuxRotation { device_id, rotation, .. } => {
+                write!(f, "SetAuxRotation({}, {:?})", device_id, rotation)
+            }
+            ControlCommand::SetStickySource { window, .. } => write!(f, "SetStickySource({:?})", window),
+            ControlCommand::SetPauseOnDisconnect { enabled, .. } => write!(f, "SetPauseOnDisconnect({})", enabled),
+            ControlCommand::SetTimeFormat { format, .. } => write!(f, "SetTimeFormat({:?})", format),
+            ControlCommand::SetOriginFilter { device_id, filter, .. } => {
+                write!(f, "SetOriginFilter({}, {:?})", device_id, filter)
+            }
+        }
+    }
+}
+
+impl OrchestratorControl {
+    /// Shows the player on the device right away, replacing any previous override of that device.
+    ///
+    /// Fails if the player is not registered or the device is not connected.
+    pub async fn force_route(&self, player_id: ManagedPlayerId, device_id: ManagedDeviceId,
+                             duration: Option<Duration>) -> Result<(), anyhow::Error> {
+        let (done, done_rx) = oneshot::channel();
+        self.send(ControlCommand::ForceRoute { player_id, device_id, duration, done })?;
+        done_rx.await.map_err(|_| anyhow!("Orchestrator stopped before handling the route override"))?
+    }
+
+    /// Removes the override of the device; returns whether there was one.
+    pub async fn clear_route(&self, device_id: ManagedDeviceId) -> Result<bool, anyhow::Error> {
+        let (done, done_rx) = oneshot::channel();
+        self.send(ControlCommand::ClearRoute { device_id, done })?;
+        done_rx.await.map_err(|_| anyhow!("Orchestrator stopped before clearing the route override"))
+    }
+
+    /// Currently active overrides.
+    pub async fn routes(&self) -> Result<Vec<RouteOverride>, anyhow::Error> {
+        let (done, done_rx) = oneshot::channel();
+        self.send(ControlCommand::ListRoutes(done))?;
+        done_rx.await.map_err(|_| anyhow!("Orchestrator stopped before listing route overrides"))
+    }
+
+    /// Suspends (or resumes) all writes to the devices in scope.
+    ///
+    /// Routing keeps tracking player state while suspended; lifting the switch resyncs the full state.
+    /// A device stays suspended while either the global or its own switch is on.
+    pub async fn set_dnd(&self, scope: DndScope, enabled: bool) -> Result<(), anyhow::Error> {
+        let (done, done_rx) = oneshot::channel();
+        self.send(ControlCommand::SetDnd { scope, enabled, done })?;
+        done_rx.await.map_err(|_| anyhow!("Orchestrator stopped before changing do-not-disturb"))
+    }
+
+    /// Whether writes to the device are suspended by do-not-disturb, for writers bypassing the orchestrator (e.g. of
+    /// audio levels); unlike [`OrchestratorControl::dnd`] answered without waiting for the orchestrator.
+    pub fn is_suspended(&self, device_id: ManagedDeviceId) -> bool {
+        let dnd = self.dnd.lock().unwrap();
+        dnd.global || dnd.devices.contains(&device_id)
+    }
+
+    /// Currently active do-not-disturb switches.
+    pub async fn dnd(&self) -> Result<DndState, anyhow::Error> {
+        let (done, done_rx) = oneshot::channel();
+        self.send(ControlCommand::GetDnd(done))?;
+        done_rx.await.map_err(|_| anyhow!("Orchestrator stopped before reporting do-not-disturb"))
+    }
+
+    /// Sets the idle policy of the device: its display is switched off after showing no playback for `timeout`
+    /// and switched back on as soon as playback resumes. `None` keeps the display always on.
+    pub async fn set_idle_timeout(&self, device_id: ManagedDeviceId, timeout: Option<Duration>)
+        -> Result<(), anyhow::Error> {
+        let (done, done_rx) = oneshot::channel();
+        self.send(ControlCommand::SetIdleTimeout { device_id, timeout, done })?;
+        done_rx.await.map_err(|_| anyhow!("Orchestrator stopped before changing the idle timeout"))
+    }
+
+    /// Sets when the device is asked for an attention signal; `None` disables signals.
+    pub async fn set_notify_policy(&self, device_id: ManagedDeviceId, policy: Option<NotifyPolicy>)
+        -> Result<(), anyhow::Error> {
+        let (done, done_rx) = oneshot::channel();
+        self.send(ControlCommand::SetNotifyPolicy { device_id, policy, done })?;
+        done_rx.await.map_err(|_| anyhow!("Orchestrator stopped before changing the notify policy"))
+    }
+
+    /// Sets which auxiliary content (clock, weather, ...) the device rotates through while it shows no playback;
+    /// `None` leaves idle devices as they are.
+    pub async fn set_aux_rotation(&self, device_id: ManagedDeviceId, rotation: Option<AuxRotation>)
+        -> Result<(), anyhow::Error> {
+        let (done, done_rx) = oneshot::channel();
+        self.send(ControlCommand::SetAuxRotation { device_id, rotation, done })?;
+        done_rx.await.map_err(|_| anyhow!("Orchestrator stopped before changing the auxiliary content rotation"))
+    }
+
+    /// Sets how long devices keep showing a player that paused before switching to another playing one; zero
+    /// switches right away. See [`Orchestrator::with_sticky_source`].
+    pub async fn set_sticky_source(&self, window: Duration) -> Result<(), anyhow::Error> {
+        let (done, done_rx) = oneshot::channel();
+        self.send(ControlCommand::SetStickySource { window, done })?;
+        done_rx.await.map_err(|_| anyhow!("Orchestrator stopped before changing the sticky source window"))
+    }
+
+    /// Sets whether players assigned to a device are paused when it disconnects. See
+    /// [`Orchestrator::with_pause_on_disconnect`].
+    pub async fn set_pause_on_disconnect(&self, enabled: bool) -> Result<(), anyhow::Error> {
+        let (done, done_rx) = oneshot::channel();
+        self.send(ControlCommand::SetPauseOnDisconnect { enabled, done })?;
+        done_rx.await.map_err(|_| anyhow!("Orchestrator stopped before changing pause on disconnect"))
+    }
+
+    /// Sets the format of clocks on devices whose auxiliary content rotation sets none. See
+    /// [`Orchestrator::with_time_format`].
+    pub async fn set_time_format(&self, format: TimeFormat) -> Result<(), anyhow::Error> {
+        let (done, done_rx) = oneshot::channel();
+        self.send(ControlCommand::SetTimeFormat { format, done })?;
+        done_rx.await.map_err(|_| anyhow!("Orchestrator stopped before changing the time format"))
+    }
+
+    /// Limits the players the device shows to those of the origins the filter accepts, e.g. living-room devices
+    /// only showing players of living-room hosts; `None` accepts players of any origin. Route overrides are not
+    /// filtered.
+    pub async fn set_origin_filter(&self, device_id: ManagedDeviceId, filter: Option<OriginFilter>)
+        -> Result<(), anyhow::Error> {
+        let (done, done_rx) = oneshot::channel();
+        self.send(ControlCommand::SetOriginFilter { device_id, filter, done })?;
+        done_rx.await.map_err(|_| anyhow!("Orchestrator stopped before changing the origin filter"))
+    }
+
+    fn send(&self, command: ControlCommand) -> Result<(), anyhow::Error> {
+        self.control_tx.send(command).map_err(|_| anyhow!("Orchestrator is not running"))
+    }
+}
 
 /// Orchestrator subscribes to PlayerManager and DeviceManager events
 /// and applies routing policy to update devices using a PlayerStateApplier.
 pub struct Orchestrator<A: PlayerStateApplier> {
     // Receivers
-    player_rx: broadcast::Receiver<PlayerEvent>,
+    player_rx: PlayerEventReceiver,
     device_rx: broadcast::Receiver<DeviceEvent>,
 
     // Applier that performs device I/O
     applier: Arc<A>,
 
+    // Pending acknowledgements, answered once no more events are queued
+    ack_tx: mpsc::UnboundedSender<oneshot::Sender<()>>,
+    ack_rx: mpsc::UnboundedReceiver<oneshot::Sender<()>>,
+
+    // Route override commands
+    control_tx: mpsc::UnboundedSender<ControlCommand>,
+    control_rx: mpsc::UnboundedReceiver<ControlCommand>,
+
     // Routing state
     players: HashMap<ManagedPlayerId, RegisteredPlayer>,
 
     connected_devices: HashMap<ManagedDeviceId, Mutex<ConnectedDevice>>,
     // Selection memory
     preferred_player: Option<ManagedPlayerId>, // user-preferred player for general group
+    route_overrides: HashMap<ManagedDeviceId, ActiveOverride>,
+
+    // Do-not-disturb switches; suspended devices get no writes
+    dnd_global: bool,
+    dnd_devices: HashSet<ManagedDeviceId>,
+    // the switches as published to controls
+    dnd_shared: Arc<Mutex<DndState>>,
+
+    // Per-device idle timeouts after which displays are switched off
+    idle_timeouts: HashMap<ManagedDeviceId, Duration>,
+
+    // Per-device attention signal triggers
+    notify_policies: HashMap<ManagedDeviceId, NotifyPolicy>,
+
+    // Auxiliary content shown on idle devices
+    aux_content: Option<Arc<AuxContentRegistry>>,
+    aux_rotations: HashMap<ManagedDeviceId, AuxRotation>,
+    time_format: TimeFormat,
+
+    // Followers of what devices show, e.g. usage statistics
+    display_observers: Vec<Arc<dyn DisplayObserver>>,
+
+    // How long buffering players are still selected like playing ones
+    buffering_grace: Duration,
+
+    // How long devices keep showing a player that stopped playing
+    sticky_source: Duration,
+
+    // Controls of players, carrying out playback commands of devices
+    player_interfaces: Option<Arc<PlayerInterfaces>>,
+
+    // Whether playing players assigned to a device are paused when it disconnects
+    pause_on_disconnect: bool,
+    // Devices whose firmware is updated; they disconnect to restart, which doesn't pause players
+    updating_devices: HashSet<ManagedDeviceId>,
+
+    // Origins of players and the origins devices accept
+    player_origins: Option<Arc<PlayerOrigins>>,
+    origin_filters: HashMap<ManagedDeviceId, OriginFilter>,
 }
 
 impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
     /// Create orchestrator with a custom PlayerStateApplier and a device events receiver.
     pub fn new_with_applier(
-        player_rx: broadcast::Receiver<PlayerEvent>,
+        player_rx: PlayerEventReceiver,
         device_rx: broadcast::Receiver<DeviceEvent>,
         applier: Arc<A>,
     ) -> Self {
+        let (ack_tx, ack_rx) = mpsc::unbounded_channel();
+        let (control_tx, control_rx) = mpsc::unbounded_channel();
         Self {
             player_rx,
             device_rx,
             applier,
+            ack_tx,
+            ack_rx,
+            control_tx,
+            control_rx,
             players: HashMap::new(),
             connected_devices: HashMap::new(),
             preferred_player: None,
+            route_overrides: HashMap::new(),
+            dnd_global: false,
+            dnd_devices: HashSet::new(),
+            dnd_shared: Arc::new(Mutex::new(DndState::default())),
+            idle_timeouts: HashMap::new(),
+            notify_policies: HashMap::new(),
+            aux_content: None,
+            aux_rotations: HashMap::new(),
+            time_format: TimeFormat::default(),
+            display_observers: Vec::new(),
+            buffering_grace: DEFAULT_BUFFERING_GRACE,
+            sticky_source: DEFAULT_STICKY_SOURCE,
+            player_interfaces: None,
+            pause_on_disconnect: false,
+            updating_devices: HashSet::new(),
+            player_origins: None,
+            origin_filters: HashMap::new(),
         }
     }
+
+    /// Uses the registry's providers for auxiliary content rotations; the registry has to be run separately.
+    pub fn with_aux_content(mut self, registry: Arc<AuxContentRegistry>) -> Self {
+        self.aux_content = Some(registry);
+        self
+    }
+
+    /// Writes clocks of auxiliary content in the format, unless the rotation of the device sets its own; see
+    /// [`AuxRotation::time_format`].
+    pub fn with_time_format(mut self, format: TimeFormat) -> Self {
+        self.time_format = format;
+        self
+    }
+
+    /// Feeds what is shown on devices to the usage statistics.
+    pub fn with_usage_stats(self, stats: Arc<UsageStats>) -> Self {
+        self.with_display_observer(stats)
+    }
+
+    /// Sets how long a buffering player keeps counting as playing before it is demoted in player selection.
+    pub fn with_buffering_grace(mut self, grace: Duration) -> Self {
+        self.buffering_grace = grace;
+        self
+    }
+
+    /// Keeps a player shown on devices for `window` after it paused or stopped, instead of switching to another
+    /// playing player right away, so displays don't flap during track skips. Devices switch once the window expires,
+    /// the player is unregistered or the user picks a player (preferred player, assignments, route overrides).
+    pub fn with_sticky_source(mut self, window: Duration) -> Self {
+        self.sticky_source = window;
+        self
+    }
+
+    /// Carries out playback commands of devices through the interfaces of the players they show; without
+    /// interfaces the commands are dropped.
+    pub fn with_player_interfaces(mut self, interfaces: Arc<PlayerInterfaces>) -> Self {
+        self.player_interfaces = Some(interfaces);
+        self
+    }
+
+    /// Pauses playing players assigned to a device when it disconnects, e.g. headphone amplifiers with a display
+    /// whose unplugging should stop playback. Needs [`with_player_interfaces`](Self::with_player_interfaces); players
+    /// without an interface keep playing.
+    pub fn with_pause_on_disconnect(mut self, enabled: bool) -> Self {
+        self.pause_on_disconnect = enabled;
+        self
+    }
+
+    /// Takes the origins of players from the registry when they register, for origin filters of devices; without
+    /// it all players are local.
+    pub fn with_player_origins(mut self, origins: Arc<PlayerOrigins>) -> Self {
+        self.player_origins = Some(origins);
+        self
+    }
+
+    /// Feeds what is shown on devices to the observer.
+    pub fn with_display_observer(mut self, observer: Arc<dyn DisplayObserver>) -> Self {
+        self.display_observers.push(observer);
+        self
+    }
 }
 
-impl Orchestrator<DirectDeviceControlApplier<DeviceManager>> {
-    /// Create orchestrator using a DeviceManager directly (DirectDeviceControlApplier).
+#[cfg(feature = "usb")]
+impl Orchestrator<DirectDeviceControlApplier<DeviceWriteQueue<DeviceManager>>> {
+    /// Create orchestrator writing to a DeviceManager through a DeviceWriteQueue (DirectDeviceControlApplier).
     pub fn with_device_manager(
-        player_rx: broadcast::Receiver<PlayerEvent>,
+        player_rx: PlayerEventReceiver,
         device_manager: Arc<DeviceManager>,
     ) -> Self {
-        let applier = Arc::new(DirectDeviceControlApplier::new(device_manager.clone()));
+        let write_queue = Arc::new(DeviceWriteQueue::new(device_manager.clone()));
+        let applier = Arc::new(DirectDeviceControlApplier::new(write_queue.clone()));
+        let failed_applier = Arc::downgrade(&applier);
+        write_queue.on_write_failed(move |device_id| {
+            if let Some(applier) = failed_applier.upgrade() {
+                applier.mark_requires_update(device_id);
+            }
+        });
         let device_rx = device_manager.subscribe();
         Self::new_with_applier(player_rx, device_rx, applier)
     }
 }
 
 impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
+    /// Handle for waiting until emitted events have been applied to devices.
+    pub fn ack_handle(&self) -> ApplyAckHandle {
+        ApplyAckHandle { ack_tx: self.ack_tx.clone() }
+    }
+
+    /// Applier performing device I/O for this orchestrator.
+    pub fn applier(&self) -> Arc<A> {
+        self.applier.clone()
+    }
+
+    /// Handle for forcing transient routes while the orchestrator runs.
+    pub fn control(&self) -> OrchestratorControl {
+        OrchestratorControl { control_tx: self.control_tx.clone(), dnd: self.dnd_shared.clone() }
+    }
+
     /// Spawn the orchestrator event loop in background and return a handle.
     pub fn run(mut self) -> ServiceHandle {
         spawn_service(move |mut stop_handle| async move {
             loop {
+                let next_deadline = self.next_deadline();
                 select! {
                     biased;
                     _ = stop_handle.signaled() => {
@@ -118,25 +579,31 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
                     }
                     recv_res = self.player_rx.recv() => {
                         match recv_res {
-                            Ok(evt) => self.on_player_event(evt).await,
-                            Err(broadcast::error::RecvError::Lagged(n)) => {
-                                warn!("PlayerEvent lagged by {} messages; catching up", n);
-                            }
-                            Err(broadcast::error::RecvError::Closed) => {
-                                info!("PlayerEvent channel closed; stopping orchestrator");
+                            Some(evt) => self.on_player_event(evt).await,
+                            None => {
+                                info!("PlayerEvent queues closed; stopping orchestrator");
                                 break;
                             }
                         }
                     }
+                    Some(command) = self.control_rx.recv() => self.on_control_command(command).await,
+                    _ = sleep_until(next_deadline.unwrap_or_else(Instant::now)), if next_deadline.is_some() => {
+                        self.on_deadline().await;
+                    }
+                    // polled last, so it's only answered when all earlier events have been handled
+                    Some(ack) = self.ack_rx.recv() => {
+                        let _ = ack.send(());
+                    }
                 }
+                self.notify_display_observers();
             }
         })
     }
 
     async fn on_player_event(&mut self, evt: PlayerEvent) {
         match evt {
-            PlayerEvent::Registered { player_id, .. } => {
-                self.handle_player_registered(player_id).await;
+            PlayerEvent::Registered { player_id, self_id } => {
+                self.handle_player_registered(player_id, self_id).await;
             }
             PlayerEvent::Unregistered { player_id } => {
                 self.handle_player_unregistered(player_id).await;
@@ -156,12 +623,20 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
             PlayerEvent::TimelineUpdated { player_id, timeline } => {
                 self.handle_player_timeline_updated(player_id, timeline).await;
             }
+            PlayerEvent::VolumeUpdated { player_id, volume } => {
+                self.handle_player_volume_updated(player_id, volume).await;
+            }
+            PlayerEvent::ModesUpdated { player_id, modes } => {
+                self.handle_player_modes_updated(player_id, modes).await;
+            }
             PlayerEvent::TextMetadataUpdated { player_id, metadata, text } => {
                 self.handle_player_text_metadata_updated(player_id, metadata, text).await;
             }
             PlayerEvent::PreferredChanged { preferred } => {
                 self.handle_preferred_changed(preferred).await;
             }
+            // derived from the state updates handled above
+            PlayerEvent::TrackStarted { .. } | PlayerEvent::TrackEnded { .. } => {}
         }
     }
 
@@ -173,13 +648,434 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
             DeviceEvent::Removed(device_id) => {
                 self.handle_device_removed(device_id).await;
             }
+            DeviceEvent::DeviceCommand { device_id, command } => {
+                self.handle_device_command(device_id, command);
+            }
+            DeviceEvent::VolumeChanged { device_id, volume } => {
+                self.handle_device_command(device_id, PlaybackCommand::SetVolume { volume });
+            }
+            DeviceEvent::FirmwareUpdate { device_id, updating } => {
+                if updating {
+                    self.updating_devices.insert(device_id);
+                } else {
+                    self.updating_devices.remove(&device_id);
+                }
+            }
+            // reported for diagnostics; routing is not affected
+            DeviceEvent::DeviceError { .. } | DeviceEvent::WarmupStep { .. } | DeviceEvent::WatchInterrupted => {}
+        }
+    }
+
+    /// Passes the command to the player the device shows, without waiting for the player to carry it out.
+    fn handle_device_command(&self, device_id: ManagedDeviceId, command: PlaybackCommand) {
+        let player_id = self.connected_devices.get(&device_id).and_then(|device| device.lock().unwrap().player_id);
+        let Some(player_id) = player_id else {
+            debug!("Device {} sent {:?} while showing no player", device_id, command);
+            return;
+        };
+        let Some(interfaces) = self.player_interfaces.clone() else { return };
+        tokio::spawn(async move {
+            if let Err(e) = interfaces.execute(player_id, command).await {
+                warn!("Player {} failed to carry out {:?} from device {}: {}", player_id, command, device_id, e);
+            }
+        });
+    }
+
+    /// Pauses the player assigned to the disconnected device, without waiting for the player to carry it out.
+    fn pause_player(&self, player_id: ManagedPlayerId, device_id: ManagedDeviceId) {
+        let Some(interfaces) = self.player_interfaces.clone() else { return };
+        if interfaces.get(player_id).is_none() {
+            debug!("Player {} assigned to disconnected device {} can't be paused", player_id, device_id);
+            return;
+        }
+        info!("Pausing player {}, its device {} disconnected", player_id, device_id);
+        tokio::spawn(async move {
+            if let Err(e) = interfaces.execute(player_id, PlaybackCommand::Pause).await {
+                warn!("Player {} failed to pause after device {} disconnected: {}", player_id, device_id, e);
+            }
+        });
+    }
+
+    async fn on_control_command(&mut self, command: ControlCommand) {
+        match command {
+            ControlCommand::ForceRoute { player_id, device_id, duration, done } => {
+                let result = self.handle_force_route(player_id, device_id, duration).await;
+                let _ = done.send(result);
+            }
+            ControlCommand::ClearRoute { device_id, done } => {
+                let cleared = self.route_overrides.remove(&device_id).is_some();
+                if cleared {
+                    info!("Route override of device {} cleared", device_id);
+                    self.release_sticky_players();
+                    self.update_selected_players_for_devices();
+                    self.apply_on_devices_requiring_update().await;
+                }
+                let _ = done.send(cleared);
+            }
+            ControlCommand::ListRoutes(done) => {
+                let now = Instant::now();
+                let routes = self.route_overrides
+                                 .iter()
+                                 .map(|(device_id, o)| RouteOverride {
+                                     player_id: o.player_id,
+                                     device_id: *device_id,
+                                     remaining: o.expires_at.map(|t| t.saturating_duration_since(now)),
+                                 })
+                                 .collect();
+                let _ = done.send(routes);
+            }
+            ControlCommand::SetDnd { scope, enabled, done } => {
+                self.handle_set_dnd(scope, enabled).await;
+                let _ = done.send(());
+            }
+            ControlCommand::GetDnd(done) => {
+                let _ = done.send(self.dnd_shared.lock().unwrap().clone());
+            }
+            ControlCommand::SetIdleTimeout { device_id, timeout, done } => {
+                self.handle_set_idle_timeout(device_id, timeout).await;
+                let _ = done.send(());
+            }
+            ControlCommand::SetNotifyPolicy { device_id, policy, done } => {
+                debug!("Notify policy of device {}: {:?}", device_id, policy);
+                match policy {
+                    Some(policy) => self.notify_policies.insert(device_id, policy),
+                    None => self.notify_policies.remove(&device_id),
+                };
+                let _ = done.send(());
+            }
+            ControlCommand::SetAuxRotation { device_id, rotation, done } => {
+                self.handle_set_aux_rotation(device_id, rotation).await;
+                let _ = done.send(());
+            }
+            ControlCommand::SetPauseOnDisconnect { enabled, done } => {
+                info!("Pause on disconnect: {}", enabled);
+                self.pause_on_disconnect = enabled;
+                let _ = done.send(());
+            }
+            ControlCommand::SetTimeFormat { format, done } => {
+                debug!("Time format: {:?}", format);
+                // clocks already shown are rewritten with their next rotation
+                self.time_format = format;
+                let _ = done.send(());
+            }
+            ControlCommand::SetOriginFilter { device_id, filter, done } => {
+                info!("Origin filter of device {}: {:?}", device_id, filter);
+                match filter {
+                    Some(filter) => self.origin_filters.insert(device_id, filter),
+                    None => self.origin_filters.remove(&device_id),
+                };
+                self.update_selected_players_for_devices();
+                self.apply_on_devices_requiring_update().await;
+                let _ = done.send(());
+            }
+            ControlCommand::SetStickySource { window, done } => {
+                info!("Sticky source window: {:?}", window);
+                self.sticky_source = window;
+                if window.is_zero() {
+                    self.release_sticky_players();
+                    self.update_selected_players_for_devices();
+                    self.apply_on_devices_requiring_update().await;
+                }
+                let _ = done.send(());
+            }
+        }
+    }
+
+    async fn handle_set_idle_timeout(&mut self, device_id: ManagedDeviceId, timeout: Option<Duration>) {
+        info!("Idle timeout of device {}: {:?}", device_id, timeout);
+        match timeout {
+            Some(timeout) => {
+                self.idle_timeouts.insert(device_id, timeout);
+            }
+            None => {
+                self.idle_timeouts.remove(&device_id);
+                let was_disabled = self.connected_devices.get(&device_id).is_some_and(|device| {
+                    let mut device = device.lock().unwrap();
+                    device.idle_since = None;
+                    std::mem::take(&mut device.disabled_by_idle)
+                });
+                if was_disabled && !self.is_suspended(&device_id) {
+                    self.applier.apply_enable(device_id, true).await.ok();
+                }
+            }
+        }
+        self.update_idle_displays().await;
+    }
+
+    async fn handle_set_aux_rotation(&mut self, device_id: ManagedDeviceId, rotation: Option<AuxRotation>) {
+        debug!("Auxiliary content rotation of device {}: {:?}", device_id, rotation);
+        match rotation {
+            Some(rotation) => {
+                self.aux_rotations.insert(device_id, rotation);
+            }
+            None => {
+                self.aux_rotations.remove(&device_id);
+            }
+        }
+        if let Some(device) = self.connected_devices.get(&device_id) {
+            let mut device = device.lock().unwrap();
+            device.aux_next = None;
+            device.aux_index = 0;
+            // bring back the regular state if auxiliary content is on the display
+            device.requires_update |= device.showing_aux;
+        }
+        self.apply_on_devices_requiring_update().await;
+    }
+
+    /// Requests an attention signal from the device if its policy enables the trigger.
+    async fn notify_device(&self, device_id: ManagedDeviceId, trigger: fn(&NotifyPolicy) -> bool) {
+        let Some(policy) = self.notify_policies.get(&device_id) else { return };
+        if !trigger(policy) || !self.connected_devices.contains_key(&device_id) || self.is_suspended(&device_id) {
+            return;
         }
+        self.applier.apply_notify(device_id, policy.notification).await.ok();
+    }
+
+    /// Signals track changes on all devices currently showing the player.
+    async fn notify_track_change(&self, player_id: ManagedPlayerId) {
+        let showing: Vec<ManagedDeviceId> = self.connected_devices
+                                                .iter()
+                                                .filter(|(_, device)| device.lock().unwrap().player_id == Some(player_id))
+                                                .map(|(device_id, _)| *device_id)
+                                                .collect();
+        for device_id in showing {
+            self.notify_device(device_id, |policy| policy.on_track_change).await;
+        }
+    }
+
+    fn is_showing_playback(&self, device: &ConnectedDevice) -> bool {
+        device.player_id
+              .and_then(|id| self.players.get(&id))
+              .is_some_and(RegisteredPlayer::is_playing)
+    }
+
+    /// Applies idle policies: switches displays off once their idle timeout passed and back on when they show
+    /// playback again, and rotates auxiliary content onto idle displays.
+    async fn update_idle_displays(&self) {
+        let now = Instant::now();
+        for (device_id, device) in self.connected_devices.iter() {
+            let timeout = self.idle_timeouts.get(device_id);
+            let rotation = self.aux_rotations.get(device_id).filter(|r| !r.providers.is_empty());
+            if (timeout.is_none() && rotation.is_none()) || self.is_suspended(device_id) {
+                continue;
+            }
+            let action = {
+                let mut device = device.lock().unwrap();
+                if self.is_showing_playback(&device) {
+                    device.idle_since = None;
+                    device.aux_next = None;
+                    std::mem::take(&mut device.disabled_by_idle).then_some(IdleAction::Enable)
+                } else {
+                    let idle_since = *device.idle_since.get_or_insert(now);
+                    if device.disabled_by_idle {
+                        None
+                    } else if let Some(timeout) = timeout && now >= idle_since + *timeout {
+                        device.disabled_by_idle = true;
+                        Some(IdleAction::Disable)
+                    } else if let Some(rotation) = rotation
+                        && now >= *device.aux_next.get_or_insert(idle_since + rotation.start_after) {
+                        let provider = rotation.providers[device.aux_index % rotation.providers.len()].clone();
+                        device.aux_index = device.aux_index.wrapping_add(1);
+                        device.aux_next = Some(now + rotation.interval);
+                        Some(IdleAction::ShowAux(provider))
+                    } else {
+                        None
+                    }
+                }
+            };
+            match action {
+                Some(IdleAction::Enable) => {
+                    debug!("Idle policy: device {} display on", device_id);
+                    self.applier.apply_enable(*device_id, true).await.ok();
+                }
+                Some(IdleAction::Disable) => {
+                    debug!("Idle policy: device {} display off", device_id);
+                    self.applier.apply_enable(*device_id, false).await.ok();
+                }
+                Some(IdleAction::ShowAux(provider)) => {
+                    let content = self.aux_content.as_ref().and_then(|registry| registry.latest(&provider));
+                    if let Some(content) = content {
+                        debug!("Idle policy: device {} shows {}", device_id, provider);
+                        device.lock().unwrap().showing_aux = true;
+                        let time_format = rotation.and_then(|r| r.time_format).unwrap_or(self.time_format);
+                        self.applier.apply_to_device(*device_id, &content.to_player_state(&time_format)).await.ok();
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+
+    fn next_idle_deadline(&self) -> Option<Instant> {
+        self.connected_devices
+            .iter()
+            .filter_map(|(device_id, device)| {
+                let device = device.lock().unwrap();
+                let idle_since = device.idle_since?;
+                if device.disabled_by_idle {
+                    return None;
+                }
+                let disable_at = self.idle_timeouts.get(device_id).map(|timeout| idle_since + *timeout);
+                let aux_at = self.aux_rotations
+                                 .get(device_id)
+                                 .filter(|r| !r.providers.is_empty())
+                                 .map(|r| device.aux_next.unwrap_or(idle_since + r.start_after));
+                disable_at.into_iter().chain(aux_at).min()
+            })
+            .min()
+    }
+
+    async fn handle_set_dnd(&mut self, scope: DndScope, enabled: bool) {
+        info!("Do-not-disturb {:?} {}", scope, if enabled { "enabled" } else { "lifted" });
+        let was_suspended: Vec<ManagedDeviceId> =
+            self.connected_devices.keys().filter(|id| self.is_suspended(id)).copied().collect();
+        match scope {
+            DndScope::Global => self.dnd_global = enabled,
+            DndScope::Device(device_id) if enabled => {
+                self.dnd_devices.insert(device_id);
+            }
+            DndScope::Device(device_id) => {
+                self.dnd_devices.remove(&device_id);
+            }
+        }
+        *self.dnd_shared.lock().unwrap() =
+            DndState { global: self.dnd_global, devices: self.dnd_devices.iter().copied().collect() };
+        // writes skipped while suspended are not tracked one by one, so resumed devices get a full resync
+        for device_id in was_suspended.iter().filter(|id| !self.is_suspended(id)) {
+            if let Some(device) = self.connected_devices.get(device_id) {
+                device.lock().unwrap().requires_update = true;
+            }
+        }
+        self.apply_on_devices_requiring_update().await;
+    }
+
+    fn is_suspended(&self, device_id: &ManagedDeviceId) -> bool {
+        self.dnd_global || self.dnd_devices.contains(device_id)
+    }
+
+    fn notify_display_observers(&self) {
+        if self.display_observers.is_empty() {
+            return;
+        }
+        for (device_id, device) in self.connected_devices.iter() {
+            let device = device.lock().unwrap();
+            let player = device.player_id.as_ref().and_then(|id| self.players.get(id));
+            for observer in &self.display_observers {
+                match player {
+                    Some(player) if !self.is_suspended(device_id) && !device.showing_aux
+                        && !device.disabled_by_idle => {
+                        observer.observe(*device_id, Some(&player.self_id), &player.state);
+                    }
+                    _ => observer.device_idle(*device_id),
+                }
+            }
+        }
+    }
+
+    async fn handle_force_route(&mut self, player_id: ManagedPlayerId, device_id: ManagedDeviceId,
+                                duration: Option<Duration>) -> Result<(), anyhow::Error> {
+        if !self.players.contains_key(&player_id) {
+            return Err(anyhow!("Player {} is not registered", player_id));
+        }
+        if !self.connected_devices.contains_key(&device_id) {
+            return Err(anyhow!("Device {} is not connected", device_id));
+        }
+        let expires_at = match duration {
+            Some(d) => {
+                Some(Instant::now().checked_add(d).ok_or_else(|| anyhow!("Route override of {:?} is too long", d))?)
+            }
+            None => None,
+        };
+        info!("Route override: player {} -> device {} for {:?}", player_id, device_id, duration);
+        self.route_overrides.insert(device_id, ActiveOverride { player_id, expires_at });
+        self.release_sticky_players();
+        self.update_selected_players_for_devices();
+        self.apply_on_devices_requiring_update().await;
+        Ok(())
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        let next_expiry = self.route_overrides.values().filter_map(|o| o.expires_at).min();
+        let next_demotion = self.players.values().filter_map(|p| p.buffering_grace_until).min();
+        let next_release = self.players.values().filter_map(|p| p.sticky_until).min();
+        next_expiry.into_iter().chain(next_demotion).chain(next_release).chain(self.next_idle_deadline()).min()
+    }
+
+    async fn on_deadline(&mut self) {
+        self.expire_overrides().await;
+        self.demote_buffering_players().await;
+        self.end_expired_stickiness().await;
+        self.update_idle_displays().await;
+    }
+
+    /// Lets devices switch away from players paused for longer than the sticky source window.
+    async fn end_expired_stickiness(&mut self) {
+        let now = Instant::now();
+        let mut released = false;
+        for player in self.players.values_mut() {
+            if player.sticky_until.is_some_and(|t| t <= now) {
+                player.sticky_until = None;
+                released = true;
+            }
+        }
+        if released {
+            self.update_selected_players_for_devices();
+            self.apply_on_devices_requiring_update().await;
+        }
+    }
+
+    /// Ends the stickiness of all players, when the user picked what devices show.
+    fn release_sticky_players(&mut self) {
+        for player in self.players.values_mut() {
+            player.sticky_until = None;
+        }
+    }
+
+    /// Stops counting players buffering for longer than the grace period as playing.
+    async fn demote_buffering_players(&mut self) {
+        let now = Instant::now();
+        let mut demoted = false;
+        for (player_id, player) in self.players.iter_mut() {
+            if player.buffering_grace_until.is_some_and(|t| t <= now) {
+                debug!("Player {} keeps buffering, no longer selected as playing", player_id);
+                player.buffering_grace_until = None;
+                demoted = true;
+            }
+        }
+        if demoted {
+            self.update_selected_players_for_devices();
+            self.apply_on_devices_requiring_update().await;
+        }
+    }
+
+    async fn expire_overrides(&mut self) {
+        let now = Instant::now();
+        let before = self.route_overrides.len();
+        self.route_overrides.retain(|device_id, o| {
+            let expired = o.expires_at.is_some_and(|t| t <= now);
+            if expired {
+                info!("Route override of device {} expired", device_id);
+            }
+            !expired
+        });
+        if before != self.route_overrides.len() {
+            self.update_selected_players_for_devices();
+            self.apply_on_devices_requiring_update().await;
+        }
+    }
+
+    /// Drops overrides of the player, e.g. when it stopped; returns whether any was dropped.
+    fn end_player_overrides(&mut self, player_id: ManagedPlayerId) -> bool {
+        let before = self.route_overrides.len();
+        self.route_overrides.retain(|_, o| o.player_id != player_id);
+        before != self.route_overrides.len()
     }
 
     // Dedicated handlers for PlayerEvent variants
-    async fn handle_player_registered(&mut self, player_id: ManagedPlayerId) {
+    async fn handle_player_registered(&mut self, player_id: ManagedPlayerId, self_id: String) {
         debug!("Player registered: {}", player_id);
-        self.players.insert(player_id, RegisteredPlayer::default());
+        let origin = self.player_origins.as_ref().map(|origins| origins.get(player_id)).unwrap_or_default();
+        self.players.insert(player_id, RegisteredPlayer { self_id, origin, ..Default::default() });
         // do nothing, because it is in idle state, so there is nothing to show, no assigment etc.
     }
 
@@ -187,6 +1083,7 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
         debug!("Player unregistered: {}", player_id);
         self.players.remove(&player_id);
         if self.preferred_player == Some(player_id) { self.preferred_player = None; }
+        self.end_player_overrides(player_id);
 
         self.update_selected_players_for_devices();
         self.apply_on_devices_requiring_update().await;
@@ -198,9 +1095,11 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
             player.assigned_device = Some(device_id);
             player.is_assigned_device_attached = self.connected_devices.contains_key(&device_id);
         }
+        self.release_sticky_players();
 
         self.update_selected_players_for_devices();
         self.apply_on_devices_requiring_update().await;
+        self.notify_device(device_id, |policy| policy.on_assignment).await;
     }
 
     async fn handle_player_unassigned(&mut self, player_id: ManagedPlayerId, device_id: ManagedDeviceId) {
@@ -210,6 +1109,7 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
             player.assigned_device = None;
             player.is_assigned_device_attached = false;
         }
+        self.release_sticky_players();
 
         self.update_selected_players_for_devices();
 
@@ -220,13 +1120,20 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
         debug!("StateUpdated: player {}", player_id);
 
         let mut status_changed = false;
+        let mut track_changed = false;
 
         if let Some(player) = self.players.get_mut(&player_id) {
             if player.state.status != state.status {
                 status_changed = true;
             }
+            track_changed = is_track_change(player.state.texts.get_text(FsctTextMetadata::CurrentTitle),
+                                            state.texts.get_text(FsctTextMetadata::CurrentTitle));
+            player.set_status(state.status, self.buffering_grace, self.sticky_source);
             player.state = state;
         }
+        if status_changed && self.players.get(&player_id).is_some_and(|p| p.state.status == FsctStatus::Stopped) {
+            self.end_player_overrides(player_id);
+        }
 
         if status_changed {
             self.update_selected_players_for_devices();
@@ -238,12 +1145,18 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
             }
         }
         self.apply_on_devices_requiring_update().await;
+        if track_changed {
+            self.notify_track_change(player_id).await;
+        }
     }
 
     async fn handle_player_status_updated(&mut self, player_id: ManagedPlayerId, status: FsctStatus) {
         debug!("StatusUpdated: player {} -> {:?}", player_id, status);
         if let Some(player) = self.players.get_mut(&player_id) {
-            player.state.status = status;
+            player.set_status(status, self.buffering_grace, self.sticky_source);
+        }
+        if status == FsctStatus::Stopped {
+            self.end_player_overrides(player_id);
         }
         // Status change can affect selection
         self.update_selected_players_for_devices();
@@ -269,7 +1182,9 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
                 let device = device.lock().unwrap();
                 device.player_id == Some(player_id)
             };
-            if is_selected {
+            if is_selected && self.is_suspended(device_id) {
+                device.lock().unwrap().requires_update = true;
+            } else if is_selected && !device.lock().unwrap().showing_aux {
                 // best-effort; ignore errors here like other handlers
                 self.applier.apply_timeline(device_id.clone(), Some(timeline.clone())).await.ok();
             }
@@ -277,6 +1192,44 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
         // Do not mark devices for full update; no selection recompute needed for timeline-only changes
     }
 
+    async fn handle_player_volume_updated(&mut self, player_id: ManagedPlayerId, volume: Option<VolumeInfo>) {
+        debug!("VolumeUpdated: player {}", player_id);
+        if let Some(player) = self.players.get_mut(&player_id) {
+            player.state.volume = volume;
+        }
+        // Directly apply only the volume to devices currently showing this player
+        for (device_id, device) in self.connected_devices.iter() {
+            let is_selected = {
+                let device = device.lock().unwrap();
+                device.player_id == Some(player_id)
+            };
+            if is_selected && self.is_suspended(device_id) {
+                device.lock().unwrap().requires_update = true;
+            } else if is_selected && !device.lock().unwrap().showing_aux {
+                self.applier.apply_volume(device_id.clone(), volume).await.ok();
+            }
+        }
+    }
+
+    async fn handle_player_modes_updated(&mut self, player_id: ManagedPlayerId, modes: PlaybackModes) {
+        debug!("ModesUpdated: player {}", player_id);
+        if let Some(player) = self.players.get_mut(&player_id) {
+            player.state.modes = modes;
+        }
+        // Directly apply only the modes to devices currently showing this player
+        for (device_id, device) in self.connected_devices.iter() {
+            let is_selected = {
+                let device = device.lock().unwrap();
+                device.player_id == Some(player_id)
+            };
+            if is_selected && self.is_suspended(device_id) {
+                device.lock().unwrap().requires_update = true;
+            } else if is_selected && !device.lock().unwrap().showing_aux {
+                self.applier.apply_modes(device_id.clone(), modes).await.ok();
+            }
+        }
+    }
+
     async fn handle_player_text_metadata_updated(&mut self, player_id: ManagedPlayerId, metadata: FsctTextMetadata, text: Option<String>) {
         debug!("TextMetadataUpdated: player {} {:?}", player_id, metadata);
         // Convert Option<String> to Option<&str> for apply_text
@@ -287,21 +1240,29 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
                 let device = device.lock().unwrap();
                 device.player_id == Some(player_id)
             };
-            if is_selected {
+            if is_selected && self.is_suspended(device_id) {
+                device.lock().unwrap().requires_update = true;
+            } else if is_selected && !device.lock().unwrap().showing_aux {
                 self.applier.apply_text(device_id.clone(), metadata, text_ref).await.ok();
             }
         }
         // Update local state after applies
+        let mut track_changed = false;
         if let Some(player) = self.players.get_mut(&player_id) {
             let slot = player.state.texts.get_mut_text(metadata);
+            track_changed = metadata == FsctTextMetadata::CurrentTitle && is_track_change(slot, &text);
             *slot = text;
         }
+        if track_changed {
+            self.notify_track_change(player_id).await;
+        }
         // Do not trigger full apply
     }
 
     async fn handle_preferred_changed(&mut self, preferred: Option<ManagedPlayerId>) {
         debug!("PreferredChanged: {:?}", preferred);
         self.preferred_player = preferred;
+        self.release_sticky_players();
 
         self.update_selected_players_for_devices();
         self.apply_on_devices_requiring_update().await;
@@ -310,6 +1271,10 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
     // Dedicated handlers for DeviceEvent variants
     async fn handle_device_added(&mut self, device_id: ManagedDeviceId) {
         debug!("Device added: {}", device_id);
+        // a re-attached device may have lost its state, so nothing from before is assumed and everything is written
+        if let Err(e) = self.applier.reset_device(device_id).await {
+            warn!("Failed to reset state of device {}: {}", device_id, e);
+        }
         self.connected_devices.insert(device_id, Mutex::new(ConnectedDevice::default()));
         for player in self.players.values_mut() {
             if player.assigned_device == Some(device_id) {
@@ -323,9 +1288,22 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
     async fn handle_device_removed(&mut self, device_id: ManagedDeviceId) {
         debug!("Device removed: {}", device_id);
         self.connected_devices.remove(&device_id);
-        for player in self.players.values_mut() {
+        for observer in &self.display_observers {
+            observer.device_detached(device_id);
+        }
+        self.route_overrides.remove(&device_id);
+        let mut playing = Vec::new();
+        for (player_id, player) in self.players.iter_mut() {
             if player.assigned_device == Some(device_id) {
                 player.is_assigned_device_attached = false;
+                if player.is_playing() {
+                    playing.push(*player_id);
+                }
+            }
+        }
+        if self.pause_on_disconnect && !self.updating_devices.contains(&device_id) {
+            for player_id in playing {
+                self.pause_player(player_id, device_id);
             }
         }
         // Players previously assigned to this device may now fall back to general group if no other connected device
@@ -335,10 +1313,19 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
 
     // Selection helpers
     fn find_player_for_device(&self, device_id: &ManagedDeviceId) -> Option<ManagedPlayerId> {
+        if let Some(route_override) = self.route_overrides.get(device_id) {
+            return Some(route_override.player_id);
+        }
         let mut selected = None;
         let mut selected_params = None;
         let last_selected = self.connected_devices.get(device_id)?.lock().unwrap().player_id.clone();
-        for (player_id, player) in self.players.iter() {
+        let origin_filter = self.origin_filters.get(device_id);
+        let accepted = |player: &RegisteredPlayer| origin_filter.is_none_or(|filter| filter.accepts(&player.origin));
+        if let Some(player_id) = last_selected
+            && self.players.get(&player_id).is_some_and(|p| p.is_sticky(Instant::now()) && accepted(p)) {
+            return Some(player_id);
+        }
+        for (player_id, player) in self.players.iter().filter(|(_, player)| accepted(player)) {
             let assignment_state = if player.assigned_device.as_ref() == Some(device_id) {
                 Assignment::AssignedToThisDevice
             } else if player.is_assigned_device_attached {
@@ -349,7 +1336,7 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
                 Assignment::Unassigned
             };
             let player_selection_params = PlayerSelectionParams {
-                is_playing: player.state.status == FsctStatus::Playing,
+                is_playing: player.is_playing(),
                 is_last_selected: last_selected.map(|id| id == *player_id).unwrap_or(false),
                 assignment: assignment_state,
             };
@@ -374,6 +1361,10 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
 
     async fn apply_on_devices_requiring_update(&self) {
         for (device_id, device) in self.connected_devices.iter() {
+            if self.is_suspended(device_id) {
+                // stays marked, so it is resynced once do-not-disturb is lifted
+                continue;
+            }
             let state = {
                 let mut device = device.lock().unwrap();
                 if device.requires_update {
@@ -383,6 +1374,7 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
                                       .map(|p| p.state.clone())
                                       .unwrap_or_default();
                     device.requires_update = false;
+                    device.showing_aux = false;
                     Some(state)
                 } else {
                     None
@@ -392,10 +1384,21 @@ impl<A: PlayerStateApplier + 'static> Orchestrator<A> {
                 self.applier.apply_to_device(device_id.clone(), &state).await.ok();
             }
         }
+        self.update_idle_displays().await;
     }
 }
 
 
+enum IdleAction {
+    Enable,
+    Disable,
+    ShowAux(String),
+}
+
+fn is_track_change(old_title: &Option<String>, new_title: &Option<String>) -> bool {
+    new_title.is_some() && old_title != new_title
+}
+
 #[derive(PartialEq, Eq, Clone, Copy, Debug, PartialOrd)]
 enum Assignment {
     /// Player is assigned to a connected device, but it is not this device
@@ -464,11 +1467,10 @@ fn is_better_selection(player_params: &PlayerSelectionParams, current_selection:
 #[cfg(test)]
 mod tests {
     use super::*;
-    use anyhow::Error;
-    use std::sync::Mutex;
-    use tokio::time::{sleep, Duration};
     use uuid::Uuid;
     use crate::definitions::FsctStatus;
+    use crate::player_event_queue::PlayerEventQueues;
+    use crate::test_util::{advance, drain, ApplyCall, EnableCall, NotifyCall, ModesCall, RecordingApplier, VolumeCall};
 
     // ----------------- Helpers for selection testing -----------------
     fn fold_best(items: &[PlayerSelectionParams]) -> PlayerSelectionParams {
@@ -510,103 +1512,30 @@ mod tests {
         for perm in permute_indices(items.len()) {
             let permuted: Vec<PlayerSelectionParams> = perm.iter().map(|&i| items[i]).collect();
             let w = fold_best(&permuted);
-            if w != base {
-                return (false, base);
-            }
-        }
-        (true, base)
-    }
-
-    // Physically sort by repeatedly picking the best remaining (deterministic for tests)
-    fn sort_by_preference(items: &[PlayerSelectionParams]) -> Vec<PlayerSelectionParams> {
-        let mut rest: Vec<PlayerSelectionParams> = items.to_vec();
-        let mut out = Vec::with_capacity(rest.len());
-        while !rest.is_empty() {
-            // find index of the best element
-            let mut best_idx = 0;
-            let mut best_opt: Option<PlayerSelectionParams> = None;
-            for (i, cand) in rest.iter().enumerate() {
-                if is_better_selection(cand, &best_opt) {
-                    best_opt = Some(*cand);
-                    best_idx = i;
-                }
-            }
-            out.push(rest.remove(best_idx));
-        }
-        out
-    }
-
-    #[derive(Debug, Clone, PartialEq)]
-    struct ApplyCall {
-        device: ManagedDeviceId,
-        state: PlayerState,
-    }
-
-    #[derive(Debug, Clone, PartialEq)]
-    struct TimelineCall {
-        device: ManagedDeviceId,
-        timeline: Option<TimelineInfo>,
-    }
-
-    #[derive(Debug, Clone, PartialEq)]
-    struct TextCall {
-        device: ManagedDeviceId,
-        text_id: FsctTextMetadata,
-        text: Option<String>,
-    }
-
-    struct MockApplier {
-        calls: Mutex<Vec<ApplyCall>>, // full applies
-        timeline_calls: Mutex<Vec<TimelineCall>>, // partial timeline applies
-        text_calls: Mutex<Vec<TextCall>>, // partial text applies
-    }
-
-    impl MockApplier {
-        fn new() -> Arc<Self> { Arc::new(Self { calls: Mutex::new(Vec::new()), timeline_calls: Mutex::new(Vec::new()), text_calls: Mutex::new(Vec::new()) }) }
-        fn take(&self) -> Vec<ApplyCall> { std::mem::take(&mut self.calls.lock().unwrap()) }
-        fn take_timeline(&self) -> Vec<TimelineCall> { std::mem::take(&mut self.timeline_calls.lock().unwrap()) }
-        fn take_text(&self) -> Vec<TextCall> { std::mem::take(&mut self.text_calls.lock().unwrap()) }
-    }
-
-    impl PlayerStateApplier for MockApplier {
-        fn apply_to_device<'a>(&'a self, device_id: ManagedDeviceId, state: &'a PlayerState)
-            -> std::pin::Pin<Box<dyn std::future::Future<Output=Result<(), Error>> + Send + 'a>> {
-            let st = state.clone();
-            Box::pin(async move {
-                let mut guard = self.calls.lock().unwrap();
-                let duplicate = guard.iter().any(|c| c.device == device_id && c.state == st);
-                if !duplicate {
-                    #[cfg(test)]
-                    {
-                        println!("APPLY dev={:?} status={:?}", device_id, st.status);
-                    }
-                    guard.push(ApplyCall { device: device_id, state: st });
-                }
-                Ok(())
-            })
-        }
-
-        fn apply_status<'a>(&'a self, _device_id: ManagedDeviceId, _status: crate::definitions::FsctStatus)
-            -> std::pin::Pin<Box<dyn std::future::Future<Output=Result<(), Error>> + Send + 'a>> {
-            Box::pin(async move { Ok(()) })
-        }
-
-        fn apply_timeline<'a>(&'a self, device_id: ManagedDeviceId, timeline: Option<crate::definitions::TimelineInfo>)
-            -> std::pin::Pin<Box<dyn std::future::Future<Output=Result<(), Error>> + Send + 'a>> {
-            Box::pin(async move {
-                self.timeline_calls.lock().unwrap().push(TimelineCall { device: device_id, timeline: timeline.clone() });
-                Ok(())
-            })
+            if w != base {
+                return (false, base);
+            }
         }
+        (true, base)
+    }
 
-        fn apply_text<'a>(&'a self, device_id: ManagedDeviceId, text_id: crate::definitions::FsctTextMetadata, text: Option<&'a str>)
-            -> std::pin::Pin<Box<dyn std::future::Future<Output=Result<(), Error>> + Send + 'a>> {
-            let owned = text.map(|s| s.to_string());
-            Box::pin(async move {
-                self.text_calls.lock().unwrap().push(TextCall { device: device_id, text_id, text: owned });
-                Ok(())
-            })
+    // Physically sort by repeatedly picking the best remaining (deterministic for tests)
+    fn sort_by_preference(items: &[PlayerSelectionParams]) -> Vec<PlayerSelectionParams> {
+        let mut rest: Vec<PlayerSelectionParams> = items.to_vec();
+        let mut out = Vec::with_capacity(rest.len());
+        while !rest.is_empty() {
+            // find index of the best element
+            let mut best_idx = 0;
+            let mut best_opt: Option<PlayerSelectionParams> = None;
+            for (i, cand) in rest.iter().enumerate() {
+                if is_better_selection(cand, &best_opt) {
+                    best_opt = Some(*cand);
+                    best_idx = i;
+                }
+            }
+            out.push(rest.remove(best_idx));
         }
+        out
     }
 
     fn make_ids(n: usize) -> Vec<ManagedDeviceId> { (0..n).map(|_| Uuid::new_v4()).collect() }
@@ -619,100 +1548,122 @@ mod tests {
     }
 
     // Helper to build orchestrator and the senders
-    fn build_orchestrator(applier: Arc<MockApplier>) -> (
-        Orchestrator<MockApplier>,
-        tokio::sync::broadcast::Sender<PlayerEvent>,
+    fn build_orchestrator(applier: Arc<RecordingApplier>) -> (
+        Orchestrator<RecordingApplier>,
+        PlayerEventQueues,
         tokio::sync::broadcast::Sender<DeviceEvent>,
     ) {
-        let (player_tx, player_rx) = tokio::sync::broadcast::channel(256);
+        let player_tx = PlayerEventQueues::default();
+        let player_rx = player_tx.subscribe();
         let (device_tx, device_rx) = tokio::sync::broadcast::channel(256);
         let orch = Orchestrator::new_with_applier(player_rx, device_rx, applier);
         (orch, player_tx, device_tx)
     }
 
-    async fn run_orchestrator(orch: Orchestrator<MockApplier>) -> ServiceHandle {
+    async fn run_orchestrator(orch: Orchestrator<RecordingApplier>) -> ServiceHandle {
         orch.run()
     }
 
-    async fn short_wait() { sleep(Duration::from_millis(10)).await }
-
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn zero_players_zero_devices_no_apply() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, _ptx, _dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
-        short_wait().await;
+        drain().await;
         assert!(applier.take().is_empty());
         let _ = handle.shutdown().await;
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn one_player_zero_devices_state_update_no_apply() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, ptx, _dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
 
         let p1 = pid(1);
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
         let s1 = default_state_with_title("S1");
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1 });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1 });
 
-        short_wait().await;
+        drain().await;
         assert!(applier.take().is_empty());
         let _ = handle.shutdown().await;
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn zero_players_one_device_add_no_apply() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, _ptx, dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
 
         let d = make_ids(1)[0];
         let _ = dtx.send(DeviceEvent::Added(d));
-        short_wait().await;
+        drain().await;
         assert!(applier.take().is_empty());
         let _ = handle.shutdown().await;
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn unassigned_state_then_device_added_applies_to_device() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, ptx, dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
 
         let p1 = pid(1);
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
         let s1 = default_state_with_title("S1");
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
-        short_wait().await;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        drain().await;
         let d = make_ids(1)[0];
         let _ = dtx.send(DeviceEvent::Added(d));
 
-        short_wait().await;
+        drain().await;
         let calls = applier.take();
         // allow possible initial Unknown applies; require that S1 was applied to d at least once
         assert!(calls.iter().any(|c| c.device == d && c.state == s1));
         let _ = handle.shutdown().await;
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
+    async fn reattached_device_is_reset_and_gets_full_state_again() {
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let handle = run_orchestrator(orch).await;
+
+        let p1 = pid(1);
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        let s1 = default_state_with_title("S1");
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        let d = make_ids(1)[0];
+        let _ = dtx.send(DeviceEvent::Added(d));
+        drain().await;
+        let _ = applier.take();
+
+        let _ = dtx.send(DeviceEvent::Removed(d));
+        let _ = dtx.send(DeviceEvent::Added(d));
+        drain().await;
+        assert_eq!(applier.take_resets(), vec![d, d]);
+        assert_eq!(applier.take(), vec![ApplyCall { device: d, state: s1 }]);
+        let _ = handle.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
     async fn assign_before_connect_then_connect_then_update() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, ptx, dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
 
         let p1 = pid(1);
         let d = make_ids(1)[0];
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
         let s1 = default_state_with_title("S1");
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
-        let _ = ptx.send(PlayerEvent::Assigned { player_id: p1, device_id: d });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        ptx.send(PlayerEvent::Assigned { player_id: p1, device_id: d });
         // give orchestrator a moment to record the assignment before device connects
-        short_wait().await;
+        drain().await;
         // device connects after assignment
         let _ = dtx.send(DeviceEvent::Added(d));
-        short_wait().await;
+        drain().await;
         // should apply s1 once due to device added with assigned player
         let mut calls = applier.take();
         assert_eq!(calls.len(), 1);
@@ -721,8 +1672,8 @@ mod tests {
 
         // update to S2 -> apply again to assigned device
         let s2 = default_state_with_title("S2");
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s2.clone() });
-        short_wait().await;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s2.clone() });
+        drain().await;
         calls = applier.take();
         assert_eq!(calls.len(), 1);
         assert_eq!(calls[0].device, d);
@@ -731,29 +1682,29 @@ mod tests {
         let _ = handle.shutdown().await;
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn multiple_players_one_device_unassigned_and_assignment_switch() {
         env_logger::init();
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, ptx, dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
         let d = make_ids(1)[0];
         let p1 = pid(1);
         let p2 = pid(2);
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
-        short_wait().await;
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        drain().await;
 
         let s1 = default_state_with_title("S1");
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
-        short_wait().await;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        drain().await;
         let _ = dtx.send(DeviceEvent::Added(d));
-        short_wait().await;
+        drain().await;
         let mut calls = applier.take();
         // Accept possible initial Unknown applies; ensure S1 reached device d
         assert!(calls.iter().any(|c| c.device == d && c.state == s1));
 
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
-        short_wait().await;
+        ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
+        drain().await;
 
         calls = applier.take();
         // ensure S2 did not reach device d yet
@@ -761,15 +1712,15 @@ mod tests {
 
         // P2 updates -> becomes not selected; should not propagate to unassigned device d
         let s2 = default_state_with_title("S2");
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
-        short_wait().await;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
+        drain().await;
         calls = applier.take();
         // ensures S2 did not reach device d yet
         assert!(calls.is_empty());
 
         // Now assign P2 to d -> should apply P2's latest state to d
-        let _ = ptx.send(PlayerEvent::Assigned { player_id: p2, device_id: d });
-        short_wait().await;
+        ptx.send(PlayerEvent::Assigned { player_id: p2, device_id: d });
+        drain().await;
         calls = applier.take();
         // P2 has known state s2 and device connected, assignment applies s2 (at least once)
         assert!(calls.iter().any(|c| c.device == d && c.state == s2));
@@ -777,89 +1728,89 @@ mod tests {
         let _ = handle.shutdown().await;
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn one_player_multiple_devices_unassigned_then_assign() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, ptx, dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
         let p1 = pid(1);
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
         let s1 = default_state_with_title("S1");
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
-        short_wait().await;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        drain().await;
         let ids = make_ids(2);
         let d1 = ids[0];
         let d2 = ids[1];
         let _ = dtx.send(DeviceEvent::Added(d1));
         let _ = dtx.send(DeviceEvent::Added(d2));
-        short_wait().await;
+        drain().await;
         let mut calls = applier.take();
         // both devices should eventually receive s1 (there may be initial Unknown applies)
         assert!(calls.iter().any(|c| c.device == d1 && c.state == s1));
         assert!(calls.iter().any(|c| c.device == d2 && c.state == s1));
 
         // Assign player to d1 -> should apply s1 to d1 again; d2 remains unassigned with prior state
-        let _ = ptx.send(PlayerEvent::Assigned { player_id: p1, device_id: d1 });
-        short_wait().await;
+        ptx.send(PlayerEvent::Assigned { player_id: p1, device_id: d1 });
+        drain().await;
         calls = applier.take();
         // S1 has not changed, so nothing has been applied
         assert!(calls.is_empty());
 
         // Update to S2 -> applies to assigned device d1
         let s2 = default_state_with_title("S2");
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s2.clone() });
-        short_wait().await;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s2.clone() });
+        drain().await;
         calls = applier.take();
         assert!(calls.iter().any(|c| c.device == d1 && c.state == s2));
 
         let _ = handle.shutdown().await;
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn preferred_change_does_not_apply() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, ptx, dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
         let p1 = pid(1);
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
-        short_wait().await;
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        drain().await;
         let d = make_ids(1)[0];
         let _ = dtx.send(DeviceEvent::Added(d));
-        short_wait().await;
+        drain().await;
         let _ = applier.take(); // clear any initial applies (e.g., Unknown)
-        let _ = ptx.send(PlayerEvent::PreferredChanged { preferred: Some(p1) });
-        short_wait().await;
+        ptx.send(PlayerEvent::PreferredChanged { preferred: Some(p1) });
+        drain().await;
         // No state known, preferred change should not cause any additional apply
         assert!(applier.take().is_empty());
         let _ = handle.shutdown().await;
     }
 
     // New tests for advanced grouping and selection
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn preferred_player_drives_general_group() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, ptx, dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
         let p1 = pid(1);
         let p2 = pid(2);
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
         let mut s1 = default_state_with_title("S1");
         s1.status = FsctStatus::Paused;
         let mut s2 = default_state_with_title("S2");
         s2.status = FsctStatus::Stopped;
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
         // set preferred to p2
-        let _ = ptx.send(PlayerEvent::PreferredChanged { preferred: Some(p2) });
-        short_wait().await;
+        ptx.send(PlayerEvent::PreferredChanged { preferred: Some(p2) });
+        drain().await;
         // connect two unassigned devices
         let ids = make_ids(2);
         let d1 = ids[0];
         let d2 = ids[1];
         let _ = dtx.send(DeviceEvent::Added(d1));
         let _ = dtx.send(DeviceEvent::Added(d2));
-        short_wait().await;
+        drain().await;
         let calls = applier.take();
         // Both devices should have preferred p2 state at least once
         assert!(calls.iter().any(|c| c.device == d1 && c.state == s2));
@@ -867,78 +1818,314 @@ mod tests {
         let _ = handle.shutdown().await;
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn general_group_picks_playing_if_no_preferred() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, ptx, dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
         let p1 = pid(1);
         let p2 = pid(2);
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
         let mut s1 = default_state_with_title("S1");
         s1.status = FsctStatus::Playing;
         let mut s2 = default_state_with_title("S2");
         s2.status = FsctStatus::Paused;
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
-        short_wait().await;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
+        drain().await;
         let d = make_ids(1)[0];
         let _ = dtx.send(DeviceEvent::Added(d));
-        short_wait().await;
+        drain().await;
         let calls = applier.take();
         assert_eq!(calls.len(), 1);
         assert_eq!(calls[0].state, s1);
         let _ = handle.shutdown().await;
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
+    async fn buffering_player_is_demoted_after_grace_period() {
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let handle = run_orchestrator(orch.with_buffering_grace(Duration::from_secs(10))).await;
+        let p1 = pid(1);
+        let p2 = pid(2);
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
+        let mut s1 = default_state_with_title("S1");
+        s1.status = FsctStatus::Playing;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        drain().await;
+        let d = make_ids(1)[0];
+        let _ = dtx.send(DeviceEvent::Added(d));
+        drain().await;
+        let _ = applier.take();
+
+        // p1 buffers while p2 starts playing: p1 is kept within the grace period
+        ptx.send(PlayerEvent::StatusUpdated { player_id: p1, status: FsctStatus::Buffering });
+        let mut s2 = default_state_with_title("S2");
+        s2.status = FsctStatus::Playing;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
+        drain().await;
+        assert!(applier.take().iter().all(|c| c.state != s2));
+
+        advance(Duration::from_secs(10)).await;
+        assert_eq!(applier.take().last().map(|c| &c.state), Some(&s2));
+        let _ = handle.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn paused_player_stays_shown_within_sticky_window() {
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let handle = run_orchestrator(orch.with_sticky_source(Duration::from_secs(5))).await;
+        let p1 = pid(1);
+        let p2 = pid(2);
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
+        let mut s1 = default_state_with_title("S1");
+        s1.status = FsctStatus::Playing;
+        let mut s2 = default_state_with_title("S2");
+        s2.status = FsctStatus::Playing;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        let d = make_ids(1)[0];
+        let _ = dtx.send(DeviceEvent::Added(d));
+        ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
+        drain().await;
+        let _ = applier.take();
+
+        // p1 pauses between tracks and resumes: the device never shows p2
+        ptx.send(PlayerEvent::StatusUpdated { player_id: p1, status: FsctStatus::Paused });
+        drain().await;
+        advance(Duration::from_secs(2)).await;
+        ptx.send(PlayerEvent::StatusUpdated { player_id: p1, status: FsctStatus::Playing });
+        drain().await;
+        assert!(applier.take().iter().all(|c| c.state != s2));
+
+        // once p1 stays paused for longer than the window, the device switches to p2; the window starts when the
+        // orchestrator handles the pause, so it has to before the clock moves on
+        ptx.send(PlayerEvent::StatusUpdated { player_id: p1, status: FsctStatus::Paused });
+        drain().await;
+        advance(Duration::from_secs(4)).await;
+        assert!(applier.take().iter().all(|c| c.state != s2));
+        advance(Duration::from_secs(1)).await;
+        assert_eq!(applier.take().last().map(|c| &c.state), Some(&s2));
+        let _ = handle.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn user_selection_ends_sticky_window() {
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let handle = run_orchestrator(orch.with_sticky_source(Duration::from_secs(60))).await;
+        let p1 = pid(1);
+        let p2 = pid(2);
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
+        let mut s1 = default_state_with_title("S1");
+        s1.status = FsctStatus::Playing;
+        let mut s2 = default_state_with_title("S2");
+        s2.status = FsctStatus::Playing;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        let d = make_ids(1)[0];
+        let _ = dtx.send(DeviceEvent::Added(d));
+        ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
+        ptx.send(PlayerEvent::StatusUpdated { player_id: p1, status: FsctStatus::Paused });
+        drain().await;
+        assert!(applier.take().iter().all(|c| c.state != s2));
+
+        ptx.send(PlayerEvent::PreferredChanged { preferred: Some(p2) });
+        drain().await;
+        assert_eq!(applier.take().last().map(|c| &c.state), Some(&s2));
+        let _ = handle.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn origin_filter_limits_players_shown_on_device() {
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let origins = Arc::new(PlayerOrigins::new());
+        let orch = orch.with_player_origins(origins.clone());
+        let control = orch.control();
+        let handle = run_orchestrator(orch).await;
+        let p1 = pid(1);
+        let p2 = pid(2);
+        origins.set(p1, Some(PlayerOrigin::new(Some("living-room".into()), None)));
+        origins.set(p2, Some(PlayerOrigin::new(Some("office".into()), None)));
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
+        let d = make_ids(1)[0];
+        let _ = dtx.send(DeviceEvent::Added(d));
+        drain().await;
+        control.set_origin_filter(d, Some(OriginFilter::hosts(["living-room"]))).await.unwrap();
+
+        // the office player plays, but the device only accepts living-room players
+        let mut s2 = default_state_with_title("Office");
+        s2.status = FsctStatus::Playing;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
+        let s1 = default_state_with_title("Living room");
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        drain().await;
+        assert_eq!(applier.take().last().map(|c| &c.state), Some(&s1));
+
+        control.set_origin_filter(d, None).await.unwrap();
+        assert_eq!(applier.take().last().map(|c| &c.state), Some(&s2));
+        let _ = handle.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn device_commands_go_to_the_shown_player() {
+        use crate::player_interface::PlayerInterface;
+
+        #[derive(Default)]
+        struct RecordingPlayer {
+            commands: Mutex<Vec<PlaybackCommand>>,
+        }
+
+        #[async_trait::async_trait]
+        impl PlayerInterface for RecordingPlayer {
+            async fn execute(&self, command: PlaybackCommand) -> Result<(), anyhow::Error> {
+                self.commands.lock().unwrap().push(command);
+                Ok(())
+            }
+        }
+
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let interfaces = Arc::new(PlayerInterfaces::new());
+        let handle = run_orchestrator(orch.with_player_interfaces(interfaces.clone())).await;
+        let (p1, p2) = (pid(1), pid(2));
+        let (player1, player2) = (Arc::new(RecordingPlayer::default()), Arc::new(RecordingPlayer::default()));
+        interfaces.set(p1, Some(player1.clone()));
+        interfaces.set(p2, Some(player2.clone()));
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
+        let mut s1 = default_state_with_title("S1");
+        s1.status = FsctStatus::Playing;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1 });
+        let d = make_ids(1)[0];
+        let _ = dtx.send(DeviceEvent::Added(d));
+        drain().await;
+
+        let _ = dtx.send(DeviceEvent::DeviceCommand { device_id: d, command: PlaybackCommand::Pause });
+        drain().await;
+        assert_eq!(*player1.commands.lock().unwrap(), vec![PlaybackCommand::Pause]);
+        assert!(player2.commands.lock().unwrap().is_empty());
+
+        let volume = VolumeInfo { level: 35, muted: false };
+        let _ = dtx.send(DeviceEvent::VolumeChanged { device_id: d, volume });
+        drain().await;
+        assert_eq!(player1.commands.lock().unwrap().last(), Some(&PlaybackCommand::SetVolume { volume }));
+        assert!(player2.commands.lock().unwrap().is_empty());
+        let _ = handle.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn assigned_players_pause_when_their_device_disconnects() {
+        use crate::player_interface::PlayerInterface;
+
+        #[derive(Default)]
+        struct RecordingPlayer {
+            commands: Mutex<Vec<PlaybackCommand>>,
+        }
+
+        #[async_trait::async_trait]
+        impl PlayerInterface for RecordingPlayer {
+            async fn execute(&self, command: PlaybackCommand) -> Result<(), anyhow::Error> {
+                self.commands.lock().unwrap().push(command);
+                Ok(())
+            }
+        }
+
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let interfaces = Arc::new(PlayerInterfaces::new());
+        let orch = orch.with_player_interfaces(interfaces.clone());
+        let control = orch.control();
+        let handle = run_orchestrator(orch).await;
+        let (p1, p2) = (pid(1), pid(2));
+        let (player1, player2) = (Arc::new(RecordingPlayer::default()), Arc::new(RecordingPlayer::default()));
+        interfaces.set(p1, Some(player1.clone()));
+        interfaces.set(p2, Some(player2.clone()));
+        let ids = make_ids(2);
+        let (d1, d2) = (ids[0], ids[1]);
+        for (player_id, device_id) in [(p1, d1), (p2, d2)] {
+            ptx.send(PlayerEvent::Registered { player_id, self_id: player_id.to_string() });
+            let mut state = default_state_with_title("Playing");
+            state.status = FsctStatus::Playing;
+            ptx.send(PlayerEvent::StateUpdated { player_id, state });
+            ptx.send(PlayerEvent::Assigned { player_id, device_id });
+            let _ = dtx.send(DeviceEvent::Added(device_id));
+        }
+        drain().await;
+
+        // off by default
+        let _ = dtx.send(DeviceEvent::Removed(d1));
+        drain().await;
+        assert!(player1.commands.lock().unwrap().is_empty());
+
+        control.set_pause_on_disconnect(true).await.unwrap();
+        let _ = dtx.send(DeviceEvent::Removed(d2));
+        drain().await;
+        assert_eq!(*player2.commands.lock().unwrap(), vec![PlaybackCommand::Pause]);
+        assert!(player1.commands.lock().unwrap().is_empty());
+
+        // restarting to update its firmware is no disconnect
+        let _ = dtx.send(DeviceEvent::Added(d1));
+        let _ = dtx.send(DeviceEvent::FirmwareUpdate { device_id: d1, updating: true });
+        let _ = dtx.send(DeviceEvent::Removed(d1));
+        drain().await;
+        assert!(player1.commands.lock().unwrap().is_empty());
+        let _ = handle.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
     async fn multiple_playing_keep_last_active_in_general() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, ptx, dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
         let p1 = pid(1);
         let p2 = pid(2);
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
         let mut s1 = default_state_with_title("S1");
         s1.status = FsctStatus::Playing;
         let mut s2 = default_state_with_title("S2");
         s2.status = FsctStatus::Playing;
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
         let d = make_ids(1)[0];
         let _ = dtx.send(DeviceEvent::Added(d));
-        short_wait().await;
+        drain().await;
         let _ = applier.take(); // p1 selected
         // now p2 starts playing as well
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
-        short_wait().await;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
+        drain().await;
         let calls = applier.take();
         // ambiguous, should keep last active (p1) and not reapply since state didn't change for p1
         assert!(calls.is_empty());
         let _ = handle.shutdown().await;
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn device_group_with_multiple_players_picks_playing() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, ptx, dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
         let d = make_ids(1)[0];
         let _ = dtx.send(DeviceEvent::Added(d));
         let p1 = pid(1);
         let p2 = pid(2);
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
         let mut s1 = default_state_with_title("S1");
         s1.status = FsctStatus::Paused;
         let mut s2 = default_state_with_title("S2");
         s2.status = FsctStatus::Playing;
-        let _ = ptx.send(PlayerEvent::Assigned { player_id: p1, device_id: d });
-        let _ = ptx.send(PlayerEvent::Assigned { player_id: p2, device_id: d });
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
-        short_wait().await;
+        ptx.send(PlayerEvent::Assigned { player_id: p1, device_id: d });
+        ptx.send(PlayerEvent::Assigned { player_id: p2, device_id: d });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
+        drain().await;
         let calls = applier.take();
         assert!(!calls.is_empty());
         assert_eq!(calls.last().unwrap().device, d);
@@ -946,29 +2133,29 @@ mod tests {
         let _ = handle.shutdown().await;
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn assigned_to_disconnected_counts_as_general() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, ptx, dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
         let d_assigned = make_ids(1)[0]; // will remain disconnected
         let d_unassigned = make_ids(1)[0];
         let _ = dtx.send(DeviceEvent::Added(d_unassigned));
         let p1 = pid(1);
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
         let s1 = default_state_with_title("S1");
-        let _ = ptx.send(PlayerEvent::Assigned { player_id: p1, device_id: d_assigned });
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
-        short_wait().await;
+        ptx.send(PlayerEvent::Assigned { player_id: p1, device_id: d_assigned });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        drain().await;
         let calls = applier.take();
         // p1 should be applied to unassigned connected device (there may be an initial Unknown)
         assert!(calls.iter().any(|c| c.device == d_unassigned && c.state == s1));
         let _ = handle.shutdown().await;
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn general_does_not_pick_playing_assigned_to_other_device() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, ptx, dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
         let p1 = pid(1);
@@ -977,16 +2164,16 @@ mod tests {
         let d2 = make_ids(1)[0];
         let _ = dtx.send(DeviceEvent::Added(d1)); // device with assigned group
         let _ = dtx.send(DeviceEvent::Added(d2)); // unassigned mirrors general
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p2".into() });
         let mut s1 = default_state_with_title("S1");
         s1.status = FsctStatus::Playing;
         let mut s2 = default_state_with_title("S2");
         s2.status = FsctStatus::Paused;
-        let _ = ptx.send(PlayerEvent::Assigned { player_id: p1, device_id: d1 });
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
-        short_wait().await;
+        ptx.send(PlayerEvent::Assigned { player_id: p1, device_id: d1 });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
+        drain().await;
         let calls = applier.take();
         // d1 gets s1 due to assignment; general (unassigned) should prefer unassigned p2 over playing p1 assigned elsewhere
         assert!(calls.iter().any(|c| c.device == d1 && c.state == s1));
@@ -1202,30 +2389,51 @@ mod tests {
     }
 
     #[tokio::test]
+    async fn ack_handle_waits_for_applies_without_timing() {
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let ack = orch.ack_handle();
+        let handle = run_orchestrator(orch).await;
+
+        let p1 = pid(1);
+        let d = make_ids(1)[0];
+        let s1 = default_state_with_title("S1");
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p1".into() });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        ack.wait_applied().await.unwrap();
+        let _ = dtx.send(DeviceEvent::Added(d));
+        ack.wait_applied().await.unwrap();
+        assert!(applier.take().iter().any(|c| c.device == d && c.state == s1));
+
+        let _ = handle.shutdown().await;
+        assert!(ack.wait_applied().await.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
     async fn timeline_update_triggers_partial_apply_only() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, ptx, dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
 
         let p1 = pid(101);
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p101".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p101".into() });
         let mut s1 = default_state_with_title("Initial");
         s1.status = FsctStatus::Playing;
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
         let d = make_ids(1)[0];
         let _ = dtx.send(DeviceEvent::Added(d));
-        short_wait().await;
+        drain().await;
         let _ = applier.take(); // clear initial full apply(s)
 
         // Send timeline update
         let tl = TimelineInfo {
             position: std::time::Duration::from_secs(12),
             update_time: std::time::SystemTime::now(),
-            duration: std::time::Duration::from_secs(300),
+            duration: Some(std::time::Duration::from_secs(300)),
             rate: 1.0,
         };
-        let _ = ptx.send(PlayerEvent::TimelineUpdated { player_id: p1, timeline: tl.clone() });
-        short_wait().await;
+        ptx.send(PlayerEvent::TimelineUpdated { player_id: p1, timeline: tl.clone() });
+        drain().await;
 
         // Expect only partial timeline calls, no full apply
         let full_calls = applier.take();
@@ -1238,26 +2446,78 @@ mod tests {
         let _ = handle.shutdown().await;
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
+    async fn volume_update_triggers_partial_apply_only() {
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let handle = run_orchestrator(orch).await;
+
+        let p1 = pid(102);
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p102".into() });
+        let mut s1 = default_state_with_title("Initial");
+        s1.status = FsctStatus::Playing;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1 });
+        let d = make_ids(1)[0];
+        let _ = dtx.send(DeviceEvent::Added(d));
+        drain().await;
+        let _ = applier.take();
+
+        let volume = Some(VolumeInfo { level: 35, muted: true });
+        ptx.send(PlayerEvent::VolumeUpdated { player_id: p1, volume });
+        drain().await;
+
+        assert!(applier.take().is_empty(), "Volume update should not trigger full apply_to_device");
+        assert_eq!(applier.take_volume(), vec![VolumeCall { device: d, volume }]);
+
+        let _ = handle.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn modes_update_triggers_partial_apply_only() {
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let handle = run_orchestrator(orch).await;
+
+        let p1 = pid(103);
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p103".into() });
+        let mut s1 = default_state_with_title("Initial");
+        s1.status = FsctStatus::Playing;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1 });
+        let d = make_ids(1)[0];
+        let _ = dtx.send(DeviceEvent::Added(d));
+        drain().await;
+        let _ = applier.take();
+
+        let modes = PlaybackModes { shuffle: Some(true), repeat: Some(crate::definitions::RepeatMode::Track) };
+        ptx.send(PlayerEvent::ModesUpdated { player_id: p1, modes });
+        drain().await;
+
+        assert!(applier.take().is_empty(), "Modes update should not trigger full apply_to_device");
+        assert_eq!(applier.take_modes(), vec![ModesCall { device: d, modes }]);
+
+        let _ = handle.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
     async fn text_update_triggers_partial_apply_only() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, ptx, dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
 
         let p1 = pid(102);
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p102".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p102".into() });
         let mut s1 = default_state_with_title("Initial");
         s1.status = FsctStatus::Playing;
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
         let d = make_ids(1)[0];
         let _ = dtx.send(DeviceEvent::Added(d));
-        short_wait().await;
+        drain().await;
         let _ = applier.take(); // clear initial full apply(s)
 
         // Send text metadata update
         let new_title = "New Title".to_string();
-        let _ = ptx.send(PlayerEvent::TextMetadataUpdated { player_id: p1, metadata: FsctTextMetadata::CurrentTitle, text: Some(new_title.clone()) });
-        short_wait().await;
+        ptx.send(PlayerEvent::TextMetadataUpdated { player_id: p1, metadata: FsctTextMetadata::CurrentTitle, text: Some(new_title.clone()) });
+        drain().await;
 
         let full_calls = applier.take();
         let txt_calls = applier.take_text();
@@ -1270,34 +2530,34 @@ mod tests {
         let _ = handle.shutdown().await;
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn status_update_reassigns_and_full_apply() {
-        let applier = MockApplier::new();
+        let applier = RecordingApplier::new();
         let (orch, ptx, dtx) = build_orchestrator(applier.clone());
         let handle = run_orchestrator(orch).await;
 
         let p1 = pid(201);
         let p2 = pid(202);
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p201".into() });
-        let _ = ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p202".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p201".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p202".into() });
 
         let mut s1 = default_state_with_title("P1");
         s1.status = FsctStatus::Playing;
-        let _ = ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
 
         let d = make_ids(1)[0];
         let _ = dtx.send(DeviceEvent::Added(d));
-        short_wait().await;
+        drain().await;
         let _ = applier.take(); // p1 applied due to selection
 
         // Assign p2 to this device, but no state yet; ensure no apply happens
-        let _ = ptx.send(PlayerEvent::Assigned { player_id: p2, device_id: d });
-        short_wait().await;
+        ptx.send(PlayerEvent::Assigned { player_id: p2, device_id: d });
+        drain().await;
         assert!(applier.take().is_empty());
 
         // Now status update on p2 should cause selection to switch and trigger full apply
-        let _ = ptx.send(PlayerEvent::StatusUpdated { player_id: p2, status: FsctStatus::Playing });
-        short_wait().await;
+        ptx.send(PlayerEvent::StatusUpdated { player_id: p2, status: FsctStatus::Playing });
+        drain().await;
         let calls = applier.take();
         assert_eq!(calls.len(), 1, "Expected one full apply after status change causing reassignment");
         assert_eq!(calls[0].device, d);
@@ -1308,4 +2568,240 @@ mod tests {
 
         let _ = handle.shutdown().await;
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn route_override_wins_until_it_expires() {
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let routes = orch.control();
+        let handle = run_orchestrator(orch).await;
+
+        let p1 = pid(301);
+        let p2 = pid(302);
+        let d = make_ids(1)[0];
+        let mut s1 = default_state_with_title("Playing");
+        s1.status = FsctStatus::Playing;
+        let s2 = default_state_with_title("Forced");
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p301".into() });
+        ptx.send(PlayerEvent::Registered { player_id: p2, self_id: "p302".into() });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        ptx.send(PlayerEvent::StateUpdated { player_id: p2, state: s2.clone() });
+        let _ = dtx.send(DeviceEvent::Added(d));
+        drain().await;
+        let _ = applier.take();
+
+        assert!(routes.force_route(p2, make_ids(1)[0], None).await.is_err());
+        routes.force_route(p2, d, Some(Duration::from_secs(60))).await.unwrap();
+        assert_eq!(applier.take(), vec![ApplyCall { device: d, state: s2 }]);
+        assert_eq!(routes.routes().await.unwrap()[0].remaining, Some(Duration::from_secs(60)));
+
+        advance(Duration::from_secs(61)).await;
+        assert_eq!(applier.take(), vec![ApplyCall { device: d, state: s1 }]);
+        assert!(routes.routes().await.unwrap().is_empty());
+
+        let _ = handle.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn endless_route_override_is_refused() {
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let routes = orch.control();
+        let handle = run_orchestrator(orch).await;
+
+        let p1 = pid(321);
+        let d = make_ids(1)[0];
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p321".into() });
+        let _ = dtx.send(DeviceEvent::Added(d));
+        drain().await;
+
+        assert!(routes.force_route(p1, d, Some(Duration::MAX)).await.is_err());
+        assert!(routes.routes().await.unwrap().is_empty());
+        routes.force_route(p1, d, Some(Duration::from_secs(60))).await.unwrap();
+        assert_eq!(routes.routes().await.unwrap().len(), 1);
+
+        let _ = handle.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn route_override_ends_when_player_stops() {
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let routes = orch.control();
+        let handle = run_orchestrator(orch).await;
+
+        let p1 = pid(311);
+        let d = make_ids(1)[0];
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p311".into() });
+        ptx.send(PlayerEvent::StatusUpdated { player_id: p1, status: FsctStatus::Playing });
+        let _ = dtx.send(DeviceEvent::Added(d));
+        drain().await;
+
+        routes.force_route(p1, d, None).await.unwrap();
+        ptx.send(PlayerEvent::StatusUpdated { player_id: p1, status: FsctStatus::Stopped });
+        drain().await;
+        assert!(routes.routes().await.unwrap().is_empty());
+
+        let _ = handle.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn dnd_suspends_writes_and_resyncs_when_lifted() {
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let control = orch.control();
+        let handle = run_orchestrator(orch).await;
+
+        let p1 = pid(401);
+        let ids = make_ids(2);
+        let (d1, d2) = (ids[0], ids[1]);
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p401".into() });
+        let _ = dtx.send(DeviceEvent::Added(d1));
+        let _ = dtx.send(DeviceEvent::Added(d2));
+        drain().await;
+        let _ = applier.take();
+
+        control.set_dnd(DndScope::Device(d1), true).await.unwrap();
+        let mut s1 = default_state_with_title("S1");
+        s1.status = FsctStatus::Playing;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: s1.clone() });
+        ptx.send(PlayerEvent::TextMetadataUpdated {
+            player_id: p1,
+            metadata: FsctTextMetadata::CurrentTitle,
+            text: Some("S2".into()),
+        });
+        drain().await;
+        assert_eq!(applier.take(), vec![ApplyCall { device: d2, state: s1.clone() }]);
+        assert!(applier.take_text().iter().all(|c| c.device == d2));
+        assert_eq!(control.dnd().await.unwrap(), DndState { global: false, devices: vec![d1] });
+        assert!(control.is_suspended(d1) && !control.is_suspended(d2));
+
+        control.set_dnd(DndScope::Device(d1), false).await.unwrap();
+        assert!(!control.is_suspended(d1));
+        let mut s2 = s1;
+        s2.texts.get_mut_text(FsctTextMetadata::CurrentTitle).replace("S2".to_string());
+        assert_eq!(applier.take(), vec![ApplyCall { device: d1, state: s2 }]);
+
+        let _ = handle.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_timeout_switches_display_off_and_playback_back_on() {
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let control = orch.control();
+        let handle = run_orchestrator(orch).await;
+
+        let p1 = pid(501);
+        let d = make_ids(1)[0];
+        control.set_idle_timeout(d, Some(Duration::from_secs(300))).await.unwrap();
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p501".into() });
+        ptx.send(PlayerEvent::StatusUpdated { player_id: p1, status: FsctStatus::Playing });
+        let _ = dtx.send(DeviceEvent::Added(d));
+        drain().await;
+        ptx.send(PlayerEvent::StatusUpdated { player_id: p1, status: FsctStatus::Paused });
+        drain().await;
+
+        advance(Duration::from_secs(299)).await;
+        assert!(applier.take_enable().is_empty());
+        advance(Duration::from_secs(1)).await;
+        assert_eq!(applier.take_enable(), vec![EnableCall { device: d, enable: false }]);
+
+        ptx.send(PlayerEvent::StatusUpdated { player_id: p1, status: FsctStatus::Playing });
+        drain().await;
+        assert_eq!(applier.take_enable(), vec![EnableCall { device: d, enable: true }]);
+
+        let _ = handle.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn notify_policy_signals_track_change_and_assignment() {
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let control = orch.control();
+        let handle = run_orchestrator(orch).await;
+
+        let p1 = pid(601);
+        let d = make_ids(1)[0];
+        let policy = NotifyPolicy { on_track_change: true, on_assignment: false, notification: FsctNotification::Beep };
+        control.set_notify_policy(d, Some(policy)).await.unwrap();
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p601".into() });
+        ptx.send(PlayerEvent::StatusUpdated { player_id: p1, status: FsctStatus::Playing });
+        let _ = dtx.send(DeviceEvent::Added(d));
+        drain().await;
+
+        ptx.send(PlayerEvent::Assigned { player_id: p1, device_id: d });
+        let title = |t: &str| PlayerEvent::TextMetadataUpdated {
+            player_id: p1,
+            metadata: FsctTextMetadata::CurrentTitle,
+            text: Some(t.into()),
+        };
+        ptx.send(title("First"));
+        ptx.send(title("First"));
+        ptx.send(title("Second"));
+        drain().await;
+
+        let beep = NotifyCall { device: d, notification: FsctNotification::Beep };
+        assert_eq!(applier.take_notify(), vec![beep.clone(), beep]);
+
+        let _ = handle.shutdown().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_device_rotates_auxiliary_content() {
+        use crate::aux_content::{AuxContent, AuxContentProvider};
+        use crate::time_format::ClockTime;
+
+        struct Fixed(&'static str);
+
+        #[async_trait::async_trait]
+        impl AuxContentProvider for Fixed {
+            fn name(&self) -> &str { self.0 }
+            fn refresh_interval(&self) -> Duration { Duration::from_secs(3600) }
+            async fn fetch(&self) -> Result<AuxContent, anyhow::Error> {
+                match self.0 {
+                    "clock" => Ok(AuxContent::clock(ClockTime { hour: 14, minute: 5 })),
+                    name => Ok(AuxContent::new(name)),
+                }
+            }
+        }
+
+        let registry = Arc::new(AuxContentRegistry::new()
+            .with_provider(Arc::new(Fixed("clock")))
+            .with_provider(Arc::new(Fixed("weather"))));
+        let aux_handle = registry.clone().run();
+        let applier = RecordingApplier::new();
+        let (orch, ptx, dtx) = build_orchestrator(applier.clone());
+        let orch = orch.with_aux_content(registry);
+        let control = orch.control();
+        let handle = run_orchestrator(orch).await;
+
+        let p1 = pid(701);
+        let d = make_ids(1)[0];
+        let rotation = AuxRotation {
+            providers: vec!["clock".into(), "weather".into()],
+            interval: Duration::from_secs(10),
+            start_after: Duration::from_secs(60),
+            time_format: Some(TimeFormat::for_locale("en-US")),
+        };
+        control.set_aux_rotation(d, Some(rotation)).await.unwrap();
+        ptx.send(PlayerEvent::Registered { player_id: p1, self_id: "p701".into() });
+        let _ = dtx.send(DeviceEvent::Added(d));
+        drain().await;
+        let _ = applier.take();
+
+        advance(Duration::from_secs(60)).await;
+        advance(Duration::from_secs(10)).await;
+        let shown: Vec<_> = applier.take().into_iter().map(|c| c.state.texts.title.unwrap()).collect();
+        assert_eq!(shown, vec!["2:05 PM".to_string(), "weather".to_string()]);
+
+        let mut playing = default_state_with_title("Song");
+        playing.status = FsctStatus::Playing;
+        ptx.send(PlayerEvent::StateUpdated { player_id: p1, state: playing.clone() });
+        drain().await;
+        assert_eq!(applier.take(), vec![ApplyCall { device: d, state: playing }]);
+
+        let _ = handle.shutdown().await;
+        let _ = aux_handle.shutdown().await;
+    }
 }
@@ -0,0 +1,280 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, Weak};
+
+use log::debug;
+use tokio::sync::Notify;
+
+use crate::player_events::PlayerEvent;
+use crate::player_manager::ManagedPlayerId;
+
+/// Default number of events buffered per player and subscriber.
+pub const DEFAULT_PLAYER_QUEUE_CAPACITY: usize = 64;
+
+/// Whether `newer` makes `queued` obsolete, i.e. carries everything of the player's state `queued` did.
+fn supersedes(newer: &PlayerEvent, queued: &PlayerEvent) -> bool {
+    use PlayerEvent::*;
+    match (newer, queued) {
+        (StateUpdated { .. },
+         StateUpdated { .. } | StatusUpdated { .. } | TimelineUpdated { .. } | VolumeUpdated { .. }
+         | ModesUpdated { .. } | TextMetadataUpdated { .. }) => true,
+        (StatusUpdated { .. }, StatusUpdated { .. })
+        | (TimelineUpdated { .. }, TimelineUpdated { .. })
+        | (VolumeUpdated { .. }, VolumeUpdated { .. })
+        | (ModesUpdated { .. }, ModesUpdated { .. }) => true,
+        (TextMetadataUpdated { metadata: newer, .. }, TextMetadataUpdated { metadata: queued, .. }) => newer == queued,
+        _ => false,
+    }
+}
+
+/// Whether the event starts or ends the life of a player, kept even in a full queue.
+fn is_lifecycle(event: &PlayerEvent) -> bool {
+    matches!(event, PlayerEvent::Registered { .. } | PlayerEvent::Unregistered { .. })
+}
+
+/// Events of one subscriber not received yet, with the sequence number they were sent with.
+#[derive(Default)]
+struct Pending {
+    players: HashMap<ManagedPlayerId, VecDeque<(u64, PlayerEvent)>>,
+    /// Players with queued events, in the order they take turns.
+    turns: VecDeque<ManagedPlayerId>,
+    global: VecDeque<(u64, PlayerEvent)>,
+    closed: bool,
+}
+
+impl Pending {
+    fn push(&mut self, seq: u64, event: PlayerEvent, capacity: usize) {
+        let Some(player_id) = event.player_id() else {
+            self.global.push_back((seq, event));
+            return;
+        };
+        let queue = self.players.entry(player_id).or_default();
+        if queue.is_empty() {
+            self.turns.push_back(player_id);
+        }
+        if queue.len() >= capacity {
+            queue.retain(|(_, queued)| !supersedes(&event, queued));
+        }
+        if queue.len() >= capacity
+            && let Some(oldest) = queue.iter().position(|(_, queued)| !is_lifecycle(queued))
+        {
+            let (_, dropped) = queue.remove(oldest).expect("position is within the queue");
+            debug!("Event queue of player {} is full, dropping {:?}", player_id, dropped);
+        }
+        queue.push_back((seq, event));
+    }
+
+    /// Next event: players take turns, but no event passes a global one sent before it.
+    fn pop(&mut self) -> Option<PlayerEvent> {
+        let barrier = self.global.front().map(|(seq, _)| *seq);
+        for _ in 0..self.turns.len() {
+            let player_id = self.turns.pop_front()?;
+            let queue = self.players.get_mut(&player_id).expect("players with turns have queues");
+            if barrier.is_some_and(|barrier| queue.front().is_some_and(|(seq, _)| *seq > barrier)) {
+                self.turns.push_back(player_id);
+                continue;
+            }
+            let (_, event) = queue.pop_front().expect("players with turns have events");
+            if queue.is_empty() {
+                self.players.remove(&player_id);
+            } else {
+                self.turns.push_back(player_id);
+            }
+            return Some(event);
+        }
+        self.global.pop_front().map(|(_, event)| event)
+    }
+}
+
+struct Subscriber {
+    pending: Mutex<Pending>,
+    notify: Notify,
+}
+
+struct Subscribers {
+    next_seq: u64,
+    subscribers: Vec<Weak<Subscriber>>,
+}
+
+/// Fan-out of player events with a bounded queue per player and subscriber.
+///
+/// Receivers get the events of every player in the order they were sent, and the players take turns, so a player
+/// flooding updates never holds back the events of others. Events without a player
+/// ([`PlayerEvent::PreferredChanged`]) are received after everything sent before them. Sending never waits: once the
+/// queue of a player is full, a new update replaces the queued ones it supersedes, e.g. a status the previous
+/// status. An event superseding none, like an assignment or a track event, makes room by dropping the oldest queued
+/// event other than the registration and unregistration of the player, so only a player sending more events than
+/// its queue holds loses some of them.
+pub struct PlayerEventQueues {
+    capacity: usize,
+    subscribers: Mutex<Subscribers>,
+}
+
+impl PlayerEventQueues {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, subscribers: Mutex::new(Subscribers { next_seq: 0, subscribers: Vec::new() }) }
+    }
+
+    /// Creates a new receiver; it gets all events sent from now on.
+    pub fn subscribe(&self) -> PlayerEventReceiver {
+        let subscriber = Arc::new(Subscriber { pending: Mutex::new(Pending::default()), notify: Notify::new() });
+        self.subscribers.lock().unwrap().subscribers.push(Arc::downgrade(&subscriber));
+        PlayerEventReceiver { subscriber }
+    }
+
+    /// Sends the event to all receivers.
+    pub fn send(&self, event: PlayerEvent) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        let seq = subscribers.next_seq;
+        subscribers.next_seq += 1;
+        subscribers.subscribers.retain(|subscriber| {
+            let Some(subscriber) = subscriber.upgrade() else { return false };
+            subscriber.pending.lock().unwrap().push(seq, event.clone(), self.capacity);
+            subscriber.notify.notify_one();
+            true
+        });
+    }
+}
+
+impl Default for PlayerEventQueues {
+    fn default() -> Self {
+        Self::new(DEFAULT_PLAYER_QUEUE_CAPACITY)
+    }
+}
+
+impl Drop for PlayerEventQueues {
+    fn drop(&mut self) {
+        for subscriber in self.subscribers.get_mut().unwrap().subscribers.iter().filter_map(Weak::upgrade) {
+            subscriber.pending.lock().unwrap().closed = true;
+            subscriber.notify.notify_one();
+        }
+    }
+}
+
+/// Receiving side of [`PlayerEventQueues`].
+pub struct PlayerEventReceiver {
+    subscriber: Arc<Subscriber>,
+}
+
+impl PlayerEventReceiver {
+    /// Receives the next event; returns `None` once the sending side has been dropped and all events consumed.
+    pub async fn recv(&mut self) -> Option<PlayerEvent> {
+        loop {
+            {
+                let mut pending = self.subscriber.pending.lock().unwrap();
+                if let Some(event) = pending.pop() {
+                    return Some(event);
+                }
+                if pending.closed {
+                    return None;
+                }
+            }
+            // a notification while not waiting is kept for the next wait
+            self.subscriber.notify.notified().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::FsctStatus;
+    use std::num::NonZeroU32;
+
+    fn pid(n: u32) -> ManagedPlayerId { NonZeroU32::new(n).unwrap() }
+
+    #[tokio::test]
+    async fn full_queue_of_one_player_does_not_block_others() {
+        let queues = PlayerEventQueues::new(2);
+        let mut rx = queues.subscribe();
+        let chatty = pid(1);
+        let quiet = pid(2);
+        queues.send(PlayerEvent::StatusUpdated { player_id: chatty, status: FsctStatus::Playing });
+        queues.send(PlayerEvent::StatusUpdated { player_id: chatty, status: FsctStatus::Paused });
+        queues.send(PlayerEvent::StatusUpdated { player_id: chatty, status: FsctStatus::Stopped });
+        queues.send(PlayerEvent::Registered { player_id: quiet, self_id: "quiet".into() });
+
+        let mut received = Vec::new();
+        for _ in 0..2 {
+            received.push(rx.recv().await.unwrap());
+        }
+        assert!(received.iter().any(|e| matches!(e, PlayerEvent::Registered { player_id, .. } if *player_id == quiet)));
+        // the full queue coalesced the statuses into the last one
+        let statuses: Vec<_> = received.iter()
+            .filter_map(|e| match e {
+                PlayerEvent::StatusUpdated { status, .. } => Some(*status),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(statuses, [FsctStatus::Stopped]);
+    }
+
+    #[tokio::test]
+    async fn full_queue_drops_the_oldest_events_superseding_nothing() {
+        let queues = PlayerEventQueues::new(4);
+        let mut rx = queues.subscribe();
+        let player_id = pid(1);
+        let devices: Vec<_> = (0..10).map(uuid::Uuid::from_u128).collect();
+        queues.send(PlayerEvent::Registered { player_id, self_id: "p1".into() });
+        for device_id in &devices {
+            queues.send(PlayerEvent::Assigned { player_id, device_id: *device_id });
+        }
+        drop(queues);
+
+        let mut received = Vec::new();
+        while let Some(event) = rx.recv().await {
+            received.push(event);
+        }
+        assert_eq!(received.len(), 4);
+        assert!(matches!(received[0], PlayerEvent::Registered { .. }));
+        assert!(matches!(received[3], PlayerEvent::Assigned { device_id, .. } if device_id == devices[9]));
+    }
+
+    #[tokio::test]
+    async fn events_of_a_player_keep_their_order() {
+        let queues = PlayerEventQueues::default();
+        let mut rx = queues.subscribe();
+        let player_id = pid(1);
+        queues.send(PlayerEvent::Registered { player_id, self_id: "p1".into() });
+        queues.send(PlayerEvent::StatusUpdated { player_id, status: FsctStatus::Playing });
+        queues.send(PlayerEvent::Unregistered { player_id });
+
+        assert!(matches!(rx.recv().await, Some(PlayerEvent::Registered { .. })));
+        assert!(matches!(rx.recv().await, Some(PlayerEvent::StatusUpdated { status: FsctStatus::Playing, .. })));
+        assert!(matches!(rx.recv().await, Some(PlayerEvent::Unregistered { .. })));
+        drop(queues);
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn preferred_changes_follow_the_events_sent_before() {
+        let queues = PlayerEventQueues::default();
+        let mut rx = queues.subscribe();
+        let (first, second) = (pid(1), pid(2));
+        queues.send(PlayerEvent::Registered { player_id: first, self_id: "first".into() });
+        queues.send(PlayerEvent::Registered { player_id: second, self_id: "second".into() });
+        queues.send(PlayerEvent::PreferredChanged { preferred: Some(second) });
+        queues.send(PlayerEvent::Unregistered { player_id: first });
+
+        assert!(matches!(rx.recv().await, Some(PlayerEvent::Registered { player_id, .. }) if player_id == first));
+        assert!(matches!(rx.recv().await, Some(PlayerEvent::Registered { player_id, .. }) if player_id == second));
+        assert!(matches!(rx.recv().await, Some(PlayerEvent::PreferredChanged { preferred: Some(p) }) if p == second));
+        assert!(matches!(rx.recv().await, Some(PlayerEvent::Unregistered { .. })));
+    }
+}
@@ -15,13 +15,22 @@
 // This file is part of an implementation of Ferrum Streaming Control Technology™,
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
-use crate::definitions::{FsctStatus, FsctTextMetadata, TimelineInfo};
+use std::collections::HashSet;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::definitions::{FsctStatus, FsctTextMetadata, PlaybackModes, TimelineInfo, VolumeInfo};
 use crate::device_manager::ManagedDeviceId;
-use crate::player_state::PlayerState;
+use crate::player_state::{PlayerState, TrackMetadata};
 use crate::player_manager::ManagedPlayerId;
+use crate::serde_format::duration_secs;
 
 /// Events emitted by PlayerManager about player lifecycle, assignments and state changes.
-#[derive(Debug, Clone)]
+///
+/// Serialized as an object tagged with a snake_case `type`, e.g. `{"type": "registered", "player_id": 1, "self_id": "..."}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum PlayerEvent {
     /// A new player has been registered.
     Registered { player_id: ManagedPlayerId, self_id: String },
@@ -44,9 +53,147 @@ pub enum PlayerEvent {
     /// Player's state has been partially updated, timeline has changed.
     TimelineUpdated { player_id: ManagedPlayerId, timeline: TimelineInfo},
 
+    /// Player's state has been partially updated, volume has changed.
+    VolumeUpdated { player_id: ManagedPlayerId, volume: Option<VolumeInfo> },
+
+    /// Player's state has been partially updated, shuffle or repeat mode has changed.
+    ModesUpdated { player_id: ManagedPlayerId, modes: PlaybackModes },
+
     /// Player's state has been partially updated, text metadata has changed.
     TextMetadataUpdated { player_id: ManagedPlayerId, metadata: FsctTextMetadata, text: Option<String>},
 
     /// Preferred player selection changed. Contains the new preferred player id or None.
     PreferredChanged { preferred: Option<ManagedPlayerId> },
+
+    /// A track started playing, see [`TrackEdgeDetector`](crate::track_events::TrackEdgeDetector).
+    ///
+    /// Carries the texts known when playback started; with partial updates the remaining texts of the track may
+    /// follow as [`PlayerEvent::TextMetadataUpdated`].
+    TrackStarted { player_id: ManagedPlayerId, track: TrackMetadata },
+
+    /// A started track ended, with all its texts, how long it was playing and whether it played to the end.
+    TrackEnded {
+        player_id: ManagedPlayerId,
+        track: TrackMetadata,
+        #[serde(with = "duration_secs")]
+        played: Duration,
+        completed: bool,
+    },
+}
+
+impl PlayerEvent {
+    /// Player the event is about, `None` for events not bound to a single player.
+    pub fn player_id(&self) -> Option<ManagedPlayerId> {
+        match self {
+            PlayerEvent::Registered { player_id, .. }
+            | PlayerEvent::Unregistered { player_id }
+            | PlayerEvent::Assigned { player_id, .. }
+            | PlayerEvent::Unassigned { player_id, .. }
+            | PlayerEvent::StateUpdated { player_id, .. }
+            | PlayerEvent::StatusUpdated { player_id, .. }
+            | PlayerEvent::TimelineUpdated { player_id, .. }
+            | PlayerEvent::VolumeUpdated { player_id, .. }
+            | PlayerEvent::ModesUpdated { player_id, .. }
+            | PlayerEvent::TextMetadataUpdated { player_id, .. }
+            | PlayerEvent::TrackStarted { player_id, .. }
+            | PlayerEvent::TrackEnded { player_id, .. } => Some(*player_id),
+            PlayerEvent::PreferredChanged { .. } => None,
+        }
+    }
+
+    pub fn kind(&self) -> PlayerEventKind {
+        match self {
+            PlayerEvent::Registered { .. } => PlayerEventKind::Registered,
+            PlayerEvent::Unregistered { .. } => PlayerEventKind::Unregistered,
+            PlayerEvent::Assigned { .. } => PlayerEventKind::Assigned,
+            PlayerEvent::Unassigned { .. } => PlayerEventKind::Unassigned,
+            PlayerEvent::StateUpdated { .. } => PlayerEventKind::StateUpdated,
+            PlayerEvent::StatusUpdated { .. } => PlayerEventKind::StatusUpdated,
+            PlayerEvent::TimelineUpdated { .. } => PlayerEventKind::TimelineUpdated,
+            PlayerEvent::VolumeUpdated { .. } => PlayerEventKind::VolumeUpdated,
+            PlayerEvent::ModesUpdated { .. } => PlayerEventKind::ModesUpdated,
+            PlayerEvent::TextMetadataUpdated { .. } => PlayerEventKind::TextMetadataUpdated,
+            PlayerEvent::PreferredChanged { .. } => PlayerEventKind::PreferredChanged,
+            PlayerEvent::TrackStarted { .. } => PlayerEventKind::TrackStarted,
+            PlayerEvent::TrackEnded { .. } => PlayerEventKind::TrackEnded,
+        }
+    }
+}
+
+/// Class of a [`PlayerEvent`], named like its `type` tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlayerEventKind {
+    Registered,
+    Unregistered,
+    Assigned,
+    Unassigned,
+    StateUpdated,
+    StatusUpdated,
+    TimelineUpdated,
+    VolumeUpdated,
+    ModesUpdated,
+    TextMetadataUpdated,
+    PreferredChanged,
+    TrackStarted,
+    TrackEnded,
+}
+
+/// Selects the player events a filtered subscriber receives; an unset criterion matches every event.
+///
+/// Events not bound to a single player ([`PlayerEvent::PreferredChanged`]) pass the player criterion.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerEventFilter {
+    pub kinds: Option<HashSet<PlayerEventKind>>,
+    pub players: Option<HashSet<ManagedPlayerId>>,
+}
+
+impl PlayerEventFilter {
+    /// Filter matching every event.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Restricts the filter to events of the given kinds.
+    pub fn with_kinds(mut self, kinds: impl IntoIterator<Item = PlayerEventKind>) -> Self {
+        self.kinds = Some(kinds.into_iter().collect());
+        self
+    }
+
+    /// Restricts the filter to events of the given players.
+    pub fn with_players(mut self, players: impl IntoIterator<Item = ManagedPlayerId>) -> Self {
+        self.players = Some(players.into_iter().collect());
+        self
+    }
+
+    pub fn matches(&self, event: &PlayerEvent) -> bool {
+        let kind_matches = self.kinds.as_ref().is_none_or(|kinds| kinds.contains(&event.kind()));
+        let player_matches = match (&self.players, event.player_id()) {
+            (Some(players), Some(player_id)) => players.contains(&player_id),
+            _ => true,
+        };
+        kind_matches && player_matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroU32;
+
+    #[test]
+    fn filter_matches_kinds_and_players() {
+        let player = NonZeroU32::new(1).unwrap();
+        let other = NonZeroU32::new(2).unwrap();
+        let filter = PlayerEventFilter::all()
+            .with_kinds([PlayerEventKind::StateUpdated, PlayerEventKind::PreferredChanged])
+            .with_players([player]);
+
+        assert!(filter.matches(&PlayerEvent::StateUpdated { player_id: player, state: PlayerState::default() }));
+        assert!(!filter.matches(&PlayerEvent::StateUpdated { player_id: other, state: PlayerState::default() }));
+        assert!(!filter.matches(&PlayerEvent::StatusUpdated { player_id: player, status: FsctStatus::Playing }));
+        assert!(filter.matches(&PlayerEvent::PreferredChanged { preferred: Some(other) }));
+        assert!(PlayerEventFilter::all().matches(&PlayerEvent::Unregistered { player_id: other }));
+    }
 }
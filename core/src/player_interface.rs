@@ -0,0 +1,138 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+
+//! Control of players by the host: the way back from devices to the players they show.
+//!
+//! Ports that can control their backend implement [`PlayerInterface`] and attach it to their player with
+//! [`FsctDriver::set_player_interface`](crate::FsctDriver::set_player_interface). Playback commands sent by a
+//! device are then carried out by the player the device shows.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+
+use crate::definitions::{PlaybackCommand, VolumeInfo};
+use crate::player_manager::ManagedPlayerId;
+
+/// Playback control of a player; controls the player doesn't offer keep the default, failing implementation.
+#[async_trait]
+pub trait PlayerInterface: Send + Sync {
+    async fn play(&self) -> Result<(), Error> {
+        Err(anyhow!("Play is not supported"))
+    }
+
+    async fn pause(&self) -> Result<(), Error> {
+        Err(anyhow!("Pause is not supported"))
+    }
+
+    async fn next_track(&self) -> Result<(), Error> {
+        Err(anyhow!("Skipping to the next track is not supported"))
+    }
+
+    async fn previous_track(&self) -> Result<(), Error> {
+        Err(anyhow!("Skipping to the previous track is not supported"))
+    }
+
+    /// Seeks to the position from the start of the current track.
+    async fn seek(&self, _position: Duration) -> Result<(), Error> {
+        Err(anyhow!("Seeking is not supported"))
+    }
+
+    async fn set_volume(&self, _volume: VolumeInfo) -> Result<(), Error> {
+        Err(anyhow!("Setting the volume is not supported"))
+    }
+
+    /// Carries out the command with the matching control.
+    async fn execute(&self, command: PlaybackCommand) -> Result<(), Error> {
+        match command {
+            PlaybackCommand::Play => self.play().await,
+            PlaybackCommand::Pause => self.pause().await,
+            PlaybackCommand::Next => self.next_track().await,
+            PlaybackCommand::Previous => self.previous_track().await,
+            PlaybackCommand::Seek { position } => self.seek(position).await,
+            PlaybackCommand::SetVolume { volume } => self.set_volume(volume).await,
+        }
+    }
+}
+
+/// Interfaces attached to registered players.
+#[derive(Default)]
+pub struct PlayerInterfaces {
+    interfaces: Mutex<HashMap<ManagedPlayerId, Arc<dyn PlayerInterface>>>,
+}
+
+impl PlayerInterfaces {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attaches the interface to the player, or detaches it with `None`.
+    pub fn set(&self, player_id: ManagedPlayerId, interface: Option<Arc<dyn PlayerInterface>>) {
+        let mut interfaces = self.interfaces.lock().unwrap();
+        match interface {
+            Some(interface) => interfaces.insert(player_id, interface),
+            None => interfaces.remove(&player_id),
+        };
+    }
+
+    pub fn get(&self, player_id: ManagedPlayerId) -> Option<Arc<dyn PlayerInterface>> {
+        self.interfaces.lock().unwrap().get(&player_id).cloned()
+    }
+
+    /// Carries out the command on the player; fails if the player has no interface or the control failed.
+    pub async fn execute(&self, player_id: ManagedPlayerId, command: PlaybackCommand) -> Result<(), Error> {
+        let interface = self.get(player_id).ok_or_else(|| anyhow!("Player {} can't be controlled", player_id))?;
+        interface.execute(command).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct PausingPlayer {
+        paused: Mutex<bool>,
+    }
+
+    #[async_trait]
+    impl PlayerInterface for PausingPlayer {
+        async fn pause(&self) -> Result<(), Error> {
+            *self.paused.lock().unwrap() = true;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn commands_are_carried_out_by_attached_interfaces() {
+        let player_id = ManagedPlayerId::new(1).unwrap();
+        let player = Arc::new(PausingPlayer::default());
+        let interfaces = PlayerInterfaces::new();
+
+        assert!(interfaces.execute(player_id, PlaybackCommand::Pause).await.is_err());
+        interfaces.set(player_id, Some(player.clone()));
+        interfaces.execute(player_id, PlaybackCommand::Pause).await.unwrap();
+        assert!(*player.paused.lock().unwrap());
+        assert!(interfaces.execute(player_id, PlaybackCommand::Next).await.is_err());
+        interfaces.set(player_id, None);
+        assert!(interfaces.get(player_id).is_none());
+    }
+}
@@ -19,14 +19,22 @@ use std::collections::HashMap;
 use std::num::NonZeroU32;
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::SystemTime;
 use anyhow::Error;
-use log::{info};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
 
 use crate::device_manager::ManagedDeviceId;
-use crate::player_events::PlayerEvent;
+use crate::event_stamp::{EventStamper, Stamped};
+use crate::player_events::{PlayerEvent, PlayerEventFilter};
+use crate::player_interface::{PlayerInterface, PlayerInterfaces};
+use crate::player_origin::{PlayerOrigin, PlayerOrigins};
+use crate::player_event_queue::{PlayerEventQueues, PlayerEventReceiver};
 use crate::player_state::PlayerState;
+use crate::track_events::TrackEdgeDetector;
+use crate::validation::{sanitize_state, sanitize_text, sanitize_timeline, sanitize_volume, ValidationError};
 use tokio::sync::broadcast;
-use crate::definitions::{FsctStatus, FsctTextMetadata, TimelineInfo};
+use crate::definitions::{FsctStatus, FsctTextMetadata, PlaybackModes, TimelineInfo, VolumeInfo};
 
 /// Type alias for player ID
 pub type ManagedPlayerId = NonZeroU32;
@@ -39,10 +47,29 @@ pub struct RegisteredPlayer {
     pub assigned_device: Option<ManagedDeviceId>,
 }
 
+/// A registered player as listed by [`PlayerManager::list_players`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    pub player_id: ManagedPlayerId,
+    pub self_id: String,
+    pub assigned_device: Option<ManagedDeviceId>,
+    pub state: PlayerState,
+    /// Where the player was registered from; local players have none.
+    #[serde(default, skip_serializing_if = "PlayerOrigin::is_local")]
+    pub origin: PlayerOrigin,
+}
+
 /// Manages players and their device assignments
 pub struct PlayerManager {
     players: Arc<Mutex<HashMap<ManagedPlayerId, RegisteredPlayer>>>,
     events_tx: broadcast::Sender<PlayerEvent>,
+    stamped_tx: broadcast::Sender<Stamped<PlayerEvent>>,
+    filtered_txs: Mutex<Vec<(PlayerEventFilter, broadcast::Sender<PlayerEvent>)>>,
+    stamper: EventStamper,
+    event_queues: PlayerEventQueues,
+    track_edges: Mutex<TrackEdgeDetector>,
+    interfaces: Arc<PlayerInterfaces>,
+    origins: Arc<PlayerOrigins>,
     next_player_id: AtomicU32,
     preferred_player_id: AtomicU32, // 0 = None, NonZeroU32 = Some
 }
@@ -51,22 +78,87 @@ impl PlayerManager {
     /// Creates a new PlayerManager
     pub fn new() -> Self {
         let (events_tx, _) = broadcast::channel(256);
+        let (stamped_tx, _) = broadcast::channel(256);
         Self {
             players: Arc::new(Mutex::new(HashMap::new())),
             events_tx,
+            stamped_tx,
+            filtered_txs: Mutex::new(Vec::new()),
+            stamper: EventStamper::new(),
+            event_queues: PlayerEventQueues::default(),
+            track_edges: Mutex::new(TrackEdgeDetector::new()),
+            interfaces: Arc::new(PlayerInterfaces::new()),
+            origins: Arc::new(PlayerOrigins::new()),
             next_player_id: AtomicU32::new(1), // Start from 1
             preferred_player_id: AtomicU32::new(0), // None by default
         }
     }
 
     /// Subscribes to player events emitted by this manager.
+    ///
+    /// Lagging receivers lose events; use [`PlayerManager::subscribe_queued`] where every event matters.
     pub fn subscribe(&self) -> broadcast::Receiver<PlayerEvent> {
         self.events_tx.subscribe()
     }
 
+    /// Subscribes to player events through lossless per-player queues.
+    ///
+    /// A full queue coalesces the updates of that player, without affecting other players.
+    pub fn subscribe_queued(&self) -> PlayerEventReceiver {
+        self.event_queues.subscribe()
+    }
+
+    /// Subscribes to player events stamped with origin time and sequence number, for consumers that order or
+    /// de-duplicate events (journal, metrics, remote subscribers). Lagging receivers lose events.
+    pub fn subscribe_stamped(&self) -> broadcast::Receiver<Stamped<PlayerEvent>> {
+        self.stamped_tx.subscribe()
+    }
+
+    /// Subscribes to the player events matching `filter`.
+    ///
+    /// Events are filtered before they are sent, so the receiver only lags behind on events it asked for.
+    pub fn subscribe_filtered(&self, filter: PlayerEventFilter) -> broadcast::Receiver<PlayerEvent> {
+        let (tx, rx) = broadcast::channel(256);
+        self.filtered_txs.lock().unwrap().push((filter, tx));
+        rx
+    }
+
+    fn broadcast(&self, event: &PlayerEvent) {
+        let _ = self.stamped_tx.send(self.stamper.stamp(event.clone()));
+        let _ = self.events_tx.send(event.clone());
+        let mut filtered_txs = self.filtered_txs.lock().unwrap();
+        filtered_txs.retain(|(_, tx)| tx.receiver_count() > 0);
+        for (filter, tx) in filtered_txs.iter() {
+            if filter.matches(event) {
+                let _ = tx.send(event.clone());
+            }
+        }
+    }
+
+    fn emit(&self, event: PlayerEvent) {
+        self.broadcast(&event);
+        self.event_queues.send(event);
+    }
+
+    /// Emits `event` followed by the track edges the player's new `state` caused.
+    fn emit_state_change(&self, player_id: ManagedPlayerId, state: PlayerState, event: PlayerEvent) {
+        let edges = self.track_edges.lock().unwrap().observe(player_id, &state, SystemTime::now());
+        self.emit(event);
+        for edge in edges {
+            self.emit(edge);
+        }
+    }
+
     /// Registers a new player
     pub async fn register_player(&self, self_id: String) -> Result<ManagedPlayerId, Error> {
+        self.register_player_with_origin(self_id, PlayerOrigin::default()).await
+    }
+
+    /// Registers a new player tagged with the host and user it was registered from.
+    pub async fn register_player_with_origin(&self, self_id: String, origin: PlayerOrigin)
+                                             -> Result<ManagedPlayerId, Error> {
         let player_id = self.assign_new_player_id();
+        self.origins.set(player_id, Some(origin));
 
         let player_state = Arc::new(Mutex::new(Default::default()));
 
@@ -81,7 +173,7 @@ impl PlayerManager {
         self.players.lock().unwrap().insert(player_id, registered_player);
 
         // Notify listeners
-        let _ = self.events_tx.send(PlayerEvent::Registered { player_id, self_id });
+        self.emit(PlayerEvent::Registered { player_id, self_id });
 
         info!("Player {} registered", player_id);
         Ok(player_id)
@@ -92,6 +184,40 @@ impl PlayerManager {
         NonZeroU32::new(id_u32).expect("ManagedPlayerId must be non-zero")
     }
 
+    /// All registered players, ordered by id.
+    pub fn list_players(&self) -> Vec<PlayerInfo> {
+        let players = self.players.lock().unwrap();
+        let mut list: Vec<_> = players.iter().map(|(player_id, player)| PlayerInfo {
+            player_id: *player_id,
+            self_id: player.self_id.clone(),
+            assigned_device: player.assigned_device,
+            state: player.state.lock().unwrap().clone(),
+            origin: self.origins.get(*player_id),
+        }).collect();
+        list.sort_by_key(|player| player.player_id);
+        list
+    }
+
+    /// Interfaces attached to the players, for carrying out playback commands of devices.
+    pub fn player_interfaces(&self) -> Arc<PlayerInterfaces> {
+        self.interfaces.clone()
+    }
+
+    /// Origins of the players, for routing by origin.
+    pub fn player_origins(&self) -> Arc<PlayerOrigins> {
+        self.origins.clone()
+    }
+
+    /// Attaches the interface the player is controlled through, or detaches it with `None`.
+    pub fn set_player_interface(&self, player_id: ManagedPlayerId, interface: Option<Arc<dyn PlayerInterface>>)
+                                -> Result<(), Error> {
+        if !self.players.lock().unwrap().contains_key(&player_id) {
+            return Err(anyhow::anyhow!("Player not found"));
+        }
+        self.interfaces.set(player_id, interface);
+        Ok(())
+    }
+
     /// Unregisters a player
     pub async fn unregister_player(&self, player_id: ManagedPlayerId) -> Result<(), Error> {
         // Remove the player and capture assigned device without holding the lock across await
@@ -103,10 +229,11 @@ impl PlayerManager {
                 return Err(anyhow::anyhow!("Player not found"));
             }
         };
+        self.interfaces.set(player_id, None);
 
         // Unassign from device if assigned (no players lock held here)
         if let Some(device_id) = assigned_device {
-            let _ = self.events_tx.send(PlayerEvent::Unassigned { player_id, device_id });
+            self.emit(PlayerEvent::Unassigned { player_id, device_id });
             info!("Player {} unassigned from device {}", player_id, device_id);
         }
 
@@ -114,10 +241,15 @@ impl PlayerManager {
         let current_pref = self.preferred_player_id.load(Ordering::SeqCst);
         if current_pref == player_id.get() {
             let _ = self.preferred_player_id.compare_exchange(player_id.get(), 0, Ordering::SeqCst, Ordering::SeqCst);
-            let _ = self.events_tx.send(PlayerEvent::PreferredChanged { preferred: None });
+            self.emit(PlayerEvent::PreferredChanged { preferred: None });
+        }
+        let track_end = self.track_edges.lock().unwrap().remove(player_id, SystemTime::now());
+        if let Some(track_end) = track_end {
+            self.emit(track_end);
         }
         // Notify listeners
-        let _ = self.events_tx.send(PlayerEvent::Unregistered { player_id });
+        self.emit(PlayerEvent::Unregistered { player_id });
+        self.origins.set(player_id, None);
 
         info!("Player {} unregistered", player_id);
         Ok(())
@@ -136,9 +268,9 @@ impl PlayerManager {
         };
 
         // Notify about assignment
-        let _ = self.events_tx.send(PlayerEvent::Assigned { player_id, device_id });
+        self.emit(PlayerEvent::Assigned { player_id, device_id });
         // Also emit current state so consumers may immediately propagate it if needed
-        let _ = self.events_tx.send(PlayerEvent::StateUpdated { player_id, state: player_state });
+        self.emit(PlayerEvent::StateUpdated { player_id, state: player_state });
 
         info!("Player {} assigned to device {}", player_id, device_id);
         Ok(())
@@ -165,7 +297,7 @@ impl PlayerManager {
         }
 
         // Notify listeners about unassignment
-        let _ = self.events_tx.send(PlayerEvent::Unassigned { player_id, device_id });
+        self.emit(PlayerEvent::Unassigned { player_id, device_id });
 
         info!("Player {} unassigned from device {}", player_id, device_id);
         Ok(())
@@ -182,7 +314,11 @@ impl PlayerManager {
     }
 
     /// Updates a player's state
-    pub async fn update_player_state(&self, player_id: ManagedPlayerId, new_state: PlayerState) -> Result<(), Error> {
+    ///
+    /// Like the partial updates below, the state is sanitized before it is stored or broadcast, so that one buggy
+    /// port can't corrupt what devices show; see [`sanitize_state`].
+    pub async fn update_player_state(&self, player_id: ManagedPlayerId, mut new_state: PlayerState) -> Result<(), Error> {
+        report_sanitized(player_id, sanitize_state(&mut new_state, SystemTime::now()));
         {
             let players = self.players.lock().unwrap();
             if let Some(player) = players.get(&player_id) {
@@ -193,7 +329,7 @@ impl PlayerManager {
         }
 
         // Notify listeners about the new state
-        let _ = self.events_tx.send(PlayerEvent::StateUpdated { player_id, state: new_state });
+        self.emit_state_change(player_id, new_state.clone(), PlayerEvent::StateUpdated { player_id, state: new_state });
 
         Ok(())
     }
@@ -201,49 +337,108 @@ impl PlayerManager {
 
     pub async fn update_player_status(&self, player_id: ManagedPlayerId, new_status: FsctStatus) -> Result<(), Error>
     {
-        {
+        let state = {
             let players = self.players.lock().unwrap();
             if let Some(player) = players.get(&player_id) {
                 let mut state = player.state.lock().unwrap();
                 state.status = new_status;
+                state.clone()
             } else {
                 return Err(anyhow::anyhow!("Player not found"));
             }
-        }
-        let _ = self.events_tx.send(PlayerEvent::StatusUpdated { player_id, status: new_status });
+        };
+        self.emit_state_change(player_id, state, PlayerEvent::StatusUpdated { player_id, status: new_status });
         Ok(())
     }
 
-    pub async fn update_player_timeline(&self, player_id: ManagedPlayerId, new_timeline: Option<TimelineInfo>) -> Result<(), Error>
+    /// Updates the timeline; timelines that can't be sanitized are rejected, keeping the previous one.
+    pub async fn update_player_timeline(&self, player_id: ManagedPlayerId, mut new_timeline: Option<TimelineInfo>) -> Result<(), Error>
     {
-        {
+        if let Some(timeline) = &mut new_timeline {
+            match sanitize_timeline(timeline, SystemTime::now()) {
+                Ok(repaired) => report_sanitized(player_id, repaired),
+                Err(e) => {
+                    warn!("Rejecting timeline of player {}: {}", player_id, e);
+                    return Err(e.into());
+                }
+            }
+        }
+        let state = {
             let players = self.players.lock().unwrap();
             if let Some(player) = players.get(&player_id) {
                 let mut state = player.state.lock().unwrap();
                 state.timeline = new_timeline.clone();
+                state.clone()
             } else {
                 return Err(anyhow::anyhow!("Player not found"));
             }
+        };
+        match new_timeline {
+            Some(timeline) => {
+                self.emit_state_change(player_id, state, PlayerEvent::TimelineUpdated { player_id, timeline });
+            }
+            None => {
+                let edges = self.track_edges.lock().unwrap().observe(player_id, &state, SystemTime::now());
+                for edge in edges {
+                    self.emit(edge);
+                }
+            }
         }
-        if let Some(timeline) = new_timeline {
-            let _ = self.events_tx.send(PlayerEvent::TimelineUpdated { player_id, timeline });
+        Ok(())
+    }
+
+    pub async fn update_player_volume(&self, player_id: ManagedPlayerId, mut new_volume: Option<VolumeInfo>) -> Result<(), Error>
+    {
+        if let Some(volume) = &mut new_volume {
+            report_sanitized(player_id, sanitize_volume(volume));
         }
+        let state = {
+            let players = self.players.lock().unwrap();
+            if let Some(player) = players.get(&player_id) {
+                let mut state = player.state.lock().unwrap();
+                state.volume = new_volume;
+                state.clone()
+            } else {
+                return Err(anyhow::anyhow!("Player not found"));
+            }
+        };
+        self.emit_state_change(player_id, state, PlayerEvent::VolumeUpdated { player_id, volume: new_volume });
         Ok(())
     }
 
-    pub async fn update_player_metadata(&self, player_id: ManagedPlayerId, metadata_id: FsctTextMetadata, new_text: Option<String>) -> Result<(), Error>
+    pub async fn update_player_modes(&self, player_id: ManagedPlayerId, new_modes: PlaybackModes) -> Result<(), Error>
     {
-        {
+        let state = {
+            let players = self.players.lock().unwrap();
+            if let Some(player) = players.get(&player_id) {
+                let mut state = player.state.lock().unwrap();
+                state.modes = new_modes;
+                state.clone()
+            } else {
+                return Err(anyhow::anyhow!("Player not found"));
+            }
+        };
+        self.emit_state_change(player_id, state, PlayerEvent::ModesUpdated { player_id, modes: new_modes });
+        Ok(())
+    }
+
+    pub async fn update_player_metadata(&self, player_id: ManagedPlayerId, metadata_id: FsctTextMetadata, mut new_text: Option<String>) -> Result<(), Error>
+    {
+        if let Some(text) = &mut new_text {
+            report_sanitized(player_id, sanitize_text(text));
+        }
+        let state = {
             let players = self.players.lock().unwrap();
             if let Some(player) = players.get(&player_id) {
                 let mut state = player.state.lock().unwrap();
                 let slot = state.texts.get_mut_text(metadata_id);
                 *slot = new_text.clone();
+                state.clone()
             } else {
                 return Err(anyhow::anyhow!("Player not found"));
             }
-        }
-        let _ = self.events_tx.send(PlayerEvent::TextMetadataUpdated { player_id, metadata: metadata_id, text: new_text });
+        };
+        self.emit_state_change(player_id, state, PlayerEvent::TextMetadataUpdated { player_id, metadata: metadata_id, text: new_text });
         Ok(())
     }
 
@@ -260,7 +455,7 @@ impl PlayerManager {
         let new_val = preferred.map(ManagedPlayerId::get).unwrap_or(0);
         let old_val = self.preferred_player_id.swap(new_val, Ordering::SeqCst);
         if old_val != new_val {
-            let _ = self.events_tx.send(PlayerEvent::PreferredChanged { preferred });
+            self.emit(PlayerEvent::PreferredChanged { preferred });
         }
         Ok(())
     }
@@ -269,4 +464,17 @@ impl PlayerManager {
     pub fn get_preferred_player(&self) -> Option<ManagedPlayerId> {
         NonZeroU32::new(self.preferred_player_id.load(Ordering::SeqCst))
     }
+
+    /// Finds a registered player by its self identifier; the first registered one if several share it.
+    pub fn find_player(&self, self_id: &str) -> Option<ManagedPlayerId> {
+        let players = self.players.lock().unwrap();
+        players.iter().filter(|(_, player)| player.self_id == self_id).map(|(id, _)| *id).min()
+    }
+}
+
+/// Warns about what was repaired in an update of the player.
+fn report_sanitized(player_id: ManagedPlayerId, repaired: Vec<ValidationError>) {
+    for e in repaired {
+        warn!("Sanitized update of player {}: {}", player_id, e);
+    }
 }
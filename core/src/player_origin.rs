@@ -0,0 +1,128 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Origins of players, for setups where several hosts forward their players to one service.
+//!
+//! Players registered over the driver server are tagged with the host and user they come from; players of the
+//! service's own process have no origin and count as host [`LOCAL_ORIGIN_HOST`]. An [`OriginFilter`] of a device
+//! (see [`OrchestratorControl::set_origin_filter`](crate::OrchestratorControl::set_origin_filter)) limits the
+//! players the device shows by origin, e.g. living-room devices only showing players of the living-room PC.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::player_manager::ManagedPlayerId;
+
+/// Host name players of the service's own process are matched by.
+pub const LOCAL_ORIGIN_HOST: &str = "local";
+
+/// Host and user a player was registered from; `None` where unknown, a player without host is local.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerOrigin {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+impl PlayerOrigin {
+    pub fn new(host: Option<String>, user: Option<String>) -> Self {
+        Self { host, user }
+    }
+
+    pub fn is_local(&self) -> bool {
+        self.host.is_none()
+    }
+
+    /// Host name the origin is matched by, [`LOCAL_ORIGIN_HOST`] for local players.
+    pub fn host_name(&self) -> &str {
+        self.host.as_deref().unwrap_or(LOCAL_ORIGIN_HOST)
+    }
+}
+
+/// Origins of the players a device accepts: a player is accepted if its host is one of `hosts` and its user one
+/// of `users`, an empty list accepting any. Names are compared case-insensitively.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OriginFilter {
+    pub hosts: Vec<String>,
+    pub users: Vec<String>,
+}
+
+impl OriginFilter {
+    /// Filter accepting the players of the given hosts only.
+    pub fn hosts(hosts: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self { hosts: hosts.into_iter().map(Into::into).collect(), users: Vec::new() }
+    }
+
+    pub fn accepts(&self, origin: &PlayerOrigin) -> bool {
+        let listed = |names: &[String], name: Option<&str>| {
+            names.is_empty() || name.is_some_and(|name| names.iter().any(|listed| listed.eq_ignore_ascii_case(name)))
+        };
+        listed(&self.hosts, Some(origin.host_name())) && listed(&self.users, origin.user.as_deref())
+    }
+}
+
+/// Origins of the registered players, shared by the player manager with the orchestrator.
+#[derive(Default)]
+pub struct PlayerOrigins {
+    origins: Mutex<HashMap<ManagedPlayerId, PlayerOrigin>>,
+}
+
+impl PlayerOrigins {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the origin of the player, or forgets it with `None`.
+    pub fn set(&self, player_id: ManagedPlayerId, origin: Option<PlayerOrigin>) {
+        let mut origins = self.origins.lock().unwrap();
+        match origin {
+            Some(origin) => origins.insert(player_id, origin),
+            None => origins.remove(&player_id),
+        };
+    }
+
+    /// Origin of the player; local if unknown.
+    pub fn get(&self, player_id: ManagedPlayerId) -> PlayerOrigin {
+        self.origins.lock().unwrap().get(&player_id).cloned().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_match_hosts_and_users() {
+        let living_room = PlayerOrigin::new(Some("Living-Room".into()), Some("anna".into()));
+        let local = PlayerOrigin::default();
+
+        assert!(OriginFilter::default().accepts(&living_room));
+        assert!(OriginFilter::hosts(["living-room"]).accepts(&living_room));
+        assert!(!OriginFilter::hosts(["living-room"]).accepts(&local));
+        assert!(OriginFilter::hosts(["living-room", LOCAL_ORIGIN_HOST]).accepts(&local));
+
+        let anna = OriginFilter { hosts: Vec::new(), users: vec!["anna".into()] };
+        assert!(anna.accepts(&living_room));
+        // the user of local players is unknown
+        assert!(!anna.accepts(&local));
+    }
+}
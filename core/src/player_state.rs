@@ -17,16 +17,30 @@
 
 use crate::definitions::FsctStatus;
 use crate::definitions::*;
+use serde::{Deserialize, Serialize};
 use std::slice::Iter;
 
-#[derive(Debug, Clone, Default, PartialEq)]
+/// Texts of the current track, and of the track playing next for devices showing the queue; missing texts are
+/// serialized as `null`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct TrackMetadata {
     pub title: Option<String>,
     pub artist: Option<String>,
     pub album: Option<String>,
     pub genre: Option<String>,
+    /// Release year, as reported by the player (usually four digits).
+    pub year: Option<String>,
+    pub composer: Option<String>,
+    /// Title of the next track in the queue of the player.
+    pub next_title: Option<String>,
+    /// Artist of the next track in the queue of the player.
+    pub next_artist: Option<String>,
 }
 
+static TEXT_TYPES: [FsctTextMetadata; 8] = [FsctTextMetadata::CurrentTitle, FsctTextMetadata::CurrentAuthor,
+    FsctTextMetadata::CurrentAlbum, FsctTextMetadata::CurrentGenre, FsctTextMetadata::CurrentYear,
+    FsctTextMetadata::CurrentComposer, FsctTextMetadata::QueueTitle, FsctTextMetadata::QueueAuthor];
+
 // Iterator for track metadata remains
 pub struct TrackMetadataIterator<'a> {
     metadata: &'a TrackMetadata,
@@ -37,10 +51,8 @@ impl<'a> Iterator for TrackMetadataIterator<'a> {
     type Item = (FsctTextMetadata, &'a Option<String>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let text_types = [FsctTextMetadata::CurrentTitle, FsctTextMetadata::CurrentAuthor,
-            FsctTextMetadata::CurrentAlbum, FsctTextMetadata::CurrentGenre];
-        if self.index < text_types.len() {
-            let text_type = text_types[self.index];
+        if self.index < TEXT_TYPES.len() {
+            let text_type = TEXT_TYPES[self.index];
             let text = self.metadata.get_text(text_type);
             self.index += 1;
             Some((text_type, text))
@@ -57,6 +69,10 @@ impl TrackMetadata {
             FsctTextMetadata::CurrentAuthor => &self.artist,
             FsctTextMetadata::CurrentAlbum => &self.album,
             FsctTextMetadata::CurrentGenre => &self.genre,
+            FsctTextMetadata::CurrentYear => &self.year,
+            FsctTextMetadata::CurrentComposer => &self.composer,
+            FsctTextMetadata::QueueTitle => &self.next_title,
+            FsctTextMetadata::QueueAuthor => &self.next_artist,
             _ => &None,
         }
     }
@@ -67,6 +83,10 @@ impl TrackMetadata {
             FsctTextMetadata::CurrentAuthor => &mut self.artist,
             FsctTextMetadata::CurrentAlbum => &mut self.album,
             FsctTextMetadata::CurrentGenre => &mut self.genre,
+            FsctTextMetadata::CurrentYear => &mut self.year,
+            FsctTextMetadata::CurrentComposer => &mut self.composer,
+            FsctTextMetadata::QueueTitle => &mut self.next_title,
+            FsctTextMetadata::QueueAuthor => &mut self.next_artist,
             _ => panic!("Unsupported text type"),
         }
     }
@@ -79,16 +99,20 @@ impl TrackMetadata {
     }
 
     pub fn iter_id(&self) -> Iter<'static, FsctTextMetadata> {
-        static TEXT_TYPES: [FsctTextMetadata; 4] = [FsctTextMetadata::CurrentTitle, FsctTextMetadata::CurrentAuthor,
-            FsctTextMetadata::CurrentAlbum, FsctTextMetadata::CurrentGenre];
         TEXT_TYPES.iter()
     }
 }
 
 // PlayerState remains as a data structure
-#[derive(Debug, Clone, Default, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct PlayerState {
     pub status: FsctStatus,
     pub timeline: Option<TimelineInfo>,
     pub texts: TrackMetadata,
-}
\ No newline at end of file
+    /// Volume of the player, `null` if it doesn't report one.
+    #[serde(default)]
+    pub volume: Option<VolumeInfo>,
+    /// Shuffle and repeat modes of the player, each `null` if it doesn't report it.
+    #[serde(default)]
+    pub modes: PlaybackModes,
+}
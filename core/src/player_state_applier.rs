@@ -15,7 +15,8 @@
 // This file is part of an implementation of Ferrum Streaming Control Technology™,
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 
 use anyhow::Error;
@@ -23,8 +24,10 @@ use std::future::Future;
 use std::pin::Pin;
 
 use crate::device_manager::{DeviceControl, ManagedDeviceId};
-use crate::player_state::PlayerState;
-use crate::definitions::{FsctStatus, FsctTextMetadata, TimelineInfo};
+use crate::player_state::{PlayerState, TrackMetadata};
+use crate::quirks::StatusMap;
+use crate::text_template::TextLayout;
+use crate::definitions::{FsctNotification, FsctStatus, FsctTextMetadata, PlaybackModes, TimelineInfo, VolumeInfo};
 
 /// Abstraction for applying PlayerState to devices.
 ///
@@ -44,9 +47,31 @@ pub trait PlayerStateApplier: Send + Sync {
     fn apply_timeline<'a>(&'a self, device_id: ManagedDeviceId, timeline: Option<TimelineInfo>)
         -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>;
 
+    /// Apply only the volume independently.
+    fn apply_volume<'a>(&'a self, device_id: ManagedDeviceId, volume: Option<VolumeInfo>)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>;
+
+    /// Apply only the shuffle and repeat modes independently.
+    fn apply_modes<'a>(&'a self, device_id: ManagedDeviceId, modes: PlaybackModes)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>;
+
     /// Apply a single text field independently.
     fn apply_text<'a>(&'a self, device_id: ManagedDeviceId, text_id: FsctTextMetadata, text: Option<&'a str>)
         -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>;
+
+    /// Switch the device display on or off.
+    fn apply_enable<'a>(&'a self, device_id: ManagedDeviceId, enable: bool)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>;
+
+    /// Request a brief attention signal from the device.
+    fn apply_notify<'a>(&'a self, device_id: ManagedDeviceId, notification: FsctNotification)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>;
+
+    /// Forget what is assumed about the device state, so the next full apply writes every field.
+    ///
+    /// Called when a device is attached; devices seen before (re-attached) are reinitialized as well.
+    fn reset_device<'a>(&'a self, device_id: ManagedDeviceId)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>;
 }
 
 /// Direct implementation that wraps a DeviceControl provider.
@@ -54,6 +79,10 @@ pub trait PlayerStateApplier: Send + Sync {
 pub struct DirectDeviceControlApplier<T: DeviceControl + Send + Sync + 'static> {
     device_control: Arc<T>,
     last_applied: Mutex<HashMap<ManagedDeviceId, PlayerState>>, // per-device snapshot to diff against
+    text_layouts: Mutex<HashMap<ManagedDeviceId, TextLayout>>,
+    source_texts: Mutex<HashMap<ManagedDeviceId, TrackMetadata>>, // player texts before composition
+    status_maps: Mutex<HashMap<ManagedDeviceId, StatusMap>>, // configured, overriding the quirks of the device
+    requires_update: Mutex<HashSet<ManagedDeviceId>>, // devices whose snapshot may not match what they show
 }
 
 impl<T: DeviceControl + Send + Sync + 'static> DirectDeviceControlApplier<T> {
@@ -61,7 +90,83 @@ impl<T: DeviceControl + Send + Sync + 'static> DirectDeviceControlApplier<T> {
         Self {
             device_control,
             last_applied: Mutex::new(HashMap::new()),
+            text_layouts: Mutex::new(HashMap::new()),
+            source_texts: Mutex::new(HashMap::new()),
+            status_maps: Mutex::new(HashMap::new()),
+            requires_update: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Marks the snapshot of the device as unreliable, e.g. after a queued write to it failed, so the next apply
+    /// writes every field again instead of only the changed ones.
+    pub fn mark_requires_update(&self, device_id: ManagedDeviceId) {
+        self.requires_update.lock().unwrap().insert(device_id);
+    }
+
+    fn requires_update(&self, device_id: ManagedDeviceId) -> bool {
+        self.requires_update.lock().unwrap().contains(&device_id)
+    }
+
+    /// DeviceControl the applier writes to.
+    pub fn device_control(&self) -> Arc<T> {
+        self.device_control.clone()
+    }
+
+    /// Sets how the device's text lines are composed from player texts and re-applies the current texts.
+    pub async fn set_text_layout(&self, device_id: ManagedDeviceId, layout: Option<TextLayout>) -> Result<(), Error> {
+        match layout {
+            Some(layout) => self.text_layouts.lock().unwrap().insert(device_id, layout),
+            None => self.text_layouts.lock().unwrap().remove(&device_id),
+        };
+        let source = self.source_texts.lock().unwrap().get(&device_id).cloned();
+        match source {
+            Some(source) => self.apply_composed_texts(device_id, &self.compose(device_id, &source)).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Sets how statuses are translated for the device, overriding its quirks; `None` falls back to the quirks.
+    ///
+    /// Takes effect with the next status change.
+    pub fn set_status_map(&self, device_id: ManagedDeviceId, status_map: Option<StatusMap>) {
+        match status_map {
+            Some(status_map) => self.status_maps.lock().unwrap().insert(device_id, status_map),
+            None => self.status_maps.lock().unwrap().remove(&device_id),
+        };
+    }
+
+    /// Status to write to the device for the player's `status`, `None` if the write is suppressed.
+    fn device_status(&self, device_id: ManagedDeviceId, status: FsctStatus) -> Option<FsctStatus> {
+        let configured = self.status_maps.lock().unwrap().get(&device_id).cloned();
+        match configured.or_else(|| self.device_control.status_map(device_id)) {
+            Some(status_map) => status_map.map(status),
+            None => Some(status),
+        }
+    }
+
+    fn compose<'s>(&self, device_id: ManagedDeviceId, texts: &'s TrackMetadata) -> Cow<'s, TrackMetadata> {
+        match self.text_layouts.lock().unwrap().get(&device_id) {
+            Some(layout) => Cow::Owned(layout.compose(texts)),
+            None => Cow::Borrowed(texts),
+        }
+    }
+
+    /// Sends the texts differing from the last applied snapshot.
+    async fn apply_composed_texts(&self, device_id: ManagedDeviceId, texts: &TrackMetadata) -> Result<(), Error> {
+        let previous = self.last_applied.lock().unwrap().get(&device_id).map(|s| s.texts.clone()).unwrap_or_default();
+        let requires_update = self.requires_update(device_id);
+        for (text_id, text) in texts.iter() {
+            if previous.get_text(text_id) == text && !requires_update {
+                continue;
+            }
+            self.device_control
+                .set_current_text(device_id, text_id, text.as_deref())
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to set text: {}", e))?;
+            let mut guard = self.last_applied.lock().unwrap();
+            *guard.entry(device_id).or_default().texts.get_mut_text(text_id) = text.clone();
         }
+        Ok(())
     }
 }
 
@@ -76,8 +181,23 @@ impl<T: DeviceControl + Send + Sync + 'static> PlayerStateApplier for DirectDevi
             // then applying task would be only one that changes the state
             // so it would be write it the same way as here
 
-            // Take a snapshot of the previous state for this device without holding the lock across awaits.
-            let prev_state = {
+            // Compose display lines from the player texts if the device has a text layout
+            self.source_texts.lock().unwrap().insert(device_id, state.texts.clone());
+            let composed;
+            let state = match self.compose(device_id, &state.texts) {
+                Cow::Borrowed(_) => state,
+                Cow::Owned(texts) => {
+                    composed = PlayerState { texts, ..state.clone() };
+                    &composed
+                }
+            };
+
+            // Take a snapshot of the previous state for this device without holding the lock across awaits;
+            // an unreliable snapshot counts as nothing known, so every field is written.
+            let requires_update = self.requires_update.lock().unwrap().remove(&device_id);
+            let prev_state = if requires_update {
+                None
+            } else {
                 let guard = self
                     .last_applied
                     .lock()
@@ -85,8 +205,19 @@ impl<T: DeviceControl + Send + Sync + 'static> PlayerStateApplier for DirectDevi
                 guard.get(&device_id).cloned()
             };
 
+            // Translate the status for the device; a suppressed status leaves the previous one on the device
+            let device_status = self.device_status(device_id, state.status);
+            let mapped;
+            let state = match device_status.or(prev_state.as_ref().map(|p| p.status)) {
+                Some(status) if status != state.status => {
+                    mapped = PlayerState { status, ..state.clone() };
+                    &mapped
+                }
+                _ => state,
+            };
+
             // Decide what changed
-            let status_changed = prev_state
+            let status_changed = device_status.is_some() && prev_state
                 .as_ref()
                 .map(|p| p.status != state.status)
                 .unwrap_or(true);
@@ -96,13 +227,24 @@ impl<T: DeviceControl + Send + Sync + 'static> PlayerStateApplier for DirectDevi
                 .map(|p| p.timeline != state.timeline)
                 .unwrap_or(true);
 
+            let volume_changed = prev_state
+                .as_ref()
+                .map(|p| p.volume != state.volume)
+                .unwrap_or(true);
+
+            let modes_changed = prev_state
+                .as_ref()
+                .map(|p| p.modes != state.modes)
+                .unwrap_or(true);
+
             // Collect text changes (covers both set and clear)
             let mut text_changes: Vec<(crate::definitions::FsctTextMetadata, Option<&str>)> = Vec::new();
             for text_id in state.texts.iter_id() {
                 let new_val = state.texts.get_text(*text_id);
                 let changed = match prev_state.as_ref() {
                     Some(prev) => prev.texts.get_text(*text_id) != new_val,
-                    None => new_val.is_some(),
+                    // nothing known about the device: write every text, clearing leftovers of earlier sessions
+                    None => true,
                 };
                 if changed {
                     text_changes.push((*text_id, new_val.as_deref()));
@@ -124,6 +266,20 @@ impl<T: DeviceControl + Send + Sync + 'static> PlayerStateApplier for DirectDevi
                     .map_err(|e| anyhow::anyhow!("Failed to set progress: {}", e))?;
             }
 
+            if volume_changed {
+                self.device_control
+                    .set_volume(device_id, state.volume)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to set volume: {}", e))?;
+            }
+
+            if modes_changed {
+                self.device_control
+                    .set_playback_modes(device_id, state.modes)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to set playback modes: {}", e))?;
+            }
+
             for (text_id, new_val) in text_changes {
                 if let Err(e) = self
                     .device_control
@@ -151,6 +307,10 @@ impl<T: DeviceControl + Send + Sync + 'static> PlayerStateApplier for DirectDevi
     fn apply_status<'a>(&'a self, device_id: ManagedDeviceId, status: FsctStatus)
         -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
         Box::pin(async move {
+            let Some(status) = self.device_status(device_id, status) else {
+                return Ok(());
+            };
+
             // Snapshot previous status (no await while locked)
             let unchanged = {
                 let guard = self
@@ -163,7 +323,7 @@ impl<T: DeviceControl + Send + Sync + 'static> PlayerStateApplier for DirectDevi
                 player_state.status == status
             };
 
-            if unchanged {
+            if unchanged && !self.requires_update(device_id) {
                 return Ok(())
             }
 
@@ -201,7 +361,7 @@ impl<T: DeviceControl + Send + Sync + 'static> PlayerStateApplier for DirectDevi
             };
 
             // If unchanged (and we have a previous state), skip
-            if unchanged {
+            if unchanged && !self.requires_update(device_id) {
                 return Ok(());
             }
 
@@ -222,9 +382,84 @@ impl<T: DeviceControl + Send + Sync + 'static> PlayerStateApplier for DirectDevi
         })
     }
 
+    fn apply_volume<'a>(&'a self, device_id: ManagedDeviceId, volume: Option<VolumeInfo>)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let unchanged = {
+                let guard = self
+                    .last_applied
+                    .lock()
+                    .map_err(|_| anyhow::anyhow!("PlayerStateApplier lock poisoned"))?;
+                let player_state = guard
+                    .get(&device_id)
+                    .ok_or_else(|| anyhow::anyhow!("PlayerStateApplier: device not found"))?;
+                player_state.volume == volume
+            };
+            if unchanged && !self.requires_update(device_id) {
+                return Ok(());
+            }
+
+            self.device_control
+                .set_volume(device_id, volume)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to set volume: {}", e))?;
+
+            let mut guard = self
+                .last_applied
+                .lock()
+                .map_err(|_| anyhow::anyhow!("PlayerStateApplier lock poisoned"))?;
+            guard.entry(device_id).or_insert_with(PlayerState::default).volume = volume;
+            Ok(())
+        })
+    }
+
+    fn apply_modes<'a>(&'a self, device_id: ManagedDeviceId, modes: PlaybackModes)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let unchanged = {
+                let guard = self
+                    .last_applied
+                    .lock()
+                    .map_err(|_| anyhow::anyhow!("PlayerStateApplier lock poisoned"))?;
+                let player_state = guard
+                    .get(&device_id)
+                    .ok_or_else(|| anyhow::anyhow!("PlayerStateApplier: device not found"))?;
+                player_state.modes == modes
+            };
+            if unchanged && !self.requires_update(device_id) {
+                return Ok(());
+            }
+
+            self.device_control
+                .set_playback_modes(device_id, modes)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to set playback modes: {}", e))?;
+
+            let mut guard = self
+                .last_applied
+                .lock()
+                .map_err(|_| anyhow::anyhow!("PlayerStateApplier lock poisoned"))?;
+            guard.entry(device_id).or_insert_with(PlayerState::default).modes = modes;
+            Ok(())
+        })
+    }
+
     fn apply_text<'a>(&'a self, device_id: ManagedDeviceId, text_id: FsctTextMetadata, text: Option<&'a str>)
         -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
         Box::pin(async move {
+            // With a text layout a single text may affect several composed lines
+            let is_current_text = TrackMetadata::default().iter_id().any(|id| *id == text_id);
+            let source = is_current_text.then(|| {
+                let mut sources = self.source_texts.lock().unwrap();
+                let source = sources.entry(device_id).or_default();
+                *source.get_mut_text(text_id) = text.map(|s| s.to_string());
+                source.clone()
+            });
+            let layout = self.text_layouts.lock().unwrap().get(&device_id).cloned();
+            if let (Some(source), Some(layout)) = (source, layout) {
+                return self.apply_composed_texts(device_id, &layout.compose(&source)).await;
+            }
+
             // Snapshot previous text
             let unchanged: bool = {
                 let guard = self
@@ -237,7 +472,7 @@ impl<T: DeviceControl + Send + Sync + 'static> PlayerStateApplier for DirectDevi
                 player_state.texts.get_text(text_id).as_ref().map(|s|s.as_str()) == text
             };
 
-            if unchanged {
+            if unchanged && !self.requires_update(device_id) {
                 return Ok(());
             }
 
@@ -258,9 +493,41 @@ impl<T: DeviceControl + Send + Sync + 'static> PlayerStateApplier for DirectDevi
             Ok(())
         })
     }
+
+    fn apply_enable<'a>(&'a self, device_id: ManagedDeviceId, enable: bool)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
+        Box::pin(async move {
+            self.device_control
+                .set_enable(device_id, enable)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to set enable: {}", e))
+        })
+    }
+
+    fn apply_notify<'a>(&'a self, device_id: ManagedDeviceId, notification: FsctNotification)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
+        Box::pin(async move {
+            self.device_control
+                .notify(device_id, notification)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to notify: {}", e))
+        })
+    }
+
+    fn reset_device<'a>(&'a self, device_id: ManagedDeviceId)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let seen_before = self.last_applied.lock().unwrap().remove(&device_id).is_some();
+            self.requires_update.lock().unwrap().remove(&device_id);
+            self.source_texts.lock().unwrap().remove(&device_id);
+            if seen_before {
+                self.device_control
+                    .reinitialize(device_id)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to reinitialize: {}", e))?;
+            }
+            Ok(())
+        })
+    }
 }
 
-// Sketch: An alternative async queue-based applier could look like this (not used by default):
-// - It owns an mpsc::Sender<Command> and spawns a worker task that processes commands.
-// - PlayerManager would only enqueue (non-blocking) and return.
-// This allows isolating device IO and applying backpressure. Left out for minimal code changes.
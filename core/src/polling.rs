@@ -0,0 +1,275 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Polling interval configuration of ports that have to poll their backend.
+//!
+//! Every polling port registers its settings under its name in a [`PollingRegistry`], from where they can be read
+//! and adjusted at runtime; ports watch their [`PollingSettings`] and pick up changes with the next poll.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::watch;
+
+use crate::serde_format::duration_secs;
+
+/// Shortest accepted polling interval; polling faster mostly burns CPU on the host and the backend.
+pub const MIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PollingConfigError {
+    #[error("Polling interval {0:?} is shorter than the minimum of {MIN_POLL_INTERVAL:?}")]
+    IntervalTooShort(Duration),
+    #[error("Polling jitter {jitter:?} exceeds the interval {interval:?}")]
+    JitterTooLarge { interval: Duration, jitter: Duration },
+    #[error("Unknown polling port {0}")]
+    UnknownPort(String),
+}
+
+/// Interval between polls plus a random jitter of up to `jitter`, which keeps several pollers from running in
+/// lockstep. In JSON both are fractional seconds; `jitter` defaults to zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PollingConfig {
+    #[serde(with = "duration_secs")]
+    pub interval: Duration,
+    #[serde(with = "duration_secs", default)]
+    pub jitter: Duration,
+}
+
+impl PollingConfig {
+    pub const fn new(interval: Duration, jitter: Duration) -> Self {
+        Self { interval, jitter }
+    }
+
+    pub fn validate(&self) -> Result<(), PollingConfigError> {
+        if self.interval < MIN_POLL_INTERVAL {
+            return Err(PollingConfigError::IntervalTooShort(self.interval));
+        }
+        if self.jitter > self.interval {
+            return Err(PollingConfigError::JitterTooLarge { interval: self.interval, jitter: self.jitter });
+        }
+        Ok(())
+    }
+
+    /// Delay until the next poll: the interval plus a random part of the jitter.
+    pub fn next_delay(&self) -> Duration {
+        if self.jitter.is_zero() {
+            return self.interval;
+        }
+        let random = RandomState::new().build_hasher().finish();
+        self.interval + self.jitter.mul_f64(random as f64 / u64::MAX as f64)
+    }
+
+    /// The configuration with interval and jitter stretched by `factor`.
+    pub fn slowed_down(&self, factor: f64) -> Self {
+        Self { interval: self.interval.mul_f64(factor), jitter: self.jitter.mul_f64(factor) }
+    }
+}
+
+/// Runtime adjustable polling configuration of a single port.
+///
+/// Pollers follow the effective configuration, which is the configured one slowed down by the current slowdown
+/// factor, e.g. while the host runs on battery (see [`crate::power`]).
+#[derive(Clone)]
+pub struct PollingSettings {
+    tx: Arc<watch::Sender<PollingConfig>>,
+    configured: Arc<Mutex<(PollingConfig, f64)>>,
+}
+
+impl PollingSettings {
+    pub fn new(config: PollingConfig) -> Self {
+        Self { tx: Arc::new(watch::Sender::new(config)), configured: Arc::new(Mutex::new((config, 1.0))) }
+    }
+
+    /// Effective configuration.
+    pub fn get(&self) -> PollingConfig {
+        *self.tx.borrow()
+    }
+
+    /// Configuration as set, without the slowdown.
+    pub fn configured(&self) -> PollingConfig {
+        self.configured.lock().unwrap().0
+    }
+
+    /// Replaces the configuration; pollers watching the settings pick it up with their next poll.
+    pub fn set(&self, config: PollingConfig) -> Result<(), PollingConfigError> {
+        config.validate()?;
+        let mut configured = self.configured.lock().unwrap();
+        configured.0 = config;
+        self.tx.send_replace(config.slowed_down(configured.1));
+        Ok(())
+    }
+
+    /// Stretches the configured polling by `factor`; 1.0 polls as configured.
+    pub fn set_slowdown(&self, factor: f64) {
+        let mut configured = self.configured.lock().unwrap();
+        configured.1 = factor.max(1.0);
+        self.tx.send_if_modified(|effective| {
+            let slowed_down = configured.0.slowed_down(configured.1);
+            std::mem::replace(effective, slowed_down) != slowed_down
+        });
+    }
+
+    /// Watches the effective configuration.
+    pub fn subscribe(&self) -> watch::Receiver<PollingConfig> {
+        self.tx.subscribe()
+    }
+}
+
+/// Polling settings of all polling ports, by port name.
+///
+/// The registry reads and sets configured polling; the slowdown applies to all ports alike.
+pub struct PollingRegistry {
+    ports: Mutex<HashMap<String, PollingSettings>>,
+    /// Polling the ports registered with, restored by [`PollingRegistry::reset`].
+    defaults: Mutex<HashMap<String, PollingConfig>>,
+    slowdown: Mutex<f64>,
+}
+
+impl Default for PollingRegistry {
+    fn default() -> Self {
+        Self { ports: Mutex::new(HashMap::new()), defaults: Mutex::new(HashMap::new()), slowdown: Mutex::new(1.0) }
+    }
+}
+
+impl PollingRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the settings of the port, registering them with `default` first if the port is new.
+    pub fn register(&self, port: &str, default: PollingConfig) -> PollingSettings {
+        self.defaults.lock().unwrap().entry(port.to_string()).or_insert(default);
+        self.settings(port, default)
+    }
+
+    fn settings(&self, port: &str, initial: PollingConfig) -> PollingSettings {
+        let slowdown = *self.slowdown.lock().unwrap();
+        self.ports.lock().unwrap().entry(port.to_string()).or_insert_with(|| {
+            let settings = PollingSettings::new(initial);
+            settings.set_slowdown(slowdown);
+            settings
+        }).clone()
+    }
+
+    /// Sets the polling of the port, e.g. from a config file, also before the port registers; the port then starts
+    /// with it instead of its default.
+    pub fn configure(&self, port: &str, config: PollingConfig) -> Result<(), PollingConfigError> {
+        config.validate()?;
+        self.settings(port, config).set(config)
+    }
+
+    /// Returns the port to the polling it registered with; a port that hasn't registered yet will start with its
+    /// default.
+    pub fn reset(&self, port: &str) {
+        match self.defaults.lock().unwrap().get(port) {
+            Some(default) => {
+                if let Some(settings) = self.ports.lock().unwrap().get(port) {
+                    let _ = settings.set(*default);
+                }
+            }
+            None => {
+                self.ports.lock().unwrap().remove(port);
+            }
+        }
+    }
+
+    pub fn get(&self, port: &str) -> Option<PollingConfig> {
+        self.ports.lock().unwrap().get(port).map(PollingSettings::configured)
+    }
+
+    pub fn set(&self, port: &str, config: PollingConfig) -> Result<(), PollingConfigError> {
+        let ports = self.ports.lock().unwrap();
+        let settings = ports.get(port).ok_or_else(|| PollingConfigError::UnknownPort(port.to_string()))?;
+        settings.set(config)
+    }
+
+    /// Slows the polling of all ports, present and future, down by `factor`.
+    pub fn set_slowdown(&self, factor: f64) {
+        *self.slowdown.lock().unwrap() = factor;
+        for settings in self.ports.lock().unwrap().values() {
+            settings.set_slowdown(factor);
+        }
+    }
+
+    pub fn slowdown(&self) -> f64 {
+        *self.slowdown.lock().unwrap()
+    }
+
+    /// Configuration of every registered port, sorted by port name.
+    pub fn list(&self) -> Vec<(String, PollingConfig)> {
+        let mut ports: Vec<_> =
+            self.ports.lock().unwrap().iter().map(|(port, settings)| (port.clone(), settings.configured())).collect();
+        ports.sort_by(|a, b| a.0.cmp(&b.0));
+        ports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_validates_and_publishes_changes() {
+        let registry = PollingRegistry::new();
+        let default = PollingConfig::new(Duration::from_millis(500), Duration::from_millis(100));
+        let settings = registry.register("jxa", default);
+        let mut rx = settings.subscribe();
+
+        let faster = PollingConfig::new(Duration::from_millis(250), Duration::ZERO);
+        registry.set("jxa", faster).unwrap();
+        assert!(rx.has_changed().unwrap());
+        assert_eq!(*rx.borrow_and_update(), faster);
+        assert_eq!(registry.register("jxa", default).get(), faster);
+
+        let too_fast = PollingConfig::new(Duration::from_millis(10), Duration::ZERO);
+        assert_eq!(registry.set("jxa", too_fast), Err(PollingConfigError::IntervalTooShort(too_fast.interval)));
+        assert!(matches!(registry.set("volumio", faster), Err(PollingConfigError::UnknownPort(_))));
+
+        let delay = default.next_delay();
+        assert!(delay >= default.interval && delay <= default.interval + default.jitter);
+
+        registry.set_slowdown(3.0);
+        assert_eq!(*rx.borrow_and_update(), faster.slowed_down(3.0));
+        assert_eq!(registry.get("jxa"), Some(faster));
+        assert_eq!(registry.register("volumio", default).get(), default.slowed_down(3.0));
+    }
+
+    #[test]
+    fn configured_polling_is_reset_to_the_default_of_the_port() {
+        let registry = PollingRegistry::new();
+        let default = PollingConfig::new(Duration::from_millis(500), Duration::ZERO);
+        let configured = PollingConfig::new(Duration::from_secs(2), Duration::ZERO);
+
+        registry.configure("jxa", configured).unwrap();
+        let settings = registry.register("jxa", default);
+        assert_eq!(settings.get(), configured);
+        registry.reset("jxa");
+        assert_eq!(settings.get(), default);
+
+        registry.configure("mpd", configured).unwrap();
+        registry.reset("mpd");
+        assert_eq!(registry.register("mpd", default).get(), default);
+        assert!(registry.configure("mpd", PollingConfig::new(Duration::ZERO, Duration::ZERO)).is_err());
+        assert_eq!(registry.get("mpd"), Some(default));
+    }
+}
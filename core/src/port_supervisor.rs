@@ -0,0 +1,179 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Supervision of the player ports running in the host service, e.g. the OS watchers of the native service.
+//!
+//! Ports run through [`PortSupervisor::run_port`] with the function starting them, under a name by which they can be
+//! restarted without stopping the rest of the service, e.g. a watcher left without updates after the OS media
+//! service it follows was restarted under it.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Error;
+use log::{info, warn};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::service::{spawn_service, ServiceHandle};
+
+#[derive(thiserror::Error, Debug)]
+pub enum PortError {
+    #[error("Unknown port {0}")]
+    UnknownPort(String),
+    #[error("Port {0} is already running")]
+    AlreadyRunning(String),
+    #[error("Failed to start port {port}: {source}")]
+    StartFailed { port: String, source: Error },
+}
+
+/// Restart request, answered once the port has been started anew.
+type RestartRequest = oneshot::Sender<Result<(), PortError>>;
+
+/// Ports of the service by name, with the channels through which their supervising services take restarts.
+#[derive(Default)]
+pub struct PortSupervisor {
+    ports: Arc<Mutex<HashMap<String, mpsc::Sender<RestartRequest>>>>,
+}
+
+impl PortSupervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts the port with `start` and supervises it under `name` until the returned service is stopped, which
+    /// stops the port too. Fails if the port doesn't start; a later failing restart leaves it stopped, but
+    /// registered for another restart.
+    pub async fn run_port<F, Fut>(&self, name: impl Into<String>, start: F) -> Result<ServiceHandle, PortError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<ServiceHandle, Error>> + Send + 'static,
+    {
+        let name = name.into();
+        let (restart_tx, mut restarts) = mpsc::channel::<RestartRequest>(4);
+        {
+            let mut ports = self.ports.lock().unwrap();
+            if ports.contains_key(&name) {
+                return Err(PortError::AlreadyRunning(name));
+            }
+            ports.insert(name.clone(), restart_tx);
+        }
+        let first = match start().await {
+            Ok(handle) => handle,
+            Err(source) => {
+                self.ports.lock().unwrap().remove(&name);
+                return Err(PortError::StartFailed { port: name, source });
+            }
+        };
+
+        let ports = self.ports.clone();
+        Ok(spawn_service(move |mut stop| async move {
+            let mut current = Some(first);
+            loop {
+                let reply = tokio::select! {
+                    _ = stop.signaled() => break,
+                    Some(reply) = restarts.recv() => reply,
+                };
+                info!("Restarting port {}", name);
+                if let Some(handle) = current.take()
+                    && let Err(e) = handle.shutdown().await
+                {
+                    warn!("Port {} failed while stopping: {}", name, e);
+                }
+                let result = match start().await {
+                    Ok(handle) => {
+                        current = Some(handle);
+                        Ok(())
+                    }
+                    Err(source) => {
+                        warn!("Failed to restart port {}: {}", name, source);
+                        Err(PortError::StartFailed { port: name.clone(), source })
+                    }
+                };
+                let _ = reply.send(result);
+            }
+            ports.lock().unwrap().remove(&name);
+            if let Some(handle) = current
+                && let Err(e) = handle.shutdown().await
+            {
+                warn!("Port {} failed while stopping: {}", name, e);
+            }
+        }))
+    }
+
+    /// Stops the port and starts it anew, returning once it runs again.
+    pub async fn restart_port(&self, name: &str) -> Result<(), PortError> {
+        let unknown = || PortError::UnknownPort(name.to_string());
+        let restart_tx = self.ports.lock().unwrap().get(name).cloned().ok_or_else(unknown)?;
+        let (reply_tx, reply) = oneshot::channel();
+        restart_tx.send(reply_tx).await.map_err(|_| unknown())?;
+        reply.await.map_err(|_| unknown())?
+    }
+
+    /// Names of the supervised ports, sorted.
+    pub fn list(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.ports.lock().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Port counting its starts and stops, failing to start once `fail_from` starts happened.
+    fn counting_port(starts: Arc<AtomicUsize>, stops: Arc<AtomicUsize>, fail_from: usize)
+                     -> impl Fn() -> futures::future::BoxFuture<'static, Result<ServiceHandle, Error>> + Send + Sync {
+        move || {
+            let (starts, stops) = (starts.clone(), stops.clone());
+            Box::pin(async move {
+                if starts.fetch_add(1, Ordering::SeqCst) >= fail_from {
+                    return Err(anyhow!("watcher unavailable"));
+                }
+                Ok(spawn_service(move |mut stop| async move {
+                    stop.signaled().await;
+                    stops.fetch_add(1, Ordering::SeqCst);
+                }))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn ports_are_restarted_by_name() {
+        let supervisor = PortSupervisor::new();
+        let (starts, stops) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        let handle = supervisor.run_port("gsmtc", counting_port(starts.clone(), stops.clone(), 2)).await.unwrap();
+        assert_eq!(supervisor.list(), vec!["gsmtc".to_string()]);
+        assert!(matches!(supervisor.run_port("gsmtc", counting_port(starts.clone(), stops.clone(), 2)).await,
+                         Err(PortError::AlreadyRunning(_))));
+
+        supervisor.restart_port("gsmtc").await.unwrap();
+        assert_eq!((starts.load(Ordering::SeqCst), stops.load(Ordering::SeqCst)), (2, 1));
+        assert!(matches!(supervisor.restart_port("mpris").await, Err(PortError::UnknownPort(_))));
+
+        // a failed restart leaves the port stopped but still restartable
+        assert!(matches!(supervisor.restart_port("gsmtc").await, Err(PortError::StartFailed { .. })));
+        assert_eq!(stops.load(Ordering::SeqCst), 2);
+        assert_eq!(supervisor.list(), vec!["gsmtc".to_string()]);
+
+        handle.shutdown().await.unwrap();
+        assert!(supervisor.list().is_empty());
+    }
+}
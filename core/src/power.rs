@@ -0,0 +1,335 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Energy-aware throttling while the host runs on battery.
+//!
+//! The [`PowerMonitor`] periodically reads the [`PowerSource`] of the host and switches to
+//! [`PowerMode::Saving`] on battery (optionally only below a charge threshold). In saving mode polling ports are
+//! slowed down through the [`PollingRegistry`] and, as a [`DriverInterceptor`], timeline-only updates which merely
+//! correct drift are forwarded at most once per [`EnergyConfig::timeline_interval`]; seeks and rate changes still
+//! reach devices right away.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Error;
+use async_trait::async_trait;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+use crate::definitions::TimelineInfo;
+use crate::driver_middleware::{DriverInterceptor, StateUpdate};
+use crate::player_manager::ManagedPlayerId;
+use crate::polling::PollingRegistry;
+use crate::serde_format::duration_secs;
+use crate::service::{spawn_service, ServiceHandle};
+use crate::timeline_smoothing::{classify_timeline, TimelineChange, DEFAULT_JUMP_THRESHOLD};
+
+/// Power supply state of the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct PowerStatus {
+    pub on_battery: bool,
+    /// Remaining battery charge in percent, if known.
+    pub battery_percent: Option<u8>,
+}
+
+/// Reads the power supply state from the OS.
+pub trait PowerSource: Send + Sync {
+    fn status(&self) -> Result<PowerStatus, Error>;
+}
+
+/// Power source of Linux hosts, read from `/sys/class/power_supply`.
+///
+/// The host is on battery while any battery is discharging; hosts without batteries are always on mains.
+pub struct SysfsPowerSource {
+    root: PathBuf,
+}
+
+impl SysfsPowerSource {
+    pub fn new() -> Self {
+        Self::with_root("/sys/class/power_supply")
+    }
+
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Default for SysfsPowerSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PowerSource for SysfsPowerSource {
+    fn status(&self) -> Result<PowerStatus, Error> {
+        let read = |supply: &PathBuf, attribute: &str| {
+            fs::read_to_string(supply.join(attribute)).map(|value| value.trim().to_string()).unwrap_or_default()
+        };
+        let mut status = PowerStatus::default();
+        for entry in fs::read_dir(&self.root)? {
+            let supply = entry?.path();
+            if read(&supply, "type") != "Battery" {
+                continue;
+            }
+            if read(&supply, "status") == "Discharging" {
+                status.on_battery = true;
+            }
+            if let Ok(percent) = read(&supply, "capacity").parse::<u8>() {
+                status.battery_percent = Some(status.battery_percent.map_or(percent, |p| p.min(percent)));
+            }
+        }
+        Ok(status)
+    }
+}
+
+/// Whether updates are throttled to save energy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerMode {
+    #[default]
+    Normal,
+    Saving,
+}
+
+/// When and how much to throttle, the `energy` table of the config file. `timeline_interval` is fractional seconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnergyConfig {
+    /// Throttle while the host is on battery.
+    pub throttle_on_battery: bool,
+    /// Throttle only once the battery charge drops to this percentage; `None` throttles on battery regardless.
+    pub battery_threshold: Option<u8>,
+    /// Factor the polling intervals of polling ports are stretched by.
+    pub polling_slowdown: f64,
+    /// Shortest time between forwarded timeline-only drift corrections of a player.
+    #[serde(with = "duration_secs")]
+    pub timeline_interval: Duration,
+}
+
+impl Default for EnergyConfig {
+    fn default() -> Self {
+        Self {
+            throttle_on_battery: true,
+            battery_threshold: None,
+            polling_slowdown: 3.0,
+            timeline_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+impl EnergyConfig {
+    pub fn mode_for(&self, status: &PowerStatus) -> PowerMode {
+        let below_threshold = match (self.battery_threshold, status.battery_percent) {
+            (Some(threshold), Some(percent)) => percent <= threshold,
+            _ => true,
+        };
+        if self.throttle_on_battery && status.on_battery && below_threshold {
+            PowerMode::Saving
+        } else {
+            PowerMode::Normal
+        }
+    }
+}
+
+/// How often [`run_power_monitor`] reads the power source by default.
+pub const DEFAULT_POWER_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Follows the power source of the host and throttles updates in [`PowerMode::Saving`].
+pub struct PowerMonitor {
+    source: Box<dyn PowerSource>,
+    config: Mutex<EnergyConfig>,
+    status: Mutex<PowerStatus>,
+    mode: watch::Sender<PowerMode>,
+    polling: Option<Arc<PollingRegistry>>,
+    /// Last forwarded timeline of each player and when it was forwarded.
+    timelines: Mutex<HashMap<ManagedPlayerId, (Option<TimelineInfo>, Instant)>>,
+}
+
+impl PowerMonitor {
+    pub fn new(source: Box<dyn PowerSource>, config: EnergyConfig) -> Self {
+        Self {
+            source,
+            config: Mutex::new(config),
+            status: Mutex::new(PowerStatus::default()),
+            mode: watch::Sender::new(PowerMode::Normal),
+            polling: None,
+            timelines: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Slows the polling ports of `registry` down in saving mode.
+    pub fn with_polling(mut self, registry: Arc<PollingRegistry>) -> Self {
+        self.polling = Some(registry);
+        self
+    }
+
+    pub fn mode(&self) -> PowerMode {
+        *self.mode.borrow()
+    }
+
+    /// Watches the power mode, e.g. for exporting it.
+    pub fn subscribe(&self) -> watch::Receiver<PowerMode> {
+        self.mode.subscribe()
+    }
+
+    /// Power supply state as last read.
+    pub fn status(&self) -> PowerStatus {
+        *self.status.lock().unwrap()
+    }
+
+    pub fn config(&self) -> EnergyConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    pub fn set_config(&self, config: EnergyConfig) {
+        *self.config.lock().unwrap() = config;
+        self.apply(self.status());
+    }
+
+    /// Reads the power source and switches the power mode accordingly.
+    pub fn refresh(&self) -> Result<PowerMode, Error> {
+        let status = self.source.status()?;
+        *self.status.lock().unwrap() = status;
+        Ok(self.apply(status))
+    }
+
+    fn apply(&self, status: PowerStatus) -> PowerMode {
+        let config = self.config();
+        let mode = config.mode_for(&status);
+        if self.mode.send_replace(mode) != mode {
+            info!("Switching to {:?} power mode ({:?})", mode, status);
+        }
+        if let Some(polling) = &self.polling {
+            let slowdown = if mode == PowerMode::Saving { config.polling_slowdown } else { 1.0 };
+            if polling.slowdown() != slowdown {
+                polling.set_slowdown(slowdown);
+            }
+        }
+        mode
+    }
+
+    /// Whether a timeline-only update of the player is forwarded; forwarded timelines become the reference.
+    fn forward_timeline(&self, player_id: ManagedPlayerId, timeline: &Option<TimelineInfo>) -> bool {
+        let interval = self.config().timeline_interval;
+        let saving = self.mode() == PowerMode::Saving;
+        let now = Instant::now();
+        let mut timelines = self.timelines.lock().unwrap();
+        if saving && let Some((previous, forwarded_at)) = timelines.get(&player_id)
+            && now - *forwarded_at < interval
+            && classify_timeline(previous.as_ref(), timeline.as_ref(), DEFAULT_JUMP_THRESHOLD) == TimelineChange::Drift {
+            return false;
+        }
+        timelines.insert(player_id, (timeline.clone(), now));
+        true
+    }
+}
+
+#[async_trait]
+impl DriverInterceptor for PowerMonitor {
+    async fn intercept(&self, player_id: ManagedPlayerId, update: StateUpdate) -> Option<StateUpdate> {
+        match update {
+            StateUpdate::Timeline(timeline) => {
+                self.forward_timeline(player_id, &timeline).then_some(StateUpdate::Timeline(timeline))
+            }
+            StateUpdate::State(state) => {
+                self.timelines.lock().unwrap().insert(player_id, (state.timeline.clone(), Instant::now()));
+                Some(StateUpdate::State(state))
+            }
+            other => Some(other),
+        }
+    }
+
+    async fn on_player_unregistered(&self, player_id: ManagedPlayerId) {
+        self.timelines.lock().unwrap().remove(&player_id);
+    }
+}
+
+/// Reads the power source every `interval` and switches the power mode of the monitor.
+pub fn run_power_monitor(monitor: Arc<PowerMonitor>, interval: Duration) -> ServiceHandle {
+    spawn_service(move |mut stop_handle| async move {
+        let mut failing = false;
+        loop {
+            match monitor.refresh() {
+                Ok(_) => failing = false,
+                Err(e) if !failing => {
+                    warn!("Failed to read the power source: {}", e);
+                    failing = true;
+                }
+                Err(_) => {}
+            }
+            tokio::select! {
+                _ = stop_handle.signaled() => break,
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polling::PollingConfig;
+    use std::time::SystemTime;
+
+    struct FixedSource(Mutex<PowerStatus>);
+
+    impl PowerSource for Arc<FixedSource> {
+        fn status(&self) -> Result<PowerStatus, Error> {
+            Ok(*self.0.lock().unwrap())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn battery_power_slows_polling_and_timeline_corrections() {
+        let source = Arc::new(FixedSource(Mutex::new(PowerStatus { on_battery: true, battery_percent: Some(80) })));
+        let polling = Arc::new(PollingRegistry::new());
+        let config = PollingConfig::new(Duration::from_secs(1), Duration::ZERO);
+        let settings = polling.register("jxa", config);
+        let config_with_threshold = EnergyConfig { battery_threshold: Some(50), ..Default::default() };
+        let monitor = PowerMonitor::new(Box::new(source.clone()), config_with_threshold).with_polling(polling);
+
+        assert_eq!(monitor.refresh().unwrap(), PowerMode::Normal);
+        *source.0.lock().unwrap() = PowerStatus { on_battery: true, battery_percent: Some(40) };
+        assert_eq!(monitor.refresh().unwrap(), PowerMode::Saving);
+        assert_eq!(settings.get(), config.slowed_down(3.0));
+
+        let start = SystemTime::now();
+        let timeline = |position_secs: u64, update_secs: u64| StateUpdate::Timeline(Some(TimelineInfo {
+            position: Duration::from_secs(position_secs),
+            update_time: start + Duration::from_secs(update_secs),
+            duration: Some(Duration::from_secs(300)),
+            rate: 1.0,
+        }));
+        let player_id = ManagedPlayerId::new(1).unwrap();
+        assert!(monitor.intercept(player_id, timeline(0, 0)).await.is_some());
+        assert!(monitor.intercept(player_id, timeline(2, 2)).await.is_none(), "drift correction is throttled");
+        assert!(monitor.intercept(player_id, timeline(120, 3)).await.is_some(), "seek passes");
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert!(monitor.intercept(player_id, timeline(130, 13)).await.is_some(), "interval passed");
+
+        *source.0.lock().unwrap() = PowerStatus { on_battery: false, battery_percent: Some(41) };
+        assert_eq!(monitor.refresh().unwrap(), PowerMode::Normal);
+        assert_eq!(settings.get(), config);
+        assert!(monitor.intercept(player_id, timeline(131, 14)).await.is_some());
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Quirks database of misbehaving device models.
+//!
+//! Entries are matched by USB vendor id and optionally product id and firmware version range. All matching entries
+//! apply, more specific ones overriding the quirks set by less specific ones. The host ships a built-in table
+//! ([`QuirkTable::builtin`]) which users can extend and override ([`QuirkTable::with_overrides`]).
+//!
+//! The built-in table is empty for now: no shipped device model is known to need quirks. Entries are added to
+//! `builtin_quirks.json` as misbehaving models and firmwares are reported; until then, quirks come from the config
+//! file of the services.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::definitions::{FsctStatus, FsctTextEncoding};
+use crate::serde_format::optional_duration_secs;
+use crate::warmup::{WarmupStep, DEFAULT_WARMUP_SEQUENCE};
+
+/// Deviations of a device model from the standard behavior; unset quirks keep the standard behavior.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceQuirks {
+    /// Warm-up sequence replacing [`DEFAULT_WARMUP_SEQUENCE`]; steps not listed are skipped.
+    pub warmup: Option<Vec<WarmupStep>>,
+    /// Text encoding to use instead of the one announced in the descriptors.
+    pub text_encoding: Option<FsctTextEncoding>,
+    /// Don't read the device clock; it is assumed to run in sync with the host's.
+    pub skip_time_sync: Option<bool>,
+    /// Maximum length of texts in bytes, lowering the lengths announced in the descriptors.
+    pub max_text_length: Option<usize>,
+    /// Minimum time between consecutive control transfers to the device.
+    #[serde(with = "optional_duration_secs")]
+    pub write_delay: Option<Duration>,
+    /// Translation of statuses the device doesn't understand.
+    pub status_map: Option<StatusMap>,
+    /// Time the device takes to show what it is sent; progress positions are sent that far ahead, so the shown
+    /// position is in step with the audio.
+    #[serde(with = "optional_duration_secs")]
+    pub display_lag: Option<Duration>,
+    /// Measure the write latency of the device with `poll` requests and send progress positions that far ahead as
+    /// well, for devices whose clock is not synchronized.
+    pub measure_latency: Option<bool>,
+    /// Write the shown status and texts again this often, for devices losing them on internal resets without
+    /// telling the host; at least [`MIN_TEXT_REFRESH_INTERVAL`], sparing displays backed by EEPROM.
+    #[serde(with = "optional_duration_secs")]
+    pub text_refresh: Option<Duration>,
+}
+
+/// Shortest interval of [`DeviceQuirks::text_refresh`]; shorter ones are raised to it.
+pub const MIN_TEXT_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+impl DeviceQuirks {
+    pub fn warmup_sequence(&self) -> Vec<WarmupStep> {
+        self.warmup.clone().unwrap_or_else(|| DEFAULT_WARMUP_SEQUENCE.to_vec())
+    }
+
+    pub fn skips_time_sync(&self) -> bool {
+        self.skip_time_sync.unwrap_or(false)
+    }
+
+    pub fn measures_latency(&self) -> bool {
+        self.measure_latency.unwrap_or(false)
+    }
+
+    /// Interval of the text refresh, bounded by [`MIN_TEXT_REFRESH_INTERVAL`]; `None` if texts are not refreshed.
+    pub fn text_refresh_interval(&self) -> Option<Duration> {
+        self.text_refresh.map(|interval| interval.max(MIN_TEXT_REFRESH_INTERVAL))
+    }
+
+    /// Takes over the quirks set in `other`, replacing those set already.
+    fn merge(&mut self, other: &DeviceQuirks) {
+        let other = other.clone();
+        self.warmup = other.warmup.or(self.warmup.take());
+        self.text_encoding = other.text_encoding.or(self.text_encoding);
+        self.skip_time_sync = other.skip_time_sync.or(self.skip_time_sync);
+        self.max_text_length = other.max_text_length.or(self.max_text_length);
+        self.write_delay = other.write_delay.or(self.write_delay);
+        self.status_map = other.status_map.or(self.status_map.take());
+        self.display_lag = other.display_lag.or(self.display_lag);
+        self.measure_latency = other.measure_latency.or(self.measure_latency);
+        self.text_refresh = other.text_refresh.or(self.text_refresh);
+    }
+}
+
+/// Translation of statuses into ones a device understands, e.g. for devices knowing only playing and paused.
+///
+/// A status mapped to `None` is not written, the device keeps showing the previous one; statuses not listed are
+/// written as they are. In JSON an object keyed by status, e.g. `{ "seeking": "playing", "unknown": null }`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct StatusMap(HashMap<FsctStatus, Option<FsctStatus>>);
+
+impl StatusMap {
+    pub fn new(mapping: impl IntoIterator<Item = (FsctStatus, Option<FsctStatus>)>) -> Self {
+        Self(mapping.into_iter().collect())
+    }
+
+    /// Status to write to the device instead of `status`, `None` if the write is suppressed.
+    pub fn map(&self, status: FsctStatus) -> Option<FsctStatus> {
+        self.0.get(&status).copied().unwrap_or(Some(status))
+    }
+}
+
+/// Quirks of a device model.
+///
+/// Without `product_id` the entry applies to all products of the vendor; the firmware range (BCD `bcdDevice`
+/// values, e.g. 258 for 1.02) is inclusive and open where a bound is missing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuirkEntry {
+    pub vendor_id: u16,
+    #[serde(default)]
+    pub product_id: Option<u16>,
+    #[serde(default)]
+    pub min_firmware_version: Option<u16>,
+    #[serde(default)]
+    pub max_firmware_version: Option<u16>,
+    #[serde(flatten)]
+    pub quirks: DeviceQuirks,
+}
+
+impl QuirkEntry {
+    fn matches(&self, vendor_id: u16, product_id: u16, firmware_version: u16) -> bool {
+        self.vendor_id == vendor_id
+            && self.product_id.is_none_or(|id| id == product_id)
+            && self.min_firmware_version.is_none_or(|min| firmware_version >= min)
+            && self.max_firmware_version.is_none_or(|max| firmware_version <= max)
+    }
+
+    fn specificity(&self) -> (bool, bool) {
+        (self.product_id.is_some(), self.min_firmware_version.is_some() || self.max_firmware_version.is_some())
+    }
+}
+
+/// Quirks of known device models, serialized as a list of entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct QuirkTable {
+    entries: Vec<QuirkEntry>,
+}
+
+impl QuirkTable {
+    pub fn new(entries: Vec<QuirkEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Quirks of device models known to misbehave, shipped with the host; none yet.
+    #[cfg(feature = "usb")]
+    pub fn builtin() -> Self {
+        serde_json::from_str(include_str!("builtin_quirks.json")).expect("built-in quirk table should be valid")
+    }
+
+    /// Adds `overrides` after the entries of this table; they win over entries of the same specificity.
+    pub fn with_overrides(mut self, overrides: QuirkTable) -> Self {
+        self.entries.extend(overrides.entries);
+        self
+    }
+
+    pub fn entries(&self) -> &[QuirkEntry] {
+        &self.entries
+    }
+
+    /// Quirks of the device: all matching entries merged, from the least to the most specific, later entries
+    /// overriding earlier ones of the same specificity.
+    pub fn lookup(&self, vendor_id: u16, product_id: u16, firmware_version: u16) -> DeviceQuirks {
+        let mut matching: Vec<_> =
+            self.entries.iter().filter(|e| e.matches(vendor_id, product_id, firmware_version)).collect();
+        matching.sort_by_key(|e| e.specificity());
+        let mut quirks = DeviceQuirks::default();
+        for entry in matching {
+            quirks.merge(&entry.quirks);
+        }
+        quirks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specific_entries_and_overrides_take_precedence() {
+        let table: QuirkTable = serde_json::from_str(r#"[
+            { "vendor_id": 4660, "product_id": 1, "max_firmware_version": 256, "skip_time_sync": true },
+            { "vendor_id": 4660, "product_id": 1, "warmup": ["time_sync", "enable"], "max_text_length": 32 },
+            { "vendor_id": 4660, "text_encoding": "ucs2", "write_delay": 0.02,
+              "status_map": { "seeking": "playing", "unknown": null } }
+        ]"#).unwrap();
+        let overrides: QuirkTable = serde_json::from_str(r#"[
+            { "vendor_id": 4660, "product_id": 1, "max_text_length": 64, "display_lag": 0.12,
+              "measure_latency": true, "text_refresh": 5.0 }
+        ]"#).unwrap();
+        let table = table.with_overrides(overrides);
+
+        let old_firmware = table.lookup(0x1234, 1, 0x0100);
+        assert_eq!(old_firmware.warmup_sequence(), vec![WarmupStep::TimeSync, WarmupStep::Enable]);
+        assert_eq!(old_firmware.text_encoding, Some(FsctTextEncoding::Ucs2));
+        assert_eq!(old_firmware.write_delay, Some(Duration::from_millis(20)));
+        assert_eq!(old_firmware.max_text_length, Some(64));
+        let status_map = old_firmware.status_map.clone().unwrap();
+        assert_eq!(status_map.map(FsctStatus::Seeking), Some(FsctStatus::Playing));
+        assert_eq!(status_map.map(FsctStatus::Unknown), None);
+        assert_eq!(status_map.map(FsctStatus::Paused), Some(FsctStatus::Paused));
+        assert!(old_firmware.skips_time_sync());
+        assert_eq!(old_firmware.display_lag, Some(Duration::from_millis(120)));
+        assert!(old_firmware.measures_latency());
+        assert_eq!(old_firmware.text_refresh_interval(), Some(MIN_TEXT_REFRESH_INTERVAL));
+        assert!(!table.lookup(0x1234, 1, 0x0101).skips_time_sync());
+        assert_eq!(table.lookup(0x1234, 2, 0x0100).warmup_sequence(), DEFAULT_WARMUP_SEQUENCE.to_vec());
+        assert_eq!(table.lookup(0x5678, 1, 0x0100), DeviceQuirks::default());
+    }
+
+    #[cfg(feature = "usb")]
+    #[test]
+    fn builtin_table_is_valid() {
+        QuirkTable::builtin();
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// Requests a client of the APIs of a service may send at once.
+pub const API_BURST: u32 = 100;
+/// Requests per second a client of the APIs of a service may send in the long run.
+pub const API_REQUESTS_PER_SECOND: f64 = 50.0;
+
+/// Per-client token bucket rate limiter for externally reachable endpoints.
+///
+/// Every client may burst up to `burst` requests, refilled at `per_second` requests per second. Clients idle for
+/// long enough to have a full bucket again are forgotten.
+pub struct RateLimiter<K> {
+    burst: f64,
+    per_second: f64,
+    // After that long without requests, a bucket is full again, the same as a new one
+    refill_time: Duration,
+    buckets: Mutex<Buckets<K>>,
+}
+
+struct Buckets<K> {
+    by_client: HashMap<K, Bucket>,
+    last_eviction: Instant,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl<K: Hash + Eq + Clone> RateLimiter<K> {
+    pub fn new(burst: u32, per_second: f64) -> Self {
+        let burst = burst as f64;
+        let refill_time = Duration::try_from_secs_f64(burst / per_second).unwrap_or(Duration::MAX);
+        let buckets = Buckets { by_client: HashMap::new(), last_eviction: Instant::now() };
+        Self { burst, per_second, refill_time, buckets: Mutex::new(buckets) }
+    }
+
+    /// Takes one token for the client; returns how long to wait before retrying if there is none left.
+    pub fn check(&self, client: &K) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if now.duration_since(buckets.last_eviction) >= self.refill_time {
+            buckets.by_client.retain(|_, bucket| now.duration_since(bucket.last_refill) < self.refill_time);
+            buckets.last_eviction = now;
+        }
+        let bucket = buckets.by_client.entry(client.clone())
+            .or_insert(Bucket { tokens: self.burst, last_refill: now });
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.burst);
+        bucket.last_refill = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.per_second))
+        }
+    }
+
+    /// Forgets the client, e.g. after it disconnected.
+    pub fn remove(&self, client: &K) {
+        self.buckets.lock().unwrap().by_client.remove(client);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn clients_are_limited_independently() {
+        let limiter = RateLimiter::new(2, 10.0);
+        assert!(limiter.check(&"a").is_ok());
+        assert!(limiter.check(&"a").is_ok());
+        let retry_after = limiter.check(&"a").unwrap_err();
+        assert!(limiter.check(&"b").is_ok());
+
+        tokio::time::advance(retry_after).await;
+        assert!(limiter.check(&"a").is_ok());
+        assert!(limiter.check(&"a").is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_clients_are_forgotten() {
+        let limiter = RateLimiter::new(2, 10.0);
+        assert!(limiter.check(&"a").is_ok());
+        assert!(limiter.check(&"a").is_ok());
+
+        tokio::time::advance(Duration::from_millis(200)).await;
+        assert!(limiter.check(&"b").is_ok());
+        assert_eq!(limiter.buckets.lock().unwrap().by_client.len(), 1);
+        assert!(limiter.check(&"a").is_ok());
+        assert!(limiter.check(&"a").is_ok());
+        assert!(limiter.check(&"a").is_err());
+    }
+}
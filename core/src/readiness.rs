@@ -0,0 +1,281 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Whether the host service is actually functional: its USB device watch runs, at least one player port is
+//! active and, if the service serves IPC, its socket listens.
+//!
+//! Services report readiness to their service manager with [`run_readiness_notifier`] (`READY=1` over
+//! `sd_notify`, a no-op without `NOTIFY_SOCKET`) and serve it on `GET /healthz` with [`run_health_server`], answering
+//! `200` when ready and `503` otherwise, with the [`Readiness`] as JSON body. `fsctctl status` shows it too. The
+//! readiness also tells the [`PowerMode`] of services throttling updates on battery.
+
+use std::io;
+#[cfg(feature = "usb")]
+use std::sync::Arc;
+#[cfg(feature = "usb")]
+use std::time::Duration;
+
+#[cfg(feature = "usb")]
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "usb")]
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+#[cfg(feature = "usb")]
+use tokio::net::{TcpListener, TcpStream};
+#[cfg(feature = "usb")]
+use tokio::task::JoinSet;
+
+use crate::power::PowerMode;
+#[cfg(feature = "usb")]
+use crate::service::{spawn_service, ServiceHandle};
+#[cfg(feature = "usb")]
+use crate::LocalDriver;
+
+/// Readiness of a host service, see the [module documentation](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Readiness {
+    /// All of the checks below pass.
+    pub ready: bool,
+    /// The USB device watch runs.
+    pub usb_watch: bool,
+    /// Names of the active player ports.
+    pub ports: Vec<String>,
+    /// Whether the IPC socket listens; `None` if the service doesn't serve IPC.
+    pub ipc: Option<bool>,
+    /// Whether updates are throttled on battery; `None` if the service doesn't monitor its power source.
+    #[serde(default)]
+    pub power_mode: Option<PowerMode>,
+}
+
+impl Readiness {
+    pub fn new(usb_watch: bool, ports: Vec<String>, ipc: Option<bool>) -> Self {
+        let ready = usb_watch && !ports.is_empty() && ipc != Some(false);
+        Self { ready, usb_watch, ports, ipc, power_mode: None }
+    }
+
+    pub fn with_power_mode(mut self, power_mode: Option<PowerMode>) -> Self {
+        self.power_mode = power_mode;
+        self
+    }
+
+    /// Checks that don't pass, e.g. for `STATUS=` of the service manager.
+    pub fn problems(&self) -> Vec<&'static str> {
+        let mut problems = Vec::new();
+        if !self.usb_watch {
+            problems.push("USB device watch not running");
+        }
+        if self.ports.is_empty() {
+            problems.push("no player port active");
+        }
+        if self.ipc == Some(false) {
+            problems.push("IPC socket not listening");
+        }
+        problems
+    }
+
+    /// One line summary, e.g. `Ready (ports: mpris)` or `Ready (ports: mpris), saving power on battery`.
+    pub fn summary(&self) -> String {
+        let summary = if self.ready {
+            format!("Ready (ports: {})", self.ports.join(", "))
+        } else {
+            format!("Not ready: {}", self.problems().join(", "))
+        };
+        match self.power_mode {
+            Some(PowerMode::Saving) => format!("{}, saving power on battery", summary),
+            _ => summary,
+        }
+    }
+}
+
+/// Sends `state`, e.g. `READY=1`, to the service manager over the socket in `NOTIFY_SOCKET`, see `sd_notify(3)`.
+/// Returns whether it was sent; `false` if the service manager doesn't listen.
+#[cfg(unix)]
+pub fn sd_notify(state: &str) -> io::Result<bool> {
+    use std::os::unix::net::UnixDatagram;
+
+    let Some(socket) = std::env::var_os("NOTIFY_SOCKET") else { return Ok(false) };
+    let datagram = UnixDatagram::unbound()?;
+    match socket.as_encoded_bytes().strip_prefix(b"@") {
+        Some(name) => send_to_abstract(&datagram, name, state)?,
+        None => {
+            datagram.send_to(state.as_bytes(), socket)?;
+        }
+    }
+    Ok(true)
+}
+
+/// Service managers notified over `sd_notify` run on Unix only.
+#[cfg(not(unix))]
+pub fn sd_notify(_state: &str) -> io::Result<bool> {
+    Ok(false)
+}
+
+#[cfg(target_os = "linux")]
+fn send_to_abstract(datagram: &std::os::unix::net::UnixDatagram, name: &[u8], state: &str) -> io::Result<()> {
+    use std::os::linux::net::SocketAddrExt;
+
+    let address = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+    datagram.send_to_addr(state.as_bytes(), &address).map(|_| ())
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn send_to_abstract(_datagram: &std::os::unix::net::UnixDatagram, _name: &[u8], _state: &str) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "abstract sockets are supported on Linux only"))
+}
+
+/// How often [`run_readiness_notifier`] checks the readiness.
+#[cfg(feature = "usb")]
+pub const DEFAULT_READINESS_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Checks the readiness of the driver every `interval`, notifying the service manager with `READY=1` once it is
+/// ready and with `STATUS=` on every change; `STOPPING=1` when the service is stopped.
+#[cfg(feature = "usb")]
+pub fn run_readiness_notifier(driver: Arc<LocalDriver>, interval: Duration) -> ServiceHandle {
+    spawn_service(move |mut stop| async move {
+        let mut ticker = tokio::time::interval(interval);
+        let mut last_summary = None;
+        let mut notified_ready = false;
+        loop {
+            tokio::select! {
+                _ = stop.signaled() => break,
+                _ = ticker.tick() => {}
+            }
+            let readiness = driver.readiness();
+            let summary = readiness.summary();
+            if last_summary.as_ref() == Some(&summary) {
+                continue;
+            }
+            info!("Service readiness: {}", summary);
+            let mut state = format!("STATUS={}", summary);
+            if readiness.ready && !notified_ready {
+                state.insert_str(0, "READY=1\n");
+                notified_ready = true;
+            }
+            if let Err(e) = sd_notify(&state) {
+                warn!("Failed to notify the service manager: {}", e);
+            }
+            last_summary = Some(summary);
+        }
+        let _ = sd_notify("STOPPING=1");
+    })
+}
+
+/// Serves the readiness of the driver on `GET /healthz` over HTTP/1.1, one request per connection.
+#[cfg(feature = "usb")]
+pub fn run_health_server(driver: Arc<LocalDriver>, listener: TcpListener) -> ServiceHandle {
+    spawn_service(move |mut stop| async move {
+        let mut connections = JoinSet::new();
+        loop {
+            tokio::select! {
+                _ = stop.signaled() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => {
+                        let driver = driver.clone();
+                        connections.spawn(async move {
+                            if let Err(e) = answer_health_request(&driver, stream).await {
+                                debug!("Health request failed: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => warn!("Failed to accept a health connection: {}", e),
+                },
+                Some(_) = connections.join_next() => {}
+            }
+        }
+        connections.shutdown().await;
+    })
+}
+
+/// How long a client may take to send its request line.
+#[cfg(feature = "usb")]
+const HEALTH_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[cfg(feature = "usb")]
+async fn answer_health_request(driver: &LocalDriver, stream: TcpStream) -> io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    let mut request_line = String::new();
+    tokio::time::timeout(HEALTH_REQUEST_TIMEOUT, stream.read_line(&mut request_line))
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "no request line"))??;
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    let path = target.split('?').next().unwrap_or_default();
+    let response = match (method, path) {
+        ("GET" | "HEAD", "/healthz") => {
+            let readiness = driver.readiness();
+            let status = if readiness.ready { "200 OK" } else { "503 Service Unavailable" };
+            let body = serde_json::to_string(&readiness).map_err(io::Error::other)?;
+            http_response(status, &body, method == "HEAD")
+        }
+        (_, "/healthz") => http_response("405 Method Not Allowed", "", false),
+        _ => http_response("404 Not Found", "", false),
+    };
+    stream.get_mut().write_all(response.as_bytes()).await?;
+    stream.get_mut().shutdown().await
+}
+
+#[cfg(feature = "usb")]
+fn http_response(status: &str, body: &str, head: bool) -> String {
+    format!("HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status, body.len(), if head { "" } else { body })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readiness_requires_watch_port_and_listening_ipc() {
+        let ports = vec!["mpris".to_string()];
+        assert!(Readiness::new(true, ports.clone(), Some(true)).ready);
+        assert!(Readiness::new(true, ports.clone(), None).ready);
+        let readiness = Readiness::new(false, Vec::new(), Some(false));
+        assert!(!readiness.ready);
+        assert_eq!(readiness.summary(),
+                   "Not ready: USB device watch not running, no player port active, IPC socket not listening");
+        assert_eq!(Readiness::new(true, ports.clone(), Some(true)).summary(), "Ready (ports: mpris)");
+        let saving = Readiness::new(true, ports, None).with_power_mode(Some(PowerMode::Saving));
+        assert_eq!(saving.summary(), "Ready (ports: mpris), saving power on battery");
+    }
+
+    #[cfg(feature = "usb")]
+    #[tokio::test]
+    async fn health_is_unavailable_until_ready() {
+        use tokio::io::AsyncReadExt;
+
+        let driver = Arc::new(LocalDriver::with_new_managers());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = run_health_server(driver, listener);
+
+        let request = |request: &'static str| async move {
+            let mut stream = TcpStream::connect(address).await.unwrap();
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+        let response = request("GET /healthz HTTP/1.1\r\nHost: localhost\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 503 "));
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        let readiness: Readiness = serde_json::from_str(body).unwrap();
+        assert_eq!(readiness, Readiness::new(false, Vec::new(), None));
+        assert!(request("GET /metrics HTTP/1.1\r\n\r\n").await.starts_with("HTTP/1.1 404 "));
+
+        server.shutdown().await.unwrap();
+    }
+}
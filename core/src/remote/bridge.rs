@@ -0,0 +1,227 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Forwarding players of one service to the devices of another, e.g. the player of the office PC to the display in
+//! the hallway attached to another host.
+//!
+//! [`run_driver_bridge`] registers the selected players of a [`LocalDriver`] with the driver server of the other
+//! service and keeps their state in step; playback commands of the other service's devices are carried out by the
+//! players here. Only players of the service's own ports are forwarded, so two services bridged to each other don't
+//! send the players back. The other service tags the forwarded players with the origin of the bridge, so its
+//! devices can pick them by [`OriginFilter`](crate::OriginFilter).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use log::{debug, info, warn};
+use tokio::sync::broadcast;
+
+use super::RemoteDriver;
+use crate::definitions::PlaybackCommand;
+use crate::player_events::PlayerEvent;
+use crate::player_interface::{PlayerInterface, PlayerInterfaces};
+use crate::player_manager::ManagedPlayerId;
+use crate::player_origin::PlayerOrigin;
+use crate::service::{spawn_service, ServiceHandle};
+use crate::{FsctDriver, LocalDriver};
+
+/// Time between attempts to reach the other service.
+pub const BRIDGE_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Driver server a bridge forwards to and the players it forwards.
+#[derive(Debug, Clone)]
+pub struct DriverBridge {
+    endpoint: String,
+    token: Option<String>,
+    players: Vec<String>,
+    origin: PlayerOrigin,
+}
+
+impl DriverBridge {
+    /// Bridge to the driver server at `endpoint`, e.g. `http://hallway.local:50151`, forwarding all players.
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self { endpoint: endpoint.into(), token: None, players: Vec::new(), origin: PlayerOrigin::default() }
+    }
+
+    /// Presents `token` to servers with an [`AuthPolicy`](crate::auth::AuthPolicy); it needs the control scope.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Forwards only the players with these self ids; all players if empty.
+    pub fn with_players(mut self, players: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.players = players.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Tags the forwarded players with `origin`; without a host, the server takes the address of the bridge.
+    pub fn with_origin(mut self, origin: PlayerOrigin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    fn forwards(&self, self_id: &str) -> bool {
+        self.players.is_empty() || self.players.iter().any(|player| player == self_id)
+    }
+
+    async fn connect(&self) -> Result<RemoteDriver, Error> {
+        let target = match &self.token {
+            Some(token) => RemoteDriver::connect_with_token(self.endpoint.clone(), token).await?,
+            None => RemoteDriver::connect(self.endpoint.clone()).await?,
+        };
+        Ok(target.with_origin(self.origin.clone()))
+    }
+}
+
+/// Carries out commands of the other service's devices with the interface of the player here.
+struct ForwardedCommands {
+    interfaces: Arc<PlayerInterfaces>,
+    player_id: ManagedPlayerId,
+}
+
+#[async_trait]
+impl PlayerInterface for ForwardedCommands {
+    async fn execute(&self, command: PlaybackCommand) -> Result<(), Error> {
+        self.interfaces.execute(self.player_id, command).await
+    }
+}
+
+/// Players forwarded over one connection to the other service.
+struct Mirror<'a> {
+    source: &'a LocalDriver,
+    bridge: &'a DriverBridge,
+    target: RemoteDriver,
+    /// Ids of the forwarded players on the other service.
+    forwarded: HashMap<ManagedPlayerId, ManagedPlayerId>,
+}
+
+impl Mirror<'_> {
+    /// Forwards players until the source driver stops (`Ok`) or a call to the other service fails.
+    async fn run(&mut self) -> Result<(), Error> {
+        // subscribe before listing so that no player registered in between is missed
+        let mut events = self.source.subscribe_player_events();
+        for player in self.source.player_manager().list_players() {
+            self.forward(player.player_id, &player.self_id).await?;
+        }
+        loop {
+            match events.recv().await {
+                Ok(PlayerEvent::Registered { player_id, self_id }) => self.forward(player_id, &self_id).await?,
+                Ok(PlayerEvent::Unregistered { player_id }) => {
+                    if let Some(remote_id) = self.forwarded.remove(&player_id) {
+                        self.target.unregister_player(remote_id).await?;
+                    }
+                }
+                Ok(PlayerEvent::StateUpdated { player_id, .. }
+                   | PlayerEvent::StatusUpdated { player_id, .. }
+                   | PlayerEvent::TimelineUpdated { player_id, .. }
+                   | PlayerEvent::VolumeUpdated { player_id, .. }
+                   | PlayerEvent::ModesUpdated { player_id, .. }
+                   | PlayerEvent::TextMetadataUpdated { player_id, .. }) => self.update(player_id).await?,
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!("Bridge to {} lagged by {} player events", self.bridge.endpoint, skipped);
+                    let player_ids: Vec<_> = self.forwarded.keys().copied().collect();
+                    for player_id in player_ids {
+                        self.update(player_id).await?;
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+
+    async fn forward(&mut self, player_id: ManagedPlayerId, self_id: &str) -> Result<(), Error> {
+        let player_manager = self.source.player_manager();
+        if self.forwarded.contains_key(&player_id) || !self.bridge.forwards(self_id)
+            || !player_manager.player_origins().get(player_id).is_local() {
+            return Ok(());
+        }
+        let remote_id = self.target.register_player(self_id.to_string()).await?;
+        self.forwarded.insert(player_id, remote_id);
+        let commands = ForwardedCommands { interfaces: player_manager.player_interfaces(), player_id };
+        self.target.set_player_interface(remote_id, Some(Arc::new(commands)))?;
+        debug!("Forwarding player {} to {} as player {}", self_id, self.bridge.endpoint, remote_id);
+        self.update(player_id).await
+    }
+
+    async fn update(&self, player_id: ManagedPlayerId) -> Result<(), Error> {
+        let Some(remote_id) = self.forwarded.get(&player_id) else {
+            return Ok(());
+        };
+        let state = self.source.player_manager().list_players().into_iter()
+            .find(|player| player.player_id == player_id)
+            .map(|player| player.state)
+            .ok_or_else(|| anyhow!("Player {} is not registered", player_id));
+        match state {
+            Ok(state) => self.target.update_player_state(*remote_id, state).await,
+            // unregistered meanwhile, the event follows
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Unregisters the forwarded players from the other service.
+    async fn withdraw(&mut self) {
+        for (_, remote_id) in self.forwarded.drain() {
+            if let Err(e) = self.target.unregister_player(remote_id).await {
+                debug!("Failed to withdraw player {} from {}: {}", remote_id, self.bridge.endpoint, e);
+            }
+        }
+    }
+}
+
+/// Forwards the players of `source` selected by `bridge` to the other service until the service is stopped,
+/// reconnecting every [`BRIDGE_RETRY_INTERVAL`] while the other service is unreachable. The forwarded players are
+/// unregistered from it when the bridge stops.
+pub fn run_driver_bridge(source: Arc<LocalDriver>, bridge: DriverBridge) -> ServiceHandle {
+    spawn_service(move |mut stop| async move {
+        loop {
+            let connected = tokio::select! {
+                _ = stop.signaled() => break,
+                connected = bridge.connect() => connected,
+            };
+            match connected {
+                Ok(target) => {
+                    info!("Forwarding players to {}", bridge.endpoint);
+                    let mut mirror = Mirror { source: &source, bridge: &bridge, target, forwarded: HashMap::new() };
+                    let stopped = tokio::select! {
+                        _ = stop.signaled() => true,
+                        result = mirror.run() => match result {
+                            Ok(()) => break,
+                            Err(e) => {
+                                warn!("Forwarding players to {} failed: {}", bridge.endpoint, e);
+                                false
+                            }
+                        },
+                    };
+                    mirror.withdraw().await;
+                    if stopped {
+                        break;
+                    }
+                }
+                Err(e) => debug!("Failed to reach {}: {}", bridge.endpoint, e),
+            }
+            tokio::select! {
+                _ = stop.signaled() => break,
+                _ = tokio::time::sleep(BRIDGE_RETRY_INTERVAL) => {}
+            }
+        }
+    })
+}
@@ -0,0 +1,487 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Driver of a player port in another process than the service owning the devices.
+
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use log::{debug, warn};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::interceptor::InterceptedService;
+use tonic::service::Interceptor;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Status, Streaming};
+
+use super::proto::driver_event::Event;
+use super::proto::firmware_update_progress::Progress;
+use super::proto::fsct_driver_client::FsctDriverClient;
+use super::proto::{self, DriverEvent, Empty};
+use super::{to_json, SESSION_HEADER};
+use crate::descriptor_dump::DeviceDescriptorDump;
+use crate::definitions::{DfuProgress, FirmwareUpdateReport};
+use crate::definitions::{DeviceLimits, FsctStatus, FsctTextMetadata, PlaybackCommand, PlaybackModes, TimelineInfo, VolumeInfo};
+use crate::device_history::DeviceAttachRecord;
+use crate::device_manager::{DeviceEvent, ManagedDeviceId};
+use crate::orchestrator::{DndScope, DndState, RouteOverride};
+use crate::player_events::{PlayerEvent, PlayerEventFilter};
+use crate::player_interface::{PlayerInterface, PlayerInterfaces};
+use crate::player_manager::{ManagedPlayerId, PlayerInfo};
+use crate::usage_stats::UsageReport;
+use crate::player_origin::PlayerOrigin;
+use crate::player_state::PlayerState;
+use crate::readiness::Readiness;
+use crate::FsctDriver;
+
+/// State of the serving driver, as followed from its event feed.
+struct Shared {
+    preferred: Mutex<Option<ManagedPlayerId>>,
+    /// Registered players and the devices they are assigned to.
+    players: Mutex<HashMap<ManagedPlayerId, Option<ManagedDeviceId>>>,
+    device_limits: Mutex<Vec<DeviceLimits>>,
+    events_tx: broadcast::Sender<PlayerEvent>,
+    filtered_txs: Mutex<Vec<(PlayerEventFilter, broadcast::Sender<PlayerEvent>)>>,
+    device_events_tx: broadcast::Sender<DeviceEvent>,
+    interfaces: Arc<PlayerInterfaces>,
+}
+
+impl Shared {
+    fn apply_snapshot(&self, snapshot: proto::Snapshot) -> Result<(), Error> {
+        *self.preferred.lock().unwrap() = snapshot.preferred_player.and_then(NonZeroU32::new);
+        *self.device_limits.lock().unwrap() = serde_json::from_str(&snapshot.device_limits_json)?;
+        Ok(())
+    }
+
+    fn apply_player_event(&self, event: PlayerEvent) {
+        {
+            let mut players = self.players.lock().unwrap();
+            match &event {
+                PlayerEvent::Registered { player_id, .. } => {
+                    players.entry(*player_id).or_default();
+                }
+                PlayerEvent::Unregistered { player_id } => {
+                    players.remove(player_id);
+                    self.interfaces.set(*player_id, None);
+                }
+                PlayerEvent::Assigned { player_id, device_id } => {
+                    players.insert(*player_id, Some(*device_id));
+                }
+                PlayerEvent::Unassigned { player_id, .. } => {
+                    players.insert(*player_id, None);
+                }
+                PlayerEvent::PreferredChanged { preferred } => *self.preferred.lock().unwrap() = *preferred,
+                _ => {}
+            }
+        }
+        let _ = self.events_tx.send(event.clone());
+        let mut filtered_txs = self.filtered_txs.lock().unwrap();
+        filtered_txs.retain(|(_, tx)| tx.receiver_count() > 0);
+        for (filter, tx) in filtered_txs.iter() {
+            if filter.matches(&event) {
+                let _ = tx.send(event.clone());
+            }
+        }
+    }
+
+    fn apply(&self, event: Event) -> Result<(), Error> {
+        match event {
+            Event::Snapshot(snapshot) => self.apply_snapshot(snapshot)?,
+            Event::PlayerEventJson(json) => self.apply_player_event(serde_json::from_str(&json)?),
+            Event::DeviceEventJson(json) => {
+                let _ = self.device_events_tx.send(serde_json::from_str(&json)?);
+            }
+            Event::DeviceLimitsJson(json) => *self.device_limits.lock().unwrap() = serde_json::from_str(&json)?,
+            Event::PlayerCommand(command) => {
+                let player_id = NonZeroU32::new(command.player_id).ok_or_else(|| anyhow!("Command for player 0"))?;
+                let command: PlaybackCommand = serde_json::from_str(&command.command_json)?;
+                let interfaces = self.interfaces.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = interfaces.execute(player_id, command).await {
+                        warn!("Failed to carry out {:?} for player {}: {}", command, player_id, e);
+                    }
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn run_feed(shared: Arc<Shared>, mut feed: Streaming<DriverEvent>) {
+    loop {
+        match feed.message().await {
+            Ok(Some(DriverEvent { event: Some(event) })) => {
+                if let Err(e) = shared.apply(event) {
+                    warn!("Ignoring malformed driver event: {}", e);
+                }
+            }
+            Ok(Some(DriverEvent { event: None })) => {}
+            Ok(None) => {
+                debug!("Driver server closed the event feed");
+                break;
+            }
+            Err(e) => {
+                warn!("Event feed of the driver server failed: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+/// Presents the session and the bearer token, if any, with every call.
+#[derive(Clone)]
+struct SessionMetadata {
+    session: MetadataValue<Ascii>,
+    token: Option<MetadataValue<Ascii>>,
+}
+
+impl SessionMetadata {
+    fn new(token: Option<MetadataValue<Ascii>>) -> Self {
+        let session = uuid::Uuid::new_v4().simple().to_string().parse().expect("uuids are ASCII");
+        Self { session, token }
+    }
+}
+
+impl Interceptor for SessionMetadata {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        request.metadata_mut().insert(SESSION_HEADER, self.session.clone());
+        if let Some(token) = &self.token {
+            request.metadata_mut().insert("authorization", token.clone());
+        }
+        Ok(request)
+    }
+}
+
+type Client = FsctDriverClient<InterceptedService<Channel, SessionMetadata>>;
+
+/// [`FsctDriver`] forwarding to a [`DriverServer`](super::DriverServer) over gRPC.
+///
+/// Queries are answered from the state followed through the server's event feed. Interfaces of players are kept
+/// in this process; the server passes playback commands for them back over the feed. The server unregisters the
+/// players registered through this driver once the feed ends, i.e. when the driver is dropped or disconnected.
+pub struct RemoteDriver {
+    client: Client,
+    shared: Arc<Shared>,
+    feed: JoinHandle<()>,
+    origin: PlayerOrigin,
+}
+
+impl RemoteDriver {
+    /// Connects to the driver server at `endpoint`, e.g. `http://127.0.0.1:50151`.
+    pub async fn connect(endpoint: impl Into<String>) -> Result<Self, Error> {
+        let channel = Endpoint::from_shared(endpoint.into())?.connect().await?;
+        Self::with_channel(channel).await
+    }
+
+    /// Connects to the driver server at `endpoint` presenting `token` as bearer token, for servers with an
+    /// [`AuthPolicy`](crate::auth::AuthPolicy).
+    pub async fn connect_with_token(endpoint: impl Into<String>, token: &str) -> Result<Self, Error> {
+        let token = format!("Bearer {}", token).parse().map_err(|_| anyhow!("Invalid characters in the token"))?;
+        let channel = Endpoint::from_shared(endpoint.into())?.connect().await?;
+        Self::start(FsctDriverClient::with_interceptor(channel, SessionMetadata::new(Some(token)))).await
+    }
+
+    /// Uses an already established channel to the driver server.
+    pub async fn with_channel(channel: Channel) -> Result<Self, Error> {
+        Self::start(FsctDriverClient::with_interceptor(channel, SessionMetadata::new(None))).await
+    }
+
+    async fn start(mut client: Client) -> Result<Self, Error> {
+        let mut feed = client.subscribe(Empty {}).await?.into_inner();
+        let shared = Arc::new(Shared {
+            preferred: Mutex::new(None),
+            players: Mutex::new(HashMap::new()),
+            device_limits: Mutex::new(Vec::new()),
+            events_tx: broadcast::channel(256).0,
+            filtered_txs: Mutex::new(Vec::new()),
+            device_events_tx: broadcast::channel(256).0,
+            interfaces: Arc::new(PlayerInterfaces::new()),
+        });
+        match feed.message().await? {
+            Some(DriverEvent { event: Some(Event::Snapshot(snapshot)) }) => shared.apply_snapshot(snapshot)?,
+            _ => return Err(anyhow!("Driver server did not start the event feed with a snapshot")),
+        }
+        let feed = tokio::spawn(run_feed(shared.clone(), feed));
+        Ok(Self { client, shared, feed, origin: PlayerOrigin::default() })
+    }
+
+    /// Tags the players registered through this driver with `origin`, for routing by origin on the server. Without
+    /// a host, the server takes the address the players are registered from.
+    pub fn with_origin(mut self, origin: PlayerOrigin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Players registered with the server.
+    pub async fn list_players(&self) -> Result<Vec<PlayerInfo>, Error> {
+        let reply = self.client.clone().list_players(Empty {}).await.map_err(error)?;
+        Ok(serde_json::from_str(&reply.into_inner().json)?)
+    }
+
+    /// Sets the preferred player of the server and waits for the server to take it, unlike
+    /// [`FsctDriver::set_preferred_player`], which returns before the call completes.
+    pub async fn set_preferred_player_and_wait(&self, preferred: Option<ManagedPlayerId>) -> Result<(), Error> {
+        let request = proto::PreferredPlayer { player_id: preferred.map(|id| id.get()) };
+        self.client.clone().set_preferred_player(request).await.map_err(error)?;
+        *self.shared.preferred.lock().unwrap() = preferred;
+        Ok(())
+    }
+
+    /// Stops the port of the server running under `name`, e.g. its OS watcher, and starts it anew.
+    pub async fn restart_port(&self, name: &str) -> Result<(), Error> {
+        self.client.clone().restart_port(proto::PortName { name: name.to_string() }).await.map_err(error)?;
+        Ok(())
+    }
+
+    /// BOS and FSCT descriptors of the device, raw and as parsed by the serving host.
+    pub async fn get_device_descriptors(&self, device_id: ManagedDeviceId) -> Result<DeviceDescriptorDump, Error> {
+        let request = proto::DeviceId { device_id: device_id.to_string() };
+        let reply = self.client.clone().get_device_descriptors(request).await.map_err(error)?;
+        Ok(serde_json::from_str(&reply.into_inner().json)?)
+    }
+
+    /// Updates the firmware of the device with `image` on the serving host, reporting progress while the image is
+    /// streamed to the device; the host waits for the device to restart and reports the firmware versions.
+    pub async fn update_firmware(&self, device_id: ManagedDeviceId, image: Vec<u8>,
+                                 mut progress: impl FnMut(DfuProgress)) -> Result<FirmwareUpdateReport, Error> {
+        let request = proto::FirmwareImage { device_id: device_id.to_string(), image };
+        let mut stream = self.client.clone().update_firmware(request).await.map_err(error)?.into_inner();
+        while let Some(message) = stream.message().await.map_err(error)? {
+            match message.progress {
+                Some(Progress::ProgressJson(json)) => progress(serde_json::from_str(&json)?),
+                Some(Progress::ReportJson(json)) => return Ok(serde_json::from_str(&json)?),
+                None => {}
+            }
+        }
+        Err(anyhow!("Firmware update of device {} ended without a report", device_id))
+    }
+
+    /// Names of the ports of the server that can be restarted.
+    pub async fn list_ports(&self) -> Result<Vec<String>, Error> {
+        let reply = self.client.clone().list_ports(Empty {}).await.map_err(error)?;
+        Ok(serde_json::from_str(&reply.into_inner().json)?)
+    }
+
+    /// Readiness of the serving host, see [`readiness`](crate::readiness).
+    pub async fn get_readiness(&self) -> Result<Readiness, Error> {
+        let reply = self.client.clone().get_readiness(Empty {}).await.map_err(error)?;
+        Ok(serde_json::from_str(&reply.into_inner().json)?)
+    }
+
+    /// Suspends (or resumes) all writes of the server to the devices in scope.
+    pub async fn set_dnd(&self, scope: DndScope, enabled: bool) -> Result<(), Error> {
+        let device_id = match scope {
+            DndScope::Global => None,
+            DndScope::Device(device_id) => Some(device_id.to_string()),
+        };
+        self.client.clone().set_dnd(proto::DndSwitch { device_id, enabled }).await.map_err(error)?;
+        Ok(())
+    }
+
+    /// Do-not-disturb switches active on the server.
+    pub async fn get_dnd(&self) -> Result<DndState, Error> {
+        let reply = self.client.clone().get_dnd(Empty {}).await.map_err(error)?;
+        Ok(serde_json::from_str(&reply.into_inner().json)?)
+    }
+
+    /// Shows the player on the device until it stops, or for `duration`, over assignments and selection rules.
+    pub async fn force_route(&self, player_id: ManagedPlayerId, device_id: ManagedDeviceId,
+                             duration: Option<Duration>) -> Result<(), Error> {
+        let request = proto::RouteOverride {
+            player_id: player_id.get(),
+            device_id: device_id.to_string(),
+            duration_secs: duration.map(|duration| duration.as_secs_f64()),
+        };
+        self.client.clone().force_route(request).await.map_err(error)?;
+        Ok(())
+    }
+
+    /// Removes the route override of the device; returns whether there was one.
+    pub async fn clear_route(&self, device_id: ManagedDeviceId) -> Result<bool, Error> {
+        let request = proto::DeviceId { device_id: device_id.to_string() };
+        Ok(self.client.clone().clear_route(request).await.map_err(error)?.into_inner().cleared)
+    }
+
+    /// Route overrides active on the server.
+    pub async fn routes(&self) -> Result<Vec<RouteOverride>, Error> {
+        let reply = self.client.clone().list_routes(Empty {}).await.map_err(error)?;
+        Ok(serde_json::from_str(&reply.into_inner().json)?)
+    }
+
+    /// Usage statistics the server collected.
+    pub async fn usage_report(&self) -> Result<UsageReport, Error> {
+        let reply = self.client.clone().get_usage_report(Empty {}).await.map_err(error)?;
+        Ok(serde_json::from_str(&reply.into_inner().json)?)
+    }
+
+    /// Runs a call of a synchronous driver method in the background.
+    fn spawn_call<T: Send + 'static>(&self, name: &'static str,
+                                     call: impl Future<Output = Result<T, tonic::Status>> + Send + 'static)
+                                     -> Result<(), Error> {
+        let runtime = tokio::runtime::Handle::try_current()
+            .map_err(|_| anyhow!("RemoteDriver::{} must be called within a Tokio runtime", name))?;
+        runtime.spawn(async move {
+            if let Err(e) = call.await {
+                warn!("RemoteDriver::{} failed: {}", name, e.message());
+            }
+        });
+        Ok(())
+    }
+}
+
+impl Drop for RemoteDriver {
+    fn drop(&mut self) {
+        self.feed.abort();
+    }
+}
+
+fn error(status: tonic::Status) -> Error {
+    anyhow!("{}", status.message())
+}
+
+#[async_trait]
+impl FsctDriver for RemoteDriver {
+    async fn register_player(&self, self_id: String) -> Result<ManagedPlayerId, Error> {
+        let request = proto::RegisterPlayerRequest {
+            self_id,
+            origin_host: self.origin.host.clone(),
+            origin_user: self.origin.user.clone(),
+        };
+        let response = self.client.clone().register_player(request).await;
+        let player_id = super::player_id(response.map_err(error)?.into_inner().player_id).map_err(error)?;
+        self.shared.players.lock().unwrap().entry(player_id).or_default();
+        Ok(player_id)
+    }
+
+    async fn unregister_player(&self, player_id: ManagedPlayerId) -> Result<(), Error> {
+        self.client.clone().unregister_player(proto::PlayerId { player_id: player_id.get() }).await.map_err(error)?;
+        self.shared.players.lock().unwrap().remove(&player_id);
+        self.shared.interfaces.set(player_id, None);
+        Ok(())
+    }
+
+    async fn assign_player_to_device(&self, player_id: ManagedPlayerId, device_id: ManagedDeviceId) -> Result<(), Error> {
+        let request = proto::PlayerDevice { player_id: player_id.get(), device_id: device_id.to_string() };
+        self.client.clone().assign_player_to_device(request).await.map_err(error)?;
+        self.shared.players.lock().unwrap().insert(player_id, Some(device_id));
+        Ok(())
+    }
+
+    async fn unassign_player_from_device(&self, player_id: ManagedPlayerId, device_id: ManagedDeviceId) -> Result<(), Error> {
+        let request = proto::PlayerDevice { player_id: player_id.get(), device_id: device_id.to_string() };
+        self.client.clone().unassign_player_from_device(request).await.map_err(error)?;
+        self.shared.players.lock().unwrap().insert(player_id, None);
+        Ok(())
+    }
+
+    async fn update_player_state(&self, player_id: ManagedPlayerId, new_state: PlayerState) -> Result<(), Error> {
+        let request = proto::PlayerUpdate { player_id: player_id.get(), json: to_json(&new_state) };
+        self.client.clone().update_player_state(request).await.map_err(error)?;
+        Ok(())
+    }
+
+    async fn update_player_status(&self, player_id: ManagedPlayerId, new_status: FsctStatus) -> Result<(), Error> {
+        let request = proto::PlayerUpdate { player_id: player_id.get(), json: to_json(&new_status) };
+        self.client.clone().update_player_status(request).await.map_err(error)?;
+        Ok(())
+    }
+
+    async fn update_player_timeline(&self, player_id: ManagedPlayerId, new_timeline: Option<TimelineInfo>) -> Result<(), Error> {
+        let request = proto::PlayerUpdate { player_id: player_id.get(), json: to_json(&new_timeline) };
+        self.client.clone().update_player_timeline(request).await.map_err(error)?;
+        Ok(())
+    }
+
+    async fn update_player_volume(&self, player_id: ManagedPlayerId, new_volume: Option<VolumeInfo>) -> Result<(), Error> {
+        let request = proto::PlayerUpdate { player_id: player_id.get(), json: to_json(&new_volume) };
+        self.client.clone().update_player_volume(request).await.map_err(error)?;
+        Ok(())
+    }
+
+    async fn update_player_modes(&self, player_id: ManagedPlayerId, new_modes: PlaybackModes) -> Result<(), Error> {
+        let request = proto::PlayerUpdate { player_id: player_id.get(), json: to_json(&new_modes) };
+        self.client.clone().update_player_modes(request).await.map_err(error)?;
+        Ok(())
+    }
+
+    async fn update_player_metadata(&self, player_id: ManagedPlayerId, metadata_id: FsctTextMetadata, new_text: Option<String>) -> Result<(), Error> {
+        let request = proto::MetadataUpdate {
+            player_id: player_id.get(),
+            metadata_json: to_json(&metadata_id),
+            text: new_text,
+        };
+        self.client.clone().update_player_metadata(request).await.map_err(error)?;
+        Ok(())
+    }
+
+    fn set_preferred_player(&self, preferred: Option<ManagedPlayerId>) -> Result<(), Error> {
+        *self.shared.preferred.lock().unwrap() = preferred;
+        let request = proto::PreferredPlayer { player_id: preferred.map(|id| id.get()) };
+        let mut client = self.client.clone();
+        self.spawn_call("set_preferred_player", async move { client.set_preferred_player(request).await })
+    }
+
+    fn get_preferred_player(&self) -> Option<ManagedPlayerId> {
+        *self.shared.preferred.lock().unwrap()
+    }
+
+    fn get_player_assigned_device(&self, player_id: ManagedPlayerId) -> Result<Option<ManagedDeviceId>, Error> {
+        self.shared.players.lock().unwrap().get(&player_id).copied()
+            .ok_or_else(|| anyhow!("Player {} is not registered", player_id))
+    }
+
+    fn set_player_interface(&self, player_id: ManagedPlayerId, interface: Option<Arc<dyn PlayerInterface>>)
+                            -> Result<(), Error> {
+        let request = proto::PlayerInterfaceAttachment { player_id: player_id.get(), attached: interface.is_some() };
+        self.shared.interfaces.set(player_id, interface);
+        let mut client = self.client.clone();
+        self.spawn_call("set_player_interface", async move { client.set_player_interface(request).await })
+    }
+
+    fn get_device_limits(&self) -> Vec<DeviceLimits> {
+        self.shared.device_limits.lock().unwrap().clone()
+    }
+
+    async fn list_devices(&self) -> Result<Vec<DeviceAttachRecord>, Error> {
+        let reply = self.client.clone().list_devices(Empty {}).await.map_err(error)?;
+        Ok(serde_json::from_str(&reply.into_inner().json)?)
+    }
+
+    fn subscribe_device_events(&self) -> broadcast::Receiver<DeviceEvent> {
+        self.shared.device_events_tx.subscribe()
+    }
+
+    fn subscribe_player_events(&self) -> broadcast::Receiver<PlayerEvent> {
+        self.shared.events_tx.subscribe()
+    }
+
+    fn subscribe_filtered(&self, filter: PlayerEventFilter) -> broadcast::Receiver<PlayerEvent> {
+        let (tx, rx) = broadcast::channel(256);
+        self.shared.filtered_txs.lock().unwrap().push((filter, tx));
+        rx
+    }
+
+    async fn wait_applied(&self) -> Result<(), Error> {
+        self.client.clone().wait_applied(Empty {}).await.map_err(error)?;
+        Ok(())
+    }
+}
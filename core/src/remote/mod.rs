@@ -0,0 +1,195 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! [`FsctDriver`](crate::FsctDriver) over gRPC, so player ports can run in another process (e.g. in the user
+//! session) than the service owning the USB devices.
+//!
+//! [`DriverServer`] serves a [`LocalDriver`](crate::LocalDriver); ports in the other process use a
+//! [`RemoteDriver`] connected to it. The protocol is in `core/proto/fsct_driver.proto`. With the `zeroconf`
+//! feature, servers reachable from the LAN can be advertised over mDNS. A server rejects every caller until given an
+//! [`AuthPolicy`](crate::auth::AuthPolicy); services on the local host use a token from
+//! [`create_token_file`](crate::auth::create_token_file).
+//!
+//! A [`DriverBridge`] forwards players of one service to the devices of another.
+
+#[cfg(feature = "usb")]
+mod bridge;
+mod client;
+#[cfg(feature = "usb")]
+mod server;
+#[cfg(feature = "zeroconf")]
+mod zeroconf;
+
+#[cfg(feature = "usb")]
+pub use bridge::{run_driver_bridge, DriverBridge, BRIDGE_RETRY_INTERVAL};
+pub use client::RemoteDriver;
+#[cfg(feature = "usb")]
+pub use server::{run_driver_server, DriverServer};
+#[cfg(feature = "zeroconf")]
+pub use zeroconf::{host_capabilities, run_zeroconf_advertisement, Advertisement, FSCT_HOST_SERVICE_TYPE};
+
+/// Generated protocol types, client and server.
+pub mod proto {
+    tonic::include_proto!("fsct.driver.v1");
+}
+
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::num::NonZeroU32;
+
+use serde::Serialize;
+use tonic::Status;
+
+use crate::player_manager::ManagedPlayerId;
+
+/// Default port of the driver server.
+pub const DEFAULT_DRIVER_SERVER_PORT: u16 = 50151;
+
+/// Default address of the driver server: the default port on loopback, reachable from the local host only.
+pub const DEFAULT_DRIVER_SERVER_ADDRESS: SocketAddr =
+    SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, DEFAULT_DRIVER_SERVER_PORT));
+
+/// Metadata key carrying the session of a [`RemoteDriver`], to which the players it registers are leased.
+const SESSION_HEADER: &str = "fsct-session";
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("core types serialize to JSON")
+}
+
+#[cfg(feature = "usb")]
+fn from_json<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, Status> {
+    serde_json::from_str(json).map_err(|e| Status::invalid_argument(format!("Invalid JSON: {}", e)))
+}
+
+fn player_id(id: u32) -> Result<ManagedPlayerId, Status> {
+    NonZeroU32::new(id).ok_or_else(|| Status::invalid_argument("Player id must not be 0"))
+}
+
+#[cfg(all(test, feature = "usb"))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use anyhow::Error;
+    use async_trait::async_trait;
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::auth::{ApiToken, AuthPolicy, Scope};
+    use crate::definitions::{FsctStatus, PlaybackCommand};
+    use crate::player_origin::PlayerOrigin;
+    use crate::{FsctDriver, LocalDriver, PlayerEvent, PlayerInterface, PlayerState};
+
+    #[derive(Default)]
+    struct PausingPlayer {
+        paused: Mutex<bool>,
+    }
+
+    #[async_trait]
+    impl PlayerInterface for PausingPlayer {
+        async fn pause(&self) -> Result<(), Error> {
+            *self.paused.lock().unwrap() = true;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn remote_players_are_registered_updated_and_controlled() {
+        let local = Arc::new(LocalDriver::with_new_managers());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = run_driver_server(DriverServer::new(local.clone()).with_auth_policy(AuthPolicy::open()), listener);
+
+        let remote = RemoteDriver::connect(format!("http://{}", address)).await.unwrap();
+        let mut events = remote.subscribe_player_events();
+        let player_id = remote.register_player("remote-port".to_string()).await.unwrap();
+        let state = PlayerState { status: FsctStatus::Playing, ..Default::default() };
+        remote.update_player_state(player_id, state.clone()).await.unwrap();
+
+        assert!(matches!(events.recv().await.unwrap(),
+                         PlayerEvent::Registered { player_id: id, self_id } if id == player_id && self_id == "remote-port"));
+        assert!(matches!(events.recv().await.unwrap(),
+                         PlayerEvent::StateUpdated { player_id: id, state: s } if id == player_id && s.status == state.status));
+        assert_eq!(remote.get_player_assigned_device(player_id).unwrap(), None);
+        let players = remote.list_players().await.unwrap();
+        assert_eq!((players[0].player_id, players[0].state.status), (player_id, FsctStatus::Playing));
+        assert!(remote.list_devices().await.unwrap().is_empty());
+
+        let player = Arc::new(PausingPlayer::default());
+        remote.set_player_interface(player_id, Some(player.clone())).unwrap();
+        let interfaces = local.player_manager().player_interfaces();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            // the attachment is sent in the background
+            while interfaces.get(player_id).is_none() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            interfaces.execute(player_id, PlaybackCommand::Pause).await.unwrap();
+            while !*player.paused.lock().unwrap() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.unwrap();
+
+        drop(remote);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            // players are unregistered once the server notices the event feed ended
+            while !local.player_manager().list_players().is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.unwrap();
+        server.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn bridge_forwards_local_players_to_authorized_server() {
+        let hallway = Arc::new(LocalDriver::with_new_managers());
+        let policy = AuthPolicy {
+            tokens: vec![ApiToken { name: "office".into(), token: "secret".into(), scope: Scope::Control }],
+            ..Default::default()
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let server = run_driver_server(DriverServer::new(hallway.clone()).with_auth_policy(policy), listener);
+        assert!(RemoteDriver::connect(endpoint.clone()).await.is_err());
+        assert!(RemoteDriver::connect_with_token(endpoint.clone(), "guess").await.is_err());
+
+        let office = Arc::new(LocalDriver::with_new_managers());
+        let music = office.register_player("music".to_string()).await.unwrap();
+        office.register_player("browser".to_string()).await.unwrap();
+        let bridge = DriverBridge::new(endpoint)
+            .with_token("secret")
+            .with_players(["music"])
+            .with_origin(PlayerOrigin::new(Some("office-pc".into()), None));
+        let bridge = run_driver_bridge(office.clone(), bridge);
+        office.update_player_status(music, FsctStatus::Playing).await.unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let players = hallway.player_manager().list_players();
+                if players.iter().any(|player| player.state.status == FsctStatus::Playing) {
+                    assert_eq!(players.len(), 1);
+                    assert_eq!(players[0].self_id, "music");
+                    assert_eq!(players[0].origin.host.as_deref(), Some("office-pc"));
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.unwrap();
+
+        bridge.shutdown().await.unwrap();
+        assert!(hallway.player_manager().list_players().is_empty());
+        server.shutdown().await.unwrap();
+    }
+}
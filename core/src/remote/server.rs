@@ -0,0 +1,487 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Serving a [`LocalDriver`] over gRPC.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use futures::Stream;
+use log::{debug, error};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc};
+use tonic::{Request, Response, Status};
+
+use super::proto::driver_event::Event;
+use super::proto::firmware_update_progress::Progress;
+use super::proto::fsct_driver_server::{FsctDriver as FsctDriverService, FsctDriverServer};
+use super::proto::{self, DriverEvent, Empty};
+use super::{from_json, player_id, to_json, SESSION_HEADER};
+use crate::auth::{audit_control, AuthError, AuthPolicy, Credentials, Principal, Scope};
+use crate::definitions::PlaybackCommand;
+use crate::device_manager::{DeviceControl, DeviceEvent, ManagedDeviceId};
+use crate::orchestrator::DndScope;
+use crate::driver_middleware::InterceptedDriver;
+use crate::player_interface::PlayerInterface;
+use crate::player_manager::ManagedPlayerId;
+use crate::player_origin::PlayerOrigin;
+use crate::port_supervisor::PortError;
+use crate::rate_limit::{RateLimiter, API_BURST, API_REQUESTS_PER_SECOND};
+use crate::service::{spawn_service, ServiceHandle};
+use crate::validation::{validate_route_duration, validate_self_id, ValidatingInterceptor};
+use crate::{FsctDriver, LocalDriver};
+
+/// Largest request the server takes, leaving room for firmware images.
+const MAX_REQUEST_SIZE: usize = 64 * 1024 * 1024;
+
+/// Playback commands for players whose interface is attached remotely.
+type CommandSender = broadcast::Sender<(ManagedPlayerId, PlaybackCommand)>;
+
+/// Carries out commands by passing them to the remote driver that attached the interface.
+struct ForwardedInterface {
+    player_id: ManagedPlayerId,
+    commands: CommandSender,
+}
+
+#[async_trait]
+impl PlayerInterface for ForwardedInterface {
+    async fn execute(&self, command: PlaybackCommand) -> Result<(), Error> {
+        self.commands
+            .send((self.player_id, command))
+            .map(|_| ())
+            .map_err(|_| anyhow!("Remote driver of player {} is not connected", self.player_id))
+    }
+}
+
+/// gRPC service of a [`LocalDriver`].
+///
+/// Callers present bearer tokens in the `authorization` metadata; queries and the event feed take
+/// [`Scope::Read`], everything else [`Scope::Control`].
+///
+/// Players registered by a [`RemoteDriver`](super::RemoteDriver) are leased to its event feed: they are unregistered
+/// when the feed of the session they were registered in ends, e.g. because the client disconnected.
+///
+/// Calls from every address are limited to [`API_REQUESTS_PER_SECOND`], whether authenticated or not.
+/// Self ids are validated, and state updates pass a [`ValidatingInterceptor`] before reaching the driver.
+pub struct DriverServer {
+    driver: Arc<LocalDriver>,
+    updates: InterceptedDriver,
+    commands: CommandSender,
+    auth: AuthPolicy,
+    limiter: Arc<RateLimiter<String>>,
+    /// Players registered in each session with a running event feed.
+    sessions: Arc<Mutex<HashMap<String, Vec<ManagedPlayerId>>>>,
+}
+
+impl DriverServer {
+    /// Server rejecting every caller until given a policy, see [`DriverServer::with_auth_policy`].
+    pub fn new(driver: Arc<LocalDriver>) -> Self {
+        Self {
+            updates: InterceptedDriver::new(driver.clone()).with_interceptor(Arc::new(ValidatingInterceptor)),
+            driver,
+            commands: broadcast::channel(16).0,
+            auth: AuthPolicy::default(),
+            limiter: Arc::new(RateLimiter::new(API_BURST, API_REQUESTS_PER_SECOND)),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Limits who may use the driver, e.g. for servers reachable from the LAN.
+    pub fn with_auth_policy(mut self, auth: AuthPolicy) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    fn authorize<T>(&self, request: &Request<T>, required: Scope) -> Result<Principal, Status> {
+        // limited before authenticating, so that guessing tokens is limited as well
+        let client = request.remote_addr().map_or_else(|| "local".to_string(), |address| address.ip().to_string());
+        self.limiter.check(&client).map_err(|retry_after| {
+            Status::resource_exhausted(format!("Too many requests, retry in {} ms", retry_after.as_millis()))
+        })?;
+        let header = request.metadata().get("authorization").and_then(|value| value.to_str().ok());
+        self.auth.authorize(&Credentials::from_authorization_header(header), required)
+            .map_err(|e| match e {
+                AuthError::Forbidden { .. } => Status::permission_denied(e.to_string()),
+                AuthError::Unauthenticated | AuthError::InvalidCredentials => Status::unauthenticated(e.to_string()),
+            })
+    }
+}
+
+fn session<T>(request: &Request<T>) -> Option<String> {
+    request.metadata().get(SESSION_HEADER).and_then(|value| value.to_str().ok()).map(str::to_string)
+}
+
+fn snapshot(driver: &LocalDriver) -> DriverEvent {
+    event(Event::Snapshot(proto::Snapshot {
+        preferred_player: driver.get_preferred_player().map(|id| id.get()),
+        device_limits_json: to_json(&driver.get_device_limits()),
+    }))
+}
+
+fn status(e: Error) -> Status {
+    Status::failed_precondition(e.to_string())
+}
+
+fn device_id(id: &str) -> Result<ManagedDeviceId, Status> {
+    id.parse().map_err(|_| Status::invalid_argument(format!("Invalid device id: {}", id)))
+}
+
+fn event(event: Event) -> DriverEvent {
+    DriverEvent { event: Some(event) }
+}
+
+#[tonic::async_trait]
+impl FsctDriverService for DriverServer {
+    async fn register_player(&self, request: Request<proto::RegisterPlayerRequest>)
+                             -> Result<Response<proto::PlayerId>, Status> {
+        let principal = self.authorize(&request, Scope::Control)?;
+        let session = session(&request);
+        let peer = request.remote_addr().map(|address| address.ip()).filter(|ip| !ip.is_loopback());
+        let request = request.into_inner();
+        validate_self_id(&request.self_id).map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let origin = PlayerOrigin::new(request.origin_host.or_else(|| peer.map(|ip| ip.to_string())),
+                                       request.origin_user);
+        audit_control(&principal, &format!("registration of player {}", request.self_id));
+        let player_id = self.driver.register_player_with_origin(request.self_id, origin).await.map_err(status)?;
+        if let Some(session) = session && let Some(players) = self.sessions.lock().unwrap().get_mut(&session) {
+            players.push(player_id);
+        }
+        Ok(Response::new(proto::PlayerId { player_id: player_id.get() }))
+    }
+
+    async fn unregister_player(&self, request: Request<proto::PlayerId>) -> Result<Response<Empty>, Status> {
+        self.authorize(&request, Scope::Control)?;
+        let player_id = player_id(request.into_inner().player_id)?;
+        self.driver.unregister_player(player_id).await.map_err(status)?;
+        for players in self.sessions.lock().unwrap().values_mut() {
+            players.retain(|id| *id != player_id);
+        }
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn assign_player_to_device(&self, request: Request<proto::PlayerDevice>) -> Result<Response<Empty>, Status> {
+        let principal = self.authorize(&request, Scope::Control)?;
+        let request = request.into_inner();
+        let (player_id, device_id) = (player_id(request.player_id)?, device_id(&request.device_id)?);
+        audit_control(&principal, &format!("assignment of player {} to device {}", player_id, device_id));
+        self.driver.assign_player_to_device(player_id, device_id).await.map_err(status)?;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn unassign_player_from_device(&self, request: Request<proto::PlayerDevice>)
+                                         -> Result<Response<Empty>, Status> {
+        let principal = self.authorize(&request, Scope::Control)?;
+        let request = request.into_inner();
+        let (player_id, device_id) = (player_id(request.player_id)?, device_id(&request.device_id)?);
+        audit_control(&principal, &format!("unassignment of player {} from device {}", player_id, device_id));
+        self.driver.unassign_player_from_device(player_id, device_id).await.map_err(status)?;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn update_player_state(&self, request: Request<proto::PlayerUpdate>) -> Result<Response<Empty>, Status> {
+        self.authorize(&request, Scope::Control)?;
+        let request = request.into_inner();
+        let player_id = player_id(request.player_id)?;
+        self.updates.update_player_state(player_id, from_json(&request.json)?).await.map_err(status)?;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn update_player_status(&self, request: Request<proto::PlayerUpdate>) -> Result<Response<Empty>, Status> {
+        self.authorize(&request, Scope::Control)?;
+        let request = request.into_inner();
+        let player_id = player_id(request.player_id)?;
+        self.updates.update_player_status(player_id, from_json(&request.json)?).await.map_err(status)?;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn update_player_timeline(&self, request: Request<proto::PlayerUpdate>) -> Result<Response<Empty>, Status> {
+        self.authorize(&request, Scope::Control)?;
+        let request = request.into_inner();
+        let player_id = player_id(request.player_id)?;
+        self.updates.update_player_timeline(player_id, from_json(&request.json)?).await.map_err(status)?;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn update_player_volume(&self, request: Request<proto::PlayerUpdate>) -> Result<Response<Empty>, Status> {
+        self.authorize(&request, Scope::Control)?;
+        let request = request.into_inner();
+        let player_id = player_id(request.player_id)?;
+        self.updates.update_player_volume(player_id, from_json(&request.json)?).await.map_err(status)?;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn update_player_modes(&self, request: Request<proto::PlayerUpdate>) -> Result<Response<Empty>, Status> {
+        self.authorize(&request, Scope::Control)?;
+        let request = request.into_inner();
+        let player_id = player_id(request.player_id)?;
+        self.updates.update_player_modes(player_id, from_json(&request.json)?).await.map_err(status)?;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn update_player_metadata(&self, request: Request<proto::MetadataUpdate>)
+                                    -> Result<Response<Empty>, Status> {
+        self.authorize(&request, Scope::Control)?;
+        let request = request.into_inner();
+        let player_id = player_id(request.player_id)?;
+        let metadata_id = from_json(&request.metadata_json)?;
+        self.updates.update_player_metadata(player_id, metadata_id, request.text).await.map_err(status)?;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn set_preferred_player(&self, request: Request<proto::PreferredPlayer>) -> Result<Response<Empty>, Status> {
+        let principal = self.authorize(&request, Scope::Control)?;
+        let preferred = request.into_inner().player_id.map(player_id).transpose()?;
+        audit_control(&principal, &format!("preference of player {:?}", preferred.map(|id| id.get())));
+        self.driver.set_preferred_player(preferred).map_err(status)?;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn set_player_interface(&self, request: Request<proto::PlayerInterfaceAttachment>)
+                                  -> Result<Response<Empty>, Status> {
+        self.authorize(&request, Scope::Control)?;
+        let request = request.into_inner();
+        let player_id = player_id(request.player_id)?;
+        let interface = request.attached.then(|| {
+            Arc::new(ForwardedInterface { player_id, commands: self.commands.clone() }) as Arc<dyn PlayerInterface>
+        });
+        self.driver.set_player_interface(player_id, interface).map_err(status)?;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn wait_applied(&self, request: Request<Empty>) -> Result<Response<Empty>, Status> {
+        self.authorize(&request, Scope::Read)?;
+        self.driver.wait_applied().await.map_err(status)?;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn list_players(&self, request: Request<Empty>) -> Result<Response<proto::JsonReply>, Status> {
+        self.authorize(&request, Scope::Read)?;
+        let players = self.driver.player_manager().list_players();
+        Ok(Response::new(proto::JsonReply { json: to_json(&players) }))
+    }
+
+    async fn list_devices(&self, request: Request<Empty>) -> Result<Response<proto::JsonReply>, Status> {
+        self.authorize(&request, Scope::Read)?;
+        let devices = self.driver.list_devices().await.map_err(status)?;
+        Ok(Response::new(proto::JsonReply { json: to_json(&devices) }))
+    }
+
+    async fn get_device_descriptors(&self, request: Request<proto::DeviceId>)
+                                    -> Result<Response<proto::JsonReply>, Status> {
+        self.authorize(&request, Scope::Read)?;
+        let device_id = device_id(&request.into_inner().device_id)?;
+        let descriptors = self.driver.device_manager().descriptor_dump(device_id)
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        Ok(Response::new(proto::JsonReply { json: to_json(&descriptors) }))
+    }
+
+    async fn restart_port(&self, request: Request<proto::PortName>) -> Result<Response<Empty>, Status> {
+        let principal = self.authorize(&request, Scope::Control)?;
+        let name = request.into_inner().name;
+        audit_control(&principal, &format!("restart of port {}", name));
+        self.driver.restart_port(&name).await.map_err(|e| match e {
+            PortError::UnknownPort(_) => Status::not_found(e.to_string()),
+            e => Status::failed_precondition(e.to_string()),
+        })?;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn list_ports(&self, request: Request<Empty>) -> Result<Response<proto::JsonReply>, Status> {
+        self.authorize(&request, Scope::Read)?;
+        Ok(Response::new(proto::JsonReply { json: to_json(&self.driver.list_ports()) }))
+    }
+
+    async fn get_readiness(&self, request: Request<Empty>) -> Result<Response<proto::JsonReply>, Status> {
+        self.authorize(&request, Scope::Read)?;
+        Ok(Response::new(proto::JsonReply { json: to_json(&self.driver.readiness()) }))
+    }
+
+    type UpdateFirmwareStream = Pin<Box<dyn Stream<Item = Result<proto::FirmwareUpdateProgress, Status>> + Send>>;
+
+    async fn update_firmware(&self, request: Request<proto::FirmwareImage>)
+                             -> Result<Response<Self::UpdateFirmwareStream>, Status> {
+        let principal = self.authorize(&request, Scope::Control)?;
+        let request = request.into_inner();
+        let device_id = device_id(&request.device_id)?;
+        audit_control(&principal, &format!("firmware update of device {}", device_id));
+        let driver = self.driver.clone();
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let progress_tx = tx.clone();
+            let result = driver.update_firmware(device_id, &request.image, move |progress| {
+                let progress = Progress::ProgressJson(to_json(&progress));
+                let _ = progress_tx.send(Ok(proto::FirmwareUpdateProgress { progress: Some(progress) }));
+            }).await;
+            let last = result
+                .map(|report| proto::FirmwareUpdateProgress { progress: Some(Progress::ReportJson(to_json(&report))) })
+                .map_err(status);
+            let _ = tx.send(last);
+        });
+        let stream = futures::stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|item| (item, rx)) });
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn set_dnd(&self, request: Request<proto::DndSwitch>) -> Result<Response<Empty>, Status> {
+        let principal = self.authorize(&request, Scope::Control)?;
+        let request = request.into_inner();
+        let scope = match request.device_id {
+            Some(device) => DndScope::Device(device_id(&device)?),
+            None => DndScope::Global,
+        };
+        audit_control(&principal, &format!("do-not-disturb {:?} turned {}", scope,
+                                           if request.enabled { "on" } else { "off" }));
+        let control = self.driver.control().map_err(status)?;
+        control.set_dnd(scope, request.enabled).await.map_err(status)?;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn get_dnd(&self, request: Request<Empty>) -> Result<Response<proto::JsonReply>, Status> {
+        self.authorize(&request, Scope::Read)?;
+        let dnd = self.driver.control().map_err(status)?.dnd().await.map_err(status)?;
+        Ok(Response::new(proto::JsonReply { json: to_json(&dnd) }))
+    }
+
+    async fn force_route(&self, request: Request<proto::RouteOverride>) -> Result<Response<Empty>, Status> {
+        let principal = self.authorize(&request, Scope::Control)?;
+        let request = request.into_inner();
+        let (player_id, device_id) = (player_id(request.player_id)?, device_id(&request.device_id)?);
+        let duration = request.duration_secs.map(Duration::try_from_secs_f64).transpose()
+            .map_err(|e| Status::invalid_argument(format!("Invalid duration: {}", e)))?;
+        if let Some(duration) = duration {
+            validate_route_duration(duration).map_err(|e| Status::invalid_argument(e.to_string()))?;
+        }
+        audit_control(&principal, &format!("route override of device {} to player {}", device_id, player_id));
+        let control = self.driver.control().map_err(status)?;
+        control.force_route(player_id, device_id, duration).await.map_err(status)?;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn clear_route(&self, request: Request<proto::DeviceId>) -> Result<Response<proto::RouteCleared>, Status> {
+        let principal = self.authorize(&request, Scope::Control)?;
+        let device_id = device_id(&request.into_inner().device_id)?;
+        audit_control(&principal, &format!("removal of the route override of device {}", device_id));
+        let cleared = self.driver.control().map_err(status)?.clear_route(device_id).await.map_err(status)?;
+        Ok(Response::new(proto::RouteCleared { cleared }))
+    }
+
+    async fn list_routes(&self, request: Request<Empty>) -> Result<Response<proto::JsonReply>, Status> {
+        self.authorize(&request, Scope::Read)?;
+        let routes = self.driver.control().map_err(status)?.routes().await.map_err(status)?;
+        Ok(Response::new(proto::JsonReply { json: to_json(&routes) }))
+    }
+
+    async fn get_usage_report(&self, request: Request<Empty>) -> Result<Response<proto::JsonReply>, Status> {
+        self.authorize(&request, Scope::Read)?;
+        let stats = self.driver.usage_stats()
+            .ok_or_else(|| Status::failed_precondition("The service collects no usage statistics"))?;
+        Ok(Response::new(proto::JsonReply { json: to_json(&stats.report()) }))
+    }
+
+    type SubscribeStream = Pin<Box<dyn Stream<Item = Result<DriverEvent, Status>> + Send>>;
+
+    async fn subscribe(&self, request: Request<Empty>) -> Result<Response<Self::SubscribeStream>, Status> {
+        self.authorize(&request, Scope::Read)?;
+        let session = session(&request);
+        if let Some(session) = &session {
+            self.sessions.lock().unwrap().entry(session.clone()).or_default();
+        }
+        let sessions = self.sessions.clone();
+        // subscribe before taking the snapshot so that no change in between is missed
+        let mut player_events = self.driver.subscribe_player_events();
+        let mut device_events = self.driver.device_manager().subscribe();
+        let mut commands = self.commands.subscribe();
+        let first = snapshot(&self.driver);
+        let driver = self.driver.clone();
+
+        let (tx, rx) = mpsc::channel(64);
+        let lease_driver = driver.clone();
+        let feed = async move {
+            let mut pending = vec![first];
+            loop {
+                for event in pending.drain(..) {
+                    if tx.send(Ok(event)).await.is_err() {
+                        return;
+                    }
+                }
+                tokio::select! {
+                    _ = tx.closed() => return,
+                    received = player_events.recv() => match received {
+                        Ok(player_event) => pending.push(event(Event::PlayerEventJson(to_json(&player_event)))),
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            debug!("Remote subscriber lagged by {} player events", skipped);
+                            pending.push(snapshot(&driver));
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return,
+                    },
+                    received = device_events.recv() => match received {
+                        Ok(device_event) => {
+                            let devices_changed = matches!(device_event, DeviceEvent::Added(_) | DeviceEvent::Removed(_));
+                            pending.push(event(Event::DeviceEventJson(to_json(&device_event))));
+                            if devices_changed {
+                                pending.push(event(Event::DeviceLimitsJson(to_json(&driver.get_device_limits()))));
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Lagged(_)) => {
+                            pending.push(event(Event::DeviceLimitsJson(to_json(&driver.get_device_limits()))));
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return,
+                    },
+                    Ok((player_id, command)) = commands.recv() => {
+                        pending.push(event(Event::PlayerCommand(proto::PlayerCommand {
+                            player_id: player_id.get(),
+                            command_json: to_json(&command),
+                        })));
+                    }
+                }
+            }
+        };
+        tokio::spawn(async move {
+            feed.await;
+            let Some(session) = session else { return };
+            let players = sessions.lock().unwrap().remove(&session).unwrap_or_default();
+            for player_id in players {
+                debug!("Unregistering player {} of the ended session {}", player_id, session);
+                if let Err(e) = lease_driver.unregister_player(player_id).await {
+                    debug!("Failed to unregister player {}: {}", player_id, e);
+                }
+            }
+        });
+        let stream = futures::stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|event| (event, rx)) });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// Serves the driver on connections accepted by `listener`, until the service is stopped.
+pub fn run_driver_server(server: DriverServer, listener: TcpListener) -> ServiceHandle {
+    spawn_service(move |mut stop| async move {
+        let incoming = futures::stream::unfold(listener, |listener| async move {
+            Some((listener.accept().await.map(|(stream, _)| stream), listener))
+        });
+        let result = tonic::transport::Server::builder()
+            .add_service(FsctDriverServer::new(server).max_decoding_message_size(MAX_REQUEST_SIZE))
+            .serve_with_incoming_shutdown(incoming, async move { stop.signaled().await })
+            .await;
+        if let Err(e) = result {
+            error!("Driver server failed: {}", e);
+        }
+    })
+}
@@ -0,0 +1,148 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! mDNS (zeroconf) advertisement of a driver server, so companion apps and remote frontends find hosts on the LAN.
+//!
+//! Hosts are advertised as [`FSCT_HOST_SERVICE_TYPE`] instances with the TXT properties `version` (of the host),
+//! `protocol` (`grpc`), `api` (the package of `fsct_driver.proto`, `fsct.driver.v1`) and `capabilities`
+//! (comma separated, see [`host_capabilities`]). Hosts serving events over WebSocket (`fsct_core::ipc::ws`) to the
+//! LAN add its port as `ws_port`.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Error;
+use log::{debug, warn};
+use mdns_sd::{ServiceDaemon, ServiceInfo};
+
+use crate::self_id::host_name;
+use crate::service::{spawn_service, ServiceHandle};
+
+/// DNS-SD service type of FSCT hosts.
+pub const FSCT_HOST_SERVICE_TYPE: &str = "_fsct-host._tcp.local.";
+
+/// What a host advertises about its driver server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advertisement {
+    /// Instance name shown by browsers, e.g. `FSCT host on studio-pc`.
+    pub instance_name: String,
+    /// Port of the driver server.
+    pub port: u16,
+    pub capabilities: Vec<String>,
+    /// Port of the WebSocket server, if the host serves one to the LAN.
+    pub ws_port: Option<u16>,
+}
+
+impl Advertisement {
+    /// Advertisement of a driver server on `port`, named after the host, with the capabilities of this build.
+    pub fn new(port: u16) -> Self {
+        Self {
+            instance_name: format!("FSCT host on {}", host_name()),
+            port,
+            capabilities: host_capabilities(),
+            ws_port: None,
+        }
+    }
+
+    /// Advertises the WebSocket server of the host on `port` as well.
+    pub fn with_ws_port(mut self, port: u16) -> Self {
+        self.ws_port = Some(port);
+        self
+    }
+
+    /// Adds a capability of the service, beyond the ones of this build.
+    pub fn with_capability(mut self, capability: impl Into<String>) -> Self {
+        self.capabilities.push(capability.into());
+        self
+    }
+
+    fn txt_properties(&self) -> HashMap<String, String> {
+        let mut properties = HashMap::from([
+            ("version".to_string(), env!("CARGO_PKG_VERSION").to_string()),
+            ("protocol".to_string(), "grpc".to_string()),
+            ("api".to_string(), "fsct.driver.v1".to_string()),
+            ("capabilities".to_string(), self.capabilities.join(",")),
+        ]);
+        if let Some(port) = self.ws_port {
+            properties.insert("ws_port".to_string(), port.to_string());
+        }
+        properties
+    }
+}
+
+/// Features of this build clients may rely on, named like the crate features.
+pub fn host_capabilities() -> Vec<String> {
+    let features = [
+        ("usb", cfg!(feature = "usb")),
+        ("network", cfg!(feature = "network")),
+        ("config", cfg!(feature = "config")),
+        ("storage", cfg!(feature = "storage")),
+        ("usage-stats", cfg!(feature = "usage-stats")),
+        ("aux-content", cfg!(feature = "aux-content")),
+        ("self-update", cfg!(feature = "self-update")),
+        ("vendor-requests", cfg!(feature = "vendor-requests")),
+    ];
+    features.into_iter().filter(|(_, enabled)| *enabled).map(|(name, _)| name.to_string()).collect()
+}
+
+/// Advertises the driver server on all interfaces, until the service is stopped; browsers are told the host is
+/// gone then.
+pub fn run_zeroconf_advertisement(advertisement: Advertisement) -> Result<ServiceHandle, Error> {
+    let daemon = ServiceDaemon::new()?;
+    let info = ServiceInfo::new(FSCT_HOST_SERVICE_TYPE, &advertisement.instance_name,
+                                &format!("{}.local.", host_name()), (), advertisement.port,
+                                advertisement.txt_properties())?
+        .enable_addr_auto();
+    let fullname = info.get_fullname().to_string();
+    daemon.register(info)?;
+    debug!("Advertising {} on port {}", fullname, advertisement.port);
+
+    Ok(spawn_service(move |mut stop| async move {
+        stop.signaled().await;
+        match daemon.unregister(&fullname) {
+            // wait for the goodbye packets to be sent before the daemon stops
+            Ok(status) => {
+                let _ = tokio::task::spawn_blocking(move || status.recv_timeout(Duration::from_secs(1))).await;
+            }
+            Err(e) => warn!("Failed to withdraw advertisement of {}: {}", fullname, e),
+        }
+        if let Err(e) = daemon.shutdown() {
+            warn!("Failed to stop mDNS daemon: {}", e);
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advertisement_describes_host_in_txt_properties() {
+        let advertisement = Advertisement::new(50151).with_capability("players");
+        assert!(advertisement.instance_name.starts_with("FSCT host on "));
+        assert!(!host_name().contains('.'));
+
+        let txt = advertisement.txt_properties();
+        assert_eq!(txt["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(txt["protocol"], "grpc");
+        assert_eq!(txt["api"], "fsct.driver.v1");
+        assert!(txt["capabilities"].ends_with("players"));
+        assert_eq!(txt["capabilities"].contains("usb"), cfg!(feature = "usb"));
+        assert!(!txt.contains_key("ws_port"));
+        assert_eq!(advertisement.with_ws_port(50152).txt_properties()["ws_port"], "50152");
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Self ids of the players registered by host services.
+//!
+//! Players of the native watchers are named `<prefix>-<source>`, e.g. `native-windows-gsmtc-Spotify.exe`. With
+//! several services registering players with one driver (bridges, several users of one machine), the machine and
+//! instance distinguish them: `native@studio-pc#2-windows-gsmtc-Spotify.exe`. Ids stay the same from run to run, so
+//! preferences and assignments persisted for a player keep matching it.
+
+use serde::{Deserialize, Serialize};
+
+/// Prefix of self ids when none is configured.
+pub const DEFAULT_SELF_ID_PREFIX: &str = "native";
+
+/// Value of [`SelfIdNamespace::machine`] standing for the name of the host.
+pub const HOST_MACHINE: &str = "auto";
+
+/// What self ids of the players of a service start with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SelfIdNamespace {
+    pub prefix: String,
+    /// Machine the players run on; `"auto"` for the host name, resolved by [`SelfIdNamespace::resolved`].
+    pub machine: Option<String>,
+    /// Instance of the service on the machine, for several services running side by side.
+    pub instance: Option<String>,
+}
+
+impl Default for SelfIdNamespace {
+    fn default() -> Self {
+        Self { prefix: DEFAULT_SELF_ID_PREFIX.to_string(), machine: None, instance: None }
+    }
+}
+
+impl SelfIdNamespace {
+    pub fn with_machine(mut self, machine: impl Into<String>) -> Self {
+        self.machine = Some(machine.into());
+        self
+    }
+
+    pub fn with_instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// The namespace with an `"auto"` machine replaced by the host name.
+    #[cfg(any(feature = "config", feature = "zeroconf"))]
+    pub fn resolved(mut self) -> Self {
+        if self.machine.as_deref() == Some(HOST_MACHINE) {
+            self.machine = Some(host_name());
+        }
+        self
+    }
+
+    /// Self id of the player of `source`, e.g. `windows-gsmtc-Spotify.exe`.
+    pub fn self_id(&self, source: &str) -> String {
+        let mut id = self.prefix.clone();
+        if let Some(machine) = self.machine.as_deref().filter(|machine| !machine.is_empty()) {
+            id.push('@');
+            id.push_str(machine);
+        }
+        if let Some(instance) = self.instance.as_deref().filter(|instance| !instance.is_empty()) {
+            id.push('#');
+            id.push_str(instance);
+        }
+        id.push('-');
+        id.push_str(source);
+        id
+    }
+}
+
+/// Name of the host as a DNS label: letters, digits and hyphens.
+#[cfg(any(feature = "config", feature = "zeroconf"))]
+pub(crate) fn host_name() -> String {
+    let name: String = gethostname::gethostname().to_string_lossy()
+        .split('.').next().unwrap_or_default()
+        .chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let name = name.trim_matches('-');
+    if name.is_empty() { "fsct-host".to_string() } else { name.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn self_ids_carry_machine_and_instance() {
+        let source = "windows-gsmtc-Spotify.exe";
+        assert_eq!(SelfIdNamespace::default().self_id(source), "native-windows-gsmtc-Spotify.exe");
+        let namespace = SelfIdNamespace::default().with_machine("studio-pc").with_instance("2");
+        assert_eq!(namespace.self_id(source), "native@studio-pc#2-windows-gsmtc-Spotify.exe");
+        let namespace = SelfIdNamespace { prefix: "office".to_string(), ..Default::default() }.with_instance("2");
+        assert_eq!(namespace.self_id(source), "office#2-windows-gsmtc-Spotify.exe");
+    }
+}
@@ -0,0 +1,155 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Helpers for the stable JSON representation of FSCT types.
+//!
+//! Durations are encoded as fractional seconds and points in time as integer milliseconds since the Unix epoch,
+//! so that clients in any language can consume them without custom parsing.
+
+/// (De)serializes a `Duration` as fractional seconds, e.g. `90.5`.
+pub mod duration_secs {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::de::Error;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(duration.as_secs_f64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let secs = f64::deserialize(deserializer)?;
+        Duration::try_from_secs_f64(secs).map_err(D::Error::custom)
+    }
+}
+
+/// (De)serializes an `Option<Duration>` as fractional seconds or `null`.
+pub mod optional_duration_secs {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::de::Error;
+
+    pub fn serialize<S: Serializer>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => serializer.serialize_some(&duration.as_secs_f64()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        Option::<f64>::deserialize(deserializer)?
+            .map(|secs| Duration::try_from_secs_f64(secs).map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+/// (De)serializes a `SystemTime` as integer milliseconds since the Unix epoch.
+pub mod system_time_millis {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::ser::Error;
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        let since_epoch = time.duration_since(UNIX_EPOCH).map_err(S::Error::custom)?;
+        serializer.serialize_u64(since_epoch.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        let millis = u64::deserialize(deserializer)?;
+        Ok(UNIX_EPOCH + Duration::from_millis(millis))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use serde_json::json;
+
+    use crate::definitions::{
+        DeviceErrorReport, FsctDeviceErrorCode, FsctStatus, TimelineInfo, UsbRequestTimeouts, VolumeInfo,
+        PlaybackModes, RepeatMode,
+    };
+    use crate::device_manager::DeviceEvent;
+    use crate::player_events::PlayerEvent;
+    use crate::player_state::{PlayerState, TrackMetadata};
+
+    #[test]
+    fn player_state_has_stable_json_representation() {
+        let state = PlayerState {
+            status: FsctStatus::Playing,
+            timeline: Some(TimelineInfo {
+                position: Duration::from_millis(1500),
+                update_time: UNIX_EPOCH + Duration::from_millis(1_700_000_000_123),
+                duration: Some(Duration::from_secs(200)),
+                rate: 1.0,
+            }),
+            texts: TrackMetadata { title: Some("Title".into()), ..Default::default() },
+            volume: Some(VolumeInfo { level: 40, muted: true }),
+            modes: PlaybackModes { shuffle: Some(true), repeat: None },
+        };
+        let expected = json!({
+            "status": "playing",
+            "timeline": { "position": 1.5, "update_time": 1_700_000_000_123u64, "duration": 200.0, "rate": 1.0 },
+            "texts": { "title": "Title", "artist": null, "album": null, "genre": null, "year": null, "composer": null,
+                       "next_title": null, "next_artist": null },
+            "volume": { "level": 40, "muted": true },
+            "modes": { "shuffle": true, "repeat": null },
+        });
+        assert_eq!(serde_json::to_value(&state).unwrap(), expected);
+        assert_eq!(serde_json::from_value::<PlayerState>(expected).unwrap(), state);
+        // states of producers that don't know the queue texts, the volume or the modes yet
+        let without_queue = json!({ "status": "playing", "timeline": null, "texts": { "title": "Title" } });
+        let without_queue = serde_json::from_value::<PlayerState>(without_queue).unwrap();
+        assert_eq!((without_queue.texts, without_queue.volume), (state.texts, None));
+        assert_eq!(without_queue.modes, PlaybackModes::default());
+        assert_eq!(serde_json::to_value(RepeatMode::Track).unwrap(), json!("track"));
+    }
+
+    #[test]
+    fn device_events_keep_flat_representation() {
+        let device_id = uuid::Uuid::nil();
+        assert_eq!(
+            serde_json::to_value(DeviceEvent::Added(device_id)).unwrap(),
+            json!({ "type": "added", "device_id": device_id })
+        );
+        let error = DeviceErrorReport { code: FsctDeviceErrorCode::Other(0x8001), detail: 0, count: 1 };
+        let value = json!({
+            "type": "device_error", "device_id": device_id, "error": { "code": { "other": 0x8001 }, "detail": 0, "count": 1 }
+        });
+        assert_eq!(serde_json::to_value(DeviceEvent::DeviceError { device_id, error }).unwrap(), value);
+        assert!(matches!(serde_json::from_value(value).unwrap(), DeviceEvent::DeviceError { error: e, .. } if e == error));
+    }
+
+    #[test]
+    fn player_events_are_tagged_with_type() {
+        let event = PlayerEvent::StatusUpdated { player_id: NonZeroU32::new(3).unwrap(), status: FsctStatus::Paused };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({ "type": "status_updated", "player_id": 3, "status": "paused" })
+        );
+    }
+
+    #[test]
+    fn request_timeouts_fill_missing_fields_with_defaults() {
+        let timeouts: UsbRequestTimeouts = serde_json::from_value(json!({ "text": 2.5 })).unwrap();
+        assert_eq!(timeouts, UsbRequestTimeouts { text: Duration::from_millis(2500), ..Default::default() });
+    }
+}
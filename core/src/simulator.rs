@@ -0,0 +1,540 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Simulated FSCT devices of configurable descriptor shapes, for testing routing and text truncation against a
+//! matrix of device models without hardware, e.g. in CI.
+//!
+//! Devices are described by descriptor profiles, loaded from TOML:
+//!
+//! ```toml
+//! [[profiles]]
+//! name = "ucs2-short-titles"
+//! # "utf8" (default), "utf16", "ucs2" or "utf32"
+//! encoding = "ucs2"
+//! # texts shown and their maximum lengths in bytes
+//! texts = { current_title = 16, current_author = 16, queue_title = 16 }
+//! # progress bar, with live mode for content without a duration; status; volume and shuffle/repeat displays
+//! progress = false
+//! live_progress = false
+//! status = true
+//! volume = false
+//! playback_modes = false
+//! # firmware updates; the image is applied when the device is restarted, bumping the version it reports
+//! firmware_update = false
+//! # FSCT protocol version, the latest by default; volume, shuffle/repeat and firmware updates need 2
+//! protocol = 2
+//! # identity the quirks and the device filter see
+//! vendor_id = 0x31c0
+//! product_id = 0x0001
+//! ```
+//!
+//! [`DeviceManager::attach_simulated_device`](crate::DeviceManager::attach_simulated_device) brings a device of
+//! the profile up like a USB device; what the host sends to it can be read from its [`SimulatedDisplay`].
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use serde::Deserialize;
+
+#[cfg(feature = "vendor-requests")]
+use crate::definitions::VendorRequest;
+use crate::definitions::{FsctExtendedFunctionality, FsctFunctionality, FsctNotification, FsctStatus, FsctTextEncoding,
+                         FsctTextMetadata, UsbRequestTimeouts};
+use crate::descriptor_dump::DeviceDescriptorDump;
+use crate::device_manager::ManagedDeviceId;
+use crate::usb::descriptor_utils::{dump_fsct_descriptor_set, parse_fsct_descriptor_set};
+use crate::usb::descriptors::{FSCT_EXTENDED_FUNCTIONALITY_DESCRIPTOR_ID, FSCT_FUNCTIONALITY_DESCRIPTOR_ID,
+                              FSCT_TEXT_METADATA_DESCRIPTOR_ID};
+use crate::usb::dfu::crc32;
+use crate::usb::errors::FsctDeviceError;
+use crate::usb::fsct_device::FsctDevice;
+use crate::usb::protocol::{FsctProtocol, FSCT_LATEST_PROTOCOL_VERSION};
+use crate::usb::requests;
+
+/// Shape of a simulated device: what its FSCT descriptors announce.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DescriptorProfile {
+    pub name: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// Device release number, BCD like bcdDevice.
+    pub firmware_version: u16,
+    pub encoding: FsctTextEncoding,
+    /// Texts shown by the device, with their maximum lengths in bytes.
+    pub texts: HashMap<FsctTextMetadata, u16>,
+    pub status: bool,
+    pub progress: bool,
+    pub live_progress: bool,
+    pub volume: bool,
+    pub playback_modes: bool,
+    pub firmware_update: bool,
+    /// FSCT protocol version the device announces.
+    pub protocol: u8,
+}
+
+impl Default for DescriptorProfile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            vendor_id: 0,
+            product_id: 0,
+            firmware_version: 0x0100,
+            encoding: FsctTextEncoding::Utf8,
+            texts: HashMap::new(),
+            status: true,
+            progress: true,
+            live_progress: false,
+            volume: false,
+            playback_modes: false,
+            firmware_update: false,
+            protocol: FSCT_LATEST_PROTOCOL_VERSION,
+        }
+    }
+}
+
+impl DescriptorProfile {
+    pub fn functionality(&self) -> FsctFunctionality {
+        let mut functionality = FsctFunctionality::empty();
+        let queue = |text: &FsctTextMetadata| *text as u8 >= FsctTextMetadata::QueueTitle as u8;
+        functionality.set(FsctFunctionality::CurrentPlaybackMetadata, self.texts.keys().any(|text| !queue(text)));
+        functionality.set(FsctFunctionality::PlaybackQueueMetadata, self.texts.keys().any(queue));
+        functionality.set(FsctFunctionality::CurrentPlaybackStatus, self.status);
+        functionality.set(FsctFunctionality::CurrentPlaybackProgress, self.progress);
+        functionality.set(FsctFunctionality::LiveProgress, self.progress && self.live_progress);
+        functionality
+    }
+
+    /// FSCT functionality descriptor set of the device, as it would be read from its FSCT interface.
+    pub fn descriptor_set(&self) -> Vec<u8> {
+        let mut texts: Vec<_> = self.texts.iter().map(|(text, max_length)| (*text, *max_length)).collect();
+        texts.sort_by_key(|(text, _)| *text as u8);
+        let mut subordinate = Vec::new();
+        if !texts.is_empty() {
+            subordinate.extend([(3 + 3 * texts.len()) as u8, FSCT_TEXT_METADATA_DESCRIPTOR_ID, self.encoding as u8]);
+            for (text, max_length) in texts {
+                subordinate.push(text as u8);
+                subordinate.extend(max_length.to_le_bytes());
+            }
+        }
+        let mut extended = FsctExtendedFunctionality::empty();
+        extended.set(FsctExtendedFunctionality::Volume, self.volume);
+        extended.set(FsctExtendedFunctionality::PlaybackModes, self.playback_modes);
+        extended.set(FsctExtendedFunctionality::FirmwareUpdate, self.firmware_update);
+        if !extended.is_empty() {
+            subordinate.extend([3, FSCT_EXTENDED_FUNCTIONALITY_DESCRIPTOR_ID, extended.bits()]);
+        }
+        let total_length = (5 + subordinate.len()) as u16;
+        let mut descriptor_set = vec![5, FSCT_FUNCTIONALITY_DESCRIPTOR_ID];
+        descriptor_set.extend(total_length.to_le_bytes());
+        descriptor_set.push(self.functionality().bits());
+        descriptor_set.extend(subordinate);
+        descriptor_set
+    }
+
+    /// Device initialized from the descriptors of the profile, like a USB device from those it announces.
+    pub(crate) fn create_device(&self) -> Result<(FsctDevice, Arc<SimulatedDisplay>), anyhow::Error> {
+        let descriptor_set = self.descriptor_set();
+        let descriptors = parse_fsct_descriptor_set(&descriptor_set)
+            .map_err(|e| anyhow::anyhow!("Invalid descriptors of profile {}: {:?}", self.name, e))?;
+        let protocol = FsctProtocol::negotiate(self.protocol)
+            .with_context(|| format!("Invalid protocol of profile {}", self.name))?;
+        let display = Arc::new(SimulatedDisplay::new(self.encoding, self.firmware_version));
+        let mut device = FsctDevice::new(SimulatedInterface::new(display.clone()));
+        device.set_protocol(protocol);
+        let fsct = dump_fsct_descriptor_set(&descriptor_set);
+        device.set_descriptor_dump(DeviceDescriptorDump { bos: Vec::new(), fsct });
+        device.init(&descriptors);
+        Ok((device, display))
+    }
+}
+
+/// Descriptor profiles of a TOML file, see the [module documentation](self).
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DescriptorProfiles {
+    pub profiles: Vec<DescriptorProfile>,
+}
+
+impl DescriptorProfiles {
+    pub fn parse(toml: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(toml)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, anyhow::Error> {
+        let toml = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read descriptor profiles {}", path.display()))?;
+        Self::parse(&toml).with_context(|| format!("Invalid descriptor profiles {}", path.display()))
+    }
+}
+
+/// Progress as last sent to a simulated device.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulatedProgress {
+    /// Duration in seconds; 0 in live mode.
+    pub duration: u32,
+    /// Position in milliseconds.
+    pub position: i32,
+    pub rate: f32,
+}
+
+#[derive(Default)]
+struct DisplayState {
+    enabled: bool,
+    status: Option<FsctStatus>,
+    progress: Option<SimulatedProgress>,
+    texts: HashMap<FsctTextMetadata, Vec<u8>>,
+    volume: Option<u16>,
+    playback_modes: Option<u16>,
+    notifications: Vec<FsctNotification>,
+    firmware_version: u16,
+    /// Firmware image being received, with its announced length.
+    firmware_upload: Option<(usize, Vec<u8>)>,
+    firmware_image: Option<Vec<u8>>,
+    /// An image was applied and the device has yet to restart into it.
+    restart_pending: bool,
+}
+
+/// What a simulated device shows: the requests the host sent to it.
+pub struct SimulatedDisplay {
+    encoding: Mutex<FsctTextEncoding>,
+    state: Mutex<DisplayState>,
+}
+
+impl SimulatedDisplay {
+    fn new(encoding: FsctTextEncoding, firmware_version: u16) -> Self {
+        let state = DisplayState { firmware_version, ..Default::default() };
+        Self { encoding: Mutex::new(encoding), state: Mutex::new(state) }
+    }
+
+    /// Encoding texts are decoded with, changed when quirks force another one than the profile announces.
+    pub(crate) fn set_encoding(&self, encoding: FsctTextEncoding) {
+        *self.encoding.lock().unwrap() = encoding;
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.state.lock().unwrap().enabled
+    }
+
+    pub fn status(&self) -> Option<FsctStatus> {
+        self.state.lock().unwrap().status
+    }
+
+    pub fn progress(&self) -> Option<SimulatedProgress> {
+        self.state.lock().unwrap().progress
+    }
+
+    /// Bytes of the text as sent, `None` if it is cleared.
+    pub fn raw_text(&self, text_id: FsctTextMetadata) -> Option<Vec<u8>> {
+        self.state.lock().unwrap().texts.get(&text_id).cloned()
+    }
+
+    /// The text as shown, decoded like the device would.
+    pub fn text(&self, text_id: FsctTextMetadata) -> Option<String> {
+        let raw = self.raw_text(text_id)?;
+        Some(match *self.encoding.lock().unwrap() {
+            FsctTextEncoding::Utf8 => String::from_utf8_lossy(&raw).into_owned(),
+            FsctTextEncoding::Utf16 | FsctTextEncoding::Ucs2 => {
+                let units: Vec<_> = raw.chunks_exact(2).map(|unit| u16::from_ne_bytes([unit[0], unit[1]])).collect();
+                String::from_utf16_lossy(&units)
+            }
+            FsctTextEncoding::Utf32 => raw
+                .chunks_exact(4)
+                .map(|unit| u32::from_ne_bytes([unit[0], unit[1], unit[2], unit[3]]))
+                .map(|c| char::from_u32(c).unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect(),
+        })
+    }
+
+    /// Value of the last volume request.
+    pub fn volume(&self) -> Option<u16> {
+        self.state.lock().unwrap().volume
+    }
+
+    /// Value of the last playback modes request.
+    pub fn playback_modes(&self) -> Option<u16> {
+        self.state.lock().unwrap().playback_modes
+    }
+
+    /// Attention signals requested so far.
+    pub fn notifications(&self) -> Vec<FsctNotification> {
+        self.state.lock().unwrap().notifications.clone()
+    }
+
+    /// Last firmware image the device applied.
+    pub fn firmware_image(&self) -> Option<Vec<u8>> {
+        self.state.lock().unwrap().firmware_image.clone()
+    }
+
+    /// Runs the firmware applied since the last restart, which reports the next version.
+    pub(crate) fn restart(&self) {
+        let mut state = self.state.lock().unwrap();
+        if std::mem::take(&mut state.restart_pending) {
+            state.firmware_version += 1;
+        }
+    }
+}
+
+/// A simulated device attached to a device manager.
+pub struct SimulatedDevice {
+    pub managed_id: ManagedDeviceId,
+    pub display: Arc<SimulatedDisplay>,
+}
+
+/// Transport of simulated devices: requests update the [`SimulatedDisplay`] and always succeed.
+pub(crate) struct SimulatedInterface {
+    display: Arc<SimulatedDisplay>,
+    timeouts: Mutex<UsbRequestTimeouts>,
+    powered_on: Instant,
+}
+
+impl SimulatedInterface {
+    fn new(display: Arc<SimulatedDisplay>) -> Self {
+        Self { display, timeouts: Mutex::new(UsbRequestTimeouts::default()), powered_on: Instant::now() }
+    }
+
+    fn update(&self, update: impl FnOnce(&mut DisplayState)) -> Result<(), FsctDeviceError> {
+        update(&mut self.display.state.lock().unwrap());
+        Ok(())
+    }
+
+    pub fn timeouts(&self) -> UsbRequestTimeouts {
+        *self.timeouts.lock().unwrap()
+    }
+
+    pub fn set_timeouts(&self, timeouts: UsbRequestTimeouts) {
+        *self.timeouts.lock().unwrap() = timeouts;
+    }
+
+    pub fn set_write_delay(&self, _write_delay: Duration) {}
+
+    pub async fn get_device_timestamp(&self) -> Result<requests::Timestamp, FsctDeviceError> {
+        Ok(self.powered_on.elapsed().as_millis() as requests::Timestamp)
+    }
+
+    pub async fn get_error_report(&self) -> Result<requests::ErrorReportRequestData, FsctDeviceError> {
+        Ok(requests::ErrorReportRequestData::default())
+    }
+
+    pub async fn get_firmware_version(&self) -> Result<u16, FsctDeviceError> {
+        Ok(self.display.state.lock().unwrap().firmware_version)
+    }
+
+    pub async fn get_enable(&self) -> Result<bool, FsctDeviceError> {
+        Ok(self.display.enabled())
+    }
+
+    pub async fn set_enable(&self, enable: bool) -> Result<(), FsctDeviceError> {
+        self.update(|state| state.enabled = enable)
+    }
+
+    /// Simulated devices have no buttons, so no command report ever comes.
+    pub async fn read_command_report(&self) -> Result<Vec<u8>, FsctDeviceError> {
+        std::future::pending().await
+    }
+
+    #[cfg(feature = "audio-levels")]
+    pub fn takes_audio_levels(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "audio-levels")]
+    pub async fn send_level_report(&self, _report: &[u8]) -> Result<(), FsctDeviceError> {
+        Err(FsctDeviceError::AudioLevelsNotSupported)
+    }
+
+    pub async fn send_poll(&self) -> Result<Duration, FsctDeviceError> {
+        Ok(Duration::ZERO)
+    }
+
+    pub async fn send_notify(&self, notification: FsctNotification) -> Result<(), FsctDeviceError> {
+        self.update(|state| state.notifications.push(notification))
+    }
+
+    /// Simulated devices have no vendor extensions; requests are answered with zeros.
+    #[cfg(feature = "vendor-requests")]
+    pub async fn send_vendor_request(&self, vendor_request: &VendorRequest) -> Result<Vec<u8>, FsctDeviceError> {
+        Ok(vec![0; vendor_request.response_length as usize])
+    }
+
+    pub async fn send_track_progress(&self, progress: &requests::TrackProgressRequestData)
+                                     -> Result<(), FsctDeviceError> {
+        let progress = *progress; // copied out of the packed struct
+        let (duration, position, rate) = (progress.duration, progress.position, progress.rate);
+        let progress = SimulatedProgress { duration, position, rate };
+        self.update(|state| state.progress = Some(progress))
+    }
+
+    pub async fn disable_track_progress(&self) -> Result<(), FsctDeviceError> {
+        self.update(|state| state.progress = None)
+    }
+
+    pub async fn send_current_text(&self, text_id: FsctTextMetadata, text_raw: &[u8]) -> Result<(), FsctDeviceError> {
+        self.update(|state| {
+            state.texts.insert(text_id, text_raw.to_vec());
+        })
+    }
+
+    pub async fn disable_current_text(&self, text_id: FsctTextMetadata) -> Result<(), FsctDeviceError> {
+        self.update(|state| {
+            state.texts.remove(&text_id);
+        })
+    }
+
+    pub async fn send_status(&self, status: FsctStatus) -> Result<(), FsctDeviceError> {
+        self.update(|state| state.status = Some(status))
+    }
+
+    pub async fn send_volume(&self, value: u16) -> Result<(), FsctDeviceError> {
+        self.update(|state| state.volume = Some(value))
+    }
+
+    pub async fn send_playback_modes(&self, value: u16) -> Result<(), FsctDeviceError> {
+        self.update(|state| state.playback_modes = Some(value))
+    }
+
+    /// Receives the image like a device would, checking its length and CRC-32 before applying it; the device runs it
+    /// once restarted with [`DeviceManager::restart_simulated_device`](crate::DeviceManager::restart_simulated_device).
+    pub async fn send_dfu_request(&self, request: requests::FsctRequestCode, _value: u16, data: &[u8],
+                                  _timeout: Duration) -> Result<(), FsctDeviceError> {
+        let mut state = self.display.state.lock().unwrap();
+        match request {
+            requests::FsctRequestCode::DfuBegin => {
+                let length = u32::from_le_bytes(data.try_into().map_err(|_| dfu_error("Invalid image length"))?);
+                state.firmware_upload = Some((length as usize, Vec::new()));
+            }
+            requests::FsctRequestCode::DfuChunk => {
+                let (_, image) = state.firmware_upload.as_mut().ok_or_else(|| dfu_error("Update not begun"))?;
+                image.extend_from_slice(data);
+            }
+            requests::FsctRequestCode::DfuFinish => {
+                let (length, image) = state.firmware_upload.take().ok_or_else(|| dfu_error("Update not begun"))?;
+                let crc = u32::from_le_bytes(data.try_into().map_err(|_| dfu_error("Invalid CRC"))?);
+                if image.len() != length || crc32(&image) != crc {
+                    return Err(dfu_error("Image corrupted"));
+                }
+                state.firmware_image = Some(image);
+                state.restart_pending = true;
+            }
+            _ => state.firmware_upload = None,
+        }
+        Ok(())
+    }
+}
+
+fn dfu_error(message: &str) -> FsctDeviceError {
+    FsctDeviceError::InvalidFirmwareImage(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::*;
+    use crate::definitions::{DfuProgress, TimelineInfo};
+    use crate::device_manager::{DeviceControl, DeviceManager, DeviceManagerError};
+
+    const PROFILES: &str = r#"
+        [[profiles]]
+        name = "utf8-long-texts"
+        texts = { current_title = 64, current_author = 64, queue_title = 64 }
+
+        [[profiles]]
+        name = "ucs2-short-title"
+        encoding = "ucs2"
+        progress = false
+        texts = { current_title = 8 }
+    "#;
+
+    #[tokio::test]
+    async fn texts_and_progress_follow_the_profile() {
+        let profiles = DescriptorProfiles::parse(PROFILES).unwrap().profiles;
+        let device_manager = DeviceManager::new();
+        let long = device_manager.attach_simulated_device(&profiles[0]).await.unwrap();
+        let short = device_manager.attach_simulated_device(&profiles[1]).await.unwrap();
+        let timeline = TimelineInfo {
+            position: Duration::from_secs(10),
+            update_time: SystemTime::now(),
+            duration: Some(Duration::from_secs(354)),
+            rate: 1.0,
+        };
+
+        for device in [&long, &short] {
+            assert!(device.display.enabled());
+            assert_eq!(device.display.status(), Some(FsctStatus::Stopped));
+            let id = device.managed_id;
+            let title = Some("Bohemian Rhapsody");
+            device_manager.set_current_text(id, FsctTextMetadata::CurrentTitle, title).await.unwrap();
+            device_manager.set_current_text(id, FsctTextMetadata::CurrentAuthor, Some("Queen")).await.unwrap();
+            device_manager.set_progress(id, Some(timeline)).await.unwrap();
+        }
+
+        assert_eq!(long.display.text(FsctTextMetadata::CurrentTitle).as_deref(), Some("Bohemian Rhapsody"));
+        assert_eq!(long.display.text(FsctTextMetadata::CurrentAuthor).as_deref(), Some("Queen"));
+        assert_eq!(long.display.progress().map(|progress| progress.duration), Some(354));
+        assert_eq!(short.display.raw_text(FsctTextMetadata::CurrentTitle).map(|raw| raw.len()), Some(8));
+        assert_eq!(short.display.text(FsctTextMetadata::CurrentTitle).as_deref(), Some("Bohe"));
+        assert_eq!(short.display.text(FsctTextMetadata::CurrentAuthor), None);
+        assert_eq!(short.display.progress(), None);
+
+        let limits = device_manager.device_limits(short.managed_id).unwrap();
+        assert_eq!(limits.text_encoding, FsctTextEncoding::Ucs2);
+        assert_eq!(limits.texts.len(), 1);
+        assert!(device_manager.detach_simulated_device(short.managed_id).is_some());
+        assert!(device_manager.device_limits(short.managed_id).is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn firmware_image_is_streamed_in_chunks() {
+        let updatable = DescriptorProfile { firmware_update: true, firmware_version: 0x0102, ..Default::default() };
+        let device_manager = DeviceManager::new();
+        let device = device_manager.attach_simulated_device(&updatable).await.unwrap();
+        let plain = device_manager.attach_simulated_device(&DescriptorProfile::default()).await.unwrap();
+        let image: Vec<u8> = (0..2500u32).map(|i| i as u8).collect();
+
+        let mut progress = Vec::new();
+        let update = device_manager.update_firmware(device.managed_id, &image,
+                                                    &mut |p: DfuProgress| progress.push(p.sent));
+        let restart = async {
+            while device.display.firmware_image().is_none() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            device_manager.restart_simulated_device(&device)
+        };
+        let (version, restarted) = tokio::join!(update, restart);
+        assert!(restarted);
+        assert_eq!(version.unwrap(), 0x0103);
+        assert_eq!(progress, vec![0, 1024, 2048, 2500]);
+        assert_eq!(device.display.firmware_image(), Some(image.clone()));
+        assert_eq!(device_manager.firmware_version(device.managed_id).await.unwrap(), 0x0103);
+
+        // a device not restarting did not take the update
+        let result = device_manager.update_firmware(device.managed_id, &image, &mut |_: DfuProgress| {}).await;
+        assert!(matches!(result, Err(DeviceManagerError::FsctDeviceError(FsctDeviceError::FirmwareNotRestarted))));
+
+        let record = device_manager.attach_record(device.managed_id).unwrap();
+        assert!(record.functionality.contains(&"FirmwareUpdate".to_string()));
+
+        let result = device_manager.update_firmware(plain.managed_id, &image, &mut |_: DfuProgress| {}).await;
+        assert!(matches!(result,
+                         Err(DeviceManagerError::FsctDeviceError(FsctDeviceError::FirmwareUpdateNotSupported))));
+        assert_eq!(plain.display.firmware_image(), None);
+    }
+}
@@ -0,0 +1,330 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Band energies and beats of what the host plays, for devices announcing a spectrum display.
+//!
+//! [`run_spectrum_stream`] analyzes the latest [`AudioBlock`] of the capture (see
+//! [`audio_levels`](crate::audio_levels)) at a configurable rate and sends the result to the devices announcing a
+//! spectrum display in their FSCT descriptor set. The analysis takes at most [`SpectrumConfig::cpu_budget`] of a
+//! core; analyses exceeding it are skipped until the next second.
+//!
+//! Reports are sent on the level endpoint as a flags byte (bit 0: beat) followed by one energy per band of the
+//! device, scaled like [`AudioLevels`](crate::audio_levels::AudioLevels). Reports carry at least
+//! [`MIN_SPECTRUM_BANDS`] bands, so they are told apart from the 2-byte level reports by their length.
+
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::debug;
+use tokio::sync::watch;
+use tokio::time::MissedTickBehavior;
+
+use crate::audio_levels::{power_level, AudioBlock, DEFAULT_AUDIO_LEVEL_RATE, MAX_AUDIO_LEVEL_RATE};
+use crate::device_manager::{DeviceManagement, DeviceManager};
+use crate::service::{spawn_service, ServiceHandle};
+
+/// Fewest bands a spectrum display can announce.
+pub const MIN_SPECTRUM_BANDS: usize = 2;
+
+/// Bands of the analysis; displays with fewer bands get the loudest of the bands they cover.
+pub const MAX_SPECTRUM_BANDS: usize = 32;
+
+const LOWEST_BAND_HZ: f32 = 40.0;
+const HIGHEST_BAND_HZ: f32 = 16000.0;
+const MIN_FFT_SIZE: usize = 256;
+const MAX_FFT_SIZE: usize = 8192;
+
+/// Upper edge of the bass range beats are detected in.
+const BEAT_BASS_HZ: f32 = 150.0;
+/// How many times the bass energy must exceed its average of the last second to count as a beat.
+const BEAT_THRESHOLD: f32 = 1.5;
+/// Bass energy below which no beats are detected (-50 dBFS).
+const BEAT_MIN_POWER: f32 = 1e-5;
+
+/// Settings of the spectrum analysis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpectrumConfig {
+    /// Analyses per second, capped at [`MAX_AUDIO_LEVEL_RATE`].
+    pub rate: u32,
+    /// Samples per analysis, rounded up to a power of two from 256 to 8192; larger sizes resolve bass better at a
+    /// higher CPU cost.
+    pub fft_size: usize,
+    /// Share of a CPU core the analysis may take, e.g. 0.02 for 2 %.
+    pub cpu_budget: f32,
+}
+
+impl Default for SpectrumConfig {
+    fn default() -> Self {
+        Self {
+            rate: DEFAULT_AUDIO_LEVEL_RATE,
+            fft_size: 2048,
+            cpu_budget: 0.02,
+        }
+    }
+}
+
+/// Result of a single analysis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Spectrum {
+    /// Energies of log-spaced bands from 40 Hz to 16 kHz, scaled like
+    /// [`AudioLevels`](crate::audio_levels::AudioLevels).
+    pub bands: [u8; MAX_SPECTRUM_BANDS],
+    /// Whether the bass energy jumped above its recent average.
+    pub beat: bool,
+}
+
+impl Spectrum {
+    /// Report sent to a device with `band_count` bands (clamped to [`MIN_SPECTRUM_BANDS`]..=[`MAX_SPECTRUM_BANDS`]).
+    pub fn to_report(&self, band_count: usize) -> Vec<u8> {
+        let band_count = band_count.clamp(MIN_SPECTRUM_BANDS, MAX_SPECTRUM_BANDS);
+        let mut report = Vec::with_capacity(band_count + 1);
+        report.push(self.beat as u8);
+        report.extend((0..band_count).map(|band| {
+            let first = band * MAX_SPECTRUM_BANDS / band_count;
+            let last = (band + 1) * MAX_SPECTRUM_BANDS / band_count;
+            self.bands[first..last].iter().copied().max().unwrap_or(0)
+        }));
+        report
+    }
+}
+
+/// FFT analyzer of captured blocks, keeping the CPU budget and the bass history of beat detection.
+pub struct SpectrumAnalyzer {
+    fft_size: usize,
+    window: Vec<f32>,
+    twiddles: Vec<(f32, f32)>,
+    cpu_budget: Duration,
+    budget_period_start: Instant,
+    spent: Duration,
+    bass_history: VecDeque<f32>,
+    history_len: usize,
+}
+
+impl SpectrumAnalyzer {
+    pub fn new(config: &SpectrumConfig) -> Self {
+        let fft_size = config.fft_size.clamp(MIN_FFT_SIZE, MAX_FFT_SIZE).next_power_of_two();
+        let window = (0..fft_size).map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / fft_size as f32).cos()).collect();
+        let twiddles = (0..fft_size / 2)
+            .map(|k| {
+                let (sin, cos) = (-2.0 * PI * k as f32 / fft_size as f32).sin_cos();
+                (cos, sin)
+            })
+            .collect();
+        let history_len = config.rate.clamp(1, MAX_AUDIO_LEVEL_RATE) as usize;
+        Self {
+            fft_size,
+            window,
+            twiddles,
+            cpu_budget: Duration::from_secs_f32(config.cpu_budget.clamp(0.0, 1.0)),
+            budget_period_start: Instant::now(),
+            spent: Duration::ZERO,
+            bass_history: VecDeque::with_capacity(history_len),
+            history_len,
+        }
+    }
+
+    /// Analyzes the latest samples of the block, or returns `None` if the CPU budget of the current second is spent.
+    pub fn analyze(&mut self, block: &AudioBlock) -> Option<Spectrum> {
+        let started = Instant::now();
+        if started.duration_since(self.budget_period_start) >= Duration::from_secs(1) {
+            self.budget_period_start = started;
+            self.spent = Duration::ZERO;
+        }
+        if self.spent > self.cpu_budget {
+            return None;
+        }
+        let spectrum = self.analyze_block(block);
+        self.spent += started.elapsed();
+        Some(spectrum)
+    }
+
+    fn analyze_block(&mut self, block: &AudioBlock) -> Spectrum {
+        let mut spectrum = Spectrum::default();
+        if block.sample_rate == 0 {
+            return spectrum;
+        }
+        let power = self.power_spectrum(block);
+        let bin_hz = block.sample_rate as f32 / self.fft_size as f32;
+        let bin_of = |hz: f32| ((hz / bin_hz).round() as usize).min(power.len());
+        let ratio = HIGHEST_BAND_HZ / LOWEST_BAND_HZ;
+        for (band, level) in spectrum.bands.iter_mut().enumerate() {
+            let low = bin_of(LOWEST_BAND_HZ * ratio.powf(band as f32 / MAX_SPECTRUM_BANDS as f32));
+            let high = bin_of(LOWEST_BAND_HZ * ratio.powf((band + 1) as f32 / MAX_SPECTRUM_BANDS as f32));
+            let energy: f32 = power[low..high.max(low + 1).min(power.len())].iter().sum();
+            *level = power_level(energy);
+        }
+
+        let bass: f32 = power[1..bin_of(BEAT_BASS_HZ).max(2).min(power.len())].iter().sum();
+        let average = self.bass_history.iter().sum::<f32>() / self.bass_history.len().max(1) as f32;
+        spectrum.beat = self.bass_history.len() * 2 >= self.history_len
+            && bass > BEAT_MIN_POWER
+            && bass > average * BEAT_THRESHOLD;
+        if self.bass_history.len() == self.history_len {
+            self.bass_history.pop_front();
+        }
+        self.bass_history.push_back(bass);
+        spectrum
+    }
+
+    /// Power of the positive frequencies of the last `fft_size` frames mixed to mono; a full scale sine is 1.0.
+    fn power_spectrum(&self, block: &AudioBlock) -> Vec<f32> {
+        let channels = block.channels.max(1);
+        let frames = block.samples.len() / channels;
+        let pad = self.fft_size.saturating_sub(frames);
+        let mut buffer = vec![(0.0, 0.0); self.fft_size];
+        for (i, frame) in block.samples.chunks_exact(channels).skip(frames.saturating_sub(self.fft_size)).enumerate() {
+            let mono = frame.iter().sum::<f32>() / channels as f32;
+            buffer[pad + i].0 = mono * self.window[pad + i];
+        }
+        self.fft(&mut buffer);
+        let scale = (self.fft_size as f32 / 4.0).powi(2);
+        buffer[..self.fft_size / 2].iter().map(|(re, im)| (re * re + im * im) / scale).collect()
+    }
+
+    /// In-place radix-2 FFT of `fft_size` complex values.
+    fn fft(&self, buffer: &mut [(f32, f32)]) {
+        let n = buffer.len();
+        let mut j = 0;
+        for i in 1..n {
+            let mut bit = n >> 1;
+            while j & bit != 0 {
+                j ^= bit;
+                bit >>= 1;
+            }
+            j |= bit;
+            if i < j {
+                buffer.swap(i, j);
+            }
+        }
+        let mut len = 2;
+        while len <= n {
+            let half = len / 2;
+            let stride = n / len;
+            for start in (0..n).step_by(len) {
+                for k in 0..half {
+                    let (cos, sin) = self.twiddles[k * stride];
+                    let (re, im) = buffer[start + k + half];
+                    let odd = (re * cos - im * sin, re * sin + im * cos);
+                    let even = buffer[start + k];
+                    buffer[start + k] = (even.0 + odd.0, even.1 + odd.1);
+                    buffer[start + k + half] = (even.0 - odd.0, even.1 - odd.1);
+                }
+            }
+            len <<= 1;
+        }
+    }
+}
+
+/// Sends the spectrum of the blocks published into `blocks` to the devices showing it, as configured by `config`,
+/// until the service is stopped or the capture ends; devices suspended by do-not-disturb are skipped.
+pub fn run_spectrum_stream(device_manager: Arc<DeviceManager>, mut blocks: watch::Receiver<AudioBlock>,
+                           config: SpectrumConfig) -> ServiceHandle {
+    spawn_service(move |mut stop| async move {
+        let mut analyzer = SpectrumAnalyzer::new(&config);
+        let mut interval = tokio::time::interval(Duration::from_secs(1) / config.rate.clamp(1, MAX_AUDIO_LEVEL_RATE));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let mut over_budget = false;
+        loop {
+            tokio::select! {
+                _ = stop.signaled() => break,
+                _ = interval.tick() => {}
+            }
+            match blocks.has_changed() {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(_) => {
+                    debug!("Audio capture ended, stopping spectrum analysis");
+                    break;
+                }
+            }
+            let block = blocks.borrow_and_update().clone();
+            let Some(spectrum) = analyzer.analyze(&block) else {
+                if !over_budget {
+                    debug!("Spectrum analysis exceeded its CPU budget, skipping analyses");
+                    over_budget = true;
+                }
+                continue;
+            };
+            over_budget = false;
+            for device_id in device_manager.get_all_managed_ids() {
+                if device_manager.is_suspended(device_id) {
+                    continue;
+                }
+                if let Err(e) = device_manager.send_spectrum(device_id, &spectrum).await {
+                    debug!("Failed to send spectrum to device {}: {}", device_id, e);
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(hz: f32, amplitude: f32, frames: usize) -> AudioBlock {
+        let samples = (0..frames).map(|i| amplitude * (2.0 * PI * hz * i as f32 / 48000.0).sin()).collect();
+        AudioBlock { samples, channels: 1, sample_rate: 48000 }
+    }
+
+    #[test]
+    fn sine_shows_in_its_band() {
+        let mut analyzer = SpectrumAnalyzer::new(&SpectrumConfig::default());
+        let spectrum = analyzer.analyze(&sine(1000.0, 1.0, 2048)).unwrap();
+
+        // 1 kHz lies in band 17 of 40 Hz..16 kHz split into 32 log-spaced bands
+        let loudest = (0..MAX_SPECTRUM_BANDS).max_by_key(|&band| spectrum.bands[band]).unwrap();
+        assert_eq!(loudest, 17);
+        assert_eq!(spectrum.bands[loudest], 255);
+        assert_eq!(spectrum.bands[0], 0);
+        assert_eq!(spectrum.bands[MAX_SPECTRUM_BANDS - 1], 0);
+    }
+
+    #[test]
+    fn report_merges_bands_of_device() {
+        let mut spectrum = Spectrum::default();
+        spectrum.bands[3] = 10;
+        spectrum.bands[20] = 200;
+        spectrum.beat = true;
+
+        let report = spectrum.to_report(4);
+        assert_eq!(report, [1, 10, 0, 200, 0]);
+        assert_eq!(spectrum.to_report(0).len(), MIN_SPECTRUM_BANDS + 1);
+        assert_eq!(spectrum.to_report(64).len(), MAX_SPECTRUM_BANDS + 1);
+    }
+
+    #[test]
+    fn bass_jump_is_a_beat() {
+        let config = SpectrumConfig { rate: 10, ..Default::default() };
+        let mut analyzer = SpectrumAnalyzer::new(&config);
+        for _ in 0..10 {
+            assert!(!analyzer.analyze(&sine(60.0, 0.05, 2048)).unwrap().beat);
+        }
+        assert!(analyzer.analyze(&sine(60.0, 0.5, 2048)).unwrap().beat);
+        assert!(!analyzer.analyze(&sine(60.0, 0.0, 2048)).unwrap().beat);
+    }
+
+    #[test]
+    fn analyses_over_budget_are_skipped() {
+        let config = SpectrumConfig { cpu_budget: 0.0, ..Default::default() };
+        let mut analyzer = SpectrumAnalyzer::new(&config);
+        assert!(analyzer.analyze(&sine(1000.0, 1.0, 2048)).is_some());
+        assert!(analyzer.analyze(&sine(1000.0, 1.0, 2048)).is_none());
+    }
+}
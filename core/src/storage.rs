@@ -0,0 +1,213 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Key-value persistence of host state (e.g. usage statistics), independent of where it is stored.
+//!
+//! [`JsonFileStorage`] keeps every key in its own JSON file and is the default. With the `storage-sqlite` feature
+//! [`SqliteStorage`] keeps all keys in a single SQLite database, for embedded deployments that need atomic writes
+//! on flash storage.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("Invalid storage key: {0:?}")]
+    InvalidKey(String),
+    #[error("Storage I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid stored value: {0}")]
+    Format(#[from] serde_json::Error),
+    #[cfg(feature = "storage-sqlite")]
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+}
+
+/// Persistent key-value store.
+///
+/// Keys consist of lowercase ASCII letters, digits, `-` and `_`. Writes are atomic: after a crash or power loss a
+/// key holds either its previous or its new value.
+pub trait Storage: Send + Sync {
+    /// Reads the value of `key`, `None` if it was never stored or has been removed.
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Replaces the value of `key`.
+    fn put(&self, key: &str, value: &[u8]) -> Result<(), StorageError>;
+
+    /// Removes `key`; removing a missing key is not an error.
+    fn remove(&self, key: &str) -> Result<(), StorageError>;
+}
+
+/// Typed access to a [`Storage`], with values encoded as JSON.
+pub trait StorageExt: Storage {
+    fn load<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, StorageError> {
+        self.get(key)?.map(|value| serde_json::from_slice(&value)).transpose().map_err(StorageError::from)
+    }
+
+    fn save<T: Serialize>(&self, key: &str, value: &T) -> Result<(), StorageError> {
+        self.put(key, &serde_json::to_vec_pretty(value)?)
+    }
+}
+
+impl<S: Storage + ?Sized> StorageExt for S {}
+
+fn validate_key(key: &str) -> Result<(), StorageError> {
+    let valid = !key.is_empty()
+        && key.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_');
+    if valid { Ok(()) } else { Err(StorageError::InvalidKey(key.to_string())) }
+}
+
+/// Storage keeping each key in a `<key>.json` file of a directory.
+///
+/// A value is written to a temporary file, synced and renamed over the previous one.
+pub struct JsonFileStorage {
+    dir: PathBuf,
+}
+
+impl JsonFileStorage {
+    /// Uses `dir`, which is created on first write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        validate_key(key)?;
+        Ok(self.dir.join(format!("{}.json", key)))
+    }
+}
+
+impl Storage for JsonFileStorage {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        match std::fs::read(self.path(key)?) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        std::fs::create_dir_all(&self.dir)?;
+        let temp_path = path.with_extension("json.tmp");
+        let mut file = File::create(&temp_path)?;
+        file.write_all(value)?;
+        file.sync_all()?;
+        std::fs::rename(&temp_path, &path)?;
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<(), StorageError> {
+        match std::fs::remove_file(self.path(key)?) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "storage-sqlite")]
+mod sqlite {
+    use std::path::Path;
+    use std::sync::Mutex;
+
+    use rusqlite::{params, Connection, OptionalExtension};
+
+    use super::{validate_key, Storage, StorageError};
+
+    /// Storage keeping all keys in a single SQLite database file.
+    pub struct SqliteStorage {
+        connection: Mutex<Connection>,
+    }
+
+    impl SqliteStorage {
+        /// Opens the database at `path`, creating it if needed.
+        pub fn open(path: &Path) -> Result<Self, StorageError> {
+            Self::with_connection(Connection::open(path)?)
+        }
+
+        /// Database living in memory only, e.g. for tests.
+        pub fn in_memory() -> Result<Self, StorageError> {
+            Self::with_connection(Connection::open_in_memory()?)
+        }
+
+        fn with_connection(connection: Connection) -> Result<Self, StorageError> {
+            connection.execute("CREATE TABLE IF NOT EXISTS entries (key TEXT PRIMARY KEY, value BLOB NOT NULL)", [])?;
+            Ok(Self { connection: Mutex::new(connection) })
+        }
+    }
+
+    impl Storage for SqliteStorage {
+        fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+            validate_key(key)?;
+            let connection = self.connection.lock().unwrap();
+            Ok(connection
+                .query_row("SELECT value FROM entries WHERE key = ?1", params![key], |row| row.get(0))
+                .optional()?)
+        }
+
+        fn put(&self, key: &str, value: &[u8]) -> Result<(), StorageError> {
+            validate_key(key)?;
+            let connection = self.connection.lock().unwrap();
+            connection.execute("INSERT OR REPLACE INTO entries (key, value) VALUES (?1, ?2)", params![key, value])?;
+            Ok(())
+        }
+
+        fn remove(&self, key: &str) -> Result<(), StorageError> {
+            validate_key(key)?;
+            let connection = self.connection.lock().unwrap();
+            connection.execute("DELETE FROM entries WHERE key = ?1", params![key])?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "storage-sqlite")]
+pub use sqlite::SqliteStorage;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_round_trip(storage: &dyn Storage) {
+        assert_eq!(storage.get("nicknames").unwrap(), None);
+        storage.put("nicknames", b"{}").unwrap();
+        storage.put("nicknames", b"{\"a\":1}").unwrap();
+        assert_eq!(storage.get("nicknames").unwrap(), Some(b"{\"a\":1}".to_vec()));
+        storage.remove("nicknames").unwrap();
+        storage.remove("nicknames").unwrap();
+        assert_eq!(storage.get("nicknames").unwrap(), None);
+        assert!(matches!(storage.put("../escape", b""), Err(StorageError::InvalidKey(_))));
+    }
+
+    #[test]
+    fn backends_store_replace_and_remove_values() {
+        let dir = std::env::temp_dir().join(format!("fsct-storage-{}", uuid::Uuid::new_v4()));
+        check_round_trip(&JsonFileStorage::new(&dir));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        #[cfg(feature = "storage-sqlite")]
+        check_round_trip(&SqliteStorage::in_memory().unwrap());
+    }
+}
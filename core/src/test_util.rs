@@ -0,0 +1,330 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Deterministic fixtures for routing tests, available with the `test-util` feature.
+//!
+//! Tests using these helpers must run on a current-thread runtime with a paused clock,
+//! i.e. `#[tokio::test(start_paused = true)]`. With a paused clock tokio only advances time when every task is
+//! idle, so [`drain`] returns exactly when the orchestrator has processed all queued events, without relying on
+//! wall clock sleeps.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Error;
+use tokio::sync::broadcast;
+
+use crate::definitions::{FsctNotification, FsctStatus, FsctTextMetadata, PlaybackModes, TimelineInfo, VolumeInfo};
+use crate::device_manager::{DeviceEvent, ManagedDeviceId};
+use crate::orchestrator::{ApplyAckHandle, Orchestrator};
+use crate::player_events::PlayerEvent;
+use crate::player_event_queue::PlayerEventQueues;
+use crate::player_state::PlayerState;
+use crate::player_state_applier::PlayerStateApplier;
+use crate::service::ServiceHandle;
+
+/// A full state apply recorded by [`RecordingApplier`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApplyCall {
+    pub device: ManagedDeviceId,
+    pub state: PlayerState,
+}
+
+/// A status-only apply recorded by [`RecordingApplier`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusCall {
+    pub device: ManagedDeviceId,
+    pub status: FsctStatus,
+}
+
+/// A timeline-only apply recorded by [`RecordingApplier`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineCall {
+    pub device: ManagedDeviceId,
+    pub timeline: Option<TimelineInfo>,
+}
+
+/// A volume-only apply recorded by [`RecordingApplier`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeCall {
+    pub device: ManagedDeviceId,
+    pub volume: Option<VolumeInfo>,
+}
+
+/// A modes-only apply recorded by [`RecordingApplier`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModesCall {
+    pub device: ManagedDeviceId,
+    pub modes: PlaybackModes,
+}
+
+/// A single text apply recorded by [`RecordingApplier`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextCall {
+    pub device: ManagedDeviceId,
+    pub text_id: FsctTextMetadata,
+    pub text: Option<String>,
+}
+
+/// A display enable switch recorded by [`RecordingApplier`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnableCall {
+    pub device: ManagedDeviceId,
+    pub enable: bool,
+}
+
+/// An attention signal request recorded by [`RecordingApplier`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotifyCall {
+    pub device: ManagedDeviceId,
+    pub notification: FsctNotification,
+}
+
+/// PlayerStateApplier that records every call instead of talking to devices.
+///
+/// Repeated full applies of the same state to the same device are recorded once.
+#[derive(Default)]
+pub struct RecordingApplier {
+    calls: Mutex<Vec<ApplyCall>>,
+    status_calls: Mutex<Vec<StatusCall>>,
+    timeline_calls: Mutex<Vec<TimelineCall>>,
+    volume_calls: Mutex<Vec<VolumeCall>>,
+    modes_calls: Mutex<Vec<ModesCall>>,
+    text_calls: Mutex<Vec<TextCall>>,
+    enable_calls: Mutex<Vec<EnableCall>>,
+    notify_calls: Mutex<Vec<NotifyCall>>,
+    resets: Mutex<Vec<ManagedDeviceId>>,
+}
+
+impl RecordingApplier {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Takes recorded full applies, leaving the record empty.
+    pub fn take(&self) -> Vec<ApplyCall> {
+        std::mem::take(&mut self.calls.lock().unwrap())
+    }
+
+    /// Takes recorded status applies, leaving the record empty.
+    pub fn take_status(&self) -> Vec<StatusCall> {
+        std::mem::take(&mut self.status_calls.lock().unwrap())
+    }
+
+    /// Takes recorded timeline applies, leaving the record empty.
+    pub fn take_timeline(&self) -> Vec<TimelineCall> {
+        std::mem::take(&mut self.timeline_calls.lock().unwrap())
+    }
+
+    /// Takes recorded volume applies, leaving the record empty.
+    pub fn take_volume(&self) -> Vec<VolumeCall> {
+        std::mem::take(&mut self.volume_calls.lock().unwrap())
+    }
+
+    /// Takes recorded modes applies, leaving the record empty.
+    pub fn take_modes(&self) -> Vec<ModesCall> {
+        std::mem::take(&mut self.modes_calls.lock().unwrap())
+    }
+
+    /// Takes recorded text applies, leaving the record empty.
+    pub fn take_text(&self) -> Vec<TextCall> {
+        std::mem::take(&mut self.text_calls.lock().unwrap())
+    }
+
+    /// Takes recorded enable switches, leaving the record empty.
+    pub fn take_enable(&self) -> Vec<EnableCall> {
+        std::mem::take(&mut self.enable_calls.lock().unwrap())
+    }
+
+    /// Takes recorded attention signal requests, leaving the record empty.
+    pub fn take_notify(&self) -> Vec<NotifyCall> {
+        std::mem::take(&mut self.notify_calls.lock().unwrap())
+    }
+
+    /// Takes devices whose state was reset, leaving the record empty.
+    pub fn take_resets(&self) -> Vec<ManagedDeviceId> {
+        std::mem::take(&mut self.resets.lock().unwrap())
+    }
+}
+
+impl PlayerStateApplier for RecordingApplier {
+    fn apply_to_device<'a>(&'a self, device_id: ManagedDeviceId, state: &'a PlayerState)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let mut calls = self.calls.lock().unwrap();
+            if !calls.iter().any(|c| c.device == device_id && c.state == *state) {
+                calls.push(ApplyCall { device: device_id, state: state.clone() });
+            }
+            Ok(())
+        })
+    }
+
+    fn apply_status<'a>(&'a self, device_id: ManagedDeviceId, status: FsctStatus)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
+        Box::pin(async move {
+            self.status_calls.lock().unwrap().push(StatusCall { device: device_id, status });
+            Ok(())
+        })
+    }
+
+    fn apply_timeline<'a>(&'a self, device_id: ManagedDeviceId, timeline: Option<TimelineInfo>)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
+        Box::pin(async move {
+            self.timeline_calls.lock().unwrap().push(TimelineCall { device: device_id, timeline });
+            Ok(())
+        })
+    }
+
+    fn apply_volume<'a>(&'a self, device_id: ManagedDeviceId, volume: Option<VolumeInfo>)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
+        Box::pin(async move {
+            self.volume_calls.lock().unwrap().push(VolumeCall { device: device_id, volume });
+            Ok(())
+        })
+    }
+
+    fn apply_modes<'a>(&'a self, device_id: ManagedDeviceId, modes: PlaybackModes)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
+        Box::pin(async move {
+            self.modes_calls.lock().unwrap().push(ModesCall { device: device_id, modes });
+            Ok(())
+        })
+    }
+
+    fn apply_text<'a>(&'a self, device_id: ManagedDeviceId, text_id: FsctTextMetadata, text: Option<&'a str>)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
+        let text = text.map(|s| s.to_string());
+        Box::pin(async move {
+            self.text_calls.lock().unwrap().push(TextCall { device: device_id, text_id, text });
+            Ok(())
+        })
+    }
+
+    fn apply_enable<'a>(&'a self, device_id: ManagedDeviceId, enable: bool)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
+        Box::pin(async move {
+            self.enable_calls.lock().unwrap().push(EnableCall { device: device_id, enable });
+            Ok(())
+        })
+    }
+
+    fn apply_notify<'a>(&'a self, device_id: ManagedDeviceId, notification: FsctNotification)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
+        Box::pin(async move {
+            self.notify_calls.lock().unwrap().push(NotifyCall { device: device_id, notification });
+            Ok(())
+        })
+    }
+
+    fn reset_device<'a>(&'a self, device_id: ManagedDeviceId)
+        -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
+        Box::pin(async move {
+            self.resets.lock().unwrap().push(device_id);
+            Ok(())
+        })
+    }
+}
+
+/// Waits until all other tasks are idle.
+///
+/// Only deterministic with a paused clock: the sleep completes when tokio auto-advances time, which it does once
+/// no task can make progress.
+pub async fn drain() {
+    tokio::time::sleep(Duration::from_millis(1)).await;
+}
+
+/// Advances the paused clock by `duration` and waits until all tasks reacted to it.
+pub async fn advance(duration: Duration) {
+    tokio::time::advance(duration).await;
+    drain().await;
+}
+
+/// Orchestrator running against a [`RecordingApplier`], fed through plain event channels.
+pub struct OrchestratorFixture {
+    pub applier: Arc<RecordingApplier>,
+    player_tx: PlayerEventQueues,
+    device_tx: broadcast::Sender<DeviceEvent>,
+    ack_handle: ApplyAckHandle,
+    handle: ServiceHandle,
+}
+
+impl OrchestratorFixture {
+    /// Starts the orchestrator; must be called from a test with a paused clock.
+    pub fn start() -> Self {
+        let applier = RecordingApplier::new();
+        let player_tx = PlayerEventQueues::default();
+        let player_rx = player_tx.subscribe();
+        let (device_tx, device_rx) = broadcast::channel(256);
+        let orchestrator = Orchestrator::new_with_applier(player_rx, device_rx, applier.clone());
+        let ack_handle = orchestrator.ack_handle();
+        let handle = orchestrator.run();
+        Self { applier, player_tx, device_tx, ack_handle, handle }
+    }
+
+    pub fn send_player_event(&self, event: PlayerEvent) {
+        self.player_tx.send(event);
+    }
+
+    pub fn send_device_event(&self, event: DeviceEvent) {
+        let _ = self.device_tx.send(event);
+    }
+
+    /// Waits until the orchestrator acknowledged all events sent so far.
+    pub async fn applied(&self) {
+        self.ack_handle.wait_applied().await.expect("orchestrator should be running");
+    }
+
+    /// Sends the event and waits until the orchestrator has fully processed it.
+    pub async fn player_event(&self, event: PlayerEvent) {
+        self.send_player_event(event);
+        self.applied().await;
+    }
+
+    /// Sends the event and waits until the orchestrator has fully processed it.
+    pub async fn device_event(&self, event: DeviceEvent) {
+        self.send_device_event(event);
+        self.applied().await;
+    }
+
+    pub async fn shutdown(self) {
+        let _ = self.handle.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroU32;
+    use uuid::Uuid;
+
+    #[tokio::test(start_paused = true)]
+    async fn fixture_applies_state_once_events_are_drained() {
+        let fixture = OrchestratorFixture::start();
+        let player_id = NonZeroU32::new(1).unwrap();
+        let device_id = Uuid::new_v4();
+        let state = PlayerState { status: FsctStatus::Playing, ..Default::default() };
+
+        fixture.send_player_event(PlayerEvent::Registered { player_id, self_id: "p1".into() });
+        fixture.send_player_event(PlayerEvent::StateUpdated { player_id, state: state.clone() });
+        fixture.device_event(DeviceEvent::Added(device_id)).await;
+
+        assert_eq!(fixture.applier.take(), vec![ApplyCall { device: device_id, state }]);
+        fixture.shutdown().await;
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Per-device composition of display lines from track metadata.
+//!
+//! A template is plain text with `{field}` placeholders (`title`, `artist`, `album`, `genre`, `year`, `composer`,
+//! and `next_title`, `next_artist` of the track playing next).
+//! Text in square brackets is optional: it is left out when any placeholder inside is missing, e.g.
+//! `"{title}[ – {artist}]"`.
+//! Missing placeholders outside brackets render as empty text. Use `{{`, `}}`, `[[` and `]]` for literal brackets.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::definitions::FsctTextMetadata;
+use crate::player_state::TrackMetadata;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("Unclosed placeholder at position {0}")]
+    UnclosedPlaceholder(usize),
+    #[error("Unclosed optional section at position {0}")]
+    UnclosedSection(usize),
+    #[error("Optional sections can't be nested (position {0})")]
+    NestedSection(usize),
+    #[error("Unexpected '{1}' at position {0}")]
+    Unexpected(usize, char),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Field(String),
+    Optional(Vec<Part>),
+}
+
+/// Parsed line template; serialized as its source text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TextTemplate {
+    source: String,
+    parts: Vec<Part>,
+}
+
+impl TextTemplate {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let mut parts = Vec::new();
+        let mut section: Option<(usize, Vec<Part>)> = None;
+        let mut literal = String::new();
+        let mut chars = source.char_indices().peekable();
+        while let Some((pos, c)) = chars.next() {
+            let in_section = section.is_some();
+            let target = match &mut section {
+                Some((_, section_parts)) => section_parts,
+                None => &mut parts,
+            };
+            match c {
+                '{' | '}' | '[' | ']' if chars.peek().map(|(_, next)| *next) == Some(c) => {
+                    chars.next();
+                    literal.push(c);
+                }
+                '{' => {
+                    flush_literal(&mut literal, target);
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '}')) => break,
+                            Some((_, c)) => name.push(c),
+                            None => return Err(TemplateError::UnclosedPlaceholder(pos)),
+                        }
+                    }
+                    target.push(Part::Field(name.trim().to_string()));
+                }
+                '[' if in_section => return Err(TemplateError::NestedSection(pos)),
+                '[' => {
+                    flush_literal(&mut literal, target);
+                    section = Some((pos, Vec::new()));
+                }
+                ']' => match section.take() {
+                    Some((_, mut section_parts)) => {
+                        flush_literal(&mut literal, &mut section_parts);
+                        parts.push(Part::Optional(section_parts));
+                    }
+                    None => return Err(TemplateError::Unexpected(pos, c)),
+                },
+                '}' => return Err(TemplateError::Unexpected(pos, c)),
+                c => literal.push(c),
+            }
+        }
+        if let Some((pos, _)) = section {
+            return Err(TemplateError::UnclosedSection(pos));
+        }
+        flush_literal(&mut literal, &mut parts);
+        Ok(Self { source: source.to_string(), parts })
+    }
+
+    /// Renders the line; `None` if it comes out empty.
+    pub fn render(&self, texts: &TrackMetadata) -> Option<String> {
+        let mut out = String::new();
+        render_parts(&self.parts, texts, &mut out);
+        let out = out.trim();
+        (!out.is_empty()).then(|| out.to_string())
+    }
+}
+
+fn flush_literal(literal: &mut String, parts: &mut Vec<Part>) {
+    if !literal.is_empty() {
+        parts.push(Part::Literal(std::mem::take(literal)));
+    }
+}
+
+fn field<'a>(texts: &'a TrackMetadata, name: &str) -> Option<&'a str> {
+    let value = match name {
+        "title" => &texts.title,
+        "artist" | "author" => &texts.artist,
+        "album" => &texts.album,
+        "genre" => &texts.genre,
+        "year" => &texts.year,
+        "composer" => &texts.composer,
+        "next_title" => &texts.next_title,
+        "next_artist" => &texts.next_artist,
+        // unknown fields (e.g. ones players don't provide yet) are always missing
+        _ => &None,
+    };
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// Returns false if a placeholder was missing.
+fn render_parts(parts: &[Part], texts: &TrackMetadata, out: &mut String) -> bool {
+    let mut complete = true;
+    for part in parts {
+        match part {
+            Part::Literal(literal) => out.push_str(literal),
+            Part::Field(name) => match field(texts, name) {
+                Some(value) => out.push_str(value),
+                None => complete = false,
+            },
+            Part::Optional(section) => {
+                let mut rendered = String::new();
+                if render_parts(section, texts, &mut rendered) {
+                    out.push_str(&rendered);
+                }
+            }
+        }
+    }
+    complete
+}
+
+impl TryFrom<String> for TextTemplate {
+    type Error = TemplateError;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Self::parse(&source)
+    }
+}
+
+impl From<TextTemplate> for String {
+    fn from(template: TextTemplate) -> Self {
+        template.source
+    }
+}
+
+impl Display for TextTemplate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Templates for the text slots of a device; slots without a template show the player's text unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextLayout {
+    #[serde(flatten)]
+    templates: HashMap<FsctTextMetadata, TextTemplate>,
+}
+
+impl TextLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_template(mut self, slot: FsctTextMetadata, template: TextTemplate) -> Self {
+        self.templates.insert(slot, template);
+        self
+    }
+
+    /// Texts to show on the device for the given player texts.
+    pub fn compose(&self, texts: &TrackMetadata) -> TrackMetadata {
+        let mut composed = texts.clone();
+        for (slot, template) in &self.templates {
+            if texts.iter_id().any(|id| id == slot) {
+                *composed.get_mut_text(*slot) = template.render(texts);
+            }
+        }
+        composed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(title: &str, artist: Option<&str>) -> TrackMetadata {
+        TrackMetadata { title: Some(title.into()), artist: artist.map(Into::into), ..Default::default() }
+    }
+
+    #[test]
+    fn optional_sections_drop_with_missing_fields() {
+        let template = TextTemplate::parse("{title}[ – {artist}][ ({year})] [[live]]").unwrap();
+        assert_eq!(template.render(&track("Song", Some("Band"))).as_deref(), Some("Song – Band [live]"));
+        assert_eq!(template.render(&track("Song", None)).as_deref(), Some("Song [live]"));
+        let with_year = TrackMetadata { year: Some("1999".into()), ..track("Song", None) };
+        assert_eq!(template.render(&with_year).as_deref(), Some("Song (1999) [live]"));
+        assert_eq!(TextTemplate::parse("{album}").unwrap().render(&track("Song", None)), None);
+        assert_eq!(TextTemplate::parse("{title"), Err(TemplateError::UnclosedPlaceholder(0)));
+        assert_eq!(TextTemplate::parse("[a[b]]"), Err(TemplateError::NestedSection(2)));
+    }
+
+    #[test]
+    fn layout_composes_lines_from_all_fields() {
+        let layout = TextLayout::new()
+            .with_template(FsctTextMetadata::CurrentTitle, TextTemplate::parse("{artist} – {title}").unwrap())
+            .with_template(FsctTextMetadata::CurrentAuthor, TextTemplate::parse("{album}").unwrap());
+        let mut texts = track("Song", Some("Band"));
+        texts.album = Some("Album".into());
+        let composed = layout.compose(&texts);
+        assert_eq!(composed.title.as_deref(), Some("Band – Song"));
+        assert_eq!(composed.artist.as_deref(), Some("Album"));
+        assert_eq!(composed.album.as_deref(), Some("Album"));
+    }
+}
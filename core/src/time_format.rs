@@ -0,0 +1,143 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! How times of day composed on the host are written on device displays, e.g. the idle clock.
+//!
+//! Serialized as `{ "locale": "en-US" }`, taking the conventions of the locale, or with explicit `hours` (`"12"` or
+//! `"24"`) and `separator`; explicit fields override those of the locale.
+
+use serde::{Deserialize, Serialize};
+
+/// 12 or 24 hour clock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HourCycle {
+    #[serde(rename = "12")]
+    H12,
+    #[default]
+    #[serde(rename = "24")]
+    H24,
+}
+
+/// Time of day, as shown by clocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockTime {
+    /// 0–23.
+    pub hour: u8,
+    pub minute: u8,
+}
+
+/// Format of times of day; `14:05` by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "TimeFormatRepr", into = "TimeFormatRepr")]
+pub struct TimeFormat {
+    pub hours: HourCycle,
+    /// Separator of hours and minutes, e.g. `.` in Finnish.
+    pub separator: char,
+}
+
+impl Default for TimeFormat {
+    fn default() -> Self {
+        Self { hours: HourCycle::H24, separator: ':' }
+    }
+}
+
+/// Regions whose clocks commonly show 12 hours.
+const H12_REGIONS: &[&str] = &["US", "CA", "AU", "NZ", "PH", "IN", "PK", "BD", "EG", "SA"];
+/// Languages separating hours and minutes with a dot.
+const DOT_LANGUAGES: &[&str] = &["fi", "da", "id"];
+
+impl TimeFormat {
+    pub fn new(hours: HourCycle, separator: char) -> Self {
+        Self { hours, separator }
+    }
+
+    /// Conventions of a BCP 47 or POSIX locale such as `en-US` or `fi_FI`; unknown locales get the default format.
+    pub fn for_locale(locale: &str) -> Self {
+        // POSIX locales carry an encoding and modifier, e.g. `fi_FI.UTF-8@euro`
+        let locale = locale.split(['.', '@']).next().unwrap_or_default();
+        let mut subtags = locale.split(['-', '_']);
+        let language = subtags.next().unwrap_or_default().to_ascii_lowercase();
+        let region = subtags.find(|subtag| subtag.len() == 2).map(str::to_ascii_uppercase);
+        let hours = match region.as_deref() {
+            Some(region) if H12_REGIONS.contains(&region) => HourCycle::H12,
+            None if language == "en" => HourCycle::H12,
+            _ => HourCycle::H24,
+        };
+        let separator = if DOT_LANGUAGES.contains(&language.as_str()) { '.' } else { ':' };
+        Self { hours, separator }
+    }
+
+    /// Writes the time, e.g. `14:05` or `2:05 PM`.
+    pub fn format_time(&self, time: ClockTime) -> String {
+        let separator = self.separator;
+        match self.hours {
+            HourCycle::H24 => format!("{:02}{}{:02}", time.hour, separator, time.minute),
+            HourCycle::H12 => {
+                let suffix = if time.hour < 12 { "AM" } else { "PM" };
+                let hour = match time.hour % 12 {
+                    0 => 12,
+                    hour => hour,
+                };
+                format!("{}{}{:02} {}", hour, separator, time.minute, suffix)
+            }
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct TimeFormatRepr {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    locale: Option<String>,
+    hours: Option<HourCycle>,
+    separator: Option<char>,
+}
+
+impl From<TimeFormatRepr> for TimeFormat {
+    fn from(repr: TimeFormatRepr) -> Self {
+        let locale = repr.locale.as_deref().map(TimeFormat::for_locale).unwrap_or_default();
+        Self { hours: repr.hours.unwrap_or(locale.hours), separator: repr.separator.unwrap_or(locale.separator) }
+    }
+}
+
+impl From<TimeFormat> for TimeFormatRepr {
+    fn from(format: TimeFormat) -> Self {
+        Self { locale: None, hours: Some(format.hours), separator: Some(format.separator) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn times_follow_the_locale_unless_overridden() {
+        let afternoon = ClockTime { hour: 14, minute: 5 };
+        let midnight = ClockTime { hour: 0, minute: 30 };
+        assert_eq!(TimeFormat::default().format_time(afternoon), "14:05");
+        assert_eq!(TimeFormat::for_locale("en-US").format_time(afternoon), "2:05 PM");
+        assert_eq!(TimeFormat::for_locale("en_US.UTF-8").format_time(midnight), "12:30 AM");
+        assert_eq!(TimeFormat::for_locale("en-GB").format_time(afternoon), "14:05");
+        assert_eq!(TimeFormat::for_locale("fi-FI").format_time(afternoon), "14.05");
+
+        let format: TimeFormat = serde_json::from_str(r#"{ "locale": "en-US", "hours": "24" }"#).unwrap();
+        assert_eq!(format, TimeFormat::new(HourCycle::H24, ':'));
+        let format: TimeFormat = serde_json::from_str(r#"{ "separator": "." }"#).unwrap();
+        assert_eq!(format.format_time(afternoon), "14.05");
+        assert_eq!(serde_json::from_value::<TimeFormat>(serde_json::to_value(format).unwrap()).unwrap(), format);
+    }
+}
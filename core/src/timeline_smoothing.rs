@@ -0,0 +1,165 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Smoothing of playback positions reported by players.
+//!
+//! Players re-report their position regularly, usually off by some milliseconds from where the previous report
+//! extrapolates to. Forwarding every such micro-correction makes device progress bars stutter, while a real seek
+//! must reach the devices right away.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use log::debug;
+
+use crate::definitions::TimelineInfo;
+use crate::driver_middleware::{DriverInterceptor, StateUpdate};
+use crate::player_manager::ManagedPlayerId;
+
+/// Smallest position deviation treated as a jump rather than drift.
+pub const DEFAULT_JUMP_THRESHOLD: Duration = Duration::from_millis(1500);
+
+/// How a new timeline relates to the previous one of the same player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineChange {
+    /// Same playback, position within the threshold of the extrapolated one.
+    Drift,
+    /// Same playback, but the position jumped (seek, or drift accumulated beyond the threshold).
+    Jump,
+    /// Rate, duration or presence of the timeline changed.
+    Other,
+}
+
+/// Classifies `next` against `previous`, extrapolating the previous position to the update time of `next`.
+pub fn classify_timeline(previous: Option<&TimelineInfo>, next: Option<&TimelineInfo>, jump_threshold: Duration)
+                         -> TimelineChange {
+    let (Some(previous), Some(next)) = (previous, next) else {
+        return TimelineChange::Other;
+    };
+    if previous.rate != next.rate || previous.duration != next.duration {
+        return TimelineChange::Other;
+    }
+    let elapsed = secs_since_epoch(next.update_time) - secs_since_epoch(previous.update_time);
+    let expected = previous.position.as_secs_f64() + elapsed * previous.rate;
+    if (next.position.as_secs_f64() - expected).abs() < jump_threshold.as_secs_f64() {
+        TimelineChange::Drift
+    } else {
+        TimelineChange::Jump
+    }
+}
+
+fn secs_since_epoch(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or(0.0)
+}
+
+/// Interceptor suppressing position micro-corrections and passing seeks through immediately.
+///
+/// Suppressed updates keep the previous timeline as the reference, so drift accumulating beyond the threshold is
+/// eventually forwarded as a correction.
+pub struct TimelineSmoother {
+    jump_threshold: Duration,
+    timelines: Mutex<HashMap<ManagedPlayerId, TimelineInfo>>,
+}
+
+impl TimelineSmoother {
+    pub fn new(jump_threshold: Duration) -> Self {
+        Self { jump_threshold, timelines: Mutex::new(HashMap::new()) }
+    }
+
+    /// Returns the timeline to forward, which is the previous one if `next` only drifted from it.
+    fn smooth(&self, player_id: ManagedPlayerId, next: Option<TimelineInfo>) -> Option<TimelineInfo> {
+        let mut timelines = self.timelines.lock().unwrap();
+        let previous = timelines.get(&player_id);
+        match classify_timeline(previous, next.as_ref(), self.jump_threshold) {
+            TimelineChange::Drift => return previous.cloned(),
+            TimelineChange::Jump => debug!("Position of player {} jumped, forwarding it", player_id),
+            TimelineChange::Other => {}
+        }
+        match &next {
+            Some(timeline) => timelines.insert(player_id, timeline.clone()),
+            None => timelines.remove(&player_id),
+        };
+        next
+    }
+}
+
+impl Default for TimelineSmoother {
+    fn default() -> Self {
+        Self::new(DEFAULT_JUMP_THRESHOLD)
+    }
+}
+
+#[async_trait]
+impl DriverInterceptor for TimelineSmoother {
+    async fn intercept(&self, player_id: ManagedPlayerId, update: StateUpdate) -> Option<StateUpdate> {
+        match update {
+            StateUpdate::State(mut state) => {
+                state.timeline = self.smooth(player_id, state.timeline);
+                Some(StateUpdate::State(state))
+            }
+            StateUpdate::Timeline(timeline) => {
+                let previous = self.timelines.lock().unwrap().get(&player_id).cloned();
+                let smoothed = self.smooth(player_id, timeline);
+                if previous.is_some() && smoothed == previous {
+                    None
+                } else {
+                    Some(StateUpdate::Timeline(smoothed))
+                }
+            }
+            other => Some(other),
+        }
+    }
+
+    async fn on_player_unregistered(&self, player_id: ManagedPlayerId) {
+        self.timelines.lock().unwrap().remove(&player_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroU32;
+
+    fn timeline(position_ms: u64, update_ms: u64) -> TimelineInfo {
+        TimelineInfo {
+            position: Duration::from_millis(position_ms),
+            update_time: UNIX_EPOCH + Duration::from_millis(update_ms),
+            duration: Some(Duration::from_secs(200)),
+            rate: 1.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn micro_corrections_are_dropped_and_seeks_forwarded() {
+        let smoother = TimelineSmoother::default();
+        let player_id = NonZeroU32::new(1).unwrap();
+        let start = timeline(10_000, 1_000_000);
+        let update = |t: TimelineInfo| StateUpdate::Timeline(Some(t));
+
+        assert_eq!(smoother.intercept(player_id, update(start.clone())).await, Some(update(start.clone())));
+        // 5 s later the player reports 15.2 s: drift, dropped
+        assert_eq!(smoother.intercept(player_id, update(timeline(15_200, 1_005_000))).await, None);
+        // seek to 90 s: forwarded right away
+        let seek = timeline(90_000, 1_006_000);
+        assert_eq!(smoother.intercept(player_id, update(seek.clone())).await, Some(update(seek.clone())));
+        // pausing changes the rate and is always forwarded
+        let paused = TimelineInfo { rate: 0.0, ..timeline(90_500, 1_006_500) };
+        assert_eq!(smoother.intercept(player_id, update(paused.clone())).await, Some(update(paused)));
+    }
+}
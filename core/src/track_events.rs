@@ -0,0 +1,185 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+
+//! Track start and end edges, derived from the state updates of a player.
+//!
+//! [`TrackEdgeDetector`] follows the state of every player and reports when a track started playing and when it
+//! ended, so that consumers like scrobblers, webhooks or statistics don't each derive them from the updates.
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use crate::definitions::{FsctStatus, TimelineInfo};
+use crate::player_events::PlayerEvent;
+use crate::player_manager::ManagedPlayerId;
+use crate::player_state::{PlayerState, TrackMetadata};
+
+/// A track whose position got this close to its duration counts as played to the end.
+pub const TRACK_END_MARGIN: Duration = Duration::from_secs(5);
+
+/// Whether `next` describes the same track as `previous`.
+///
+/// Titles have to match; a missing artist or album on either side is taken as not reported yet, because partial
+/// updates deliver the texts of a new track one at a time.
+fn same_track(previous: &TrackMetadata, next: &TrackMetadata) -> bool {
+    let compatible = |a: &Option<String>, b: &Option<String>| a.is_none() || b.is_none() || a == b;
+    previous.title == next.title && compatible(&previous.artist, &next.artist)
+        && compatible(&previous.album, &next.album)
+}
+
+fn position_at(timeline: &TimelineInfo, now: SystemTime) -> Duration {
+    let elapsed = now.duration_since(timeline.update_time).unwrap_or_default();
+    timeline.position + elapsed.mul_f64(timeline.rate.max(0.0))
+}
+
+#[derive(Debug, Default)]
+struct PlayerTrack {
+    texts: TrackMetadata,
+    timeline: Option<TimelineInfo>,
+    /// Whether [`PlayerEvent::TrackStarted`] has been sent for the track.
+    started: bool,
+    played: Duration,
+    playing_since: Option<SystemTime>,
+    completed: bool,
+}
+
+impl PlayerTrack {
+    fn new(texts: TrackMetadata) -> Self {
+        Self { texts, ..Default::default() }
+    }
+
+    /// Accounts playing time and whether the end was reached up to `now`.
+    fn advance(&mut self, now: SystemTime) {
+        if let Some(since) = self.playing_since.replace(now) {
+            self.played += now.duration_since(since).unwrap_or_default();
+        }
+        if let Some(timeline) = &self.timeline
+            && let Some(duration) = timeline.duration
+            && position_at(timeline, now) + TRACK_END_MARGIN >= duration {
+            self.completed = true;
+        }
+    }
+
+    fn ended(&self, player_id: ManagedPlayerId) -> PlayerEvent {
+        PlayerEvent::TrackEnded {
+            player_id,
+            track: self.texts.clone(),
+            played: self.played,
+            completed: self.completed,
+        }
+    }
+}
+
+/// Derives [`PlayerEvent::TrackStarted`] and [`PlayerEvent::TrackEnded`] from player states.
+///
+/// A track starts when a titled track is playing for the first time since it became the player's current one. It
+/// ends when the player switches to another track, restarts it after it played to the end (repeat), stops, or is
+/// unregistered.
+#[derive(Debug, Default)]
+pub struct TrackEdgeDetector {
+    players: HashMap<ManagedPlayerId, PlayerTrack>,
+}
+
+impl TrackEdgeDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Follows the new `state` of a player, returning the edges it caused.
+    pub fn observe(&mut self, player_id: ManagedPlayerId, state: &PlayerState, now: SystemTime) -> Vec<PlayerEvent> {
+        let mut edges = Vec::new();
+        let track = self.players.entry(player_id).or_default();
+        track.advance(now);
+
+        let changed = !same_track(&track.texts, &state.texts);
+        let repeated = track.completed && !changed
+            && state.timeline.as_ref().is_some_and(|timeline| timeline.position < TRACK_END_MARGIN);
+        if track.started && (changed || repeated || state.status == FsctStatus::Stopped) {
+            edges.push(track.ended(player_id));
+            *track = PlayerTrack::new(state.texts.clone());
+        } else if changed {
+            *track = PlayerTrack::new(state.texts.clone());
+        } else {
+            track.texts = state.texts.clone();
+        }
+
+        track.timeline = state.timeline.clone();
+        let playing = state.status == FsctStatus::Playing;
+        track.playing_since = playing.then_some(now);
+        if playing && !track.started && state.texts.title.is_some() {
+            track.started = true;
+            edges.push(PlayerEvent::TrackStarted { player_id, track: state.texts.clone() });
+        }
+        edges
+    }
+
+    /// Forgets an unregistered player, returning the end of its track if one was playing.
+    pub fn remove(&mut self, player_id: ManagedPlayerId, now: SystemTime) -> Option<PlayerEvent> {
+        let mut track = self.players.remove(&player_id)?;
+        track.advance(now);
+        track.started.then(|| track.ended(player_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playing(title: &str, position: u64, now: SystemTime) -> PlayerState {
+        let mut state = PlayerState { status: FsctStatus::Playing, ..Default::default() };
+        state.texts.title = Some(title.to_string());
+        state.timeline = Some(TimelineInfo {
+            position: Duration::from_secs(position),
+            update_time: now,
+            duration: Some(Duration::from_secs(200)),
+            rate: 1.0,
+        });
+        state
+    }
+
+    #[test]
+    fn tracks_start_and_end_once() {
+        let player_id = ManagedPlayerId::new(1).unwrap();
+        let mut detector = TrackEdgeDetector::new();
+        let start = SystemTime::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        let edges = detector.observe(player_id, &playing("First", 0, at(0)), at(0));
+        assert!(matches!(&edges[..], [PlayerEvent::TrackStarted { track, .. }] if track.title.as_deref() == Some("First")));
+
+        // the artist arriving later belongs to the same track
+        let mut with_artist = playing("First", 10, at(10));
+        with_artist.texts.artist = Some("Band".to_string());
+        assert!(detector.observe(player_id, &with_artist, at(10)).is_empty());
+
+        let edges = detector.observe(player_id, &playing("Second", 0, at(60)), at(60));
+        match &edges[..] {
+            [PlayerEvent::TrackEnded { track, played, completed, .. }, PlayerEvent::TrackStarted { .. }] => {
+                assert_eq!(track.artist.as_deref(), Some("Band"));
+                assert_eq!((*played, *completed), (Duration::from_secs(60), false));
+            }
+            other => panic!("unexpected edges: {:?}", other),
+        }
+
+        // repeated after playing to the end
+        let edges = detector.observe(player_id, &playing("Second", 1, at(261)), at(261));
+        assert!(matches!(&edges[..], [PlayerEvent::TrackEnded { completed: true, .. }, PlayerEvent::TrackStarted { .. }]));
+
+        assert!(matches!(detector.remove(player_id, at(300)), Some(PlayerEvent::TrackEnded { completed: false, .. })));
+    }
+}
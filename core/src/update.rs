@@ -0,0 +1,345 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Self-update of the host, available with the `self-update` feature.
+//!
+//! Releases are described by a JSON manifest published per channel. Every artifact is signed with the release
+//! key together with the version and platform it is published for (see [`signed_message`]), and a release may be
+//! rolled out to a percentage of installations only; each installation falls into a stable bucket derived from its
+//! installation id.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, bail, Context, Error};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use log::info;
+pub use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Base URL of the published manifests (`<base>/<channel>.json`), baked in by release builds.
+const BUILD_MANIFEST_BASE_URL: Option<&str> = option_env!("FSCT_UPDATE_MANIFEST_URL");
+
+/// Hex encoded ed25519 public key verifying release artifacts, baked in by release builds.
+const BUILD_PUBLIC_KEY: Option<&str> = option_env!("FSCT_UPDATE_PUBLIC_KEY");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+impl UpdateChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => "stable",
+            UpdateChannel::Beta => "beta",
+        }
+    }
+}
+
+impl std::str::FromStr for UpdateChannel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "stable" => Ok(UpdateChannel::Stable),
+            "beta" => Ok(UpdateChannel::Beta),
+            _ => Err(anyhow!("Invalid update channel: {}", s)),
+        }
+    }
+}
+
+/// Release manifest of a channel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReleaseManifest {
+    pub releases: Vec<Release>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Release {
+    pub version: Version,
+    /// Percentage (0-100) of installations the release is offered to.
+    #[serde(default = "full_rollout")]
+    pub rollout_percent: u8,
+    #[serde(default)]
+    pub notes: String,
+    pub artifacts: Vec<Artifact>,
+}
+
+fn full_rollout() -> u8 {
+    100
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Artifact {
+    /// Platform the artifact installs on, see [`current_platform`].
+    pub platform: String,
+    pub url: String,
+    /// Hex encoded SHA-256 of the artifact.
+    pub sha256: String,
+    /// Hex encoded ed25519 signature of the [`signed_message`] of the artifact.
+    pub signature: String,
+}
+
+/// Update offered to this installation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AvailableUpdate {
+    pub version: Version,
+    pub notes: String,
+    pub artifact: Artifact,
+}
+
+/// Platform identifier used in manifests, e.g. `windows-x86_64` or `macos-aarch64`.
+///
+/// Static musl builds get their own artifacts (`linux-aarch64-musl`): a glibc build does not run on the embedded
+/// systems they are made for.
+pub fn current_platform() -> String {
+    let libc = if cfg!(target_env = "musl") { "-musl" } else { "" };
+    format!("{}-{}{}", std::env::consts::OS, std::env::consts::ARCH, libc)
+}
+
+/// Rollout bucket (0-99) of an installation; stable for a given installation id.
+pub fn rollout_bucket(installation_id: &str) -> u8 {
+    let digest = Sha256::digest(installation_id.as_bytes());
+    (u16::from_be_bytes([digest[0], digest[1]]) % 100) as u8
+}
+
+/// Picks the newest release newer than `current` that has an artifact for `platform` and is rolled out to `bucket`.
+pub fn select_update(manifest: &ReleaseManifest, current: &Version, platform: &str, bucket: u8) -> Option<AvailableUpdate> {
+    manifest
+        .releases
+        .iter()
+        .filter(|release| release.version > *current && bucket < release.rollout_percent)
+        .filter_map(|release| {
+            let artifact = release.artifacts.iter().find(|a| a.platform == platform)?;
+            Some(AvailableUpdate { version: release.version.clone(), notes: release.notes.clone(), artifact: artifact.clone() })
+        })
+        .max_by(|a, b| a.version.cmp(&b.version))
+}
+
+/// What the release key signs for an artifact of a release.
+///
+/// It binds the artifact bytes, through their checksum, to the version and platform they are published for, so
+/// that an older signed artifact cannot be offered as a newer release.
+pub fn signed_message(version: &Version, artifact: &Artifact) -> String {
+    format!("fsct-update\n{}\n{}\n{}\n", version, artifact.platform, artifact.sha256.to_ascii_lowercase())
+}
+
+/// Checks the artifact bytes against the checksum from the manifest, and the signature of the artifact against the
+/// release it is offered in.
+pub fn verify_artifact(data: &[u8], version: &Version, artifact: &Artifact, public_key: &[u8; 32])
+    -> Result<(), Error> {
+    let checksum = hex::encode(Sha256::digest(data));
+    if !checksum.eq_ignore_ascii_case(&artifact.sha256) {
+        bail!("Checksum mismatch: expected {}, got {}", artifact.sha256, checksum);
+    }
+    let key = VerifyingKey::from_bytes(public_key).context("Invalid release public key")?;
+    let signature_bytes: [u8; 64] = hex::decode(&artifact.signature)
+        .context("Invalid signature encoding")?
+        .try_into()
+        .map_err(|_| anyhow!("Invalid signature length"))?;
+    key.verify(signed_message(version, artifact).as_bytes(), &Signature::from_bytes(&signature_bytes))
+        .context("Signature verification failed")
+}
+
+/// Fetches manifests and downloads updates.
+pub struct Updater {
+    client: reqwest::Client,
+    manifest_base_url: String,
+    public_key: [u8; 32],
+}
+
+impl Updater {
+    /// Creates an updater using the manifest URL and public key the binary was built with.
+    ///
+    /// Fails for builds without `FSCT_UPDATE_MANIFEST_URL` and `FSCT_UPDATE_PUBLIC_KEY` set at compile time.
+    pub fn from_build_config() -> Result<Self, Error> {
+        let (Some(url), Some(key)) = (BUILD_MANIFEST_BASE_URL, BUILD_PUBLIC_KEY) else {
+            bail!("Self-update is not configured in this build");
+        };
+        let public_key: [u8; 32] = hex::decode(key)
+            .context("Invalid FSCT_UPDATE_PUBLIC_KEY")?
+            .try_into()
+            .map_err(|_| anyhow!("FSCT_UPDATE_PUBLIC_KEY must be 32 bytes"))?;
+        Ok(Self::with_source(url, public_key))
+    }
+
+    pub fn with_source(manifest_base_url: &str, public_key: [u8; 32]) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            manifest_base_url: manifest_base_url.trim_end_matches('/').to_string(),
+            public_key,
+        }
+    }
+
+    pub async fn fetch_manifest(&self, channel: UpdateChannel) -> Result<ReleaseManifest, Error> {
+        let url = format!("{}/{}.json", self.manifest_base_url, channel.as_str());
+        let response = self.client.get(&url).send().await?.error_for_status()?;
+        Ok(response.json().await?)
+    }
+
+    /// Returns the update offered to this installation on the given channel, if any.
+    pub async fn check(&self, channel: UpdateChannel, current: &Version, installation_id: &str)
+        -> Result<Option<AvailableUpdate>, Error> {
+        let manifest = self.fetch_manifest(channel).await?;
+        Ok(select_update(&manifest, current, &current_platform(), rollout_bucket(installation_id)))
+    }
+
+    /// Downloads and verifies the update artifact into `dir`, returning the path of the installer.
+    pub async fn download(&self, update: &AvailableUpdate, dir: &Path) -> Result<PathBuf, Error> {
+        let data = self.client.get(&update.artifact.url).send().await?.error_for_status()?.bytes().await?;
+        verify_artifact(&data, &update.version, &update.artifact, &self.public_key)?;
+        let path = dir.join(artifact_file_name(&update.artifact.url));
+        tokio::fs::write(&path, &data).await?;
+        info!("Downloaded update {} to {}", update.version, path.display());
+        Ok(path)
+    }
+}
+
+/// Name the artifact is saved under: the last segment of its URL if that is a plain file name, so that installers
+/// keep their extension, `fsct-update` otherwise.
+fn artifact_file_name(url: &str) -> &str {
+    let name = url.rsplit('/').next().unwrap_or_default();
+    let plain = name.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-'));
+    if plain && !name.trim_start_matches('.').is_empty() { name } else { "fsct-update" }
+}
+
+/// Location of the installation id, `installation-id` in the directory of the default config file:
+/// `%ProgramData%\FSCT` on Windows, `~/Library/Application Support/FSCT` on macOS and `$XDG_CONFIG_HOME/fsct`
+/// elsewhere.
+pub fn default_installation_id_path() -> PathBuf {
+    let env_dir = |name: &str| std::env::var_os(name).filter(|dir| !dir.is_empty()).map(PathBuf::from);
+    if cfg!(target_os = "windows") {
+        env_dir("PROGRAMDATA").unwrap_or_else(|| PathBuf::from("C:\\ProgramData")).join("FSCT").join("installation-id")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").unwrap_or_default().join("Library/Application Support/FSCT/installation-id")
+    } else {
+        env_dir("XDG_CONFIG_HOME")
+            .or_else(|| env_dir("HOME").map(|home| home.join(".config")))
+            .unwrap_or_else(|| PathBuf::from("/etc"))
+            .join("fsct/installation-id")
+    }
+}
+
+/// Id of this installation placing it in a rollout bucket, created at `path` on first use.
+pub fn installation_id(path: &Path) -> Result<String, Error> {
+    if let Ok(id) = std::fs::read_to_string(path)
+        && !id.trim().is_empty()
+    {
+        return Ok(id.trim().to_string());
+    }
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, &id).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(id)
+}
+
+/// Runs the downloaded installer; the installer replaces and restarts the service.
+pub fn install(installer: &Path) -> Result<(), Error> {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("msiexec");
+        command.arg("/i").arg(installer).args(["/quiet", "/norestart"]);
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("installer");
+        command.arg("-pkg").arg(installer).args(["-target", "/"]);
+        command
+    } else {
+        bail!("Self-update is not supported on this platform");
+    };
+    let status = command.status().context("Failed to start installer")?;
+    if !status.success() {
+        bail!("Installer failed: {}", status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn release(version: &str, rollout_percent: u8) -> Release {
+        Release {
+            version: Version::parse(version).unwrap(),
+            rollout_percent,
+            notes: String::new(),
+            artifacts: vec![Artifact {
+                platform: "windows-x86_64".into(),
+                url: format!("https://example.com/{}.msi", version),
+                sha256: String::new(),
+                signature: String::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn newest_rolled_out_release_is_selected() {
+        let manifest = ReleaseManifest { releases: vec![release("0.3.0", 100), release("0.4.0", 10), release("0.2.0", 100)] };
+        let current = Version::parse("0.2.13").unwrap();
+        assert_eq!(select_update(&manifest, &current, "windows-x86_64", 5).unwrap().version, Version::parse("0.4.0").unwrap());
+        assert_eq!(select_update(&manifest, &current, "windows-x86_64", 50).unwrap().version, Version::parse("0.3.0").unwrap());
+        assert!(select_update(&manifest, &current, "macos-aarch64", 5).is_none());
+        assert_eq!(rollout_bucket("install-1"), rollout_bucket("install-1"));
+    }
+
+    #[test]
+    fn artifacts_must_match_checksum_and_signature() {
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let public_key = signing_key.verifying_key().to_bytes();
+        let data = b"installer";
+        let version = Version::parse("0.3.0").unwrap();
+        let mut artifact = Artifact {
+            platform: current_platform(),
+            url: "https://example.com/installer".into(),
+            sha256: hex::encode(Sha256::digest(data)),
+            signature: String::new(),
+        };
+        let message = signed_message(&version, &artifact);
+        artifact.signature = hex::encode(signing_key.sign(message.as_bytes()).to_bytes());
+        assert!(verify_artifact(data, &version, &artifact, &public_key).is_ok());
+        assert!(verify_artifact(b"tampered", &version, &artifact, &public_key).is_err());
+        assert!(verify_artifact(data, &Version::parse("0.4.0").unwrap(), &artifact, &public_key).is_err());
+        let relabeled = Artifact { platform: "macos-aarch64".into(), ..artifact.clone() };
+        assert!(verify_artifact(data, &version, &relabeled, &public_key).is_err());
+        artifact.signature = hex::encode(SigningKey::from_bytes(&[8u8; 32]).sign(message.as_bytes()).to_bytes());
+        assert!(verify_artifact(data, &version, &artifact, &public_key).is_err());
+    }
+
+    #[test]
+    fn artifacts_are_saved_under_plain_file_names() {
+        assert_eq!(artifact_file_name("https://example.com/FSCTService-0.3.0.msi"), "FSCTService-0.3.0.msi");
+        assert_eq!(artifact_file_name("https://example.com/a/%2F.."), "fsct-update");
+        assert_eq!(artifact_file_name("https://example.com/.."), "fsct-update");
+        assert_eq!(artifact_file_name("https://example.com/"), "fsct-update");
+        assert_eq!(artifact_file_name("https://example.com/installer.pkg?token=1"), "fsct-update");
+    }
+
+    #[test]
+    fn installation_id_is_kept() {
+        let path = std::env::temp_dir().join(format!("fsct-installation-id-{}", uuid::Uuid::new_v4()));
+        let id = installation_id(&path).unwrap();
+        assert_eq!(installation_id(&path).unwrap(), id);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,312 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Aggregate usage statistics: how long devices displayed playback, how many tracks they showed and which
+//! sources (players) they were fed by.
+//!
+//! Recording is opt-in; a disabled [`UsageStats`] ignores all observations. With the `usage-stats` feature the
+//! statistics can be persisted to a [`Storage`](crate::storage::Storage), so vendors can analyze reliability of
+//! returned units.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::definitions::FsctStatus;
+use crate::device_manager::ManagedDeviceId;
+use crate::orchestrator::DisplayObserver;
+use crate::player_state::PlayerState;
+use crate::serde_format::{duration_secs, system_time_millis};
+
+/// Number of sources listed in the rendered report.
+pub const REPORT_TOP_SOURCES: usize = 5;
+
+/// Usage aggregated for a single device or source.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageCounters {
+    /// Time playback was displayed.
+    #[serde(with = "duration_secs")]
+    pub display_time: Duration,
+    pub tracks_shown: u64,
+}
+
+/// Usage statistics collected since `since`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageReport {
+    #[serde(with = "system_time_millis")]
+    pub since: SystemTime,
+    pub devices: BTreeMap<ManagedDeviceId, UsageCounters>,
+    /// Keyed by the player's self id.
+    pub sources: BTreeMap<String, UsageCounters>,
+}
+
+impl UsageReport {
+    pub fn new() -> Self {
+        Self { since: SystemTime::now(), devices: BTreeMap::new(), sources: BTreeMap::new() }
+    }
+
+    /// Sources ordered by display time, longest first.
+    pub fn top_sources(&self, count: usize) -> Vec<(&str, &UsageCounters)> {
+        let mut sources: Vec<_> = self.sources.iter().map(|(id, counters)| (id.as_str(), counters)).collect();
+        sources.sort_by(|a, b| b.1.display_time.cmp(&a.1.display_time).then(a.0.cmp(b.0)));
+        sources.truncate(count);
+        sources
+    }
+
+    fn add(&mut self, device_id: ManagedDeviceId, source: Option<&str>, display_time: Duration, tracks_shown: u64) {
+        let counters = self.devices.entry(device_id).or_default();
+        counters.display_time += display_time;
+        counters.tracks_shown += tracks_shown;
+        if let Some(source) = source {
+            let counters = self.sources.entry(source.to_string()).or_default();
+            counters.display_time += display_time;
+            counters.tracks_shown += tracks_shown;
+        }
+    }
+}
+
+impl Default for UsageReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn format_hours(duration: Duration) -> String {
+    format!("{:.1} h", duration.as_secs_f64() / 3600.0)
+}
+
+impl Display for UsageReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let since = self.since.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+        writeln!(f, "Usage since {} s after Unix epoch", since.as_secs())?;
+        writeln!(f, "Devices:")?;
+        if self.devices.is_empty() {
+            writeln!(f, "  none")?;
+        }
+        for (device_id, counters) in &self.devices {
+            writeln!(f, "  {}: {} displayed, {} tracks shown", device_id, format_hours(counters.display_time),
+                     counters.tracks_shown)?;
+        }
+        writeln!(f, "Top sources:")?;
+        if self.sources.is_empty() {
+            writeln!(f, "  none")?;
+        }
+        for (source, counters) in self.top_sources(REPORT_TOP_SOURCES) {
+            writeln!(f, "  {}: {} displayed, {} tracks shown", source, format_hours(counters.display_time),
+                     counters.tracks_shown)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct DeviceDisplay {
+    source: Option<String>,
+    title: Option<String>,
+    playing_since: Option<Instant>,
+}
+
+/// Collects usage statistics from what the orchestrator shows on devices.
+pub struct UsageStats {
+    enabled: AtomicBool,
+    report: Mutex<UsageReport>,
+    displays: Mutex<HashMap<ManagedDeviceId, DeviceDisplay>>,
+}
+
+impl UsageStats {
+    /// Creates statistics starting from `report`; nothing is recorded unless `enabled`.
+    pub fn new(report: UsageReport, enabled: bool) -> Self {
+        Self { enabled: AtomicBool::new(enabled), report: Mutex::new(report), displays: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Opts in or out of recording; collected statistics are kept either way.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.accrue();
+            self.displays.lock().unwrap().clear();
+        }
+    }
+
+    /// Statistics collected so far, including ongoing display time.
+    pub fn report(&self) -> UsageReport {
+        self.accrue();
+        self.report.lock().unwrap().clone()
+    }
+
+    /// Drops all collected statistics and starts over.
+    pub fn clear(&self) {
+        self.accrue();
+        *self.report.lock().unwrap() = UsageReport::new();
+    }
+
+    fn accrue(&self) {
+        let now = Instant::now();
+        let mut displays = self.displays.lock().unwrap();
+        let mut report = self.report.lock().unwrap();
+        for (device_id, display) in displays.iter_mut() {
+            if let Some(since) = display.playing_since.as_mut() {
+                report.add(*device_id, display.source.as_deref(), now - *since, 0);
+                *since = now;
+            }
+        }
+    }
+}
+
+impl DisplayObserver for UsageStats {
+    /// Records what the device shows now; `source` is the self id of the player the state comes from.
+    fn observe(&self, device_id: ManagedDeviceId, source: Option<&str>, state: &PlayerState) {
+        if !self.is_enabled() {
+            return;
+        }
+        let now = Instant::now();
+        let mut displays = self.displays.lock().unwrap();
+        let display = displays.entry(device_id).or_default();
+        let mut report = self.report.lock().unwrap();
+
+        if let Some(since) = display.playing_since.take() {
+            report.add(device_id, display.source.as_deref(), now - since, 0);
+        }
+        let title = state.texts.title.as_ref();
+        if title.is_some() && display.title.as_ref() != title {
+            report.add(device_id, source, Duration::ZERO, 1);
+        }
+        display.source = source.map(|s| s.to_string());
+        display.title = title.cloned();
+        display.playing_since = (state.status == FsctStatus::Playing).then_some(now);
+    }
+
+    /// Records that the device shows no playback (nothing routed, auxiliary content or switched off).
+    ///
+    /// The last shown title is remembered, so resuming the same track doesn't count it again.
+    fn device_idle(&self, device_id: ManagedDeviceId) {
+        if let Some(display) = self.displays.lock().unwrap().get_mut(&device_id)
+            && let Some(since) = display.playing_since.take() {
+            self.report.lock().unwrap().add(device_id, display.source.as_deref(), since.elapsed(), 0);
+        }
+    }
+
+    /// Records that the device was detached, ending its display time.
+    fn device_detached(&self, device_id: ManagedDeviceId) {
+        if let Some(display) = self.displays.lock().unwrap().remove(&device_id)
+            && let Some(since) = display.playing_since {
+            self.report.lock().unwrap().add(device_id, display.source.as_deref(), since.elapsed(), 0);
+        }
+    }
+}
+
+#[cfg(feature = "usage-stats")]
+mod persistence {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use log::warn;
+
+    use super::{UsageReport, UsageStats};
+    use crate::service::{spawn_service, ServiceHandle};
+    use crate::storage::{Storage, StorageError, StorageExt};
+
+    /// Storage key of the usage statistics.
+    pub const USAGE_STATS_KEY: &str = "usage_stats";
+
+    /// Default interval of writing statistics to storage.
+    pub const DEFAULT_USAGE_FLUSH_INTERVAL: Duration = Duration::from_secs(300);
+
+    impl UsageReport {
+        /// Reads the report saved by [`UsageReport::save`]; yields an empty report if none was saved yet.
+        pub fn load(storage: &dyn Storage) -> Result<Self, StorageError> {
+            Ok(storage.load(USAGE_STATS_KEY)?.unwrap_or_default())
+        }
+
+        pub fn save(&self, storage: &dyn Storage) -> Result<(), StorageError> {
+            storage.save(USAGE_STATS_KEY, self)
+        }
+    }
+
+    impl UsageStats {
+        /// Continues statistics saved in `storage`.
+        pub fn open(storage: &dyn Storage, enabled: bool) -> Result<Self, StorageError> {
+            Ok(Self::new(UsageReport::load(storage)?, enabled))
+        }
+    }
+
+    /// Saves the statistics every `interval` and once more when stopped; nothing is written while recording is
+    /// disabled.
+    pub fn run_usage_stats_flush(stats: Arc<UsageStats>, storage: Arc<dyn Storage>, interval: Duration)
+                                 -> ServiceHandle {
+        spawn_service(move |mut stop_handle| async move {
+            loop {
+                let stopped = tokio::select! {
+                    _ = stop_handle.signaled() => true,
+                    _ = tokio::time::sleep(interval) => false,
+                };
+                if stats.is_enabled() && let Err(e) = stats.report().save(storage.as_ref()) {
+                    warn!("Failed to save usage statistics: {}", e);
+                }
+                if stopped {
+                    break;
+                }
+            }
+        })
+    }
+}
+
+#[cfg(feature = "usage-stats")]
+pub use persistence::{run_usage_stats_flush, DEFAULT_USAGE_FLUSH_INTERVAL, USAGE_STATS_KEY};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn playing(title: &str) -> PlayerState {
+        let mut state = PlayerState { status: FsctStatus::Playing, ..Default::default() };
+        state.texts.title = Some(title.to_string());
+        state
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn playback_time_and_tracks_are_counted_per_device_and_source() {
+        let stats = UsageStats::new(UsageReport::new(), true);
+        let device = Uuid::new_v4();
+
+        stats.observe(device, Some("spotify"), &playing("Song A"));
+        tokio::time::advance(Duration::from_secs(60)).await;
+        stats.observe(device, Some("spotify"), &playing("Song B"));
+        tokio::time::advance(Duration::from_secs(30)).await;
+        stats.observe(device, Some("spotify"), &PlayerState { status: FsctStatus::Paused, ..playing("Song B") });
+        tokio::time::advance(Duration::from_secs(600)).await;
+
+        let report = stats.report();
+        let expected = UsageCounters { display_time: Duration::from_secs(90), tracks_shown: 2 };
+        assert_eq!(report.devices.get(&device), Some(&expected));
+        assert_eq!(report.top_sources(REPORT_TOP_SOURCES), vec![("spotify", &expected)]);
+
+        stats.set_enabled(false);
+        stats.observe(device, Some("spotify"), &playing("Song C"));
+        assert_eq!(stats.report().devices.get(&device), Some(&expected));
+    }
+}
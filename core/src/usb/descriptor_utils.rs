@@ -15,12 +15,14 @@
 // This file is part of an implementation of Ferrum Streaming Control Technology™,
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
+use std::fmt::Debug;
 use std::mem::size_of;
 use nusb::descriptors::Descriptor;
 use nusb::{Interface};
 use log::warn;
 use nusb::transfer::{ControlIn, ControlType, Recipient};
-use crate::usb::descriptors::{FsctFunctionalityDescriptor, FsctImageMetadataDescriptor, FsctTextMetadataDescriptor, FsctTextMetadataDescriptorHeader, FsctTextMetadataDescriptorMultiPart, FSCT_FUNCTIONALITY_DESCRIPTOR_ID, FSCT_IMAGE_METADATA_DESCRIPTOR_ID, FSCT_TEXT_METADATA_DESCRIPTOR_ID};
+use crate::usb::descriptors::{FsctExtendedFunctionalityDescriptor, FsctFunctionalityDescriptor, FsctImageMetadataDescriptor, FsctSpectrumDescriptor, FsctTextMetadataDescriptor, FsctTextMetadataDescriptorHeader, FsctTextMetadataDescriptorMultiPart, FSCT_EXTENDED_FUNCTIONALITY_DESCRIPTOR_ID, FSCT_FUNCTIONALITY_DESCRIPTOR_ID, FSCT_IMAGE_METADATA_DESCRIPTOR_ID, FSCT_SPECTRUM_DESCRIPTOR_ID, FSCT_TEXT_METADATA_DESCRIPTOR_ID};
+use crate::descriptor_dump::DescriptorDump;
 use crate::usb::errors::{DescriptorError, IoErrorOrAny};
 
 async fn get_interface_descriptor(interface: &Interface,
@@ -45,7 +47,7 @@ async fn get_interface_descriptor(interface: &Interface,
 
 const FSCT_FUNCTIONALITY_DESCRIPTOR_SIZE: usize = size_of::<FsctFunctionalityDescriptor>();
 
-async fn get_fsct_functionality_descriptor_set_raw(interface: &Interface) -> Result<Vec<u8>, IoErrorOrAny>
+pub(crate) async fn get_fsct_functionality_descriptor_set_raw(interface: &Interface) -> Result<Vec<u8>, IoErrorOrAny>
 {
     let descriptor = get_interface_descriptor(
         interface,
@@ -79,12 +81,21 @@ pub enum FsctDescriptorSet {
     Functionality(FsctFunctionalityDescriptor),
     ImageMetadata(FsctImageMetadataDescriptor),
     TextMetadata(FsctTextMetadataDescriptor),
+    Spectrum(FsctSpectrumDescriptor),
+    ExtendedFunctionality(FsctExtendedFunctionalityDescriptor),
 }
 
 pub async fn get_fsct_functionality_descriptor_set(interface: &Interface) -> Result<Vec<FsctDescriptorSet>, IoErrorOrAny>
 {
     let raw_descriptor = get_fsct_functionality_descriptor_set_raw(interface).await?;
-    let descriptors = Descriptors(&raw_descriptor);
+    parse_fsct_descriptor_set(&raw_descriptor)
+}
+
+/// Parses the FSCT functionality descriptor with its subordinate descriptors, as read from the interface or sent by
+/// a network device in its hello; unknown descriptors are skipped.
+pub fn parse_fsct_descriptor_set(raw_descriptor: &[u8]) -> Result<Vec<FsctDescriptorSet>, IoErrorOrAny>
+{
+    let descriptors = Descriptors(raw_descriptor);
     let mut fsct_descriptors = Vec::new();
     for descriptor in descriptors {
         match descriptor.descriptor_type() {
@@ -100,12 +111,61 @@ pub async fn get_fsct_functionality_descriptor_set(interface: &Interface) -> Res
                 let fsct_descriptor: FsctTextMetadataDescriptor = descriptor.try_into()?;
                 fsct_descriptors.push(FsctDescriptorSet::TextMetadata(fsct_descriptor));
             }
+            FSCT_SPECTRUM_DESCRIPTOR_ID => {
+                let fsct_descriptor: FsctSpectrumDescriptor = descriptor.try_into()?;
+                fsct_descriptors.push(FsctDescriptorSet::Spectrum(fsct_descriptor));
+            }
+            FSCT_EXTENDED_FUNCTIONALITY_DESCRIPTOR_ID => {
+                let fsct_descriptor: FsctExtendedFunctionalityDescriptor = descriptor.try_into()?;
+                fsct_descriptors.push(FsctDescriptorSet::ExtendedFunctionality(fsct_descriptor));
+            }
             _ => {}
         }
     }
     Ok(fsct_descriptors)
 }
 
+/// Dumps the FSCT functionality descriptor set descriptor by descriptor, with what [`parse_fsct_descriptor_set`]
+/// makes of each; bytes that don't form a descriptor are dumped last.
+pub fn dump_fsct_descriptor_set(raw_descriptor: &[u8]) -> Vec<DescriptorDump> {
+    let mut descriptors = Descriptors(raw_descriptor);
+    let mut dumps = Vec::new();
+    for descriptor in descriptors.by_ref() {
+        let raw = descriptor.to_vec();
+        let (name, decoded) = match descriptor.descriptor_type() {
+            FSCT_FUNCTIONALITY_DESCRIPTOR_ID => {
+                ("FSCT functionality", decode::<FsctFunctionalityDescriptor>(descriptor))
+            }
+            FSCT_IMAGE_METADATA_DESCRIPTOR_ID => {
+                ("FSCT image metadata", decode::<FsctImageMetadataDescriptor>(descriptor))
+            }
+            FSCT_TEXT_METADATA_DESCRIPTOR_ID => {
+                ("FSCT text metadata", decode::<FsctTextMetadataDescriptor>(descriptor))
+            }
+            FSCT_SPECTRUM_DESCRIPTOR_ID => ("FSCT spectrum", decode::<FsctSpectrumDescriptor>(descriptor)),
+            FSCT_EXTENDED_FUNCTIONALITY_DESCRIPTOR_ID => {
+                ("FSCT extended functionality", decode::<FsctExtendedFunctionalityDescriptor>(descriptor))
+            }
+            other => ("Unknown", format!("Descriptor type {:#04x}, skipped", other)),
+        };
+        dumps.push(DescriptorDump::new(name, &raw, decoded));
+    }
+    if !descriptors.0.is_empty() {
+        dumps.push(DescriptorDump::new("Malformed", descriptors.0, "bLength doesn't fit the remaining bytes, skipped"));
+    }
+    dumps
+}
+
+fn decode<'a, T>(descriptor: Descriptor<'a>) -> String
+where
+    T: TryFrom<Descriptor<'a>, Error = DescriptorError> + Debug,
+{
+    match T::try_from(descriptor) {
+        Ok(decoded) => format!("{:#?}", decoded),
+        Err(e) => format!("Invalid: {}", e),
+    }
+}
+
 // Copied from nusb::descriptors::Descriptors, because it is not public
 /// An iterator over a sequence of USB descriptors.
 #[derive(Clone)]
@@ -183,6 +243,38 @@ impl TryFrom<Descriptor<'_>> for FsctImageMetadataDescriptor {
     }
 }
 
+impl TryFrom<Descriptor<'_>> for FsctSpectrumDescriptor {
+    type Error = DescriptorError;
+    fn try_from(value: Descriptor<'_>) -> Result<Self, Self::Error> {
+        if value.descriptor_type() != FSCT_SPECTRUM_DESCRIPTOR_ID {
+            return Err(DescriptorError::NotFsctSpectrumDescriptor);
+        }
+        if value.len() != size_of::<FsctSpectrumDescriptor>() {
+            return Err(DescriptorError::TooShort);
+        }
+        let fsct_spectrum_descriptor: FsctSpectrumDescriptor = unsafe {
+            *std::mem::transmute::<*const u8, &FsctSpectrumDescriptor>(value.as_ptr())
+        };
+        Ok(fsct_spectrum_descriptor)
+    }
+}
+
+impl TryFrom<Descriptor<'_>> for FsctExtendedFunctionalityDescriptor {
+    type Error = DescriptorError;
+    fn try_from(value: Descriptor<'_>) -> Result<Self, Self::Error> {
+        if value.descriptor_type() != FSCT_EXTENDED_FUNCTIONALITY_DESCRIPTOR_ID {
+            return Err(DescriptorError::NotFsctExtendedFunctionalityDescriptor);
+        }
+        if value.len() != size_of::<FsctExtendedFunctionalityDescriptor>() {
+            return Err(DescriptorError::TooShort);
+        }
+        let fsct_extended_functionality_descriptor: FsctExtendedFunctionalityDescriptor = unsafe {
+            *std::mem::transmute::<*const u8, &FsctExtendedFunctionalityDescriptor>(value.as_ptr())
+        };
+        Ok(fsct_extended_functionality_descriptor)
+    }
+}
+
 const FSCT_TEXT_METADATA_DESCRIPTOR_HEADER_SIZE: usize = size_of::<FsctTextMetadataDescriptorHeader>();
 
 impl TryFrom<Descriptor<'_>> for FsctTextMetadataDescriptor {
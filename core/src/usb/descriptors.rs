@@ -15,11 +15,14 @@
 // This file is part of an implementation of Ferrum Streaming Control Technology™,
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
-use crate::definitions::{FsctFunctionality, FsctImagePixelFormat, FsctTextEncoding, FsctTextMetadata};
+use crate::definitions::{FsctExtendedFunctionality, FsctFunctionality, FsctImagePixelFormat, FsctTextEncoding,
+                         FsctTextMetadata};
 
 pub const FSCT_FUNCTIONALITY_DESCRIPTOR_ID: u8 = 0x31;
 pub const FSCT_TEXT_METADATA_DESCRIPTOR_ID: u8 = 0x32;
 pub const FSCT_IMAGE_METADATA_DESCRIPTOR_ID: u8 = 0x33;
+pub const FSCT_SPECTRUM_DESCRIPTOR_ID: u8 = 0x34;
+pub const FSCT_EXTENDED_FUNCTIONALITY_DESCRIPTOR_ID: u8 = 0x35;
 
 #[repr(C, packed)]
 #[derive(Debug, Default, Clone, Copy)]
@@ -69,3 +72,22 @@ pub struct FsctImageMetadataDescriptor {
     pub bPixelFormat: FsctImagePixelFormat, // Updated type
 }
 
+/// Announces a spectrum display taking band energies on the level endpoint of the FSCT interface.
+#[repr(C, packed)]
+#[derive(Debug, Default, Clone, Copy)]
+#[allow(non_snake_case)]
+pub struct FsctSpectrumDescriptor {
+    pub bLength: u8,
+    pub bDescriptorType: u8,
+    pub bBandCount: u8,
+}
+
+/// Announces functionalities beyond the bits of the functionality descriptor.
+#[repr(C, packed)]
+#[derive(Debug, Default, Clone, Copy)]
+#[allow(non_snake_case)]
+pub struct FsctExtendedFunctionalityDescriptor {
+    pub bLength: u8,
+    pub bDescriptorType: u8,
+    pub bmFunctionality: FsctExtendedFunctionality,
+}
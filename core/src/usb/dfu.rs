@@ -0,0 +1,96 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Firmware updates of FSCT devices over their FSCT interface.
+//!
+//! Devices announcing [`FsctExtendedFunctionality::FirmwareUpdate`](crate::definitions::FsctExtendedFunctionality)
+//! (protocol version 2) take a firmware image with three requests: `dfuBegin` announces the length of the image, the
+//! image follows in `dfuChunk` requests of at most [`DFU_CHUNK_SIZE`] bytes, and `dfuFinish` carries its CRC-32.
+//! The device verifies the image, applies it and restarts, attaching anew; the `firmwareVersion` request tells
+//! which firmware it runs afterwards, and an update leaving the version unchanged counts as failed. A failed update
+//! is aborted with `dfuAbort`, returning the device to normal operation.
+//!
+//! Images are opaque to the host: which image suits which device model is up to whoever runs the update.
+
+use std::time::Duration;
+
+use crate::definitions::DfuProgress;
+use crate::usb::errors::FsctDeviceError;
+use crate::usb::fsct_interface::FsctInterface;
+use crate::usb::requests::{FsctRequestCode, DFU_CHUNK_SIZE};
+
+/// Timeout of `dfuBegin`, which may erase the flash, and `dfuFinish`, which writes it.
+pub const DFU_FLASH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a device may take to detach after `dfuFinish`; a device still attached afterwards did not restart into
+/// the new firmware.
+pub const DFU_DETACH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a device may take to attach again after detaching to apply the image.
+pub const DFU_RESTART_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Streams `image` to the device, reporting progress after each chunk; aborts the update on the device if it fails.
+pub(crate) async fn stream_image(interface: &FsctInterface, image: &[u8],
+                                 progress: &mut (dyn FnMut(DfuProgress) + Send)) -> Result<(), FsctDeviceError> {
+    let length = u32::try_from(image.len()).ok().filter(|length| *length > 0).ok_or_else(|| {
+        FsctDeviceError::InvalidFirmwareImage(format!("length of {} bytes out of range", image.len()))
+    })?;
+    interface.send_dfu_request(FsctRequestCode::DfuBegin, 0, &length.to_le_bytes(), DFU_FLASH_TIMEOUT).await?;
+    let result = send_chunks_and_finish(interface, image, progress).await;
+    if result.is_err() {
+        let abort = interface.send_dfu_request(FsctRequestCode::DfuAbort, 0, &[], interface.timeouts().control).await;
+        if let Err(e) = abort {
+            log::warn!("Failed to abort firmware update: {}", e);
+        }
+    }
+    result
+}
+
+async fn send_chunks_and_finish(interface: &FsctInterface, image: &[u8],
+                                progress: &mut (dyn FnMut(DfuProgress) + Send)) -> Result<(), FsctDeviceError> {
+    let total = image.len();
+    progress(DfuProgress { sent: 0, total });
+    for (sequence, chunk) in image.chunks(DFU_CHUNK_SIZE).enumerate() {
+        interface.send_dfu_request(FsctRequestCode::DfuChunk, sequence as u16, chunk, interface.timeouts().control)
+                 .await?;
+        progress(DfuProgress { sent: sequence * DFU_CHUNK_SIZE + chunk.len(), total });
+    }
+    interface.send_dfu_request(FsctRequestCode::DfuFinish, 0, &crc32(image).to_le_bytes(), DFU_FLASH_TIMEOUT).await
+}
+
+/// CRC-32 of `data`, the IEEE 802.3 one of zip and PNG.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_matches_the_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(&[]), 0);
+    }
+}
@@ -132,6 +132,12 @@ pub enum DescriptorError {
     #[error("Not a FSCT text metadata descriptor")]
     NotFsctTextMetadataDescriptor,
 
+    #[error("Not a FSCT spectrum descriptor")]
+    NotFsctSpectrumDescriptor,
+
+    #[error("Not a FSCT extended functionality descriptor")]
+    NotFsctExtendedFunctionalityDescriptor,
+
     #[error("Descriptor is too short")]
     TooShort,
 }
@@ -156,11 +162,54 @@ pub enum FsctDeviceError {
     #[error("USB control transfer failed: {0}")]
     UsbControlTransferError(#[source] anyhow::Error),
 
+    #[error("USB control transfer timed out after {0:?}")]
+    UsbControlTransferTimeout(std::time::Duration),
+
+    #[error("USB interrupt transfer failed: {0}")]
+    UsbInterruptTransferError(#[source] anyhow::Error),
+
+    #[cfg(feature = "network")]
+    #[error("Network request failed: {0}")]
+    NetworkRequestError(#[source] anyhow::Error),
+
+    #[cfg(feature = "network")]
+    #[error("Network request timed out after {0:?}")]
+    NetworkRequestTimeout(std::time::Duration),
+
+    #[error("Device does not send playback commands")]
+    PlaybackCommandsNotSupported,
+
+    #[cfg(feature = "audio-levels")]
+    #[error("Device does not take audio levels")]
+    AudioLevelsNotSupported,
+
+    #[cfg(feature = "spectrum")]
+    #[error("Device does not show a spectrum")]
+    SpectrumNotSupported,
+
     #[error("Expected {expected} bytes, got {actual}")]
     DataSizeMismatch {
         expected: usize,
         actual: usize,
     },
+
+    #[error("Invalid vendor request: {0}")]
+    InvalidVendorRequest(String),
+
+    #[error("Device does not take firmware updates")]
+    FirmwareUpdateNotSupported,
+
+    #[error("Invalid firmware image: {0}")]
+    InvalidFirmwareImage(String),
+
+    #[error("A firmware update of the device is already in progress")]
+    FirmwareUpdateInProgress,
+
+    #[error("Device did not restart after the firmware update")]
+    FirmwareNotRestarted,
+
+    #[error("Device still runs firmware version {0:#06x} after the update")]
+    FirmwareNotUpdated(u16),
 }
 
 pub trait ToFsctDeviceError {
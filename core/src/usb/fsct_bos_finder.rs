@@ -29,6 +29,7 @@ struct BosDescriptor {
     bNumDeviceCaps: u8,
 }
 
+use crate::descriptor_dump::DescriptorDump;
 use crate::usb::errors::{BosError, IoErrorOrAny};
 
 #[repr(u8)]
@@ -185,11 +186,10 @@ struct FSCTCapabilityDesc {
     vendorSubClassNumber: u8,
 }
 
-const FSCT_CAPABILITY_DESCRIPTOR_VERSION: u16 = 0x0100;
+pub(crate) const FSCT_CAPABILITY_DESCRIPTOR_VERSION: u16 = 0x0100;
 const FSCT_UUID: Uuid = Uuid::from_u128(0xc433beeb_8d00_4420_9515_bcb7faf38a41);
 
 #[derive(Debug, Copy, Clone)]
-#[allow(dead_code)]
 struct FSCTCapability {
     vendor_sub_class_number: u8,
     version: (u8, u8),
@@ -228,19 +228,74 @@ fn get_fsct_vendor_subclass_number(
     Ok(get_fsct_capability(platform_capabilities)?.vendor_sub_class_number)
 }
 
-pub fn get_fsct_vendor_subclass_number_from_device(
-    device: &DeviceInfo,
-) -> Result<u8, IoErrorOrAny> {
+/// Reads the BOS descriptor of the device with its device capabilities.
+pub fn read_bos_descriptor(device: &DeviceInfo) -> Result<Vec<u8>, IoErrorOrAny> {
     if device.usb_version() <= 0x0200 {
         return Err(BosError::NotAvailable(device.usb_version()).into());
     }
 
     let handle = device.open()?;
-    let desc = handle
-        .get_descriptor(15, 0, 0, Duration::from_secs(1))?;
-    let bos_desc = decode_bos_descriptor_with_capabilities(&desc)?;
+    Ok(handle.get_descriptor(15, 0, 0, Duration::from_secs(1))?)
+}
+
+pub fn get_fsct_vendor_subclass_number_from_bos(bos: &[u8]) -> Result<u8, BosError> {
+    let bos_desc = decode_bos_descriptor_with_capabilities(bos)?;
     let platform_caps = get_platform_capabilities(bos_desc)?;
-    Ok(get_fsct_vendor_subclass_number(platform_caps)?)
+    get_fsct_vendor_subclass_number(platform_caps)
+}
+
+pub fn get_fsct_vendor_subclass_number_from_device(
+    device: &DeviceInfo,
+) -> Result<u8, IoErrorOrAny> {
+    Ok(get_fsct_vendor_subclass_number_from_bos(&read_bos_descriptor(device)?)?)
+}
+
+/// Dumps the BOS descriptor capability by capability; what follows a malformed capability is dumped undecoded.
+pub fn dump_bos_descriptor(data: &[u8]) -> Vec<DescriptorDump> {
+    let descriptor = match decode_bos_descriptor(data) {
+        Ok(descriptor) => descriptor,
+        Err(e) => return vec![DescriptorDump::new("BOS", data, format!("Invalid: {}", e))],
+    };
+    let (total_length, num_caps) = (descriptor.wTotalLength, descriptor.bNumDeviceCaps);
+    let header_end = (descriptor.bLength as usize).min(data.len());
+    let end = (total_length as usize).clamp(header_end, data.len());
+    let mut dumps = vec![DescriptorDump::new("BOS", &data[..header_end],
+                                             format!("wTotalLength {}, bNumDeviceCaps {}", total_length, num_caps))];
+    let mut offset = header_end;
+    for _ in 0..num_caps {
+        match decode_bos_capability(&data[offset..end]) {
+            Ok(capability) => {
+                let name = format!("{:?} capability", capability.capability);
+                dumps.push(DescriptorDump::new(name, &data[offset..offset + capability.length],
+                                               describe_capability(&capability)));
+                offset += capability.length;
+            }
+            Err(e) => {
+                dumps.push(DescriptorDump::new("Capability", &data[offset..end], format!("Invalid: {}", e)));
+                break;
+            }
+        }
+    }
+    dumps
+}
+
+fn describe_capability(capability: &BosCapabilityDescWithData) -> String {
+    if !matches!(capability.capability, BosCapabilityType::Platform) {
+        return format!("{} bytes of capability data", capability.data.len());
+    }
+    let platform_caps = match get_platform_capabilities(vec![capability.clone()]) {
+        Ok(platform_caps) => platform_caps,
+        Err(e) => return format!("Invalid: {}", e),
+    };
+    let uuid = platform_caps[0].uuid;
+    if uuid != FSCT_UUID {
+        return format!("Platform capability {}", uuid);
+    }
+    match get_fsct_capability(platform_caps) {
+        Ok(fsct) => format!("FSCT platform capability {}, version {}.{}, vendor subclass {:#04x}", uuid,
+                            fsct.version.0, fsct.version.1, fsct.vendor_sub_class_number),
+        Err(e) => format!("FSCT platform capability {}, invalid: {}", uuid, e),
+    }
 }
 
 #[cfg(test)]
@@ -460,4 +515,22 @@ mod tests {
             Err(BosError::NotFsctCapability)
         ));
     }
+
+    #[test]
+    fn test_bos_dump() {
+        let mut data = create_bos_descriptor(34, 3);
+        data.extend(create_capability_descriptor(BosCapabilityType::SuperspeedUsb as u8, &[1, 2, 3]));
+        data.extend(create_capability_descriptor(BosCapabilityType::Platform as u8, &FSCT_PLATFORM_CAPABILITY_DATA));
+
+        let dumps = dump_bos_descriptor(&data);
+
+        assert_eq!(dumps.len(), 4);
+        assert_eq!((dumps[0].raw.as_str(), dumps[0].decoded.as_str()),
+                   ("05 0f 22 00 03", "wTotalLength 34, bNumDeviceCaps 3"));
+        assert_eq!((dumps[1].name.as_str(), dumps[1].raw.as_str()), ("SuperspeedUsb capability", "06 10 03 01 02 03"));
+        assert_eq!(dumps[2].decoded,
+                   "FSCT platform capability c433beeb-8d00-4420-9515-bcb7faf38a41, version 1.0, vendor subclass 0x42");
+        // the third capability is missing
+        assert!(dumps[3].decoded.starts_with("Invalid"));
+    }
 }
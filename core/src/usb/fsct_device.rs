@@ -15,14 +15,24 @@
 // This file is part of an implementation of Ferrum Streaming Control Technology™,
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
+use std::borrow::Cow;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use crate::definitions::TimelineInfo;
-use crate::definitions::{FsctFunctionality, FsctTextEncoding, FsctTextMetadata};
+use unicode_segmentation::UnicodeSegmentation;
+use crate::definitions::{DfuProgress, FsctExtendedFunctionality, FsctStatus, PlaybackCommand, PlaybackModes, TimelineInfo, VolumeInfo};
+use crate::definitions::{DeviceErrorReport, DeviceLimits, FsctDeviceErrorCode, FsctFunctionality, FsctNotification, FsctTextEncoding, FsctTextMetadata, SupportedText, UsbRequestTimeouts};
+#[cfg(feature = "vendor-requests")]
+use crate::definitions::{VendorRequest, FIRST_VENDOR_REQUEST_CODE};
+use crate::descriptor_dump::DeviceDescriptorDump;
+use crate::device_manager::ManagedDeviceId;
 use crate::usb::descriptor_utils::FsctDescriptorSet;
+use crate::usb::dfu;
 use crate::usb::errors::FsctDeviceError;
-use crate::usb::fsct_usb_interface::FsctUsbInterface;
-use crate::usb::requests::TrackProgressRequestData;
+use crate::usb::fsct_interface::FsctInterface;
+use crate::usb::protocol::FsctProtocol;
+use crate::usb::requests::{self, FsctCommandCode, TrackProgressRequestData};
+use crate::quirks::DeviceQuirks;
+use crate::warmup::WarmupStep;
 
 
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
@@ -31,53 +41,175 @@ struct SupportedMetadata {
     pub max_length: usize,
 }
 
+/// Number of `poll` requests timed per latency measurement; the fastest counts, the others took longer because of
+/// host scheduling or other transfers.
+const LATENCY_SAMPLES: usize = 3;
+
 struct FsctDeviceSharedState {
     time_diff: Option<Duration>,
+    /// Measured write latency, if the quirks ask for it.
+    write_latency: Option<Duration>,
     fsct_text_encoding: FsctTextEncoding,
     supported_current_texts: Vec<SupportedMetadata>,
     supported_functionalities: FsctFunctionality,
+    extended_functionalities: FsctExtendedFunctionality,
+    quirks: DeviceQuirks,
+    /// A firmware update is in progress; periodic time synchronization leaves the device alone.
+    updating: bool,
+    /// Band count of the spectrum display, if the device announced one.
+    #[cfg(feature = "spectrum")]
+    spectrum_bands: Option<u8>,
+}
+
+impl FsctDeviceSharedState {
+    fn max_text_length(&self, supported: &SupportedMetadata) -> usize {
+        self.quirks.max_text_length.map_or(supported.max_length, |max| max.min(supported.max_length))
+    }
+}
+
+/// FSCT capabilities a device announced in its descriptors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FsctDeviceCapabilities {
+    pub functionality: FsctFunctionality,
+    /// Extended functionalities whose requests are in the protocol of the device.
+    pub extended_functionality: FsctExtendedFunctionality,
+    pub text_encoding: FsctTextEncoding,
+    pub supported_texts: Vec<(FsctTextMetadata, usize)>,
 }
+
 pub struct FsctDevice {
-    fsct_interface: Arc<FsctUsbInterface>,
+    fsct_interface: Arc<FsctInterface>,
     time_sync_handle: Option<tokio::task::JoinHandle<()>>,
     state: Arc<Mutex<FsctDeviceSharedState>>,
+    descriptor_dump: DeviceDescriptorDump,
+    protocol: FsctProtocol,
 }
 
 impl FsctDevice {
-    pub(super) fn new(fsct_interface: FsctUsbInterface) -> Self {
+    pub(crate) fn new(fsct_interface: impl Into<FsctInterface>) -> Self {
         let fsct_device = Self {
-            fsct_interface: Arc::new(fsct_interface),
+            fsct_interface: Arc::new(fsct_interface.into()),
             time_sync_handle: None,
             state: Arc::new(Mutex::new(FsctDeviceSharedState {
                 time_diff: None,
+                write_latency: None,
                 fsct_text_encoding: FsctTextEncoding::Utf8,
                 supported_current_texts: Vec::new(),
                 supported_functionalities: FsctFunctionality::empty(),
+                extended_functionalities: FsctExtendedFunctionality::empty(),
+                quirks: DeviceQuirks::default(),
+                updating: false,
+                #[cfg(feature = "spectrum")]
+                spectrum_bands: None,
             })),
+            descriptor_dump: DeviceDescriptorDump::default(),
+            protocol: FsctProtocol::default(),
         };
         fsct_device
     }
 
-    pub(super) async fn init(&mut self, fsct_descriptors: &[FsctDescriptorSet]) -> Result<(), FsctDeviceError> {
+    /// Keeps the descriptors the device was initialized from, for [`FsctDevice::descriptor_dump`].
+    pub(crate) fn set_descriptor_dump(&mut self, descriptor_dump: DeviceDescriptorDump) {
+        self.descriptor_dump = descriptor_dump;
+    }
+
+    /// Descriptors the device announced its capabilities in.
+    pub fn descriptor_dump(&self) -> &DeviceDescriptorDump {
+        &self.descriptor_dump
+    }
+
+    /// Sets the protocol negotiated with the device; requests it doesn't have are skipped.
+    pub(crate) fn set_protocol(&mut self, protocol: FsctProtocol) {
+        self.protocol = protocol;
+    }
+
+    /// FSCT protocol negotiated with the device.
+    pub fn protocol(&self) -> FsctProtocol {
+        self.protocol
+    }
+
+    /// Reads the capabilities from the descriptors and starts periodic time synchronization; the device is brought
+    /// up by [`FsctDevice::warm_up`] afterwards.
+    pub(crate) fn init(&mut self, fsct_descriptors: &[FsctDescriptorSet]) {
         self.parse_descriptors(fsct_descriptors);
-        if self.state.lock().unwrap().supported_functionalities.contains(FsctFunctionality::CurrentPlaybackProgress) {
-            self.synchronize_time().await?;
-        }
-        self.fsct_interface.set_enable(true).await?;
-      
+
         let state = self.state.clone();
         let fsct_interface = self.fsct_interface.clone();
         self.time_sync_handle = Some(tokio::spawn(async move {
             loop {
                 tokio::time::sleep(Duration::from_secs(60 * 10)).await;
+                if state.lock().unwrap().updating {
+                    continue;
+                }
+                if state.lock().unwrap().quirks.measures_latency()
+                    && let Err(e) = Self::measure_latency_impl(state.clone(), &fsct_interface).await {
+                    log::warn!("Failed to measure latency: {}", e);
+                }
+                if state.lock().unwrap().quirks.skips_time_sync() {
+                    continue;
+                }
                 Self::synchronize_time_impl(state.clone(), fsct_interface.clone()).await.unwrap_or_else(|e|
                     log::error!("Failed to synchronize time: {}", e)
                 )
             }
         }));
+    }
 
-        Ok(())
+    pub fn quirks(&self) -> DeviceQuirks {
+        self.state.lock().unwrap().quirks.clone()
     }
+
+    /// Applies quirks of the device model; the forced text encoding takes effect with [`WarmupStep::Encoding`].
+    pub fn set_quirks(&self, quirks: DeviceQuirks) {
+        self.fsct_interface.set_write_delay(quirks.write_delay.unwrap_or_default());
+        let mut state = self.state.lock().unwrap();
+        if quirks.skips_time_sync() {
+            state.time_diff = Some(Duration::ZERO);
+        }
+        state.quirks = quirks;
+    }
+
+    /// Performs a single step of bringing up the freshly attached device.
+    pub async fn warm_up(&self, step: WarmupStep) -> Result<(), FsctDeviceError> {
+        let (functionality, quirks) = {
+            let state = self.state.lock().unwrap();
+            (state.supported_functionalities, state.quirks.clone())
+        };
+        match step {
+            WarmupStep::Enable => self.fsct_interface.set_enable(true).await,
+            WarmupStep::Encoding => {
+                if let Some(text_encoding) = quirks.text_encoding {
+                    self.state.lock().unwrap().fsct_text_encoding = text_encoding;
+                }
+                Ok(())
+            }
+            WarmupStep::TimeSync if functionality.contains(FsctFunctionality::CurrentPlaybackProgress) => {
+                if !quirks.skips_time_sync() {
+                    Self::synchronize_time_impl(self.state.clone(), self.fsct_interface.clone()).await?;
+                }
+                if quirks.measures_latency() {
+                    self.measure_latency().await?;
+                }
+                Ok(())
+            }
+            WarmupStep::Status if functionality.contains(FsctFunctionality::CurrentPlaybackStatus) => {
+                self.set_status(FsctStatus::Stopped).await
+            }
+            WarmupStep::Texts => {
+                let texts: Vec<_> =
+                    self.state.lock().unwrap().supported_current_texts.iter().map(|text| text.metadata).collect();
+                for text_id in texts {
+                    self.fsct_interface.disable_current_text(text_id).await?;
+                }
+                Ok(())
+            }
+            WarmupStep::Progress if functionality.contains(FsctFunctionality::CurrentPlaybackProgress) => {
+                self.fsct_interface.disable_track_progress().await
+            }
+            _ => Ok(()), // not supported, omitting
+        }
+    }
+
     fn parse_descriptors(&mut self, fsct_descriptor_set: &[FsctDescriptorSet]) {
         for descriptor in fsct_descriptor_set {
             let mut state = self.state.lock().unwrap();
@@ -85,6 +217,9 @@ impl FsctDevice {
                 FsctDescriptorSet::Functionality(functionality_descriptor) => {
                     state.supported_functionalities = functionality_descriptor.bmFunctionality;
                 }
+                FsctDescriptorSet::ExtendedFunctionality(functionality_descriptor) => {
+                    state.extended_functionalities = functionality_descriptor.bmFunctionality;
+                }
                 FsctDescriptorSet::TextMetadata(text_metadata_descriptor) => {
                     state.fsct_text_encoding = text_metadata_descriptor.bSystemTextCoding;
                     for metadata_part in &text_metadata_descriptor.aMetadata {
@@ -94,23 +229,79 @@ impl FsctDevice {
                         });
                     }
                 }
+                #[cfg(feature = "spectrum")]
+                FsctDescriptorSet::Spectrum(spectrum_descriptor) => {
+                    state.spectrum_bands = Some(spectrum_descriptor.bBandCount);
+                }
                 _ => ()
             }
         }
     }
 
+    pub fn capabilities(&self) -> FsctDeviceCapabilities {
+        let state = self.state.lock().unwrap();
+        FsctDeviceCapabilities {
+            functionality: state.supported_functionalities,
+            extended_functionality: state.extended_functionalities
+                .iter()
+                .filter(|functionality| self.protocol.supports_extended(*functionality))
+                .collect(),
+            text_encoding: state.fsct_text_encoding,
+            supported_texts: state.supported_current_texts.iter().map(|t| (t.metadata, state.max_text_length(t))).collect(),
+        }
+    }
+
+    /// Limits of the device as shown to producers, with quirks applied.
+    pub fn limits(&self, device_id: ManagedDeviceId) -> DeviceLimits {
+        let capabilities = self.capabilities();
+        DeviceLimits {
+            device_id,
+            protocol_version: self.protocol.version(),
+            text_encoding: capabilities.text_encoding,
+            texts: capabilities
+                .supported_texts
+                .into_iter()
+                .map(|(metadata, max_length)| SupportedText { metadata, max_length })
+                .collect(),
+        }
+    }
+
+    pub fn request_timeouts(&self) -> UsbRequestTimeouts {
+        self.fsct_interface.timeouts()
+    }
+
+    /// Sets the timeouts of USB control transfers to the device, taking effect for the next requests.
+    pub fn set_request_timeouts(&self, timeouts: UsbRequestTimeouts) {
+        self.fsct_interface.set_timeouts(timeouts);
+    }
+
     pub fn time_diff(&self) -> Option<Duration> {
         self.state.lock().unwrap().time_diff
     }
 
-    async fn synchronize_time(&mut self) -> Result<(), FsctDeviceError> {
-        let state = self.state.clone();
-        let fsct_interface = self.fsct_interface.clone();
+    /// Write latency measured last, `None` unless the quirks of the device ask for measurements.
+    pub fn write_latency(&self) -> Option<Duration> {
+        self.state.lock().unwrap().write_latency
+    }
+
+    /// Measures the write latency of the device by timing `poll` requests; progress positions are sent that far
+    /// ahead while the quirks of the device ask for it.
+    pub async fn measure_latency(&self) -> Result<Duration, FsctDeviceError> {
+        Self::measure_latency_impl(self.state.clone(), &self.fsct_interface).await
+    }
 
-        Self::synchronize_time_impl(state, fsct_interface).await
+    async fn measure_latency_impl(state: Arc<Mutex<FsctDeviceSharedState>>, fsct_interface: &FsctInterface)
+                                  -> Result<Duration, FsctDeviceError> {
+        let mut latency = Duration::MAX;
+        for _ in 0..LATENCY_SAMPLES {
+            latency = latency.min(fsct_interface.send_poll().await?);
+        }
+        log::debug!("Measured write latency of {:?}", latency);
+        state.lock().unwrap().write_latency = Some(latency);
+        Ok(latency)
     }
 
-    async fn synchronize_time_impl(state: Arc<Mutex<FsctDeviceSharedState>>, fsct_interface: Arc<FsctUsbInterface>) -> Result<(), FsctDeviceError> {
+    async fn synchronize_time_impl(state: Arc<Mutex<FsctDeviceSharedState>>, fsct_interface: Arc<FsctInterface>) -> Result<(), FsctDeviceError> {
         if !state.lock().unwrap().supported_functionalities.contains(FsctFunctionality::CurrentPlaybackProgress) {
             return Err(FsctDeviceError::PlaybackProgressNotSupported);
         }
@@ -130,6 +321,23 @@ impl FsctDevice {
         Ok(())
     }
 
+    /// Brings the device back to a known state after the host lost track of it: synchronizes time again and
+    /// enables the FSCT function.
+    pub async fn reinitialize(&self) -> Result<(), FsctDeviceError> {
+        let needs_time_sync = {
+            let state = self.state.lock().unwrap();
+            state.supported_functionalities.contains(FsctFunctionality::CurrentPlaybackProgress)
+                && !state.quirks.skips_time_sync()
+        };
+        if needs_time_sync {
+            Self::synchronize_time_impl(self.state.clone(), self.fsct_interface.clone()).await?;
+        }
+        if self.quirks().measures_latency() {
+            self.measure_latency().await?;
+        }
+        self.fsct_interface.set_enable(true).await
+    }
+
     pub async fn get_enable(&self) -> Result<bool, FsctDeviceError> {
         self.fsct_interface.get_enable().await
     }
@@ -137,12 +345,87 @@ impl FsctDevice {
         self.fsct_interface.set_enable(enable).await
     }
 
+    pub async fn notify(&self, notification: FsctNotification) -> Result<(), FsctDeviceError> {
+        if !self.state.lock().unwrap().supported_functionalities.contains(FsctFunctionality::AttentionSignal) {
+            return Ok(()); // not supported, omitting
+        }
+        self.fsct_interface.send_notify(notification).await
+    }
+
+    /// Sends audio levels to a device with a VU meter display; devices without a level endpoint are skipped.
+    #[cfg(feature = "audio-levels")]
+    pub async fn send_audio_levels(&self, levels: crate::audio_levels::AudioLevels) -> Result<(), FsctDeviceError> {
+        if !self.fsct_interface.takes_audio_levels() {
+            return Ok(()); // not supported, omitting
+        }
+        self.fsct_interface.send_level_report(&levels.to_report()).await
+    }
+
+    /// Sends band energies to a device announcing a spectrum display; other devices are skipped.
+    #[cfg(feature = "spectrum")]
+    pub async fn send_spectrum(&self, spectrum: &crate::spectrum::Spectrum) -> Result<(), FsctDeviceError> {
+        let Some(band_count) = self.state.lock().unwrap().spectrum_bands else {
+            return Ok(()); // not supported, omitting
+        };
+        if !self.fsct_interface.takes_audio_levels() {
+            return Err(FsctDeviceError::SpectrumNotSupported);
+        }
+        self.fsct_interface.send_level_report(&spectrum.to_report(band_count as usize)).await
+    }
+
+    /// Sends a raw vendor request; only codes from [`FIRST_VENDOR_REQUEST_CODE`] on are allowed, so FSCT state
+    /// of the device can't be changed behind the host's back.
+    #[cfg(feature = "vendor-requests")]
+    pub async fn send_vendor_request(&self, vendor_request: &VendorRequest) -> Result<Vec<u8>, FsctDeviceError> {
+        if vendor_request.request < FIRST_VENDOR_REQUEST_CODE {
+            return Err(FsctDeviceError::InvalidVendorRequest(
+                format!("request code {:#04x} is reserved for FSCT", vendor_request.request)));
+        }
+        if vendor_request.response_length > 0 && !vendor_request.payload.is_empty() {
+            return Err(FsctDeviceError::InvalidVendorRequest("a request can't both send and receive data".into()));
+        }
+        self.fsct_interface.send_vendor_request(vendor_request).await
+    }
+
+    /// Reads and clears the most recent firmware error; `None` if there was none or the device doesn't report errors.
+    pub async fn take_error_report(&self) -> Result<Option<DeviceErrorReport>, FsctDeviceError> {
+        if !self.state.lock().unwrap().supported_functionalities.contains(FsctFunctionality::ErrorReporting) {
+            return Ok(None); // not supported, omitting
+        }
+        let report = self.fsct_interface.get_error_report().await?;
+        let (code, detail, count) = (report.code, report.detail, report.count);
+        Ok(FsctDeviceErrorCode::from_raw(code).map(|code| DeviceErrorReport { code, detail, count }))
+    }
+
+    /// Whether the device sends playback commands, see [`FsctDevice::read_command`].
+    pub fn sends_commands(&self) -> bool {
+        self.state.lock().unwrap().supported_functionalities.contains(FsctFunctionality::PlaybackCommands)
+    }
+
+    /// Waits for the next playback command of the device; reports with unknown command codes are skipped.
+    pub async fn read_command(&self) -> Result<PlaybackCommand, FsctDeviceError> {
+        if !self.sends_commands() {
+            return Err(FsctDeviceError::PlaybackCommandsNotSupported);
+        }
+        loop {
+            let report = self.fsct_interface.read_command_report().await?;
+            match decode_command_report(&report)? {
+                Some(command) => return Ok(command),
+                None => log::debug!("Skipping command report {:02x?}", report),
+            }
+        }
+    }
+
     pub async fn set_progress(&self, progress: Option<TimelineInfo>) -> Result<(), FsctDeviceError>
     {
         if !self.state.lock().unwrap().supported_functionalities.contains(FsctFunctionality::CurrentPlaybackProgress) {
             return Ok(()); // not supported, omitting
         }
         let time_diff = self.state.lock().unwrap().time_diff.ok_or(FsctDeviceError::TimeNotSynchronized)?;
+        let lead = self.progress_lead();
+        let supports_live = self.state.lock().unwrap().supported_functionalities.contains(FsctFunctionality::LiveProgress);
+        // without live mode a progress bar of unknown length would be meaningless, so it is switched off instead
+        let progress = progress.filter(|progress| supports_live || !progress.is_live());
         match progress {
             None => self.fsct_interface.disable_track_progress().await,
             Some(progress) => {
@@ -151,12 +434,13 @@ impl FsctDevice {
                     |e| FsctDeviceError::TimeDifferenceCalculationError(e.to_string())
                 )?;
 
-                let position = progress.position.as_secs_f64() + (duration_since_update_time.as_secs_f64() * progress.rate as f64);
+                let ahead = duration_since_update_time + lead;
+                let position = progress.position.as_secs_f64() + (ahead.as_secs_f64() * progress.rate);
                 let position = position * 1000.0; // position is in milliseconds
                 let device_timestamp = (timestamp - time_diff).duration_since(std::time::UNIX_EPOCH)
                                                               .unwrap().as_millis() as u64;
                 let track_progress_request_data = TrackProgressRequestData {
-                    duration: progress.duration.as_secs_f64().round() as u32,
+                    duration: progress.duration.map_or(0, |duration| duration.as_secs_f64().round() as u32),
                     position: position.round() as i32,
                     timestamp: device_timestamp,
                     rate: progress.rate as f32,
@@ -167,28 +451,85 @@ impl FsctDevice {
     }
 
 
+    /// How far ahead progress positions are sent: the display lag of the device and its measured write latency.
+    fn progress_lead(&self) -> Duration {
+        let state = self.state.lock().unwrap();
+        let latency = state.write_latency.filter(|_| state.quirks.measures_latency()).unwrap_or_default();
+        state.quirks.display_lag.unwrap_or_default() + latency
+    }
+
     pub async fn set_current_text(&self, text_id: FsctTextMetadata, text: Option<&str>) -> Result<(), FsctDeviceError>
     {
-        let supported_metadata =
-            self.state.lock().unwrap().supported_current_texts.iter().find(|metadata| metadata.metadata == text_id).copied();
-        if supported_metadata.is_none() {
-            return Ok(());
-        }
-        let supported_metadata = supported_metadata.unwrap();
+        let (text_encoding, max_length) = {
+            let state = self.state.lock().unwrap();
+            match state.supported_current_texts.iter().find(|metadata| metadata.metadata == text_id) {
+                Some(supported_metadata) => (state.fsct_text_encoding, state.max_text_length(supported_metadata)),
+                None => return Ok(()),
+            }
+        };
 
         match text {
             None => self.fsct_interface.disable_current_text(text_id).await,
             Some(text) => {
-                let data_text = to_usb_encoded_text(self.state.lock().unwrap().fsct_text_encoding, text, supported_metadata.max_length);
+                let data_text = to_usb_encoded_text(text_encoding, text, max_length);
                 self.fsct_interface.send_current_text(text_id, data_text.as_slice()).await
             }
         }
     }
 
-    pub async fn set_status(&self, status: crate::definitions::FsctStatus) -> Result<(), FsctDeviceError>
+    pub async fn set_status(&self, status: FsctStatus) -> Result<(), FsctDeviceError>
     {
         self.fsct_interface.send_status(status).await
     }
+
+    /// Whether the device announced `functionality` and its protocol has the requests of it.
+    fn supports_extended(&self, functionality: FsctExtendedFunctionality) -> bool {
+        self.protocol.supports_extended(functionality)
+            && self.state.lock().unwrap().extended_functionalities.contains(functionality)
+    }
+
+    /// Sends the volume of the player to a device showing it; other devices are skipped.
+    pub async fn set_volume(&self, volume: Option<VolumeInfo>) -> Result<(), FsctDeviceError> {
+        if !self.supports_extended(FsctExtendedFunctionality::Volume) {
+            return Ok(()); // not supported, omitting
+        }
+        self.fsct_interface.send_volume(encode_volume(volume)).await
+    }
+
+    /// Sends the shuffle and repeat modes of the player to a device showing them; other devices are skipped.
+    pub async fn set_playback_modes(&self, modes: PlaybackModes) -> Result<(), FsctDeviceError> {
+        if !self.supports_extended(FsctExtendedFunctionality::PlaybackModes) {
+            return Ok(()); // not supported, omitting
+        }
+        self.fsct_interface.send_playback_modes(encode_playback_modes(modes)).await
+    }
+
+    /// Whether the device takes firmware updates, see [`dfu`](crate::usb::dfu).
+    pub fn supports_firmware_update(&self) -> bool {
+        self.supports_extended(FsctExtendedFunctionality::FirmwareUpdate)
+    }
+
+    /// Reads the version of the firmware the device runs, BCD like bcdDevice.
+    pub async fn firmware_version(&self) -> Result<u16, FsctDeviceError> {
+        if !self.supports_firmware_update() {
+            return Err(FsctDeviceError::FirmwareUpdateNotSupported);
+        }
+        self.fsct_interface.get_firmware_version().await
+    }
+
+    /// Marks a firmware update of the device as in progress, pausing periodic time synchronization.
+    pub(crate) fn set_updating(&self, updating: bool) {
+        self.state.lock().unwrap().updating = updating;
+    }
+
+    /// Streams the firmware image to the device, which applies it and restarts; see [`dfu`](crate::usb::dfu).
+    pub async fn update_firmware(&self, image: &[u8], progress: &mut (dyn FnMut(DfuProgress) + Send))
+                                 -> Result<(), FsctDeviceError> {
+        if !self.supports_firmware_update() {
+            return Err(FsctDeviceError::FirmwareUpdateNotSupported);
+        }
+        dfu::stream_image(&self.fsct_interface, image, progress).await
+    }
 }
 
 impl Drop for FsctDevice {
@@ -200,15 +541,92 @@ impl Drop for FsctDevice {
     }
 }
 
-fn floor_char_boundary_utf8(text: &str, max_length: usize) -> &str {
-    let mut new_text_length = text.len().min(max_length);
-    while !text.is_char_boundary(new_text_length) {
-        new_text_length -= 1;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BidiScope {
+    /// Opened by LRE, RLE, LRO or RLO and closed by PDF.
+    Embedding,
+    /// Opened by LRI, RLI or FSI and closed by PDI.
+    Isolate,
+}
+
+const POP_DIRECTIONAL_FORMATTING: char = '\u{202C}';
+const POP_DIRECTIONAL_ISOLATE: char = '\u{2069}';
+
+/// Tracks explicit bidirectional formatting characters, so a truncated text can be closed properly.
+/// Without closing, an embedding or isolate cut in the middle would leak its direction into whatever the device
+/// renders next to the text, showing fragments in reversed order.
+#[derive(Debug, Default)]
+struct BidiScopes {
+    open: Vec<BidiScope>,
+}
+
+impl BidiScopes {
+    fn push_char(&mut self, c: char) {
+        match c {
+            '\u{202A}' | '\u{202B}' | '\u{202D}' | '\u{202E}' => self.open.push(BidiScope::Embedding),
+            '\u{2066}' | '\u{2067}' | '\u{2068}' => self.open.push(BidiScope::Isolate),
+            POP_DIRECTIONAL_FORMATTING if self.open.last() == Some(&BidiScope::Embedding) => {
+                self.open.pop();
+            }
+            POP_DIRECTIONAL_ISOLATE => {
+                // PDI also terminates all embeddings opened inside the isolate
+                if let Some(isolate_position) = self.open.iter().rposition(|scope| *scope == BidiScope::Isolate) {
+                    self.open.truncate(isolate_position);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn closing_chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.open.iter().rev().map(|scope| match scope {
+            BidiScope::Embedding => POP_DIRECTIONAL_FORMATTING,
+            BidiScope::Isolate => POP_DIRECTIONAL_ISOLATE,
+        })
     }
-    &text[..new_text_length]
+}
+
+/// Truncates text so that its encoded form fits into `max_length_in_bytes`.
+///
+/// Text is cut only on extended grapheme cluster boundaries, so base characters never lose their combining marks
+/// and multi-codepoint sequences (emoji, Hangul syllables, flags) are never split. When text is cut inside
+/// an explicit bidirectional embedding or isolate, the matching terminators are appended, which may require dropping
+/// a few more clusters to make room for them.
+fn truncate_text(fsct_text_encoding: FsctTextEncoding, text: &str, max_length_in_bytes: usize) -> Cow<'_, str> {
+    if fsct_text_encoding.encoded_length(text) <= max_length_in_bytes {
+        return Cow::Borrowed(text);
+    }
+
+    // (end byte offset in text, encoded length up to end, bidi scopes open at end)
+    let mut boundaries: Vec<(usize, usize, Vec<BidiScope>)> = vec![(0, 0, Vec::new())];
+    let mut scopes = BidiScopes::default();
+    let mut length = 0;
+    for (offset, grapheme) in text.grapheme_indices(true) {
+        let grapheme_length = fsct_text_encoding.encoded_length(grapheme);
+        if length + grapheme_length > max_length_in_bytes {
+            break;
+        }
+        length += grapheme_length;
+        grapheme.chars().for_each(|c| scopes.push_char(c));
+        boundaries.push((offset + grapheme.len(), length, scopes.open.clone()));
+    }
+
+    let closing_length = |open: &Vec<BidiScope>| {
+        BidiScopes { open: open.clone() }.closing_chars().map(|c| fsct_text_encoding.encoded_char_length(c)).sum::<usize>()
+    };
+    let (end, _, open) = boundaries
+        .into_iter()
+        .rev()
+        .find(|(_, length, open)| length + closing_length(open) <= max_length_in_bytes)
+        .unwrap_or_default();
+
+    let mut truncated = text[..end].to_string();
+    truncated.extend(BidiScopes { open }.closing_chars());
+    Cow::Owned(truncated)
 }
 
 fn to_usb_encoded_text(fsct_text_encoding: FsctTextEncoding, text: &str, max_length_in_bytes: usize) -> Vec<u8> {
+    let text = truncate_text(fsct_text_encoding, text, max_length_in_bytes);
     match fsct_text_encoding {
         FsctTextEncoding::Ucs2 => {
             text.chars().map(|c| {
@@ -217,33 +635,108 @@ fn to_usb_encoded_text(fsct_text_encoding: FsctTextEncoding, text: &str, max_len
                 } else {
                     char::REPLACEMENT_CHARACTER as u16
                 }
-            }).take(max_length_in_bytes / 2).map(u16::to_ne_bytes).flatten().collect()
+            }).flat_map(u16::to_ne_bytes).collect()
         }
         FsctTextEncoding::Utf8 => {
-            floor_char_boundary_utf8(text, max_length_in_bytes).as_bytes().to_vec()
+            text.as_bytes().to_vec()
         }
         FsctTextEncoding::Utf16 => {
-            let mut res: Vec<u8> = text.encode_utf16().take(max_length_in_bytes / 2)
-                                       .map(u16::to_ne_bytes)
-                                       .flatten()
-                                       .collect();
-            if (res.last().unwrap_or(&0) & 0xFC) == 0xD8 {
-                // when last word starts from utf-16 4-word marker, we remove half of the character
-                let new_len = res.len() - 2;
-                res.resize(new_len, 0);
-            }
-            res
+            text.encode_utf16().flat_map(u16::to_ne_bytes).collect()
         }
         FsctTextEncoding::Utf32 => {
-            text.chars().map(|c| c as u32).take(max_length_in_bytes / 4).map(u32::to_ne_bytes).flatten().collect()
+            text.chars().map(|c| c as u32).flat_map(u32::to_ne_bytes).collect()
         }
     }
 }
 
+/// Value of the `volume` request: the level, capped at 100, and the muted flag.
+fn encode_volume(volume: Option<VolumeInfo>) -> u16 {
+    match volume {
+        None => requests::VOLUME_UNKNOWN,
+        Some(volume) if volume.muted => volume.level.min(100) as u16 | requests::VOLUME_MUTED,
+        Some(volume) => volume.level.min(100) as u16,
+    }
+}
+
+/// Value of the `playbackModes` request: the shuffle flag in the lower byte and the repeat mode in the upper one.
+fn encode_playback_modes(modes: PlaybackModes) -> u16 {
+    let shuffle = modes.shuffle.map_or(requests::PLAYBACK_MODE_UNKNOWN, u8::from);
+    let repeat = modes.repeat.map_or(requests::PLAYBACK_MODE_UNKNOWN, |repeat| repeat as u8);
+    u16::from_le_bytes([shuffle, repeat])
+}
+
+/// Inverse of [`encode_volume`]; `None` for unknown volumes and levels above 100.
+fn decode_volume(value: u16) -> Option<VolumeInfo> {
+    let level = (value & 0x00FF) as u8;
+    if value == requests::VOLUME_UNKNOWN || level > 100 {
+        return None;
+    }
+    Some(VolumeInfo { level, muted: value & requests::VOLUME_MUTED != 0 })
+}
+
+/// Decodes a command report; `None` for unknown command codes and volumes.
+fn decode_command_report(report: &[u8]) -> Result<Option<PlaybackCommand>, FsctDeviceError> {
+    let Some((&code, argument)) = report.split_first() else {
+        return Err(FsctDeviceError::DataSizeMismatch { expected: 1, actual: 0 });
+    };
+    let command = match FsctCommandCode::from_raw(code) {
+        Some(FsctCommandCode::Play) => PlaybackCommand::Play,
+        Some(FsctCommandCode::Pause) => PlaybackCommand::Pause,
+        Some(FsctCommandCode::Next) => PlaybackCommand::Next,
+        Some(FsctCommandCode::Previous) => PlaybackCommand::Previous,
+        Some(FsctCommandCode::Seek) => {
+            let position: [u8; 4] = argument.get(..4).and_then(|bytes| bytes.try_into().ok())
+                .ok_or(FsctDeviceError::DataSizeMismatch { expected: 5, actual: report.len() })?;
+            let position = i32::from_le_bytes(position).max(0) as u64;
+            PlaybackCommand::Seek { position: Duration::from_secs(position) }
+        }
+        Some(FsctCommandCode::Volume) => {
+            let value: [u8; 2] = argument.get(..2).and_then(|bytes| bytes.try_into().ok())
+                .ok_or(FsctDeviceError::DataSizeMismatch { expected: 3, actual: report.len() })?;
+            match decode_volume(u16::from_le_bytes(value)) {
+                Some(volume) => PlaybackCommand::SetVolume { volume },
+                None => return Ok(None),
+            }
+        }
+        None => return Ok(None),
+    };
+    Ok(Some(command))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn command_reports_are_decoded() {
+        assert_eq!(decode_command_report(&[0x02]).unwrap(), Some(PlaybackCommand::Pause));
+        assert_eq!(decode_command_report(&[0x05, 90, 0, 0, 0]).unwrap(),
+                   Some(PlaybackCommand::Seek { position: Duration::from_secs(90) }));
+        assert_eq!(decode_command_report(&[0x06, 40, 0x01]).unwrap(),
+                   Some(PlaybackCommand::SetVolume { volume: VolumeInfo { level: 40, muted: true } }));
+        assert_eq!(decode_command_report(&[0x06, 0xff, 0xff]).unwrap(), None);
+        assert!(decode_command_report(&[0x06, 40]).is_err());
+        assert_eq!(decode_command_report(&[0x7f]).unwrap(), None);
+        assert!(decode_command_report(&[0x05, 90]).is_err());
+        assert!(decode_command_report(&[]).is_err());
+    }
+
+    #[test]
+    fn volume_is_encoded_with_muted_flag() {
+        assert_eq!(encode_volume(Some(VolumeInfo { level: 40, muted: false })), 40);
+        assert_eq!(encode_volume(Some(VolumeInfo { level: 140, muted: true })), 0x0164);
+        assert_eq!(encode_volume(None), 0xFFFF);
+    }
+
+    #[test]
+    fn playback_modes_are_encoded_per_byte() {
+        use crate::definitions::RepeatMode;
+        let modes = PlaybackModes { shuffle: Some(true), repeat: Some(RepeatMode::List) };
+        assert_eq!(encode_playback_modes(modes), 0x0201);
+        assert_eq!(encode_playback_modes(PlaybackModes { shuffle: Some(false), repeat: None }), 0xFF00);
+        assert_eq!(encode_playback_modes(PlaybackModes::default()), 0xFFFF);
+    }
+
     #[test]
     fn test_fsct_device_to_usb_encoded_utf16_simple_text() {
         let text = "Hello World";
@@ -323,5 +816,41 @@ mod tests {
         let required: Vec<u8> = "".as_bytes().to_vec();
         assert_eq!(encoded_text, required);
     }
-}
 
+    #[test]
+    fn test_fsct_device_to_usb_encoded_utf8_does_not_split_combining_characters() {
+        let text = "cafe\u{301} noir";
+        let encoded_text = to_usb_encoded_text(FsctTextEncoding::Utf8, text, 5);
+        assert_eq!(encoded_text, "caf".as_bytes().to_vec());
+    }
+
+    #[test]
+    fn test_fsct_device_to_usb_encoded_utf16_does_not_split_combining_characters() {
+        let text = "cafe\u{301}";
+        let encoded_text = to_usb_encoded_text(FsctTextEncoding::Utf16, text, 8);
+        let required: Vec<u8> = "caf".encode_utf16().map(u16::to_ne_bytes).flatten().collect();
+        assert_eq!(encoded_text, required);
+    }
+
+    #[test]
+    fn test_fsct_device_to_usb_encoded_utf8_does_not_split_emoji_sequence() {
+        let text = "ab\u{1F469}\u{200D}\u{1F3A4}";
+        let encoded_text = to_usb_encoded_text(FsctTextEncoding::Utf8, text, 9);
+        assert_eq!(encoded_text, "ab".as_bytes().to_vec());
+    }
+
+    #[test]
+    fn test_fsct_device_to_usb_encoded_utf8_closes_truncated_isolate() {
+        let text = "ab\u{2067}\u{5E9}\u{5DC}\u{5D5}\u{5DD}\u{2069}";
+        let encoded_text = to_usb_encoded_text(FsctTextEncoding::Utf8, text, 12);
+        let required = "ab\u{2067}\u{5E9}\u{5DC}\u{2069}".as_bytes().to_vec();
+        assert_eq!(encoded_text, required);
+    }
+
+    #[test]
+    fn test_fsct_device_to_usb_encoded_utf8_keeps_untruncated_text_unchanged() {
+        let text = "\u{202B}\u{5E9}\u{5DC}";
+        let encoded_text = to_usb_encoded_text(FsctTextEncoding::Utf8, text, 16);
+        assert_eq!(encoded_text, text.as_bytes().to_vec());
+    }
+}
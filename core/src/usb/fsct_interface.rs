@@ -0,0 +1,169 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Transport of the FSCT requests of a device: the FSCT interface of a USB device or, with the `network` feature,
+//! a TCP session with a device on the LAN speaking the framed requests (see [`crate::network`]) or, with the
+//! `simulator` feature, a simulated device (see [`crate::simulator`]).
+
+use std::time::Duration;
+
+use crate::definitions::{FsctNotification, FsctStatus, FsctTextMetadata, UsbRequestTimeouts};
+#[cfg(feature = "vendor-requests")]
+use crate::definitions::VendorRequest;
+#[cfg(feature = "network")]
+use crate::network::FsctNetworkInterface;
+#[cfg(feature = "simulator")]
+use crate::simulator::SimulatedInterface;
+use crate::usb::errors::FsctDeviceError;
+use crate::usb::fsct_usb_interface::FsctUsbInterface;
+use crate::usb::requests;
+
+pub(crate) enum FsctInterface {
+    Usb(FsctUsbInterface),
+    #[cfg(feature = "network")]
+    Network(FsctNetworkInterface),
+    #[cfg(feature = "simulator")]
+    Simulated(SimulatedInterface),
+}
+
+impl From<FsctUsbInterface> for FsctInterface {
+    fn from(interface: FsctUsbInterface) -> Self {
+        FsctInterface::Usb(interface)
+    }
+}
+
+#[cfg(feature = "network")]
+impl From<FsctNetworkInterface> for FsctInterface {
+    fn from(interface: FsctNetworkInterface) -> Self {
+        FsctInterface::Network(interface)
+    }
+}
+
+#[cfg(feature = "simulator")]
+impl From<SimulatedInterface> for FsctInterface {
+    fn from(interface: SimulatedInterface) -> Self {
+        FsctInterface::Simulated(interface)
+    }
+}
+
+/// Calls the method of the same name on the transport.
+macro_rules! dispatch {
+    ($self:ident, $interface:ident => $call:expr) => {
+        match $self {
+            FsctInterface::Usb($interface) => $call,
+            #[cfg(feature = "network")]
+            FsctInterface::Network($interface) => $call,
+            #[cfg(feature = "simulator")]
+            FsctInterface::Simulated($interface) => $call,
+        }
+    };
+}
+
+impl FsctInterface {
+    pub fn timeouts(&self) -> UsbRequestTimeouts {
+        dispatch!(self, interface => interface.timeouts())
+    }
+
+    pub fn set_timeouts(&self, timeouts: UsbRequestTimeouts) {
+        dispatch!(self, interface => interface.set_timeouts(timeouts))
+    }
+
+    pub fn set_write_delay(&self, write_delay: Duration) {
+        dispatch!(self, interface => interface.set_write_delay(write_delay))
+    }
+
+    pub async fn get_device_timestamp(&self) -> Result<requests::Timestamp, FsctDeviceError> {
+        dispatch!(self, interface => interface.get_device_timestamp().await)
+    }
+
+    pub async fn get_error_report(&self) -> Result<requests::ErrorReportRequestData, FsctDeviceError> {
+        dispatch!(self, interface => interface.get_error_report().await)
+    }
+
+    pub async fn get_firmware_version(&self) -> Result<u16, FsctDeviceError> {
+        dispatch!(self, interface => interface.get_firmware_version().await)
+    }
+
+    pub async fn get_enable(&self) -> Result<bool, FsctDeviceError> {
+        dispatch!(self, interface => interface.get_enable().await)
+    }
+
+    pub async fn set_enable(&self, enable: bool) -> Result<(), FsctDeviceError> {
+        dispatch!(self, interface => interface.set_enable(enable).await)
+    }
+
+    pub async fn read_command_report(&self) -> Result<Vec<u8>, FsctDeviceError> {
+        dispatch!(self, interface => interface.read_command_report().await)
+    }
+
+    #[cfg(feature = "audio-levels")]
+    pub fn takes_audio_levels(&self) -> bool {
+        dispatch!(self, interface => interface.takes_audio_levels())
+    }
+
+    #[cfg(feature = "audio-levels")]
+    pub async fn send_level_report(&self, report: &[u8]) -> Result<(), FsctDeviceError> {
+        dispatch!(self, interface => interface.send_level_report(report).await)
+    }
+
+    pub async fn send_poll(&self) -> Result<Duration, FsctDeviceError> {
+        dispatch!(self, interface => interface.send_poll().await)
+    }
+
+    pub async fn send_notify(&self, notification: FsctNotification) -> Result<(), FsctDeviceError> {
+        dispatch!(self, interface => interface.send_notify(notification).await)
+    }
+
+    #[cfg(feature = "vendor-requests")]
+    pub async fn send_vendor_request(&self, vendor_request: &VendorRequest) -> Result<Vec<u8>, FsctDeviceError> {
+        dispatch!(self, interface => interface.send_vendor_request(vendor_request).await)
+    }
+
+    pub async fn send_track_progress(&self, progress: &requests::TrackProgressRequestData)
+                                     -> Result<(), FsctDeviceError> {
+        dispatch!(self, interface => interface.send_track_progress(progress).await)
+    }
+
+    pub async fn disable_track_progress(&self) -> Result<(), FsctDeviceError> {
+        dispatch!(self, interface => interface.disable_track_progress().await)
+    }
+
+    pub async fn send_current_text(&self, text_id: FsctTextMetadata, text_raw: &[u8]) -> Result<(), FsctDeviceError> {
+        dispatch!(self, interface => interface.send_current_text(text_id, text_raw).await)
+    }
+
+    pub async fn disable_current_text(&self, text_id: FsctTextMetadata) -> Result<(), FsctDeviceError> {
+        dispatch!(self, interface => interface.disable_current_text(text_id).await)
+    }
+
+    pub async fn send_status(&self, status: FsctStatus) -> Result<(), FsctDeviceError> {
+        dispatch!(self, interface => interface.send_status(status).await)
+    }
+
+    pub async fn send_volume(&self, value: u16) -> Result<(), FsctDeviceError> {
+        dispatch!(self, interface => interface.send_volume(value).await)
+    }
+
+    pub async fn send_playback_modes(&self, value: u16) -> Result<(), FsctDeviceError> {
+        dispatch!(self, interface => interface.send_playback_modes(value).await)
+    }
+
+    pub async fn send_dfu_request(&self, request: requests::FsctRequestCode, value: u16, data: &[u8],
+                                  timeout: Duration) -> Result<(), FsctDeviceError> {
+        dispatch!(self, interface => interface.send_dfu_request(request, value, data, timeout).await)
+    }
+}
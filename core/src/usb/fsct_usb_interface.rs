@@ -15,25 +15,100 @@
 // This file is part of an implementation of Ferrum Streaming Control Technology™,
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
+use std::future::Future;
 use std::mem::size_of;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
 use anyhow::{Context};
 use nusb::Interface;
-use nusb::transfer::{ControlIn, ControlOut, ControlType, Recipient};
+use nusb::transfer::{ControlIn, ControlOut, ControlType, Direction, EndpointType, Recipient, RequestBuffer};
 use crate::definitions::FsctTextMetadata;
 use crate::usb::requests;
-use crate::definitions::FsctStatus;
+use crate::definitions::{FsctNotification, FsctStatus, UsbRequestTimeouts};
+#[cfg(feature = "vendor-requests")]
+use crate::definitions::VendorRequest;
 use crate::usb::errors::{FsctDeviceError, ToFsctDeviceResult};
 
 pub struct FsctUsbInterface {
     interface: Interface,
+    /// Interrupt IN endpoint the device sends playback commands on, if it has one.
+    command_endpoint: Option<u8>,
+    /// Interrupt or bulk OUT endpoint the device takes audio levels on, if it has one.
+    #[cfg(feature = "audio-levels")]
+    level_endpoint: Option<(u8, EndpointType)>,
+    timeouts: Mutex<UsbRequestTimeouts>,
+    write_delay: Mutex<Duration>,
+    last_transfer: tokio::sync::Mutex<Option<Instant>>,
+}
+
+/// Fails the transfer if it doesn't complete within `timeout`; dropping the transfer cancels it.
+async fn with_timeout<T>(timeout: Duration, transfer: impl Future<Output = T>) -> Result<T, FsctDeviceError> {
+    tokio::time::timeout(timeout, transfer).await.map_err(|_| FsctDeviceError::UsbControlTransferTimeout(timeout))
+}
+
+/// Address of the first interrupt IN endpoint of the interface.
+fn find_command_endpoint(interface: &Interface) -> Option<u8> {
+    interface.descriptors().find_map(|alt_setting| {
+        alt_setting.endpoints()
+                   .find(|endpoint| endpoint.transfer_type() == EndpointType::Interrupt
+                       && endpoint.direction() == Direction::In)
+                   .map(|endpoint| endpoint.address())
+    })
+}
+
+/// Address and type of the first interrupt or bulk OUT endpoint of the interface.
+#[cfg(feature = "audio-levels")]
+fn find_level_endpoint(interface: &Interface) -> Option<(u8, EndpointType)> {
+    interface.descriptors().find_map(|alt_setting| {
+        alt_setting.endpoints()
+                   .find(|endpoint| matches!(endpoint.transfer_type(), EndpointType::Interrupt | EndpointType::Bulk)
+                       && endpoint.direction() == Direction::Out)
+                   .map(|endpoint| (endpoint.address(), endpoint.transfer_type()))
+    })
 }
 
 impl FsctUsbInterface {
     pub fn new(interface: Interface) -> Self {
         Self {
+            command_endpoint: find_command_endpoint(&interface),
+            #[cfg(feature = "audio-levels")]
+            level_endpoint: find_level_endpoint(&interface),
             interface,
+            timeouts: Mutex::new(UsbRequestTimeouts::default()),
+            write_delay: Mutex::new(Duration::ZERO),
+            last_transfer: tokio::sync::Mutex::new(None),
         }
     }
+
+    pub fn timeouts(&self) -> UsbRequestTimeouts {
+        *self.timeouts.lock().unwrap()
+    }
+
+    pub fn set_timeouts(&self, timeouts: UsbRequestTimeouts) {
+        *self.timeouts.lock().unwrap() = timeouts;
+    }
+
+    /// Sets the minimum time between consecutive transfers, for devices that can't keep up with back-to-back ones.
+    pub fn set_write_delay(&self, write_delay: Duration) {
+        *self.write_delay.lock().unwrap() = write_delay;
+    }
+
+    /// Starts the transfer once the write delay since the previous transfer has passed, see [`with_timeout`].
+    async fn paced<F: Future>(&self, timeout: Duration, start: impl FnOnce() -> F) -> Result<F::Output, FsctDeviceError> {
+        let write_delay = *self.write_delay.lock().unwrap();
+        if write_delay.is_zero() {
+            return with_timeout(timeout, start()).await;
+        }
+        let mut last_transfer = self.last_transfer.lock().await;
+        if let Some(last_transfer) = *last_transfer {
+            tokio::time::sleep_until(last_transfer + write_delay).await;
+        }
+        let result = with_timeout(timeout, start()).await;
+        *last_transfer = Some(Instant::now());
+        result
+    }
+
     pub async fn get_device_timestamp(&self) -> Result<requests::Timestamp, FsctDeviceError> {
         let control_in = ControlIn {
             control_type: ControlType::Vendor,
@@ -43,8 +118,7 @@ impl FsctUsbInterface {
             index: self.interface.interface_number() as u16,
             length: size_of::<requests::Timestamp>() as u16,
         };
-        let timestamp_raw = self.interface.control_in(control_in)
-                                .await
+        let timestamp_raw = self.paced(self.timeouts().control, || self.interface.control_in(control_in)).await?
                                 .into_result()
                                 .context("Failed to get device timestamp")
                                 .map_err_to_fsct_device_control_transfer_error()?;
@@ -59,6 +133,52 @@ impl FsctUsbInterface {
         Ok(timestamp)
     }
 
+    pub async fn get_error_report(&self) -> Result<requests::ErrorReportRequestData, FsctDeviceError> {
+        let control_in = ControlIn {
+            control_type: ControlType::Vendor,
+            recipient: Recipient::Interface,
+            request: requests::FsctRequestCode::ErrorReport as u8,
+            value: 0x00,
+            index: self.interface.interface_number() as u16,
+            length: size_of::<requests::ErrorReportRequestData>() as u16,
+        };
+        let report_raw = self.paced(self.timeouts().control, || self.interface.control_in(control_in)).await?
+                             .into_result()
+                             .context("Failed to get error report")
+                             .map_err_to_fsct_device_control_transfer_error()?;
+
+        if report_raw.len() != size_of::<requests::ErrorReportRequestData>() {
+            return Err(FsctDeviceError::DataSizeMismatch {
+                expected: size_of::<requests::ErrorReportRequestData>(),
+                actual: report_raw.len(),
+            });
+        }
+        let report = unsafe { *(report_raw.as_ptr() as *const requests::ErrorReportRequestData) };
+        Ok(report)
+    }
+
+    pub async fn get_firmware_version(&self) -> Result<u16, FsctDeviceError> {
+        let control_in = ControlIn {
+            control_type: ControlType::Vendor,
+            recipient: Recipient::Interface,
+            request: requests::FsctRequestCode::FirmwareVersion as u8,
+            value: 0x00,
+            index: self.interface.interface_number() as u16,
+            length: 2,
+        };
+        let version_raw = self.paced(self.timeouts().control, || self.interface.control_in(control_in)).await?
+                              .into_result()
+                              .context("Failed to get firmware version")
+                              .map_err_to_fsct_device_control_transfer_error()?;
+        if version_raw.len() != 2 {
+            return Err(FsctDeviceError::DataSizeMismatch {
+                expected: 2,
+                actual: version_raw.len(),
+            });
+        }
+        Ok(u16::from_le_bytes([version_raw[0], version_raw[1]]))
+    }
+
     pub async fn get_enable(&self) -> Result<bool, FsctDeviceError> {
         let control_in = ControlIn {
             control_type: ControlType::Vendor,
@@ -69,8 +189,7 @@ impl FsctUsbInterface {
             length: 1,
         };
 
-        let enable_raw = self.interface.control_in(control_in)
-                             .await
+        let enable_raw = self.paced(self.timeouts().control, || self.interface.control_in(control_in)).await?
                              .into_result()
                              .context("Failed to get enable.")
                              .map_err_to_fsct_device_control_transfer_error()?;
@@ -92,14 +211,118 @@ impl FsctUsbInterface {
             index: self.interface.interface_number() as u16,
             data: &[],
         };
-        self.interface.control_out(control_out)
-            .await
+        self.paced(self.timeouts().control, || self.interface.control_out(control_out)).await?
             .into_result()
             .context("Failed to set enable")
             .map_err_to_fsct_device_control_transfer_error()?;
         Ok(())
     }
 
+    /// Waits for the next command report on the interrupt IN endpoint, see
+    /// [`FsctCommandCode`](requests::FsctCommandCode).
+    ///
+    /// There is no timeout, as devices only send reports when a command is issued; the wait is cancelled by
+    /// dropping the future.
+    pub async fn read_command_report(&self) -> Result<Vec<u8>, FsctDeviceError> {
+        let endpoint = self.command_endpoint.ok_or(FsctDeviceError::PlaybackCommandsNotSupported)?;
+        self.interface.interrupt_in(endpoint, RequestBuffer::new(requests::COMMAND_REPORT_MAX_LENGTH)).await
+            .into_result()
+            .context("Failed to read command report")
+            .map_err(FsctDeviceError::UsbInterruptTransferError)
+    }
+
+    /// Whether the interface has an endpoint for audio levels.
+    #[cfg(feature = "audio-levels")]
+    pub fn takes_audio_levels(&self) -> bool {
+        self.level_endpoint.is_some()
+    }
+
+    /// Sends an audio level or spectrum report on the level endpoint; not paced, as it doesn't share the control
+    /// endpoint.
+    #[cfg(feature = "audio-levels")]
+    pub async fn send_level_report(&self, report: &[u8]) -> Result<(), FsctDeviceError> {
+        let (endpoint, transfer_type) = self.level_endpoint.ok_or(FsctDeviceError::AudioLevelsNotSupported)?;
+        let timeout = self.timeouts().control;
+        let completion = match transfer_type {
+            EndpointType::Bulk => with_timeout(timeout, self.interface.bulk_out(endpoint, report.to_vec())).await?,
+            _ => with_timeout(timeout, self.interface.interrupt_out(endpoint, report.to_vec())).await?,
+        };
+        completion.into_result()
+                  .context("Failed to send level report")
+                  .map_err(FsctDeviceError::UsbInterruptTransferError)?;
+        Ok(())
+    }
+
+    /// Sends the empty `poll` request, returning how long the transfer took, not counting the write delay.
+    pub async fn send_poll(&self) -> Result<Duration, FsctDeviceError> {
+        let control_out = ControlOut {
+            control_type: ControlType::Vendor,
+            recipient: Recipient::Interface,
+            request: requests::FsctRequestCode::Poll as u8,
+            value: 0x00,
+            index: self.interface.interface_number() as u16,
+            data: &[],
+        };
+        let mut started = None;
+        self.paced(self.timeouts().control, || {
+            started = Some(Instant::now());
+            self.interface.control_out(control_out)
+        }).await?
+            .into_result()
+            .context("Failed to send poll")
+            .map_err_to_fsct_device_control_transfer_error()?;
+        Ok(started.map_or(Duration::ZERO, |started| started.elapsed()))
+    }
+
+    pub async fn send_notify(&self, notification: FsctNotification) -> Result<(), FsctDeviceError> {
+        let control_out = ControlOut {
+            control_type: ControlType::Vendor,
+            recipient: Recipient::Interface,
+            request: requests::FsctRequestCode::Notify as u8,
+            value: notification as u16,
+            index: self.interface.interface_number() as u16,
+            data: &[],
+        };
+        self.paced(self.timeouts().control, || self.interface.control_out(control_out)).await?
+            .into_result()
+            .context("Failed to send notify")
+            .map_err_to_fsct_device_control_transfer_error()?;
+        Ok(())
+    }
+
+    #[cfg(feature = "vendor-requests")]
+    pub async fn send_vendor_request(&self, vendor_request: &VendorRequest) -> Result<Vec<u8>, FsctDeviceError> {
+        let index = self.interface.interface_number() as u16;
+        if vendor_request.response_length == 0 {
+            let control_out = ControlOut {
+                control_type: ControlType::Vendor,
+                recipient: Recipient::Interface,
+                request: vendor_request.request,
+                value: vendor_request.value,
+                index,
+                data: &vendor_request.payload,
+            };
+            self.paced(self.timeouts().control, || self.interface.control_out(control_out)).await?
+                .into_result()
+                .context("Failed to send vendor request")
+                .map_err_to_fsct_device_control_transfer_error()?;
+            Ok(Vec::new())
+        } else {
+            let control_in = ControlIn {
+                control_type: ControlType::Vendor,
+                recipient: Recipient::Interface,
+                request: vendor_request.request,
+                value: vendor_request.value,
+                index,
+                length: vendor_request.response_length,
+            };
+            self.paced(self.timeouts().control, || self.interface.control_in(control_in)).await?
+                .into_result()
+                .context("Failed to receive vendor request response")
+                .map_err_to_fsct_device_control_transfer_error()
+        }
+    }
+
     pub async fn send_track_progress(&self, progress: &requests::TrackProgressRequestData) -> Result<(), FsctDeviceError> {
         let control_out = ControlOut {
             control_type: ControlType::Vendor,
@@ -114,7 +337,7 @@ impl FsctUsbInterface {
                 )
             },
         };
-        self.interface.control_out(control_out).await.into_result()
+        self.paced(self.timeouts().progress, || self.interface.control_out(control_out)).await?.into_result()
             .context("Failed to send track progress")
             .map_err_to_fsct_device_control_transfer_error()?;
 
@@ -130,7 +353,7 @@ impl FsctUsbInterface {
             index: self.interface.interface_number() as u16,
             data: &[],
         };
-        self.interface.control_out(control_out).await.into_result()
+        self.paced(self.timeouts().progress, || self.interface.control_out(control_out)).await?.into_result()
             .context("Failed to disable track progress")
             .map_err_to_fsct_device_control_transfer_error()?;
         Ok(())
@@ -146,7 +369,7 @@ impl FsctUsbInterface {
             index: self.interface.interface_number() as u16 | ((text_id as u16) << 8),
             data: text_raw,
         };
-        self.interface.control_out(control_out).await.into_result()
+        self.paced(self.timeouts().text, || self.interface.control_out(control_out)).await?.into_result()
             .context("Failed to send current text")
             .map_err_to_fsct_device_control_transfer_error()?;
         Ok(())
@@ -162,7 +385,7 @@ impl FsctUsbInterface {
             index: self.interface.interface_number() as u16 | ((text_id as u16) << 8),
             data: &[],
         };
-        self.interface.control_out(control_out).await.into_result()
+        self.paced(self.timeouts().text, || self.interface.control_out(control_out)).await?.into_result()
             .context("Failed to send current text")
             .map_err_to_fsct_device_control_transfer_error()?;
         Ok(())
@@ -177,9 +400,56 @@ impl FsctUsbInterface {
             index: self.interface.interface_number() as u16,
             data: &[],
         };
-        self.interface.control_out(control_out).await.into_result()
+        self.paced(self.timeouts().status, || self.interface.control_out(control_out)).await?.into_result()
             .context("Failed to send status")
             .map_err_to_fsct_device_control_transfer_error()?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    pub async fn send_volume(&self, value: u16) -> Result<(), FsctDeviceError> {
+        let control_out = ControlOut {
+            control_type: ControlType::Vendor,
+            recipient: Recipient::Interface,
+            request: requests::FsctRequestCode::Volume as u8,
+            value,
+            index: self.interface.interface_number() as u16,
+            data: &[],
+        };
+        self.paced(self.timeouts().status, || self.interface.control_out(control_out)).await?.into_result()
+            .context("Failed to send volume")
+            .map_err_to_fsct_device_control_transfer_error()?;
+        Ok(())
+    }
+
+    pub async fn send_playback_modes(&self, value: u16) -> Result<(), FsctDeviceError> {
+        let control_out = ControlOut {
+            control_type: ControlType::Vendor,
+            recipient: Recipient::Interface,
+            request: requests::FsctRequestCode::PlaybackModes as u8,
+            value,
+            index: self.interface.interface_number() as u16,
+            data: &[],
+        };
+        self.paced(self.timeouts().status, || self.interface.control_out(control_out)).await?.into_result()
+            .context("Failed to send playback modes")
+            .map_err_to_fsct_device_control_transfer_error()?;
+        Ok(())
+    }
+
+    /// Sends a firmware update request, see [`dfu`](crate::usb::dfu).
+    pub async fn send_dfu_request(&self, request: requests::FsctRequestCode, value: u16, data: &[u8],
+                                  timeout: Duration) -> Result<(), FsctDeviceError> {
+        let control_out = ControlOut {
+            control_type: ControlType::Vendor,
+            recipient: Recipient::Interface,
+            request: request as u8,
+            value,
+            index: self.interface.interface_number() as u16,
+            data,
+        };
+        self.paced(timeout, || self.interface.control_out(control_out)).await?.into_result()
+            .context("Failed to send firmware update request")
+            .map_err_to_fsct_device_control_transfer_error()?;
+        Ok(())
+    }
+}
@@ -15,33 +15,37 @@
 // This file is part of an implementation of Ferrum Streaming Control Technology™,
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::os::fd::OwnedFd;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::time::Duration;
+
 use nusb::DeviceInfo;
+use crate::descriptor_dump::DeviceDescriptorDump;
 use crate::usb::errors::{DeviceDiscoveryError};
+use crate::usb::protocol::FsctProtocol;
+use crate::warmup::DEFAULT_WARMUP_SEQUENCE;
 
 pub mod descriptors;
 pub mod fsct_bos_finder;
 pub mod descriptor_utils;
 mod fsct_usb_interface;
+pub(crate) mod fsct_interface;
 pub mod fsct_device;
 pub mod requests;
+pub mod protocol;
+pub mod dfu;
 
 pub mod errors;
 
-const FSCT_SUPPORTED_PROTOCOL_VERSION: u8 = 0x01;
-
-fn check_fsct_interface_protocol(device_info: &DeviceInfo, fsct_interface_number: u8) -> Result<(), DeviceDiscoveryError> {
+fn negotiate_fsct_interface_protocol(device_info: &DeviceInfo, fsct_interface_number: u8) -> Result<FsctProtocol, DeviceDiscoveryError> {
     let protocol = device_info
         .interfaces()
         .find(|i| i.interface_number() == fsct_interface_number)
         .map(|v| v.protocol())
         .ok_or(DeviceDiscoveryError::InterfaceNotFound)?;
 
-
-    if protocol == FSCT_SUPPORTED_PROTOCOL_VERSION {
-        Ok(())
-    } else {
-        Err(DeviceDiscoveryError::ProtocolVersionNotSupported(protocol))
-    }
+    FsctProtocol::negotiate(protocol)
 }
 
 
@@ -52,16 +56,79 @@ pub async fn open_interface(device_info: &DeviceInfo, interface_number: u8) -> R
     Ok(interface)
 }
 
-pub async fn create_and_configure_fsct_device(device_info: &DeviceInfo) -> Result<fsct_device::FsctDevice, DeviceDiscoveryError> {
-    let fsct_vendor_subclass_number = fsct_bos_finder::get_fsct_vendor_subclass_number_from_device(device_info)?;
+/// Opens the FSCT interface of the device and reads its capabilities, without bringing it up.
+pub async fn create_fsct_device(device_info: &DeviceInfo) -> Result<fsct_device::FsctDevice, DeviceDiscoveryError> {
+    let bos = fsct_bos_finder::read_bos_descriptor(device_info)?;
+    let fsct_vendor_subclass_number = fsct_bos_finder::get_fsct_vendor_subclass_number_from_bos(&bos)
+        .map_err(errors::IoErrorOrAny::from)?;
 
     let fsct_interface_number = find_fsct_interface_number(device_info, fsct_vendor_subclass_number)?;
-    check_fsct_interface_protocol(device_info, fsct_interface_number)?;
+    let protocol = negotiate_fsct_interface_protocol(device_info, fsct_interface_number)?;
     let interface = open_interface(&device_info, fsct_interface_number).await?;
-    let fsct_descriptors = descriptor_utils::get_fsct_functionality_descriptor_set(&interface).await?;
+    read_fsct_device(bos, protocol, interface).await
+}
+
+/// Identity of a device read from its device descriptor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceIdentity {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub usb_version: u16,
+    pub device_version: u16,
+}
+
+/// Like [`create_fsct_device`], for a device the OS opened on behalf of the host, e.g. one Android's `UsbManager`
+/// handed over as a file descriptor once the user granted access to it.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub async fn create_fsct_device_from_fd(fd: OwnedFd)
+                                        -> Result<(fsct_device::FsctDevice, DeviceIdentity), DeviceDiscoveryError> {
+    const TIMEOUT: Duration = Duration::from_secs(1);
+    let device = nusb::Device::from_fd(fd)?;
+    let descriptor = device.get_descriptor(1, 0, 0, TIMEOUT)?;
+    if descriptor.len() < 14 {
+        return Err(anyhow::anyhow!("Device descriptor too short: {} bytes", descriptor.len()).into());
+    }
+    let field = |offset: usize| u16::from_le_bytes([descriptor[offset], descriptor[offset + 1]]);
+    let identity =
+        DeviceIdentity { vendor_id: field(8), product_id: field(10), usb_version: field(2), device_version: field(12) };
+
+    let bos = device.get_descriptor(15, 0, 0, TIMEOUT)?;
+    let fsct_vendor_subclass_number = fsct_bos_finder::get_fsct_vendor_subclass_number_from_bos(&bos)
+        .map_err(errors::IoErrorOrAny::from)?;
+    let configuration = device.active_configuration().map_err(anyhow::Error::from)?;
+    let fsct_interface = configuration
+        .interface_alt_settings()
+        .find(|alt| {
+            alt.alternate_setting() == 0 && alt.class() == 0xFF && alt.subclass() == fsct_vendor_subclass_number
+        })
+        .ok_or(DeviceDiscoveryError::InterfaceNotFound)?;
+    let protocol = FsctProtocol::negotiate(fsct_interface.protocol())?;
+    let interface = device.claim_interface(fsct_interface.interface_number())?;
+    Ok((read_fsct_device(bos, protocol, interface).await?, identity))
+}
+
+/// Reads the capabilities of the device from the claimed FSCT interface.
+async fn read_fsct_device(bos: Vec<u8>, protocol: FsctProtocol, interface: nusb::Interface)
+                          -> Result<fsct_device::FsctDevice, DeviceDiscoveryError> {
+    let raw_descriptors = descriptor_utils::get_fsct_functionality_descriptor_set_raw(&interface).await?;
+    let fsct_descriptors = descriptor_utils::parse_fsct_descriptor_set(&raw_descriptors)?;
     let fsct_interface = fsct_usb_interface::FsctUsbInterface::new(interface);
     let mut fsct_device = fsct_device::FsctDevice::new(fsct_interface);
-    fsct_device.init(&fsct_descriptors).await?;
+    fsct_device.set_protocol(protocol);
+    fsct_device.set_descriptor_dump(DeviceDescriptorDump {
+        bos: fsct_bos_finder::dump_bos_descriptor(&bos),
+        fsct: descriptor_utils::dump_fsct_descriptor_set(&raw_descriptors),
+    });
+    fsct_device.init(&fsct_descriptors);
+    Ok(fsct_device)
+}
+
+/// Opens the FSCT interface of the device and brings it up with [`DEFAULT_WARMUP_SEQUENCE`].
+pub async fn create_and_configure_fsct_device(device_info: &DeviceInfo) -> Result<fsct_device::FsctDevice, DeviceDiscoveryError> {
+    let fsct_device = create_fsct_device(device_info).await?;
+    for step in DEFAULT_WARMUP_SEQUENCE {
+        fsct_device.warm_up(step).await?;
+    }
     Ok(fsct_device)
 }
 
@@ -0,0 +1,152 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Negotiation of the FSCT protocol version announced by a device, in the `bInterfaceProtocol` of its FSCT interface
+//! or the hello of a network session.
+//!
+//! Each version maps to the requests the device understands. Version 1 devices get the requests they always got;
+//! requests added later are sent only to devices speaking a version that has them, so firmware with new requests
+//! works with this host and old firmware isn't sent requests it would stall on.
+
+use bitflags::bitflags;
+use crate::definitions::FsctExtendedFunctionality;
+use crate::usb::errors::DeviceDiscoveryError;
+use crate::usb::requests::FsctRequestCode;
+
+/// First FSCT protocol version: enable, time synchronization, progress, status and current texts.
+pub const FSCT_PROTOCOL_V1: u8 = 0x01;
+/// Adds the volume and playback modes requests, images, the playback queue requests and firmware updates.
+pub const FSCT_PROTOCOL_V2: u8 = 0x02;
+/// Newest FSCT protocol version this host knows.
+pub const FSCT_LATEST_PROTOCOL_VERSION: u8 = FSCT_PROTOCOL_V2;
+
+bitflags! {
+    /// Request groups beyond those of version 1.
+    #[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+    pub struct ProtocolFeatures: u8 {
+        /// `volume` request, for devices announcing [`FsctExtendedFunctionality::Volume`].
+        const Volume = 0x01;
+        /// `playbackModes` request, for devices announcing [`FsctExtendedFunctionality::PlaybackModes`].
+        const PlaybackModes = 0x02;
+        /// `currentImage` request, for artwork.
+        const Images = 0x04;
+        /// `queueLength`, `queuePosition` and `queueText` requests.
+        const Queue = 0x08;
+        /// `firmwareVersion` and firmware update requests, for devices announcing
+        /// [`FsctExtendedFunctionality::FirmwareUpdate`].
+        const FirmwareUpdate = 0x10;
+    }
+}
+
+/// FSCT protocol negotiated with a device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FsctProtocol {
+    version: u8,
+}
+
+impl Default for FsctProtocol {
+    fn default() -> Self {
+        Self { version: FSCT_PROTOCOL_V1 }
+    }
+}
+
+impl FsctProtocol {
+    /// Protocol for a device announcing `version`. Versions newer than [`FSCT_LATEST_PROTOCOL_VERSION`] keep the
+    /// requests of older ones, so such devices are served with the requests of the latest version.
+    pub fn negotiate(version: u8) -> Result<Self, DeviceDiscoveryError> {
+        if version < FSCT_PROTOCOL_V1 {
+            return Err(DeviceDiscoveryError::ProtocolVersionNotSupported(version));
+        }
+        if version > FSCT_LATEST_PROTOCOL_VERSION {
+            log::info!("Device speaks FSCT protocol {}, using the requests of protocol {}", version,
+                       FSCT_LATEST_PROTOCOL_VERSION);
+        }
+        Ok(Self { version })
+    }
+
+    /// Version the device announced.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Request groups the device understands beyond those of version 1.
+    pub fn features(&self) -> ProtocolFeatures {
+        match self.version {
+            FSCT_PROTOCOL_V1 => ProtocolFeatures::empty(),
+            _ => ProtocolFeatures::all(),
+        }
+    }
+
+    /// Whether the device understands `request`.
+    pub fn supports(&self, request: FsctRequestCode) -> bool {
+        let required = match request {
+            FsctRequestCode::Volume => ProtocolFeatures::Volume,
+            FsctRequestCode::PlaybackModes => ProtocolFeatures::PlaybackModes,
+            FsctRequestCode::CurrentImage => ProtocolFeatures::Images,
+            FsctRequestCode::QueueLength | FsctRequestCode::QueuePosition | FsctRequestCode::QueueText => {
+                ProtocolFeatures::Queue
+            }
+            FsctRequestCode::FirmwareVersion | FsctRequestCode::DfuBegin | FsctRequestCode::DfuChunk
+            | FsctRequestCode::DfuFinish | FsctRequestCode::DfuAbort => ProtocolFeatures::FirmwareUpdate,
+            _ => ProtocolFeatures::empty(),
+        };
+        self.features().contains(required)
+    }
+
+    /// Whether the requests of the extended `functionality` are in the protocol; devices only get them if they
+    /// announce the functionality as well.
+    pub fn supports_extended(&self, functionality: FsctExtendedFunctionality) -> bool {
+        let mut required = ProtocolFeatures::empty();
+        required.set(ProtocolFeatures::Volume, functionality.contains(FsctExtendedFunctionality::Volume));
+        required.set(ProtocolFeatures::PlaybackModes, functionality.contains(FsctExtendedFunctionality::PlaybackModes));
+        required.set(ProtocolFeatures::FirmwareUpdate,
+                     functionality.contains(FsctExtendedFunctionality::FirmwareUpdate));
+        self.features().contains(required)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_requests_are_gated_behind_v2() {
+        let v1 = FsctProtocol::negotiate(FSCT_PROTOCOL_V1).unwrap();
+        assert!(v1.supports(FsctRequestCode::CurrentText));
+        assert!(v1.supports(FsctRequestCode::Progress));
+        assert!(!v1.supports(FsctRequestCode::Volume));
+        assert!(!v1.supports(FsctRequestCode::CurrentImage));
+        assert!(!v1.supports(FsctRequestCode::QueueText));
+
+        let v2 = FsctProtocol::negotiate(FSCT_PROTOCOL_V2).unwrap();
+        assert!(v2.supports(FsctRequestCode::CurrentText));
+        assert!(v2.supports(FsctRequestCode::Volume));
+        assert!(v2.supports(FsctRequestCode::PlaybackModes));
+        assert!(v2.supports(FsctRequestCode::QueueLength));
+        assert!(v2.supports(FsctRequestCode::DfuChunk));
+        assert!(!v1.supports_extended(FsctExtendedFunctionality::FirmwareUpdate));
+        assert!(v2.supports_extended(FsctExtendedFunctionality::Volume | FsctExtendedFunctionality::FirmwareUpdate));
+    }
+
+    #[test]
+    fn newer_versions_get_the_latest_features() {
+        let v3 = FsctProtocol::negotiate(0x03).unwrap();
+        assert_eq!(v3.version(), 0x03);
+        assert_eq!(v3.features(), ProtocolFeatures::all());
+        assert!(matches!(FsctProtocol::negotiate(0x00), Err(DeviceDiscoveryError::ProtocolVersionNotSupported(0))));
+    }
+}
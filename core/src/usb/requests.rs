@@ -28,7 +28,8 @@ pub type Timestamp = u64;
 /// and the timestamp when the playback state was recorded. It allows tracking
 /// the real-time status and progress of the audio playback.
 pub struct TrackProgressRequestData {
-    /// Audio track duration in seconds.
+    /// Audio track duration in seconds; 0 for content without a known duration (live mode), which is only sent to
+    /// devices announcing `LiveProgress`.
     pub duration: u32,
     /// Position in seconds from the start of playback. Position below 0 means pre-track silence.
     pub position: i32,
@@ -38,6 +39,61 @@ pub struct TrackProgressRequestData {
     pub rate: f32,
 }
 
+#[repr(C, packed)]
+#[derive(Debug, Default, Clone, Copy)]
+/// Most recent error of the device firmware, read with the `errorReport` request.
+pub struct ErrorReportRequestData {
+    /// Error code, 0 if no error occurred since the previous read.
+    pub code: u16,
+    /// Code specific detail, e.g. the text metadata id that failed to render.
+    pub detail: u16,
+    /// Number of occurrences since the previous read.
+    pub count: u16,
+}
+
+/// Playback command codes, the first byte of a command report read from the interrupt IN endpoint of the FSCT
+/// interface.
+///
+/// `Seek` is followed by the target position in seconds from the start of the track, as a little-endian `i32`;
+/// the other commands have no arguments.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsctCommandCode {
+    Play = 0x01,
+    Pause = 0x02,
+    Next = 0x03,
+    Previous = 0x04,
+    Seek = 0x05,
+    /// Volume set on the device, e.g. with its knob; the argument is a 2-byte `volume` request value.
+    Volume = 0x06,
+}
+
+impl FsctCommandCode {
+    pub fn from_raw(raw: u8) -> Option<Self> {
+        match raw {
+            0x01 => Some(Self::Play),
+            0x02 => Some(Self::Pause),
+            0x03 => Some(Self::Next),
+            0x04 => Some(Self::Previous),
+            0x05 => Some(Self::Seek),
+            0x06 => Some(Self::Volume),
+            _ => None,
+        }
+    }
+}
+
+/// Maximum length of a command report: the command code and a 4-byte argument.
+pub const COMMAND_REPORT_MAX_LENGTH: usize = 5;
+
+/// Flag of the `volume` request value set when the player is muted.
+pub const VOLUME_MUTED: u16 = 0x0100;
+
+/// Value of the `volume` request when the player doesn't report its volume.
+pub const VOLUME_UNKNOWN: u16 = 0xFFFF;
+
+/// Byte of the `playbackModes` request value for modes the player doesn't report.
+pub const PLAYBACK_MODE_UNKNOWN: u8 = 0xFF;
+
 /// Represents the request codes used in Fsct USB communication.
 ///
 /// This enumeration defines specific codes for handling vendor-specific USB requests
@@ -57,8 +113,22 @@ pub enum FsctRequestCode {
     Progress = 0x03,
     /// `status`: type: FsctStatus.
     Status = 0x04,
-    /// `poll`: empty request for ensuring that service is alive i.e. reset devices internal watchdog without sending any data
+    /// `poll`: empty request for ensuring that service is alive i.e. reset devices internal watchdog without sending any data;
+    /// also timed by the host to measure the write latency of the device.
     Poll = 0x05,
+    /// `notify`: wValue lower half word contains FsctNotification enum values; only for devices supporting attention signals.
+    Notify = 0x06,
+    /// `errorReport`: type: ErrorReportRequestData; reading it clears the reported error on the device.
+    ErrorReport = 0x07,
+    /// `volume`: wValue contains the volume in percent in the lower byte, with [`VOLUME_MUTED`] set when muted, or
+    /// [`VOLUME_UNKNOWN`]; only for devices announcing the volume extended functionality.
+    Volume = 0x08,
+    /// `playbackModes`: wValue contains the shuffle flag (0 or 1) in the lower byte and the RepeatMode enum value in
+    /// the upper byte, each [`PLAYBACK_MODE_UNKNOWN`] when not reported; only for devices announcing the playback
+    /// modes extended functionality.
+    PlaybackModes = 0x09,
+    /// `firmwareVersion`: type: u16, BCD version of the running firmware, like bcdDevice.
+    FirmwareVersion = 0x0A,
     /// `currentText`: wIndex lower half word contains FsctTextMetadata enum values.
     CurrentText = 0x10,
     /// `currentImage`: image data is provided in the format described in FsctImageMetadataDescriptor; wIndex contains index of image.
@@ -69,8 +139,23 @@ pub enum FsctRequestCode {
     QueuePosition = 0x22,
     /// `queueText`: wIndex lower half word contains FsctTextMetadata enum values; wValue contains index in queue.
     QueueText = 0x23,
+    /// `dfuBegin`: data contains the length of the firmware image as u32; the device leaves normal operation and
+    /// prepares for the image. Firmware update requests are only for devices announcing the firmware update extended
+    /// functionality.
+    DfuBegin = 0x30,
+    /// `dfuChunk`: data contains the next part of the image, at most [`DFU_CHUNK_SIZE`] bytes; wValue contains the
+    /// sequence number of the chunk, wrapping around.
+    DfuChunk = 0x31,
+    /// `dfuFinish`: data contains the CRC-32 of the whole image as u32; the device verifies and applies the image,
+    /// then restarts.
+    DfuFinish = 0x32,
+    /// `dfuAbort`: empty request discarding the image received so far; the device returns to normal operation.
+    DfuAbort = 0x33,
 }
 
+/// Maximum length of the data of a `dfuChunk` request.
+pub const DFU_CHUNK_SIZE: usize = 1024;
+
 
 /// Defines the enabling or disabling states for Ferrum Streaming Control Technology (FSCT) USB function.
 ///
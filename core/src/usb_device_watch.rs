@@ -19,22 +19,22 @@ use std::sync::Arc;
 use std::time::Duration;
 use nusb::{list_devices, DeviceId, DeviceInfo};
 use log::{debug, info, warn};
-use nusb::hotplug::HotplugEvent;
+use nusb::hotplug::{HotplugEvent, HotplugWatch};
 use futures::StreamExt;
 use crate::device_manager::{DeviceManagement, ManagedDeviceId};
-use crate::usb::create_and_configure_fsct_device;
+use crate::usb::create_fsct_device;
 use crate::usb::errors::DeviceDiscoveryError;
-use crate::service::{ServiceHandle, spawn_service};
+use crate::service::{ServiceHandle, StopHandle, spawn_service};
 
 /// Tries to initialize a device and add it to the device manager
 async fn try_initialize_device_and_add_to_manager<T: DeviceManagement>(
     device_info: &DeviceInfo,
     device_manager: &T,
 ) -> Result<ManagedDeviceId, DeviceDiscoveryError> {
-    let device = create_and_configure_fsct_device(device_info).await?;
+    let device = create_fsct_device(device_info).await?;
 
-    // Enable the device
-    device.set_enable(true).await?;
+    // Bring the device up, as its quirks require
+    device_manager.warm_up_device(&device, device_info).await?;
 
     // Add to device manager
     let managed_id = device_manager.add_device(Arc::new(device), device_info);
@@ -47,11 +47,24 @@ async fn get_device_info_by_id(device_id: DeviceId) -> Option<nusb::DeviceInfo>
     list_devices().ok()?.find(|device| device.id() == device_id)
 }
 
+/// Whether the device filter of the manager lets the device be driven, logging devices it leaves alone
+fn is_accepted<T: DeviceManagement>(device_info: &DeviceInfo, device_manager: &T) -> bool {
+    let accepted = device_manager.accepts_device(device_info);
+    if !accepted {
+        debug!("Ignoring device {:04x}:{:04x} excluded by the device filter", device_info.vendor_id(),
+               device_info.product_id());
+    }
+    accepted
+}
+
 /// Runs device initialization in a separate task
 async fn run_device_initialization<T: DeviceManagement + Send + Sync + 'static>(
     device_info: DeviceInfo,
     device_manager: Arc<T>,
 ) {
+    if !is_accepted(&device_info, device_manager.as_ref()) {
+        return;
+    }
     tokio::spawn(async move {
         let retry_timeout = Duration::from_secs(3);
         let retry_period = Duration::from_millis(100);
@@ -117,52 +130,127 @@ async fn deinitialize_devices<T: DeviceManagement>(device_manager: &T) {
     }
 }
 
+/// First delay before re-establishing an interrupted watch, doubled up to [`MAX_REWATCH_DELAY`] while it fails.
+const INITIAL_REWATCH_DELAY: Duration = Duration::from_secs(1);
+const MAX_REWATCH_DELAY: Duration = Duration::from_secs(30);
+
+/// Brings the device manager in line with the present devices: initializes devices not managed yet and removes
+/// managed devices that are gone
+pub(crate) async fn enumerate_devices<T: DeviceManagement>(device_manager: &T) {
+    let devices: Vec<DeviceInfo> = match list_devices() {
+        Ok(devices) => devices.collect(),
+        Err(e) => {
+            warn!("Failed to enumerate USB devices: {}", e);
+            return;
+        }
+    };
+    for usb_id in device_manager.get_all_usb_ids() {
+        if !devices.iter().any(|device_info| device_info.id() == usb_id)
+            && device_manager.remove_device_by_usb_id(usb_id).is_some() {
+            info!("FSCT Device removed while the device watch was interrupted");
+        }
+    }
+    for device_info in devices {
+        if device_manager.get_managed_id_for_usb_id(device_info.id()).is_some()
+            || !is_accepted(&device_info, device_manager) {
+            continue;
+        }
+        let res = try_initialize_device_and_add_to_manager(&device_info, device_manager).await;
+        log_device_initialize_result(Some(res), &device_info);
+    }
+}
+
+/// Why processing of hotplug events ended
+enum WatchEnd {
+    Stopped,
+    Interrupted,
+}
+
+/// Processes hotplug events until shutdown is requested or the stream ends
+async fn process_hotplug_events<T: DeviceManagement + Send + Sync + 'static>(
+    devices_plug_events_stream: &mut HotplugWatch,
+    device_manager: &Arc<T>,
+    stop_handle: &mut StopHandle,
+) -> WatchEnd {
+    loop {
+        // Use tokio::select! to wait for either a device event or shutdown signal
+        tokio::select! {
+            maybe_event = devices_plug_events_stream.next() => {
+                match maybe_event {
+                    Some(HotplugEvent::Connected(device_info)) => {
+                        run_device_initialization(
+                            device_info,
+                            device_manager.clone(),
+                        ).await;
+                    }
+                    Some(HotplugEvent::Disconnected(device_id)) => {
+                        // Remove the device from the manager
+                        if let Some(removed_device) = device_manager.remove_device_by_usb_id(device_id) {
+                            drop(removed_device);
+                            info!("FSCT Device removed");
+                        }
+                    }
+                    None => {
+                        // Stream ended
+                        debug!("Device events stream ended");
+                        return WatchEnd::Interrupted;
+                    }
+                }
+            },
+            _ = stop_handle.signaled() => {
+                debug!("Shutdown requested, stopping USB device watch task");
+                return WatchEnd::Stopped;
+            }
+        }
+    }
+}
+
+/// Re-establishes the hotplug watch, retrying with a growing delay; `None` if shutdown was requested meanwhile
+async fn rewatch_devices(stop_handle: &mut StopHandle) -> Option<HotplugWatch> {
+    let mut delay = INITIAL_REWATCH_DELAY;
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = stop_handle.signaled() => return None,
+        }
+        match nusb::watch_devices() {
+            Ok(stream) => return Some(stream),
+            Err(e) => {
+                delay = (delay * 2).min(MAX_REWATCH_DELAY);
+                warn!("Failed to re-establish the USB device watch: {}, retrying in {:?}", e, delay);
+            }
+        }
+    }
+}
+
 /// Runs the USB device watch task
+///
+/// If the hotplug event stream ends (e.g. on a backend error or a udev restart), the watch is re-established and
+/// devices attached or detached meanwhile are picked up by a re-enumeration.
 pub async fn run_usb_device_watch<T: DeviceManagement + Send + Sync + 'static>(
     device_manager: Arc<T>,
 ) -> Result<ServiceHandle, anyhow::Error> {
     let mut devices_plug_events_stream = nusb::watch_devices()?;
 
     let handle = spawn_service(move |mut stop_handle| async move {
-        // Initialize existing devices
-        let devices = list_devices().unwrap();
-        for device_info in devices {
-            let res = try_initialize_device_and_add_to_manager(&device_info, &*device_manager).await;
-            log_device_initialize_result(Some(res), &device_info);
-        }
-
-        // Process events until shutdown is requested or stream ends
         loop {
-            // Use tokio::select! to wait for either a device event or shutdown signal
-            tokio::select! {
-                maybe_event = devices_plug_events_stream.next() => {
-                    match maybe_event {
-                        Some(event) => {
-                            match event {
-                                HotplugEvent::Connected(device_info) => {
-                                    run_device_initialization(
-                                        device_info,
-                                        device_manager.clone(),
-                                    ).await;
-                                }
-                                HotplugEvent::Disconnected(device_id) => {
-                                    // Remove the device from the manager
-                                    if let Some(removed_device) = device_manager.remove_device_by_usb_id(device_id) {
-                                        drop(removed_device);
-                                        info!("FSCT Device removed");
-                                    }
-                                }
-                            }
-                        },
-                        None => {
-                            // Stream ended
-                            debug!("Device events stream ended");
-                            break;
-                        }
-                    }
-                },
-                _ = stop_handle.signaled() => {
-                    debug!("Shutdown requested, stopping USB device watch task");
+            // Initialize present devices
+            enumerate_devices(&*device_manager).await;
+
+            match process_hotplug_events(&mut devices_plug_events_stream, &device_manager, &mut stop_handle).await {
+                WatchEnd::Stopped => {
+                    deinitialize_devices(&*device_manager).await;
+                    break;
+                }
+                WatchEnd::Interrupted => {
+                    warn!("USB device watch interrupted, re-establishing it");
+                    device_manager.report_watch_interrupted();
+                }
+            }
+
+            match rewatch_devices(&mut stop_handle).await {
+                Some(stream) => devices_plug_events_stream = stream,
+                None => {
                     deinitialize_devices(&*device_manager).await;
                     break;
                 }
@@ -171,4 +259,4 @@ pub async fn run_usb_device_watch<T: DeviceManagement + Send + Sync + 'static>(
     });
 
     Ok(handle)
-}
\ No newline at end of file
+}
@@ -0,0 +1,322 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Validation of player input arriving through externally reachable APIs, and sanitizing of what ports report.
+
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use log::warn;
+use thiserror::Error;
+
+use crate::definitions::{TimelineInfo, VolumeInfo};
+use crate::driver_middleware::{DriverInterceptor, StateUpdate};
+use crate::player_manager::ManagedPlayerId;
+use crate::player_state::PlayerState;
+
+/// Maximum length of a player self id, in bytes.
+pub const MAX_SELF_ID_LENGTH: usize = 256;
+/// Maximum length of a single metadata text, in bytes. Devices truncate further to what they can display.
+pub const MAX_TEXT_LENGTH: usize = 4096;
+/// Longest accepted track duration.
+pub const MAX_TRACK_DURATION: Duration = Duration::from_secs(7 * 24 * 3600);
+/// Largest accepted absolute playback rate.
+pub const MAX_PLAYBACK_RATE: f64 = 16.0;
+/// Longest accepted route override; overrides meant to last longer are given without a duration.
+pub const MAX_ROUTE_DURATION: Duration = Duration::from_secs(7 * 24 * 3600);
+/// How far the position may exceed the duration, to tolerate clients rounding differently.
+const POSITION_TOLERANCE: Duration = Duration::from_secs(5);
+/// How far in the future a timeline update time may lie, to tolerate clock skew.
+const UPDATE_TIME_TOLERANCE: Duration = Duration::from_secs(60);
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    #[error("{field} is {length} bytes long, at most {max} are allowed")]
+    TooLong { field: &'static str, length: usize, max: usize },
+    #[error("{field} is empty")]
+    Empty { field: &'static str },
+    #[error("{field} is not valid UTF-8")]
+    InvalidUtf8 { field: &'static str },
+    #[error("{field} contains control characters")]
+    ControlCharacters { field: &'static str },
+    #[error("Invalid timeline: {0}")]
+    InvalidTimeline(&'static str),
+    #[error("Volume {0} is above 100")]
+    VolumeOutOfRange(u8),
+    #[error("Route override of {0:?} is longer than allowed")]
+    RouteTooLong(Duration),
+}
+
+/// Decodes raw bytes received from a client, rejecting invalid UTF-8 instead of replacing it.
+pub fn decode_text<'a>(field: &'static str, bytes: &'a [u8]) -> Result<&'a str, ValidationError> {
+    std::str::from_utf8(bytes).map_err(|_| ValidationError::InvalidUtf8 { field })
+}
+
+pub fn validate_self_id(self_id: &str) -> Result<(), ValidationError> {
+    if self_id.is_empty() {
+        return Err(ValidationError::Empty { field: "self_id" });
+    }
+    validate_str("self_id", self_id, MAX_SELF_ID_LENGTH)
+}
+
+pub fn validate_text(text: &str) -> Result<(), ValidationError> {
+    validate_str("text", text, MAX_TEXT_LENGTH)
+}
+
+pub fn validate_timeline(timeline: &TimelineInfo) -> Result<(), ValidationError> {
+    if !timeline.rate.is_finite() || timeline.rate.abs() > MAX_PLAYBACK_RATE {
+        return Err(ValidationError::InvalidTimeline("rate out of range"));
+    }
+    if let Some(duration) = timeline.duration {
+        if duration > MAX_TRACK_DURATION {
+            return Err(ValidationError::InvalidTimeline("duration too long"));
+        }
+        if timeline.position > duration + POSITION_TOLERANCE {
+            return Err(ValidationError::InvalidTimeline("position beyond duration"));
+        }
+    }
+    if timeline.update_time > SystemTime::now() + UPDATE_TIME_TOLERANCE {
+        return Err(ValidationError::InvalidTimeline("update time in the future"));
+    }
+    Ok(())
+}
+
+pub fn validate_volume(volume: &VolumeInfo) -> Result<(), ValidationError> {
+    if volume.level > 100 {
+        return Err(ValidationError::VolumeOutOfRange(volume.level));
+    }
+    Ok(())
+}
+
+pub fn validate_route_duration(duration: Duration) -> Result<(), ValidationError> {
+    if duration > MAX_ROUTE_DURATION {
+        return Err(ValidationError::RouteTooLong(duration));
+    }
+    Ok(())
+}
+
+pub fn validate_update(update: &StateUpdate) -> Result<(), ValidationError> {
+    match update {
+        StateUpdate::State(state) => {
+            if let Some(timeline) = &state.timeline {
+                validate_timeline(timeline)?;
+            }
+            if let Some(volume) = &state.volume {
+                validate_volume(volume)?;
+            }
+            state.texts.iter().filter_map(|(_, text)| text.as_deref()).try_for_each(validate_text)
+        }
+        StateUpdate::Status(_) => Ok(()),
+        StateUpdate::Timeline(timeline) => timeline.as_ref().map_or(Ok(()), validate_timeline),
+        StateUpdate::Volume(volume) => volume.as_ref().map_or(Ok(()), validate_volume),
+        StateUpdate::Modes(_) => Ok(()),
+        StateUpdate::Text(_, text) => text.as_deref().map_or(Ok(()), validate_text),
+    }
+}
+
+/// Repairs a timeline [`validate_timeline`] rejects where a sane value is obvious: the rate is capped, a too long
+/// duration dropped as for live streams, the position capped at the duration and a future update time moved to `now`.
+///
+/// Returns what was repaired, or an error for timelines that can't be repaired, i.e. with a NaN or infinite rate.
+pub fn sanitize_timeline(timeline: &mut TimelineInfo, now: SystemTime)
+                         -> Result<Vec<ValidationError>, ValidationError> {
+    if !timeline.rate.is_finite() {
+        return Err(ValidationError::InvalidTimeline("rate is not finite"));
+    }
+    let mut repaired = Vec::new();
+    if timeline.rate.abs() > MAX_PLAYBACK_RATE {
+        timeline.rate = timeline.rate.clamp(-MAX_PLAYBACK_RATE, MAX_PLAYBACK_RATE);
+        repaired.push(ValidationError::InvalidTimeline("rate out of range"));
+    }
+    if timeline.duration.is_some_and(|duration| duration > MAX_TRACK_DURATION) {
+        timeline.duration = None;
+        repaired.push(ValidationError::InvalidTimeline("duration too long"));
+    }
+    if let Some(duration) = timeline.duration
+        && timeline.position > duration + POSITION_TOLERANCE
+    {
+        timeline.position = duration;
+        repaired.push(ValidationError::InvalidTimeline("position beyond duration"));
+    }
+    if timeline.update_time > now + UPDATE_TIME_TOLERANCE {
+        timeline.update_time = now;
+        repaired.push(ValidationError::InvalidTimeline("update time in the future"));
+    }
+    Ok(repaired)
+}
+
+/// Removes control characters from the text, turning line breaks and tabs into spaces, and truncates it to
+/// [`MAX_TEXT_LENGTH`]; returns what was repaired.
+pub fn sanitize_text(text: &mut String) -> Vec<ValidationError> {
+    let mut repaired = Vec::new();
+    if text.chars().any(char::is_control) {
+        *text = text.chars()
+                    .filter_map(|c| match c {
+                        '\n' | '\r' | '\t' => Some(' '),
+                        c if c.is_control() => None,
+                        c => Some(c),
+                    })
+                    .collect();
+        repaired.push(ValidationError::ControlCharacters { field: "text" });
+    }
+    if text.len() > MAX_TEXT_LENGTH {
+        repaired.push(ValidationError::TooLong { field: "text", length: text.len(), max: MAX_TEXT_LENGTH });
+        let end = (0..=MAX_TEXT_LENGTH).rev().find(|&i| text.is_char_boundary(i)).unwrap_or(0);
+        text.truncate(end);
+    }
+    repaired
+}
+
+/// Caps the volume level at 100; returns what was repaired.
+pub fn sanitize_volume(volume: &mut VolumeInfo) -> Vec<ValidationError> {
+    match validate_volume(volume) {
+        Ok(()) => Vec::new(),
+        Err(e) => {
+            volume.level = 100;
+            vec![e]
+        }
+    }
+}
+
+/// Sanitizes all parts of the state, see [`sanitize_timeline`], [`sanitize_text`] and [`sanitize_volume`]; a
+/// timeline that can't be repaired is dropped. Returns what was repaired or dropped.
+pub fn sanitize_state(state: &mut PlayerState, now: SystemTime) -> Vec<ValidationError> {
+    let mut repaired = Vec::new();
+    if let Some(timeline) = &mut state.timeline {
+        match sanitize_timeline(timeline, now) {
+            Ok(timeline_repaired) => repaired.extend(timeline_repaired),
+            Err(e) => {
+                state.timeline = None;
+                repaired.push(e);
+            }
+        }
+    }
+    for &text_id in state.texts.iter_id() {
+        if let Some(text) = state.texts.get_mut_text(text_id) {
+            repaired.extend(sanitize_text(text));
+        }
+    }
+    if let Some(volume) = &mut state.volume {
+        repaired.extend(sanitize_volume(volume));
+    }
+    repaired
+}
+
+fn validate_str(field: &'static str, value: &str, max: usize) -> Result<(), ValidationError> {
+    if value.len() > max {
+        return Err(ValidationError::TooLong { field, length: value.len(), max });
+    }
+    if value.chars().any(char::is_control) {
+        return Err(ValidationError::ControlCharacters { field });
+    }
+    Ok(())
+}
+
+/// Interceptor dropping invalid state updates before they reach any device.
+pub struct ValidatingInterceptor;
+
+#[async_trait]
+impl DriverInterceptor for ValidatingInterceptor {
+    async fn intercept(&self, player_id: ManagedPlayerId, update: StateUpdate) -> Option<StateUpdate> {
+        match validate_update(&update) {
+            Ok(()) => Some(update),
+            Err(e) => {
+                warn!("Dropping update of player {}: {}", player_id, e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::FsctTextMetadata;
+
+    fn timeline(position: u64, duration: u64, rate: f64) -> TimelineInfo {
+        TimelineInfo {
+            position: Duration::from_secs(position),
+            update_time: SystemTime::now(),
+            duration: Some(Duration::from_secs(duration)),
+            rate,
+        }
+    }
+
+    #[test]
+    fn texts_are_capped_and_free_of_control_characters() {
+        assert!(validate_update(&StateUpdate::Text(FsctTextMetadata::CurrentTitle, Some("Song".into()))).is_ok());
+        assert_eq!(
+            validate_text(&"a".repeat(MAX_TEXT_LENGTH + 1)),
+            Err(ValidationError::TooLong { field: "text", length: MAX_TEXT_LENGTH + 1, max: MAX_TEXT_LENGTH })
+        );
+        assert_eq!(validate_text("bad\u{1b}[2J"), Err(ValidationError::ControlCharacters { field: "text" }));
+        assert_eq!(validate_self_id(""), Err(ValidationError::Empty { field: "self_id" }));
+        assert_eq!(decode_text("text", b"\xff\xfe"), Err(ValidationError::InvalidUtf8 { field: "text" }));
+    }
+
+    #[test]
+    fn timelines_must_be_sane() {
+        assert!(validate_timeline(&timeline(10, 100, 1.0)).is_ok());
+        assert!(validate_timeline(&timeline(200, 100, 1.0)).is_err());
+        assert!(validate_timeline(&timeline(10, 100, f64::NAN)).is_err());
+        assert!(validate_timeline(&timeline(10, 100, 100.0)).is_err());
+        let live = TimelineInfo { duration: None, ..timeline(200, 100, 1.0) };
+        assert!(validate_timeline(&live).is_ok());
+        let mut future = timeline(10, 100, 1.0);
+        future.update_time += Duration::from_secs(3600);
+        assert!(validate_timeline(&future).is_err());
+    }
+
+    #[test]
+    fn timelines_are_repaired_where_possible() {
+        let now = SystemTime::now();
+        let mut beyond = timeline(200, 100, 100.0);
+        beyond.update_time = now + Duration::from_secs(3600);
+        assert_eq!(sanitize_timeline(&mut beyond, now).unwrap().len(), 3);
+        assert_eq!(beyond.position, Duration::from_secs(100));
+        assert_eq!((beyond.rate, beyond.update_time), (MAX_PLAYBACK_RATE, now));
+        assert!(validate_timeline(&beyond).is_ok());
+        let mut endless = TimelineInfo { duration: Some(MAX_TRACK_DURATION * 2), ..timeline(10, 100, 1.0) };
+        assert_eq!(sanitize_timeline(&mut endless, now).unwrap().len(), 1);
+        assert_eq!(endless.duration, None);
+        assert!(sanitize_timeline(&mut timeline(10, 100, f64::NAN), now).is_err());
+    }
+
+    #[test]
+    fn states_are_sanitized() {
+        let mut state = PlayerState {
+            timeline: Some(timeline(10, 100, f64::INFINITY)),
+            volume: Some(VolumeInfo { level: 150, muted: false }),
+            ..Default::default()
+        };
+        state.texts.title = Some("Line\nbreak\u{1b}[2J".into());
+        state.texts.artist = Some("é".repeat(MAX_TEXT_LENGTH));
+        assert_eq!(sanitize_state(&mut state, SystemTime::now()).len(), 4);
+        assert_eq!(state.timeline, None);
+        assert_eq!(state.volume, Some(VolumeInfo { level: 100, muted: false }));
+        assert_eq!(state.texts.title.as_deref(), Some("Line break[2J"));
+        assert_eq!(state.texts.artist.as_ref().map(String::len), Some(MAX_TEXT_LENGTH));
+        assert!(state.texts.iter().filter_map(|(_, text)| text.as_deref()).all(|text| validate_text(text).is_ok()));
+    }
+
+    #[test]
+    fn volume_is_a_percentage() {
+        assert!(validate_update(&StateUpdate::Volume(Some(VolumeInfo { level: 100, muted: true }))).is_ok());
+        let volume = VolumeInfo { level: 101, muted: false };
+        assert_eq!(validate_volume(&volume), Err(ValidationError::VolumeOutOfRange(101)));
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Attach-time warm-up of devices.
+//!
+//! Before a device is announced with [`DeviceEvent::Added`](crate::DeviceEvent::Added), it is brought up in an
+//! explicit sequence of [`WarmupStep`]s, each completed step being reported as
+//! [`DeviceEvent::WarmupStep`](crate::DeviceEvent::WarmupStep). Devices needing a different bring-up are described
+//! in the [`QuirkTable`](crate::quirks::QuirkTable).
+
+use serde::{Deserialize, Serialize};
+
+/// A step of bringing up a freshly attached device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarmupStep {
+    /// Enable the FSCT function of the device.
+    Enable,
+    /// Select the text encoding: the one announced by the device or the one forced by its quirks.
+    Encoding,
+    /// Synchronize the device clock, for devices showing progress.
+    TimeSync,
+    /// Reset the status to stopped.
+    Status,
+    /// Clear all supported texts.
+    Texts,
+    /// Switch the progress off.
+    Progress,
+}
+
+/// Warm-up sequence of devices without quirks.
+pub const DEFAULT_WARMUP_SEQUENCE: [WarmupStep; 6] = [
+    WarmupStep::Enable,
+    WarmupStep::Encoding,
+    WarmupStep::TimeSync,
+    WarmupStep::Status,
+    WarmupStep::Texts,
+    WarmupStep::Progress,
+];
@@ -0,0 +1,637 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! `fsctctl`, control of a running FSCT host service from the command line.
+//!
+//! Talks to the driver the service serves over gRPC: the `driver_server` address of the config file the service reads,
+//! port 50151 on loopback by default. Unless given one, it presents a token of the `driver_auth` of the config file,
+//! or else the token the service wrote to its token file. Players are named by their managed id or by their self id
+//! (e.g. the MPRIS bus name).
+
+use std::fmt::Write as _;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use fsct_core::assignment_file::AssignmentFile;
+use fsct_core::auth::{default_token_path, read_token_file, Scope};
+use fsct_core::config::{default_config_path, HostConfig};
+use fsct_core::descriptor_dump::{DescriptorDump, DeviceDescriptorDump};
+use fsct_core::device_history::{format_bcd_version, DeviceAttachRecord};
+use fsct_core::orchestrator::DndScope;
+use fsct_core::remote::{RemoteDriver, DEFAULT_DRIVER_SERVER_ADDRESS};
+#[cfg(feature = "self-update")]
+use fsct_core::update::{default_installation_id_path, install, installation_id, UpdateChannel, Updater, Version};
+use fsct_core::{FsctDriver, ManagedDeviceId, ManagedPlayerId, PlayerInfo};
+use tokio::sync::broadcast::error::RecvError;
+
+#[derive(Parser)]
+#[command(author, version, about = "Controls a running FSCT host service", long_about = None)]
+struct Cli {
+    /// Driver server of the service; by default the `driver_server` of the config file, 127.0.0.1:50151 without one
+    #[arg(long, global = true)]
+    endpoint: Option<String>,
+
+    /// Bearer token of the driver server; by default a `driver_auth` token of the config file, or else the one the
+    /// service on this host wrote to its token file
+    #[arg(long, global = true, env = "FSCT_TOKEN")]
+    token: Option<String>,
+
+    /// Print JSON instead of text
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// List attached devices or registered players
+    List {
+        #[arg(value_enum)]
+        what: ListTarget,
+    },
+    /// Assign a player to a device
+    Assign {
+        /// Managed id or self id of the player
+        player: String,
+        /// Managed id of the device, as listed by `list devices`
+        device: ManagedDeviceId,
+    },
+    /// Set the preferred player, shown on devices whenever it plays; `none` clears it
+    SetPreferred {
+        /// Managed id or self id of the player, or `none`
+        player: String,
+    },
+    /// Show the readiness of the service, the preferred player, the registered players and the attached devices;
+    /// exits with status 1 if the service is not ready
+    Status,
+    /// Print player and device events as JSON lines until interrupted
+    Watch,
+    /// Restart a port of the service, e.g. an OS watcher that stopped following the players
+    RestartPort {
+        /// Name of the port, as listed by `list ports`
+        port: String,
+    },
+    /// Inspect an attached device
+    Device {
+        #[command(subcommand)]
+        command: DeviceCommands,
+    },
+    /// Export or import the assignments of players to devices, to replicate a setup onto another machine
+    Assignments {
+        #[command(subcommand)]
+        command: AssignmentCommands,
+    },
+    /// Update the firmware of devices taking firmware updates over their FSCT interface
+    Firmware {
+        #[command(subcommand)]
+        command: FirmwareCommands,
+    },
+    /// Suspend writes to devices (do not disturb), resume them or show which devices are suspended
+    Dnd {
+        #[command(subcommand)]
+        command: DndCommands,
+    },
+    /// Check for a new release of the host or install it; does not need the service running
+    #[cfg(feature = "self-update")]
+    Update {
+        #[command(subcommand)]
+        command: UpdateCommands,
+    },
+    /// Force a player onto a device over assignments and selection rules, until it stops or for a while
+    Route {
+        #[command(subcommand)]
+        command: RouteCommands,
+    },
+    /// Show for how long players were shown on devices, recorded by services with `usage_stats` in their config file
+    Report,
+}
+
+#[cfg(feature = "self-update")]
+#[derive(Subcommand)]
+enum UpdateCommands {
+    /// Show the release offered to this installation, if any
+    Check {
+        #[arg(long, default_value = "stable")]
+        channel: UpdateChannel,
+    },
+    /// Download and verify the release offered to this installation and run its installer
+    Install {
+        #[arg(long, default_value = "stable")]
+        channel: UpdateChannel,
+    },
+}
+
+#[derive(Subcommand)]
+enum RouteCommands {
+    /// Show the player on the device right away, replacing any previous override of the device
+    Set {
+        /// Managed id or self id of the player
+        player: String,
+        /// Managed id of the device, as listed by `list devices`
+        device: ManagedDeviceId,
+        /// Seconds after which the override ends; by default it ends when the player stops
+        #[arg(long = "for")]
+        duration_secs: Option<f64>,
+    },
+    /// Remove the override of the device
+    Clear {
+        /// Managed id of the device
+        device: ManagedDeviceId,
+    },
+    /// Show the active overrides
+    List,
+}
+
+#[derive(Subcommand)]
+enum DndCommands {
+    /// Suspend writes to a device, or to all devices
+    On {
+        /// Managed id of the device; all devices if not given
+        #[arg(long)]
+        device: Option<ManagedDeviceId>,
+    },
+    /// Resume writes to a device, or lift the switch of all devices
+    Off {
+        /// Managed id of the device; the switch of all devices if not given
+        #[arg(long)]
+        device: Option<ManagedDeviceId>,
+    },
+    /// Show the active switches
+    Status,
+}
+
+#[derive(Subcommand)]
+enum DeviceCommands {
+    /// Print the BOS and FSCT descriptors of the device, raw and as parsed by the host
+    Descriptors {
+        /// Managed id of the device, as listed by `list devices`
+        device: ManagedDeviceId,
+    },
+}
+
+#[derive(Subcommand)]
+enum AssignmentCommands {
+    /// Write the assignments of the registered players as a JSON file
+    Export {
+        /// File to write; standard output if not given
+        file: Option<PathBuf>,
+    },
+    /// Assign the registered players to the devices of an exported file; `*` in its self ids matches any characters
+    Import {
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum FirmwareCommands {
+    /// Stream a firmware image to a device, wait for it to restart and print the firmware version it runs
+    Update {
+        /// Firmware image
+        file: PathBuf,
+        /// Managed id of the device; may be left out if a single attached device takes firmware updates
+        #[arg(long)]
+        device: Option<ManagedDeviceId>,
+        /// Firmware version of the image, as listed by `list devices`, e.g. 1.02; fails if the device reports another
+        #[arg(long)]
+        expect_version: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ListTarget {
+    Devices,
+    Players,
+    Ports,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    #[cfg(feature = "self-update")]
+    if let Commands::Update { command } = &cli.command {
+        return update(command, cli.json).await;
+    }
+    let config = HostConfig::load(&default_config_path()).unwrap_or_else(|e| {
+        eprintln!("{}, connecting with defaults", e);
+        HostConfig::default()
+    });
+    let endpoint = cli.endpoint.clone().unwrap_or_else(|| driver_endpoint(&config));
+    let token = cli.token.clone().or_else(|| driver_token(&config));
+    let driver = match &token {
+        Some(token) => RemoteDriver::connect_with_token(endpoint.clone(), token).await,
+        None => RemoteDriver::connect(endpoint.clone()).await,
+    };
+    let driver = driver
+        .map_err(|e| anyhow!("Failed to connect to the FSCT service at {}: {}", endpoint, e))?;
+
+    match cli.command {
+        Commands::List { what: ListTarget::Devices } => {
+            let devices = driver.list_devices().await?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&devices)?);
+            } else {
+                print!("{}", format_devices(&devices));
+            }
+        }
+        Commands::List { what: ListTarget::Players } => {
+            let players = driver.list_players().await?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&players)?);
+            } else {
+                print!("{}", format_players(&players, None));
+            }
+        }
+        Commands::List { what: ListTarget::Ports } => {
+            let ports = driver.list_ports().await?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&ports)?);
+            } else {
+                ports.iter().for_each(|port| println!("{}", port));
+            }
+        }
+        Commands::Assign { player, device } => {
+            let player_id = resolve_player(&driver.list_players().await?, &player)?;
+            driver.assign_player_to_device(player_id, device).await?;
+        }
+        Commands::SetPreferred { player } => {
+            let preferred = match player.as_str() {
+                "none" => None,
+                player => Some(resolve_player(&driver.list_players().await?, player)?),
+            };
+            driver.set_preferred_player_and_wait(preferred).await?;
+        }
+        Commands::Status => {
+            let readiness = driver.get_readiness().await?;
+            let preferred = driver.get_preferred_player();
+            let players = driver.list_players().await?;
+            let devices = driver.list_devices().await?;
+            if cli.json {
+                let status = serde_json::json!({
+                    "readiness": readiness,
+                    "preferred_player": preferred,
+                    "players": players,
+                    "devices": devices,
+                });
+                println!("{}", serde_json::to_string_pretty(&status)?);
+            } else {
+                print!("Service: {}\n\n{}", readiness.summary(), format_status(preferred, &players, &devices));
+            }
+            if !readiness.ready {
+                std::process::exit(1);
+            }
+        }
+        Commands::Watch => watch(&driver).await?,
+        Commands::RestartPort { port } => driver.restart_port(&port).await?,
+        Commands::Device { command: DeviceCommands::Descriptors { device } } => {
+            let descriptors = driver.get_device_descriptors(device).await?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&descriptors)?);
+            } else {
+                print!("{}", format_descriptors(&descriptors));
+            }
+        }
+        Commands::Assignments { command: AssignmentCommands::Export { file } } => {
+            let json = serde_json::to_string_pretty(&AssignmentFile::export(&driver.list_players().await?))? + "\n";
+            match file {
+                Some(file) => std::fs::write(&file, json)
+                    .map_err(|e| anyhow!("Failed to write {}: {}", file.display(), e))?,
+                None => print!("{}", json),
+            }
+        }
+        Commands::Assignments { command: AssignmentCommands::Import { file } } => {
+            let json = std::fs::read_to_string(&file)
+                .map_err(|e| anyhow!("Failed to read {}: {}", file.display(), e))?;
+            let assignments: AssignmentFile = serde_json::from_str(&json)
+                .map_err(|e| anyhow!("Invalid assignment file {}: {}", file.display(), e))?;
+            assignments.check_version()?;
+            let (resolved, unmatched) = assignments.resolve(&driver.list_players().await?);
+            for (player_id, device) in &resolved {
+                driver.assign_player_to_device(*player_id, *device).await?;
+            }
+            println!("Assigned {} player(s)", resolved.len());
+            for entry in unmatched {
+                eprintln!("No registered player matches \"{}\" (device {})", entry.player, entry.device);
+            }
+        }
+        Commands::Firmware { command: FirmwareCommands::Update { file, device, expect_version } } => {
+            let image = std::fs::read(&file).map_err(|e| anyhow!("Failed to read {}: {}", file.display(), e))?;
+            let device = match device {
+                Some(device) => device,
+                None => updatable_device(&driver.list_devices().await?)?,
+            };
+            let report = driver.update_firmware(device, image, |progress| {
+                eprint!("\rSent {} of {} bytes", progress.sent, progress.total);
+            }).await;
+            eprintln!();
+            let report = report?;
+            let version = format_bcd_version(report.version);
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("Firmware of device {} updated from {} to {}", device,
+                         format_bcd_version(report.previous_version), version);
+            }
+            if let Some(expected) = expect_version && expected != version {
+                bail!("Device {} runs firmware {}, expected {}", device, version, expected);
+            }
+        }
+        Commands::Dnd { command: DndCommands::On { device } } => {
+            driver.set_dnd(device.map_or(DndScope::Global, DndScope::Device), true).await?;
+        }
+        Commands::Dnd { command: DndCommands::Off { device } } => {
+            driver.set_dnd(device.map_or(DndScope::Global, DndScope::Device), false).await?;
+        }
+        Commands::Dnd { command: DndCommands::Status } => {
+            let dnd = driver.get_dnd().await?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&dnd)?);
+            } else {
+                println!("All devices: {}", if dnd.global { "suspended" } else { "not suspended" });
+                dnd.devices.iter().for_each(|device| println!("Suspended: {}", device));
+            }
+        }
+        #[cfg(feature = "self-update")]
+        Commands::Update { .. } => unreachable!("handled before connecting"),
+        Commands::Route { command: RouteCommands::Set { player, device, duration_secs } } => {
+            let player_id = resolve_player(&driver.list_players().await?, &player)?;
+            let duration = duration_secs.map(Duration::try_from_secs_f64).transpose()?;
+            driver.force_route(player_id, device, duration).await?;
+        }
+        Commands::Route { command: RouteCommands::Clear { device } } => {
+            if !driver.clear_route(device).await? {
+                bail!("Device {} has no route override", device);
+            }
+        }
+        Commands::Report => {
+            let report = driver.usage_report().await?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print!("{}", report);
+            }
+        }
+        Commands::Route { command: RouteCommands::List } => {
+            let routes = driver.routes().await?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&routes)?);
+            } else {
+                for route in routes {
+                    let until = match route.remaining {
+                        Some(remaining) => format!("for {:.0}s", remaining.as_secs_f64()),
+                        None => "until it stops".to_string(),
+                    };
+                    println!("{} -> player {} ({})", route.device_id, route.player_id, until);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Endpoint of the driver server of the config file; a server listening on all addresses is reached on loopback.
+fn driver_endpoint(config: &HostConfig) -> String {
+    let mut address = config.driver_server.unwrap_or(DEFAULT_DRIVER_SERVER_ADDRESS);
+    match address {
+        SocketAddr::V4(_) if address.ip().is_unspecified() => address.set_ip(Ipv4Addr::LOCALHOST.into()),
+        SocketAddr::V6(_) if address.ip().is_unspecified() => address.set_ip(Ipv6Addr::LOCALHOST.into()),
+        _ => {}
+    }
+    format!("http://{}", address)
+}
+
+/// Token accepted by the driver server: a control token of the `driver_auth` of the config file, else any of its
+/// tokens; the token of the token file the service writes without `driver_auth`.
+fn driver_token(config: &HostConfig) -> Option<String> {
+    match &config.driver_auth {
+        Some(auth) => auth.tokens.iter()
+            .max_by_key(|token| token.scope == Scope::Control)
+            .map(|token| token.token.clone()),
+        None => read_token_file(&default_token_path()).ok(),
+    }
+}
+
+/// The only attached device taking firmware updates.
+fn updatable_device(devices: &[DeviceAttachRecord]) -> Result<ManagedDeviceId> {
+    let updatable: Vec<_> = devices
+        .iter()
+        .filter(|device| device.functionality.iter().any(|functionality| functionality == "FirmwareUpdate"))
+        .collect();
+    match updatable.as_slice() {
+        [] => bail!("No attached device takes firmware updates"),
+        [device] => Ok(device.device_id),
+        _ => {
+            let ids: Vec<_> = updatable.iter().map(|device| device.device_id.to_string()).collect();
+            bail!("Several devices take firmware updates, choose one with --device: {}", ids.join(", "))
+        }
+    }
+}
+
+/// Prints the events of the service, one JSON object per line, until Ctrl+C.
+async fn watch(driver: &RemoteDriver) -> Result<()> {
+    let mut player_events = driver.subscribe_player_events();
+    let mut device_events = driver.subscribe_device_events();
+    loop {
+        let line = tokio::select! {
+            event = player_events.recv() => match event {
+                Ok(event) => serde_json::json!({ "player_event": event }),
+                Err(RecvError::Lagged(skipped)) => serde_json::json!({ "skipped_player_events": skipped }),
+                Err(RecvError::Closed) => return Ok(()),
+            },
+            event = device_events.recv() => match event {
+                Ok(event) => serde_json::json!({ "device_event": event }),
+                Err(RecvError::Lagged(skipped)) => serde_json::json!({ "skipped_device_events": skipped }),
+                Err(RecvError::Closed) => return Ok(()),
+            },
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        };
+        println!("{}", line);
+    }
+}
+
+/// Checks for, or installs, the release the update channel offers this installation.
+#[cfg(feature = "self-update")]
+async fn update(command: &UpdateCommands, json: bool) -> Result<()> {
+    let (UpdateCommands::Check { channel } | UpdateCommands::Install { channel }) = command;
+    let updater = Updater::from_build_config()?;
+    let current = Version::parse(env!("CARGO_PKG_VERSION"))?;
+    let installation_id = installation_id(&default_installation_id_path())?;
+    let Some(available) = updater.check(*channel, &current, &installation_id).await? else {
+        println!("{}", if json { "null".to_string() } else { format!("Up to date ({})", current) });
+        return Ok(());
+    };
+    if json {
+        let update = serde_json::json!({ "version": available.version.to_string(), "notes": available.notes });
+        println!("{}", serde_json::to_string_pretty(&update)?);
+    } else {
+        println!("Update available: {} -> {}", current, available.version);
+        if !available.notes.is_empty() {
+            println!("{}", available.notes);
+        }
+    }
+    if let UpdateCommands::Install { .. } = command {
+        let installer = updater.download(&available, &std::env::temp_dir()).await?;
+        tokio::task::spawn_blocking(move || install(&installer)).await??;
+    }
+    Ok(())
+}
+
+/// Finds the player named by `name`: its managed id, or else its self id, which must name a single player.
+fn resolve_player(players: &[PlayerInfo], name: &str) -> Result<ManagedPlayerId> {
+    if let Ok(id) = name.parse::<ManagedPlayerId>()
+        && players.iter().any(|player| player.player_id == id)
+    {
+        return Ok(id);
+    }
+    let mut matching = players.iter().filter(|player| player.self_id == name);
+    match (matching.next(), matching.next()) {
+        (Some(player), None) => Ok(player.player_id),
+        (Some(_), Some(_)) => bail!("Several players are registered as \"{}\", name one by its id", name),
+        (None, _) => bail!("No player \"{}\" is registered", name),
+    }
+}
+
+fn format_devices(devices: &[DeviceAttachRecord]) -> String {
+    let mut out = String::new();
+    for device in devices {
+        let name = [device.manufacturer.as_deref(), device.product.as_deref()]
+            .into_iter().flatten().collect::<Vec<_>>().join(" ");
+        let _ = writeln!(out, "{}  {:04x}:{:04x}  {}  (firmware {})", device.device_id, device.vendor_id,
+                         device.product_id, if name.is_empty() { "-" } else { &name }, device.firmware_version);
+    }
+    out
+}
+
+fn format_players(players: &[PlayerInfo], preferred: Option<ManagedPlayerId>) -> String {
+    let mut out = String::new();
+    for player in players {
+        let marker = if Some(player.player_id) == preferred { "*" } else { " " };
+        let device = player.assigned_device.map(|id| id.to_string()).unwrap_or_else(|| "-".to_string());
+        let title = player.state.texts.title.as_deref().unwrap_or("-");
+        let _ = write!(out, "{}{:>3}  {}  {:?}  \"{}\"  device {}", marker, player.player_id, player.self_id,
+                       player.state.status, title, device);
+        if !player.origin.is_local() {
+            let _ = write!(out, "  from {}", player.origin.host_name());
+        }
+        if let Some(user) = &player.origin.user {
+            let _ = write!(out, " ({})", user);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn format_status(preferred: Option<ManagedPlayerId>, players: &[PlayerInfo], devices: &[DeviceAttachRecord]) -> String {
+    let preferred_name = match preferred {
+        Some(id) => match players.iter().find(|player| player.player_id == id) {
+            Some(player) => format!("{} ({})", id, player.self_id),
+            None => id.to_string(),
+        },
+        None => "none".to_string(),
+    };
+    format!("Preferred player: {}\n\nPlayers ({}):\n{}\nDevices ({}):\n{}", preferred_name, players.len(),
+            format_players(players, preferred), devices.len(), format_devices(devices))
+}
+
+fn format_descriptors(descriptors: &DeviceDescriptorDump) -> String {
+    let mut out = String::new();
+    let sections = [("BOS descriptor", &descriptors.bos), ("FSCT functionality descriptor set", &descriptors.fsct)];
+    for (title, dumps) in sections {
+        if dumps.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "{}:", title);
+        dumps.iter().for_each(|dump| format_descriptor(&mut out, dump));
+    }
+    out
+}
+
+/// Writes the descriptor as a hex dump of 16 bytes per row, followed by what the host parsed from it.
+fn format_descriptor(out: &mut String, dump: &DescriptorDump) {
+    let bytes: Vec<_> = dump.raw.split_whitespace().collect();
+    let _ = writeln!(out, "  {} ({} bytes)", dump.name, bytes.len());
+    for (row, chunk) in bytes.chunks(16).enumerate() {
+        let _ = writeln!(out, "    {:04x}  {}", row * 16, chunk.join(" "));
+    }
+    for line in dump.decoded.lines() {
+        let _ = writeln!(out, "    {}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fsct_core::PlayerState;
+
+    fn player(id: u32, self_id: &str) -> PlayerInfo {
+        PlayerInfo {
+            player_id: ManagedPlayerId::new(id).unwrap(),
+            self_id: self_id.to_string(),
+            assigned_device: None,
+            state: PlayerState::default(),
+            origin: Default::default(),
+        }
+    }
+
+    #[test]
+    fn players_are_resolved_by_id_then_self_id() {
+        let players = [player(1, "spotify"), player(2, "3"), player(3, "vlc"), player(4, "vlc")];
+
+        assert_eq!(resolve_player(&players, "2").unwrap().get(), 2);
+        assert_eq!(resolve_player(&players, "3").unwrap().get(), 3);
+        assert_eq!(resolve_player(&players, "spotify").unwrap().get(), 1);
+        assert!(resolve_player(&players, "vlc").is_err());
+        assert!(resolve_player(&players, "mpv").is_err());
+    }
+
+    #[test]
+    fn driver_server_of_the_config_file_is_used() {
+        let config = HostConfig::parse(r#"
+            driver_server = "0.0.0.0:50200"
+            [driver_auth]
+            tokens = [{ name = "dashboard", token = "r", scope = "read" },
+                      { name = "office", token = "c", scope = "control" }]
+        "#).unwrap();
+        assert_eq!(driver_endpoint(&config), "http://127.0.0.1:50200");
+        assert_eq!(driver_token(&config).as_deref(), Some("c"));
+        assert_eq!(driver_endpoint(&HostConfig::default()), "http://127.0.0.1:50151");
+    }
+
+    #[test]
+    fn descriptors_are_dumped_in_rows_of_16_bytes() {
+        let raw: Vec<u8> = (0..18).collect();
+        let descriptors = DeviceDescriptorDump {
+            bos: Vec::new(),
+            fsct: vec![DescriptorDump::new("FSCT text metadata", &raw, "FsctTextMetadataDescriptor {\n    ...\n}")],
+        };
+
+        let expected = [
+            "FSCT functionality descriptor set:",
+            "  FSCT text metadata (18 bytes)",
+            "    0000  00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f",
+            "    0010  10 11",
+            "    FsctTextMetadataDescriptor {",
+            "        ...",
+            "    }",
+        ];
+        assert_eq!(format_descriptors(&descriptors), expected.join("\n") + "\n");
+    }
+}
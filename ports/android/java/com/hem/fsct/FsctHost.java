@@ -0,0 +1,49 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+package com.hem.fsct;
+
+/** Entry points of the FSCT host library (libfsct_android_lib.so). */
+public final class FsctHost {
+    static {
+        System.loadLibrary("fsct_android_lib");
+    }
+
+    private FsctHost() {}
+
+    /** Starts the driver; returns false if it was already running. */
+    public static native boolean nativeStart(boolean debugLogs);
+
+    public static native void nativeStop();
+
+    static native void nativeSessionAdded(String tag);
+
+    static native void nativeSessionRemoved(String tag);
+
+    /** Position in milliseconds at the time of the call, negative when unknown. */
+    static native void nativeSessionPlayback(String tag, int state, long positionMs, float speed);
+
+    /** Year and duration are 0 or negative when unknown. */
+    static native void nativeSessionMetadata(String tag, String title, String artist, String album, String genre,
+                                             long year, String composer, long durationMs);
+
+    /** Attaches a device opened by {@link android.hardware.usb.UsbManager}; {@code fd} stays owned by the caller. */
+    static native void nativeDeviceOpened(String name, int fd, String manufacturer, String product,
+                                          String serialNumber);
+
+    static native void nativeDeviceClosed(String name);
+}
@@ -0,0 +1,132 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+package com.hem.fsct;
+
+import android.content.ComponentName;
+import android.media.MediaMetadata;
+import android.media.session.MediaController;
+import android.media.session.MediaSessionManager;
+import android.media.session.PlaybackState;
+import android.os.SystemClock;
+import android.service.notification.NotificationListenerService;
+
+import java.util.HashMap;
+import java.util.HashSet;
+import java.util.List;
+import java.util.Map;
+import java.util.Set;
+
+/**
+ * Forwards the active media sessions to the FSCT host, one player per app, and hands attached FSCT devices over to
+ * it with {@link FsctUsbDevices}. Media sessions of other apps are only visible to an enabled notification listener,
+ * so this service has to be declared in the manifest and enabled by the user (or preinstalled as a system app).
+ */
+public class FsctMediaListenerService extends NotificationListenerService {
+    private final Map<String, MediaController.Callback> callbacks = new HashMap<>();
+    private final Map<String, MediaController> controllers = new HashMap<>();
+    private MediaSessionManager sessionManager;
+    private FsctUsbDevices usbDevices;
+
+    private final MediaSessionManager.OnActiveSessionsChangedListener sessionsChanged = this::updateSessions;
+
+    @Override
+    public void onListenerConnected() {
+        FsctHost.nativeStart(false);
+        usbDevices = new FsctUsbDevices(this);
+        usbDevices.start();
+        sessionManager = getSystemService(MediaSessionManager.class);
+        ComponentName listener = new ComponentName(this, FsctMediaListenerService.class);
+        sessionManager.addOnActiveSessionsChangedListener(sessionsChanged, listener);
+        updateSessions(sessionManager.getActiveSessions(listener));
+    }
+
+    @Override
+    public void onListenerDisconnected() {
+        sessionManager.removeOnActiveSessionsChangedListener(sessionsChanged);
+        updateSessions(null);
+        usbDevices.stop();
+        FsctHost.nativeStop();
+    }
+
+    private void updateSessions(List<MediaController> active) {
+        Set<String> present = new HashSet<>();
+        if (active != null) {
+            for (MediaController controller : active) {
+                String tag = controller.getPackageName();
+                present.add(tag);
+                if (!controllers.containsKey(tag)) {
+                    follow(tag, controller);
+                }
+            }
+        }
+        for (String tag : new HashSet<>(controllers.keySet())) {
+            if (!present.contains(tag)) {
+                controllers.remove(tag).unregisterCallback(callbacks.remove(tag));
+                FsctHost.nativeSessionRemoved(tag);
+            }
+        }
+    }
+
+    private void follow(String tag, MediaController controller) {
+        MediaController.Callback callback = new MediaController.Callback() {
+            @Override
+            public void onPlaybackStateChanged(PlaybackState state) {
+                sendPlayback(tag, state);
+            }
+
+            @Override
+            public void onMetadataChanged(MediaMetadata metadata) {
+                sendMetadata(tag, metadata);
+            }
+        };
+        controllers.put(tag, controller);
+        callbacks.put(tag, callback);
+        FsctHost.nativeSessionAdded(tag);
+        controller.registerCallback(callback);
+        sendMetadata(tag, controller.getMetadata());
+        sendPlayback(tag, controller.getPlaybackState());
+    }
+
+    private static void sendPlayback(String tag, PlaybackState state) {
+        if (state == null) {
+            FsctHost.nativeSessionPlayback(tag, PlaybackState.STATE_NONE, -1, 0);
+            return;
+        }
+        long position = state.getPosition();
+        if (position >= 0 && state.getState() == PlaybackState.STATE_PLAYING) {
+            long elapsed = SystemClock.elapsedRealtime() - state.getLastPositionUpdateTime();
+            position += (long) (elapsed * state.getPlaybackSpeed());
+        }
+        FsctHost.nativeSessionPlayback(tag, state.getState(), position, state.getPlaybackSpeed());
+    }
+
+    private static void sendMetadata(String tag, MediaMetadata metadata) {
+        if (metadata == null) {
+            FsctHost.nativeSessionMetadata(tag, null, null, null, null, 0, null, -1);
+            return;
+        }
+        FsctHost.nativeSessionMetadata(tag,
+                metadata.getString(MediaMetadata.METADATA_KEY_TITLE),
+                metadata.getString(MediaMetadata.METADATA_KEY_ARTIST),
+                metadata.getString(MediaMetadata.METADATA_KEY_ALBUM),
+                metadata.getString(MediaMetadata.METADATA_KEY_GENRE),
+                metadata.getLong(MediaMetadata.METADATA_KEY_YEAR),
+                metadata.getString(MediaMetadata.METADATA_KEY_COMPOSER),
+                metadata.getLong(MediaMetadata.METADATA_KEY_DURATION));
+    }
+}
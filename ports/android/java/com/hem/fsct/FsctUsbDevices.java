@@ -0,0 +1,148 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+package com.hem.fsct;
+
+import android.app.PendingIntent;
+import android.content.BroadcastReceiver;
+import android.content.Context;
+import android.content.Intent;
+import android.content.IntentFilter;
+import android.hardware.usb.UsbConstants;
+import android.hardware.usb.UsbDevice;
+import android.hardware.usb.UsbDeviceConnection;
+import android.hardware.usb.UsbManager;
+import android.os.Build;
+import android.util.Log;
+
+import java.util.HashMap;
+import java.util.Map;
+
+/**
+ * Hands the attached devices with a vendor specific interface, which FSCT devices have, over to the FSCT host.
+ * Apps other than system apps cannot open USB devices themselves: the user is asked for the USB host permission of
+ * each device as it attaches, and devices it was granted for are opened through the {@link UsbManager}, whose file
+ * descriptors the driver takes over. Devices are closed again when they detach.
+ */
+public final class FsctUsbDevices extends BroadcastReceiver {
+    /** Broadcast sent back by Android when the user answered a USB permission request. */
+    public static final String ACTION_USB_PERMISSION = "com.hem.fsct.USB_PERMISSION";
+
+    private static final String TAG = "FsctUsbDevices";
+
+    private final Context context;
+    private final UsbManager usbManager;
+    private final Map<String, UsbDeviceConnection> connections = new HashMap<>();
+
+    public FsctUsbDevices(Context context) {
+        this.context = context;
+        this.usbManager = (UsbManager) context.getSystemService(Context.USB_SERVICE);
+    }
+
+    /** Follows attached devices, asking for the permission of those attached already. Call after starting the host. */
+    public void start() {
+        IntentFilter filter = new IntentFilter(ACTION_USB_PERMISSION);
+        filter.addAction(UsbManager.ACTION_USB_DEVICE_ATTACHED);
+        filter.addAction(UsbManager.ACTION_USB_DEVICE_DETACHED);
+        if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.TIRAMISU) {
+            context.registerReceiver(this, filter, Context.RECEIVER_NOT_EXPORTED);
+        } else {
+            context.registerReceiver(this, filter);
+        }
+        for (UsbDevice device : usbManager.getDeviceList().values()) {
+            attached(device);
+        }
+    }
+
+    /** Stops following devices and closes the opened ones. Call before stopping the host. */
+    public void stop() {
+        context.unregisterReceiver(this);
+        for (String name : connections.keySet().toArray(new String[0])) {
+            close(name);
+        }
+    }
+
+    @Override
+    public void onReceive(Context context, Intent intent) {
+        UsbDevice device = intent.getParcelableExtra(UsbManager.EXTRA_DEVICE);
+        if (device == null) {
+            return;
+        }
+        switch (intent.getAction()) {
+            case UsbManager.ACTION_USB_DEVICE_ATTACHED:
+                attached(device);
+                break;
+            case ACTION_USB_PERMISSION:
+                if (intent.getBooleanExtra(UsbManager.EXTRA_PERMISSION_GRANTED, false)) {
+                    open(device);
+                } else {
+                    Log.i(TAG, "USB host permission of " + device.getDeviceName() + " denied");
+                }
+                break;
+            case UsbManager.ACTION_USB_DEVICE_DETACHED:
+                close(device.getDeviceName());
+                break;
+            default:
+                break;
+        }
+    }
+
+    private void attached(UsbDevice device) {
+        if (!hasVendorSpecificInterface(device)) {
+            return;
+        }
+        if (usbManager.hasPermission(device)) {
+            open(device);
+            return;
+        }
+        PendingIntent permissionIntent = PendingIntent.getBroadcast(context, 0,
+                new Intent(ACTION_USB_PERMISSION).setPackage(context.getPackageName()), PendingIntent.FLAG_MUTABLE);
+        usbManager.requestPermission(device, permissionIntent);
+    }
+
+    private void open(UsbDevice device) {
+        String name = device.getDeviceName();
+        if (connections.containsKey(name)) {
+            return;
+        }
+        UsbDeviceConnection connection = usbManager.openDevice(device);
+        if (connection == null) {
+            Log.w(TAG, "Failed to open USB device " + name);
+            return;
+        }
+        connections.put(name, connection);
+        FsctHost.nativeDeviceOpened(name, connection.getFileDescriptor(), device.getManufacturerName(),
+                device.getProductName(), device.getSerialNumber());
+    }
+
+    private void close(String name) {
+        UsbDeviceConnection connection = connections.remove(name);
+        if (connection != null) {
+            FsctHost.nativeDeviceClosed(name);
+            connection.close();
+        }
+    }
+
+    private static boolean hasVendorSpecificInterface(UsbDevice device) {
+        for (int i = 0; i < device.getInterfaceCount(); i++) {
+            if (device.getInterface(i).getInterfaceClass() == UsbConstants.USB_CLASS_VENDOR_SPEC) {
+                return true;
+            }
+        }
+        return false;
+    }
+}
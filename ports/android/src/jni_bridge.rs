@@ -0,0 +1,198 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! JNI entry points of `com.hem.fsct.FsctHost`.
+
+use std::collections::HashMap;
+use std::os::fd::BorrowedFd;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use fsct_core::device_manager::DeviceStrings;
+use fsct_core::player_state::TrackMetadata;
+use fsct_core::service::MultiServiceHandle;
+use fsct_core::timeline_smoothing::TimelineSmoother;
+use fsct_core::{DeviceManager, FsctDriver, InterceptedDriver, LocalDriver, ManagedDeviceId};
+use jni::objects::{JClass, JString};
+use jni::sys::{jboolean, jfloat, jint, jlong, JNI_FALSE, JNI_TRUE};
+use jni::JNIEnv;
+use log::{error, info, warn, LevelFilter};
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+
+use crate::logger::init_logger;
+use crate::media_session::{run_media_session_port, SessionEvent};
+
+struct AndroidHost {
+    runtime: Runtime,
+    services: MultiServiceHandle,
+    sessions: mpsc::UnboundedSender<SessionEvent>,
+    device_manager: Arc<DeviceManager>,
+    /// Devices handed over by `UsbManager`, by their Android device name.
+    opened_devices: Arc<Mutex<HashMap<String, ManagedDeviceId>>>,
+}
+
+static HOST: Mutex<Option<AndroidHost>> = Mutex::new(None);
+
+fn start() -> anyhow::Result<AndroidHost> {
+    let runtime = tokio::runtime::Builder::new_multi_thread().worker_threads(2).enable_all().build()?;
+    let (sessions, events) = mpsc::unbounded_channel();
+    let driver = Arc::new(LocalDriver::with_new_managers());
+    let services = runtime.block_on(async {
+        let mut services = driver.run().await?;
+        let player_driver: Arc<dyn FsctDriver> = Arc::new(InterceptedDriver::new(driver.clone())
+            .with_interceptor(Arc::new(TimelineSmoother::default())));
+        services.add(run_media_session_port(player_driver, events));
+        anyhow::Ok(services)
+    })?;
+    let device_manager = driver.device_manager();
+    Ok(AndroidHost { runtime, services, sessions, device_manager, opened_devices: Default::default() })
+}
+
+fn send(event: SessionEvent) {
+    if let Some(host) = HOST.lock().unwrap().as_ref() {
+        let _ = host.sessions.send(event);
+    }
+}
+
+fn string(env: &mut JNIEnv, value: &JString) -> Option<String> {
+    if value.is_null() {
+        return None;
+    }
+    env.get_string(value).ok().map(String::from).filter(|s| !s.is_empty())
+}
+
+/// Durations from Java are in milliseconds, negative when unknown.
+fn millis(value: jlong) -> Option<Duration> {
+    u64::try_from(value).ok().map(Duration::from_millis)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_hem_fsct_FsctHost_nativeStart(mut env: JNIEnv, _class: JClass, debug: jboolean) -> jboolean {
+    init_logger(if debug == JNI_TRUE { LevelFilter::Debug } else { LevelFilter::Info });
+    let mut host = HOST.lock().unwrap();
+    if host.is_some() {
+        return JNI_FALSE;
+    }
+    match start() {
+        Ok(started) => {
+            *host = Some(started);
+            JNI_TRUE
+        }
+        Err(e) => {
+            error!("Failed to start FSCT host: {}", e);
+            let _ = env.throw_new("java/lang/IllegalStateException", format!("Failed to start FSCT host: {}", e));
+            JNI_FALSE
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_hem_fsct_FsctHost_nativeStop(_env: JNIEnv, _class: JClass) {
+    let Some(host) = HOST.lock().unwrap().take() else { return };
+    if let Err(e) = host.runtime.block_on(host.services.shutdown()) {
+        error!("Failed to stop FSCT host: {}", e);
+    }
+}
+
+/// Attaches a device `UsbManager` opened for the app; `fd` stays owned by the Java connection, the driver uses a
+/// duplicate of it.
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_hem_fsct_FsctHost_nativeDeviceOpened(mut env: JNIEnv, _class: JClass, name: JString,
+                                                                    fd: jint, manufacturer: JString,
+                                                                    product: JString, serial_number: JString) {
+    let Some(name) = string(&mut env, &name) else { return };
+    let strings = DeviceStrings {
+        manufacturer: string(&mut env, &manufacturer),
+        product: string(&mut env, &product),
+        serial_number: string(&mut env, &serial_number),
+    };
+    // SAFETY: the Java side keeps the connection, and with it the descriptor, open for the duration of the call
+    let fd = match unsafe { BorrowedFd::borrow_raw(fd) }.try_clone_to_owned() {
+        Ok(fd) => fd,
+        Err(e) => {
+            error!("Failed to take over USB device {}: {}", name, e);
+            return;
+        }
+    };
+    let host = HOST.lock().unwrap();
+    let Some(host) = host.as_ref() else { return };
+    let (device_manager, opened_devices) = (host.device_manager.clone(), host.opened_devices.clone());
+    host.runtime.spawn(async move {
+        match device_manager.attach_opened_device(fd, strings).await {
+            Ok(Some(device_id)) => {
+                info!("USB device {} attached as {}", name, device_id);
+                opened_devices.lock().unwrap().insert(name, device_id);
+            }
+            Ok(None) => {}
+            Err(e) => warn!("USB device {} is not a usable FSCT device: {}", name, e),
+        }
+    });
+}
+
+/// Detaches a device handed over with `nativeDeviceOpened`, e.g. once it was unplugged.
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_hem_fsct_FsctHost_nativeDeviceClosed(mut env: JNIEnv, _class: JClass, name: JString) {
+    let Some(name) = string(&mut env, &name) else { return };
+    let host = HOST.lock().unwrap();
+    let Some(host) = host.as_ref() else { return };
+    if let Some(device_id) = host.opened_devices.lock().unwrap().remove(&name) {
+        host.device_manager.detach_opened_device(device_id);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_hem_fsct_FsctHost_nativeSessionAdded(mut env: JNIEnv, _class: JClass, tag: JString) {
+    if let Some(tag) = string(&mut env, &tag) {
+        send(SessionEvent::Added { tag });
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_hem_fsct_FsctHost_nativeSessionRemoved(mut env: JNIEnv, _class: JClass, tag: JString) {
+    if let Some(tag) = string(&mut env, &tag) {
+        send(SessionEvent::Removed { tag });
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_hem_fsct_FsctHost_nativeSessionPlayback(mut env: JNIEnv, _class: JClass, tag: JString,
+                                                                       state: jint, position_ms: jlong,
+                                                                       speed: jfloat) {
+    if let Some(tag) = string(&mut env, &tag) {
+        send(SessionEvent::Playback { tag, state, position: millis(position_ms), speed, at: SystemTime::now() });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_hem_fsct_FsctHost_nativeSessionMetadata(mut env: JNIEnv, _class: JClass, tag: JString,
+                                                                       title: JString, artist: JString,
+                                                                       album: JString, genre: JString, year: jlong,
+                                                                       composer: JString, duration_ms: jlong) {
+    let Some(tag) = string(&mut env, &tag) else { return };
+    let texts = TrackMetadata {
+        title: string(&mut env, &title),
+        artist: string(&mut env, &artist),
+        album: string(&mut env, &album),
+        genre: string(&mut env, &genre),
+        year: (year > 0).then(|| year.to_string()),
+        composer: string(&mut env, &composer),
+        ..Default::default()
+    };
+    send(SessionEvent::Metadata { tag, texts, duration: millis(duration_ms).filter(|d| !d.is_zero()) });
+}
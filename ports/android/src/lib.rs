@@ -0,0 +1,33 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Android host: runs the driver inside an Android app and reports the app's media sessions as players.
+//!
+//! The Java side (`java/com/hem/fsct`) listens to media sessions through a `NotificationListenerService` and
+//! forwards them over JNI ([`jni_bridge`]); [`media_session`] turns them into players, one per session.
+//!
+//! Devices are reached through usbfs like on Linux. System apps of streamer firmware with access to `/dev/bus/usb`
+//! find them on their own; other apps are handed them by `FsctUsbDevices`, which asks the user for the USB host
+//! permission of each attached FSCT device and passes the file descriptor `UsbManager` opens once it is granted to
+//! the driver (`DeviceManager::attach_opened_device`).
+
+pub mod media_session;
+
+#[cfg(target_os = "android")]
+mod jni_bridge;
+#[cfg(target_os = "android")]
+mod logger;
@@ -0,0 +1,65 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Logging to logcat.
+
+use std::ffi::CString;
+use std::sync::OnceLock;
+
+use android_log_sys::{__android_log_write, LogPriority};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+const TAG: &str = "FSCT";
+
+struct LogcatLogger {
+    tag: CString,
+}
+
+impl Log for LogcatLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let priority = match record.level() {
+            Level::Error => LogPriority::ERROR,
+            Level::Warn => LogPriority::WARN,
+            Level::Info => LogPriority::INFO,
+            Level::Debug => LogPriority::DEBUG,
+            Level::Trace => LogPriority::VERBOSE,
+        };
+        // interior NUL bytes would end the message early
+        let text = format!("{}: {}", record.target(), record.args()).replace('\0', "");
+        let Ok(text) = CString::new(text) else { return };
+        unsafe {
+            __android_log_write(priority as _, self.tag.as_ptr(), text.as_ptr());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Routes logs to logcat under the `FSCT` tag; later calls only change the level.
+pub fn init_logger(level: LevelFilter) {
+    static LOGGER: OnceLock<LogcatLogger> = OnceLock::new();
+    let logger = LOGGER.get_or_init(|| LogcatLogger { tag: CString::new(TAG).expect("tag has no NUL bytes") });
+    let _ = log::set_logger(logger);
+    log::set_max_level(level);
+}
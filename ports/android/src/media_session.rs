@@ -0,0 +1,199 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Translation of Android media sessions into players.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use fsct_core::definitions::{FsctStatus, TimelineInfo};
+use fsct_core::player_state::{PlayerState, TrackMetadata};
+use fsct_core::service::{spawn_service, ServiceHandle};
+use fsct_core::FsctDriver;
+use fsct_port_sdk::PortPlayer;
+use log::{error, info, warn};
+use tokio::sync::mpsc;
+
+/// Playback states of `android.media.session.PlaybackState`.
+pub mod playback_state {
+    pub const STATE_NONE: i32 = 0;
+    pub const STATE_STOPPED: i32 = 1;
+    pub const STATE_PAUSED: i32 = 2;
+    pub const STATE_PLAYING: i32 = 3;
+    pub const STATE_FAST_FORWARDING: i32 = 4;
+    pub const STATE_REWINDING: i32 = 5;
+    pub const STATE_BUFFERING: i32 = 6;
+    pub const STATE_ERROR: i32 = 7;
+    pub const STATE_CONNECTING: i32 = 8;
+    pub const STATE_SKIPPING_TO_PREVIOUS: i32 = 9;
+    pub const STATE_SKIPPING_TO_NEXT: i32 = 10;
+    pub const STATE_SKIPPING_TO_QUEUE_ITEM: i32 = 11;
+}
+
+/// Status of a `PlaybackState` state.
+pub fn status(state: i32) -> FsctStatus {
+    use playback_state::*;
+    match state {
+        STATE_NONE | STATE_STOPPED => FsctStatus::Stopped,
+        STATE_PAUSED => FsctStatus::Paused,
+        STATE_PLAYING => FsctStatus::Playing,
+        STATE_FAST_FORWARDING | STATE_REWINDING => FsctStatus::Seeking,
+        STATE_BUFFERING | STATE_CONNECTING | STATE_SKIPPING_TO_PREVIOUS | STATE_SKIPPING_TO_NEXT
+        | STATE_SKIPPING_TO_QUEUE_ITEM => FsctStatus::Buffering,
+        STATE_ERROR => FsctStatus::Error,
+        _ => FsctStatus::Unknown,
+    }
+}
+
+/// Change of a media session, as reported by the Java side.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionEvent {
+    Added { tag: String },
+    Removed { tag: String },
+    /// `PlaybackState` of the session, with the position extrapolated to `at`.
+    Playback { tag: String, state: i32, position: Option<Duration>, speed: f32, at: SystemTime },
+    /// `MediaMetadata` of the session.
+    Metadata { tag: String, texts: TrackMetadata, duration: Option<Duration> },
+}
+
+/// What is known about a media session.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionState {
+    pub state: i32,
+    pub position: Option<(Duration, f32, SystemTime)>,
+    pub duration: Option<Duration>,
+    pub texts: TrackMetadata,
+}
+
+impl SessionState {
+    pub fn player_state(&self) -> PlayerState {
+        let status = status(self.state);
+        let timeline = self.position.filter(|_| status != FsctStatus::Stopped).map(|(position, speed, at)| {
+            TimelineInfo {
+                position,
+                update_time: at,
+                duration: self.duration,
+                rate: if status == FsctStatus::Playing { speed.into() } else { 0.0 },
+            }
+        });
+        PlayerState { status, timeline, texts: self.texts.clone(), volume: None, modes: Default::default() }
+    }
+}
+
+/// Self id of the player of a media session; the tag is the package name of the app owning the session.
+pub fn player_self_id(tag: &str) -> String {
+    format!("android-mediasession-{}", tag)
+}
+
+struct Session {
+    player: PortPlayer,
+    state: SessionState,
+}
+
+impl Session {
+    async fn update(&mut self, tag: &str) {
+        if let Err(e) = self.player.update_partial(self.state.player_state()).await {
+            warn!("Failed to update media session of {}: {}", tag, e);
+        }
+    }
+}
+
+async fn apply(sessions: &mut HashMap<String, Session>, driver: &Arc<dyn FsctDriver>, event: SessionEvent) {
+    match event {
+        SessionEvent::Added { tag } => {
+            if sessions.contains_key(&tag) {
+                return;
+            }
+            match PortPlayer::register(driver.clone(), player_self_id(&tag)).await {
+                Ok(player) => {
+                    info!("Following media session of {}", tag);
+                    sessions.insert(tag, Session { player, state: SessionState::default() });
+                }
+                Err(e) => error!("Failed to register media session of {}: {}", tag, e),
+            }
+        }
+        SessionEvent::Removed { tag } => {
+            if let Some(session) = sessions.remove(&tag) {
+                let _ = session.player.unregister().await;
+            }
+        }
+        SessionEvent::Playback { tag, state, position, speed, at } => {
+            let Some(session) = sessions.get_mut(&tag) else { return };
+            session.state.state = state;
+            session.state.position = position.map(|position| (position, speed, at));
+            session.update(&tag).await;
+        }
+        SessionEvent::Metadata { tag, texts, duration } => {
+            let Some(session) = sessions.get_mut(&tag) else { return };
+            session.state.texts = texts;
+            session.state.duration = duration;
+            session.update(&tag).await;
+        }
+    }
+}
+
+/// Follows the media sessions reported through `events`, each as a player of its own.
+pub fn run_media_session_port(driver: Arc<dyn FsctDriver>, mut events: mpsc::UnboundedReceiver<SessionEvent>) -> ServiceHandle {
+    spawn_service(move |mut stop| async move {
+        let mut sessions = HashMap::new();
+        loop {
+            let event = tokio::select! {
+                _ = stop.signaled() => break,
+                event = events.recv() => event,
+            };
+            let Some(event) = event else { break };
+            apply(&mut sessions, &driver, event).await;
+        }
+        for (_, session) in sessions.drain() {
+            let _ = session.player.unregister().await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_state_is_translated_to_player_state() {
+        let at = SystemTime::now();
+        let mut session = SessionState {
+            state: playback_state::STATE_PLAYING,
+            position: Some((Duration::from_secs(30), 1.0, at)),
+            duration: Some(Duration::from_secs(245)),
+            texts: TrackMetadata { title: Some("Song".to_string()), ..Default::default() },
+        };
+
+        let state = session.player_state();
+        assert_eq!(state.status, FsctStatus::Playing);
+        assert_eq!(state.texts.title.as_deref(), Some("Song"));
+        assert_eq!(state.timeline, Some(TimelineInfo {
+            position: Duration::from_secs(30),
+            update_time: at,
+            duration: Some(Duration::from_secs(245)),
+            rate: 1.0,
+        }));
+
+        session.state = playback_state::STATE_SKIPPING_TO_NEXT;
+        let state = session.player_state();
+        assert_eq!((state.status, state.timeline.unwrap().rate), (FsctStatus::Buffering, 0.0));
+
+        session.state = playback_state::STATE_STOPPED;
+        assert_eq!(session.player_state().timeline, None);
+    }
+}
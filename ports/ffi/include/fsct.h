@@ -0,0 +1,100 @@
+/*
+ * Copyright 2025 HEM Sp. z o.o.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * This file is part of an implementation of Ferrum Streaming Control Technology™,
+ * which is subject to additional terms found in the LICENSE-FSCT.md file.
+ */
+
+/*
+ * C API of FSCT Host (libfsct_ffi). Functions return FSCT_OK or a negative error code; the message of the last
+ * error of the calling thread is available from fsct_last_error(). Calls block until the driver has taken them.
+ */
+
+#ifndef FSCT_H
+#define FSCT_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define FSCT_OK 0
+#define FSCT_ERROR_INVALID_ARGUMENT (-1)
+#define FSCT_ERROR_DRIVER (-2)
+
+#define FSCT_STATUS_STOPPED 0x00
+#define FSCT_STATUS_PLAYING 0x01
+#define FSCT_STATUS_PAUSED 0x02
+#define FSCT_STATUS_SEEKING 0x03
+#define FSCT_STATUS_BUFFERING 0x04
+#define FSCT_STATUS_ERROR 0x05
+#define FSCT_STATUS_UNKNOWN 0x0F
+
+#define FSCT_DEVICE_ADDED 1
+#define FSCT_DEVICE_REMOVED 2
+
+/* Driver owning the FSCT devices of the host. */
+typedef struct FsctDriver FsctDriver;
+
+/* Full state of a player. */
+typedef struct FsctPlayerState {
+    uint8_t status;            /* FSCT_STATUS_* */
+    bool has_timeline;         /* whether the position fields are set */
+    uint64_t position_ms;
+    int64_t duration_ms;       /* negative for live streams */
+    double rate;
+    const char *title;         /* UTF-8 texts of the current track, NULL if none */
+    const char *artist;
+    const char *album;
+    const char *genre;
+    const char *year;
+    const char *composer;
+} FsctPlayerState;
+
+/* Called on a thread of the driver dedicated to the callback, which may call the other functions; device_id is a
+ * UUID string valid during the call. */
+typedef void (*FsctDeviceCallback)(void *user_data, uint32_t event, const char *device_id);
+
+/* Creates a driver and starts watching devices; NULL on failure. */
+FsctDriver *fsct_driver_create(void);
+
+/* Stops the driver and frees it; NULL is ignored. */
+void fsct_driver_destroy(FsctDriver *driver);
+
+/* Registers a player named self_id, storing its id in player_id. */
+int32_t fsct_register_player(const FsctDriver *driver, const char *self_id, uint32_t *player_id);
+
+int32_t fsct_unregister_player(const FsctDriver *driver, uint32_t player_id);
+
+/* Publishes the full state of a player, with its position taken now. */
+int32_t fsct_update_state(const FsctDriver *driver, uint32_t player_id, const FsctPlayerState *state);
+
+/* Sets the player shown on devices whenever it plays; 0 clears it. */
+int32_t fsct_set_preferred_player(const FsctDriver *driver, uint32_t player_id);
+
+/* Calls callback with user_data when devices are added or removed, replacing the previous callback; NULL stops
+ * the calls. Returns once a call of the previous callback in progress has ended, unless called from it. */
+int32_t fsct_set_device_callback(const FsctDriver *driver, FsctDeviceCallback callback, void *user_data);
+
+/* Message of the last error on the calling thread, NULL if none; valid until the next failing call on the thread. */
+const char *fsct_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* FSCT_H */
@@ -0,0 +1,283 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! C API of the host, for host applications not written in Rust (e.g. C++/Qt players or firmware simulators).
+//!
+//! The declarations are in `include/fsct.h`. A driver created with `fsct_driver_create` owns the devices, like the
+//! native service does; players are registered with it and publish their full state with `fsct_update_state`.
+//! Functions return `FSCT_OK` or a negative error code, with the message of the last error of the calling thread
+//! in `fsct_last_error`.
+
+mod state;
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use anyhow::anyhow;
+use fsct_core::{DeviceControl as _, DeviceEvent, FsctDriver as _, LocalDriver, ManagedPlayerId, MultiServiceHandle};
+use log::error;
+use tokio::runtime::Runtime;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+pub use state::FsctPlayerState;
+
+pub const FSCT_OK: i32 = 0;
+pub const FSCT_ERROR_INVALID_ARGUMENT: i32 = -1;
+pub const FSCT_ERROR_DRIVER: i32 = -2;
+
+pub const FSCT_DEVICE_ADDED: u32 = 1;
+pub const FSCT_DEVICE_REMOVED: u32 = 2;
+
+/// Called with the user data it was set with, the kind of event and the managed id of the device (a UUID string,
+/// valid during the call). Called on a thread dedicated to the callback, not a worker of the driver's runtime, so
+/// the callback may call the other functions.
+pub type FsctDeviceCallback = Option<unsafe extern "C" fn(user_data: *mut c_void, event: u32, device_id: *const c_char)>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn fail(code: i32, e: impl std::fmt::Display) -> i32 {
+    let message = CString::new(e.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    code
+}
+
+/// Driver of the host with its background services.
+pub struct FsctDriver {
+    runtime: Runtime,
+    driver: Arc<LocalDriver>,
+    services: Option<MultiServiceHandle>,
+    device_callback: Mutex<Option<DeviceCallbackThread>>,
+}
+
+/// User data of a device callback, handed to the thread calling it.
+struct UserData(*mut c_void);
+
+// The application is responsible for the user data being usable from the callback thread.
+unsafe impl Send for UserData {}
+
+/// Thread calling a device callback, fed with device events by a task of the driver.
+struct DeviceCallbackThread {
+    forwarder: JoinHandle<()>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+impl DeviceCallbackThread {
+    /// Stops the calls, waiting for a call in progress unless called from the callback itself.
+    fn stop(self, runtime: &Runtime) {
+        self.forwarder.abort();
+        let _ = runtime.block_on(self.forwarder);
+        if self.thread.thread().id() != std::thread::current().id() {
+            let _ = self.thread.join();
+        }
+    }
+}
+
+/// Value of a checked argument, or the error code returned for it.
+macro_rules! check {
+    ($result:expr) => {
+        match $result {
+            Ok(value) => value,
+            Err(code) => return code,
+        }
+    };
+}
+
+fn player_id(id: u32) -> Result<ManagedPlayerId, i32> {
+    ManagedPlayerId::new(id).ok_or_else(|| fail(FSCT_ERROR_INVALID_ARGUMENT, "Player id must not be 0"))
+}
+
+/// Borrows the driver behind `driver`, failing for NULL.
+///
+/// # Safety
+/// `driver` must be NULL or returned by `fsct_driver_create` and not yet destroyed.
+unsafe fn driver<'a>(driver: *const FsctDriver) -> Result<&'a FsctDriver, i32> {
+    unsafe { driver.as_ref() }.ok_or_else(|| fail(FSCT_ERROR_INVALID_ARGUMENT, "Driver must not be NULL"))
+}
+
+/// Creates a driver and starts watching devices; NULL on failure, see `fsct_last_error`.
+#[unsafe(no_mangle)]
+pub extern "C" fn fsct_driver_create() -> *mut FsctDriver {
+    let start = || -> anyhow::Result<FsctDriver> {
+        let runtime = tokio::runtime::Builder::new_multi_thread().worker_threads(2).enable_all().build()?;
+        let driver = Arc::new(LocalDriver::with_new_managers());
+        let services = runtime.block_on(driver.run()).map_err(|e| anyhow!(e))?;
+        Ok(FsctDriver { runtime, driver, services: Some(services), device_callback: Mutex::new(None) })
+    };
+    match start() {
+        Ok(driver) => Box::into_raw(Box::new(driver)),
+        Err(e) => {
+            fail(FSCT_ERROR_DRIVER, format!("Failed to start FSCT driver: {}", e));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Stops the driver and frees it.
+///
+/// # Safety
+/// `driver` must be NULL or returned by `fsct_driver_create` and not yet destroyed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fsct_driver_destroy(driver: *mut FsctDriver) {
+    if driver.is_null() {
+        return;
+    }
+    let mut driver = unsafe { Box::from_raw(driver) };
+    let callback = driver.device_callback.lock().unwrap().take();
+    if let Some(callback) = callback {
+        callback.stop(&driver.runtime);
+    }
+    if let Some(services) = driver.services.take()
+        && let Err(e) = driver.runtime.block_on(services.shutdown())
+    {
+        error!("Failed to stop FSCT driver: {}", e);
+    }
+}
+
+/// Registers a player named `self_id` (UTF-8), storing its managed id in `player_id`.
+///
+/// # Safety
+/// `driver` must be valid, `self_id` a NUL terminated string and `player_id` writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fsct_register_player(driver: *const FsctDriver, self_id: *const c_char,
+                                              player_id: *mut u32) -> i32 {
+    let driver = check!(unsafe { self::driver(driver) });
+    if self_id.is_null() || player_id.is_null() {
+        return fail(FSCT_ERROR_INVALID_ARGUMENT, "Self id and player id must not be NULL");
+    }
+    let Ok(self_id) = unsafe { CStr::from_ptr(self_id) }.to_str() else {
+        return fail(FSCT_ERROR_INVALID_ARGUMENT, "Self id must be UTF-8");
+    };
+    match driver.runtime.block_on(driver.driver.register_player(self_id.to_string())) {
+        Ok(id) => {
+            unsafe { *player_id = id.get() };
+            FSCT_OK
+        }
+        Err(e) => fail(FSCT_ERROR_DRIVER, e),
+    }
+}
+
+/// Unregisters a player, so devices stop showing it.
+///
+/// # Safety
+/// `driver` must be valid.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fsct_unregister_player(driver: *const FsctDriver, player_id: u32) -> i32 {
+    let driver = check!(unsafe { self::driver(driver) });
+    let player_id = check!(self::player_id(player_id));
+    match driver.runtime.block_on(driver.driver.unregister_player(player_id)) {
+        Ok(()) => FSCT_OK,
+        Err(e) => fail(FSCT_ERROR_DRIVER, e),
+    }
+}
+
+/// Publishes the full state of a player, with its position taken now.
+///
+/// # Safety
+/// `driver` must be valid and `state` point to a state whose strings are NULL or NUL terminated.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fsct_update_state(driver: *const FsctDriver, player_id: u32,
+                                           state: *const FsctPlayerState) -> i32 {
+    let driver = check!(unsafe { self::driver(driver) });
+    let player_id = check!(self::player_id(player_id));
+    let Some(state) = (unsafe { state.as_ref() }) else {
+        return fail(FSCT_ERROR_INVALID_ARGUMENT, "State must not be NULL");
+    };
+    let state = match unsafe { state.player_state(SystemTime::now()) } {
+        Ok(state) => state,
+        Err(e) => return fail(FSCT_ERROR_INVALID_ARGUMENT, e),
+    };
+    match driver.runtime.block_on(driver.driver.update_player_state(player_id, state)) {
+        Ok(()) => FSCT_OK,
+        Err(e) => fail(FSCT_ERROR_DRIVER, e),
+    }
+}
+
+/// Sets the player shown on devices whenever it plays; 0 clears it.
+///
+/// # Safety
+/// `driver` must be valid.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fsct_set_preferred_player(driver: *const FsctDriver, player_id: u32) -> i32 {
+    let driver = check!(unsafe { self::driver(driver) });
+    match driver.driver.set_preferred_player(ManagedPlayerId::new(player_id)) {
+        Ok(()) => FSCT_OK,
+        Err(e) => fail(FSCT_ERROR_DRIVER, e),
+    }
+}
+
+/// Calls `callback` with `user_data` when devices are added or removed, replacing the previous callback; a NULL
+/// callback stops the calls. Returns once a call of the previous callback in progress has ended, unless called from
+/// that callback.
+///
+/// # Safety
+/// `driver` must be valid; `user_data` must stay usable from another thread until the callback is replaced or the
+/// driver destroyed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fsct_set_device_callback(driver: *const FsctDriver, callback: FsctDeviceCallback,
+                                                  user_data: *mut c_void) -> i32 {
+    let driver = check!(unsafe { self::driver(driver) });
+    let previous = driver.device_callback.lock().unwrap().take();
+    if let Some(previous) = previous {
+        previous.stop(&driver.runtime);
+    }
+    let Some(callback) = callback else { return FSCT_OK };
+    let mut events = driver.driver.device_manager().subscribe();
+    let (event_tx, event_rx) = std::sync::mpsc::channel();
+    let user_data = UserData(user_data);
+    let thread = std::thread::Builder::new().name("fsct-device-callback".to_string()).spawn(move || {
+        let user_data = user_data;
+        // ends once the forwarder is stopped and the events it forwarded are delivered
+        for (event, device_id) in event_rx {
+            let device_id = CString::new(device_id.to_string()).unwrap_or_default();
+            unsafe { callback(user_data.0, event, device_id.as_ptr()) };
+        }
+    });
+    let thread = match thread {
+        Ok(thread) => thread,
+        Err(e) => return fail(FSCT_ERROR_DRIVER, format!("Failed to start device callback thread: {}", e)),
+    };
+    let forwarder = driver.runtime.spawn(async move {
+        loop {
+            let event = match events.recv().await {
+                Ok(DeviceEvent::Added(device_id)) => (FSCT_DEVICE_ADDED, device_id),
+                Ok(DeviceEvent::Removed(device_id)) => (FSCT_DEVICE_REMOVED, device_id),
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            if event_tx.send(event).is_err() {
+                break;
+            }
+        }
+    });
+    let replaced = driver.device_callback.lock().unwrap().replace(DeviceCallbackThread { forwarder, thread });
+    // set by a concurrent call meanwhile
+    if let Some(replaced) = replaced {
+        replaced.stop(&driver.runtime);
+    }
+    FSCT_OK
+}
+
+/// Message of the last error on the calling thread, NULL if none; valid until the next call failing on the thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn fsct_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(std::ptr::null(), |message| message.as_ptr()))
+}
@@ -0,0 +1,135 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Player state passed from C.
+
+use std::ffi::{c_char, CStr};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, Error};
+use fsct_core::definitions::{FsctStatus, TimelineInfo};
+use fsct_core::player_state::{PlayerState, TrackMetadata};
+
+/// Full state of a player, see `FsctPlayerState` in `include/fsct.h`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FsctPlayerState {
+    /// FSCT status code, e.g. `FSCT_STATUS_PLAYING`.
+    pub status: u8,
+    /// Whether the position fields are set.
+    pub has_timeline: bool,
+    pub position_ms: u64,
+    /// Negative for live streams.
+    pub duration_ms: i64,
+    pub rate: f64,
+    /// UTF-8 texts of the current track, NULL if none.
+    pub title: *const c_char,
+    pub artist: *const c_char,
+    pub album: *const c_char,
+    pub genre: *const c_char,
+    pub year: *const c_char,
+    pub composer: *const c_char,
+}
+
+fn status(code: u8) -> Result<FsctStatus, Error> {
+    Ok(match code {
+        0x00 => FsctStatus::Stopped,
+        0x01 => FsctStatus::Playing,
+        0x02 => FsctStatus::Paused,
+        0x03 => FsctStatus::Seeking,
+        0x04 => FsctStatus::Buffering,
+        0x05 => FsctStatus::Error,
+        0x0F => FsctStatus::Unknown,
+        code => return Err(anyhow!("Unknown status code {:#04x}", code)),
+    })
+}
+
+/// # Safety
+/// `text` must be NULL or a NUL terminated string.
+unsafe fn text(text: *const c_char) -> Result<Option<String>, Error> {
+    if text.is_null() {
+        return Ok(None);
+    }
+    let text = unsafe { CStr::from_ptr(text) }.to_str().map_err(|_| anyhow!("Texts must be UTF-8"))?;
+    Ok(Some(text.to_string()))
+}
+
+impl FsctPlayerState {
+    /// State of the driver, with the position taken at `now`.
+    ///
+    /// # Safety
+    /// The texts must be NULL or NUL terminated strings.
+    pub(crate) unsafe fn player_state(&self, now: SystemTime) -> Result<PlayerState, Error> {
+        Ok(PlayerState {
+            status: status(self.status)?,
+            timeline: self.has_timeline.then(|| TimelineInfo {
+                position: Duration::from_millis(self.position_ms),
+                update_time: now,
+                duration: u64::try_from(self.duration_ms).ok().map(Duration::from_millis),
+                rate: self.rate,
+            }),
+            texts: unsafe {
+                TrackMetadata {
+                    title: text(self.title)?,
+                    artist: text(self.artist)?,
+                    album: text(self.album)?,
+                    genre: text(self.genre)?,
+                    year: text(self.year)?,
+                    composer: text(self.composer)?,
+                    ..Default::default()
+                }
+            },
+            volume: None,
+            modes: Default::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr::null;
+
+    #[test]
+    fn c_state_becomes_player_state() {
+        let now = SystemTime::now();
+        let title = c"Song";
+        let mut state = FsctPlayerState {
+            status: 0x01,
+            has_timeline: true,
+            position_ms: 12500,
+            duration_ms: -1,
+            rate: 1.0,
+            title: title.as_ptr(),
+            artist: null(),
+            album: null(),
+            genre: null(),
+            year: null(),
+            composer: null(),
+        };
+        let player_state = unsafe { state.player_state(now) }.unwrap();
+        assert_eq!(player_state.status, FsctStatus::Playing);
+        assert_eq!(player_state.texts.title.as_deref(), Some("Song"));
+        assert_eq!(player_state.texts.artist, None);
+        let timeline = player_state.timeline.unwrap();
+        assert_eq!(timeline.position, Duration::from_millis(12500));
+        assert_eq!(timeline.duration, None);
+
+        state.status = 0x42;
+        assert!(unsafe { state.player_state(now) }.is_err());
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Connection of the app to the host.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Error;
+use async_trait::async_trait;
+use fsct_core::remote::RemoteDriver;
+use fsct_core::{FsctDriver, ManagedPlayerId, PlayerInterface};
+use log::warn;
+use thiserror::Error;
+use tokio::runtime::Runtime;
+
+use crate::now_playing::NowPlaying;
+
+#[derive(Debug, Error, uniffi::Error)]
+pub enum CompanionError {
+    #[error("Failed to connect to the FSCT host: {0}")]
+    Connection(String),
+    #[error("FSCT host refused the request: {0}")]
+    Host(String),
+}
+
+fn host_error(e: Error) -> CompanionError {
+    CompanionError::Host(e.to_string())
+}
+
+/// Playback commands of the devices showing the app, to be carried out through `MPRemoteCommandCenter`'s targets.
+#[uniffi::export(with_foreign)]
+pub trait PlaybackControl: Send + Sync {
+    fn play(&self);
+    fn pause(&self);
+    fn next_track(&self);
+    fn previous_track(&self);
+    fn seek(&self, position_secs: f64);
+}
+
+/// Carries out commands of devices through the app's control.
+struct ForeignInterface(Arc<dyn PlaybackControl>);
+
+#[async_trait]
+impl PlayerInterface for ForeignInterface {
+    async fn play(&self) -> Result<(), Error> {
+        self.0.play();
+        Ok(())
+    }
+
+    async fn pause(&self) -> Result<(), Error> {
+        self.0.pause();
+        Ok(())
+    }
+
+    async fn next_track(&self) -> Result<(), Error> {
+        self.0.next_track();
+        Ok(())
+    }
+
+    async fn previous_track(&self) -> Result<(), Error> {
+        self.0.previous_track();
+        Ok(())
+    }
+
+    async fn seek(&self, position: Duration) -> Result<(), Error> {
+        self.0.seek(position.as_secs_f64());
+        Ok(())
+    }
+}
+
+/// The app as a player of an FSCT host.
+#[derive(uniffi::Object)]
+pub struct FsctCompanion {
+    runtime: Runtime,
+    driver: RemoteDriver,
+    player_id: Mutex<Option<ManagedPlayerId>>,
+}
+
+#[uniffi::export]
+impl FsctCompanion {
+    /// Connects to the host at `endpoint`, e.g. `http://streamer.local:50151`, and registers the app as the player
+    /// `self_id`, e.g. its bundle identifier. `token` is presented to hosts limiting who may use their driver server.
+    #[uniffi::constructor]
+    pub fn connect(endpoint: String, self_id: String, token: Option<String>) -> Result<Arc<Self>, CompanionError> {
+        // the event feed of the driver runs on the worker while the app is not calling in
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .map_err(|e| CompanionError::Connection(e.to_string()))?;
+        let driver = match token {
+            Some(token) => runtime.block_on(RemoteDriver::connect_with_token(endpoint, &token)),
+            None => runtime.block_on(RemoteDriver::connect(endpoint)),
+        };
+        let driver = driver.map_err(|e| CompanionError::Connection(e.to_string()))?;
+        let player_id = runtime.block_on(driver.register_player(self_id)).map_err(host_error)?;
+        Ok(Arc::new(Self { runtime, driver, player_id: Mutex::new(Some(player_id)) }))
+    }
+
+    /// Publishes what the app plays now.
+    pub fn publish(&self, now_playing: NowPlaying) -> Result<(), CompanionError> {
+        let player_id = self.player_id()?;
+        let state = now_playing.player_state(SystemTime::now());
+        self.runtime.block_on(self.driver.update_player_state(player_id, state)).map_err(host_error)
+    }
+
+    /// Attaches the control through which devices showing the app play, pause and skip; `None` detaches it.
+    pub fn set_control(&self, control: Option<Arc<dyn PlaybackControl>>) -> Result<(), CompanionError> {
+        let player_id = self.player_id()?;
+        let interface = control.map(|control| Arc::new(ForeignInterface(control)) as Arc<dyn PlayerInterface>);
+        let _guard = self.runtime.enter();
+        self.driver.set_player_interface(player_id, interface).map_err(host_error)
+    }
+
+    /// Unregisters the player, so devices stop showing the app; the companion can't publish afterwards.
+    pub fn disconnect(&self) -> Result<(), CompanionError> {
+        let Some(player_id) = self.player_id.lock().unwrap().take() else { return Ok(()) };
+        self.runtime.block_on(self.driver.unregister_player(player_id)).map_err(host_error)
+    }
+}
+
+impl FsctCompanion {
+    fn player_id(&self) -> Result<ManagedPlayerId, CompanionError> {
+        self.player_id.lock().unwrap().ok_or_else(|| CompanionError::Host("Companion is disconnected".to_string()))
+    }
+}
+
+impl Drop for FsctCompanion {
+    fn drop(&mut self) {
+        if let Err(e) = self.disconnect() {
+            warn!("{}", e);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! iOS/iPadOS companion: publishes the now-playing state of an app to an FSCT host on the LAN, e.g. to show what
+//! an iPhone plays on a desk display.
+//!
+//! The host drives the devices and serves its driver over gRPC (`fsct_core::remote::DriverServer`); the companion
+//! registers one player through a `RemoteDriver` connected to it. Swift bindings are generated with UniFFI from
+//! the built library:
+//!
+//! ```sh
+//! cargo build -p fsct-ios-lib --release --target aarch64-apple-ios
+//! uniffi-bindgen generate --library target/aarch64-apple-ios/release/libfsct_ios_lib.a --language swift --out-dir out
+//! ```
+//!
+//! Calls block until the host answered, so apps make them off the main thread.
+
+uniffi::setup_scaffolding!();
+
+mod companion;
+mod now_playing;
+
+pub use companion::{CompanionError, FsctCompanion, PlaybackControl};
+pub use now_playing::{NowPlaying, PlaybackStatus};
@@ -0,0 +1,117 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Now-playing state as reported by the app.
+
+use std::time::{Duration, SystemTime};
+
+use fsct_core::definitions::{FsctStatus, TimelineInfo};
+use fsct_core::player_state::{PlayerState, TrackMetadata};
+
+/// Playback status, as in `MPNowPlayingPlaybackState` plus the transient states of players.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum PlaybackStatus {
+    Stopped,
+    Playing,
+    Paused,
+    Seeking,
+    Buffering,
+    Interrupted,
+    Unknown,
+}
+
+/// What the app plays, mirroring the `MPNowPlayingInfoCenter` properties the host shows.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct NowPlaying {
+    pub status: PlaybackStatus,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub genre: Option<String>,
+    /// `MPNowPlayingInfoPropertyElapsedPlaybackTime`, in seconds.
+    pub elapsed_secs: Option<f64>,
+    /// `MPMediaItemPropertyPlaybackDuration`, in seconds; none for live streams.
+    pub duration_secs: Option<f64>,
+    /// `MPNowPlayingInfoPropertyPlaybackRate`.
+    pub rate: f64,
+}
+
+fn status(status: PlaybackStatus) -> FsctStatus {
+    match status {
+        PlaybackStatus::Stopped => FsctStatus::Stopped,
+        PlaybackStatus::Playing => FsctStatus::Playing,
+        PlaybackStatus::Paused | PlaybackStatus::Interrupted => FsctStatus::Paused,
+        PlaybackStatus::Seeking => FsctStatus::Seeking,
+        PlaybackStatus::Buffering => FsctStatus::Buffering,
+        PlaybackStatus::Unknown => FsctStatus::Unknown,
+    }
+}
+
+fn secs(secs: f64) -> Option<Duration> {
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+impl NowPlaying {
+    /// State of the player, with the elapsed time taken at `now`.
+    pub fn player_state(&self, now: SystemTime) -> PlayerState {
+        PlayerState {
+            status: status(self.status),
+            timeline: self.elapsed_secs.and_then(secs).map(|position| TimelineInfo {
+                position,
+                update_time: now,
+                duration: self.duration_secs.and_then(secs),
+                rate: self.rate,
+            }),
+            texts: TrackMetadata {
+                title: self.title.clone(),
+                artist: self.artist.clone(),
+                album: self.album.clone(),
+                genre: self.genre.clone(),
+                ..Default::default()
+            },
+            volume: None,
+            modes: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn now_playing_info_becomes_player_state() {
+        let now = SystemTime::now();
+        let now_playing = NowPlaying {
+            status: PlaybackStatus::Interrupted,
+            title: Some("Song".to_string()),
+            artist: Some("Band".to_string()),
+            album: None,
+            genre: None,
+            elapsed_secs: Some(12.5),
+            duration_secs: Some(-1.0),
+            rate: 0.0,
+        };
+        let state = now_playing.player_state(now);
+        assert_eq!(state.status, FsctStatus::Paused);
+        assert_eq!(state.texts.title.as_deref(), Some("Song"));
+        let timeline = state.timeline.unwrap();
+        assert_eq!(timeline.position, Duration::from_millis(12500));
+        assert_eq!(timeline.update_time, now);
+        assert_eq!(timeline.duration, None);
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Audio of what the host plays, captured from the monitor of the default PipeWire sink for level analysis.
+//!
+//! The capture runs `pw-record` from the PipeWire tools rather than linking libpipewire, so the port keeps
+//! building as a static musl binary; without the tools (or PipeWire) nothing is published.
+
+use std::process::Stdio;
+
+use fsct_core::audio_levels::{AudioBlock, AudioCapture, MAX_AUDIO_LEVEL_RATE};
+use fsct_core::service::{spawn_service, ServiceHandle};
+use log::{debug, warn};
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+use tokio::sync::watch;
+
+const SAMPLE_RATE: usize = 48000;
+const CHANNELS: usize = 2;
+/// Samples of the shortest report interval, 4 bytes each.
+const CHUNK_SAMPLES: usize = SAMPLE_RATE / MAX_AUDIO_LEVEL_RATE as usize * CHANNELS;
+
+/// [`AudioCapture`] backend of the default sink's monitor.
+pub struct PipeWireCapture;
+
+impl AudioCapture for PipeWireCapture {
+    fn name(&self) -> &'static str {
+        "PipeWire"
+    }
+
+    fn run(&self, blocks: watch::Sender<AudioBlock>) -> ServiceHandle {
+        spawn_service(move |mut stop| async move {
+            let child = Command::new("pw-record")
+                .args(["-P", "{ stream.capture.sink = true }", "--format", "f32", "--rate", &SAMPLE_RATE.to_string(),
+                       "--channels", &CHANNELS.to_string(), "-"])
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .kill_on_drop(true)
+                .spawn();
+            let mut child = match child {
+                Ok(child) => child,
+                Err(e) => {
+                    warn!("Failed to start PipeWire monitor capture (pw-record): {}", e);
+                    return;
+                }
+            };
+            let Some(mut stdout) = child.stdout.take() else { return };
+            let mut chunk = vec![0u8; CHUNK_SAMPLES * 4];
+            loop {
+                tokio::select! {
+                    _ = stop.signaled() => break,
+                    read = stdout.read_exact(&mut chunk) => {
+                        if let Err(e) = read {
+                            warn!("PipeWire monitor capture ended: {}", e);
+                            break;
+                        }
+                        let samples = chunk.chunks_exact(4)
+                            .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                            .collect();
+                        blocks.send_replace(AudioBlock {
+                            samples,
+                            channels: CHANNELS,
+                            sample_rate: SAMPLE_RATE as u32,
+                        });
+                    }
+                }
+            }
+            if let Err(e) = child.kill().await {
+                debug!("Failed to stop pw-record: {}", e);
+            }
+        })
+    }
+}
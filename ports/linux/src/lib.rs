@@ -0,0 +1,38 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+
+//! Linux player port: follows media players implementing the
+//! [MPRIS2](https://specifications.freedesktop.org/mpris-spec/latest/) D-Bus interface on the session bus.
+//!
+//! Unlike the Windows and macOS watchers, which follow the single session the OS considers current, every
+//! `org.mpris.MediaPlayer2.*` bus is registered as a player of its own and the orchestrator picks which one
+//! devices show.
+//!
+//! Nothing here depends on glibc or libsystemd, so the port also builds as a static musl binary for embedded
+//! streamers (e.g. `aarch64-unknown-linux-musl`).
+//!
+//! With the `audio-levels` feature, [`audio_levels`] captures levels of what the host plays for devices with VU
+//! meter displays.
+
+#[cfg(feature = "audio-levels")]
+pub mod audio_levels;
+pub mod logging;
+pub mod metadata;
+pub mod watcher;
+
+pub use watcher::{run_os_watcher, run_os_watcher_with_self_ids};
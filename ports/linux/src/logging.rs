@@ -0,0 +1,85 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Logging of Linux services.
+//!
+//! Whether the output goes to the systemd journal is detected when the service starts, so the same (possibly static
+//! musl) binary runs under systemd, under other init systems of embedded streamers and from a terminal.
+//! Under systemd, log lines carry the `<N>` priority prefixes of sd-daemon(3) instead of timestamps; journald parses
+//! them from the standard error stream without linking libsystemd.
+
+use std::io::Write;
+use std::os::unix::fs::MetadataExt;
+
+use env_logger::{Env, Target};
+use log::{Level, LevelFilter};
+
+/// Whether standard error is connected to the systemd journal, as announced by systemd in `JOURNAL_STREAM`.
+pub fn connected_to_journal() -> bool {
+    let Some(stream) = std::env::var_os("JOURNAL_STREAM") else { return false };
+    let Some((device, inode)) = parse_journal_stream(&stream.to_string_lossy()) else { return false };
+    // the variable is inherited by children whose output is redirected elsewhere
+    std::fs::metadata("/proc/self/fd/2").is_ok_and(|stderr| stderr.dev() == device && stderr.ino() == inode)
+}
+
+fn parse_journal_stream(value: &str) -> Option<(u64, u64)> {
+    let (device, inode) = value.split_once(':')?;
+    Some((device.parse().ok()?, inode.parse().ok()?))
+}
+
+/// Syslog priority of a log level, see sd-daemon(3).
+fn journal_priority(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+/// Initializes logging to standard error, filtered by `FSCT_LOG` (default `info`).
+pub fn init_logger() {
+    init_logger_with_level(LevelFilter::Info);
+}
+
+/// Initializes logging to standard error, filtered by `FSCT_LOG` (default `default_level`).
+pub fn init_logger_with_level(default_level: LevelFilter) {
+    let env = Env::default()
+        .filter_or("FSCT_LOG", default_level.as_str())
+        .write_style("FSCT_LOG_STYLE");
+    let mut builder = env_logger::Builder::from_env(env);
+    builder.target(Target::Stderr);
+    if connected_to_journal() {
+        builder.format(|buf, record| {
+            writeln!(buf, "<{}>{}: {}", journal_priority(record.level()), record.target(), record.args())
+        });
+    }
+    builder.init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn journal_stream_is_device_and_inode() {
+        assert_eq!(parse_journal_stream("8:45210"), Some((8, 45210)));
+        assert_eq!(parse_journal_stream("8"), None);
+        assert_eq!(parse_journal_stream("x:1"), None);
+        assert_eq!(journal_priority(Level::Warn), 4);
+    }
+}
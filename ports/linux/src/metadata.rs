@@ -0,0 +1,233 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+
+//! Translation of MPRIS2 player properties into [`PlayerState`].
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use fsct_core::definitions::{FsctStatus, PlaybackModes, RepeatMode, TimelineInfo, VolumeInfo};
+use fsct_core::player_state::{PlayerState, TrackMetadata};
+use zbus::zvariant::{OwnedValue, Value};
+
+/// Properties of an `org.mpris.MediaPlayer2.Player` object.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MprisProperties {
+    /// `PlaybackStatus`: `Playing`, `Paused` or `Stopped`.
+    pub playback_status: String,
+    pub metadata: MprisMetadata,
+    /// `Position` in microseconds; not all players report it.
+    pub position: Option<i64>,
+    /// `Rate`; 1.0 for players not reporting it.
+    pub rate: f64,
+    /// `Volume`, 0.0 to 1.0; not all players report it.
+    pub volume: Option<f64>,
+    /// `Shuffle`; not all players report it.
+    pub shuffle: Option<bool>,
+    /// `LoopStatus`: `None`, `Track` or `Playlist`; not all players report it.
+    pub loop_status: Option<String>,
+}
+
+/// The `Metadata` property, limited to what devices show.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MprisMetadata {
+    pub title: Option<String>,
+    pub artists: Vec<String>,
+    pub album: Option<String>,
+    pub genres: Vec<String>,
+    /// `xesam:contentCreated`, an ISO 8601 date or date-time.
+    pub content_created: Option<String>,
+    pub composers: Vec<String>,
+    /// `mpris:length` in microseconds.
+    pub length: Option<i64>,
+}
+
+impl MprisMetadata {
+    pub fn from_dbus(metadata: &HashMap<String, OwnedValue>) -> Self {
+        let value = |key: &str| metadata.get(key).map(|value| unwrap_variant(value));
+        Self {
+            title: value("xesam:title").and_then(string),
+            artists: value("xesam:artist").map(strings).unwrap_or_default(),
+            album: value("xesam:album").and_then(string),
+            genres: value("xesam:genre").map(strings).unwrap_or_default(),
+            content_created: value("xesam:contentCreated").and_then(string),
+            composers: value("xesam:composer").map(strings).unwrap_or_default(),
+            length: value("mpris:length").and_then(integer),
+        }
+    }
+
+    fn year(&self) -> Option<String> {
+        let year = self.content_created.as_deref()?.get(..4)?;
+        year.bytes().all(|b| b.is_ascii_digit()).then(|| year.to_string())
+    }
+}
+
+fn unwrap_variant<'a>(value: &'a Value<'a>) -> &'a Value<'a> {
+    match value {
+        Value::Value(inner) => unwrap_variant(inner),
+        value => value,
+    }
+}
+
+fn string(value: &Value) -> Option<String> {
+    match value {
+        Value::Str(s) if !s.as_str().is_empty() => Some(s.as_str().to_string()),
+        _ => None,
+    }
+}
+
+/// Lists of strings like `xesam:artist`; some players send a single string instead.
+fn strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::Array(array) => array.iter().filter_map(|value| string(unwrap_variant(value))).collect(),
+        value => string(value).into_iter().collect(),
+    }
+}
+
+/// `mpris:length` is specified as a signed 64-bit integer, but players send other integer types as well.
+fn integer(value: &Value) -> Option<i64> {
+    match *value {
+        Value::I64(n) => Some(n),
+        Value::U64(n) => i64::try_from(n).ok(),
+        Value::I32(n) => Some(n.into()),
+        Value::U32(n) => Some(n.into()),
+        _ => None,
+    }
+}
+
+fn joined(values: &[String]) -> Option<String> {
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+fn microseconds(us: i64) -> Duration {
+    Duration::from_micros(us.max(0) as u64)
+}
+
+impl MprisProperties {
+    pub fn status(&self) -> FsctStatus {
+        match self.playback_status.as_str() {
+            "Playing" => FsctStatus::Playing,
+            "Paused" => FsctStatus::Paused,
+            "Stopped" => FsctStatus::Stopped,
+            _ => FsctStatus::Unknown,
+        }
+    }
+
+    fn texts(&self) -> TrackMetadata {
+        let metadata = &self.metadata;
+        TrackMetadata {
+            title: metadata.title.clone(),
+            artist: joined(&metadata.artists),
+            album: metadata.album.clone(),
+            genre: joined(&metadata.genres),
+            year: metadata.year(),
+            composer: joined(&metadata.composers),
+            // MPRIS doesn't tell what plays next
+            ..Default::default()
+        }
+    }
+
+    fn timeline(&self, now: SystemTime) -> Option<TimelineInfo> {
+        let status = self.status();
+        if status == FsctStatus::Stopped || (self.position.is_none() && self.metadata.length.is_none()) {
+            return None;
+        }
+        // live streams have a position but no length
+        let duration = self.metadata.length.filter(|length| *length > 0).map(microseconds);
+        let rate = if status == FsctStatus::Playing { self.rate } else { 0.0 };
+        Some(TimelineInfo {
+            position: microseconds(self.position.unwrap_or(0)),
+            update_time: now,
+            duration,
+            rate,
+        })
+    }
+
+    fn volume(&self) -> Option<VolumeInfo> {
+        // MPRIS has no mute, players mute by setting the volume to zero
+        self.volume.map(|volume| VolumeInfo { level: (volume.clamp(0.0, 1.0) * 100.0).round() as u8, muted: false })
+    }
+
+    fn modes(&self) -> PlaybackModes {
+        let repeat = match self.loop_status.as_deref() {
+            Some("None") => Some(RepeatMode::Off),
+            Some("Track") => Some(RepeatMode::Track),
+            Some("Playlist") => Some(RepeatMode::List),
+            _ => None,
+        };
+        PlaybackModes { shuffle: self.shuffle, repeat }
+    }
+
+    /// The player state, with the position taken at `now`.
+    pub fn player_state(&self, now: SystemTime) -> PlayerState {
+        PlayerState {
+            status: self.status(),
+            timeline: self.timeline(now),
+            texts: self.texts(),
+            volume: self.volume(),
+            modes: self.modes(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn properties_are_translated_to_player_state() {
+        let now = SystemTime::now();
+        let mut properties = MprisProperties {
+            playback_status: "Playing".to_string(),
+            metadata: MprisMetadata {
+                title: Some("Song".to_string()),
+                artists: vec!["Band".to_string(), "Guest".to_string()],
+                content_created: Some("2009-03-01T00:00:00Z".to_string()),
+                length: Some(245_000_000),
+                ..Default::default()
+            },
+            position: Some(30_500_000),
+            rate: 1.0,
+            volume: Some(0.755),
+            shuffle: Some(false),
+            loop_status: Some("Playlist".to_string()),
+        };
+
+        let state = properties.player_state(now);
+        assert_eq!(state.status, FsctStatus::Playing);
+        assert_eq!(state.texts.title.as_deref(), Some("Song"));
+        assert_eq!(state.texts.artist.as_deref(), Some("Band, Guest"));
+        assert_eq!(state.texts.year.as_deref(), Some("2009"));
+        assert_eq!(state.volume, Some(VolumeInfo { level: 76, muted: false }));
+        assert_eq!(state.modes, PlaybackModes { shuffle: Some(false), repeat: Some(RepeatMode::List) });
+        assert_eq!(state.timeline, Some(TimelineInfo {
+            position: Duration::from_millis(30_500),
+            update_time: now,
+            duration: Some(Duration::from_secs(245)),
+            rate: 1.0,
+        }));
+
+        properties.playback_status = "Paused".to_string();
+        properties.metadata.length = None;
+        let timeline = properties.player_state(now).timeline.unwrap();
+        assert_eq!((timeline.duration, timeline.rate), (None, 0.0));
+
+        properties.playback_status = "Stopped".to_string();
+        assert_eq!(properties.player_state(now).timeline, None);
+    }
+}
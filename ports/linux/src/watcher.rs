@@ -0,0 +1,247 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use fsct_core::definitions::VolumeInfo;
+use fsct_core::player_state::PlayerState;
+use fsct_core::self_id::SelfIdNamespace;
+use fsct_core::service::{spawn_service, ServiceHandle};
+use fsct_core::{FsctDriver, PlayerInterface};
+use fsct_port_sdk::PortPlayer;
+use futures::StreamExt;
+use log::{debug, error, info, warn};
+use zbus::fdo::DBusProxy;
+use zbus::zvariant::{ObjectPath, OwnedValue};
+use zbus::{proxy, Connection};
+
+use crate::metadata::{MprisMetadata, MprisProperties};
+
+/// Bus name prefix under which MPRIS2 players are reachable.
+pub const MPRIS_BUS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+
+#[proxy(interface = "org.mpris.MediaPlayer2.Player", default_path = "/org/mpris/MediaPlayer2")]
+trait MediaPlayer2Player {
+    #[zbus(property)]
+    fn playback_status(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn metadata(&self) -> zbus::Result<HashMap<String, OwnedValue>>;
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn position(&self) -> zbus::Result<i64>;
+
+    #[zbus(property)]
+    fn rate(&self) -> zbus::Result<f64>;
+
+    #[zbus(property)]
+    fn volume(&self) -> zbus::Result<f64>;
+
+    #[zbus(property)]
+    fn set_volume(&self, volume: f64) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn shuffle(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn loop_status(&self) -> zbus::Result<String>;
+
+    #[zbus(signal)]
+    fn seeked(&self, position: i64) -> zbus::Result<()>;
+
+    fn play(&self) -> zbus::Result<()>;
+
+    fn pause(&self) -> zbus::Result<()>;
+
+    fn next(&self) -> zbus::Result<()>;
+
+    fn previous(&self) -> zbus::Result<()>;
+
+    fn set_position(&self, track_id: &ObjectPath<'_>, position: i64) -> zbus::Result<()>;
+}
+
+/// Controls the player through its MPRIS interface.
+struct MprisPlayerInterface {
+    proxy: MediaPlayer2PlayerProxy<'static>,
+}
+
+#[async_trait]
+impl PlayerInterface for MprisPlayerInterface {
+    async fn play(&self) -> Result<(), Error> {
+        Ok(self.proxy.play().await?)
+    }
+
+    async fn pause(&self) -> Result<(), Error> {
+        Ok(self.proxy.pause().await?)
+    }
+
+    async fn next_track(&self) -> Result<(), Error> {
+        Ok(self.proxy.next().await?)
+    }
+
+    async fn previous_track(&self) -> Result<(), Error> {
+        Ok(self.proxy.previous().await?)
+    }
+
+    async fn seek(&self, position: Duration) -> Result<(), Error> {
+        // SetPosition is ignored unless it names the current track
+        let metadata = self.proxy.metadata().await?;
+        let track_id = metadata.get("mpris:trackid")
+                               .and_then(|value| ObjectPath::try_from(value.try_clone().ok()?).ok())
+                               .ok_or_else(|| anyhow!("Player reports no track id"))?;
+        Ok(self.proxy.set_position(&track_id, position.as_micros() as i64).await?)
+    }
+
+    async fn set_volume(&self, volume: VolumeInfo) -> Result<(), Error> {
+        // MPRIS has no mute, muting sets the volume to zero
+        let level = if volume.muted { 0.0 } else { f64::from(volume.level.min(100)) / 100.0 };
+        Ok(self.proxy.set_volume(level).await?)
+    }
+}
+
+/// Id under which the player owning `bus_name` is registered, e.g. `native-linux-mpris-spotify`.
+fn player_self_id(self_ids: &SelfIdNamespace, bus_name: &str) -> String {
+    self_ids.self_id(&format!("linux-mpris-{}", bus_name.strip_prefix(MPRIS_BUS_PREFIX).unwrap_or(bus_name)))
+}
+
+async fn read_state(proxy: &MediaPlayer2PlayerProxy<'_>) -> Result<PlayerState, Error> {
+    let properties = MprisProperties {
+        playback_status: proxy.playback_status().await?,
+        metadata: MprisMetadata::from_dbus(&proxy.metadata().await.unwrap_or_default()),
+        position: proxy.position().await.ok(),
+        rate: proxy.rate().await.unwrap_or(1.0),
+        volume: proxy.volume().await.ok(),
+        shuffle: proxy.shuffle().await.ok(),
+        loop_status: proxy.loop_status().await.ok(),
+    };
+    Ok(properties.player_state(SystemTime::now()))
+}
+
+/// Follows one player until its properties can no longer be read, pushing what changed on every
+/// `PropertiesChanged` and `Seeked`.
+async fn follow_player(connection: &Connection, bus_name: &str, player: &mut PortPlayer) -> Result<(), Error> {
+    let proxy = MediaPlayer2PlayerProxy::builder(connection)
+        .destination(bus_name.to_string())?
+        .build()
+        .await?;
+    player.set_interface(Arc::new(MprisPlayerInterface { proxy: proxy.clone() }))?;
+    let mut status_changes = proxy.receive_playback_status_changed().await;
+    let mut metadata_changes = proxy.receive_metadata_changed().await;
+    let mut rate_changes = proxy.receive_rate_changed().await;
+    let mut volume_changes = proxy.receive_volume_changed().await;
+    let mut shuffle_changes = proxy.receive_shuffle_changed().await;
+    let mut loop_status_changes = proxy.receive_loop_status_changed().await;
+    let mut seeks = proxy.receive_seeked().await?;
+    loop {
+        player.update_partial(read_state(&proxy).await?).await?;
+        tokio::select! {
+            Some(_) = status_changes.next() => {}
+            Some(_) = metadata_changes.next() => {}
+            Some(_) = rate_changes.next() => {}
+            Some(_) = volume_changes.next() => {}
+            Some(_) = shuffle_changes.next() => {}
+            Some(_) = loop_status_changes.next() => {}
+            Some(_) = seeks.next() => {}
+            else => return Ok(()),
+        }
+    }
+}
+
+/// Registers the player owning `bus_name` and keeps its state up to date until the service is stopped.
+fn spawn_player(connection: Connection, driver: Arc<dyn FsctDriver>, self_id: String, bus_name: String)
+    -> ServiceHandle {
+    spawn_service(move |mut stop| async move {
+        let mut player = match PortPlayer::register(driver, self_id).await {
+            Ok(player) => player,
+            Err(e) => {
+                error!("Failed to register MPRIS player {}: {}", bus_name, e);
+                return;
+            }
+        };
+        info!("Following MPRIS player {}", bus_name);
+        let result = tokio::select! {
+            _ = stop.signaled() => Ok(()),
+            result = follow_player(&connection, &bus_name, &mut player) => result,
+        };
+        if let Err(e) = result {
+            warn!("Stopped following MPRIS player {}: {}", bus_name, e);
+        }
+        let _ = player.unregister().await;
+    })
+}
+
+async fn stop_player(bus_name: &str, handle: ServiceHandle) {
+    if let Err(e) = handle.shutdown().await {
+        warn!("Failed to stop following MPRIS player {}: {}", bus_name, e);
+    }
+}
+
+/// Watches all MPRIS2 players on the session bus, registering each `org.mpris.MediaPlayer2.*` bus as a separate
+/// player with the driver and unregistering it when the bus name goes away.
+pub async fn run_os_watcher(driver: Arc<dyn FsctDriver>) -> Result<ServiceHandle, Error> {
+    run_os_watcher_with_self_ids(driver, SelfIdNamespace::default()).await
+}
+
+/// Like [`run_os_watcher`], registering the players under the self ids of `self_ids`.
+pub async fn run_os_watcher_with_self_ids(driver: Arc<dyn FsctDriver>, self_ids: SelfIdNamespace)
+    -> Result<ServiceHandle, Error> {
+    let connection = Connection::session().await?;
+    let dbus = DBusProxy::new(&connection).await?;
+    // subscribe before listing so that no player appearing in between is missed
+    let mut owner_changes = dbus.receive_name_owner_changed().await?;
+    let names = dbus.list_names().await?;
+
+    Ok(spawn_service(move |mut stop| async move {
+        let mut players = HashMap::new();
+        for name in names.iter().map(|name| name.to_string()).filter(|name| name.starts_with(MPRIS_BUS_PREFIX)) {
+            let self_id = player_self_id(&self_ids, &name);
+            players.insert(name.clone(), spawn_player(connection.clone(), driver.clone(), self_id, name));
+        }
+        loop {
+            let signal = tokio::select! {
+                _ = stop.signaled() => break,
+                signal = owner_changes.next() => signal,
+            };
+            let Some(signal) = signal else {
+                error!("D-Bus name owner changes ended, MPRIS players are no longer tracked");
+                break;
+            };
+            let Ok(args) = signal.args() else { continue };
+            let name = args.name().to_string();
+            if !name.starts_with(MPRIS_BUS_PREFIX) {
+                continue;
+            }
+            // a new owner of a known name is a restarted player, followed anew
+            if let Some(handle) = players.remove(&name) {
+                debug!("MPRIS player {} went away", name);
+                stop_player(&name, handle).await;
+            }
+            if args.new_owner().is_some() {
+                let self_id = player_self_id(&self_ids, &name);
+                players.insert(name.clone(), spawn_player(connection.clone(), driver.clone(), self_id, name));
+            }
+        }
+        for (name, handle) in players {
+            stop_player(&name, handle).await;
+        }
+    }))
+}
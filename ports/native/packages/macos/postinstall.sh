@@ -2,6 +2,8 @@
 
 # Configuration
 LOG_FILE="/tmp/fsct_installer.log"
+AGENT_LABEL="com.hem-e.fsctdriverservice"
+AGENT_PLIST="/Library/LaunchAgents/${AGENT_LABEL}.plist"
 
 # Logging function
 log_message() {
@@ -12,9 +14,19 @@ log_message() {
 # Initialize log file
 log_message "Postinstall started"
 
-chmod 755 /usr/local/bin/fsctdriverservice
-chmod 644 /Library/LaunchDaemons/com.hem-e.fsctdriverservice.plist
+chmod 755 /usr/local/bin/fsct_driver_service
+chown root:wheel "$AGENT_PLIST"
+chmod 644 "$AGENT_PLIST"
 
-launchctl load -w /Library/LaunchDaemons/com.hem-e.fsctdriverservice.plist 2>> $LOG_FILE
+# Start the agent for the user logged in at the console; other users get it at their next login
+CONSOLE_USER=$(stat -f "%Su" /dev/console)
+if [ -n "$CONSOLE_USER" ] && [ "$CONSOLE_USER" != "root" ]; then
+    CONSOLE_UID=$(id -u "$CONSOLE_USER")
+    log_message "Loading agent for $CONSOLE_USER"
+    launchctl bootstrap "gui/$CONSOLE_UID" "$AGENT_PLIST" 2>> $LOG_FILE || \
+        launchctl kickstart -k "gui/$CONSOLE_UID/$AGENT_LABEL" 2>> $LOG_FILE || true
+else
+    log_message "No user logged in, agent will start at next login"
+fi
 
-log_message "Postinstall finished"
\ No newline at end of file
+log_message "Postinstall finished"
@@ -50,10 +50,21 @@ remove_service() {
     fi
 }
 
-# Stop and remove old daemon services if running
+# Function to unload the agent of the current version from all user sessions before it is replaced
+unload_agent() {
+    local agent_label="com.hem-e.fsctdriverservice"
+    for uid in $(ps -axo uid= -o comm= | awk '$2 ~ /loginwindow/ { print $1 }' | sort -u); do
+        log_message "Unloading agent for uid $uid"
+        launchctl bootout "gui/$uid/$agent_label" 2>> $LOG_FILE || true
+    done
+}
+
+# Stop and remove old daemon services if running (versions before the LaunchAgent)
 remove_service "fsct_service" "prerelease fsct service"
 remove_service "fsct_driver_service" "previous fsct driver service"
 
+unload_agent
+
 log_message "Preinstall finished"
 
 exit 0
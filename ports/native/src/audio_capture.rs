@@ -0,0 +1,28 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Audio capture backend of the platform, for devices with VU meter or spectrum displays.
+
+use fsct_core::audio_levels::AudioCapture;
+
+/// PipeWire monitor capture on Linux; Windows and macOS have no backend yet and capture nothing.
+pub fn platform_audio_capture() -> Box<dyn AudioCapture> {
+    #[cfg(target_os = "linux")]
+    return Box::new(fsct_port_linux::audio_levels::PipeWireCapture);
+    #[cfg(not(target_os = "linux"))]
+    Box::new(fsct_core::audio_levels::NullCapture)
+}
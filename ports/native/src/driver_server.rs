@@ -0,0 +1,118 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+use std::sync::Arc;
+use fsct_core::config::ConfigHandle;
+use fsct_core::auth::{create_token_file, default_token_path, generate_token, AuthPolicy};
+use fsct_core::ipc::ws::{run_ws_server, WsServer};
+use fsct_core::ipc::{default_ipc_path, run_ipc_server};
+use fsct_core::readiness::{run_health_server, run_readiness_notifier, DEFAULT_READINESS_CHECK_INTERVAL};
+use fsct_core::remote::{run_driver_bridge, run_driver_server, run_zeroconf_advertisement, Advertisement, DriverBridge,
+                        DriverServer, DEFAULT_DRIVER_SERVER_ADDRESS};
+use fsct_core::{LocalDriver, MultiServiceHandle, PlayerOrigin};
+use log::{info, warn};
+use tokio::net::TcpListener;
+
+/// Policy of the driver server when none is configured: callers must present the token written to
+/// [`default_token_path`], which `fsctctl` reads.
+fn local_token_policy() -> AuthPolicy {
+    let path = default_token_path();
+    match create_token_file(&path) {
+        Ok(token) => AuthPolicy::token(token),
+        Err(e) => {
+            warn!("Failed to write the driver server token to {}, rejecting every caller: {}", path.display(), e);
+            AuthPolicy::token(generate_token())
+        }
+    }
+}
+
+/// Serves the driver over gRPC on the address from the config file, for `fsctctl` and player ports running in other
+/// processes, and advertises it over mDNS if it is reachable from the LAN. Also serves JSON-RPC on the IPC socket
+/// for GUIs on the host and, if configured, events over WebSocket for web dashboards and the readiness over HTTP.
+/// Failures are logged and leave the service running without the server. Players are forwarded to the services of
+/// the configured bridges. The service manager is notified once the service is ready.
+pub(crate) async fn serve_driver(driver: Arc<LocalDriver>, config: &ConfigHandle, services: &mut MultiServiceHandle) {
+    let config = config.config();
+    let auth = config.driver_auth.clone().unwrap_or_else(local_token_policy);
+    for bridge in &config.bridges {
+        let mut forwarded = DriverBridge::new(bridge.target.clone())
+            .with_players(bridge.players.clone())
+            .with_origin(PlayerOrigin::new(bridge.host.clone(), None));
+        if let Some(token) = &bridge.token {
+            forwarded = forwarded.with_token(token.clone());
+        }
+        services.add(run_driver_bridge(driver.clone(), forwarded));
+    }
+
+    let ipc_path = config.ipc_socket.clone().unwrap_or_else(default_ipc_path);
+    match run_ipc_server(driver.clone(), &ipc_path) {
+        Ok(ipc) => {
+            driver.set_ipc_listening(true);
+            services.add(ipc);
+        }
+        Err(e) => {
+            driver.set_ipc_listening(false);
+            warn!("Failed to serve IPC on {}: {}", ipc_path.display(), e);
+        }
+    }
+    let mut lan_ws_port = None;
+    if let Some(address) = config.ws_server {
+        match TcpListener::bind(address).await {
+            Ok(listener) => {
+                if !address.ip().is_loopback() {
+                    lan_ws_port = Some(listener.local_addr().map(|local| local.port()).unwrap_or(address.port()));
+                }
+                let mut server = WsServer::new(driver.clone()).with_auth_policy(auth.clone());
+                if let Some(origins) = config.ws_allowed_origins.clone() {
+                    server = server.with_allowed_origins(origins);
+                }
+                services.add(run_ws_server(server, listener));
+            }
+            Err(e) => warn!("Failed to serve WebSocket events on {}: {}", address, e),
+        }
+    }
+    services.add(run_readiness_notifier(driver.clone(), DEFAULT_READINESS_CHECK_INTERVAL));
+    if let Some(address) = config.health_endpoint {
+        match TcpListener::bind(address).await {
+            Ok(listener) => services.add(run_health_server(driver.clone(), listener)),
+            Err(e) => warn!("Failed to serve the readiness on {}: {}", address, e),
+        }
+    }
+
+    let address = config.driver_server.unwrap_or(DEFAULT_DRIVER_SERVER_ADDRESS);
+    let listener = match TcpListener::bind(address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("Failed to serve the driver on {}: {}", address, e);
+            return;
+        }
+    };
+    let port = listener.local_addr().map(|local| local.port()).unwrap_or(address.port());
+    info!("Serving the driver on {}", address);
+    services.add(run_driver_server(DriverServer::new(driver).with_auth_policy(auth), listener));
+
+    if config.zeroconf.unwrap_or(!address.ip().is_loopback()) {
+        let mut advertisement = Advertisement::new(port);
+        if let Some(ws_port) = lan_ws_port {
+            advertisement = advertisement.with_ws_port(ws_port);
+        }
+        match run_zeroconf_advertisement(advertisement) {
+            Ok(advertisement) => services.add(advertisement),
+            Err(e) => warn!("Failed to advertise the driver server: {}", e),
+        }
+    }
+}
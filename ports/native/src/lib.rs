@@ -15,6 +15,11 @@
 // This file is part of an implementation of Ferrum Streaming Control Technology™,
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
+mod driver_server;
+mod local_driver;
+#[cfg(feature = "audio-levels")]
+mod audio_capture;
+
 #[cfg(target_os = "windows")]
 pub mod windows;
 
@@ -27,5 +32,11 @@ pub mod macos;
 #[cfg(target_os = "macos")]
 use macos::*;
 
+#[cfg(target_os = "linux")]
+pub mod linux;
+
+#[cfg(target_os = "linux")]
+use linux::*;
+
 pub use service::fsct_main;
-pub use player::run_os_watcher;
\ No newline at end of file
+pub use player::run_os_watcher;
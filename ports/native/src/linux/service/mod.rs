@@ -0,0 +1,89 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+
+use anyhow::anyhow;
+use fsct_core::{FsctDriver, InterceptedDriver};
+use fsct_core::config::{default_config_path, run_config_service, ConfigHandle, HostConfig};
+use fsct_core::power::{run_power_monitor, EnergyConfig, PowerMonitor, SysfsPowerSource, DEFAULT_POWER_CHECK_INTERVAL};
+use fsct_core::timeline_smoothing::TimelineSmoother;
+use std::sync::Arc;
+use crate::driver_server::serve_driver;
+use crate::local_driver::{local_driver, run_usage_stats_persistence};
+use tokio::signal::unix::{signal, SignalKind};
+use fsct_port_linux::logging::init_logger_with_level;
+use log::{warn, LevelFilter};
+use crate::linux::player::{run_os_watcher_with_self_ids, MPRIS_PORT};
+
+#[tokio::main(flavor = "current_thread")]
+pub async fn fsct_main() -> anyhow::Result<()> {
+    let config_path = default_config_path();
+    let log_level = HostConfig::load(&config_path).ok().and_then(|config| config.log_level);
+    init_logger_with_level(log_level.unwrap_or(LevelFilter::Info));
+
+    // Apply the config file before devices get attached; it is reloaded on SIGHUP
+    let power = Arc::new(PowerMonitor::new(Box::new(SysfsPowerSource::new()), EnergyConfig::default()));
+    let driver = Arc::new(local_driver().with_power_monitor(power.clone()));
+    let config = Arc::new(ConfigHandle::new(config_path, driver.clone()));
+    if let Err(e) = config.reload().await {
+        warn!("{}, running with defaults", e);
+    }
+    let self_ids = config.config().self_ids.resolved();
+
+    // Run background services (orchestrator + USB watch)
+    let mut handle = driver.run().await.map_err(|e| anyhow!(e))?;
+    serve_driver(driver.clone(), &config, &mut handle).await;
+    handle.add(run_config_service(config));
+    if let Some(persistence) = run_usage_stats_persistence(&driver) {
+        handle.add(persistence);
+    }
+
+    // Stream levels (and the spectrum) of what plays to devices with VU meter or spectrum displays
+    #[cfg(feature = "audio-levels")]
+    handle.add(fsct_core::audio_levels::run_audio_analysis(driver.device_manager(),
+                                                           &*crate::audio_capture::platform_audio_capture(),
+                                                           Default::default()));
+
+    // Throttle timeline corrections while on battery
+    handle.add(run_power_monitor(power.clone(), DEFAULT_POWER_CHECK_INTERVAL));
+
+    // Start MPRIS watcher, registering every media player on the session bus via the driver
+    let player_driver: Arc<dyn FsctDriver> = Arc::new(InterceptedDriver::new(driver.clone())
+        .with_interceptor(Arc::new(TimelineSmoother::default()))
+        .with_interceptor(power));
+    let watcher = driver.run_port(MPRIS_PORT, move || {
+        run_os_watcher_with_self_ids(player_driver.clone(), self_ids.clone())
+    }).await?;
+
+    handle.add(watcher);
+
+    // stopped from a terminal or by the user's service manager
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        res = tokio::signal::ctrl_c() => res.expect("Failed to listen for Ctrl+C signal"),
+        _ = terminate.recv() => {}
+    }
+    println!("Stopping service.");
+
+    let res = handle.shutdown().await;
+    if let Err(e) = res {
+        println!("Error while stopping service: {}", e);
+        return Err(e.into());
+    }
+    println!("Exit.");
+    Ok(())
+}
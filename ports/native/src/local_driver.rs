@@ -0,0 +1,68 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! The driver the native services run.
+
+use std::sync::Arc;
+use fsct_core::announcements::{AnnouncementConfig, Announcer, CommandSpeech};
+#[cfg(feature = "aux-content")]
+use fsct_core::aux_content::AuxContentRegistry;
+#[cfg(feature = "usage-stats")]
+use fsct_core::config::default_config_path;
+#[cfg(feature = "usage-stats")]
+use fsct_core::storage::{JsonFileStorage, Storage};
+#[cfg(feature = "usage-stats")]
+use fsct_core::usage_stats::{run_usage_stats_flush, UsageReport, UsageStats, DEFAULT_USAGE_FLUSH_INTERVAL};
+use fsct_core::{LocalDriver, ServiceHandle};
+#[cfg(feature = "usage-stats")]
+use log::warn;
+
+/// A driver with the built-in auxiliary content providers, for the idle displays of devices with an
+/// `aux_rotation` in the config file, usage statistics, recorded once `usage_stats` opts in, and an announcer
+/// speaking through the text-to-speech of the OS once `announcements` are configured.
+pub(crate) fn local_driver() -> LocalDriver {
+    let announcer = Announcer::new(AnnouncementConfig::silent(), Arc::new(CommandSpeech::system()));
+    let driver = LocalDriver::with_new_managers().with_announcer(Arc::new(announcer));
+    #[cfg(feature = "aux-content")]
+    let driver = driver.with_aux_content(Arc::new(AuxContentRegistry::builtin()));
+    #[cfg(feature = "usage-stats")]
+    let driver = {
+        let stats = UsageStats::open(state_storage().as_ref(), false).unwrap_or_else(|e| {
+            warn!("Failed to read usage statistics, starting over: {}", e);
+            UsageStats::new(UsageReport::new(), false)
+        });
+        driver.with_usage_stats(Arc::new(stats))
+    };
+    driver
+}
+
+/// Saves the usage statistics of the driver to `state` beside the config file while they are recorded.
+pub(crate) fn run_usage_stats_persistence(driver: &LocalDriver) -> Option<ServiceHandle> {
+    #[cfg(feature = "usage-stats")]
+    if let Some(stats) = driver.usage_stats() {
+        return Some(run_usage_stats_flush(stats, state_storage(), DEFAULT_USAGE_FLUSH_INTERVAL));
+    }
+    #[cfg(not(feature = "usage-stats"))]
+    let _ = driver;
+    None
+}
+
+/// State the services keep across restarts.
+#[cfg(feature = "usage-stats")]
+fn state_storage() -> Arc<dyn Storage> {
+    Arc::new(JsonFileStorage::new(default_config_path().with_file_name("state")))
+}
@@ -17,3 +17,4 @@
 
 pub mod service;
 pub mod player;
+pub mod power;
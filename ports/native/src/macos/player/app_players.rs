@@ -0,0 +1,154 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Players of the apps reported as now playing, one per app bundle.
+
+use std::collections::{HashMap, HashSet};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Error};
+use fsct_core::definitions::{FsctStatus, TimelineInfo};
+use fsct_core::player_state::PlayerState;
+use fsct_core::self_id::SelfIdNamespace;
+use fsct_core::FsctDriver;
+use fsct_port_sdk::PortPlayer;
+use log::{debug, error, info};
+use media_remote::NowPlayingInfo;
+
+/// Stands in for the bundle id of now playing information that doesn't name its app.
+const UNKNOWN_APP: &str = "unknown";
+
+/// Id under which the now playing app is registered, e.g. `native-macos-nowplaying-com.spotify.client`.
+fn player_self_id(self_ids: &SelfIdNamespace, bundle_id: &str) -> String {
+    self_ids.self_id(&format!("macos-nowplaying-{}", bundle_id))
+}
+
+/// The timeline stopped at the position reached at `now`.
+fn paused_at(timeline: &TimelineInfo, now: SystemTime) -> TimelineInfo {
+    let elapsed = now.duration_since(timeline.update_time).unwrap_or_default();
+    let position = timeline.position + elapsed.mul_f64(timeline.rate.max(0.0));
+    TimelineInfo {
+        position: timeline.duration.map_or(position, |duration| position.min(duration)),
+        update_time: now,
+        duration: timeline.duration,
+        rate: 0.0,
+    }
+}
+
+/// Bundle ids of the running apps, read from `lsappinfo list`.
+pub(super) fn running_bundle_ids() -> Result<HashSet<String>, Error> {
+    let output = Command::new("lsappinfo").arg("list").output()?;
+    if !output.status.success() {
+        return Err(anyhow!("lsappinfo failed with {}", output.status));
+    }
+    Ok(parse_bundle_ids(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Collects the `bundleID="com.spotify.client"` lines of `lsappinfo list`.
+fn parse_bundle_ids(output: &str) -> HashSet<String> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("bundleID=\"")?.strip_suffix('"'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Registers a player for every app MediaRemote reports as now playing, and unregisters it once the app quits.
+///
+/// MediaRemote only reports the app playing last, so an app that stops being the now playing one is shown as
+/// paused if it was playing; it most likely paused, or lost the audio to the app that took over.
+pub(super) struct AppPlayers {
+    driver: Arc<dyn FsctDriver>,
+    self_ids: SelfIdNamespace,
+    players: HashMap<String, PortPlayer>,
+    now_playing_app: Option<String>,
+}
+
+impl AppPlayers {
+    pub(super) fn new(driver: Arc<dyn FsctDriver>, self_ids: SelfIdNamespace) -> Self {
+        Self { driver, self_ids, players: HashMap::new(), now_playing_app: None }
+    }
+
+    /// Passes the state to the player of the app the information comes from, registering it if the app is new.
+    pub(super) async fn push(&mut self, info: &NowPlayingInfo, state: PlayerState) {
+        let bundle_id = info.bundle_id.clone().unwrap_or_else(|| UNKNOWN_APP.to_string());
+        if self.now_playing_app.as_ref() != Some(&bundle_id)
+            && let Some(previous) = self.now_playing_app.take().and_then(|app| self.players.get_mut(&app))
+            && previous.state().status == FsctStatus::Playing
+        {
+            let paused = previous.state().timeline.as_ref().map(|timeline| paused_at(timeline, SystemTime::now()));
+            let _ = previous.set_status(FsctStatus::Paused).await;
+            let _ = previous.set_timeline(paused).await;
+        }
+        if !self.players.contains_key(&bundle_id) {
+            match PortPlayer::register(self.driver.clone(), player_self_id(&self.self_ids, &bundle_id)).await {
+                Ok(player) => {
+                    info!("[MacOSPlayer] Following now playing app {}", bundle_id);
+                    self.players.insert(bundle_id.clone(), player);
+                }
+                Err(e) => {
+                    error!("[MacOSPlayer] Failed to register player of {}: {}", bundle_id, e);
+                    return;
+                }
+            }
+        }
+        if let Some(player) = self.players.get_mut(&bundle_id) {
+            let _ = player.update(state).await;
+        }
+        self.now_playing_app = Some(bundle_id);
+    }
+
+    /// Unregisters the players of apps that are no longer running.
+    pub(super) async fn remove_quit_apps(&mut self, running: &HashSet<String>) {
+        let quit: Vec<String> = self.players.keys()
+            .filter(|bundle_id| *bundle_id != UNKNOWN_APP && !running.contains(*bundle_id))
+            .cloned()
+            .collect();
+        for bundle_id in quit {
+            debug!("[MacOSPlayer] Now playing app {} quit", bundle_id);
+            if self.now_playing_app.as_ref() == Some(&bundle_id) {
+                self.now_playing_app = None;
+            }
+            if let Some(player) = self.players.remove(&bundle_id) {
+                let _ = player.unregister().await;
+            }
+        }
+    }
+
+    pub(super) async fn unregister_all(&mut self) {
+        self.now_playing_app = None;
+        for (_, player) in self.players.drain() {
+            let _ = player.unregister().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundle_ids_are_parsed_from_lsappinfo() {
+        let output = " 1) \"Finder\" ASN:0x0-0x1001-\"Finder\":\n    bundleID=\"com.apple.finder\"\n    \
+                      bundle path=\"/System/Library/CoreServices/Finder.app\"\n \
+                      2) \"Spotify\" ASN:0x0-0x5005-\"Spotify\":\n    bundleID=\"com.spotify.client\"\n";
+        let expected: HashSet<String> = ["com.apple.finder", "com.spotify.client"].map(String::from).into();
+        assert_eq!(parse_bundle_ids(output), expected);
+    }
+}
@@ -17,16 +17,39 @@
 
 use fsct_core::definitions::{FsctStatus, TimelineInfo};
 use fsct_core::player_state::{PlayerState, TrackMetadata};
-use fsct_core::{FsctDriver, ManagedPlayerId};
-use fsct_core::service::{ServiceHandle, spawn_service};
+use fsct_core::FsctDriver;
+use fsct_core::polling::{PollingConfig, PollingSettings};
+use fsct_core::self_id::SelfIdNamespace;
+use fsct_core::service::{ServiceHandle, StopHandle, spawn_service};
 use media_remote::{NowPlaying, NowPlayingInfo, NowPlayingJXA, Subscription};
 use std::process::Command;
 use std::sync::Mutex;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
-use anyhow::anyhow;
+use log::{debug, error, info, warn};
 use tokio::sync::mpsc;
 
+mod app_players;
+mod notifications;
+mod permissions;
+
+use app_players::{running_bundle_ids, AppPlayers};
+use notifications::NowPlayingNotifications;
+
+pub use permissions::{probe_media_remote_access, show_permission_dialog, PermissionEvent, PermissionIssue};
+
+const PERMISSION_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+/// Interval of checking whether the apps with registered players are still running.
+const APP_QUIT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Name under which the watcher runs in the driver, see `LocalDriver::restart_port`.
+pub const NOW_PLAYING_PORT: &str = "native-macos-nowplaying";
+/// Name under which the JXA polling settings are registered.
+pub const JXA_POLLING_PORT: &str = "macos-jxa";
+/// Default JXA polling; the jitter is drawn once per (re)start of the poller.
+pub const DEFAULT_JXA_POLLING: PollingConfig =
+    PollingConfig::new(Duration::from_millis(500), Duration::from_millis(100));
+
 #[allow(dead_code)]
 struct NowPlayingWrapper {
     now_playing: NowPlaying,
@@ -46,7 +69,11 @@ fn get_current_track(now_playing_info: &NowPlayingInfo) -> TrackMetadata {
 }
 
 fn get_timeline_info(now_playing_info: &NowPlayingInfo) -> Option<TimelineInfo> {
-    let duration = now_playing_info.duration?;
+    // live streams have an elapsed time but no duration
+    if now_playing_info.duration.is_none() && now_playing_info.elapsed_time.is_none() {
+        return None;
+    }
+    let duration = now_playing_info.duration.filter(|duration| *duration > 0.0);
     let position = now_playing_info.elapsed_time.unwrap_or(0.0);
     let update_time = now_playing_info.info_update_time.unwrap_or(SystemTime::now());
     let is_playing = now_playing_info.is_playing.unwrap_or(false);
@@ -59,7 +86,7 @@ fn get_timeline_info(now_playing_info: &NowPlayingInfo) -> Option<TimelineInfo>
     Some(TimelineInfo {
         position: Duration::from_secs_f64(position),
         update_time,
-        duration: Duration::from_secs_f64(duration),
+        duration: duration.map(Duration::from_secs_f64),
         rate: rate as f64,
     })
 }
@@ -77,16 +104,22 @@ fn build_state(info: &NowPlayingInfo) -> PlayerState {
         status: get_status(info),
         texts: get_current_track(info),
         timeline: get_timeline_info(info),
+        volume: None,
+        modes: Default::default(),
     }
 }
 
-async fn push_state(driver: Arc<dyn FsctDriver>, player_id: ManagedPlayerId, previous_state: &mut PlayerState, info: Option<NowPlayingInfo>) {
+async fn push_state(players: &mut AppPlayers, info: Option<NowPlayingInfo>) {
     if let Some(info) = info {
-        let state = build_state(&info);
-        if *previous_state != state {
-            *previous_state = state.clone();
-            let _ = driver.update_player_state(player_id, state).await;
-        }
+        players.push(&info, build_state(&info)).await;
+    }
+}
+
+async fn remove_quit_apps(players: &mut AppPlayers) {
+    match tokio::task::spawn_blocking(running_bundle_ids).await {
+        Ok(Ok(running)) => players.remove_quit_apps(&running).await,
+        Ok(Err(e)) => warn!("[MacOSPlayer] Can't list running apps: {}", e),
+        Err(e) => warn!("[MacOSPlayer] Can't list running apps: {}", e),
     }
 }
 
@@ -111,54 +144,161 @@ enum NowPlayingImpl {
     Native(NowPlayingWrapper),
 }
 
+fn start_jxa(tx: &mpsc::UnboundedSender<Option<NowPlayingInfo>>, polling: &PollingConfig) -> NowPlayingImpl {
+    let now_playing = NowPlayingJXA::new(polling.next_delay());
+    let tx_clone = tx.clone();
+    now_playing.subscribe(move |guard| {
+        let _ = tx_clone.send(guard.as_ref().cloned());
+    });
+    // push initial state via the same queue
+    let initial = now_playing.get_info().as_ref().cloned();
+    let _ = tx.send(initial);
+
+    NowPlayingImpl::JXA(now_playing)
+}
+
+fn start_native(now_playing: NowPlaying, tx: &mpsc::UnboundedSender<Option<NowPlayingInfo>>) -> NowPlayingImpl {
+    let tx_clone = tx.clone();
+    now_playing.subscribe(move |guard| {
+        let _ = tx_clone.send(guard.as_ref().cloned());
+    });
+    // push initial state via the same queue
+    let initial = now_playing.get_info().as_ref().cloned();
+    let _ = tx.send(initial);
+
+    NowPlayingImpl::Native(NowPlayingWrapper { now_playing })
+}
+
+/// Checks whether native MediaRemote actually serves now playing information to this process.
+///
+/// Called after a native notification arrived, so an empty answer means the information is withheld
+/// (as for non-entitled processes on macOS 15.4+) rather than that nothing is playing.
+fn try_native() -> Option<NowPlaying> {
+    let now_playing = NowPlaying::new();
+    let available = now_playing.get_info().is_some();
+    available.then_some(now_playing)
+}
+
+fn report_permission_event(events: &Option<mpsc::UnboundedSender<PermissionEvent>>, event: PermissionEvent) {
+    match &event {
+        PermissionEvent::Denied(issue) => error!("[MacOSPlayer] Can't access now playing information: {}. {}", issue, issue.guidance()),
+        PermissionEvent::Granted => info!("[MacOSPlayer] Access to now playing information granted"),
+    }
+    if let Some(events) = events {
+        let _ = events.send(event);
+    }
+}
+
+/// Waits until MediaRemote is accessible, probing again every [`PERMISSION_RETRY_INTERVAL`].
+///
+/// Returns `false` if the stop signal arrived first.
+async fn wait_for_media_remote_access(stop: &mut StopHandle,
+                                      events: &Option<mpsc::UnboundedSender<PermissionEvent>>) -> bool {
+    let mut reported_issue: Option<PermissionIssue> = None;
+    loop {
+        let probe = tokio::task::spawn_blocking(probe_media_remote_access).await
+            .unwrap_or_else(|e| Err(PermissionIssue::MediaRemoteUnavailable(e.to_string())));
+        match probe {
+            Ok(()) => {
+                if reported_issue.is_some() {
+                    report_permission_event(events, PermissionEvent::Granted);
+                }
+                return true;
+            }
+            Err(issue) => {
+                if reported_issue.as_ref() != Some(&issue) {
+                    report_permission_event(events, PermissionEvent::Denied(issue.clone()));
+                    reported_issue = Some(issue);
+                }
+            }
+        }
+        tokio::select! {
+            _ = stop.signaled() => return false,
+            _ = tokio::time::sleep(PERMISSION_RETRY_INTERVAL) => {
+                debug!("[MacOSPlayer] Retrying MediaRemote access check");
+            }
+        }
+    }
+}
+
 pub async fn run_os_watcher(driver: Arc<dyn FsctDriver>) -> anyhow::Result<ServiceHandle> {
-    // Register a single native macOS player (for the OS global now playing)
-    let player_id = driver
-        .register_player("native-macos-nowplaying".to_string())
-        .await
-        .map_err(|e| anyhow!(e))?;
+    run_os_watcher_with_permission_events(driver, None).await
+}
 
-    // Spawn a single service task that consumes the queue and updates state
+/// Runs the now playing watcher, reporting permission problems (and their resolution) to `permission_events`.
+pub async fn run_os_watcher_with_permission_events(driver: Arc<dyn FsctDriver>,
+                                                   permission_events: Option<mpsc::UnboundedSender<PermissionEvent>>)
+    -> anyhow::Result<ServiceHandle> {
+    run_os_watcher_with_options(driver, permission_events, PollingSettings::new(DEFAULT_JXA_POLLING),
+                                SelfIdNamespace::default()).await
+}
+
+/// Runs the now playing watcher with runtime adjustable JXA polling; changes restart the JXA poller. Every app
+/// reported as now playing is registered as a separate player under the self ids of `self_ids`, until it quits.
+pub async fn run_os_watcher_with_options(driver: Arc<dyn FsctDriver>,
+                                         permission_events: Option<mpsc::UnboundedSender<PermissionEvent>>,
+                                         polling: PollingSettings, self_ids: SelfIdNamespace)
+    -> anyhow::Result<ServiceHandle> {
+    // Spawn a single service task that consumes the queue and updates the players of the now playing apps
     let handle = spawn_service(move |mut stop| async move {
+        let mut players = AppPlayers::new(driver, self_ids);
+
         // Channel to move updates from callback context to our service task
         let (tx, mut rx) = mpsc::unbounded_channel::<Option<NowPlayingInfo>>();
 
+        // Channel for native MediaRemote change notifications
+        let (notification_tx, mut notification_rx) = mpsc::unbounded_channel::<()>();
+
         // Choose implementation based on macOS version and set up subscriptions
-        let _now_playing: NowPlayingImpl = if let Some((major, minor)) = get_macos_version() && (major > 15 || (major == 15 && minor >= 4)) {
-                let now_playing = NowPlayingJXA::new(Duration::from_millis(500));
-                let tx_clone = tx.clone();
-                now_playing.subscribe(move |guard| {
-                    let _ = tx_clone.send(guard.as_ref().cloned());
-                });
-                // push initial state via the same queue
-                let initial = now_playing.get_info().as_ref().cloned();
-                let _ = tx.send(initial);
-
-                NowPlayingImpl::JXA(now_playing)
+        let mut now_playing: NowPlayingImpl = if let Some((major, minor)) = get_macos_version() && (major > 15 || (major == 15 && minor >= 4)) {
+            if !wait_for_media_remote_access(&mut stop, &permission_events).await {
+                return;
+            }
+            start_jxa(&tx, &polling.get())
         } else {
-            // Fallback to native implementation
-            let now_playing = NowPlaying::new();
-            let tx_clone = tx.clone();
-            now_playing.subscribe(move |guard| {
-                let _ = tx_clone.send(guard.as_ref().cloned());
-            });
-            // push initial state via the same queue
-            let initial = now_playing.get_info().as_ref().cloned();
-            let _ = tx.send(initial);
-
-            NowPlayingImpl::Native(NowPlayingWrapper { now_playing })
+            start_native(NowPlaying::new(), &tx)
+        };
+
+        // While polling JXA, listen for native notifications; once they are delivered and native MediaRemote
+        // serves the now playing information, the polling is replaced with native callbacks.
+        let mut notifications = match now_playing {
+            NowPlayingImpl::JXA(_) => NowPlayingNotifications::register(notification_tx)
+                .inspect_err(|e| debug!("[MacOSPlayer] Native now playing notifications unavailable: {}", e))
+                .ok(),
+            NowPlayingImpl::Native(_) => None,
         };
 
-        let mut previous_state = PlayerState::default();
+        let mut polling_rx = polling.subscribe();
+        let mut quit_check = tokio::time::interval(APP_QUIT_CHECK_INTERVAL);
         loop {
             tokio::select! {
                 _ = stop.signaled() => {
                     break;
                 }
+                Ok(()) = polling_rx.changed(), if matches!(now_playing, NowPlayingImpl::JXA(_)) => {
+                    let config = *polling_rx.borrow_and_update();
+                    info!("[MacOSPlayer] Restarting JXA polling every {:?} (jitter {:?})", config.interval, config.jitter);
+                    now_playing = start_jxa(&tx, &config);
+                }
+                Some(()) = notification_rx.recv(), if notifications.is_some() => {
+                    // asking MediaRemote blocks until it answers
+                    let native = tokio::task::spawn_blocking(try_native).await.ok().flatten();
+                    if let Some(native) = native {
+                        info!("[MacOSPlayer] Native now playing notifications available, stopping JXA polling");
+                        now_playing = start_native(native, &tx);
+                        // the native callbacks rely on the registration from now on
+                        if let Some(notifications) = notifications.take() {
+                            notifications.hand_over();
+                        }
+                    }
+                }
+                _ = quit_check.tick() => {
+                    remove_quit_apps(&mut players).await;
+                }
                 maybe = rx.recv() => {
                     match maybe {
                         Some(opt) => {
-                            push_state(driver.clone(), player_id, &mut previous_state, opt).await;
+                            push_state(&mut players, opt).await;
                         }
                         None => {
                             // Sender dropped; exit loop
@@ -168,6 +308,7 @@ pub async fn run_os_watcher(driver: Arc<dyn FsctDriver>) -> anyhow::Result<Servi
                 }
             }
         }
+        players.unregister_all().await;
     });
 
     Ok(handle)
@@ -0,0 +1,146 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Native MediaRemote change notifications.
+//!
+//! `MRMediaRemoteRegisterForNowPlayingNotifications` makes MediaRemote post its now playing notifications to the
+//! process-local notification center, which is observed here with plain CoreFoundation calls.
+
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::sync::Mutex;
+use anyhow::anyhow;
+use tokio::sync::mpsc;
+
+type CFNotificationCenterRef = *const c_void;
+type CFStringRef = *const c_void;
+type CFNotificationCallback = extern "C" fn(CFNotificationCenterRef, *const c_void, CFStringRef, *const c_void, *const c_void);
+
+const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+const CF_NOTIFICATION_SUSPENSION_BEHAVIOR_DELIVER_IMMEDIATELY: isize = 4;
+const RTLD_LAZY: c_int = 0x1;
+
+const MEDIA_REMOTE_PATH: &CStr = c"/System/Library/PrivateFrameworks/MediaRemote.framework/MediaRemote";
+const REGISTER_SYMBOL: &CStr = c"MRMediaRemoteRegisterForNowPlayingNotifications";
+const UNREGISTER_SYMBOL: &CStr = c"MRMediaRemoteUnregisterForNowPlayingNotifications";
+
+const NOTIFICATION_NAMES: [&CStr; 3] = [
+    c"kMRMediaRemoteNowPlayingInfoDidChangeNotification",
+    c"kMRMediaRemoteNowPlayingApplicationIsPlayingDidChangeNotification",
+    c"kMRMediaRemoteNowPlayingApplicationDidChangeNotification",
+];
+
+#[link(name = "CoreFoundation", kind = "framework")]
+unsafe extern "C" {
+    fn CFNotificationCenterGetLocalCenter() -> CFNotificationCenterRef;
+    fn CFNotificationCenterAddObserver(center: CFNotificationCenterRef, observer: *const c_void,
+                                       callback: CFNotificationCallback, name: CFStringRef, object: *const c_void,
+                                       suspension_behavior: isize);
+    fn CFNotificationCenterRemoveEveryObserver(center: CFNotificationCenterRef, observer: *const c_void);
+    fn CFStringCreateWithCString(allocator: *const c_void, c_str: *const c_char, encoding: u32) -> CFStringRef;
+    fn CFRelease(cf: *const c_void);
+}
+
+unsafe extern "C" {
+    fn dlopen(path: *const c_char, mode: c_int) -> *mut c_void;
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    fn dispatch_get_global_queue(identifier: isize, flags: usize) -> *mut c_void;
+}
+
+/// Registrations of the process with MediaRemote made here.
+struct Registrations {
+    /// Still owned, i.e. not handed over; the last one dropped unregisters the process.
+    owned: usize,
+    /// Whether one was handed over, after which the process stays registered.
+    handed_over: bool,
+}
+
+static REGISTRATIONS: Mutex<Registrations> = Mutex::new(Registrations { owned: 0, handed_over: false });
+
+extern "C" fn on_notification(_center: CFNotificationCenterRef, observer: *const c_void, _name: CFStringRef,
+                              _object: *const c_void, _user_info: *const c_void) {
+    // SAFETY: observer is the sender boxed by NowPlayingNotifications, which outlives its registration
+    let sender = unsafe { &*(observer as *const mpsc::UnboundedSender<()>) };
+    let _ = sender.send(());
+}
+
+/// Registration for native now playing change notifications.
+///
+/// Every notification is forwarded as `()` to the channel given at registration. The registration of the process
+/// with MediaRemote is removed when the last value still owning it is dropped, unless it was handed over.
+pub(super) struct NowPlayingNotifications {
+    observer: *mut mpsc::UnboundedSender<()>,
+    unregister: unsafe extern "C" fn(),
+    owned: bool,
+}
+
+unsafe impl Send for NowPlayingNotifications {}
+
+impl NowPlayingNotifications {
+    pub(super) fn register(sender: mpsc::UnboundedSender<()>) -> anyhow::Result<Self> {
+        unsafe {
+            let media_remote = dlopen(MEDIA_REMOTE_PATH.as_ptr(), RTLD_LAZY);
+            if media_remote.is_null() {
+                return Err(anyhow!("MediaRemote.framework could not be loaded"));
+            }
+            let register = dlsym(media_remote, REGISTER_SYMBOL.as_ptr());
+            let unregister = dlsym(media_remote, UNREGISTER_SYMBOL.as_ptr());
+            if register.is_null() || unregister.is_null() {
+                return Err(anyhow!("MediaRemote notification registration is not available"));
+            }
+            let register: unsafe extern "C" fn(*mut c_void) = std::mem::transmute(register);
+            let unregister: unsafe extern "C" fn() = std::mem::transmute(unregister);
+
+            let observer = Box::into_raw(Box::new(sender));
+            let center = CFNotificationCenterGetLocalCenter();
+            for name in NOTIFICATION_NAMES {
+                let cf_name = CFStringCreateWithCString(std::ptr::null(), name.as_ptr(), CF_STRING_ENCODING_UTF8);
+                CFNotificationCenterAddObserver(center, observer as *const c_void, on_notification, cf_name,
+                                                std::ptr::null(), CF_NOTIFICATION_SUSPENSION_BEHAVIOR_DELIVER_IMMEDIATELY);
+                CFRelease(cf_name);
+            }
+            register(dispatch_get_global_queue(0, 0));
+            REGISTRATIONS.lock().unwrap().owned += 1;
+            Ok(Self { observer, unregister, owned: true })
+        }
+    }
+
+    /// Stops forwarding notifications but leaves the process registered with MediaRemote, for native now playing
+    /// callbacks relying on the registration.
+    pub(super) fn hand_over(mut self) {
+        let mut registrations = REGISTRATIONS.lock().unwrap();
+        registrations.owned -= 1;
+        registrations.handed_over = true;
+        self.owned = false;
+    }
+}
+
+impl Drop for NowPlayingNotifications {
+    fn drop(&mut self) {
+        if self.owned {
+            let mut registrations = REGISTRATIONS.lock().unwrap();
+            registrations.owned -= 1;
+            if registrations.owned == 0 && !registrations.handed_over {
+                // SAFETY: resolved from MediaRemote at registration, which stays loaded
+                unsafe { (self.unregister)() };
+            }
+        }
+        unsafe {
+            CFNotificationCenterRemoveEveryObserver(CFNotificationCenterGetLocalCenter(), self.observer as *const c_void);
+            drop(Box::from_raw(self.observer));
+        }
+    }
+}
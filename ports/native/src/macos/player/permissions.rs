@@ -0,0 +1,132 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+use std::fmt::{Display, Formatter};
+use std::process::Command;
+
+const AUTOMATION_SETTINGS_URL: &str = "x-apple.systempreferences:com.apple.preference.security?Privacy_Automation";
+
+// Loads MediaRemote the same way the JXA now playing implementation does, so TCC rejects it the same way.
+const MEDIA_REMOTE_PROBE_SCRIPT: &str = r#"
+ObjC.import('Foundation');
+const bundle = $.NSBundle.bundleWithPath('/System/Library/PrivateFrameworks/MediaRemote.framework/');
+if (!bundle.load) { throw new Error('MediaRemote.framework could not be loaded'); }
+'ok';
+"#;
+
+/// Reason why now playing information can't be read from MediaRemote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermissionIssue {
+    /// The user (or an MDM profile) denied automation access for the service.
+    AutomationDenied,
+    /// MediaRemote could not be loaded; the message is the raw error reported by the probe.
+    MediaRemoteUnavailable(String),
+}
+
+impl PermissionIssue {
+    /// Human readable instructions how to resolve the issue.
+    pub fn guidance(&self) -> &'static str {
+        match self {
+            PermissionIssue::AutomationDenied => {
+                "Open System Settings > Privacy & Security > Automation and allow FSCT Driver Service there; \
+                the service retries automatically and picks up now playing information once access is granted."
+            }
+            PermissionIssue::MediaRemoteUnavailable(_) => {
+                "MediaRemote is not accessible on this system. Make sure the service is installed from the signed \
+                package and restart it; if the problem persists please report it together with the service log."
+            }
+        }
+    }
+}
+
+impl Display for PermissionIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PermissionIssue::AutomationDenied => write!(f, "automation access denied"),
+            PermissionIssue::MediaRemoteUnavailable(message) => write!(f, "MediaRemote unavailable: {}", message),
+        }
+    }
+}
+
+/// Permission state changes reported by the macOS watcher.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermissionEvent {
+    /// Now playing information can't be read; the watcher keeps retrying in the background.
+    Denied(PermissionIssue),
+    /// Access was (re)gained and the watcher is subscribed to now playing updates.
+    Granted,
+}
+
+/// Checks whether MediaRemote can be accessed from this process by running a minimal JXA script.
+pub fn probe_media_remote_access() -> Result<(), PermissionIssue> {
+    let output = Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", MEDIA_REMOTE_PROBE_SCRIPT])
+        .output()
+        .map_err(|e| PermissionIssue::MediaRemoteUnavailable(e.to_string()))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(classify_probe_failure(&String::from_utf8_lossy(&output.stderr)))
+}
+
+fn classify_probe_failure(stderr: &str) -> PermissionIssue {
+    // -1743 is errAEEventNotPermitted, -1744 is errAEEventWouldRequireUserConsent
+    const DENIAL_MARKERS: [&str; 4] = ["-1743", "-1744", "not authorized", "not permitted"];
+    let lowercase = stderr.to_lowercase();
+    if DENIAL_MARKERS.iter().any(|marker| lowercase.contains(marker)) {
+        PermissionIssue::AutomationDenied
+    } else {
+        PermissionIssue::MediaRemoteUnavailable(stderr.trim().to_string())
+    }
+}
+
+/// Shows a modal dialog explaining the issue, offering to open the relevant System Settings pane.
+///
+/// Meant for interactive frontends (e.g. a menubar app); the background service only logs the issue.
+pub fn show_permission_dialog(issue: &PermissionIssue) -> anyhow::Result<()> {
+    let message = format!("FSCT can't read now playing information ({}).\n\n{}", issue, issue.guidance());
+    let script = format!(
+        "button returned of (display dialog {:?} with title \"FSCT\" buttons {{\"Later\", \"Open Settings\"}} \
+        default button \"Open Settings\" with icon caution)",
+        message
+    );
+    let output = Command::new("osascript").args(["-e", &script]).output()?;
+    if String::from_utf8_lossy(&output.stdout).trim() == "Open Settings" {
+        Command::new("open").arg(AUTOMATION_SETTINGS_URL).status()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apple_event_denial_is_classified_as_automation_denied() {
+        let stderr = "execution error: Not authorized to send Apple events to System Events. (-1743)";
+        assert_eq!(classify_probe_failure(stderr), PermissionIssue::AutomationDenied);
+    }
+
+    #[test]
+    fn other_failures_keep_the_raw_message() {
+        let stderr = "Error: MediaRemote.framework could not be loaded\n";
+        assert_eq!(
+            classify_probe_failure(stderr),
+            PermissionIssue::MediaRemoteUnavailable("Error: MediaRemote.framework could not be loaded".to_string())
+        );
+    }
+}
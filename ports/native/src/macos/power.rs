@@ -0,0 +1,56 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+use std::process::Command;
+
+use anyhow::{anyhow, Error};
+use fsct_core::power::{PowerSource, PowerStatus};
+
+/// Power source of macOS hosts, read from `pmset -g batt`.
+pub struct PmsetPowerSource;
+
+impl PowerSource for PmsetPowerSource {
+    fn status(&self) -> Result<PowerStatus, Error> {
+        let output = Command::new("pmset").args(["-g", "batt"]).output()?;
+        if !output.status.success() {
+            return Err(anyhow!("pmset failed with {}", output.status));
+        }
+        Ok(parse_pmset(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+/// Parses e.g. `Now drawing from 'Battery Power'` followed by `-InternalBattery-0 (id=1234)\t85%; discharging; ...`.
+fn parse_pmset(output: &str) -> PowerStatus {
+    PowerStatus {
+        on_battery: output.contains("'Battery Power'"),
+        battery_percent: output
+            .split_whitespace()
+            .find_map(|word| word.strip_suffix("%;").and_then(|percent| percent.parse().ok())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_battery_power() {
+        let output = "Now drawing from 'Battery Power'\n \
+                      -InternalBattery-0 (id=4653155)\t85%; discharging; 5:12 remaining present: true\n";
+        assert_eq!(parse_pmset(output), PowerStatus { on_battery: true, battery_percent: Some(85) });
+    }
+}
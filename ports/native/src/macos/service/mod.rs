@@ -17,23 +17,64 @@
 
 use anyhow::anyhow;
 use env_logger::Env;
-use fsct_core::{LocalDriver};
+use log::{warn, LevelFilter};
+use fsct_core::{FsctDriver, InterceptedDriver};
+use fsct_core::config::{default_config_path, run_config_service, ConfigHandle, HostConfig};
+use fsct_core::polling::PollingRegistry;
+use fsct_core::power::{run_power_monitor, EnergyConfig, PowerMonitor, DEFAULT_POWER_CHECK_INTERVAL};
+use fsct_core::timeline_smoothing::TimelineSmoother;
 use std::sync::Arc;
-use crate::run_os_watcher;
+use crate::driver_server::serve_driver;
+use crate::local_driver::{local_driver, run_usage_stats_persistence};
+use crate::macos::player::{run_os_watcher_with_options, DEFAULT_JXA_POLLING, JXA_POLLING_PORT, NOW_PLAYING_PORT};
+use crate::macos::power::PmsetPowerSource;
 
 #[tokio::main(flavor = "current_thread")]
 pub async fn fsct_main() -> anyhow::Result<()> {
+    let config_path = default_config_path();
+    let log_level = HostConfig::load(&config_path).ok().and_then(|config| config.log_level);
     let env = Env::default()
-        .filter_or("FSCT_LOG", "info")
+        .filter_or("FSCT_LOG", log_level.unwrap_or(LevelFilter::Info).as_str())
         .write_style("FSCT_LOG_STYLE");
     env_logger::init_from_env(env);
 
-    // Initialize local driver and run background services (orchestrator + USB watch)
-    let driver = Arc::new(LocalDriver::with_new_managers());
+    // Apply the config file before devices get attached; it is reloaded on SIGHUP
+    let polling = Arc::new(PollingRegistry::new());
+    let power = Arc::new(PowerMonitor::new(Box::new(PmsetPowerSource), EnergyConfig::default())
+        .with_polling(polling.clone()));
+    let driver = Arc::new(local_driver().with_power_monitor(power.clone()));
+    let config = Arc::new(ConfigHandle::new(config_path, driver.clone()).with_polling(polling.clone()));
+    if let Err(e) = config.reload().await {
+        warn!("{}, running with defaults", e);
+    }
+    let self_ids = config.config().self_ids.resolved();
+
+    // Run background services (orchestrator + USB watch)
     let mut handle = driver.run().await.map_err(|e| anyhow!(e))?;
+    serve_driver(driver.clone(), &config, &mut handle).await;
+    handle.add(run_config_service(config));
+    if let Some(persistence) = run_usage_stats_persistence(&driver) {
+        handle.add(persistence);
+    }
+
+    // Stream levels (and the spectrum) of what plays to devices with VU meter or spectrum displays
+    #[cfg(feature = "audio-levels")]
+    handle.add(fsct_core::audio_levels::run_audio_analysis(driver.device_manager(),
+                                                           &*crate::audio_capture::platform_audio_capture(),
+                                                           Default::default()));
+
+    // Throttle JXA polling and timeline corrections while on battery
+    handle.add(run_power_monitor(power.clone(), DEFAULT_POWER_CHECK_INTERVAL));
 
     // Start macOS Now Playing watcher, registering a player and streaming state via the driver
-    let watcher = run_os_watcher(driver.clone()).await?;
+    let player_driver: Arc<dyn FsctDriver> = Arc::new(InterceptedDriver::new(driver.clone())
+        .with_interceptor(Arc::new(TimelineSmoother::default()))
+        .with_interceptor(power));
+    // Run it as a port, so that `fsctctl restart-port` can restart it after MediaRemote went away under it
+    let jxa_polling = polling.register(JXA_POLLING_PORT, DEFAULT_JXA_POLLING);
+    let watcher = driver.run_port(NOW_PLAYING_PORT, move || {
+        run_os_watcher_with_options(player_driver.clone(), None, jxa_polling.clone(), self_ids.clone())
+    }).await?;
 
     handle.add(watcher);
 
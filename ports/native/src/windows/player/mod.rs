@@ -15,9 +15,11 @@
 // This file is part of an implementation of Ferrum Streaming Control Technology™,
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use log::{debug, error, warn};
+use log::{debug, error, info, warn};
 use windows::{
     core::Error as WindowsError,
     Media::Control::{
@@ -26,13 +28,21 @@ use windows::{
     },
 };
 use windows::Foundation::TypedEventHandler;
-use windows::Media::Control::{CurrentSessionChangedEventArgs, GlobalSystemMediaTransportControlsSessionMediaProperties, GlobalSystemMediaTransportControlsSessionPlaybackInfo, GlobalSystemMediaTransportControlsSessionTimelineProperties, MediaPropertiesChangedEventArgs, PlaybackInfoChangedEventArgs, TimelinePropertiesChangedEventArgs};
-use fsct_core::definitions::{TimelineInfo, FsctStatus};
+use windows::Media::Control::{SessionsChangedEventArgs, GlobalSystemMediaTransportControlsSessionMediaProperties, GlobalSystemMediaTransportControlsSessionPlaybackInfo, GlobalSystemMediaTransportControlsSessionTimelineProperties, MediaPropertiesChangedEventArgs, PlaybackInfoChangedEventArgs, TimelinePropertiesChangedEventArgs};
+use fsct_core::definitions::{TimelineInfo, FsctStatus, PlaybackModes, RepeatMode};
 use fsct_core::player_state::{PlayerState, TrackMetadata};
+use fsct_core::self_id::SelfIdNamespace;
 use fsct_core::{spawn_service, FsctDriver, ManagedPlayerId, ServiceHandle};
 use anyhow::Error as AnyError;
 use windows_core::HRESULT;
 
+mod session_filter;
+
+pub use session_filter::SessionFilter;
+
+/// Name under which the watcher runs in the driver, see `LocalDriver::restart_port`.
+pub const GSMTC_PORT: &str = "native-windows-gsmtc";
+
 #[derive(Debug)]
 pub enum PlayerError {
     PermissionDenied,
@@ -61,7 +71,8 @@ Result<Option<TimelineInfo>, PlayerError> {
     Ok(Some(TimelineInfo {
         position: Duration::from_secs_f64(position_sec),
         update_time,
-        duration: Duration::from_secs_f64(end_time),
+        // live streams report no end time
+        duration: (end_time > 0.0).then(|| Duration::from_secs_f64(end_time)),
         rate,
     }))
 }
@@ -78,6 +89,19 @@ fn get_status(playback_info: &GlobalSystemMediaTransportControlsSessionPlaybackI
     }
 }
 
+fn get_modes(playback_info: &GlobalSystemMediaTransportControlsSessionPlaybackInfo) -> PlaybackModes {
+    use windows::Media::MediaPlaybackAutoRepeatMode;
+    // sessions report no modes when they don't support them
+    let shuffle = playback_info.IsShuffleActive().and_then(|shuffle| shuffle.Value()).ok();
+    let repeat = playback_info.AutoRepeatMode().and_then(|repeat| repeat.Value()).ok().and_then(|repeat| match repeat {
+        MediaPlaybackAutoRepeatMode::None => Some(RepeatMode::Off),
+        MediaPlaybackAutoRepeatMode::Track => Some(RepeatMode::Track),
+        MediaPlaybackAutoRepeatMode::List => Some(RepeatMode::List),
+        _ => None,
+    });
+    PlaybackModes { shuffle, repeat }
+}
+
 fn windows_string_convert(winstr: windows_core::Result<windows_core::HSTRING>) -> Option<String> {
     winstr.map(|v| v.to_string()).ok()
 }
@@ -87,6 +111,10 @@ fn get_texts(media_properties: &GlobalSystemMediaTransportControlsSessionMediaPr
     texts.title = windows_string_convert(media_properties.Title());
     texts.artist = windows_string_convert(media_properties.Artist());
     texts.album = windows_string_convert(media_properties.AlbumTitle());
+    // GSMTC reports genres as a list and has no year or composer properties
+    texts.genre = media_properties.Genres().ok()
+        .map(|genres| genres.into_iter().map(|genre| genre.to_string()).collect::<Vec<_>>().join(", "))
+        .filter(|genres| !genres.is_empty());
 
     texts
 }
@@ -113,6 +141,7 @@ async fn get_playback_state(session: &GlobalSystemMediaTransportControlsSession)
     let playback_info = session.GetPlaybackInfo().into_player_error()
                                .inspect_err(|e| error!("[WindowsPlayer] Failed to get playback info: {:?}", e)).ok();
     let status = playback_info.as_ref().map(|info| get_status(info)).unwrap_or(FsctStatus::Unknown);
+    let modes = playback_info.as_ref().map(get_modes).unwrap_or_default();
 
     let timeline_properties = session.GetTimelineProperties().into_player_error()
                                      .inspect_err(|e| error!("[WindowsPlayer] Failed to get timeline properties: {:?}", e)).ok();
@@ -125,6 +154,8 @@ async fn get_playback_state(session: &GlobalSystemMediaTransportControlsSession)
         status,
         timeline,
         texts,
+        volume: None,
+        modes,
     })
 }
 
@@ -231,10 +262,17 @@ impl Drop for WindowsSessionHandles {
     }
 }
 
+/// Player registered for the sessions of one app, identified by its AppUserModelID.
+struct SessionPlayer {
+    player_id: ManagedPlayerId,
+    handles: WindowsSessionHandles,
+}
+
 struct WindowsOsWatcher {
     driver: Arc<dyn FsctDriver>,
-    player_id: ManagedPlayerId,
-    handles: Mutex<Option<WindowsSessionHandles>>,
+    players: Mutex<HashMap<String, SessionPlayer>>,
+    session_filter: SessionFilter,
+    self_ids: SelfIdNamespace,
 }
 
 
@@ -246,71 +284,159 @@ async fn get_session_manager() -> Result<GlobalSystemMediaTransportControlsSessi
     Ok(session_manager)
 }
 
+/// Id under which the sessions of `app_id` are registered, e.g. `native-windows-gsmtc-Spotify.exe`.
+fn player_self_id(self_ids: &SelfIdNamespace, app_id: &str) -> String {
+    self_ids.self_id(&format!("windows-gsmtc-{}", app_id))
+}
+
+fn is_playing(session: &GlobalSystemMediaTransportControlsSession) -> bool {
+    session.GetPlaybackInfo().map(|info| get_status(&info) == FsctStatus::Playing).unwrap_or(false)
+}
+
 impl WindowsOsWatcher {
-    async fn new_with_driver(driver: Arc<dyn FsctDriver>) -> Result<Self, PlayerError> {
-        let player_id = driver.register_player("native-windows-gsmtc".to_string()).await.map_err(|e| PlayerError::Other(e.into()))?;
-        Ok(WindowsOsWatcher {
+    fn new_with_driver(driver: Arc<dyn FsctDriver>, session_filter: SessionFilter, self_ids: SelfIdNamespace) -> Self {
+        WindowsOsWatcher {
             driver,
-            player_id,
-            handles: Mutex::new(None),
-        })
+            players: Mutex::new(HashMap::new()),
+            session_filter,
+            self_ids,
+        }
     }
 
+    fn is_app_allowed(&self, app_id: &str) -> bool {
+        let allowed = self.session_filter.is_allowed(app_id);
+        if !allowed {
+            debug!("[WindowsPlayer] Session of {} is excluded by the denylist", app_id);
+        }
+        allowed
+    }
+
+    /// Returns one session per allowed app, preferring a playing one when an app has several sessions.
+    fn select_sessions(&self, session_manager: &GlobalSystemMediaTransportControlsSessionManager)
+        -> Result<HashMap<String, GlobalSystemMediaTransportControlsSession>, PlayerError> {
+        let mut sessions = HashMap::new();
+        for session in session_manager.GetSessions().into_player_error()? {
+            // sessions without an app id can't be told apart, so they are not exposed
+            let Some(app_id) = windows_string_convert(session.SourceAppUserModelId()) else {
+                debug!("[WindowsPlayer] Ignoring session without an app id");
+                continue;
+            };
+            if !self.is_app_allowed(&app_id) {
+                continue;
+            }
+            match sessions.entry(app_id) {
+                Entry::Vacant(entry) => {
+                    entry.insert(session);
+                }
+                Entry::Occupied(mut entry) => {
+                    if !is_playing(entry.get()) && is_playing(&session) {
+                        entry.insert(session);
+                    }
+                }
+            }
+        }
+        Ok(sessions)
+    }
 
     async fn init_session_manager(&self, session_manager: &GlobalSystemMediaTransportControlsSessionManager,
                                   notification_sender: tokio::sync::mpsc::Sender<WindowsNotification>) -> Result<(),
         PlayerError> {
-        let current_session_change_event_handler = TypedEventHandler::<GlobalSystemMediaTransportControlsSessionManager,
-            CurrentSessionChangedEventArgs>::new(move |session_manager, _event_args| -> windows_core::Result<()> {
-            debug!("[WindowsPlayer] Current session changed handler called");
-            notification_sender.blocking_send(WindowsNotification::CurrentSessionChanged(session_manager.clone())).ok();
+        let sessions_changed_event_handler = TypedEventHandler::<GlobalSystemMediaTransportControlsSessionManager,
+            SessionsChangedEventArgs>::new(move |session_manager, _event_args| -> windows_core::Result<()> {
+            debug!("[WindowsPlayer] Sessions changed handler called");
+            notification_sender.blocking_send(WindowsNotification::SessionsChanged(session_manager.clone())).ok();
             Ok(())
         });
 
-        session_manager.CurrentSessionChanged(&current_session_change_event_handler).into_player_error()?;
+        session_manager.SessionsChanged(&sessions_changed_event_handler).into_player_error()?;
 
         Ok(())
     }
 
+    /// Registers a player for every app that got a session, follows the session picked for each app and
+    /// unregisters the players of apps whose sessions are gone.
+    async fn update_sessions(&self,
+                             session_manager: Option<&GlobalSystemMediaTransportControlsSessionManager>,
+                             notification_sender: tokio::sync::mpsc::Sender<WindowsNotification>) {
+        let sessions = match session_manager.ok_or(PlayerError::PermissionDenied)
+                                            .and_then(|session_manager| self.select_sessions(session_manager)) {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                error!("[WindowsPlayer] Can't get sessions, error: {:?}", e);
+                return;
+            }
+        };
+
+        let gone: Vec<(String, ManagedPlayerId)> = {
+            let mut players = self.players.lock().unwrap();
+            let gone_ids: Vec<String> =
+                players.keys().filter(|app_id| !sessions.contains_key(*app_id)).cloned().collect();
+            gone_ids.into_iter()
+                    .filter_map(|app_id| players.remove(&app_id).map(|player| (app_id, player.player_id)))
+                    .collect()
+        };
+        for (app_id, player_id) in gone {
+            debug!("[WindowsPlayer] Session of {} is gone", app_id);
+            let _ = self.driver.unregister_player(player_id).await;
+        }
 
-    async fn try_update_current_session(&self,
-                                        session_manager: Option<&GlobalSystemMediaTransportControlsSessionManager>,
-                                        notification_sender: tokio::sync::mpsc::Sender<WindowsNotification>) -> Result<(), PlayerError> {
-        let session_manager = session_manager.ok_or(PlayerError::PermissionDenied)?;
-        let session = session_manager
-            .GetCurrentSession()
-            .inspect_err(|e|
-                if e.code() != HRESULT(0) {
-                    error!("[WindowsPlayer] Can't get current session, error: {:?}",e)
+        for (app_id, session) in sessions {
+            if let Err(e) = self.follow_session(&app_id, session, notification_sender.clone()).await {
+                warn!("[WindowsPlayer] Failed to follow session of {}: {:?}", app_id, e);
+            }
+        }
+    }
+
+    async fn follow_session(&self, app_id: &str, session: GlobalSystemMediaTransportControlsSession,
+                            notification_sender: tokio::sync::mpsc::Sender<WindowsNotification>)
+        -> Result<(), PlayerError> {
+        let known_player_id = {
+            let players = self.players.lock().unwrap();
+            match players.get(app_id) {
+                Some(player) if player.handles.session == session => return Ok(()),
+                Some(player) => Some(player.player_id),
+                None => None,
+            }
+        };
+        let player_id = match known_player_id {
+            Some(player_id) => player_id,
+            None => {
+                debug!("[WindowsPlayer] New session of {}", app_id);
+                self.driver.register_player(player_self_id(&self.self_ids, app_id)).await
+                    .map_err(|e| PlayerError::Other(e.into()))?
+            }
+        };
+        let handles = match WindowsSessionHandles::new(session.clone(), notification_sender) {
+            Ok(handles) => handles,
+            Err(e) => {
+                if known_player_id.is_none() {
+                    let _ = self.driver.unregister_player(player_id).await;
                 }
-            )
-            .into_player_error()?;
-        debug!("[WindowsPlayer] Current session: {:?}", session);
+                return Err(e);
+            }
+        };
+        // replacing the entry drops the handles of the previous session of the app
+        self.players.lock().unwrap().insert(app_id.to_string(), SessionPlayer { player_id, handles });
         let new_player_state = get_playback_state(&session).await?;
-        debug!("[WindowsPlayer] New player state: {:?}", new_player_state);
-        self.handles.lock().unwrap().take();
-        *self.handles.lock().unwrap() = Some(WindowsSessionHandles::new(session, notification_sender)?);
-        self.driver.update_player_state(self.player_id, new_player_state).await.map_err(|e| PlayerError::Other(e.into()))?;
-        Ok(())
+        debug!("[WindowsPlayer] New player state of {}: {:?}", app_id, new_player_state);
+        self.driver.update_player_state(player_id, new_player_state).await.map_err(|e| PlayerError::Other(e.into()))
     }
 
-    async fn update_current_session(&self,
-                                    session_manager: Option<&GlobalSystemMediaTransportControlsSessionManager>,
-                                    notification_sender: tokio::sync::mpsc::Sender<WindowsNotification>) {
-        if self.try_update_current_session(session_manager, notification_sender).await.is_err() {
-            debug!("[WindowsPlayer] Cannot init current session, resetting state");
-            let _ = self.driver.update_player_state(self.player_id, PlayerState::default()).await;
-        }
+    /// Returns the player following `session`, if any.
+    fn player_of_session(&self, session: &GlobalSystemMediaTransportControlsSession) -> Option<ManagedPlayerId> {
+        let app_id = windows_string_convert(session.SourceAppUserModelId())?;
+        let players = self.players.lock().unwrap();
+        players.get(&app_id).filter(|player| player.handles.session == *session).map(|player| player.player_id)
     }
 
-    fn is_current_session(&self, session: &GlobalSystemMediaTransportControlsSession) -> bool {
-        let handles = self.handles.lock().unwrap();
-        if handles.is_none() {
-            return false;
+    async fn unregister_all(&self) {
+        let player_ids: Vec<ManagedPlayerId> =
+            self.players.lock().unwrap().drain().map(|(_, player)| player.player_id).collect();
+        for player_id in player_ids {
+            let _ = self.driver.unregister_player(player_id).await;
         }
-        let handles = handles.as_ref().unwrap();
-        *session == handles.session
     }
+
     async fn run_notification_task(self: Arc<Self>) -> Result<ServiceHandle, PlayerError> {
         let (startup_done_signal, startup_awaiter) = tokio::sync::oneshot::channel::<()>();
         let service_handle = spawn_service(move |mut stop_token| async move {
@@ -324,25 +450,32 @@ impl WindowsOsWatcher {
             }
             let (notification_sender, mut notification_receiver) = tokio::sync::mpsc::channel::<WindowsNotification>(100);
 
-            let session_manager = session_manager.unwrap();
+            let mut session_manager = session_manager.unwrap();
             if self.init_session_manager(&session_manager, notification_sender.clone()).await.is_err() {
                 debug!("[WindowsPlayer] Failed to init session manager");
                 startup_done_signal.send(()).unwrap_or_default();
                 return;
             }
-            self.update_current_session(Some(&session_manager), notification_sender.clone()).await;
+            self.update_sessions(Some(&session_manager), notification_sender.clone()).await;
             startup_done_signal.send(()).unwrap_or_default();
 
-            while let Some(notification) = tokio::select! {
-                                                                Some(n) = notification_receiver.recv() => Some(n),
-                                                                _ = stop_token.signaled() => None,
-                                                            }
-            {
+            let mut availability_check = tokio::time::interval(SESSION_MANAGER_CHECK_INTERVAL);
+            loop {
+                let notification = tokio::select! {
+                    Some(n) = notification_receiver.recv() => n,
+                    _ = availability_check.tick() => {
+                        if let Some(recreated) = self.recreate_if_unavailable(&session_manager,
+                                                                              notification_sender.clone()).await {
+                            session_manager = recreated;
+                        }
+                        continue;
+                    }
+                    _ = stop_token.signaled() => break,
+                };
                 match notification {
-                    WindowsNotification::CurrentSessionChanged(session_manager) => {
-                        debug!("[WindowsPlayer] Current session changed");
-                        self.update_current_session(session_manager.as_ref(), notification_sender.clone())
-                            .await;
+                    WindowsNotification::SessionsChanged(session_manager) => {
+                        debug!("[WindowsPlayer] Sessions changed");
+                        self.update_sessions(session_manager.as_ref(), notification_sender.clone()).await;
                     }
                     WindowsNotification::SessionNotification { topic, session } => {
                         debug!("[WindowsPlayer] Session notification");
@@ -350,61 +483,89 @@ impl WindowsOsWatcher {
                     }
                 }
             }
+            self.unregister_all().await;
             debug!("[WindowsPlayer] Notification task stopped");
         });
         startup_awaiter.await.map_err(|_| PlayerError::PermissionDenied)?;
         Ok(service_handle)
     }
 
+    /// Recreates the session manager, re-registering all handlers, if the one in use lost its server, e.g. after
+    /// explorer crashed; the players of the apps are kept and follow the sessions of the new manager. Returns
+    /// `None` while the manager works, and also when GSMTC is not back yet, so that it is retried with the next check.
+    async fn recreate_if_unavailable(&self, session_manager: &GlobalSystemMediaTransportControlsSessionManager,
+                                     notification_sender: tokio::sync::mpsc::Sender<WindowsNotification>)
+        -> Option<GlobalSystemMediaTransportControlsSessionManager> {
+        let error = session_manager.GetSessions().err().filter(is_server_unavailable)?;
+        warn!("[WindowsPlayer] Session manager became unavailable ({:?}), recreating it", error);
+        let recreated = match get_session_manager().await {
+            Ok(recreated) => recreated,
+            Err(e) => {
+                debug!("[WindowsPlayer] GSMTC is not available yet: {:?}", e);
+                return None;
+            }
+        };
+        if let Err(e) = self.init_session_manager(&recreated, notification_sender.clone()).await {
+            debug!("[WindowsPlayer] Failed to init recreated session manager: {:?}", e);
+            return None;
+        }
+        self.update_sessions(Some(&recreated), notification_sender).await;
+        info!("[WindowsPlayer] Session manager recreated");
+        Some(recreated)
+    }
+
     async fn handle_session_notification(&self, topic: SessionNotificationTopic, session:
     Option<GlobalSystemMediaTransportControlsSession>) {
         if let Some(session) = session {
-            if !self.is_current_session(&session) {
+            let Some(player_id) = self.player_of_session(&session) else {
                 return;
-            }
+            };
             match topic {
                 SessionNotificationTopic::PlaybackInfoChanged => {
                     debug!("[WindowsPlayer] Playback info changed");
-                    self.handle_playback_info_changed(session).await;
+                    self.handle_playback_info_changed(player_id, session).await;
                 }
                 SessionNotificationTopic::TimelinePropertiesChanged => {
                     debug!("[WindowsPlayer] Timeline properties changed");
-                    self.handle_timeline_properties_changed(session).await;
+                    self.handle_timeline_properties_changed(player_id, session).await;
                 }
                 SessionNotificationTopic::MediaPropertiesChanged => {
                     debug!("[WindowsPlayer] Media properties changed");
-                    self.handle_media_properties_changed(session).await;
+                    self.handle_media_properties_changed(player_id, session).await;
                 }
             }
         }
     }
 
-    async fn handle_media_properties_changed(&self, session: GlobalSystemMediaTransportControlsSession) {
+    async fn handle_media_properties_changed(&self, player_id: ManagedPlayerId,
+                                             session: GlobalSystemMediaTransportControlsSession) {
         // Partial update: update only text metadata fields that we can fetch
         if let Ok(texts) = get_texts_from_session(&session).await {
             for meta_id in texts.iter_id() {
                 let value = texts.get_text(*meta_id).clone();
-                let _ = self.driver.update_player_metadata(self.player_id, *meta_id, value).await;
+                let _ = self.driver.update_player_metadata(player_id, *meta_id, value).await;
             }
         }
     }
 
-    async fn handle_timeline_properties_changed(&self, session: GlobalSystemMediaTransportControlsSession) {
+    async fn handle_timeline_properties_changed(&self, player_id: ManagedPlayerId,
+                                                session: GlobalSystemMediaTransportControlsSession) {
         // Partial update: recompute timeline (position, duration, rate)
         let playback_info = session.GetPlaybackInfo().into_player_error().ok();
         let timeline_props = session.GetTimelineProperties().into_player_error().ok();
         if let Some(tprops) = timeline_props {
             if let Ok(Some(timeline)) = get_timeline_info(playback_info.as_ref(), &tprops) {
-                let _ = self.driver.update_player_timeline(self.player_id, Some(timeline)).await;
+                let _ = self.driver.update_player_timeline(player_id, Some(timeline)).await;
             }
         }
     }
 
-    async fn handle_playback_info_changed(&self, session: GlobalSystemMediaTransportControlsSession) {
+    async fn handle_playback_info_changed(&self, player_id: ManagedPlayerId,
+                                          session: GlobalSystemMediaTransportControlsSession) {
         // Partial update: update only playback status
         if let Ok(info) = session.GetPlaybackInfo().into_player_error() {
             let status = get_status(&info);
-            let _ = self.driver.update_player_status(self.player_id, status).await;
+            let _ = self.driver.update_player_status(player_id, status).await;
         }
     }
 }
@@ -416,7 +577,7 @@ enum SessionNotificationTopic {
 }
 
 enum WindowsNotification {
-    CurrentSessionChanged(Option<GlobalSystemMediaTransportControlsSessionManager>),
+    SessionsChanged(Option<GlobalSystemMediaTransportControlsSessionManager>),
     SessionNotification {
         topic: SessionNotificationTopic,
         session: Option<GlobalSystemMediaTransportControlsSession>,
@@ -426,9 +587,33 @@ enum WindowsNotification {
 
 const UNIX_EPOCH_OFFSET: i64 = 116444736000000000;
 
+/// Interval of checking whether the session manager still reaches its server.
+const SESSION_MANAGER_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Errors of calls on WinRT objects whose server process is gone (RPC_E_DISCONNECTED, RPC_E_SERVER_DIED,
+/// RPC_E_SERVER_DIED_DNE, RPC_S_SERVER_UNAVAILABLE).
+const SERVER_UNAVAILABLE_ERRORS: [HRESULT; 4] = [
+    HRESULT(0x80010108_u32 as i32),
+    HRESULT(0x80010007_u32 as i32),
+    HRESULT(0x80010012_u32 as i32),
+    HRESULT(0x800706BA_u32 as i32),
+];
+
+fn is_server_unavailable(error: &WindowsError) -> bool {
+    SERVER_UNAVAILABLE_ERRORS.contains(&error.code())
+}
+
 
 pub async fn run_os_watcher(driver: Arc<dyn FsctDriver>) -> Result<ServiceHandle, PlayerError> {
-    let windows_watcher = Arc::new(WindowsOsWatcher::new_with_driver(driver).await?);
+    run_os_watcher_with_filter(driver, SessionFilter::default(), SelfIdNamespace::default()).await
+}
+
+/// Runs the GSMTC watcher, registering every app with a media session as a separate player and ignoring the
+/// sessions of apps excluded by `session_filter`. The players are registered under the self ids of `self_ids`.
+pub async fn run_os_watcher_with_filter(driver: Arc<dyn FsctDriver>, session_filter: SessionFilter,
+                                        self_ids: SelfIdNamespace)
+    -> Result<ServiceHandle, PlayerError> {
+    let windows_watcher = Arc::new(WindowsOsWatcher::new_with_driver(driver, session_filter, self_ids));
     windows_watcher.run_notification_task().await
 }
 
@@ -0,0 +1,94 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+/// Decides which media sessions may be exposed as players, based on the AppUserModelID of the source app.
+///
+/// Denylist entries are matched case-insensitively against the whole AppUserModelID
+/// (e.g. `Microsoft.MicrosoftEdge.Stable_8wekyb3d8bbwe!App` or `chrome.exe`) and may contain `*` wildcards,
+/// so `*Edge*` excludes every Edge channel.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionFilter {
+    denylist: Vec<String>,
+}
+
+impl SessionFilter {
+    pub fn new<I, S>(denylist: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            denylist: denylist.into_iter().map(|p| p.into().to_lowercase()).collect(),
+        }
+    }
+
+    pub fn denylist(&self) -> &[String] {
+        &self.denylist
+    }
+
+    pub fn is_allowed(&self, app_user_model_id: &str) -> bool {
+        let app_user_model_id = app_user_model_id.to_lowercase();
+        !self.denylist.iter().any(|pattern| matches_pattern(pattern, &app_user_model_id))
+    }
+}
+
+fn matches_pattern(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // no wildcard at all, so the whole value has to match
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_filter_allows_everything() {
+        let filter = SessionFilter::default();
+        assert!(filter.is_allowed("Spotify.exe"));
+    }
+
+    #[test]
+    fn exact_entry_is_matched_case_insensitively() {
+        let filter = SessionFilter::new(["chrome.exe"]);
+        assert!(!filter.is_allowed("Chrome.EXE"));
+        assert!(filter.is_allowed("chrome.exe.backup"));
+    }
+
+    #[test]
+    fn wildcard_entries_match_parts_of_the_id() {
+        let filter = SessionFilter::new(["*Edge*", "Microsoft.Xbox*!App"]);
+        assert!(!filter.is_allowed("Microsoft.MicrosoftEdge.Stable_8wekyb3d8bbwe!App"));
+        assert!(!filter.is_allowed("Microsoft.XboxGamingOverlay_8wekyb3d8bbwe!App"));
+        assert!(filter.is_allowed("Microsoft.XboxGamingOverlay_8wekyb3d8bbwe!Widget"));
+        assert!(filter.is_allowed("Spotify.exe"));
+    }
+}
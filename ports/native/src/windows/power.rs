@@ -0,0 +1,35 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+use anyhow::Error;
+use fsct_core::power::{PowerSource, PowerStatus};
+use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+/// Power source of Windows hosts, read with `GetSystemPowerStatus`.
+pub struct WindowsPowerSource;
+
+impl PowerSource for WindowsPowerSource {
+    fn status(&self) -> Result<PowerStatus, Error> {
+        let mut status = SYSTEM_POWER_STATUS::default();
+        unsafe { GetSystemPowerStatus(&mut status)? };
+        Ok(PowerStatus {
+            // 0 is offline, 1 online and 255 unknown
+            on_battery: status.ACLineStatus == 0,
+            battery_percent: (status.BatteryLifePercent <= 100).then_some(status.BatteryLifePercent),
+        })
+    }
+}
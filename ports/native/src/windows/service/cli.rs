@@ -75,6 +75,10 @@ pub struct Cli {
     #[arg(short, long, value_enum, default_value_t = LogLevel::Info)]
     pub log_level: LogLevel,
 
+    /// Exclude media sessions of the given app (AppUserModelID, `*` wildcards allowed); can be repeated
+    #[arg(long = "exclude-app", value_name = "AUMID", global = true)]
+    pub exclude_apps: Vec<String>,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -37,7 +37,7 @@ fn get_service_type(user_service: bool) -> ServiceType
     }
 }
 
-pub fn install_service(log_level: Option<LogLevel>, user_service: bool) -> Result<()> {
+pub fn install_service(log_level: Option<LogLevel>, user_service: bool, excluded_apps: &[String]) -> Result<()> {
     debug!("Starting service installation");
 
     debug!("Connecting to service manager");
@@ -74,6 +74,9 @@ pub fn install_service(log_level: Option<LogLevel>, user_service: bool) -> Resul
     if let Some(log_level) = log_level {
         launch_arguments.extend_from_slice(&[OsString::from("--log-level"), OsString::from(log_level.to_string())])
     };
+    for app in excluded_apps {
+        launch_arguments.extend_from_slice(&[OsString::from("--exclude-app"), OsString::from(app)]);
+    }
     launch_arguments.extend_from_slice(&[OsString::from("service"), OsString::from("run")]);
 
     // Create the service info
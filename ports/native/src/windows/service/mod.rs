@@ -34,11 +34,13 @@ pub use standalone::run_standalone;
 use anyhow::bail;
 use log::{info, error, debug};
 use clap::Parser;
+use crate::windows::player::SessionFilter;
 
 pub fn fsct_main() -> anyhow::Result<()> {
     // Parse command line arguments using clap
     let cli = Cli::parse();
     let log_level = cli.log_level;
+    let session_filter = SessionFilter::new(cli.exclude_apps.iter());
 
     // Check if a command was provided
     if let Some(command) = cli.command {
@@ -52,7 +54,7 @@ pub fn fsct_main() -> anyhow::Result<()> {
                             bail!("Failed to initialize logger: {}", e);
                         }
                         debug!("Installing service with log level: {}", log_level);
-                        let result = install_service(service_log_level, user_service);
+                        let result = install_service(service_log_level, user_service, &cli.exclude_apps);
                         if let Err(ref e) = result {
                             error!("Failed to install service: {}", e);
                         } else {
@@ -84,7 +86,7 @@ pub fn fsct_main() -> anyhow::Result<()> {
                         }
                         // Run as a service
                         info!("Service starting with log level: {}", log_level);
-                        return runtime::start_service();
+                        return runtime::start_service(session_filter);
                     }
                 }
             }
@@ -92,5 +94,5 @@ pub fn fsct_main() -> anyhow::Result<()> {
     }
 
     // If no arguments provided, run in standalone mode
-    run_standalone(log_level)
-}
\ No newline at end of file
+    run_standalone(log_level, session_filter)
+}
@@ -16,10 +16,10 @@
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
 use std::ffi::OsString;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 use anyhow::Result;
-use log::{info, error, debug};
+use log::{info, error, debug, warn};
 use windows::Win32::System::RemoteDesktop::WTSGetActiveConsoleSessionId;
 use windows_service::{
     service::{
@@ -32,14 +32,22 @@ use windows_service::{
 };
 use windows_service::service::ServiceType;
 use crate::windows::service::constants::SERVICE_NAME;
-use fsct_core::LocalDriver;
-use crate::run_os_watcher;
+use fsct_core::{FsctDriver, InterceptedDriver, LocalDriver, ServiceHandle};
+use fsct_core::config::{default_config_path, run_config_service, ConfigHandle};
+use fsct_core::power::{run_power_monitor, EnergyConfig, PowerMonitor, DEFAULT_POWER_CHECK_INTERVAL};
+use fsct_core::timeline_smoothing::TimelineSmoother;
+use crate::windows::player::{run_os_watcher_with_filter, SessionFilter, GSMTC_PORT};
+use crate::windows::power::WindowsPowerSource;
+use crate::driver_server::serve_driver;
+use crate::local_driver::{local_driver, run_usage_stats_persistence};
 
 // Define service events
 #[derive(Clone)]
 pub enum ServiceEvent {
     Shutdown,
     SessionChange(windows_service::service::SessionChangeParam),
+    /// The config file is to be applied again (`sc control <service> paramchange`)
+    ReloadConfig,
 }
 
 pub fn get_current_session_id() -> Option<u32> {
@@ -53,8 +61,12 @@ pub fn get_current_session_id() -> Option<u32> {
 
 define_windows_service!(ffi_service_main, service_main);
 
+// The service entry point is called by the dispatcher without our context, so the filter is stashed here
+static SESSION_FILTER: OnceLock<SessionFilter> = OnceLock::new();
+
 // Public function to start the service
-pub fn start_service() -> Result<()> {
+pub fn start_service(session_filter: SessionFilter) -> Result<()> {
+    SESSION_FILTER.set(session_filter).ok();
     service_dispatcher::start(SERVICE_NAME, ffi_service_main)?;
     Ok(())
 }
@@ -65,6 +77,23 @@ pub fn service_main(arguments: Vec<OsString>) {
     }
 }
 
+/// Runs the GSMTC watcher as a port of the driver, so that it can be restarted after explorer crashed under it. Its
+/// players get the self ids configured in `config`.
+async fn run_gsmtc_port(driver: &LocalDriver, config: &ConfigHandle, player_driver: Arc<dyn FsctDriver>,
+                        session_filter: SessionFilter)
+    -> Result<ServiceHandle> {
+    let self_ids = config.config().self_ids.resolved();
+    let handle = driver.run_port(GSMTC_PORT, move || {
+        let (player_driver, session_filter) = (player_driver.clone(), session_filter.clone());
+        let self_ids = self_ids.clone();
+        async move {
+            run_os_watcher_with_filter(player_driver, session_filter, self_ids).await
+                .map_err(|e| anyhow::anyhow!("{:?}", e))
+        }
+    }).await?;
+    Ok(handle)
+}
+
 fn get_service_type_from_manager() -> anyhow::Result<ServiceType> {
     let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
     let service = manager.open_service(SERVICE_NAME, ServiceAccess::QUERY_CONFIG)?;
@@ -95,6 +124,11 @@ pub fn run_service_main(_arguments: Vec<OsString>) -> anyhow::Result<()> {
                 ServiceControlHandlerResult::NoError
             }
             ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            ServiceControl::ParamChange => {
+                debug!("Received param change control event");
+                let _ = event_tx_clone.send(ServiceEvent::ReloadConfig);
+                ServiceControlHandlerResult::NoError
+            }
             ServiceControl::SessionChange(param) => {
                 debug!("Received session change event: {:?}, session ID: {}", param.reason, param.notification.session_id);
                 let _ = event_tx_clone.send(ServiceEvent::SessionChange(param));
@@ -106,6 +140,7 @@ pub fn run_service_main(_arguments: Vec<OsString>) -> anyhow::Result<()> {
             }
         }
     };
+    let session_filter = SESSION_FILTER.get().cloned().unwrap_or_default();
     let service_type = get_service_type_from_manager()?;
     let is_user_service = service_type.contains(ServiceType::USER_OWN_PROCESS);
 
@@ -140,7 +175,12 @@ pub fn run_service_main(_arguments: Vec<OsString>) -> anyhow::Result<()> {
 
         // Run driver
         debug!("Initializing driver");
-        let driver = Arc::new(LocalDriver::with_new_managers());
+        let power = Arc::new(PowerMonitor::new(Box::new(WindowsPowerSource), EnergyConfig::default()));
+        let driver = Arc::new(local_driver().with_power_monitor(power.clone()));
+        let config = Arc::new(ConfigHandle::new(default_config_path(), driver.clone()));
+        if let Err(e) = config.reload().await {
+            warn!("{}, running with defaults", e);
+        }
         let mut driver_handle = match driver.clone().run().await
         {
             Ok(driver_handle) => driver_handle,
@@ -149,12 +189,24 @@ pub fn run_service_main(_arguments: Vec<OsString>) -> anyhow::Result<()> {
                 return;
             }
         };
+        serve_driver(driver.clone(), &config, &mut driver_handle).await;
+        driver_handle.add(run_config_service(config.clone()));
+        if let Some(persistence) = run_usage_stats_persistence(&driver) {
+            driver_handle.add(persistence);
+        }
+        #[cfg(feature = "audio-levels")]
+        driver_handle.add(fsct_core::audio_levels::run_audio_analysis(
+            driver.device_manager(), &*crate::audio_capture::platform_audio_capture(), Default::default()));
 
         // Initialize the player
         debug!("Initializing native platform player");
+        driver_handle.add(run_power_monitor(power.clone(), DEFAULT_POWER_CHECK_INTERVAL));
+        let player_driver: Arc<dyn FsctDriver> = Arc::new(InterceptedDriver::new(driver.clone())
+            .with_interceptor(Arc::new(TimelineSmoother::default()))
+            .with_interceptor(power));
         let mut retries = 0;
         let os_watcher_handle = loop {
-            match run_os_watcher(driver.clone()).await {
+            match run_gsmtc_port(&driver, &config, player_driver.clone(), session_filter.clone()).await {
                 Ok(player) => break player,
                 Err(e) => {
                     retries += 1;
@@ -176,7 +228,8 @@ pub fn run_service_main(_arguments: Vec<OsString>) -> anyhow::Result<()> {
         let result = status_handle.set_service_status(ServiceStatus {
             service_type,
             current_state: ServiceState::Running,
-            controls_accepted: ServiceControlAccept::STOP | ServiceControlAccept::SESSION_CHANGE,
+            controls_accepted: ServiceControlAccept::STOP | ServiceControlAccept::SESSION_CHANGE
+                | ServiceControlAccept::PARAM_CHANGE,
             exit_code: ServiceExitCode::Win32(0),
             checkpoint: 0,
             wait_hint: Duration::default(),
@@ -209,6 +262,11 @@ pub fn run_service_main(_arguments: Vec<OsString>) -> anyhow::Result<()> {
                             info!("Received shutdown event, stopping...");
                             break;
                         },
+                        ServiceEvent::ReloadConfig => {
+                            if let Err(e) = config.reload().await {
+                                error!("Failed to reload config: {}", e);
+                            }
+                        },
                         ServiceEvent::SessionChange(param) => {
                             let session_id = param.notification.session_id;
                             debug!("Processing session change event: {:?}, session ID: {}", param.reason, session_id);
@@ -247,10 +305,19 @@ pub fn run_service_main(_arguments: Vec<OsString>) -> anyhow::Result<()> {
                                                 continue;
                                             }
                                         };
+                                        serve_driver(driver.clone(), &config, &mut driver_handle).await;
+                                        driver_handle.add(run_config_service(config.clone()));
+                                        if let Some(persistence) = run_usage_stats_persistence(&driver) {
+                                            driver_handle.add(persistence);
+                                        }
+                                        #[cfg(feature = "audio-levels")]
+                                        driver_handle.add(fsct_core::audio_levels::run_audio_analysis(
+                                            driver.device_manager(), &*crate::audio_capture::platform_audio_capture(), Default::default()));
 
                                         // Initialize the player
                                         debug!("Initializing native platform player");
-                                        let os_watcher_handle = match run_os_watcher(driver.clone()).await {
+                                        let os_watcher_handle = match run_gsmtc_port(&driver, &config, driver.clone(),
+                                                                                     session_filter.clone()).await {
                                             Ok(watcher_handle) => watcher_handle,
                                             Err(e) => {
                                                     error!("Failed to initialize player: {:?}", e);
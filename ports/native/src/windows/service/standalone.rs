@@ -15,15 +15,21 @@
 // This file is part of an implementation of Ferrum Streaming Control Technology™,
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
-use log::{info, error, debug};
+use log::{info, error, debug, warn};
 use tokio::runtime::Runtime;
 use std::sync::Arc;
-use fsct_core::{LocalDriver, MultiServiceHandle};
+use crate::driver_server::serve_driver;
+use crate::local_driver::{local_driver, run_usage_stats_persistence};
+use fsct_core::{FsctDriver, InterceptedDriver, MultiServiceHandle};
+use fsct_core::config::{default_config_path, run_config_service, ConfigHandle};
+use fsct_core::power::{run_power_monitor, EnergyConfig, PowerMonitor, DEFAULT_POWER_CHECK_INTERVAL};
+use fsct_core::timeline_smoothing::TimelineSmoother;
 
 use crate::windows::service::cli::LogLevel;
 use crate::windows::service::logger::init_standalone_logger;
 use tokio::signal::windows::ctrl_close;
-use crate::run_os_watcher;
+use crate::windows::player::{run_os_watcher_with_filter, SessionFilter, GSMTC_PORT};
+use crate::windows::power::WindowsPowerSource;
 
 async fn shutdown_signal() {
     debug!("Press Ctrl+C or close the console window to exit");
@@ -41,21 +47,47 @@ async fn shutdown_signal() {
     }
 }
 
-async fn standalone_task() -> anyhow::Result<()> {
+async fn standalone_task(session_filter: SessionFilter) -> anyhow::Result<()> {
     debug!("Creating LocalDriver and starting services");
-    let driver = Arc::new(LocalDriver::with_new_managers());
+    let power = Arc::new(PowerMonitor::new(Box::new(WindowsPowerSource), EnergyConfig::default()));
+    let driver = Arc::new(local_driver().with_power_monitor(power.clone()));
+    let config = Arc::new(ConfigHandle::new(default_config_path(), driver.clone()));
+    if let Err(e) = config.reload().await {
+        warn!("{}, running with defaults", e);
+    }
+    let self_ids = config.config().self_ids.resolved();
 
     debug!("Starting orchestrator + USB watch via LocalDriver::run()");
     let mut services = driver.run().await
                              .inspect(|_| debug!("Orchestrator + USB watch started successfully"))
                              .map_err(|e| anyhow::anyhow!("Failed to start orchestrator + USB watch: {}", e))?;
+    serve_driver(driver.clone(), &config, &mut services).await;
+    services.add(run_config_service(config));
+    if let Some(persistence) = run_usage_stats_persistence(&driver) {
+        services.add(persistence);
+    }
+    #[cfg(feature = "audio-levels")]
+    services.add(fsct_core::audio_levels::run_audio_analysis(driver.device_manager(),
+                                                             &*crate::audio_capture::platform_audio_capture(),
+                                                             Default::default()));
 
 
     debug!("Starting GSMTC watcher (WindowsSystemPlayer)");
 
-    let result = run_os_watcher(driver.clone()).await
-                                               .map(|w| services.add(w))
-                                               .inspect_err(|e| error!("Failed to start OS watcher: {:?}", e));
+    services.add(run_power_monitor(power.clone(), DEFAULT_POWER_CHECK_INTERVAL));
+    let player_driver: Arc<dyn FsctDriver> = Arc::new(InterceptedDriver::new(driver.clone())
+        .with_interceptor(Arc::new(TimelineSmoother::default()))
+        .with_interceptor(power));
+    let result = driver.run_port(GSMTC_PORT, move || {
+        let (player_driver, session_filter) = (player_driver.clone(), session_filter.clone());
+        let self_ids = self_ids.clone();
+        async move {
+            run_os_watcher_with_filter(player_driver, session_filter, self_ids).await
+                .map_err(|e| anyhow::anyhow!("{:?}", e))
+        }
+    }).await
+      .map(|w| services.add(w))
+      .inspect_err(|e| error!("Failed to start OS watcher: {}", e));
 
     if result.is_ok() {
         shutdown_signal().await;
@@ -67,7 +99,7 @@ async fn standalone_task() -> anyhow::Result<()> {
 }
 
 // Function to run the service in standalone mode (for debugging)
-pub fn run_standalone(log_level: LogLevel) -> anyhow::Result<()> {
+pub fn run_standalone(log_level: LogLevel, session_filter: SessionFilter) -> anyhow::Result<()> {
     // Initialize logger for standalone mode
     if let Err(e) = init_standalone_logger(log_level) {
         eprintln!("Failed to initialize logger: {}", e);
@@ -81,7 +113,7 @@ pub fn run_standalone(log_level: LogLevel) -> anyhow::Result<()> {
 
     // Run the service in the Tokio runtime
     rt.block_on(async {
-        standalone_task().await
+        standalone_task(session_filter).await
                          .map_err(|e| error!("Failed with error: {}", e))
                          .ok();
     });
@@ -22,8 +22,8 @@ export const enum PlayerStatus {
 export interface TimelineInfo {
   /** Position in seconds from track start */
   position: number
-  /** Track duration in seconds */
-  duration: number
+  /** Track duration in seconds; omit for content without a known duration (e.g. live streams) */
+  duration?: number
   /** Playback speed rate. Use 1.0 */
   rate: number
 }
@@ -31,7 +31,106 @@ export const enum CurrentTextMetadata {
   Title = 'Title',
   Author = 'Author',
   Album = 'Album',
-  Genre = 'Genre'
+  Genre = 'Genre',
+  Year = 'Year',
+  Composer = 'Composer',
+  /** Title of the next track in the queue */
+  NextTitle = 'NextTitle',
+  /** Author of the next track in the queue */
+  NextAuthor = 'NextAuthor'
+}
+/** Texts of a track; in partial updates omitted texts keep their values. */
+export interface TrackInfo {
+  title?: string
+  /** Shown as the author of the track */
+  artist?: string
+  album?: string
+  genre?: string
+  year?: string
+  composer?: string
+  /** Title of the next track in the queue */
+  nextTitle?: string
+  /** Shown as the author of the next track in the queue */
+  nextArtist?: string
+}
+/** Part of the player state to change; omitted fields keep their values. */
+export interface PartialPlayerState {
+  status?: PlayerStatus
+  timeline?: TimelineInfo
+  /** Clears the timeline, e.g. when playback stops */
+  clearTimeline?: boolean
+  /** Texts to change; an empty string clears the text */
+  track?: TrackInfo
+}
+export const enum TextEncoding {
+  Utf8 = 'Utf8',
+  Utf16 = 'Utf16',
+  Ucs2 = 'Ucs2',
+  Utf32 = 'Utf32'
+}
+export interface TextLimit {
+  textType: CurrentTextMetadata
+  /** Maximum length of the text in bytes, encoded in the device's text encoding */
+  maxLength: number
+}
+export interface DeviceLimits {
+  deviceId: string
+  protocolVersion: number
+  textEncoding: TextEncoding
+  /** Texts the device shows; texts not listed are dropped */
+  texts: Array<TextLimit>
+}
+export interface DeviceInfo {
+  deviceId: string
+  vendorId: number
+  productId: number
+  manufacturer?: string
+  product?: string
+  serialNumber?: string
+  /** Device release number, e.g. "1.02" */
+  firmwareVersion: string
+  protocolVersion: number
+  /** Names of the supported FSCT functionalities */
+  functionality: Array<string>
+  textEncoding: TextEncoding
+  /** Texts the device shows; texts not listed are dropped */
+  texts: Array<TextLimit>
+}
+export const enum DeviceEventType {
+  /** A device was attached */
+  Added = 'Added',
+  /** A device was removed */
+  Removed = 'Removed',
+  /** The firmware of the device reported an error */
+  DeviceError = 'DeviceError',
+  /** The device sent a playback command */
+  DeviceCommand = 'DeviceCommand',
+  /** The volume was changed on the device */
+  VolumeChanged = 'VolumeChanged'
+}
+export interface DeviceEventInfo {
+  eventType: DeviceEventType
+  deviceId: string
+}
+export const enum ControlCommandType {
+  Play = 'Play',
+  Pause = 'Pause',
+  Next = 'Next',
+  Previous = 'Previous',
+  /** Seek to `position` */
+  Seek = 'Seek',
+  /** Set the volume to `volume` */
+  SetVolume = 'SetVolume'
+}
+/** Playback command of a device showing the player, e.g. a button press. */
+export interface ControlCommand {
+  command: ControlCommandType
+  /** Position in seconds from track start, for `Seek` */
+  position?: number
+  /** Volume in percent (0-100), for `SetVolume` */
+  volume?: number
+  /** Whether the player is muted, for `SetVolume` */
+  muted?: boolean
 }
 export const enum LogLevelFilter {
   Trace = 0,
@@ -41,7 +140,36 @@ export const enum LogLevelFilter {
   Error = 4,
   Off = 5
 }
+/** Where the log of the service goes, see `FsctServiceOptions.logTarget`. */
+export const enum LogTarget {
+  Stdout = 0,
+  Systemd = 1
+}
+/** What `FsctService.runFsct` runs in-process; everything is optional. */
+export interface FsctServiceOptions {
+  /**
+   * Whether the service watches USB for FSCT devices; defaults to true. Hosts embedded in apps (e.g. Electron)
+   * whose devices are driven by the system service turn it off or set `remoteEndpoint`.
+   */
+  usb?: boolean
+  /**
+   * Driver server of a running system service (e.g. `http://127.0.0.1:50151`) the player is registered with,
+   * instead of driving devices in-process.
+   */
+  remoteEndpoint?: string
+  /** Bearer token for system services limiting who may use their driver server. */
+  remoteToken?: string
+  /** Self id the player is registered under; defaults to `node-js`. */
+  selfId?: string
+  /** Installs a logger before the service starts; hosts with their own logger leave it unset. */
+  logTarget?: LogTarget
+  logLevel?: LogLevelFilter
+}
 export declare function initStdoutLogger(): void
+/**
+ * Logs to the systemd journal when the host runs one; systems without systemd (e.g. musl based streamers) get the
+ * stdout logger instead.
+ */
 export declare function initSystemdLogger(syslogIdentifier: string): void
 export declare function setLogLevel(level: LogLevelFilter): void
 export declare class NodePlayer {
@@ -49,10 +177,40 @@ export declare class NodePlayer {
   setStatus(status: PlayerStatus): Promise<void>
   setTimeline(timeline?: TimelineInfo | undefined | null): Promise<void>
   setText(textType: CurrentTextMetadata, text?: string | undefined | null): Promise<void>
+  /** Replaces all texts of the current track at once; texts omitted from `track` are cleared. */
+  setTrack(track: TrackInfo): Promise<void>
+  /**
+   * Changes the given fields of the player state in a single update, e.g. status, timeline and texts on a
+   * track change.
+   */
+  updateState(partialState: PartialPlayerState): Promise<void>
+  /**
+   * Calls `callback` with the playback commands of devices showing the player (play, pause, next, previous,
+   * seek, volume), replacing the previous callback. Commands fail on the device side while no callback is set.
+   */
+  onControlCommand(callback: (command: ControlCommand) => void): void
 }
 export declare class FsctService {
   constructor()
-  runFsct(player: NodePlayer): Promise<void>
+  /**
+   * Starts the service and registers `player` with it; `options` select what runs in-process, by default
+   * everything.
+   */
+  runFsct(player: NodePlayer, options?: FsctServiceOptions | undefined | null): Promise<void>
   stopFsct(): Promise<void>
+  /** Limits of the connected devices, e.g. for shortening texts before they get cut on the device. */
+  getDeviceLimits(): Array<DeviceLimits>
+  /** Connected devices with their USB ids, protocol version, functionalities and text limits. */
+  listDevices(): Promise<Array<DeviceInfo>>
+  /**
+   * Calls `callback` for every device attached, removed, reporting an error or sending a playback command,
+   * until the service is stopped.
+   */
+  onDeviceEvent(callback: (event: DeviceEventInfo) => void): void
+  /**
+   * Calls `callback` with the descriptor of every device attached (`deviceAdded`) or removed (`deviceRemoved`),
+   * until the service is stopped, e.g. for rendering the connected hardware on hotplug.
+   */
+  on(event: 'deviceAdded' | 'deviceRemoved', callback: (device: DeviceInfo) => void): void
   
 }
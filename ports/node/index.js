@@ -310,13 +310,17 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { PlayerStatus, CurrentTextMetadata, NodePlayer, FsctService, LogLevelFilter, initStdoutLogger, initSystemdLogger, setLogLevel } = nativeBinding
+const { PlayerStatus, CurrentTextMetadata, TextEncoding, DeviceEventType, ControlCommandType, NodePlayer, FsctService, LogLevelFilter, LogTarget, initStdoutLogger, initSystemdLogger, setLogLevel } = nativeBinding
 
 module.exports.PlayerStatus = PlayerStatus
 module.exports.CurrentTextMetadata = CurrentTextMetadata
+module.exports.TextEncoding = TextEncoding
+module.exports.DeviceEventType = DeviceEventType
+module.exports.ControlCommandType = ControlCommandType
 module.exports.NodePlayer = NodePlayer
 module.exports.FsctService = FsctService
 module.exports.LogLevelFilter = LogLevelFilter
+module.exports.LogTarget = LogTarget
 module.exports.initStdoutLogger = initStdoutLogger
 module.exports.initSystemdLogger = initSystemdLogger
 module.exports.setLogLevel = setLogLevel
@@ -16,7 +16,10 @@
 // which is subject to additional terms found in the LICENSE-FSCT.md file.
 
 pub use fsct_core::definitions::TimelineInfo as FsctTimelineInfo;
-use fsct_core::definitions::{FsctStatus, FsctTextMetadata};
+use fsct_core::definitions::{DeviceLimits as FsctDeviceLimits, FsctStatus, FsctTextEncoding, FsctTextMetadata,
+                              PlaybackCommand, SupportedText};
+use fsct_core::device_history::DeviceAttachRecord;
+use fsct_core::DeviceEvent;
 use std::time::{Duration, SystemTime};
 
 #[napi(string_enum)]
@@ -56,8 +59,8 @@ impl From<PlayerStatus> for FsctStatus {
 pub struct TimelineInfo {
     /// Position in seconds from track start
     pub position: f64,
-    /// Track duration in seconds
-    pub duration: f64,
+    /// Track duration in seconds; omit for content without a known duration (e.g. live streams)
+    pub duration: Option<f64>,
     /// Playback speed rate. Use 1.0
     pub rate: f64,
 }
@@ -70,7 +73,9 @@ impl TryFrom<TimelineInfo> for FsctTimelineInfo {
         }
         Ok(FsctTimelineInfo {
             position: Duration::try_from_secs_f64(value.position).map_err(|e| napi::Error::from_reason(e.to_string()))?,
-            duration: Duration::try_from_secs_f64(value.duration).map_err(|e| napi::Error::from_reason(e.to_string()))?,
+            duration: value.duration
+                .map(|duration| Duration::try_from_secs_f64(duration).map_err(|e| napi::Error::from_reason(e.to_string())))
+                .transpose()?,
             update_time: SystemTime::now(),
             rate: value.rate,
         })
@@ -83,6 +88,12 @@ pub enum CurrentTextMetadata {
     Author,
     Album,
     Genre,
+    Year,
+    Composer,
+    /// Title of the next track in the queue
+    NextTitle,
+    /// Author of the next track in the queue
+    NextAuthor,
 }
 
 impl From<CurrentTextMetadata> for FsctTextMetadata {
@@ -92,6 +103,289 @@ impl From<CurrentTextMetadata> for FsctTextMetadata {
             CurrentTextMetadata::Author => FsctTextMetadata::CurrentAuthor,
             CurrentTextMetadata::Album => FsctTextMetadata::CurrentAlbum,
             CurrentTextMetadata::Genre => FsctTextMetadata::CurrentGenre,
+            CurrentTextMetadata::Year => FsctTextMetadata::CurrentYear,
+            CurrentTextMetadata::Composer => FsctTextMetadata::CurrentComposer,
+            CurrentTextMetadata::NextTitle => FsctTextMetadata::QueueTitle,
+            CurrentTextMetadata::NextAuthor => FsctTextMetadata::QueueAuthor,
         }
     }
 }
+
+impl CurrentTextMetadata {
+    /// Text type of the FSCT text, `None` for queue texts players don't provide (album, genre, year, composer).
+    fn from_fsct(value: FsctTextMetadata) -> Option<Self> {
+        match value {
+            FsctTextMetadata::CurrentTitle => Some(CurrentTextMetadata::Title),
+            FsctTextMetadata::CurrentAuthor => Some(CurrentTextMetadata::Author),
+            FsctTextMetadata::CurrentAlbum => Some(CurrentTextMetadata::Album),
+            FsctTextMetadata::CurrentGenre => Some(CurrentTextMetadata::Genre),
+            FsctTextMetadata::CurrentYear => Some(CurrentTextMetadata::Year),
+            FsctTextMetadata::CurrentComposer => Some(CurrentTextMetadata::Composer),
+            FsctTextMetadata::QueueTitle => Some(CurrentTextMetadata::NextTitle),
+            FsctTextMetadata::QueueAuthor => Some(CurrentTextMetadata::NextAuthor),
+            _ => None,
+        }
+    }
+}
+
+/// Texts of a track; in partial updates omitted texts keep their values.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct TrackInfo {
+    pub title: Option<String>,
+    /// Shown as the author of the track
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub genre: Option<String>,
+    pub year: Option<String>,
+    pub composer: Option<String>,
+    /// Title of the next track in the queue
+    pub next_title: Option<String>,
+    /// Shown as the author of the next track in the queue
+    pub next_artist: Option<String>,
+}
+
+impl TrackInfo {
+    pub fn into_texts(self) -> [(FsctTextMetadata, Option<String>); 8] {
+        [
+            (FsctTextMetadata::CurrentTitle, self.title),
+            (FsctTextMetadata::CurrentAuthor, self.artist),
+            (FsctTextMetadata::CurrentAlbum, self.album),
+            (FsctTextMetadata::CurrentGenre, self.genre),
+            (FsctTextMetadata::CurrentYear, self.year),
+            (FsctTextMetadata::CurrentComposer, self.composer),
+            (FsctTextMetadata::QueueTitle, self.next_title),
+            (FsctTextMetadata::QueueAuthor, self.next_artist),
+        ]
+    }
+}
+
+/// Part of the player state to change; omitted fields keep their values.
+#[napi(object)]
+pub struct PartialPlayerState {
+    pub status: Option<PlayerStatus>,
+    pub timeline: Option<TimelineInfo>,
+    /// Clears the timeline, e.g. when playback stops
+    pub clear_timeline: Option<bool>,
+    /// Texts to change; an empty string clears the text
+    pub track: Option<TrackInfo>,
+}
+
+#[napi(string_enum)]
+pub enum TextEncoding {
+    Utf8,
+    Utf16,
+    Ucs2,
+    Utf32,
+}
+
+impl From<FsctTextEncoding> for TextEncoding {
+    fn from(value: FsctTextEncoding) -> Self {
+        match value {
+            FsctTextEncoding::Utf8 => TextEncoding::Utf8,
+            FsctTextEncoding::Utf16 => TextEncoding::Utf16,
+            FsctTextEncoding::Ucs2 => TextEncoding::Ucs2,
+            FsctTextEncoding::Utf32 => TextEncoding::Utf32,
+        }
+    }
+}
+
+#[napi(object)]
+pub struct TextLimit {
+    pub text_type: CurrentTextMetadata,
+    /// Maximum length of the text in bytes, encoded in the device's text encoding
+    pub max_length: u32,
+}
+
+#[napi(object)]
+pub struct DeviceLimits {
+    pub device_id: String,
+    pub protocol_version: u32,
+    pub text_encoding: TextEncoding,
+    /// Texts the device shows; texts not listed are dropped
+    pub texts: Vec<TextLimit>,
+}
+
+fn text_limits(texts: Vec<SupportedText>) -> Vec<TextLimit> {
+    texts
+        .into_iter()
+        .filter_map(|text| {
+            Some(TextLimit {
+                text_type: CurrentTextMetadata::from_fsct(text.metadata)?,
+                max_length: text.max_length.try_into().unwrap_or(u32::MAX),
+            })
+        })
+        .collect()
+}
+
+impl From<FsctDeviceLimits> for DeviceLimits {
+    fn from(value: FsctDeviceLimits) -> Self {
+        DeviceLimits {
+            device_id: value.device_id.to_string(),
+            protocol_version: value.protocol_version as u32,
+            text_encoding: value.text_encoding.into(),
+            texts: text_limits(value.texts),
+        }
+    }
+}
+
+#[napi(object)]
+pub struct DeviceInfo {
+    pub device_id: String,
+    pub vendor_id: u32,
+    pub product_id: u32,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<String>,
+    /// Device release number, e.g. "1.02"
+    pub firmware_version: String,
+    pub protocol_version: u32,
+    /// Names of the supported FSCT functionalities
+    pub functionality: Vec<String>,
+    pub text_encoding: TextEncoding,
+    /// Texts the device shows; texts not listed are dropped
+    pub texts: Vec<TextLimit>,
+}
+
+impl From<DeviceAttachRecord> for DeviceInfo {
+    fn from(value: DeviceAttachRecord) -> Self {
+        DeviceInfo {
+            device_id: value.device_id.to_string(),
+            vendor_id: value.vendor_id as u32,
+            product_id: value.product_id as u32,
+            manufacturer: value.manufacturer,
+            product: value.product,
+            serial_number: value.serial_number,
+            firmware_version: value.firmware_version,
+            protocol_version: value.fsct_protocol_version as u32,
+            functionality: value.functionality,
+            text_encoding: value.text_encoding.into(),
+            texts: text_limits(value.supported_texts),
+        }
+    }
+}
+
+#[napi(string_enum)]
+pub enum DeviceEventType {
+    /// A device was attached
+    Added,
+    /// A device was removed
+    Removed,
+    /// The firmware of the device reported an error
+    DeviceError,
+    /// The device sent a playback command
+    DeviceCommand,
+    /// The volume was changed on the device
+    VolumeChanged,
+}
+
+#[napi(object)]
+pub struct DeviceEventInfo {
+    pub event_type: DeviceEventType,
+    pub device_id: String,
+}
+
+impl DeviceEventInfo {
+    /// Events of devices JavaScript is told about; bring-up steps, watch interruptions and firmware updates are not.
+    pub fn from_event(event: &DeviceEvent) -> Option<Self> {
+        let (event_type, device_id) = match event {
+            DeviceEvent::Added(device_id) => (DeviceEventType::Added, device_id),
+            DeviceEvent::Removed(device_id) => (DeviceEventType::Removed, device_id),
+            DeviceEvent::DeviceError { device_id, .. } => (DeviceEventType::DeviceError, device_id),
+            DeviceEvent::DeviceCommand { device_id, .. } => (DeviceEventType::DeviceCommand, device_id),
+            DeviceEvent::VolumeChanged { device_id, .. } => (DeviceEventType::VolumeChanged, device_id),
+            DeviceEvent::WarmupStep { .. } | DeviceEvent::WatchInterrupted | DeviceEvent::FirmwareUpdate { .. } => {
+                return None;
+            }
+        };
+        Some(DeviceEventInfo { event_type, device_id: device_id.to_string() })
+    }
+}
+
+#[napi(string_enum)]
+pub enum ControlCommandType {
+    Play,
+    Pause,
+    Next,
+    Previous,
+    /// Seek to `position`
+    Seek,
+    /// Set the volume to `volume`
+    SetVolume,
+}
+
+/// Playback command of a device showing the player, e.g. a button press.
+#[napi(object)]
+pub struct ControlCommand {
+    pub command: ControlCommandType,
+    /// Position in seconds from track start, for `Seek`
+    pub position: Option<f64>,
+    /// Volume in percent (0-100), for `SetVolume`
+    pub volume: Option<u32>,
+    /// Whether the player is muted, for `SetVolume`
+    pub muted: Option<bool>,
+}
+
+impl From<PlaybackCommand> for ControlCommand {
+    fn from(value: PlaybackCommand) -> Self {
+        let command = |command| ControlCommand { command, position: None, volume: None, muted: None };
+        match value {
+            PlaybackCommand::Play => command(ControlCommandType::Play),
+            PlaybackCommand::Pause => command(ControlCommandType::Pause),
+            PlaybackCommand::Next => command(ControlCommandType::Next),
+            PlaybackCommand::Previous => command(ControlCommandType::Previous),
+            PlaybackCommand::Seek { position } => ControlCommand {
+                position: Some(position.as_secs_f64()),
+                ..command(ControlCommandType::Seek)
+            },
+            PlaybackCommand::SetVolume { volume } => ControlCommand {
+                volume: Some(volume.level as u32),
+                muted: Some(volume.muted),
+                ..command(ControlCommandType::SetVolume)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_types_convert_both_ways() {
+        let text_types = [
+            CurrentTextMetadata::Title,
+            CurrentTextMetadata::Author,
+            CurrentTextMetadata::Album,
+            CurrentTextMetadata::Genre,
+            CurrentTextMetadata::Year,
+            CurrentTextMetadata::Composer,
+            CurrentTextMetadata::NextTitle,
+            CurrentTextMetadata::NextAuthor,
+        ];
+        for text_type in text_types {
+            let fsct: FsctTextMetadata = text_type.into();
+            let back: FsctTextMetadata = CurrentTextMetadata::from_fsct(fsct).unwrap().into();
+            assert_eq!(back, fsct);
+        }
+        assert!(CurrentTextMetadata::from_fsct(FsctTextMetadata::QueueAlbum).is_none());
+    }
+
+    #[test]
+    fn track_texts_cover_every_text_type() {
+        let track = TrackInfo {
+            title: Some("Title".into()),
+            artist: Some("Artist".into()),
+            next_title: Some("Next".into()),
+            ..Default::default()
+        };
+        let texts = track.into_texts();
+        assert!(texts.iter().all(|(text_type, _)| CurrentTextMetadata::from_fsct(*text_type).is_some()));
+        let set: Vec<_> = texts.into_iter().filter_map(|(text_type, text)| Some((text_type, text?))).collect();
+        assert_eq!(set, [
+            (FsctTextMetadata::CurrentTitle, "Title".to_string()),
+            (FsctTextMetadata::CurrentAuthor, "Artist".to_string()),
+            (FsctTextMetadata::QueueTitle, "Next".to_string()),
+        ]);
+    }
+}
@@ -22,16 +22,45 @@ mod js_types;
 #[macro_use]
 extern crate napi_derive;
 
-use fsct_core::definitions::{FsctStatus, FsctTextMetadata};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use fsct_core::definitions::{FsctStatus, FsctTextMetadata, PlaybackCommand};
 use fsct_core::player_state::PlayerState;
-use fsct_core::{FsctDriver, LocalDriver, ManagedPlayerId, service::MultiServiceHandle};
+use fsct_core::remote::RemoteDriver;
+use fsct_core::validation::{validate_text, validate_timeline};
+use fsct_core::{DeviceEvent, FsctDriver, LocalDriver, ManagedPlayerId, PlayerInterface, service::MultiServiceHandle};
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::JsFunction;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use js_types::{CurrentTextMetadata, FsctTimelineInfo, PlayerStatus, TimelineInfo};
+use tokio::sync::broadcast::error::RecvError;
+use js_types::{
+    ControlCommand, CurrentTextMetadata, DeviceEventInfo, DeviceInfo, DeviceLimits, FsctTimelineInfo, PartialPlayerState,
+    PlayerStatus, TimelineInfo, TrackInfo,
+};
+
+type ControlCallback = ThreadsafeFunction<ControlCommand, ErrorStrategy::Fatal>;
+
+/// Hands playback commands of devices to the JS callback of the player.
+struct JsPlayerInterface {
+    control_callback: Arc<Mutex<Option<ControlCallback>>>,
+}
+
+#[async_trait]
+impl PlayerInterface for JsPlayerInterface {
+    async fn execute(&self, command: PlaybackCommand) -> Result<(), anyhow::Error> {
+        let callback = self.control_callback.lock().unwrap();
+        let callback = callback.as_ref().ok_or_else(|| anyhow!("No control command callback is set"))?;
+        callback.call(command.into(), ThreadsafeFunctionCallMode::NonBlocking);
+        Ok(())
+    }
+}
 
 pub struct NodePlayerImpl {
     current_state: Mutex<PlayerState>,
-    driver: Mutex<Option<Arc<LocalDriver>>>,
+    driver: Mutex<Option<Arc<dyn FsctDriver>>>,
     player_id: Mutex<Option<ManagedPlayerId>>,
+    control_callback: Arc<Mutex<Option<ControlCallback>>>,
 }
 
 impl NodePlayerImpl {
@@ -40,6 +69,7 @@ impl NodePlayerImpl {
             current_state: Mutex::new(PlayerState::default()),
             driver: Mutex::new(None),
             player_id: Mutex::new(None),
+            control_callback: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -51,12 +81,18 @@ impl NodePlayerImpl {
 
     async fn set_timeline(&self, timeline: Option<TimelineInfo>) -> napi::Result<()> {
         let timeline: Option<FsctTimelineInfo> = timeline.and_then(|v| v.try_into().ok());
+        if let Some(timeline) = &timeline {
+            validate_timeline(timeline).map_err(|e| napi::Error::from_reason(e.to_string()))?;
+        }
         self.current_state.lock().unwrap().timeline = timeline;
         self.push_state().await
     }
 
     async fn set_text(&self, text_type: CurrentTextMetadata, text: Option<String>) -> napi::Result<()> {
         let text_type: FsctTextMetadata = text_type.into();
+        if let Some(text) = &text {
+            validate_text(text).map_err(|e| napi::Error::from_reason(e.to_string()))?;
+        }
         *self
             .current_state
             .lock()
@@ -66,6 +102,49 @@ impl NodePlayerImpl {
         self.push_state().await
     }
 
+    async fn set_track(&self, track: TrackInfo) -> napi::Result<()> {
+        let texts = track.into_texts();
+        for text in texts.iter().filter_map(|(_, text)| text.as_deref()) {
+            validate_text(text).map_err(|e| napi::Error::from_reason(e.to_string()))?;
+        }
+        {
+            let mut state = self.current_state.lock().unwrap();
+            for (text_type, text) in texts {
+                *state.texts.get_mut_text(text_type) = text;
+            }
+        }
+        self.push_state().await
+    }
+
+    /// Applies the changed fields to the state, sent to the driver in a single update.
+    async fn update_state(&self, partial: PartialPlayerState) -> napi::Result<()> {
+        let timeline: Option<FsctTimelineInfo> = partial.timeline.map(TryInto::try_into).transpose()?;
+        if let Some(timeline) = &timeline {
+            validate_timeline(timeline).map_err(|e| napi::Error::from_reason(e.to_string()))?;
+        }
+        let texts: Vec<_> = partial
+            .track
+            .map(|track| track.into_texts().into_iter().filter(|(_, text)| text.is_some()).collect())
+            .unwrap_or_default();
+        for (_, text) in &texts {
+            validate_text(text.as_deref().unwrap_or_default())
+                .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+        }
+        {
+            let mut state = self.current_state.lock().unwrap();
+            if let Some(status) = partial.status {
+                state.status = status.into();
+            }
+            if timeline.is_some() || partial.clear_timeline == Some(true) {
+                state.timeline = timeline;
+            }
+            for (text_type, text) in texts {
+                *state.texts.get_mut_text(text_type) = text.filter(|text| !text.is_empty());
+            }
+        }
+        self.push_state().await
+    }
+
     async fn push_state(&self) -> napi::Result<()> {
         let state = self.current_state.lock().unwrap().clone();
         let driver_opt = self.driver.lock().unwrap().clone();
@@ -79,11 +158,15 @@ impl NodePlayerImpl {
         Ok(())
     }
 
-    async fn attach_driver_and_register(&self, driver: Arc<LocalDriver>, self_id: String) -> napi::Result<()> {
+    async fn attach_driver_and_register(&self, driver: Arc<dyn FsctDriver>, self_id: String) -> napi::Result<()> {
         let player_id = driver
             .register_player(self_id)
             .await
             .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+        let interface = JsPlayerInterface { control_callback: self.control_callback.clone() };
+        driver
+            .set_player_interface(player_id, Some(Arc::new(interface)))
+            .map_err(|e| napi::Error::from_reason(e.to_string()))?;
         *self.driver.lock().unwrap() = Some(driver);
         *self.player_id.lock().unwrap() = Some(player_id);
         // push initial default state
@@ -123,12 +206,34 @@ impl NodePlayer {
     ) -> napi::Result<()> {
         self.player_impl.set_text(text_type, text).await
     }
+
+    /// Replaces all texts of the current track at once; texts omitted from `track` are cleared.
+    #[napi]
+    pub async fn set_track(&self, track: TrackInfo) -> napi::Result<()> {
+        self.player_impl.set_track(track).await
+    }
+
+    /// Changes the given fields of the player state in a single update, e.g. status, timeline and texts on a
+    /// track change.
+    #[napi]
+    pub async fn update_state(&self, partial_state: PartialPlayerState) -> napi::Result<()> {
+        self.player_impl.update_state(partial_state).await
+    }
+
+    /// Calls `callback` with the playback commands of devices showing the player (play, pause, next, previous,
+    /// seek, volume), replacing the previous callback. Commands fail on the device side while no callback is set.
+    #[napi(ts_args_type = "callback: (command: ControlCommand) => void")]
+    pub fn on_control_command(&self, callback: JsFunction) -> napi::Result<()> {
+        let callback: ControlCallback = callback.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+        *self.player_impl.control_callback.lock().unwrap() = Some(callback);
+        Ok(())
+    }
 }
 
 
 #[napi]
 pub struct FsctService {
-    driver: Mutex<Option<Arc<LocalDriver>>>,
+    driver: Mutex<Option<Arc<dyn FsctDriver>>>,
     service_handle: Mutex<Option<MultiServiceHandle>>,
 }
 
@@ -155,12 +260,39 @@ impl From<LogLevelFilter> for log::LevelFilter {
     }
 }
 
+/// Where the log of the service goes, see [`FsctServiceOptions::log_target`].
+#[napi]
+pub enum LogTarget {
+    Stdout,
+    Systemd,
+}
+
+/// What [`FsctService::run_fsct`] runs in-process; everything is optional.
+#[napi(object)]
+#[derive(Default)]
+pub struct FsctServiceOptions {
+    /// Whether the service watches USB for FSCT devices; defaults to true. Hosts embedded in apps (e.g. Electron)
+    /// whose devices are driven by the system service turn it off or set `remoteEndpoint`.
+    pub usb: Option<bool>,
+    /// Driver server of a running system service (e.g. `http://127.0.0.1:50151`) the player is registered with,
+    /// instead of driving devices in-process.
+    pub remote_endpoint: Option<String>,
+    /// Bearer token for system services limiting who may use their driver server.
+    pub remote_token: Option<String>,
+    /// Self id the player is registered under; defaults to `node-js`.
+    pub self_id: Option<String>,
+    /// Installs a logger before the service starts; hosts with their own logger leave it unset.
+    pub log_target: Option<LogTarget>,
+    pub log_level: Option<LogLevelFilter>,
+}
+
 #[napi]
 pub fn init_stdout_logger() -> Result<(), napi::Error> {
-    env_logger::init();
-    Ok(())
+    env_logger::try_init().map_err(|e| napi::Error::from_reason(e.to_string()))
 }
 
+/// Logs to the systemd journal when the host runs one; systems without systemd (e.g. musl based streamers) get the
+/// stdout logger instead.
 #[allow(unreachable_code, unused_variables)]
 #[napi]
 pub fn init_systemd_logger(syslog_identifier: String) -> Result<(), napi::Error> {
@@ -168,14 +300,22 @@ pub fn init_systemd_logger(syslog_identifier: String) -> Result<(), napi::Error>
     {
         use systemd_journal_logger::JournalLog;
 
-        return JournalLog::new()?
-            .with_syslog_identifier(syslog_identifier)
-            .install().map_err(|e| napi::Error::from_reason(e.to_string()));
+        if std::path::Path::new(JOURNAL_SOCKET).exists() {
+            return JournalLog::new()?
+                .with_syslog_identifier(syslog_identifier)
+                .install().map_err(|e| napi::Error::from_reason(e.to_string()));
+        }
+        init_stdout_logger()?;
+        log::warn!("systemd journal not available, logging to stdout");
+        return Ok(());
     }
 
     Err(napi::Error::from_reason("systemd logger not supported on this platform"))
 }
 
+#[cfg(target_os = "linux")]
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+
 
 #[napi]
 pub fn set_log_level(level: LogLevelFilter) {
@@ -192,24 +332,47 @@ impl FsctService {
         }
     }
 
+    /// Starts the service and registers `player` with it; `options` select what runs in-process, by default
+    /// everything.
     #[napi]
-    pub async fn run_fsct(&self, player: &NodePlayer) -> napi::Result<()> {
+    pub async fn run_fsct(&self, player: &NodePlayer, options: Option<FsctServiceOptions>) -> napi::Result<()> {
         if self.service_handle.lock().unwrap().is_some() {
             return Err(napi::Error::from_reason("FSCT service already run"));
         }
+        let options = options.unwrap_or_default();
+        let self_id = options.self_id.unwrap_or_else(|| "node-js".to_string());
+        match options.log_target {
+            Some(LogTarget::Stdout) => init_stdout_logger()?,
+            Some(LogTarget::Systemd) => init_systemd_logger(self_id.clone())?,
+            None => {}
+        }
+        if let Some(level) = options.log_level {
+            set_log_level(level);
+        }
 
-        // Create driver and run background services
-        let driver = Arc::new(LocalDriver::with_new_managers());
-        let handle = driver
-            .run()
-            .await
-            .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+        // Connect to the system service, or create a driver and run background services
+        let (driver, handle): (Arc<dyn FsctDriver>, _) = match &options.remote_endpoint {
+            Some(endpoint) => {
+                let driver = match &options.remote_token {
+                    Some(token) => RemoteDriver::connect_with_token(endpoint.clone(), token).await,
+                    None => RemoteDriver::connect(endpoint.clone()).await,
+                };
+                let driver = driver.map_err(|e| {
+                    napi::Error::from_reason(format!("Failed to connect to the FSCT service at {}: {}", endpoint, e))
+                })?;
+                (Arc::new(driver), MultiServiceHandle::new())
+            }
+            None => {
+                let driver = Arc::new(
+                    LocalDriver::with_new_managers().with_usb_device_watch(options.usb.unwrap_or(true)),
+                );
+                let handle = driver.run().await.map_err(|e| napi::Error::from_reason(e.to_string()))?;
+                (driver, handle)
+            }
+        };
 
         // Register the node player with the driver and attach it
-        player
-            .player_impl
-            .attach_driver_and_register(driver.clone(), "node-js".to_string())
-            .await?;
+        player.player_impl.attach_driver_and_register(driver.clone(), self_id).await?;
 
         // Store driver and handle if still empty (avoid race)
         {
@@ -245,6 +408,112 @@ impl FsctService {
             .await
             .map_err(|e| napi::Error::from_reason(e.to_string()))
     }
+
+    /// Limits of the connected devices, e.g. for shortening texts before they get cut on the device.
+    #[napi]
+    pub fn get_device_limits(&self) -> napi::Result<Vec<DeviceLimits>> {
+        let driver = self.running_driver()?;
+        Ok(driver.get_device_limits().into_iter().map(DeviceLimits::from).collect())
+    }
+
+    /// Connected devices with their USB ids, protocol version, functionalities and text limits.
+    #[napi]
+    pub async fn list_devices(&self) -> napi::Result<Vec<DeviceInfo>> {
+        let driver = self.running_driver()?;
+        let devices = driver.list_devices().await.map_err(|e| napi::Error::from_reason(e.to_string()))?;
+        Ok(devices.into_iter().map(DeviceInfo::from).collect())
+    }
+
+    /// Calls `callback` for every device attached, removed, reporting an error or sending a playback command,
+    /// until the service is stopped.
+    #[napi(ts_args_type = "callback: (event: DeviceEventInfo) => void")]
+    pub fn on_device_event(&self, callback: JsFunction) -> napi::Result<()> {
+        let driver = self.running_driver()?;
+        let callback: ThreadsafeFunction<DeviceEventInfo, ErrorStrategy::Fatal> =
+            callback.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+        let mut events = driver.subscribe_device_events();
+        // the driver's device manager closes the channel once the service is stopped and the driver dropped
+        drop(driver);
+        napi::bindgen_prelude::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        if let Some(info) = DeviceEventInfo::from_event(&event) {
+                            callback.call(info, ThreadsafeFunctionCallMode::NonBlocking);
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => log::warn!("Missed {} device events", skipped),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Calls `callback` with the descriptor of every device attached (`deviceAdded`) or removed (`deviceRemoved`),
+    /// until the service is stopped, e.g. for rendering the connected hardware on hotplug.
+    #[napi(ts_args_type = "event: 'deviceAdded' | 'deviceRemoved', callback: (device: DeviceInfo) => void")]
+    pub fn on(&self, event: String, callback: JsFunction) -> napi::Result<()> {
+        let added = match event.as_str() {
+            "deviceAdded" => true,
+            "deviceRemoved" => false,
+            _ => {
+                let reason = format!("Unknown event {}, expected deviceAdded or deviceRemoved", event);
+                return Err(napi::Error::from_reason(reason));
+            }
+        };
+        let driver = self.running_driver()?;
+        let callback: ThreadsafeFunction<DeviceInfo, ErrorStrategy::Fatal> =
+            callback.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+        let mut events = driver.subscribe_device_events();
+        // a weak reference, so the channel still closes once the service is stopped and the driver dropped
+        let driver = Arc::downgrade(&driver);
+        napi::bindgen_prelude::spawn(async move {
+            // records of the attached devices, kept to describe them once removed
+            let mut records = HashMap::new();
+            if let Some(driver) = driver.upgrade() {
+                let devices = driver.list_devices().await.unwrap_or_default();
+                records.extend(devices.into_iter().map(|record| (record.device_id, record)));
+            }
+            loop {
+                let (device_id, record, was_added) = match events.recv().await {
+                    Ok(DeviceEvent::Added(device_id)) => {
+                        let Some(driver) = driver.upgrade() else { break };
+                        let devices = driver.list_devices().await.unwrap_or_default();
+                        let record = devices.into_iter().find(|record| record.device_id == device_id);
+                        if let Some(record) = &record {
+                            records.insert(device_id, record.clone());
+                        }
+                        (device_id, record, true)
+                    }
+                    Ok(DeviceEvent::Removed(device_id)) => (device_id, records.remove(&device_id), false),
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("Missed {} device events", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if was_added != added {
+                    continue;
+                }
+                if let Some(record) = record {
+                    callback.call(DeviceInfo::from(record), ThreadsafeFunctionCallMode::NonBlocking);
+                } else {
+                    log::warn!("No attach record of device {}", device_id);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    fn running_driver(&self) -> napi::Result<Arc<dyn FsctDriver>> {
+        self.driver
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| napi::Error::from_reason("FSCT service not run"))
+    }
 }
 
 #[napi]
@@ -0,0 +1,34 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Building blocks for player ports.
+//!
+//! A port watches one media backend (an OS media API, a network player, ...) and reports its state to an
+//! [`FsctDriver`](fsct_core::FsctDriver). This crate covers the parts every port needs, so a port only has to
+//! implement talking to its backend:
+//!
+//! - [`PortPlayer`]: player registration and state diffing, sending only what actually changed,
+//! - [`Backoff`] and [`run_reconnecting`]: reconnect loops for backends that come and go,
+//! - [`spawn_polling_port`]: a complete service for backends that have to be polled.
+
+pub mod backoff;
+pub mod player;
+pub mod watcher;
+
+pub use backoff::Backoff;
+pub use player::PortPlayer;
+pub use watcher::{run_reconnecting, spawn_polling_port};
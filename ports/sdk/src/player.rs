@@ -0,0 +1,153 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+use std::sync::Arc;
+
+use anyhow::Error;
+use fsct_core::definitions::{FsctStatus, FsctTextMetadata, PlaybackModes, TimelineInfo, VolumeInfo};
+use fsct_core::player_state::PlayerState;
+use fsct_core::{FsctDriver, ManagedPlayerId, PlayerInterface};
+
+/// A player registered by a port, remembering what was last sent so unchanged values are not sent again.
+pub struct PortPlayer {
+    driver: Arc<dyn FsctDriver>,
+    player_id: ManagedPlayerId,
+    state: PlayerState,
+}
+
+impl PortPlayer {
+    pub async fn register(driver: Arc<dyn FsctDriver>, self_id: impl Into<String>) -> Result<Self, Error> {
+        let player_id = driver.register_player(self_id.into()).await?;
+        Ok(Self { driver, player_id, state: PlayerState::default() })
+    }
+
+    pub fn id(&self) -> ManagedPlayerId {
+        self.player_id
+    }
+
+    /// Lets devices showing the player control the backend through `interface`.
+    pub fn set_interface(&self, interface: Arc<dyn PlayerInterface>) -> Result<(), Error> {
+        self.driver.set_player_interface(self.player_id, Some(interface))
+    }
+
+    /// State as last sent to the driver.
+    pub fn state(&self) -> &PlayerState {
+        &self.state
+    }
+
+    /// Sends the whole state if it differs from the last one; returns whether it was sent.
+    pub async fn update(&mut self, state: PlayerState) -> Result<bool, Error> {
+        if state == self.state {
+            return Ok(false);
+        }
+        self.driver.update_player_state(self.player_id, state.clone()).await?;
+        self.state = state;
+        Ok(true)
+    }
+
+    /// Sends only the parts of the state that changed, as partial updates.
+    ///
+    /// Preferable over [`update`](Self::update) for backends reporting changes piecemeal, as the orchestrator
+    /// then updates devices partially too.
+    pub async fn update_partial(&mut self, state: PlayerState) -> Result<(), Error> {
+        self.set_status(state.status).await?;
+        self.set_timeline(state.timeline).await?;
+        self.set_volume(state.volume).await?;
+        self.set_modes(state.modes).await?;
+        for (text_id, text) in state.texts.iter() {
+            self.set_text(text_id, text.clone()).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn set_status(&mut self, status: FsctStatus) -> Result<(), Error> {
+        if status != self.state.status {
+            self.driver.update_player_status(self.player_id, status).await?;
+            self.state.status = status;
+        }
+        Ok(())
+    }
+
+    pub async fn set_timeline(&mut self, timeline: Option<TimelineInfo>) -> Result<(), Error> {
+        if timeline != self.state.timeline {
+            self.driver.update_player_timeline(self.player_id, timeline.clone()).await?;
+            self.state.timeline = timeline;
+        }
+        Ok(())
+    }
+
+    pub async fn set_volume(&mut self, volume: Option<VolumeInfo>) -> Result<(), Error> {
+        if volume != self.state.volume {
+            self.driver.update_player_volume(self.player_id, volume).await?;
+            self.state.volume = volume;
+        }
+        Ok(())
+    }
+
+    pub async fn set_modes(&mut self, modes: PlaybackModes) -> Result<(), Error> {
+        if modes != self.state.modes {
+            self.driver.update_player_modes(self.player_id, modes).await?;
+            self.state.modes = modes;
+        }
+        Ok(())
+    }
+
+    pub async fn set_text(&mut self, text_id: FsctTextMetadata, text: Option<String>) -> Result<(), Error> {
+        if *self.state.texts.get_text(text_id) != text {
+            self.driver.update_player_metadata(self.player_id, text_id, text.clone()).await?;
+            *self.state.texts.get_mut_text(text_id) = text;
+        }
+        Ok(())
+    }
+
+    /// Resets the player to an empty state, e.g. after losing the connection to the backend.
+    pub async fn clear(&mut self) -> Result<(), Error> {
+        self.update(PlayerState::default()).await.map(|_| ())
+    }
+
+    pub async fn unregister(self) -> Result<(), Error> {
+        self.driver.unregister_player(self.player_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fsct_core::{LocalDriver, PlayerEvent};
+
+    #[tokio::test]
+    async fn unchanged_values_are_not_sent_again() {
+        let driver = Arc::new(LocalDriver::with_new_managers());
+        let mut events = driver.subscribe_player_events();
+        let mut player = PortPlayer::register(driver.clone(), "port").await.unwrap();
+        let state = PlayerState { status: FsctStatus::Playing, ..Default::default() };
+
+        assert!(player.update(state.clone()).await.unwrap());
+        assert!(!player.update(state.clone()).await.unwrap());
+        player.set_status(FsctStatus::Playing).await.unwrap();
+        player.set_text(FsctTextMetadata::CurrentTitle, Some("Song".into())).await.unwrap();
+        player.set_text(FsctTextMetadata::CurrentTitle, Some("Song".into())).await.unwrap();
+        player.unregister().await.unwrap();
+
+        assert!(matches!(events.recv().await, Ok(PlayerEvent::Registered { .. })));
+        assert!(matches!(events.recv().await, Ok(PlayerEvent::StateUpdated { .. })));
+        assert!(matches!(events.recv().await, Ok(PlayerEvent::TextMetadataUpdated { .. })));
+        assert!(matches!(events.recv().await, Ok(PlayerEvent::TrackStarted { .. })));
+        assert!(matches!(events.recv().await, Ok(PlayerEvent::TrackEnded { .. })));
+        assert!(matches!(events.recv().await, Ok(PlayerEvent::Unregistered { .. })));
+    }
+}
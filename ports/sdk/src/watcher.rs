@@ -0,0 +1,145 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use anyhow::Error;
+use fsct_core::player_state::PlayerState;
+use fsct_core::polling::PollingSettings;
+use fsct_core::service::{spawn_service, ServiceHandle, StopHandle};
+use fsct_core::{FsctDriver, PlayerInterface};
+use log::{error, warn};
+
+use crate::backoff::Backoff;
+use crate::player::PortPlayer;
+
+/// Runs backend sessions one after another until stopped.
+///
+/// `session` connects to the backend and follows it until the connection ends. A session ending with `Ok` is
+/// restarted right away; after an error the next session starts after the backoff delay.
+pub async fn run_reconnecting<F, Fut>(stop: &mut StopHandle, mut backoff: Backoff, mut session: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), Error>>,
+{
+    loop {
+        let result = tokio::select! {
+            _ = stop.signaled() => return,
+            result = session() => result,
+        };
+        match result {
+            Ok(()) => backoff.reset(),
+            Err(e) => {
+                let delay = backoff.next_delay();
+                warn!("Backend session failed: {}, reconnecting in {:?}", e, delay);
+                tokio::select! {
+                    _ = stop.signaled() => return,
+                    _ = tokio::time::sleep(delay) => {}
+                }
+            }
+        }
+    }
+}
+
+/// Spawns a complete port service for a backend that has to be polled.
+///
+/// Registers a player as `self_id`, controlled through `interface` if given, and calls `poll` with the current
+/// polling settings, sending each returned state if it changed; `None` means nothing is playing. While polling fails
+/// the player is cleared and polls are retried with backoff. The player is unregistered when the service stops.
+pub fn spawn_polling_port<P, Fut>(driver: Arc<dyn FsctDriver>, self_id: String, polling: PollingSettings,
+                                  interface: Option<Arc<dyn PlayerInterface>>, mut poll: P) -> ServiceHandle
+where
+    P: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Option<PlayerState>, Error>> + Send,
+{
+    spawn_service(move |mut stop| async move {
+        let mut player = match PortPlayer::register(driver, self_id.clone()).await {
+            Ok(player) => player,
+            Err(e) => {
+                error!("Failed to register player {}: {}", self_id, e);
+                return;
+            }
+        };
+        if let Some(interface) = interface
+            && let Err(e) = player.set_interface(interface) {
+            warn!("Failed to set interface of player {}: {}", self_id, e);
+        }
+        let mut backoff = Backoff::default();
+        loop {
+            let delay = match poll().await {
+                Ok(state) => {
+                    backoff.reset();
+                    if let Err(e) = player.update(state.unwrap_or_default()).await {
+                        warn!("Failed to update player {}: {}", self_id, e);
+                    }
+                    polling.get().next_delay()
+                }
+                Err(e) => {
+                    let delay = backoff.next_delay().max(polling.get().interval);
+                    warn!("Polling {} failed: {}, retrying in {:?}", self_id, e, delay);
+                    let _ = player.clear().await;
+                    delay
+                }
+            };
+            tokio::select! {
+                _ = stop.signaled() => break,
+                _ = tokio::time::sleep(delay) => {}
+            }
+        }
+        let _ = player.unregister().await;
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+    use fsct_core::definitions::FsctStatus;
+    use fsct_core::polling::PollingConfig;
+    use fsct_core::{LocalDriver, PlayerEvent};
+
+    #[tokio::test(start_paused = true)]
+    async fn polled_states_are_sent_and_failures_clear_the_player() {
+        let driver = Arc::new(LocalDriver::with_new_managers());
+        let mut events = driver.subscribe_player_events();
+        let polls = Arc::new(AtomicU32::new(0));
+        let counter = polls.clone();
+        let polling = PollingSettings::new(PollingConfig::new(Duration::from_secs(1), Duration::ZERO));
+        let handle = spawn_polling_port(driver.clone(), "poller".into(), polling, None, move || {
+            let n = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                match n {
+                    0 | 1 => Ok(Some(PlayerState { status: FsctStatus::Playing, ..Default::default() })),
+                    _ => Err(anyhow::anyhow!("backend gone")),
+                }
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(2500)).await;
+        handle.shutdown().await.unwrap();
+
+        assert_eq!(polls.load(Ordering::SeqCst), 3);
+        assert!(matches!(events.recv().await, Ok(PlayerEvent::Registered { .. })));
+        assert!(matches!(events.recv().await,
+            Ok(PlayerEvent::StateUpdated { state: PlayerState { status: FsctStatus::Playing, .. }, .. })));
+        assert!(matches!(events.recv().await,
+            Ok(PlayerEvent::StateUpdated { state, .. }) if state == PlayerState::default()));
+        assert!(matches!(events.recv().await, Ok(PlayerEvent::Unregistered { .. })));
+    }
+}
@@ -0,0 +1,46 @@
+// Follows Spotify Connect playback of an account on the attached FSCT devices.
+//
+// SPOTIFY_CLIENT_ID is the client id of a Spotify app with http://127.0.0.1:8888/callback as a redirect URI; the
+// token is kept in SPOTIFY_TOKEN_FILE (spotify_token.json by default). SPOTIFY_DEVICE follows only one device.
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use fsct_core::polling::PollingSettings;
+use fsct_core::{FsctDriver, LocalDriver};
+use fsct_port_spotify::auth::authorize_on_loopback;
+use fsct_port_spotify::port::DEFAULT_SPOTIFY_POLLING;
+use fsct_port_spotify::{run_spotify_port, SpotifyAuth, SpotifyClient, SpotifyPortConfig, Token};
+use log::{info, warn};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::init();
+
+    let client_id = std::env::var("SPOTIFY_CLIENT_ID").context("SPOTIFY_CLIENT_ID is not set")?;
+    let token_file = std::env::var("SPOTIFY_TOKEN_FILE").unwrap_or_else(|_| "spotify_token.json".to_string());
+    let token = match std::fs::read(&token_file).ok().and_then(|json| serde_json::from_slice::<Token>(&json).ok()) {
+        Some(token) => token,
+        None => authorize_on_loopback(&client_id, 8888, |url| println!("Authorize Spotify at: {}", url)).await?,
+    };
+    let save = move |token: &Token| {
+        if let Err(e) = serde_json::to_vec(token).map_err(anyhow::Error::from)
+                                                .and_then(|json| Ok(std::fs::write(&token_file, json)?)) {
+            warn!("Failed to save Spotify token: {}", e);
+        }
+    };
+    save(&token);
+    let auth = Arc::new(SpotifyAuth::new(client_id, token).with_refresh_callback(save));
+
+    let driver = Arc::new(LocalDriver::with_new_managers());
+    let handle = driver.run().await?;
+    let config = SpotifyPortConfig { device_name: std::env::var("SPOTIFY_DEVICE").ok() };
+    let port = run_spotify_port(driver.clone() as Arc<dyn FsctDriver>, Arc::new(SpotifyClient::new(auth)), config,
+                                PollingSettings::new(DEFAULT_SPOTIFY_POLLING));
+
+    info!("Spotify port is running. Press Ctrl+C to shut down.");
+    tokio::signal::ctrl_c().await.expect("failed to listen for ctrl_c");
+
+    port.shutdown().await.expect("failed to shutdown the port");
+    handle.shutdown().await.expect("failed to shutdown services");
+    Ok(())
+}
@@ -0,0 +1,280 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! OAuth authorization code flow with PKCE and refreshing of access tokens.
+//!
+//! [`authorize_on_loopback`] runs the whole flow for desktop hosts: the user opens the returned authorization URL
+//! in a browser, Spotify redirects to `http://127.0.0.1:<port>/callback` (which has to be registered as a redirect
+//! URI of the Spotify app) and the code is exchanged for a [`Token`]. Hosts persist the token, as refreshed tokens
+//! replace it (see [`SpotifyAuth::with_refresh_callback`]).
+
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, bail, Error};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use log::{debug, info};
+use rand::distr::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+use url::Url;
+
+pub const AUTHORIZE_URL: &str = "https://accounts.spotify.com/authorize";
+pub const TOKEN_URL: &str = "https://accounts.spotify.com/api/token";
+
+/// Scopes needed to read and control playback.
+pub const SCOPES: &str = "user-read-playback-state user-modify-playback-state";
+
+/// Access tokens are refreshed this long before they expire.
+const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Verifier and challenge of a single authorization (RFC 7636).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pkce {
+    pub verifier: String,
+    pub challenge: String,
+}
+
+impl Pkce {
+    /// A random verifier of 64 characters with its S256 challenge.
+    pub fn new() -> Self {
+        Self::from_verifier(random_string(64))
+    }
+
+    pub fn from_verifier(verifier: impl Into<String>) -> Self {
+        let verifier = verifier.into();
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        Self { verifier, challenge }
+    }
+}
+
+impl Default for Pkce {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn random_string(length: usize) -> String {
+    rand::rng().sample_iter(&Alphanumeric).take(length).map(char::from).collect()
+}
+
+/// URL the user authorizes the app at; Spotify redirects to `redirect_uri` with the code and `state`.
+pub fn authorize_url(client_id: &str, redirect_uri: &str, pkce: &Pkce, state: &str) -> String {
+    let mut url = Url::parse(AUTHORIZE_URL).expect("valid authorize URL");
+    url.query_pairs_mut()
+       .append_pair("client_id", client_id)
+       .append_pair("response_type", "code")
+       .append_pair("redirect_uri", redirect_uri)
+       .append_pair("code_challenge_method", "S256")
+       .append_pair("code_challenge", &pkce.challenge)
+       .append_pair("scope", SCOPES)
+       .append_pair("state", state);
+    url.into()
+}
+
+/// Tokens of an authorized account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Token {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_at: SystemTime,
+}
+
+impl Token {
+    fn needs_refresh(&self, now: SystemTime) -> bool {
+        !self.expires_at.duration_since(now).is_ok_and(|left| left > REFRESH_MARGIN)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    /// Missing on refreshes keeping the refresh token.
+    refresh_token: Option<String>,
+    expires_in: u64,
+}
+
+impl TokenResponse {
+    fn into_token(self, previous_refresh_token: Option<&str>, now: SystemTime) -> Result<Token, Error> {
+        let refresh_token = self.refresh_token
+                                .or_else(|| previous_refresh_token.map(str::to_string))
+                                .ok_or_else(|| anyhow!("Spotify returned no refresh token"))?;
+        Ok(Token {
+            access_token: self.access_token,
+            refresh_token,
+            expires_at: now + Duration::from_secs(self.expires_in),
+        })
+    }
+}
+
+fn form(pairs: &[(&str, &str)]) -> String {
+    url::form_urlencoded::Serializer::new(String::new()).extend_pairs(pairs).finish()
+}
+
+async fn request_token(http: &reqwest::Client, pairs: &[(&str, &str)]) -> Result<TokenResponse, Error> {
+    let response = http.post(TOKEN_URL)
+                       .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                       .body(form(pairs))
+                       .send()
+                       .await?;
+    if !response.status().is_success() {
+        bail!("Spotify token request failed: {} {}", response.status(), response.text().await.unwrap_or_default());
+    }
+    Ok(response.json().await?)
+}
+
+/// Exchanges the code of an authorization started with `pkce` for tokens.
+pub async fn exchange_code(http: &reqwest::Client, client_id: &str, code: &str, redirect_uri: &str, pkce: &Pkce)
+                           -> Result<Token, Error> {
+    let response = request_token(http, &[
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", redirect_uri),
+        ("client_id", client_id),
+        ("code_verifier", &pkce.verifier),
+    ]).await?;
+    response.into_token(None, SystemTime::now())
+}
+
+/// Code of the redirect request `GET /callback?code=...&state=... HTTP/1.1`, if its state matches.
+fn code_of_redirect(request_line: &str, state: &str) -> Result<String, Error> {
+    let target = request_line.split_whitespace().nth(1).ok_or_else(|| anyhow!("Malformed redirect request"))?;
+    let url = Url::parse("http://127.0.0.1")?.join(target)?;
+    let param = |name: &str| url.query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.into_owned());
+    if let Some(error) = param("error") {
+        bail!("Spotify authorization denied: {}", error);
+    }
+    if param("state").as_deref() != Some(state) {
+        bail!("Spotify authorization redirect has a wrong state");
+    }
+    param("code").ok_or_else(|| anyhow!("Spotify authorization redirect has no code"))
+}
+
+/// Authorizes the account of the user: binds `127.0.0.1:port`, passes the authorization URL to `open` (e.g. to
+/// print it or open a browser) and waits for the redirect to `http://127.0.0.1:<port>/callback`.
+pub async fn authorize_on_loopback(client_id: &str, port: u16, open: impl FnOnce(&str)) -> Result<Token, Error> {
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+    let redirect_uri = format!("http://127.0.0.1:{}/callback", port);
+    let pkce = Pkce::new();
+    let state = random_string(16);
+    open(&authorize_url(client_id, &redirect_uri, &pkce, &state));
+
+    let code = loop {
+        let (mut stream, _) = listener.accept().await?;
+        let mut request_line = String::new();
+        BufReader::new(&mut stream).read_line(&mut request_line).await?;
+        if !request_line.contains("/callback") {
+            debug!("Ignoring request on the authorization redirect port: {}", request_line.trim());
+            continue;
+        }
+        let result = code_of_redirect(&request_line, &state);
+        let body = match &result {
+            Ok(_) => "Spotify is authorized for FSCT, you can close this window.",
+            Err(_) => "Spotify authorization failed, see the log of the FSCT host.",
+        };
+        let response = format!("HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\n\
+                                Connection: close\r\n\r\n{}", body.len(), body);
+        let _ = stream.write_all(response.as_bytes()).await;
+        break result?;
+    };
+    let token = exchange_code(&reqwest::Client::new(), client_id, &code, &redirect_uri, &pkce).await?;
+    info!("Spotify account authorized");
+    Ok(token)
+}
+
+type RefreshCallback = Box<dyn Fn(&Token) + Send + Sync>;
+
+/// Tokens of the followed account, refreshed when the access token is about to expire.
+pub struct SpotifyAuth {
+    http: reqwest::Client,
+    client_id: String,
+    token: Mutex<Token>,
+    on_refresh: Option<RefreshCallback>,
+}
+
+impl SpotifyAuth {
+    pub fn new(client_id: impl Into<String>, token: Token) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            client_id: client_id.into(),
+            token: Mutex::new(token),
+            on_refresh: None,
+        }
+    }
+
+    /// Calls `on_refresh` with every refreshed token, e.g. to persist it.
+    pub fn with_refresh_callback(mut self, on_refresh: impl Fn(&Token) + Send + Sync + 'static) -> Self {
+        self.on_refresh = Some(Box::new(on_refresh));
+        self
+    }
+
+    pub async fn token(&self) -> Token {
+        self.token.lock().await.clone()
+    }
+
+    /// Current access token, refreshed first if it is about to expire.
+    pub async fn access_token(&self) -> Result<String, Error> {
+        let mut token = self.token.lock().await;
+        if token.needs_refresh(SystemTime::now()) {
+            debug!("Refreshing Spotify access token");
+            let response = request_token(&self.http, &[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", &token.refresh_token),
+                ("client_id", &self.client_id),
+            ]).await?;
+            *token = response.into_token(Some(&token.refresh_token), SystemTime::now())?;
+            if let Some(on_refresh) = &self.on_refresh {
+                on_refresh(&token);
+            }
+        }
+        Ok(token.access_token.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn challenge_is_s256_of_verifier() {
+        // example of RFC 7636, appendix B
+        let pkce = Pkce::from_verifier("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk");
+        assert_eq!(pkce.challenge, "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+        assert_eq!(Pkce::new().verifier.len(), 64);
+    }
+
+    #[test]
+    fn redirect_code_is_accepted_with_matching_state() {
+        assert_eq!(code_of_redirect("GET /callback?code=abc&state=xyz HTTP/1.1", "xyz").unwrap(), "abc");
+        assert!(code_of_redirect("GET /callback?code=abc&state=other HTTP/1.1", "xyz").is_err());
+        assert!(code_of_redirect("GET /callback?error=access_denied&state=xyz HTTP/1.1", "xyz").is_err());
+    }
+
+    #[test]
+    fn refreshes_keep_the_refresh_token() {
+        let now = SystemTime::now();
+        let response = TokenResponse { access_token: "new".into(), refresh_token: None, expires_in: 3600 };
+        let token = response.into_token(Some("refresh"), now).unwrap();
+        assert_eq!(token.refresh_token, "refresh");
+        assert!(!token.needs_refresh(now));
+        assert!(token.needs_refresh(now + Duration::from_secs(3550)));
+    }
+}
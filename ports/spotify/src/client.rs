@@ -0,0 +1,177 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Calls of the Spotify Web API reading and controlling playback.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{bail, Error};
+use async_trait::async_trait;
+use fsct_core::definitions::VolumeInfo;
+use fsct_core::PlayerInterface;
+use reqwest::{Method, StatusCode};
+use url::Url;
+
+use crate::auth::SpotifyAuth;
+use crate::playback::{Item, Playback, Queue};
+
+pub const API_URL: &str = "https://api.spotify.com/v1";
+
+/// Client of the Web API authorized as the followed account.
+pub struct SpotifyClient {
+    http: reqwest::Client,
+    auth: Arc<SpotifyAuth>,
+    api_url: String,
+}
+
+impl SpotifyClient {
+    pub fn new(auth: Arc<SpotifyAuth>) -> Self {
+        Self::with_api_url(auth, API_URL)
+    }
+
+    pub fn with_api_url(auth: Arc<SpotifyAuth>, api_url: impl Into<String>) -> Self {
+        Self { http: reqwest::Client::new(), auth, api_url: api_url.into() }
+    }
+
+    async fn request(&self, method: Method, path: &str, query: &[(&str, &str)]) -> Result<reqwest::Response, Error> {
+        let mut url = Url::parse(&format!("{}{}", self.api_url, path))?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        let response = self.http.request(method, url)
+                           .bearer_auth(self.auth.access_token().await?)
+                           // PUT and POST without a body need the length
+                           .header(reqwest::header::CONTENT_LENGTH, 0)
+                           .send()
+                           .await?;
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response.headers()
+                                      .get(reqwest::header::RETRY_AFTER)
+                                      .and_then(|value| value.to_str().ok()?.parse().ok())
+                                      .map(Duration::from_secs);
+            bail!("Spotify Web API rate limit exceeded, retry after {:?}", retry_after.unwrap_or_default());
+        }
+        if !status.is_success() {
+            bail!("Spotify Web API {} failed: {} {}", path, status, response.text().await.unwrap_or_default());
+        }
+        Ok(response)
+    }
+
+    /// Current playback of the account, `None` if nothing plays on any device.
+    pub async fn playback(&self) -> Result<Option<Playback>, Error> {
+        let response = self.request(Method::GET, "/me/player", &[("additional_types", "track,episode")]).await?;
+        if response.status() == StatusCode::NO_CONTENT {
+            return Ok(None);
+        }
+        Ok(Some(response.json().await?))
+    }
+
+    /// Track or episode playing after the current one, `None` if the queue is empty.
+    pub async fn next_in_queue(&self) -> Result<Option<Item>, Error> {
+        let response = self.request(Method::GET, "/me/player/queue", &[]).await?;
+        let queue: Queue = response.json().await?;
+        Ok(queue.queue.into_iter().next())
+    }
+
+    /// Runs a playback command on `device_id`, or on the active device if `None`.
+    async fn command(&self, method: Method, path: &str, device_id: Option<&str>, query: &[(&str, &str)])
+                     -> Result<(), Error> {
+        let mut query = query.to_vec();
+        if let Some(device_id) = device_id {
+            query.push(("device_id", device_id));
+        }
+        self.request(method, path, &query).await.map(|_| ())
+    }
+
+    pub async fn play(&self, device_id: Option<&str>) -> Result<(), Error> {
+        self.command(Method::PUT, "/me/player/play", device_id, &[]).await
+    }
+
+    pub async fn pause(&self, device_id: Option<&str>) -> Result<(), Error> {
+        self.command(Method::PUT, "/me/player/pause", device_id, &[]).await
+    }
+
+    pub async fn next(&self, device_id: Option<&str>) -> Result<(), Error> {
+        self.command(Method::POST, "/me/player/next", device_id, &[]).await
+    }
+
+    pub async fn previous(&self, device_id: Option<&str>) -> Result<(), Error> {
+        self.command(Method::POST, "/me/player/previous", device_id, &[]).await
+    }
+
+    pub async fn seek(&self, position: Duration, device_id: Option<&str>) -> Result<(), Error> {
+        let position_ms = position.as_millis().to_string();
+        self.command(Method::PUT, "/me/player/seek", device_id, &[("position_ms", position_ms.as_str())]).await
+    }
+
+    pub async fn set_volume(&self, volume_percent: u8, device_id: Option<&str>) -> Result<(), Error> {
+        let volume_percent = volume_percent.min(100).to_string();
+        self.command(Method::PUT, "/me/player/volume", device_id, &[("volume_percent", volume_percent.as_str())]).await
+    }
+}
+
+/// Controls playback on the Spotify Connect device the port follows.
+pub(crate) struct SpotifyPlayerInterface {
+    client: Arc<SpotifyClient>,
+    /// Device of the last polled playback.
+    device_id: Mutex<Option<String>>,
+}
+
+impl SpotifyPlayerInterface {
+    pub(crate) fn new(client: Arc<SpotifyClient>) -> Self {
+        Self { client, device_id: Mutex::new(None) }
+    }
+
+    pub(crate) fn set_device_id(&self, device_id: Option<String>) {
+        *self.device_id.lock().unwrap() = device_id;
+    }
+
+    fn device_id(&self) -> Option<String> {
+        self.device_id.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl PlayerInterface for SpotifyPlayerInterface {
+    async fn play(&self) -> Result<(), Error> {
+        self.client.play(self.device_id().as_deref()).await
+    }
+
+    async fn pause(&self) -> Result<(), Error> {
+        self.client.pause(self.device_id().as_deref()).await
+    }
+
+    async fn next_track(&self) -> Result<(), Error> {
+        self.client.next(self.device_id().as_deref()).await
+    }
+
+    async fn previous_track(&self) -> Result<(), Error> {
+        self.client.previous(self.device_id().as_deref()).await
+    }
+
+    async fn seek(&self, position: Duration) -> Result<(), Error> {
+        self.client.seek(position, self.device_id().as_deref()).await
+    }
+
+    async fn set_volume(&self, volume: VolumeInfo) -> Result<(), Error> {
+        // the Web API has no mute
+        let level = if volume.muted { 0 } else { volume.level };
+        self.client.set_volume(level, self.device_id().as_deref()).await
+    }
+}
@@ -0,0 +1,33 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Spotify player port: follows Spotify Connect playback of an account through the
+//! [Spotify Web API](https://developer.spotify.com/documentation/web-api), whichever device it plays on.
+//!
+//! The account is authorized once with the OAuth authorization code flow with PKCE ([`auth`]), which needs only the
+//! client id of a Spotify app, no secret. The port then polls the playback state ([`SpotifyClient::playback`]) and
+//! registers a single player, optionally following only one Spotify Connect device by name. Access tokens are
+//! refreshed by the port as they expire; devices showing the player control playback on the device Spotify plays on.
+
+pub mod auth;
+pub mod client;
+pub mod playback;
+pub mod port;
+
+pub use auth::{SpotifyAuth, Token};
+pub use client::SpotifyClient;
+pub use port::{run_spotify_port, SpotifyPortConfig};
@@ -0,0 +1,239 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Playback state as returned by `GET /me/player`, with what plays next from `GET /me/player/queue`, and its
+//! translation into [`PlayerState`].
+
+use std::time::{Duration, SystemTime};
+
+use fsct_core::definitions::{FsctStatus, PlaybackModes, RepeatMode, TimelineInfo, VolumeInfo};
+use fsct_core::player_state::{PlayerState, TrackMetadata};
+use serde::Deserialize;
+
+/// Playback state of the account, limited to what devices show.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Playback {
+    /// Spotify Connect device playing.
+    pub device: Option<Device>,
+    pub is_playing: bool,
+    pub progress_ms: Option<u64>,
+    pub shuffle_state: Option<bool>,
+    /// `off`, `track` or `context`.
+    pub repeat_state: Option<String>,
+    /// Track or episode; missing e.g. while an ad plays.
+    pub item: Option<Item>,
+    /// Track or episode playing next, see [`Queue`].
+    #[serde(skip)]
+    pub next: Option<Item>,
+}
+
+/// Queue of the account as returned by `GET /me/player/queue`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Queue {
+    /// Items playing after the current one, in order.
+    #[serde(default)]
+    pub queue: Vec<Item>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Device {
+    /// Missing for devices that can't be controlled through the Web API.
+    pub id: Option<String>,
+    pub name: String,
+    /// Missing for devices whose volume can't be read.
+    pub volume_percent: Option<u8>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Item {
+    pub name: String,
+    pub duration_ms: u64,
+    /// Artists of a track.
+    #[serde(default)]
+    pub artists: Vec<Artist>,
+    /// Album of a track.
+    pub album: Option<Album>,
+    /// Show of an episode.
+    pub show: Option<Show>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Artist {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Album {
+    pub name: String,
+    /// `YYYY`, `YYYY-MM` or `YYYY-MM-DD`.
+    pub release_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Show {
+    pub name: String,
+    pub publisher: Option<String>,
+}
+
+impl Item {
+    /// Artists of a track, or the publisher of an episode.
+    fn artist(&self) -> Option<String> {
+        if self.artists.is_empty() {
+            return self.show.as_ref().and_then(|show| show.publisher.clone());
+        }
+        Some(self.artists.iter().map(|artist| artist.name.as_str()).collect::<Vec<_>>().join(", "))
+    }
+}
+
+impl Playback {
+    /// Whether the playback is on the Spotify Connect device named `device_name`, ignoring case.
+    pub fn is_on_device(&self, device_name: &str) -> bool {
+        self.device.as_ref().is_some_and(|device| device.name.eq_ignore_ascii_case(device_name))
+    }
+
+    pub fn status(&self) -> FsctStatus {
+        match (self.is_playing, &self.item) {
+            (true, _) => FsctStatus::Playing,
+            (false, Some(_)) => FsctStatus::Paused,
+            (false, None) => FsctStatus::Stopped,
+        }
+    }
+
+    fn texts(&self) -> TrackMetadata {
+        let Some(item) = &self.item else { return TrackMetadata::default() };
+        let year = item.album.as_ref()
+                       .and_then(|album| album.release_date.as_deref()?.get(..4))
+                       .filter(|year| year.bytes().all(|b| b.is_ascii_digit()))
+                       .map(str::to_string);
+        TrackMetadata {
+            title: Some(item.name.clone()),
+            artist: item.artist(),
+            album: item.album.as_ref().map(|album| album.name.clone())
+                       .or_else(|| item.show.as_ref().map(|show| show.name.clone())),
+            year,
+            next_title: self.next.as_ref().map(|next| next.name.clone()),
+            next_artist: self.next.as_ref().and_then(Item::artist),
+            ..Default::default()
+        }
+    }
+
+    fn timeline(&self, now: SystemTime) -> Option<TimelineInfo> {
+        let item = self.item.as_ref()?;
+        Some(TimelineInfo {
+            position: Duration::from_millis(self.progress_ms.unwrap_or(0)),
+            update_time: now,
+            duration: (item.duration_ms > 0).then(|| Duration::from_millis(item.duration_ms)),
+            rate: if self.is_playing { 1.0 } else { 0.0 },
+        })
+    }
+
+    fn volume(&self) -> Option<VolumeInfo> {
+        let level = self.device.as_ref()?.volume_percent?;
+        Some(VolumeInfo { level: level.min(100), muted: false })
+    }
+
+    fn modes(&self) -> PlaybackModes {
+        let repeat = match self.repeat_state.as_deref() {
+            Some("off") => Some(RepeatMode::Off),
+            Some("track") => Some(RepeatMode::Track),
+            Some("context") => Some(RepeatMode::List),
+            _ => None,
+        };
+        PlaybackModes { shuffle: self.shuffle_state, repeat }
+    }
+
+    /// The player state, with the progress taken at `now`.
+    pub fn player_state(&self, now: SystemTime) -> PlayerState {
+        PlayerState {
+            status: self.status(),
+            timeline: self.timeline(now),
+            texts: self.texts(),
+            volume: self.volume(),
+            modes: self.modes(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn playback_is_translated_to_player_state() {
+        let now = SystemTime::now();
+        let mut playback: Playback = serde_json::from_str(r#"{
+            "device": { "id": "abc", "name": "Living Room", "is_active": true, "volume_percent": 40 },
+            "is_playing": true,
+            "progress_ms": 30500,
+            "shuffle_state": true,
+            "repeat_state": "context",
+            "currently_playing_type": "track",
+            "item": {
+                "name": "Song",
+                "duration_ms": 245000,
+                "artists": [{ "name": "Band" }, { "name": "Guest" }],
+                "album": { "name": "Album", "release_date": "2009-03-01" }
+            }
+        }"#).unwrap();
+
+        assert!(playback.is_on_device("living room"));
+        assert!(!playback.is_on_device("Kitchen"));
+        let state = playback.player_state(now);
+        assert_eq!(state.status, FsctStatus::Playing);
+        assert_eq!(state.texts.title.as_deref(), Some("Song"));
+        assert_eq!(state.texts.artist.as_deref(), Some("Band, Guest"));
+        assert_eq!(state.texts.album.as_deref(), Some("Album"));
+        assert_eq!(state.texts.year.as_deref(), Some("2009"));
+        assert_eq!(state.volume, Some(VolumeInfo { level: 40, muted: false }));
+        assert_eq!(state.modes, PlaybackModes { shuffle: Some(true), repeat: Some(RepeatMode::List) });
+        assert_eq!(state.timeline, Some(TimelineInfo {
+            position: Duration::from_millis(30_500),
+            update_time: now,
+            duration: Some(Duration::from_secs(245)),
+            rate: 1.0,
+        }));
+
+        let queue: Queue = serde_json::from_str(r#"{
+            "currently_playing": null,
+            "queue": [{ "name": "Next Song", "duration_ms": 180000, "artists": [{ "name": "Other Band" }] }]
+        }"#).unwrap();
+        playback.next = queue.queue.into_iter().next();
+        let texts = playback.player_state(now).texts;
+        assert_eq!(texts.next_title.as_deref(), Some("Next Song"));
+        assert_eq!(texts.next_artist.as_deref(), Some("Other Band"));
+
+        playback.is_playing = false;
+        assert_eq!(playback.player_state(now).timeline.unwrap().rate, 0.0);
+        assert_eq!(playback.status(), FsctStatus::Paused);
+
+        playback.item = None;
+        assert_eq!(playback.player_state(now), PlayerState { status: FsctStatus::Stopped, ..Default::default() });
+    }
+
+    #[test]
+    fn episodes_show_publisher_and_show() {
+        let playback: Playback = serde_json::from_str(r#"{
+            "is_playing": true,
+            "item": { "name": "Episode", "duration_ms": 0, "show": { "name": "Show", "publisher": "Studio" } }
+        }"#).unwrap();
+
+        let state = playback.player_state(SystemTime::now());
+        assert_eq!(state.texts.artist.as_deref(), Some("Studio"));
+        assert_eq!(state.texts.album.as_deref(), Some("Show"));
+        assert_eq!(state.timeline.unwrap().duration, None);
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! The port service following the playback of the account.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use fsct_core::polling::{PollingConfig, PollingSettings};
+use fsct_core::service::ServiceHandle;
+use fsct_core::FsctDriver;
+use fsct_port_sdk::spawn_polling_port;
+use log::debug;
+
+use crate::client::{SpotifyClient, SpotifyPlayerInterface};
+use crate::playback::Item;
+
+/// Id under which the player is registered.
+pub const SPOTIFY_SELF_ID: &str = "spotify-connect";
+
+/// Polling of the playback; slower polls keep well within the rate limit of the Web API.
+pub const DEFAULT_SPOTIFY_POLLING: PollingConfig =
+    PollingConfig::new(Duration::from_secs(2), Duration::from_millis(500));
+
+/// Settings of the Spotify port.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpotifyPortConfig {
+    /// Follows only playback on the Spotify Connect device of this name (ignoring case), e.g. the streamer the FSCT
+    /// device is attached to; playback on other devices is shown as nothing playing.
+    pub device_name: Option<String>,
+}
+
+/// What plays after `item`. The queue is read only when the item changes, to stay within the rate limit, so edits of
+/// the queue show with the next track.
+async fn next_in_queue(client: &SpotifyClient, known: &Mutex<Option<(Item, Option<Item>)>>, item: Item)
+                       -> Option<Item> {
+    let cached = known.lock().unwrap().as_ref()
+                      .filter(|(known_item, _)| *known_item == item)
+                      .map(|(_, next)| next.clone());
+    if let Some(next) = cached {
+        return next;
+    }
+    let next = client.next_in_queue().await
+                     .inspect_err(|e| debug!("Failed to read the Spotify queue: {}", e))
+                     .ok()
+                     .flatten();
+    *known.lock().unwrap() = Some((item, next.clone()));
+    next
+}
+
+/// Registers the Spotify player with `driver` and keeps it up to date with the playback of the account of `client`,
+/// polled as set by `polling`, until the service is stopped. Devices showing the queue get what plays next.
+pub fn run_spotify_port(driver: Arc<dyn FsctDriver>, client: Arc<SpotifyClient>, config: SpotifyPortConfig,
+                        polling: PollingSettings) -> ServiceHandle {
+    let interface = Arc::new(SpotifyPlayerInterface::new(client.clone()));
+    let followed = interface.clone();
+    let known_queue = Arc::new(Mutex::new(None));
+    spawn_polling_port(driver, SPOTIFY_SELF_ID.to_string(), polling, Some(interface), move || {
+        let client = client.clone();
+        let config = config.clone();
+        let followed = followed.clone();
+        let known_queue = known_queue.clone();
+        async move {
+            let mut playback = client.playback().await?.filter(|playback| {
+                config.device_name.as_deref().is_none_or(|device_name| playback.is_on_device(device_name))
+            });
+            let device_id = playback.as_ref().and_then(|playback| playback.device.as_ref()?.id.clone());
+            // a followed device keeps receiving the commands while it plays nothing, so devices can resume on it
+            if config.device_name.is_none() || device_id.is_some() {
+                followed.set_device_id(device_id);
+            }
+            if let Some(playback) = &mut playback
+                && let Some(item) = playback.item.clone()
+            {
+                playback.next = next_in_queue(&client, &known_queue, item).await;
+            }
+            Ok(playback.map(|playback| playback.player_state(SystemTime::now())))
+        }
+    })
+}
@@ -0,0 +1,149 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Connection of an app to the host.
+
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use anyhow::Error;
+use fsct_core::remote::RemoteDriver;
+use fsct_core::{FsctDriver, ManagedPlayerId};
+use log::warn;
+use thiserror::Error;
+use tokio::runtime::Runtime;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+use crate::types::{Device, DeviceEvent, PlayerState};
+
+#[derive(Debug, Error, uniffi::Error)]
+pub enum FsctError {
+    #[error("Failed to connect to the FSCT host: {0}")]
+    Connection(String),
+    #[error("FSCT host refused the request: {0}")]
+    Host(String),
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+}
+
+fn host_error(e: Error) -> FsctError {
+    FsctError::Host(e.to_string())
+}
+
+fn player_id(id: u32) -> Result<ManagedPlayerId, FsctError> {
+    ManagedPlayerId::new(id).ok_or_else(|| FsctError::InvalidArgument("Player id must not be 0".to_string()))
+}
+
+/// Receives the device events of the host.
+#[uniffi::export(with_foreign)]
+pub trait DeviceListener: Send + Sync {
+    fn on_device_event(&self, event: DeviceEvent);
+}
+
+/// Client of the driver of an FSCT host.
+#[derive(uniffi::Object)]
+pub struct FsctClient {
+    runtime: Runtime,
+    driver: RemoteDriver,
+    device_listener: Mutex<Option<JoinHandle<()>>>,
+}
+
+#[uniffi::export]
+impl FsctClient {
+    /// Connects to the host at `endpoint`, e.g. `http://127.0.0.1:50151`, presenting `token` to hosts limiting who
+    /// may use their driver server.
+    #[uniffi::constructor]
+    pub fn connect(endpoint: String, token: Option<String>) -> Result<Arc<Self>, FsctError> {
+        // the event feed of the driver and device listeners run on the worker while the app is not calling in
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .map_err(|e| FsctError::Connection(e.to_string()))?;
+        let driver = match token {
+            Some(token) => runtime.block_on(RemoteDriver::connect_with_token(endpoint, &token)),
+            None => runtime.block_on(RemoteDriver::connect(endpoint)),
+        };
+        let driver = driver.map_err(|e| FsctError::Connection(e.to_string()))?;
+        Ok(Arc::new(Self { runtime, driver, device_listener: Mutex::new(None) }))
+    }
+
+    /// Registers a player named `self_id`, e.g. the app's package name, returning its managed id.
+    pub fn register_player(&self, self_id: String) -> Result<u32, FsctError> {
+        let player_id = self.runtime.block_on(self.driver.register_player(self_id)).map_err(host_error)?;
+        Ok(player_id.get())
+    }
+
+    pub fn unregister_player(&self, player_id: u32) -> Result<(), FsctError> {
+        let player_id = self::player_id(player_id)?;
+        self.runtime.block_on(self.driver.unregister_player(player_id)).map_err(host_error)
+    }
+
+    /// Publishes the full state of a player, with its position taken now.
+    pub fn update_state(&self, player_id: u32, state: PlayerState) -> Result<(), FsctError> {
+        let player_id = self::player_id(player_id)?;
+        let state = state.into_driver_state(SystemTime::now());
+        self.runtime.block_on(self.driver.update_player_state(player_id, state)).map_err(host_error)
+    }
+
+    /// Sets the player shown on devices whenever it plays; `None` clears it.
+    pub fn set_preferred_player(&self, player_id: Option<u32>) -> Result<(), FsctError> {
+        let preferred = player_id.map(self::player_id).transpose()?;
+        self.runtime.block_on(self.driver.set_preferred_player_and_wait(preferred)).map_err(host_error)
+    }
+
+    /// Devices attached to the host.
+    pub fn devices(&self) -> Result<Vec<Device>, FsctError> {
+        let devices = self.runtime.block_on(self.driver.list_devices()).map_err(host_error)?;
+        Ok(devices.into_iter().map(Device::from).collect())
+    }
+
+    /// Passes the device events of the host to `listener`, replacing the previous listener.
+    pub fn subscribe_devices(&self, listener: Arc<dyn DeviceListener>) {
+        let mut events = self.driver.subscribe_device_events();
+        let task = self.runtime.spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        if let Some(event) = DeviceEvent::from_driver_event(event) {
+                            listener.on_device_event(event);
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => warn!("Device listener skipped {} events", skipped),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+        if let Some(previous) = self.device_listener.lock().unwrap().replace(task) {
+            previous.abort();
+        }
+    }
+
+    /// Stops passing device events to the listener.
+    pub fn unsubscribe_devices(&self) {
+        if let Some(listener) = self.device_listener.lock().unwrap().take() {
+            listener.abort();
+        }
+    }
+}
+
+impl Drop for FsctClient {
+    fn drop(&mut self) {
+        self.unsubscribe_devices();
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! UniFFI bindings of the driver client: apps register players with an FSCT host, publish their state and follow
+//! the host's devices, from Kotlin or Swift without hand-written FFI.
+//!
+//! The host serves its driver over gRPC (`fsct_core::remote::DriverServer`, port 50151 on loopback by default);
+//! `FsctClient` wraps a `RemoteDriver` connected to it. Bindings are generated from the built library:
+//!
+//! ```sh
+//! cargo build -p fsct-uniffi --release
+//! uniffi-bindgen generate --library target/release/libfsct_uniffi.so --language kotlin --out-dir out
+//! uniffi-bindgen generate --library target/release/libfsct_uniffi.so --language swift --out-dir out
+//! ```
+//!
+//! Calls block until the host answered, so apps make them off their UI thread. Device listeners are called on a
+//! worker thread of the client.
+
+uniffi::setup_scaffolding!();
+
+mod client;
+mod types;
+
+pub use client::{DeviceListener, FsctClient, FsctError};
+pub use types::{Device, DeviceEvent, PlayerState, PlayerStatus, Timeline};
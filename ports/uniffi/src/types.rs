@@ -0,0 +1,176 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Records passed over the bindings.
+
+use std::time::{Duration, SystemTime};
+
+use fsct_core::definitions::{FsctStatus, TimelineInfo};
+use fsct_core::device_history::DeviceAttachRecord;
+use fsct_core::player_state::{self, TrackMetadata};
+
+/// Playback status of a player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum PlayerStatus {
+    Stopped,
+    Playing,
+    Paused,
+    Seeking,
+    Buffering,
+    Error,
+    Unknown,
+}
+
+impl From<PlayerStatus> for FsctStatus {
+    fn from(status: PlayerStatus) -> Self {
+        match status {
+            PlayerStatus::Stopped => FsctStatus::Stopped,
+            PlayerStatus::Playing => FsctStatus::Playing,
+            PlayerStatus::Paused => FsctStatus::Paused,
+            PlayerStatus::Seeking => FsctStatus::Seeking,
+            PlayerStatus::Buffering => FsctStatus::Buffering,
+            PlayerStatus::Error => FsctStatus::Error,
+            PlayerStatus::Unknown => FsctStatus::Unknown,
+        }
+    }
+}
+
+/// Playback position of a player, taken when the state is published.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct Timeline {
+    pub position_ms: u64,
+    /// None for live streams.
+    pub duration_ms: Option<u64>,
+    pub rate: f64,
+}
+
+/// Full state of a player: status, position and the texts of the current track.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct PlayerState {
+    pub status: PlayerStatus,
+    pub timeline: Option<Timeline>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub genre: Option<String>,
+    pub year: Option<String>,
+    pub composer: Option<String>,
+}
+
+impl PlayerState {
+    /// State of the driver, with the position taken at `now`.
+    pub(crate) fn into_driver_state(self, now: SystemTime) -> player_state::PlayerState {
+        player_state::PlayerState {
+            status: self.status.into(),
+            timeline: self.timeline.map(|timeline| TimelineInfo {
+                position: Duration::from_millis(timeline.position_ms),
+                update_time: now,
+                duration: timeline.duration_ms.map(Duration::from_millis),
+                rate: timeline.rate,
+            }),
+            texts: TrackMetadata {
+                title: self.title,
+                artist: self.artist,
+                album: self.album,
+                genre: self.genre,
+                year: self.year,
+                composer: self.composer,
+                ..Default::default()
+            },
+            volume: None,
+            modes: Default::default(),
+        }
+    }
+}
+
+/// Device attached to the host.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct Device {
+    /// Managed id of the device, a UUID.
+    pub device_id: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<String>,
+    pub firmware_version: String,
+}
+
+impl From<DeviceAttachRecord> for Device {
+    fn from(record: DeviceAttachRecord) -> Self {
+        Self {
+            device_id: record.device_id.to_string(),
+            vendor_id: record.vendor_id,
+            product_id: record.product_id,
+            manufacturer: record.manufacturer,
+            product: record.product,
+            serial_number: record.serial_number,
+            firmware_version: record.firmware_version,
+        }
+    }
+}
+
+/// Change of the devices of the host.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Enum)]
+pub enum DeviceEvent {
+    Added { device_id: String },
+    Removed { device_id: String },
+    /// The firmware of the device reported an error.
+    Error { device_id: String, message: String },
+}
+
+impl DeviceEvent {
+    /// Event of the bindings, if apps are told about `event`; warm-up steps and commands stay in the host.
+    pub(crate) fn from_driver_event(event: fsct_core::DeviceEvent) -> Option<Self> {
+        match event {
+            fsct_core::DeviceEvent::Added(device_id) => Some(Self::Added { device_id: device_id.to_string() }),
+            fsct_core::DeviceEvent::Removed(device_id) => Some(Self::Removed { device_id: device_id.to_string() }),
+            fsct_core::DeviceEvent::DeviceError { device_id, error } => Some(Self::Error {
+                device_id: device_id.to_string(),
+                message: format!("{:?} (detail {}, {} times)", error.code, error.detail, error.count),
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn player_state_becomes_driver_state() {
+        let now = SystemTime::now();
+        let state = PlayerState {
+            status: PlayerStatus::Playing,
+            timeline: Some(Timeline { position_ms: 12500, duration_ms: None, rate: 1.0 }),
+            title: Some("Song".to_string()),
+            artist: Some("Band".to_string()),
+            album: None,
+            genre: None,
+            year: Some("1999".to_string()),
+            composer: None,
+        };
+        let state = state.into_driver_state(now);
+        assert_eq!(state.status, FsctStatus::Playing);
+        assert_eq!(state.texts.year.as_deref(), Some("1999"));
+        let timeline = state.timeline.unwrap();
+        assert_eq!(timeline.position, Duration::from_millis(12500));
+        assert_eq!(timeline.update_time, now);
+        assert_eq!(timeline.duration, None);
+    }
+}
@@ -6,7 +6,7 @@
 #   1. Building the Rust service
 #   2. Signing the executable (if enabled) - supports both local certificate and Azure Key Vault
 #   3. Downloading the Visual C++ Redistributable
-#   4. Creating an MSI installer using WiX Toolset v6.0
+#   4. Creating an MSI installer using WiX Toolset v6.0 (service registration, log directory, optional tray app)
 #   5. Creating an EXE bundle installer
 #   6. Signing the installers (if enabled) - supports both local certificate and Azure Key Vault
 #
@@ -33,7 +33,8 @@ param(
     [string]$AzureCertificateName = "",
     [string]$AzureTenantId = "",
     [string]$AzureClientId = "",
-    [string]$AzureClientSecret = ""
+    [string]$AzureClientSecret = "",
+    [string]$TrayExe = ""
 )
 
 echo $PSVersionTable
@@ -62,6 +63,7 @@ try
         Write-Host "  -AzureTenantId       Azure tenant ID (required when using Azure Key Vault)"
         Write-Host "  -AzureClientId       Azure client ID (required when using Azure Key Vault)"
         Write-Host "  -AzureClientSecret   Azure client secret (required when using Azure Key Vault)"
+        Write-Host "  -TrayExe             Path to the tray app executable to install and autostart (optional)"
         Write-Host "  -Help                Display this help message"
         Write-Host ""
         Write-Host "Examples:"
@@ -392,6 +394,28 @@ try
         exit 1
     }
 
+    # === Tray app ===
+    $trayDefine = @()
+    if (-not [string]::IsNullOrEmpty($TrayExe))
+    {
+        if (-not (Test-Path $TrayExe))
+        {
+            Write-Error "[ERROR] Tray app not found: $TrayExe"
+            exit 1
+        }
+        Copy-Item $TrayExe "$BUILD_DIR\fsct_tray.exe" -Force
+        if (-not (Sign-File -FilePath "$BUILD_DIR\fsct_tray.exe" -Description "tray app EXE"))
+        {
+            exit 1
+        }
+        $trayDefine = @("-d", "TrayExe=fsct_tray.exe")
+        Write-Host "[INFO] Tray app will be installed with autostart"
+    }
+    else
+    {
+        Write-Host "[INFO] No tray app given, installer will contain the service only"
+    }
+
     # === Copying WiX source files ===
     Write-Host "[INFO] Copying WiX source files..."
     try
@@ -528,7 +552,7 @@ For complete license information, please build with license generation enabled.
 
     # === WiX Compilation ===
     Write-Host "[INFO] Compiling WiX files..."
-    $msiResult = & wix build -arch x64 -d Version=$installerVersion -ext WixToolset.Util.wixext         `
+    $msiResult = & wix build -arch x64 -d Version=$installerVersion @trayDefine -ext WixToolset.Util.wixext `
         -o FSCTServiceInstaller.msi fsct_service_installer.wxs 2>&1
     if ($LASTEXITCODE -ne 0)
     {
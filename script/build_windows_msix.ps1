@@ -0,0 +1,295 @@
+#
+# FSCT Driver Service Windows MSIX Build Script
+#
+# This script builds the FSCT Driver Service and packages it, optionally with the tray app, as MSIX for enterprise
+# deployment tooling (Intune, App Installer) and the Microsoft Store.
+# It handles:
+#   1. Building the Rust service
+#   2. Laying out the package (executables, licenses, assets, AppxManifest.xml)
+#   3. Packing it with makeappx
+#   4. Signing the executables and the package (if enabled) - supports both local certificate and Azure Key Vault
+#
+# Store submissions are built with -Store: the package is left unsigned (the Store signs it) and gets revision 0,
+# as required by Partner Center.
+#
+# Requirements:
+#   - Rust (cargo)
+#       - cargo about
+#   - Windows SDK (makeappx)
+#   - signtool (if using local certificate signing)
+#   - AzureSignTool (if using Azure Key Vault signing)
+#
+param(
+    [Parameter()][int]$BuildNumber = 0,
+    [Parameter(Mandatory = $true)][string]$AssetsDir,
+    [string]$Publisher = "CN=HEM Sp. z o.o., O=HEM Sp. z o.o., L=Szczecin, C=PL",
+    [string]$LogLevel = "info",
+    [string]$ServiceExtraArgs = "",
+    [switch]$Store,
+    [switch]$NoSign,
+    [switch]$NoLicense,
+    [switch]$Help,
+    [switch]$UseAzureKeyVault,
+    [string]$AzureKeyVaultUrl = "",
+    [string]$AzureCertificateName = "",
+    [string]$AzureTenantId = "",
+    [string]$AzureClientId = "",
+    [string]$AzureClientSecret = "",
+    [string]$TrayExe = ""
+)
+
+# Store initial location
+$initialLocation = Get-Location
+$scriptLocation = Split-Path -Parent $MyInvocation.MyCommand.Path
+$projectLocation = Split-Path -Parent $scriptLocation
+
+# Ensure we return to initial location on exit
+try
+{
+    # Display help information if requested
+    if ($Help)
+    {
+        Write-Host "FSCT Driver Service Windows MSIX Build Script"
+        Write-Host "Usage: .\build_windows_msix.ps1 -AssetsDir <dir> [-Store] [-NoSign] [-NoLicense] [-UseAzureKeyVault] [-Help]"
+        Write-Host ""
+        Write-Host "Options:"
+        Write-Host "  -AssetsDir           Directory with StoreLogo.png, Square150x150Logo.png and Square44x44Logo.png"
+        Write-Host "  -Publisher           Package publisher, must match the subject of the signing certificate"
+        Write-Host "  -LogLevel            Log level of the service (default: info)"
+        Write-Host "  -ServiceExtraArgs    Additional service arguments, e.g. '--exclude-app Teams.exe'"
+        Write-Host "  -Store               Build for Microsoft Store submission (unsigned package, revision 0)"
+        Write-Host "  -NoSign              Skip signing of executables and the package"
+        Write-Host "  -NoLicense           Skip generating license files (assumes they already exist)"
+        Write-Host "  -UseAzureKeyVault    Use Azure Key Vault for signing instead of local certificate"
+        Write-Host "  -AzureKeyVaultUrl    Azure Key Vault URL (required when using Azure Key Vault)"
+        Write-Host "  -AzureCertificateName Certificate name in Azure Key Vault (required when using Azure Key Vault)"
+        Write-Host "  -AzureTenantId       Azure tenant ID (required when using Azure Key Vault)"
+        Write-Host "  -AzureClientId       Azure client ID (required when using Azure Key Vault)"
+        Write-Host "  -AzureClientSecret   Azure client secret (required when using Azure Key Vault)"
+        Write-Host "  -TrayExe             Path to the tray app executable to package and autostart (optional)"
+        Write-Host "  -Help                Display this help message"
+        Write-Host ""
+        Write-Host "Examples:"
+        Write-Host "  .\build_windows_msix.ps1 -AssetsDir .\assets -NoSign"
+        Write-Host "  .\build_windows_msix.ps1 -AssetsDir .\assets -TrayExe ..\fsct-tray\fsct_tray.exe -Store"
+        exit 0
+    }
+
+    # === Configuration ===
+    $PROJECT_NAME = "fsct_driver_service"
+    $SIGN_CERT_THUMBPRINT = "aef0182f5de48143c336a56f9ef5b706a9eb0403"
+    $TIMESTAMP_URL = "http://timestamp.globalsign.com/tsa/r6advanced1"
+    $SIGN_ENABLED = -not ($NoSign -or $Store)
+    $LICENSE_ENABLE = -not $NoLicense
+
+    $PROJECT_DIR = $projectLocation
+    $MSIX_SOURCE_DIR = Join-Path $projectLocation "ports\native\packages\windows\msix"
+    $BUILD_DIR = Join-Path $projectLocation "target\msix_build"
+    $LAYOUT_DIR = Join-Path $BUILD_DIR "layout"
+    $MSIX_FILE = Join-Path $BUILD_DIR "FSCTDriverService.msix"
+
+    # Validate Azure Key Vault parameters
+    if ($UseAzureKeyVault -and $SIGN_ENABLED)
+    {
+        $missingParams = @()
+        if ([string]::IsNullOrEmpty($AzureKeyVaultUrl)) { $missingParams += "AzureKeyVaultUrl" }
+        if ([string]::IsNullOrEmpty($AzureCertificateName)) { $missingParams += "AzureCertificateName" }
+        if ([string]::IsNullOrEmpty($AzureTenantId)) { $missingParams += "AzureTenantId" }
+        if ([string]::IsNullOrEmpty($AzureClientId)) { $missingParams += "AzureClientId" }
+        if ([string]::IsNullOrEmpty($AzureClientSecret)) { $missingParams += "AzureClientSecret" }
+
+        if ($missingParams.Count -gt 0)
+        {
+            Write-Error "[ERROR] When using Azure Key Vault signing, the following parameters are required: $($missingParams -join ', ')"
+            exit 1
+        }
+
+        Write-Host "[INFO] Using Azure Key Vault for signing"
+    }
+    elseif ($SIGN_ENABLED)
+    {
+        Write-Host "[INFO] Using local certificate for signing"
+        Write-Host "[INFO] Certificate Thumbprint: $SIGN_CERT_THUMBPRINT"
+    }
+
+    # === Signing Functions ===
+    function Sign-File
+    {
+        param(
+            [string]$FilePath,
+            [string]$Description = ""
+        )
+
+        if (-not $SIGN_ENABLED)
+        {
+            Write-Host "[INFO] Skipping signing of $Description (signing disabled)"
+            return $true
+        }
+
+        Write-Host "[INFO] Signing $Description..."
+        if ($UseAzureKeyVault)
+        {
+            $signResult = & AzureSignTool sign -kvu $AzureKeyVaultUrl -kvc $AzureCertificateName -kvi $AzureClientId `
+                -kvs $AzureClientSecret -kvt $AzureTenantId -tr $TIMESTAMP_URL -td sha256 -fd sha256 $FilePath 2>&1
+        }
+        else
+        {
+            $signResult = signtool sign /sha1 $SIGN_CERT_THUMBPRINT /fd SHA256 /tr $TIMESTAMP_URL /td SHA256 $FilePath 2>&1
+        }
+        if ($LASTEXITCODE -ne 0)
+        {
+            Write-Error "[ERROR] Failed to sign $Description"
+            Write-Error "[ERROR] Error details: $signResult"
+            return $false
+        }
+        Write-Host "[INFO] $Description signed successfully"
+        return $true
+    }
+
+    # === Checking dependencies ===
+    function Check-Tool
+    {
+        param(
+            [string]$toolName
+        )
+
+        if (-not (Get-Command $toolName -ErrorAction SilentlyContinue))
+        {
+            Write-Error "[ERROR] Required tool '$toolName' is not installed or not in PATH."
+            exit 1
+        }
+    }
+
+    Check-Tool -toolName "cargo"
+    Check-Tool -toolName "makeappx"
+    if ($SIGN_ENABLED)
+    {
+        if ($UseAzureKeyVault) { Check-Tool -toolName "AzureSignTool" } else { Check-Tool -toolName "signtool" }
+    }
+
+    $assets = @("StoreLogo.png", "Square150x150Logo.png", "Square44x44Logo.png")
+    foreach ($asset in $assets)
+    {
+        if (-not (Test-Path (Join-Path $AssetsDir $asset)))
+        {
+            Write-Error "[ERROR] Missing package asset: $(Join-Path $AssetsDir $asset)"
+            exit 1
+        }
+    }
+
+    # === Prepare build directory ===
+    Write-Host "[INFO] Preparing build directory..."
+    if (Test-Path $BUILD_DIR)
+    {
+        Remove-Item -Path $BUILD_DIR -Recurse -Force
+    }
+    New-Item -Path "$LAYOUT_DIR\Assets" -ItemType Directory -Force | Out-Null
+
+    # === Building service ===
+    Write-Host "[INFO] Building Rust service..."
+    cargo build --release
+    if ($LASTEXITCODE -ne 0)
+    {
+        Write-Error "[ERROR] Failed to build Rust service"
+        exit 1
+    }
+    Copy-Item "$PROJECT_DIR\target\release\$PROJECT_NAME.exe" "$LAYOUT_DIR\$PROJECT_NAME.exe" -Force
+    if (-not (Sign-File -FilePath "$LAYOUT_DIR\$PROJECT_NAME.exe" -Description "EXE"))
+    {
+        exit 1
+    }
+
+    # === Package version ===
+    $cargoMetadata = cargo metadata --format-version 1 --no-deps | ConvertFrom-Json
+    $packageVersion = ($cargoMetadata.packages | Where-Object { $_.name -eq $PROJECT_NAME }).version
+    if ([string]::IsNullOrEmpty($packageVersion))
+    {
+        Write-Error "[ERROR] Failed to get package version"
+        exit 1
+    }
+    # Partner Center reserves the revision number, Store packages must leave it at 0
+    if ($Store) { $BuildNumber = 0 }
+    $msixVersion = "$packageVersion.$BuildNumber"
+    Write-Host "[INFO] Package version: $msixVersion"
+
+    # === Tray app ===
+    $hasTray = -not [string]::IsNullOrEmpty($TrayExe)
+    if ($hasTray)
+    {
+        if (-not (Test-Path $TrayExe))
+        {
+            Write-Error "[ERROR] Tray app not found: $TrayExe"
+            exit 1
+        }
+        Copy-Item $TrayExe "$LAYOUT_DIR\fsct_tray.exe" -Force
+        if (-not (Sign-File -FilePath "$LAYOUT_DIR\fsct_tray.exe" -Description "tray app EXE"))
+        {
+            exit 1
+        }
+        Write-Host "[INFO] Tray app will be packaged with a startup task"
+    }
+    else
+    {
+        Write-Host "[INFO] No tray app given, package will contain the service only"
+    }
+
+    # === Manifest ===
+    Write-Host "[INFO] Generating AppxManifest.xml..."
+    $serviceArgs = "--log-level $LogLevel $ServiceExtraArgs service run" -replace "\s+", " "
+    $manifest = Get-Content "$MSIX_SOURCE_DIR\AppxManifest.xml" -Raw
+    $manifest = $manifest.Replace('$(Version)', $msixVersion).
+        Replace('$(Publisher)', [System.Security.SecurityElement]::Escape($Publisher)).
+        Replace('$(ServiceArgs)', [System.Security.SecurityElement]::Escape($serviceArgs))
+    if (-not $hasTray)
+    {
+        $manifest = $manifest -replace "(?s)\s*<!-- tray:begin -->.*?<!-- tray:end -->", ""
+    }
+    Set-Content -Path "$LAYOUT_DIR\AppxManifest.xml" -Value $manifest -Encoding UTF8
+
+    foreach ($asset in $assets)
+    {
+        Copy-Item (Join-Path $AssetsDir $asset) "$LAYOUT_DIR\Assets\$asset" -Force
+    }
+    Copy-Item "$PROJECT_DIR\LICENSE-FSCT.md" "$LAYOUT_DIR\LICENSE-FSCT.md" -Force
+    Copy-Item "$PROJECT_DIR\NOTICE" "$LAYOUT_DIR\NOTICE" -Force
+
+    # === Generating Licenses ===
+    if ($LICENSE_ENABLE)
+    {
+        Write-Host "[INFO] Generating license files..."
+        & cargo about generate -c about.toml -m ports/native/Cargo.toml licenses.hbs -o "$LAYOUT_DIR/LICENSES.md"
+        if ($LASTEXITCODE -ne 0)
+        {
+            Write-Error "[ERROR] License generation failed"
+            exit 1
+        }
+    }
+    elseif (Test-Path "$projectLocation\target\wix_build\LICENSES.md")
+    {
+        Write-Host "[INFO] Reusing license files of the MSI build"
+        Copy-Item "$projectLocation\target\wix_build\LICENSES.md" "$LAYOUT_DIR\LICENSES.md" -Force
+    }
+
+    # === Packing ===
+    Write-Host "[INFO] Packing MSIX..."
+    $packResult = & makeappx pack /o /d $LAYOUT_DIR /p $MSIX_FILE 2>&1
+    if ($LASTEXITCODE -ne 0)
+    {
+        Write-Error "[ERROR] makeappx failed"
+        Write-Error "[ERROR] Error details: $packResult"
+        exit 1
+    }
+
+    # === Signing MSIX ===
+    if (-not (Sign-File -FilePath $MSIX_FILE -Description "MSIX"))
+    {
+        exit 1
+    }
+
+    # === Done ===
+    Write-Host "[SUCCESS] Package generated:"
+    Write-Host "  - $MSIX_FILE"
+}
+finally {
+    Set-Location $initialLocation
+}
@@ -10,7 +10,7 @@ APP_NAME="fsct_driver_service"                                # Application name
 IDENTIFIER="com.hem-e.fsctdriverservice"                     # Unique package identifier
 BUILD_DIR="${ROOT_DIR}/target"                          # Directory where build will be performed
 INSTALL_DIR="/usr/local/bin"                           # Target install directory for the binary
-DAEMON_DIR="/Library/LaunchDaemons"                    # Target install directory for the plist
+AGENT_DIR="/Library/LaunchAgents"                      # Target install directory for the LaunchAgent plist
 INSTALLER_FILES_DIR="${ROOT_DIR}/ports/native/packages/macos"  # Directory with prepared files (plist, postinstall, distribution.xml)
 
 # Code signing certificate (ensure the certificate is installed in your Keychain)
@@ -41,7 +41,7 @@ COMPONENT_PKGS_DIR="${PACKAGE_DIR}/components"
 
 # Component package paths
 BIN_PKG="${COMPONENT_PKGS_DIR}/bin.pkg"
-DAEMON_PKG="${COMPONENT_PKGS_DIR}/daemon.pkg"
+AGENT_PKG="${COMPONENT_PKGS_DIR}/agent.pkg"
 
 # Exit the script if any command fails
 set -e
@@ -127,10 +127,10 @@ mkdir -p "${COMPONENT_PKGS_DIR}"
 
 # Create temporary directories for each component
 BIN_ROOT="${PACKAGE_DIR}/bin_root"
-DAEMON_ROOT="${PACKAGE_DIR}/daemon_root"
+AGENT_ROOT="${PACKAGE_DIR}/agent_root"
 SCRIPTS_DIR="${PACKAGE_DIR}/scripts"
 
-mkdir -p "${BIN_ROOT}${INSTALL_DIR}" "${DAEMON_ROOT}${DAEMON_DIR}" "${SCRIPTS_DIR}"
+mkdir -p "${BIN_ROOT}${INSTALL_DIR}" "${AGENT_ROOT}${AGENT_DIR}" "${SCRIPTS_DIR}"
 
 mkdir -p "${BIN_ROOT}/usr/local/share/fsct-driver"
 cp "${ROOT_DIR}/LICENSE-FSCT.md" "${BIN_ROOT}/usr/local/share/fsct-driver/"
@@ -182,17 +182,17 @@ fi
 
 # Copy the prepared files
 echo "Copying prepared files..."
-cp "${INSTALLER_FILES_DIR}/$IDENTIFIER.xml" "${DAEMON_ROOT}${DAEMON_DIR}/$IDENTIFIER.plist"
+cp "${INSTALLER_FILES_DIR}/$IDENTIFIER.xml" "${AGENT_ROOT}${AGENT_DIR}/$IDENTIFIER.plist"
 cp "${INSTALLER_FILES_DIR}/postinstall.sh" "${SCRIPTS_DIR}/postinstall"
 chmod +x "${SCRIPTS_DIR}/postinstall"
 
 echo "========================================"
 echo "Building component packages..."
 
-# Prepare scripts for the daemon component
-mkdir -p "${PACKAGE_DIR}/daemon_scripts"
-cp "${INSTALLER_FILES_DIR}/postinstall.sh" "${PACKAGE_DIR}/daemon_scripts/postinstall"
-chmod +x "${PACKAGE_DIR}/daemon_scripts/postinstall"
+# Prepare scripts for the agent component
+mkdir -p "${PACKAGE_DIR}/agent_scripts"
+cp "${INSTALLER_FILES_DIR}/postinstall.sh" "${PACKAGE_DIR}/agent_scripts/postinstall"
+chmod +x "${PACKAGE_DIR}/agent_scripts/postinstall"
 
 # Prepare scripts for the bin component
 mkdir -p "${PACKAGE_DIR}/bin_scripts"
@@ -289,22 +289,22 @@ if [ "$SKIP_SIGNING" = false ]; then
         echo "Successfully built and signed bin package"
     fi
     
-    echo "Building and signing daemon package..."
-    pkgbuild --root "${DAEMON_ROOT}" \
-             --identifier "${IDENTIFIER}.daemon" \
+    echo "Building and signing agent package..."
+    pkgbuild --root "${AGENT_ROOT}" \
+             --identifier "${IDENTIFIER}.agent" \
              --version "${VERSION}" \
              --install-location "/" \
-             --scripts "${PACKAGE_DIR}/daemon_scripts" \
+             --scripts "${PACKAGE_DIR}/agent_scripts" \
              --sign "${APPLE_DEVELOPER_ID_INSTALLER}" \
              --verbose \
-             "${DAEMON_PKG}" 2>&1
-    DAEMON_PKG_STATUS=$?
+             "${AGENT_PKG}" 2>&1
+    AGENT_PKG_STATUS=$?
     
-    if [ $DAEMON_PKG_STATUS -ne 0 ]; then
-        echo "Error: Failed to build and sign daemon package (exit code: $DAEMON_PKG_STATUS)"
-        exit $DAEMON_PKG_STATUS
+    if [ $AGENT_PKG_STATUS -ne 0 ]; then
+        echo "Error: Failed to build and sign agent package (exit code: $AGENT_PKG_STATUS)"
+        exit $AGENT_PKG_STATUS
     else
-        echo "Successfully built and signed daemon package"
+        echo "Successfully built and signed agent package"
     fi
     
     set -e  # Restore exit on error
@@ -317,12 +317,12 @@ else
              --scripts "${PACKAGE_DIR}/bin_scripts" \
              "${BIN_PKG}"
 
-    pkgbuild --root "${DAEMON_ROOT}" \
-             --identifier "${IDENTIFIER}.daemon" \
+    pkgbuild --root "${AGENT_ROOT}" \
+             --identifier "${IDENTIFIER}.agent" \
              --version "${VERSION}" \
              --install-location "/" \
-             --scripts "${PACKAGE_DIR}/daemon_scripts" \
-             "${DAEMON_PKG}"
+             --scripts "${PACKAGE_DIR}/agent_scripts" \
+             "${AGENT_PKG}"
 fi
 
 echo "========================================"
@@ -0,0 +1,113 @@
+// Copyright 2025 HEM Sp. z o.o.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// This file is part of an implementation of Ferrum Streaming Control Technology™,
+// which is subject to additional terms found in the LICENSE-FSCT.md file.
+
+//! Repository automation, run with `cargo xtask <command>`.
+//!
+//! `package` builds the installer of the current host platform by running the platform packaging script:
+//! the notarization-ready .pkg on macOS (`script/macos_service_package_builder.sh`) and the MSI/EXE bundle on
+//! Windows (`script/build_windows_installer.ps1`). Remaining arguments are passed to the script unchanged.
+//!
+//! `smoke-test [target]` runs the test suites of the platform independent crates and the Linux port for another
+//! target through `cross`, which executes them under QEMU; the default target is the static
+//! `aarch64-unknown-linux-musl` build of embedded streamers.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+
+const USAGE: &str = "Usage: cargo xtask package [script options]
+       cargo xtask smoke-test [target]
+
+macOS options:   --skip-signing --skip-notarization --skip-license
+Windows options: -NoSign -NoDwnld -NoLicense -BuildNumber <n> -TrayExe <path> (see the script -Help)";
+
+fn main() -> ExitCode {
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("package") => package(args.collect()),
+        Some("smoke-test") => smoke_test(args.next().as_deref().unwrap_or(SMOKE_TEST_TARGET)),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Default target of `smoke-test`.
+const SMOKE_TEST_TARGET: &str = "aarch64-unknown-linux-musl";
+
+/// Crates whose tests run in `smoke-test`; the native service and the node bindings need the target's OS APIs.
+const SMOKE_TEST_PACKAGES: &[&str] = &["fsct_core", "fsct-port-sdk", "fsct-port-linux"];
+
+fn project_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().expect("xtask is inside the workspace").to_path_buf()
+}
+
+fn package(script_args: Vec<String>) -> ExitCode {
+    let root = project_root();
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("bash");
+        command.arg(root.join("script").join("macos_service_package_builder.sh"));
+        command
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("powershell");
+        command.args(["-ExecutionPolicy", "Bypass", "-File"]);
+        command.arg(root.join("script").join("build_windows_installer.ps1"));
+        command
+    } else {
+        eprintln!("Packaging is supported on macOS and Windows only");
+        return ExitCode::FAILURE;
+    };
+    command.args(script_args).current_dir(&root);
+
+    match command.status() {
+        Ok(status) if status.success() => ExitCode::SUCCESS,
+        Ok(status) => {
+            eprintln!("Packaging failed: {}", status);
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("Failed to run packaging script: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn smoke_test(target: &str) -> ExitCode {
+    let mut command = Command::new("cross");
+    command.args(["test", "--target", target]);
+    for package in SMOKE_TEST_PACKAGES {
+        command.args(["-p", package]);
+    }
+    command.current_dir(project_root());
+
+    match command.status() {
+        Ok(status) if status.success() => ExitCode::SUCCESS,
+        Ok(status) => {
+            eprintln!("Smoke tests for {} failed: {}", target, status);
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("Failed to run cross (install it with `cargo install cross`): {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}